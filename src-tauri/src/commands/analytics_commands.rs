@@ -2,7 +2,9 @@
 
 use anyhow::Result;
 use duckcoding::services::token_stats::{
-    CostGroupBy, CostSummaryQuery, TimeGranularity, TokenStatsAnalytics, TrendDataPoint, TrendQuery,
+    custom_query, CacheRoi, CostGroupBy, CostSummary as GroupedCostSummary, CostSummaryQuery,
+    IpUsage, QueryResult, SavingsSummary, StopReasonStat, SuccessRatePoint, TimeGranularity,
+    TokenStatsAnalytics, TrendDataPoint, TrendQuery, UpstreamStat, WeeklyReport,
 };
 use duckcoding::utils::config_dir;
 use serde::{Deserialize, Serialize};
@@ -80,6 +82,138 @@ pub async fn query_token_trends(query: TrendQuery) -> Result<Vec<TrendDataPoint>
         .map_err(|e| format!("Failed to query trends: {}", e))
 }
 
+/// 查询请求结束原因分布
+///
+/// # 参数
+/// - `query`: 分布查询参数
+///
+/// # 返回
+/// - `Ok(Vec<StopReasonStat>)`: 按请求数降序的结束原因分布
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn query_stop_reason_distribution(
+    query: TrendQuery,
+) -> Result<Vec<StopReasonStat>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let analytics = TokenStatsAnalytics::new(db_path);
+
+    analytics
+        .query_stop_reason_distribution(&query)
+        .map_err(|e| format!("Failed to query stop reason distribution: {}", e))
+}
+
+/// 查询请求成功率趋势
+///
+/// # 参数
+/// - `query`: 过滤条件（时间范围、工具、模型、配置、会话）
+/// - `granularity`: 时间粒度
+///
+/// # 返回
+/// - `Ok(Vec<SuccessRatePoint>)`: 按时间排序的成功率数据点列表
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_success_rate_trend(
+    query: TrendQuery,
+    granularity: TimeGranularity,
+) -> Result<Vec<SuccessRatePoint>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let analytics = TokenStatsAnalytics::new(db_path);
+
+    analytics
+        .query_success_rate_trend(&query, granularity)
+        .map_err(|e| format!("Failed to query success rate trend: {}", e))
+}
+
+/// 查询 Prompt Caching 成本收益
+///
+/// # 参数
+/// - `query`: 过滤条件（时间范围、工具、模型、配置、会话；granularity 被忽略）
+///
+/// # 返回
+/// - `Ok(CacheRoi)`: 缓存写入成本、读取节省与净收益
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_cache_roi(query: TrendQuery) -> Result<CacheRoi, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let analytics = TokenStatsAnalytics::new(db_path);
+
+    analytics
+        .query_cache_roi(&query)
+        .map_err(|e| format!("Failed to query cache roi: {}", e))
+}
+
+/// 查询按上游聚合的用量、成功率与延迟
+///
+/// # 参数
+/// - `query`: 过滤条件（时间范围、工具、模型、配置、会话；granularity 被忽略）
+///
+/// # 返回
+/// - `Ok(Vec<UpstreamStat>)`: 按请求数降序的上游统计
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_upstream_stats(query: TrendQuery) -> Result<Vec<UpstreamStat>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let analytics = TokenStatsAnalytics::new(db_path);
+
+    analytics
+        .query_upstream_stats(&query)
+        .map_err(|e| format!("Failed to query upstream stats: {}", e))
+}
+
+/// 查询按客户端 IP 聚合的用量（共享代理场景）
+///
+/// # 参数
+/// - `query`: 过滤条件（时间范围、工具、模型、配置、会话；granularity 被忽略）
+///
+/// # 返回
+/// - `Ok(Vec<IpUsage>)`: 按成本降序的各 IP 请求数与成本
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_usage_by_ip(query: TrendQuery) -> Result<Vec<IpUsage>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let analytics = TokenStatsAnalytics::new(db_path);
+
+    analytics
+        .query_usage_by_ip(&query)
+        .map_err(|e| format!("Failed to query usage by ip: {}", e))
+}
+
+/// 查询 Prompt Caching 累计节省（对比无缓存时的花费）
+///
+/// # 参数
+/// - `query`: 过滤条件（时间范围、工具、模型、配置、会话；granularity 被忽略）
+///
+/// # 返回
+/// - `Ok(SavingsSummary)`: 实际花费、无缓存花费、累计节省及按模型明细
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_savings_summary(query: TrendQuery) -> Result<SavingsSummary, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let analytics = TokenStatsAnalytics::new(db_path);
+
+    analytics
+        .query_savings_summary(&query)
+        .map_err(|e| format!("Failed to query savings summary: {}", e))
+}
+
 /// 查询成本汇总数据
 ///
 /// # 参数
@@ -239,6 +373,81 @@ pub async fn query_cost_summary(
     })
 }
 
+/// 按工具/模型/配置/会话分组查询成本汇总
+///
+/// # 参数
+/// - `query`: 汇总查询参数（含分组方式）
+///
+/// # 返回
+/// - `Ok(Vec<CostSummary>)`: 按总成本降序的分组汇总
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_cost_summary(query: CostSummaryQuery) -> Result<Vec<GroupedCostSummary>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    TokenStatsAnalytics::new(db_path)
+        .query_cost_summary(&query)
+        .map_err(|e| format!("Failed to query cost summary: {}", e))
+}
+
+/// 按时间桶（小时/天/周等）查询成本趋势
+///
+/// # 参数
+/// - `query`: 趋势查询参数
+///
+/// # 返回
+/// - `Ok(Vec<TrendDataPoint>)`: 按时间排序的趋势数据点（指定时间范围时补齐空桶）
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_cost_trend(query: TrendQuery) -> Result<Vec<TrendDataPoint>, String> {
+    query_token_trends(query).await
+}
+
+/// 生成指定周的 Token 统计周报
+///
+/// # 参数
+/// - `week_offset`: 往前数的周数（0 为本周，1 为上周，按本地时区周一起算）
+///
+/// # 返回
+/// - `Ok(WeeklyReport)`: 每日成本、top 模型、总请求数与环比上周变化
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn get_weekly_report(week_offset: Option<u32>) -> Result<WeeklyReport, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let week_start = duckcoding::services::token_stats::weekly_report::week_start_date(
+        chrono::Local::now().date_naive(),
+        week_offset.unwrap_or(0),
+    );
+    WeeklyReport::generate(&TokenStatsAnalytics::new(db_path), week_start)
+        .map_err(|e| format!("Failed to generate weekly report: {}", e))
+}
+
+/// 对 Token 统计库执行自定义只读查询
+///
+/// # 参数
+/// - `sql`: 单条 SELECT / WITH 查询语句（以只读连接执行，拒绝写操作）
+///
+/// # 返回
+/// - `Ok(QueryResult)`: 列名与行数据（最多返回 1000 行）
+/// - `Err`: 语句被拒绝、查询失败或超时（10 秒）
+#[tauri::command]
+pub async fn run_stats_query(sql: String) -> Result<QueryResult, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    // 查询可能长时间占用 CPU，放到阻塞线程中执行（超时由 progress handler 中断）
+    tokio::task::spawn_blocking(move || custom_query::run_readonly_query(&db_path, &sql))
+        .await
+        .map_err(|e| format!("查询任务异常退出: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,33 +471,22 @@ mod tests {
             .timestamp_millis();
 
         for i in 0..10 {
-            let log = TokenLog::new(
-                "claude-code".to_string(),
-                base_time - (i * 3600 * 1000), // 每小时一条
-                "127.0.0.1".to_string(),
-                "test_session".to_string(),
-                "default".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                Some(format!("msg_{}", i)),
-                100,
-                50,
-                10,
-                0, // cache_creation_1h_tokens
-                20,
-                0, // reasoning_tokens
-                "success".to_string(),
-                "json".to_string(),
-                None,
-                None,
-                Some(100),
-                Some(0.001),
-                Some(0.002),
-                Some(0.0001),
-                Some(0.0002),
-                None, // reasoning_price
-                0.0033,
-                Some("test_template".to_string()),
-            );
+            let log = TokenLog {
+                session_id: "test_session".to_string(),
+                model: "claude-sonnet-4-5-20250929".to_string(),
+                message_id: Some(format!("msg_{}", i)),
+                cache_creation_tokens: 10,
+                cache_read_tokens: 20,
+                response_time_ms: Some(100),
+                input_price: Some(0.001),
+                output_price: Some(0.002),
+                cache_write_price: Some(0.0001),
+                cache_read_price: Some(0.0002),
+                total_cost: 0.0033,
+                pricing_template_id: Some("test_template".to_string()),
+                // 每小时一条
+                ..TokenLog::fixture("claude-code", base_time - (i * 3600 * 1000))
+            };
             db.insert_log(&log).unwrap();
         }
 
@@ -329,33 +527,22 @@ mod tests {
         for (i, model) in models.iter().enumerate() {
             for (j, config) in configs.iter().enumerate() {
                 for k in 0..3 {
-                    let log = TokenLog::new(
-                        "claude-code".to_string(),
-                        base_time - (k * 1000),
-                        "127.0.0.1".to_string(),
-                        format!("session_{}_{}", i, j),
-                        config.to_string(),
-                        model.to_string(),
-                        Some(format!("msg_{}_{}_{}", i, j, k)),
-                        100,
-                        50,
-                        10,
-                        0, // cache_creation_1h_tokens
-                        20,
-                        0, // reasoning_tokens
-                        "success".to_string(),
-                        "json".to_string(),
-                        None,
-                        None,
-                        Some(100),
-                        Some(0.001),
-                        Some(0.002),
-                        Some(0.0001),
-                        Some(0.0002),
-                        None, // reasoning_price
-                        0.0033,
-                        Some("test_template".to_string()),
-                    );
+                    let log = TokenLog {
+                        session_id: format!("session_{}_{}", i, j),
+                        config_name: config.to_string(),
+                        model: model.to_string(),
+                        message_id: Some(format!("msg_{}_{}_{}", i, j, k)),
+                        cache_creation_tokens: 10,
+                        cache_read_tokens: 20,
+                        response_time_ms: Some(100),
+                        input_price: Some(0.001),
+                        output_price: Some(0.002),
+                        cache_write_price: Some(0.0001),
+                        cache_read_price: Some(0.0002),
+                        total_cost: 0.0033,
+                        pricing_template_id: Some("test_template".to_string()),
+                        ..TokenLog::fixture("claude-code", base_time - (k * 1000))
+                    };
                     db.insert_log(&log).unwrap();
                 }
             }
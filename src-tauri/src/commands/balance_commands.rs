@@ -5,9 +5,32 @@
 
 use ::duckcoding::http_client::build_client;
 use ::duckcoding::models::{BalanceConfig, BalanceStore};
-use ::duckcoding::services::balance::BalanceManager;
+use ::duckcoding::services::balance::{
+    emit_balance_events, BalanceExecutor, BalanceManager, BalanceSnapshot,
+};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
 use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+/// 余额监控执行器 State
+pub struct BalanceExecutorState {
+    pub executor: Arc<BalanceExecutor>,
+}
+
+impl BalanceExecutorState {
+    pub fn new() -> Self {
+        Self {
+            executor: Arc::new(BalanceExecutor::new()),
+        }
+    }
+}
+
+impl Default for BalanceExecutorState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// Tauri command: 通用 API 请求
 ///
@@ -123,3 +146,31 @@ pub async fn migrate_balance_from_localstorage(
     tracing::info!("从 localStorage 迁移了 {} 个余额监控配置", count);
     Ok(count)
 }
+
+// ========== 后台执行命令 ==========
+
+/// 获取所有配置最近一次的后台查询结果
+#[tauri::command]
+pub async fn get_balance_snapshots(
+    state: State<'_, BalanceExecutorState>,
+) -> Result<Vec<BalanceSnapshot>, String> {
+    Ok(state.executor.snapshots().await)
+}
+
+/// 立即查询指定配置的余额（由后端执行请求与提取器脚本）
+#[tauri::command]
+pub async fn refresh_balance(
+    state: State<'_, BalanceExecutorState>,
+    app_handle: AppHandle,
+    id: String,
+) -> Result<BalanceSnapshot, String> {
+    let manager = BalanceManager::new().map_err(|e| e.to_string())?;
+    let config = manager
+        .get_config(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("余额监控配置不存在: {id}"))?;
+
+    let snapshot = state.executor.refresh(&config).await;
+    emit_balance_events(&app_handle, &config, &snapshot);
+    Ok(snapshot)
+}
@@ -73,6 +73,18 @@ pub async fn get_global_config() -> Result<Option<GlobalConfig>, String> {
     read_global_config()
 }
 
+/// 修复 ~/.duckcoding 下含敏感信息文件的权限（Unix 0600 / Windows 仅当前用户）
+///
+/// 返回实际修改了权限的文件路径
+#[tauri::command]
+pub async fn fix_config_permissions() -> Result<Vec<String>, String> {
+    let fixed = ::duckcoding::utils::fix_sensitive_file_permissions().map_err(|e| e.to_string())?;
+    Ok(fixed
+        .into_iter()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect())
+}
+
 #[tauri::command]
 pub async fn generate_api_key_for_tool(tool: String) -> Result<GenerateApiKeyResult, String> {
     // 应用代理配置（如果已配置）
@@ -226,8 +238,12 @@ pub fn get_codex_settings() -> Result<CodexSettingsPayload, String> {
 }
 
 #[tauri::command]
-pub fn save_codex_settings(settings: Value, auth_token: Option<String>) -> Result<(), String> {
-    codex::save_codex_settings(&settings, auth_token).map_err(|e| e.to_string())
+pub fn save_codex_settings(
+    settings: Value,
+    auth_token: Option<String>,
+    auth_mode: Option<String>,
+) -> Result<(), String> {
+    codex::save_codex_settings(&settings, auth_token, auth_mode).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -0,0 +1,39 @@
+// Health Check Commands
+//
+// 工具健康巡检 Tauri 命令
+
+use ::duckcoding::services::health_check::{HealthInspector, HealthReport};
+use std::sync::Arc;
+use tauri::State;
+
+/// 健康巡检器 State
+pub struct HealthInspectorState {
+    pub inspector: Arc<HealthInspector>,
+}
+
+impl HealthInspectorState {
+    pub fn new(inspector: HealthInspector) -> Self {
+        Self {
+            inspector: Arc::new(inspector),
+        }
+    }
+}
+
+/// 获取最近一次健康报告（尚未巡检时立即执行一次）
+#[tauri::command]
+pub async fn get_health_report(
+    state: State<'_, HealthInspectorState>,
+) -> Result<HealthReport, String> {
+    if let Some(report) = state.inspector.latest_report().await {
+        return Ok(report);
+    }
+    Ok(state.inspector.run_once().await)
+}
+
+/// 立即执行一次健康巡检
+#[tauri::command]
+pub async fn run_health_check(
+    state: State<'_, HealthInspectorState>,
+) -> Result<HealthReport, String> {
+    Ok(state.inspector.run_once().await)
+}
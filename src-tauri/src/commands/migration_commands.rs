@@ -0,0 +1,22 @@
+// Migration Commands
+//
+// 配置迁移向导 Tauri 命令
+
+use ::duckcoding::services::migration_manager::{MigrationReport, MigrationTask};
+
+/// 检测仍有旧配置待迁移的任务（旧备份文件格式、旧目录结构等）
+#[tauri::command]
+pub async fn check_migration_needed() -> Result<Vec<MigrationTask>, String> {
+    Ok(::duckcoding::create_migration_manager()
+        .check_needed()
+        .await)
+}
+
+/// 执行指定迁移任务，返回迁移报告
+#[tauri::command]
+pub async fn run_migration(task_id: String) -> Result<MigrationReport, String> {
+    ::duckcoding::create_migration_manager()
+        .run_task(&task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
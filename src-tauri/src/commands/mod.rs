@@ -5,7 +5,9 @@ pub mod checkin_scheduler_state; // 签到调度器状态
 pub mod config_commands;
 pub mod dashboard_commands; // 仪表板状态管理命令
 pub mod error; // 错误处理统一模块
+pub mod health_commands; // 工具健康巡检命令
 pub mod log_commands;
+pub mod migration_commands; // 配置迁移向导命令
 pub mod onboarding;
 pub mod pricing_commands; // 价格配置管理命令（Phase 6）
 pub mod profile_commands; // Profile 管理命令（v2.0）
@@ -29,7 +31,9 @@ pub use balance_commands::*;
 pub use checkin_scheduler_state::CheckinSchedulerState;
 pub use config_commands::*;
 pub use dashboard_commands::*; // 仪表板状态管理命令
+pub use health_commands::*; // 工具健康巡检命令
 pub use log_commands::*;
+pub use migration_commands::*; // 配置迁移向导命令
 pub use onboarding::*;
 pub use pricing_commands::*; // 价格配置管理命令（Phase 6）
 pub use profile_commands::*; // Profile 管理命令（v2.0）
@@ -30,6 +30,12 @@ fn create_minimal_config() -> GlobalConfig {
         startup_enabled: false,
         config_watch: duckcoding::models::config::ConfigWatchConfig::default(),
         token_stats_config: duckcoding::models::config::TokenStatsConfig::default(),
+        health_check: duckcoding::models::config::HealthCheckConfig::default(),
+        download_cache: duckcoding::models::config::DownloadCacheConfig::default(),
+        usage_anomaly: duckcoding::models::config::UsageAnomalyConfig::default(),
+        budget: duckcoding::models::config::BudgetConfig::default(),
+        session_ttl_hours: 24,
+        close_behavior: duckcoding::models::config::CloseBehavior::default(),
     }
 }
 
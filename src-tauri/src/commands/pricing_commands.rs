@@ -1,8 +1,8 @@
 /// 价格配置管理命令
 ///
 /// 提供价格模板的 CRUD 操作和工具默认模板管理
-use duckcoding::models::pricing::PricingTemplate;
-use duckcoding::services::pricing::PRICING_MANAGER;
+use duckcoding::models::pricing::{FallbackPricesConfig, PriceChange, PricingTemplate};
+use duckcoding::services::pricing::{PricingSyncStatus, PRICING_MANAGER};
 
 use super::error::AppResult;
 
@@ -100,3 +100,93 @@ pub async fn get_default_template(tool_id: String) -> AppResult<PricingTemplate>
     let template = PRICING_MANAGER.get_default_template(&tool_id)?;
     Ok(template)
 }
+
+/// 导出价格模板为 JSON 字符串
+///
+/// # 参数
+///
+/// - `template_id`: 模板 ID
+///
+/// # 返回
+///
+/// 带 schema 版本号的模板 JSON（可在其他机器通过 `import_pricing_template` 导入）
+#[tauri::command]
+pub async fn export_pricing_template(template_id: String) -> AppResult<String> {
+    let data = PRICING_MANAGER.export_template(&template_id)?;
+    Ok(data)
+}
+
+/// 从 JSON 字符串导入价格模板
+///
+/// # 参数
+///
+/// - `data`: 模板 JSON（`export_pricing_template` 导出的带版本号格式）
+/// - `overwrite`: ID 冲突时是否覆盖已有用户模板（默认 false，冲突时报错）
+///
+/// # 返回
+///
+/// 实际保存的模板
+///
+/// # 注意
+///
+/// - 不允许覆盖内置预设模板，冲突时另存为 `<id>_imported`
+/// - 保留原模板的 `created_at`
+#[tauri::command]
+pub async fn import_pricing_template(
+    data: String,
+    overwrite: Option<bool>,
+) -> AppResult<PricingTemplate> {
+    let template = PRICING_MANAGER.import_template(&data, overwrite.unwrap_or(false))?;
+    Ok(template)
+}
+
+/// 获取未知模型的兜底价格配置
+///
+/// # 返回
+///
+/// 各 provider（claude / openai / gemini）的默认档位价格，未配置时为内置默认值
+#[tauri::command]
+pub async fn get_pricing_fallback_prices() -> AppResult<FallbackPricesConfig> {
+    let config = PRICING_MANAGER.get_fallback_prices()?;
+    Ok(config)
+}
+
+/// 保存未知模型的兜底价格配置
+///
+/// # 参数
+///
+/// - `config`: 兜底价格配置（`enabled = false` 时未知模型不计价）
+#[tauri::command]
+pub async fn save_pricing_fallback_prices(config: FallbackPricesConfig) -> AppResult<()> {
+    PRICING_MANAGER.save_fallback_prices(&config)?;
+    Ok(())
+}
+
+/// 获取远程价格同步状态
+///
+/// # 返回
+///
+/// 上次成功同步时间、最近错误、连续失败次数及距上次成功的时长
+#[tauri::command]
+pub async fn get_pricing_sync_status() -> AppResult<PricingSyncStatus> {
+    let state = PRICING_MANAGER.load_sync_state()?;
+    Ok(PricingSyncStatus::from_state(
+        &state,
+        chrono::Utc::now().timestamp_millis(),
+    ))
+}
+
+/// 获取指定模型的价格变更历史
+///
+/// # 参数
+///
+/// - `model`: 模型 ID
+///
+/// # 返回
+///
+/// 远程同步产生的价格变更记录（按时间倒序）
+#[tauri::command]
+pub async fn get_price_history(model: String) -> AppResult<Vec<PriceChange>> {
+    let history = PRICING_MANAGER.get_price_history(&model)?;
+    Ok(history)
+}
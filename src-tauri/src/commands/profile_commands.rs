@@ -1,7 +1,10 @@
 //! Profile 管理 Tauri 命令（v2.1 - 简化版）
 
 use super::error::AppResult;
-use ::duckcoding::services::profile_manager::{AmpProfileSelection, ProfileDescriptor, ProfileRef};
+use ::duckcoding::services::audit_log::{self, AuditEntry};
+use ::duckcoding::services::profile_manager::{
+    AmpProfileSelection, GeminiAuth, Inconsistency, ProfileDescriptor, ProfileRef, ProfileSwitch,
+};
 use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -38,6 +41,8 @@ pub enum ProfileInput {
         model: Option<String>,
         #[serde(default)]
         pricing_template_id: Option<String>, // 🆕 Phase 6: 价格模板 ID
+        #[serde(default)]
+        auth: Option<GeminiAuth>, // 认证模式（api-key / vertex / oauth，不传则保持不变）
     },
 }
 
@@ -60,6 +65,16 @@ pub async fn pm_list_tool_profiles(
     Ok(manager.list_profiles(&tool_id)?)
 }
 
+/// 列出指定工具的 Profile 描述符（包含最后使用时间）
+#[tauri::command]
+pub async fn pm_list_tool_profile_details(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool_id: String,
+) -> AppResult<Vec<ProfileDescriptor>> {
+    let manager = state.manager.read().await;
+    Ok(manager.list_tool_descriptors(&tool_id)?)
+}
+
 /// 获取指定 Profile（返回 JSON 供前端使用）
 #[tauri::command]
 pub async fn pm_get_profile(
@@ -164,6 +179,7 @@ pub async fn pm_save_profile(
                 base_url,
                 model,
                 pricing_template_id,
+                auth,
             } = input
             {
                 Ok(manager.save_gemini_profile_with_template(
@@ -172,6 +188,7 @@ pub async fn pm_save_profile(
                     base_url,
                     model,
                     pricing_template_id,
+                    auth,
                 )?)
             } else {
                 Err(super::error::AppError::ValidationError {
@@ -195,17 +212,38 @@ pub async fn pm_delete_profile(
     Ok(manager.delete_profile(&tool_id, &name)?)
 }
 
-/// 激活 Profile
+/// 激活 Profile（返回切换前后的 Profile 名称）
 #[tauri::command]
 pub async fn pm_activate_profile(
     state: tauri::State<'_, ProfileManagerState>,
     tool_id: String,
     name: String,
-) -> AppResult<()> {
+) -> AppResult<ProfileSwitch> {
     let manager = state.manager.write().await;
     Ok(manager.activate_profile(&tool_id, &name)?)
 }
 
+/// 撤销最近一次 Profile 切换，恢复切换前的配置（返回撤销产生的切换）
+#[tauri::command]
+pub async fn undo_last_switch(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool: String,
+) -> AppResult<ProfileSwitch> {
+    let manager = state.manager.write().await;
+    Ok(manager.undo_last_switch(&tool)?)
+}
+
+/// 按 DuckCoding 推荐配置一键配置工具（用户只需提供 API Key）
+#[tauri::command]
+pub async fn apply_recommended_config(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool: String,
+    api_key: String,
+) -> AppResult<ProfileSwitch> {
+    let manager = state.manager.write().await;
+    Ok(manager.apply_recommended_config(&tool, &api_key)?)
+}
+
 /// 获取当前激活的 Profile 名称
 #[tauri::command]
 pub async fn pm_get_active_profile_name(
@@ -227,6 +265,57 @@ pub async fn pm_capture_from_native(
     Ok(manager.capture_from_native(&tool_id, &name)?)
 }
 
+/// 导出 Profile 为可分享的 JSON 文本（mask 为真时隐藏 API Key）
+#[tauri::command]
+pub async fn export_profile(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool: String,
+    profile: String,
+    mask: bool,
+) -> AppResult<String> {
+    let manager = state.manager.read().await;
+    Ok(manager.export_profile(&tool, &profile, mask)?)
+}
+
+/// 导出去除所有密钥字段的 Profile 配置（分享给他人，导入后自行填写 API Key）
+#[tauri::command]
+pub async fn export_config_sanitized(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool: String,
+    profile: String,
+) -> AppResult<String> {
+    let manager = state.manager.read().await;
+    Ok(manager.export_config_sanitized(&tool, &profile)?)
+}
+
+/// 检查各工具当前配置是否指向同一供应商，返回不一致的工具
+#[tauri::command]
+pub async fn check_config_consistency(
+    state: tauri::State<'_, ProfileManagerState>,
+) -> AppResult<Vec<Inconsistency>> {
+    let manager = state.manager.read().await;
+    Ok(manager.check_config_consistency()?)
+}
+
+/// 从导出的 JSON 文本导入 Profile，返回最终的 Profile 名称
+#[tauri::command]
+pub async fn import_profile(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool: String,
+    data: String,
+    new_name: Option<String>,
+) -> AppResult<String> {
+    let manager = state.manager.write().await;
+    Ok(manager.import_profile(&tool, &data, new_name)?)
+}
+
+/// 获取最近的配置变更审计记录（按时间倒序，默认 100 条）
+#[tauri::command]
+pub async fn get_audit_log(limit: Option<usize>) -> AppResult<Vec<AuditEntry>> {
+    let path = audit_log::audit_log_path()?;
+    Ok(audit_log::read_recent(&path, limit.unwrap_or(100))?)
+}
+
 // ==================== AMP Profile Selection ====================
 
 /// AMP Profile 选择输入（前端传递）
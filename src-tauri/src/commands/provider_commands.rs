@@ -164,6 +164,71 @@ pub async fn delete_provider(
         .map_err(|e| format!("删除供应商失败: {}", e))
 }
 
+/// 获取自动签到全局总开关状态
+#[tauri::command]
+pub async fn get_checkin_enabled(state: State<'_, ProviderManagerState>) -> Result<bool, String> {
+    // 签到调度器会写入 providers.json，读取前丢弃缓存
+    state.manager.clear_cache();
+    state
+        .manager
+        .is_checkin_enabled()
+        .map_err(|e| format!("读取签到总开关失败: {}", e))
+}
+
+/// 设置自动签到全局总开关（关闭后调度器跳过所有供应商）
+#[tauri::command]
+pub async fn set_checkin_enabled(
+    enabled: bool,
+    state: State<'_, ProviderManagerState>,
+) -> Result<(), String> {
+    state.manager.clear_cache();
+    state
+        .manager
+        .set_checkin_enabled(enabled)
+        .map_err(|e| format!("设置签到总开关失败: {}", e))
+}
+
+/// 批量切换所有供应商的签到开关，返回受影响的供应商数量
+#[tauri::command]
+pub async fn set_checkin_enabled_all(
+    enabled: bool,
+    state: State<'_, ProviderManagerState>,
+) -> Result<usize, String> {
+    state.manager.clear_cache();
+    state
+        .manager
+        .set_checkin_enabled_all(enabled)
+        .map_err(|e| format!("批量切换签到开关失败: {}", e))
+}
+
+/// 立即为指定供应商执行一次签到（与调度器互斥），结果写回签到统计
+#[tauri::command]
+pub async fn checkin_now(
+    provider_id: String,
+    state: State<'_, ProviderManagerState>,
+) -> Result<::duckcoding::services::checkin::CheckinResponse, String> {
+    ::duckcoding::services::checkin::checkin_now(&state.manager, &provider_id)
+        .await
+        .map_err(|e| format!("签到失败: {}", e))
+}
+
+/// 分页查询签到流水（按时间倒序，可按供应商过滤）
+#[tauri::command]
+pub async fn get_checkin_history(
+    provider_id: Option<String>,
+    limit: u32,
+    offset: Option<u32>,
+    state: State<'_, ProviderManagerState>,
+) -> Result<Vec<::duckcoding::services::checkin::history::CheckinRecord>, String> {
+    ::duckcoding::services::checkin::history::CheckinHistory::beside(state.manager.store_path())
+        .query(
+            provider_id.as_deref(),
+            offset.unwrap_or(0) as usize,
+            limit as usize,
+        )
+        .map_err(|e| format!("读取签到流水失败: {}", e))
+}
+
 /// 验证结果结构
 #[derive(serde::Serialize)]
 pub struct ValidationResult {
@@ -2,7 +2,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 use crate::commands::profile_commands::ProfileManagerState;
 use ::duckcoding::services::amp_native_config;
@@ -114,23 +114,68 @@ pub async fn test_proxy_request(
             .map_err(|e| format!("Failed to build client: {e}"))?
     };
 
-    match client.get(&test_url).send().await {
+    Ok(send_test_request(client.get(&test_url)).await)
+}
+
+/// 全局代理连通性测试地址
+const GLOBAL_PROXY_TEST_URL: &str = "https://api.github.com";
+
+/// 使用已保存的全局代理配置验证代理连通性
+///
+/// 与其他出站请求共用 `get_global_client`，测试结果即实际请求所走的链路
+#[tauri::command]
+pub async fn test_proxy() -> Result<TestProxyResult, String> {
+    let enabled = read_global_config()?
+        .map(|cfg| cfg.proxy_enabled)
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(TestProxyResult {
+            success: false,
+            status: 0,
+            url: None,
+            error: Some("全局代理未启用".to_string()),
+        });
+    }
+
+    let client = match ::duckcoding::core::get_global_client() {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(TestProxyResult {
+                success: false,
+                status: 0,
+                url: None,
+                error: Some(e.to_string()),
+            });
+        }
+    };
+
+    Ok(send_test_request(
+        client
+            .get(GLOBAL_PROXY_TEST_URL)
+            .timeout(std::time::Duration::from_secs(10)),
+    )
+    .await)
+}
+
+/// 发送测试请求并转换为测试结果
+async fn send_test_request(request: reqwest::RequestBuilder) -> TestProxyResult {
+    match request.send().await {
         Ok(resp) => {
             let status = resp.status().as_u16();
             let url_ret = resp.url().as_str().to_string();
-            Ok(TestProxyResult {
+            TestProxyResult {
                 success: resp.status().is_success(),
                 status,
                 url: Some(url_ret),
                 error: None,
-            })
+            }
         }
-        Err(e) => Ok(TestProxyResult {
+        Err(e) => TestProxyResult {
             success: false,
             status: 0,
             url: None,
             error: Some(e.to_string()),
-        }),
+        },
     }
 }
 
@@ -324,11 +369,14 @@ pub(crate) async fn start_tool_proxy_internal(
 
 #[tauri::command]
 pub async fn start_tool_proxy(
+    app: AppHandle,
     tool_id: String,
     manager_state: State<'_, ProxyManagerState>,
     profile_state: State<'_, ProfileManagerState>,
 ) -> Result<String, String> {
-    start_tool_proxy_internal(&tool_id, &manager_state, &profile_state).await
+    let result = start_tool_proxy_internal(&tool_id, &manager_state, &profile_state).await;
+    crate::setup::tray::refresh_tray_menu(&app).await;
+    result
 }
 
 /// 停止指定工具的透明代理
@@ -420,11 +468,14 @@ pub(crate) async fn stop_tool_proxy_internal(
 
 #[tauri::command]
 pub async fn stop_tool_proxy(
+    app: AppHandle,
     tool_id: String,
     manager_state: State<'_, ProxyManagerState>,
     profile_state: State<'_, ProfileManagerState>,
 ) -> Result<String, String> {
-    stop_tool_proxy_internal(&tool_id, &manager_state, &profile_state).await
+    let result = stop_tool_proxy_internal(&tool_id, &manager_state, &profile_state).await;
+    crate::setup::tray::refresh_tray_menu(&app).await;
+    result
 }
 
 /// 获取所有工具的透明代理状态
@@ -462,6 +513,34 @@ pub async fn get_all_proxy_status(
     Ok(status_map)
 }
 
+/// 获取各工具代理的运行状态、端口与监听模式
+///
+/// 运行中的代理取当前生效配置，未运行的取已保存配置（无配置的工具不返回）
+#[tauri::command]
+pub async fn get_proxy_status(
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<Vec<::duckcoding::services::proxy::ProxyStatus>, String> {
+    let mut statuses = manager_state.manager.get_proxy_status().await;
+
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let proxy_store = proxy_config_mgr
+        .load_proxy_store()
+        .map_err(|e| e.to_string())?;
+    for tool_id in &["claude-code", "codex", "gemini-cli", "amp-code"] {
+        if statuses.iter().any(|s| s.tool_id == *tool_id) {
+            continue;
+        }
+        if let Some(config) = proxy_store.get_config(tool_id) {
+            statuses.push(::duckcoding::services::proxy::ProxyStatus::from_config(
+                tool_id, false, config,
+            ));
+        }
+    }
+    statuses.sort_by(|a, b| a.tool_id.cmp(&b.tool_id));
+
+    Ok(statuses)
+}
+
 /// 从 Profile 更新代理配置（不激活 Profile）
 pub(crate) async fn update_proxy_from_profile_internal(
     tool_id: &str,
@@ -558,6 +637,34 @@ pub async fn get_proxy_config(
     proxy_mgr.get_config(&tool_id).map_err(|e| e.to_string())
 }
 
+/// 获取运行中代理实例的当前配置快照（密钥已脱敏）
+#[tauri::command]
+pub async fn get_runtime_proxy_config(
+    tool: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<::duckcoding::models::proxy_config::ToolProxyConfig, String> {
+    manager_state
+        .manager
+        .runtime_config(&tool)
+        .await
+        .map(|config| config.masked())
+        .ok_or_else(|| format!("{tool} 代理未运行"))
+}
+
+/// 获取代理最近的请求摘要（方法、路径、状态码、耗时、上游 URL，默认 50 条，最新的在前）
+#[tauri::command]
+pub async fn get_proxy_recent_requests(
+    tool_id: String,
+    limit: Option<usize>,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<Vec<::duckcoding::services::proxy::utils::request_history::RequestSummary>, String> {
+    manager_state
+        .manager
+        .recent_requests(&tool_id, limit.unwrap_or(50))
+        .await
+        .ok_or_else(|| format!("{tool_id} 代理未运行"))
+}
+
 /// 更新指定工具的代理配置
 #[tauri::command]
 pub async fn update_proxy_config(
@@ -566,9 +673,26 @@ pub async fn update_proxy_config(
     manager_state: State<'_, ProxyManagerState>,
     profile_state: State<'_, ProfileManagerState>,
 ) -> Result<(), String> {
-    // ========== 运行时保护检查 ==========
+    // ========== 校验内容过滤规则 ==========
+    ::duckcoding::services::proxy::utils::content_filter::ContentFilter::compile(
+        &config.response_filters,
+    )
+    .map_err(|e| format!("{:#}", e))?;
+    ::duckcoding::services::proxy::utils::status_mapping::validate(&config.status_code_mappings)
+        .map_err(|e| format!("{:#}", e))?;
+
+    // ========== 运行中热更新（端口变化时平滑切换监听，失败则不保存） ==========
+    let port_changed = manager_state
+        .manager
+        .runtime_config(&tool_id)
+        .await
+        .is_some_and(|running| running.port != config.port);
     if manager_state.manager.is_running(&tool_id).await {
-        return Err(format!("{} 代理正在运行，请先停止代理再修改配置", tool_id));
+        manager_state
+            .manager
+            .update_config(&tool_id, config.clone())
+            .await
+            .map_err(|e| format!("{:#}", e))?;
     }
 
     // ========== 更新配置到全局配置文件 ==========
@@ -637,9 +761,62 @@ pub async fn update_proxy_config(
         );
     }
 
+    // ========== 运行中端口变化：重新应用到原生配置，让工具指向新端口 ==========
+    if port_changed {
+        reapply_proxy_endpoint(&tool_id, &config, &profile_state).await?;
+    }
+
+    Ok(())
+}
+
+/// 运行中的代理端口变化后，将新的代理地址重新写入工具原生配置
+///
+/// 普通工具重新激活内置 Profile（同步 settings.json / config.toml / .env），
+/// amp-code 直接重写 AMP Code 配置
+async fn reapply_proxy_endpoint(
+    tool_id: &str,
+    config: &::duckcoding::models::proxy_config::ToolProxyConfig,
+    profile_state: &ProfileManagerState,
+) -> Result<(), String> {
+    let local_key = config
+        .local_api_key
+        .as_ref()
+        .ok_or_else(|| "透明代理保护密钥未设置".to_string())?;
+
+    if tool_id == "amp-code" {
+        let proxy_url = format!("http://127.0.0.1:{}", config.port);
+        amp_native_config::apply_proxy_config(&proxy_url, local_key)
+            .map_err(|e| format!("应用 AMP Code 代理配置失败: {}", e))?;
+    } else {
+        let proxy_profile_name = format!("dc_proxy_{}", tool_id.replace("-", "_"));
+        profile_state
+            .manager
+            .read()
+            .await
+            .activate_profile(tool_id, &proxy_profile_name)
+            .map_err(|e| format!("激活内置 Profile 失败: {}", e))?;
+    }
+
+    tracing::info!(tool_id = %tool_id, port = config.port, "代理端口已变更，已更新工具原生配置");
     Ok(())
 }
 
+/// 检查工具代理配置的上游连通性与 API Key 有效性
+#[tauri::command]
+pub async fn proxy_health_check(
+    tool_id: String,
+) -> Result<::duckcoding::services::proxy::utils::upstream_check::ProxyHealthCheck, String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let config = proxy_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{tool_id} 尚未配置代理"))?;
+
+    ::duckcoding::services::proxy::utils::upstream_check::check_upstream(&tool_id, &config)
+        .await
+        .map_err(|e| format!("{:#}", e))
+}
+
 /// 获取所有工具的代理配置
 #[tauri::command]
 pub async fn get_all_proxy_configs(
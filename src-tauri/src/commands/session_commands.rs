@@ -13,6 +13,22 @@ pub async fn get_session_list(
     Ok(SESSION_MANAGER.get_session_list(&tool_id, page, page_size)?)
 }
 
+/// 分页查询会话（tool_id 为空时返回所有工具的会话）
+#[tauri::command]
+pub async fn list_sessions(
+    tool_id: Option<String>,
+    page: u32,
+    page_size: u32,
+) -> AppResult<SessionListResponse> {
+    Ok(SESSION_MANAGER.list_sessions(tool_id.as_deref(), page as usize, page_size as usize)?)
+}
+
+/// 获取未过期的活跃会话数量（调试用）
+#[tauri::command]
+pub async fn get_active_session_count() -> AppResult<usize> {
+    Ok(SESSION_MANAGER.get_active_session_count()?)
+}
+
 /// 删除单个会话
 #[tauri::command]
 pub async fn delete_session(session_id: String) -> AppResult<()> {
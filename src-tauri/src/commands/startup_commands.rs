@@ -2,8 +2,9 @@
 
 //! 开机自启动管理命令
 //!
-//! 提供前端调用的开机自启动配置管理接口
+//! 提供前端调用的开机自启动配置管理接口，以及本次启动的性能报告
 
+use duckcoding::core::startup_timing::{startup_report, StartupReport};
 use duckcoding::utils::auto_startup::{
     disable_auto_startup, enable_auto_startup, is_auto_startup_enabled,
 };
@@ -97,3 +98,11 @@ mod tests {
         assert!(!enabled);
     }
 }
+
+/// 获取本次启动的性能报告（各初始化阶段耗时）
+///
+/// 启动初始化尚未完成时返回错误
+#[tauri::command]
+pub async fn get_startup_timings() -> Result<StartupReport, String> {
+    startup_report().ok_or_else(|| "应用启动尚未完成".to_string())
+}
@@ -177,6 +177,7 @@ pub async fn import_token_as_profile(
                 raw_settings: None,
                 raw_env: None,
                 pricing_template_id: pricing_template_id.clone(),
+                auth: None,
             };
             store.gemini_cli.insert(profile_name.clone(), profile);
         }
@@ -274,6 +275,7 @@ pub async fn create_custom_profile(
                 raw_settings: None,
                 raw_env: None,
                 pricing_template_id: pricing_template_id.clone(),
+                auth: None,
             };
             store.gemini_cli.insert(profile_name.clone(), profile);
         }
@@ -1,5 +1,9 @@
-use duckcoding::models::token_stats::{SessionStats, TokenLogsPage, TokenStatsQuery};
-use duckcoding::services::token_stats::TokenStatsManager;
+use duckcoding::models::token_stats::{
+    ExportFormat, ImportFormat, ImportSummary, SessionStats, TokenLog, TokenLogsPage,
+    TokenStatsQuery,
+};
+use duckcoding::services::token_stats::{BudgetProgress, TokenStatsManager};
+use duckcoding::utils::config::read_global_config;
 
 /// 查询会话实时统计
 #[tauri::command]
@@ -20,6 +24,47 @@ pub async fn query_token_logs(query_params: TokenStatsQuery) -> Result<TokenLogs
         .map_err(|e| e.to_string())
 }
 
+/// 查询工具最近的失败请求日志（默认 50 条）
+#[tauri::command]
+pub async fn query_errors(tool: String, limit: Option<u32>) -> Result<Vec<TokenLog>, String> {
+    TokenStatsManager::get()
+        .query_errors(&tool, limit.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取本月成本目标进度
+#[tauri::command]
+pub async fn get_budget_progress() -> Result<BudgetProgress, String> {
+    let monthly_target = read_global_config()?.and_then(|cfg| cfg.budget.monthly_target);
+    TokenStatsManager::get()
+        .budget_progress(monthly_target)
+        .map_err(|e| e.to_string())
+}
+
+/// 导出 Token 日志到用户选择的文件（CSV 或 JSON Lines）
+#[tauri::command]
+pub async fn export_token_logs(
+    query: TokenStatsQuery,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    let content = TokenStatsManager::get()
+        .export_logs(query, format)
+        .map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入导出文件失败: {}", e))
+}
+
+/// 从其他用量统计工具导入历史日志（CSV 或 JSON），按 message_id / 时间去重
+#[tauri::command]
+pub async fn import_token_logs(
+    format: ImportFormat,
+    data: String,
+) -> Result<ImportSummary, String> {
+    TokenStatsManager::get()
+        .import_logs(format, &data)
+        .map_err(|e| e.to_string())
+}
+
 /// 手动清理旧日志
 #[tauri::command]
 pub async fn cleanup_token_logs(
@@ -31,6 +76,29 @@ pub async fn cleanup_token_logs(
         .map_err(|e| e.to_string())
 }
 
+/// 删除指定时间窗内的日志（毫秒时间戳，含端点），返回删除条数
+#[tauri::command]
+pub async fn delete_token_logs_range(
+    start_ts: i64,
+    end_ts: i64,
+    tool_type: Option<String>,
+) -> Result<usize, String> {
+    TokenStatsManager::get()
+        .delete_logs_in_range(start_ts, end_ts, tool_type)
+        .map_err(|e| e.to_string())
+}
+
+/// 按当前（或指定）定价模板重算匹配日志的成本，返回重算条数
+#[tauri::command]
+pub async fn recalculate_costs(
+    query: TokenStatsQuery,
+    template_id: Option<String>,
+) -> Result<usize, String> {
+    TokenStatsManager::get()
+        .recalculate_costs(&query, template_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
 /// 获取数据库统计摘要
 #[tauri::command]
 pub async fn get_token_stats_summary() -> Result<(i64, Option<i64>, Option<i64>), String> {
@@ -1,9 +1,15 @@
 use crate::commands::error::{AppError, AppResult};
 use crate::commands::tool_management::ToolRegistryState;
-use crate::commands::types::{InstallResult, ToolStatus};
+use crate::commands::types::{InstallProgress, InstallResult, ToolStatus};
 use ::duckcoding::models::{InstallMethod, Tool};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::services::tool::PackageManager;
 use ::duckcoding::services::InstallerService;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// 安装进度事件名
+pub const INSTALL_PROGRESS_EVENT: &str = "install-progress";
 
 /// 检查所有工具的安装状态（新架构：优先从数据库读取）
 ///
@@ -41,9 +47,24 @@ pub async fn refresh_tool_status(
         .map_err(|e| format!("获取工具状态失败: {}", e))
 }
 
+/// 检测系统中可用的 Node 包管理器（npm/pnpm/yarn/bun），前端据此展示安装方式
+#[tauri::command]
+pub async fn detect_package_managers() -> Result<Vec<String>, String> {
+    Ok(InstallerService::new()
+        .detect_package_managers()
+        .await
+        .into_iter()
+        .map(|manager| manager.as_str().to_string())
+        .collect())
+}
+
 /// 安装指定工具
+///
+/// `method` 支持 npm/pnpm/yarn/bun/brew/official；
+/// 安装命令的每行输出通过 `install-progress` 事件实时推送给前端
 #[tauri::command]
 pub async fn install_tool(
+    app: AppHandle,
     tool: String,
     method: String,
     force: Option<bool>,
@@ -59,9 +80,9 @@ pub async fn install_tool(
     let tool_obj =
         Tool::by_id(&tool).ok_or_else(|| AppError::ToolNotFound { tool: tool.clone() })?;
 
-    // 转换安装方法
+    // 转换安装方法（pnpm/yarn/bun 与 npm 同属 Node 包管理器安装）
     let install_method = match method.as_str() {
-        "npm" => InstallMethod::Npm,
+        "npm" | "pnpm" | "yarn" | "bun" => InstallMethod::Npm,
         "brew" => InstallMethod::Brew,
         "official" => InstallMethod::Official,
         _ => {
@@ -72,25 +93,62 @@ pub async fn install_tool(
         }
     };
 
-    // 使用 InstallerService 安装
+    // 使用 InstallerService 安装，逐行推送输出并保留完整输出
     let installer = InstallerService::new();
+    let output_lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let output_lines = Arc::clone(&output_lines);
+        let tool = tool.clone();
+        Arc::new(move |line: &str| {
+            if let Ok(mut lines) = output_lines.lock() {
+                lines.push(line.to_string());
+            }
+            let progress = InstallProgress {
+                tool: tool.clone(),
+                line: line.to_string(),
+            };
+            if let Err(e) = app.emit(INSTALL_PROGRESS_EVENT, progress) {
+                tracing::warn!(error = ?e, "发送安装进度事件失败");
+            }
+        })
+    };
+
+    let outcome = match PackageManager::parse(&method) {
+        Some(manager) => {
+            installer
+                .install_with_package_manager(&tool_obj, manager, force, sink)
+                .await
+        }
+        None => {
+            installer
+                .install_with_progress(&tool_obj, &install_method, force, sink)
+                .await
+        }
+    };
 
-    match installer.install(&tool_obj, &install_method, force).await {
+    match outcome {
         Ok(_) => {
             // 安装成功（前端会调用 refresh_tool_status 更新数据库）
 
             // 构造成功消息
             let message = match method.as_str() {
-                "npm" => format!("✅ {} 安装成功！(通过 npm)", tool_obj.name),
+                "npm" | "pnpm" | "yarn" | "bun" => {
+                    format!("✅ {} 安装成功！(通过 {})", tool_obj.name, method)
+                }
                 "brew" => format!("✅ {} 安装成功！(通过 Homebrew)", tool_obj.name),
                 "official" => format!("✅ {} 安装成功！", tool_obj.name),
                 _ => format!("✅ {} 安装成功！", tool_obj.name),
             };
 
+            let output = output_lines
+                .lock()
+                .map(|lines| lines.join("\n"))
+                .unwrap_or_default();
             Ok(InstallResult {
                 success: true,
                 message,
-                output: String::new(),
+                output,
+                backup_path: None,
             })
         }
         Err(e) => {
@@ -99,3 +157,74 @@ pub async fn install_tool(
         }
     }
 }
+
+/// 卸载指定工具
+///
+/// 仅支持 Node 包管理器（npm/pnpm/yarn/bun）安装的工具，按安装时使用的包管理器执行全局卸载，
+/// 其他安装方式返回手动卸载提示；
+/// 卸载前先把生效配置备份到 `~/.duckcoding/backups`（备份失败则中止，不卸载也不删除），
+/// 卸载成功后删除本程序写入的生效配置文件，Profile 备份保持不变
+#[tauri::command]
+pub async fn uninstall_tool(tool: String, method: String) -> AppResult<InstallResult> {
+    let tool_obj =
+        Tool::by_id(&tool).ok_or_else(|| AppError::ToolNotFound { tool: tool.clone() })?;
+
+    // npm/pnpm/yarn/bun 按对应包管理器卸载，其余安装方式交给 Detector 处理
+    let manager = PackageManager::parse(&method);
+    let install_method = match method.as_str() {
+        _ if manager.is_some() => InstallMethod::Npm,
+        "brew" => InstallMethod::Brew,
+        "official" => InstallMethod::Official,
+        "other" => InstallMethod::Other,
+        _ => {
+            return Err(AppError::ValidationError {
+                field: "method".to_string(),
+                reason: format!("未知的安装方法: {}", method),
+            })
+        }
+    };
+
+    // 先确认能自动卸载，不支持的安装方式直接返回，不留下备份
+    let installer = InstallerService::new();
+    installer.check_uninstall(&tool_obj, &install_method)?;
+
+    // 删除配置前必须先备份成功
+    let backup_dir = InstallerService::backup_config_to_default_dir(&tool_obj)?;
+
+    match manager {
+        Some(manager) => {
+            installer
+                .uninstall_with_package_manager(&tool_obj, manager)
+                .await?
+        }
+        None => installer.uninstall(&tool_obj, &install_method).await?,
+    }
+
+    let removed = InstallerService::remove_generated_config(&tool_obj)?;
+    let mut lines: Vec<String> = removed
+        .iter()
+        .map(|path| format!("已删除配置文件: {}", path.display()))
+        .collect();
+    if let Some(dir) = &backup_dir {
+        lines.insert(0, format!("配置已备份到: {}", dir.display()));
+    }
+
+    Ok(InstallResult {
+        success: true,
+        message: format!("✅ {} 已卸载", tool_obj.name),
+        output: lines.join("\n"),
+        backup_path: backup_dir.map(|p| p.to_string_lossy().into_owned()),
+    })
+}
+
+/// 卸载工具前备份其全部配置到 ~/.duckcoding/backups
+///
+/// 返回备份目录路径（没有配置文件时返回 None），重装后可据此还原
+#[tauri::command]
+pub async fn backup_tool_config_before_uninstall(tool: String) -> AppResult<Option<String>> {
+    let tool_obj =
+        Tool::by_id(&tool).ok_or_else(|| AppError::ToolNotFound { tool: tool.clone() })?;
+
+    let backup_dir = InstallerService::backup_config_to_default_dir(&tool_obj)?;
+    Ok(backup_dir.map(|p| p.to_string_lossy().into_owned()))
+}
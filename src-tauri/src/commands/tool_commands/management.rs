@@ -1,7 +1,7 @@
 use crate::commands::error::{AppError, AppResult};
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::ToolStatus;
-use ::duckcoding::models::InstallMethod;
+use ::duckcoding::models::{InstallMethod, ToolInstance};
 
 /// 手动添加工具实例（保存用户指定的路径）
 ///
@@ -38,3 +38,19 @@ pub async fn add_manual_tool_instance(
         .add_tool_instance(&tool_id, &path, parsed_method, installer_path)
         .await?)
 }
+
+/// 扫描系统已安装的工具并批量导入为实例
+///
+/// 工作流程：
+/// 1. 委托给 ToolRegistry.scan_and_import_instances
+/// 2. Registry 负责扫描路径、获取版本、跳过已登记路径并保存
+///
+/// 返回：本次新建的实例列表
+#[tauri::command]
+pub async fn scan_and_import_instances(
+    base_id: String,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<Vec<ToolInstance>> {
+    let registry = registry_state.registry.lock().await;
+    Ok(registry.scan_and_import_instances(&base_id).await?)
+}
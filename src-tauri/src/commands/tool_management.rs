@@ -71,6 +71,20 @@ pub async fn add_ssh_tool_instance(
         .map_err(|e| format!("添加SSH实例失败: {}", e))
 }
 
+/// 设置工具实例的自定义环境变量（执行 --version / 更新命令时注入）
+#[tauri::command]
+pub async fn set_tool_instance_env(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+    env: HashMap<String, String>,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .set_instance_env(&instance_id, env)
+        .await
+        .map_err(|e| format!("设置实例环境变量失败: {}", e))
+}
+
 /// 删除工具实例（仅SSH类型）
 #[tauri::command]
 pub async fn delete_tool_instance(
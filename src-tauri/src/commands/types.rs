@@ -18,4 +18,14 @@ pub struct InstallResult {
     pub success: bool,
     pub message: String,
     pub output: String,
+    /// 卸载前的配置备份目录（仅卸载时返回，没有配置文件时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<String>,
+}
+
+/// 安装进度（`install-progress` 事件载荷，每行安装命令输出一条）
+#[derive(Clone, serde::Serialize)]
+pub struct InstallProgress {
+    pub tool: String,
+    pub line: String,
 }
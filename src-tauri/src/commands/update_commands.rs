@@ -6,6 +6,7 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, State};
 
 use ::duckcoding::models::update::{PackageFormatInfo, PlatformInfo};
+use ::duckcoding::services::tool::DownloadCacheUsage;
 use ::duckcoding::services::update::{UpdateInfo, UpdateService, UpdateStatus};
 
 /// 统一管理 UpdateService 的 Tauri State
@@ -91,6 +92,24 @@ pub async fn rollback_app_update(state: State<'_, UpdateServiceState>) -> Result
         .map_err(|e| format!("Failed to rollback update: {e}"))
 }
 
+/// 获取安装包缓存占用（未启用缓存时返回 null）
+#[tauri::command]
+pub async fn get_download_cache_usage(
+    state: State<'_, UpdateServiceState>,
+) -> Result<Option<DownloadCacheUsage>, String> {
+    Ok(state.service.download_cache_usage().await)
+}
+
+/// 清空安装包缓存
+#[tauri::command]
+pub async fn clear_download_cache(state: State<'_, UpdateServiceState>) -> Result<u64, String> {
+    state
+        .service
+        .clear_download_cache()
+        .await
+        .map_err(|e| format!("Failed to clear download cache: {e}"))
+}
+
 /// 获取当前应用版本
 #[tauri::command]
 pub async fn get_current_app_version(
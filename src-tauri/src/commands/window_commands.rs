@@ -1,32 +1,53 @@
 use crate::commands::error::{AppError, AppResult};
+use ::duckcoding::models::config::CloseBehavior;
 use ::duckcoding::ui;
-use tauri::{AppHandle, Manager, WebviewWindow};
+use ::duckcoding::utils::config::{read_global_config, write_global_config};
+use tauri::{AppHandle, WebviewWindow};
 
 /// 处理窗口关闭操作
 ///
 /// # 参数
 /// - `window`: WebviewWindow 实例
 /// - `action`: 关闭操作类型 ("minimize" 或 "quit")
+/// - `remember`: 是否记住选择（写入全局配置的 `close_behavior`，之后关闭不再询问）
 #[tauri::command]
-pub fn handle_close_action(window: WebviewWindow, action: String) -> AppResult<()> {
-    match action.as_str() {
-        "minimize" => {
-            // 隐藏到托盘
-            ui::hide_window_to_tray(&window);
-            Ok(())
+pub fn handle_close_action(
+    window: WebviewWindow,
+    action: String,
+    remember: Option<bool>,
+) -> AppResult<()> {
+    let behavior = match action.as_str() {
+        "minimize" => CloseBehavior::Minimize,
+        "quit" => CloseBehavior::Exit,
+        other => {
+            return Err(AppError::ValidationError {
+                field: "action".to_string(),
+                reason: format!("未知的关闭操作: {}", other),
+            })
         }
-        "quit" => {
-            window.app_handle().exit(0);
-            Ok(())
+    };
+
+    // 先保存选择再执行（退出后无法再写入）
+    if remember.unwrap_or(false) {
+        if let Err(e) = remember_close_behavior(behavior) {
+            tracing::warn!(error = %e, "保存关闭行为失败");
         }
-        other => Err(AppError::ValidationError {
-            field: "action".to_string(),
-            reason: format!("未知的关闭操作: {}", other),
-        }),
     }
+
+    ui::handle_close_request(&window, behavior);
+    Ok(())
+}
+
+/// 将关闭行为写入全局配置
+fn remember_close_behavior(behavior: CloseBehavior) -> Result<(), String> {
+    let Some(mut config) = read_global_config()? else {
+        return Ok(());
+    };
+    config.close_behavior = behavior;
+    write_global_config(&config)
 }
 
-/// 刷新应用菜单栏（仅 macOS）
+/// 刷新应用菜单栏（macOS）或系统托盘菜单（其他平台）
 #[tauri::command]
 pub fn refresh_app_menu(app: AppHandle) -> AppResult<()> {
     #[cfg(target_os = "macos")]
@@ -37,7 +58,9 @@ pub fn refresh_app_menu(app: AppHandle) -> AppResult<()> {
     }
     #[cfg(not(target_os = "macos"))]
     {
-        let _ = app;
+        tauri::async_runtime::spawn(async move {
+            crate::setup::tray::refresh_tray_menu(&app).await;
+        });
     }
     Ok(())
 }
@@ -2,6 +2,7 @@ pub mod error;
 pub mod http;
 pub mod log_utils;
 pub mod logger;
+pub mod startup_timing;
 
 #[cfg(test)]
 mod error_test;
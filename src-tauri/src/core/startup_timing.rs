@@ -0,0 +1,161 @@
+//! 启动性能计时
+//!
+//! `initialize_app` 按阶段记录耗时，结束后生成启动性能报告并保存在进程内，
+//! 供前端通过 `get_startup_timings` 查看各服务初始化耗时、定位启动瓶颈
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// 本次启动的性能报告（启动完成后写入一次）
+static STARTUP_REPORT: OnceCell<StartupReport> = OnceCell::new();
+
+/// 单个启动阶段耗时
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupPhase {
+    /// 阶段名称
+    pub name: String,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 启动性能报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupReport {
+    /// 各阶段耗时（按执行顺序）
+    pub phases: Vec<StartupPhase>,
+    /// 启动总耗时（毫秒，含阶段之间的其他开销）
+    pub total_ms: u64,
+    /// 耗时最长的阶段
+    pub slowest_phase: Option<String>,
+}
+
+/// 启动阶段计时器
+pub struct StartupTimer {
+    started: Instant,
+    phases: Vec<StartupPhase>,
+}
+
+impl StartupTimer {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// 记录一个已完成阶段的耗时
+    pub fn record(&mut self, name: &str, duration: Duration) {
+        tracing::debug!(
+            phase = name,
+            elapsed_ms = duration.as_millis(),
+            "启动阶段完成"
+        );
+        self.phases.push(StartupPhase {
+            name: name.to_string(),
+            duration_ms: duration.as_millis() as u64,
+        });
+    }
+
+    /// 执行同步阶段并记录耗时
+    pub fn measure<T>(&mut self, name: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    /// 执行异步阶段并记录耗时
+    pub async fn measure_async<T>(&mut self, name: &str, fut: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let result = fut.await;
+        self.record(name, start.elapsed());
+        result
+    }
+
+    /// 生成启动性能报告
+    pub fn report(&self) -> StartupReport {
+        let slowest_phase = self
+            .phases
+            .iter()
+            .max_by_key(|phase| phase.duration_ms)
+            .map(|phase| phase.name.clone());
+        StartupReport {
+            phases: self.phases.clone(),
+            total_ms: self.started.elapsed().as_millis() as u64,
+            slowest_phase,
+        }
+    }
+
+    /// 结束计时并保存报告（重复调用时保留首次报告）
+    pub fn finish(self) -> StartupReport {
+        let report = self.report();
+        tracing::info!(
+            total_ms = report.total_ms,
+            slowest = ?report.slowest_phase,
+            "应用启动完成"
+        );
+        let _ = STARTUP_REPORT.set(report.clone());
+        report
+    }
+}
+
+impl Default for StartupTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 获取本次启动的性能报告（启动尚未完成时为 None）
+pub fn startup_report() -> Option<StartupReport> {
+    STARTUP_REPORT.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_records_phases_in_order() {
+        let mut timer = StartupTimer::new();
+        let value = timer.measure("fast", || 1);
+        assert_eq!(value, 1);
+        timer.measure("slow", || std::thread::sleep(Duration::from_millis(20)));
+        timer.record("manual", Duration::from_millis(5));
+
+        let report = timer.report();
+        let names: Vec<&str> = report.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["fast", "slow", "manual"]);
+        assert!(report.phases[1].duration_ms >= 20);
+        assert_eq!(report.phases[2].duration_ms, 5);
+        assert_eq!(report.slowest_phase.as_deref(), Some("slow"));
+        assert!(report.total_ms >= 20);
+    }
+
+    #[tokio::test]
+    async fn test_measure_async_and_finish() {
+        let mut timer = StartupTimer::new();
+        let value = timer
+            .measure_async("async", async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                "done"
+            })
+            .await;
+        assert_eq!(value, "done");
+
+        let report = timer.finish();
+        assert!(report.phases[0].duration_ms >= 10);
+        // 首次 finish 的报告被保存，之后的报告不覆盖
+        assert_eq!(startup_report(), Some(report.clone()));
+        StartupTimer::new().finish();
+        assert_eq!(startup_report(), Some(report));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = StartupTimer::new().report();
+        assert!(report.phases.is_empty());
+        assert!(report.slowest_phase.is_none());
+    }
+}
@@ -124,12 +124,23 @@ impl JsonManager {
             fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
         }
 
-        // 写入文件（格式化输出）
+        // 写入同目录临时文件后 rename 替换（格式化输出），写入中断时不会损坏原文件
         let content = serde_json::to_string_pretty(value)?;
-        fs::write(path, content).map_err(|e| DataError::io(path.to_path_buf(), e))?;
-
-        // 设置权限
-        set_permissions(path)?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+        let result = fs::write(&tmp_path, content)
+            .map_err(|e| DataError::io(tmp_path.clone(), e))
+            .and_then(|_| set_permissions(&tmp_path))
+            .and_then(|_| {
+                fs::rename(&tmp_path, path).map_err(|e| DataError::io(path.to_path_buf(), e))
+            });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result?;
 
         // 使缓存失效（文件已变更）
         if let Some(cache) = &self.cache {
@@ -512,6 +523,11 @@ mod tests {
         // 读取
         let read_content = manager.read(&file_path).unwrap();
         assert_eq!(read_content, content);
+
+        // 覆盖写入后不残留临时文件
+        manager.write(&file_path, &json!({"key": "new"})).unwrap();
+        assert_eq!(manager.read(&file_path).unwrap()["key"], "new");
+        assert!(!temp_dir.path().join(".config.json.tmp").exists());
     }
 
     #[test]
@@ -8,6 +8,9 @@ pub mod services;
 pub mod ui; // 🆕 UI 管理层
 pub mod utils;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 pub use models::*;
 // Explicitly re-export only selected service types to avoid ambiguous glob re-exports
 pub use models::InstallMethod; // InstallMethod is defined in models (tool.rs) — re-export from models
@@ -128,6 +128,120 @@ fn schedule_update_check(app_handle: AppHandle) {
     });
 }
 
+/// 启动工具健康巡检（如果启用）
+fn start_health_inspector(app_handle: AppHandle) {
+    let config = read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.health_check)
+        .unwrap_or_default();
+
+    if !config.enabled {
+        tracing::info!("工具健康巡检已禁用");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<HealthInspectorState>();
+        state
+            .inspector
+            .start(app_handle.clone(), config.interval_secs)
+            .await;
+    });
+}
+
+/// 启动用量异常检测（如果启用）
+fn start_usage_anomaly_monitor(app_handle: AppHandle) {
+    let config = read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.usage_anomaly)
+        .unwrap_or_default();
+
+    if !config.enabled {
+        tracing::info!("用量异常检测已禁用");
+        return;
+    }
+
+    duckcoding::services::token_stats::anomaly::start_usage_anomaly_monitor(app_handle, config);
+}
+
+/// 健康巡检发现代理运行状态变化时同步刷新托盘菜单
+fn sync_tray_menu_on_health_report(app_handle: AppHandle) {
+    use duckcoding::services::health_check::HEALTH_REPORT_EVENT;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tauri::Listener;
+
+    // 巡检周期性触发，仅在运行状态变化时重建菜单
+    let last_states: Arc<Mutex<Option<HashMap<String, bool>>>> = Arc::default();
+    let handle = app_handle.clone();
+    app_handle.listen(HEALTH_REPORT_EVENT, move |_| {
+        let handle = handle.clone();
+        let last_states = last_states.clone();
+        tauri::async_runtime::spawn(async move {
+            let states = setup::proxy_menu::load_proxy_running_states(&handle).await;
+            let changed = match last_states.lock() {
+                Ok(mut last) => {
+                    let changed = last.as_ref() != Some(&states);
+                    *last = Some(states);
+                    changed
+                }
+                Err(_) => true,
+            };
+            if changed {
+                setup::tray::refresh_tray_menu(&handle).await;
+            }
+        });
+    });
+}
+
+/// 将代理启动探测结果转发为前端事件（先补发订阅前已完成的探测）
+fn forward_proxy_upstream_status(app_handle: AppHandle) {
+    use duckcoding::services::proxy::utils::startup_probe;
+
+    let mut receiver = startup_probe::subscribe();
+    tauri::async_runtime::spawn(async move {
+        for status in startup_probe::latest_statuses() {
+            if let Err(e) = app_handle.emit(startup_probe::PROXY_UPSTREAM_STATUS_EVENT, &status) {
+                tracing::error!(error = ?e, "发送代理上游状态事件失败");
+            }
+        }
+        loop {
+            match receiver.recv().await {
+                Ok(status) => {
+                    if let Err(e) =
+                        app_handle.emit(startup_probe::PROXY_UPSTREAM_STATUS_EVENT, &status)
+                    {
+                        tracing::error!(error = ?e, "发送代理上游状态事件失败");
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// 修复含敏感信息配置文件的权限（失败不影响启动）
+fn fix_sensitive_file_permissions() {
+    match duckcoding::utils::fix_sensitive_file_permissions() {
+        Ok(fixed) if !fixed.is_empty() => {
+            tracing::info!(files = ?fixed, "已修复 {} 个配置文件的权限", fixed.len());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = ?e, "修复配置文件权限失败"),
+    }
+}
+
+/// 启动余额监控后台执行任务
+fn start_balance_executor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<BalanceExecutorState>();
+        state.executor.start(app_handle.clone()).await;
+    });
+}
+
 /// 执行应用启动钩子（setup）
 fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
     // 1. 应用代理配置
@@ -153,6 +267,24 @@ fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
     // 7. 启动后检查更新
     schedule_update_check(app.handle().clone());
 
+    // 8. 启动工具健康巡检
+    start_health_inspector(app.handle().clone());
+
+    // 9. 启动余额监控后台执行
+    start_balance_executor(app.handle().clone());
+
+    // 10. 启动用量异常检测
+    start_usage_anomaly_monitor(app.handle().clone());
+
+    // 11. 收紧敏感配置文件权限
+    fix_sensitive_file_permissions();
+
+    // 12. 转发代理启动后的上游探测结果
+    forward_proxy_upstream_status(app.handle().clone());
+
+    // 13. 健康巡检后同步托盘菜单中的代理状态
+    sync_tray_menu_on_health_report(app.handle().clone());
+
     Ok(())
 }
 
@@ -162,6 +294,14 @@ fn main() {
         setup::initialize_app().await.expect("应用初始化失败")
     });
 
+    let health_inspector_state =
+        HealthInspectorState::new(duckcoding::services::HealthInspector::new(
+            init_ctx.tool_registry.clone(),
+            init_ctx.proxy_manager.clone(),
+        ));
+
+    let balance_executor_state = BalanceExecutorState::new();
+
     let proxy_manager_state = ProxyManagerState {
         manager: init_ctx.proxy_manager,
     };
@@ -219,6 +359,8 @@ fn main() {
         .manage(provider_manager_state)
         .manage(dashboard_manager_state)
         .manage(checkin_scheduler_state)
+        .manage(health_inspector_state)
+        .manage(balance_executor_state)
         .setup(|app| {
             setup_app_hooks(app)?;
             Ok(())
@@ -260,6 +402,9 @@ fn main() {
         refresh_tool_status,
         check_node_environment,
         install_tool,
+        backup_tool_config_before_uninstall,
+        detect_package_managers,
+        uninstall_tool,
         check_update,
         check_update_for_instance,
         refresh_all_tool_versions,
@@ -267,6 +412,7 @@ fn main() {
         update_tool_instance,
         validate_tool_path,
         add_manual_tool_instance,
+        scan_and_import_instances,
         scan_installer_for_tool_path,
         scan_all_tool_candidates,
         detect_single_tool,
@@ -275,6 +421,7 @@ fn main() {
         save_global_config,
         update_token_stats_config,
         get_global_config,
+        fix_config_permissions,
         generate_api_key_for_tool,
         // 使用统计
         get_usage_stats,
@@ -287,6 +434,8 @@ fn main() {
         update_balance_config,
         delete_balance_config,
         migrate_balance_from_localstorage,
+        get_balance_snapshots,
+        refresh_balance,
         // 窗口管理
         handle_close_action,
         refresh_app_menu,
@@ -294,6 +443,7 @@ fn main() {
         get_current_proxy,
         apply_proxy_now,
         test_proxy_request,
+        test_proxy,
         // Claude Code 配置
         get_claude_settings,
         save_claude_settings,
@@ -310,16 +460,22 @@ fn main() {
         start_tool_proxy,
         stop_tool_proxy,
         get_all_proxy_status,
+        get_proxy_status,
         update_proxy_from_profile,
         get_proxy_config,
+        get_runtime_proxy_config,
+        get_proxy_recent_requests,
         update_proxy_config,
         get_all_proxy_configs,
+        proxy_health_check,
         // AMP 用户认证命令
         get_amp_user_info,
         validate_and_save_amp_token,
         get_saved_amp_user_info,
         // 会话管理命令
         get_session_list,
+        list_sessions,
+        get_active_session_count,
         delete_session,
         clear_all_sessions,
         update_session_config,
@@ -327,12 +483,28 @@ fn main() {
         // Token统计命令
         get_session_stats,
         query_token_logs,
+        query_errors,
+        get_budget_progress,
+        export_token_logs,
+        import_token_logs,
         cleanup_token_logs,
+        delete_token_logs_range,
+        recalculate_costs,
         get_token_stats_summary,
         force_token_stats_checkpoint,
         // Token统计分析命令（Phase 4）
         query_token_trends,
         query_cost_summary,
+        get_cost_summary,
+        get_cost_trend,
+        get_weekly_report,
+        query_stop_reason_distribution,
+        get_success_rate_trend,
+        get_cache_roi,
+        get_upstream_stats,
+        get_usage_by_ip,
+        get_savings_summary,
+        run_stats_query,
         // 配置监听控制
         block_external_change,
         allow_external_change,
@@ -354,6 +526,8 @@ fn main() {
         install_app_update,
         get_app_update_status,
         rollback_app_update,
+        get_download_cache_usage,
+        clear_download_cache,
         get_current_app_version,
         restart_app_for_update,
         get_platform_info,
@@ -370,6 +544,7 @@ fn main() {
         add_wsl_tool_instance,
         add_ssh_tool_instance,
         delete_tool_instance,
+        set_tool_instance_env,
         // 引导管理命令
         get_onboarding_status,
         save_onboarding_progress,
@@ -381,16 +556,25 @@ fn main() {
         // 开机自启动管理命令
         get_startup_config,
         update_startup_config,
+        get_startup_timings,
         // Profile 管理命令（v2.0）
         pm_list_all_profiles,
         pm_list_tool_profiles,
+        pm_list_tool_profile_details,
         pm_get_profile,
         pm_save_profile,
         pm_delete_profile,
         pm_activate_profile,
+        undo_last_switch,
+        apply_recommended_config,
         pm_get_active_profile_name,
         pm_get_active_profile,
         pm_capture_from_native,
+        export_profile,
+        export_config_sanitized,
+        check_config_consistency,
+        import_profile,
+        get_audit_log,
         pm_get_amp_selection,
         pm_save_amp_selection,
         // 供应商管理命令（v1.5.0）
@@ -398,6 +582,11 @@ fn main() {
         create_provider,
         update_provider,
         delete_provider,
+        get_checkin_enabled,
+        set_checkin_enabled,
+        set_checkin_enabled_all,
+        checkin_now,
+        get_checkin_history,
         validate_provider_config,
         fetch_provider_api_addresses,
         // 令牌资产管理命令（NEW API 集成）
@@ -415,6 +604,12 @@ fn main() {
         set_tool_instance_selection,
         get_selected_provider_id,
         set_selected_provider_id,
+        // 工具健康巡检命令
+        get_health_report,
+        run_health_check,
+        // 配置迁移向导命令
+        check_migration_needed,
+        run_migration,
         // 价格配置管理命令（Phase 6）
         list_pricing_templates,
         get_pricing_template,
@@ -422,6 +617,12 @@ fn main() {
         delete_pricing_template,
         set_default_template,
         get_default_template,
+        export_pricing_template,
+        import_pricing_template,
+        get_pricing_fallback_prices,
+        save_pricing_fallback_prices,
+        get_pricing_sync_status,
+        get_price_history,
         // AMP 用户认证命令
         get_amp_user_info,
         validate_and_save_amp_token,
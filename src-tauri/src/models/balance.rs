@@ -33,6 +33,9 @@ pub struct BalanceConfig {
     /// API Key（可选，明文存储）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+    /// 低余额告警阈值（剩余额度低于该值时告警）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_threshold: Option<f64>,
     /// 创建时间（Unix 时间戳，毫秒）
     pub created_at: i64,
     /// 更新时间（Unix 时间戳，毫秒）
@@ -46,6 +49,9 @@ pub struct BalanceStore {
     pub version: u32,
     /// 所有配置列表
     pub configs: Vec<BalanceConfig>,
+    /// 各配置是否处于已告警状态（配置 ID -> 是否已告警），用于跨越阈值去抖
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub last_alerted: HashMap<String, bool>,
 }
 
 impl Default for BalanceStore {
@@ -53,6 +59,7 @@ impl Default for BalanceStore {
         Self {
             version: 1,
             configs: Vec::new(),
+            last_alerted: HashMap::new(),
         }
     }
 }
@@ -77,6 +84,7 @@ mod tests {
             timeout_ms: Some(5000),
             save_api_key: false,
             api_key: None,
+            alert_threshold: Some(5.0),
             created_at: 1234567890000,
             updated_at: 1234567890000,
         };
@@ -87,6 +95,7 @@ mod tests {
         assert_eq!(config.id, deserialized.id);
         assert_eq!(config.name, deserialized.name);
         assert_eq!(config.save_api_key, deserialized.save_api_key);
+        assert_eq!(deserialized.alert_threshold, Some(5.0));
     }
 
     #[test]
@@ -142,6 +142,119 @@ fn default_auto_cleanup_enabled() -> bool {
     true
 }
 
+/// 工具健康巡检配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HealthCheckConfig {
+    /// 是否启用后台巡检
+    #[serde(default = "default_health_check_enabled")]
+    pub enabled: bool,
+    /// 巡检间隔（秒）
+    #[serde(default = "default_health_check_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_health_check_enabled(),
+            interval_secs: default_health_check_interval_secs(),
+        }
+    }
+}
+
+fn default_health_check_enabled() -> bool {
+    true
+}
+
+fn default_health_check_interval_secs() -> u64 {
+    300
+}
+
+/// 安装包下载缓存配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DownloadCacheConfig {
+    /// 是否缓存下载的安装包
+    #[serde(default = "default_download_cache_enabled")]
+    pub enabled: bool,
+    /// 缓存容量上限（MB）
+    #[serde(default = "default_download_cache_max_size_mb")]
+    pub max_size_mb: u64,
+}
+
+impl Default for DownloadCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_download_cache_enabled(),
+            max_size_mb: default_download_cache_max_size_mb(),
+        }
+    }
+}
+
+fn default_download_cache_enabled() -> bool {
+    true
+}
+
+fn default_download_cache_max_size_mb() -> u64 {
+    1024
+}
+
+/// 用量异常检测配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsageAnomalyConfig {
+    /// 是否启用用量突增检测
+    #[serde(default = "default_usage_anomaly_enabled")]
+    pub enabled: bool,
+    /// 超过基线的倍数阈值
+    #[serde(default = "default_usage_anomaly_multiplier")]
+    pub multiplier: f64,
+    /// 最低告警用量（Token），避免低用量时的噪声告警
+    #[serde(default = "default_usage_anomaly_min_tokens")]
+    pub min_tokens: i64,
+}
+
+impl Default for UsageAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_usage_anomaly_enabled(),
+            multiplier: default_usage_anomaly_multiplier(),
+            min_tokens: default_usage_anomaly_min_tokens(),
+        }
+    }
+}
+
+fn default_usage_anomaly_enabled() -> bool {
+    true
+}
+
+fn default_usage_anomaly_multiplier() -> f64 {
+    5.0
+}
+
+fn default_usage_anomaly_min_tokens() -> i64 {
+    100_000
+}
+
+/// 成本目标配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BudgetConfig {
+    /// 月度成本目标（USD，None 表示未设置）
+    #[serde(default)]
+    pub monthly_target: Option<f64>,
+}
+
+/// 关闭主窗口时的行为
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseBehavior {
+    /// 最小化到托盘
+    #[default]
+    Minimize,
+    /// 直接退出应用
+    Exit,
+    /// 每次询问（由前端弹出确认框）
+    Ask,
+}
+
 /// 配置文件快照
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigSnapshot {
@@ -282,6 +395,28 @@ pub struct GlobalConfig {
     /// Token统计配置
     #[serde(default)]
     pub token_stats_config: TokenStatsConfig,
+    /// 工具健康巡检配置
+    #[serde(default)]
+    pub health_check: HealthCheckConfig,
+    /// 安装包下载缓存配置
+    #[serde(default)]
+    pub download_cache: DownloadCacheConfig,
+    /// 用量异常检测配置
+    #[serde(default)]
+    pub usage_anomaly: UsageAnomalyConfig,
+    /// 成本目标配置
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// 代理会话过期时间（小时），超过该时长未活动的会话会被自动清理
+    #[serde(default = "default_session_ttl_hours")]
+    pub session_ttl_hours: u64,
+    /// 关闭主窗口时的行为（默认最小化到托盘）
+    #[serde(default)]
+    pub close_behavior: CloseBehavior,
+}
+
+fn default_session_ttl_hours() -> u64 {
+    24
 }
 
 fn default_proxy_configs() -> HashMap<String, ToolProxyConfig> {
@@ -37,6 +37,53 @@ pub struct ModelPrice {
     /// 模型别名列表（支持多种 ID 格式）
     #[serde(default)]
     pub aliases: Vec<String>,
+
+    /// 阶梯定价（按本次请求提示 Token 总数分档，为空时使用上方单一价格）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<PriceTier>,
+}
+
+/// 阶梯价格档位（提示 Token 总数达到 `min_input_tokens` 时生效）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceTier {
+    /// 档位起始阈值（本次请求提示 Token 总数 >= 该值时生效，含缓存读取与缓存创建）
+    pub min_input_tokens: i64,
+
+    /// 输入价格（USD/百万 Token）
+    pub input_price_per_1m: f64,
+
+    /// 输出价格（USD/百万 Token）
+    pub output_price_per_1m: f64,
+
+    /// 缓存写入价格 - 5分钟TTL（未设置时沿用基础价格）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_write_price_per_1m: Option<f64>,
+
+    /// 缓存写入价格 - 1小时TTL（未设置时沿用基础价格）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_write_1h_price_per_1m: Option<f64>,
+
+    /// 缓存读取价格（未设置时沿用基础价格）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read_price_per_1m: Option<f64>,
+}
+
+impl PriceTier {
+    /// 按倍率缩放档位价格（用于继承模型）
+    pub fn scaled(&self, multiplier: f64) -> Self {
+        Self {
+            min_input_tokens: self.min_input_tokens,
+            input_price_per_1m: self.input_price_per_1m * multiplier,
+            output_price_per_1m: self.output_price_per_1m * multiplier,
+            cache_write_price_per_1m: self.cache_write_price_per_1m.map(|p| p * multiplier),
+            cache_write_1h_price_per_1m: self.cache_write_1h_price_per_1m.map(|p| p * multiplier),
+            cache_read_price_per_1m: self.cache_read_price_per_1m.map(|p| p * multiplier),
+        }
+    }
 }
 
 impl ModelPrice {
@@ -62,8 +109,88 @@ impl ModelPrice {
             reasoning_output_price_per_1m,
             currency: default_currency(),
             aliases,
+            tiers: Vec::new(),
         }
     }
+
+    /// 按本次请求的提示 Token 总数选择生效价格
+    ///
+    /// `prompt_tokens` 为未缓存输入、缓存读取与缓存创建之和（长上下文按完整提示长度计档）；
+    /// 选择阈值不超过它的最高档位覆盖基础价格，无 tiers 或未达到任何档位时返回原价格
+    pub fn for_prompt_tokens(&self, prompt_tokens: i64) -> ModelPrice {
+        let tier = self
+            .tiers
+            .iter()
+            .filter(|t| prompt_tokens >= t.min_input_tokens)
+            .max_by_key(|t| t.min_input_tokens);
+
+        let mut price = self.clone();
+        if let Some(tier) = tier {
+            price.input_price_per_1m = tier.input_price_per_1m;
+            price.output_price_per_1m = tier.output_price_per_1m;
+            if tier.cache_write_price_per_1m.is_some() {
+                price.cache_write_price_per_1m = tier.cache_write_price_per_1m;
+            }
+            if tier.cache_write_1h_price_per_1m.is_some() {
+                price.cache_write_1h_price_per_1m = tier.cache_write_1h_price_per_1m;
+            }
+            if tier.cache_read_price_per_1m.is_some() {
+                price.cache_read_price_per_1m = tier.cache_read_price_per_1m;
+            }
+        }
+        price
+    }
+
+    /// 价格是否与另一价格一致（忽略别名等非价格字段）
+    pub fn same_price(&self, other: &ModelPrice) -> bool {
+        fn eq(a: f64, b: f64) -> bool {
+            (a - b).abs() < 1e-9
+        }
+        fn eq_opt(a: Option<f64>, b: Option<f64>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+        }
+
+        eq(self.input_price_per_1m, other.input_price_per_1m)
+            && eq(self.output_price_per_1m, other.output_price_per_1m)
+            && eq_opt(
+                self.cache_write_price_per_1m,
+                other.cache_write_price_per_1m,
+            )
+            && eq_opt(
+                self.cache_write_1h_price_per_1m,
+                other.cache_write_1h_price_per_1m,
+            )
+            && eq_opt(self.cache_read_price_per_1m, other.cache_read_price_per_1m)
+            && eq_opt(
+                self.reasoning_output_price_per_1m,
+                other.reasoning_output_price_per_1m,
+            )
+            && self.currency == other.currency
+            && self.tiers == other.tiers
+    }
+}
+
+/// 模型价格变更记录（远程同步更新价格时生成）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceChange {
+    /// 模型 ID
+    pub model: String,
+
+    /// 所属模板 ID
+    pub template_id: String,
+
+    /// 变更前价格（新增模型时为 None）
+    pub old_price: Option<ModelPrice>,
+
+    /// 变更后价格
+    pub new_price: ModelPrice,
+
+    /// 变更时间（Unix 时间戳，毫秒）
+    pub changed_at: i64,
 }
 
 /// 单个模型的继承配置
@@ -239,6 +366,25 @@ impl Default for DefaultTemplatesConfig {
     }
 }
 
+/// 未知模型兜底价格配置（存储在 fallback_prices.json）
+///
+/// 精确模型名与别名都匹配不到时，按模型所属 provider（claude / openai / gemini）
+/// 使用对应的默认档位价格估算成本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackPricesConfig {
+    /// 是否启用兜底估算
+    #[serde(default = "default_fallback_enabled")]
+    pub enabled: bool,
+
+    /// provider -> 默认档位价格
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPrice>,
+}
+
+fn default_fallback_enabled() -> bool {
+    true
+}
+
 /// 默认货币类型
 fn default_currency() -> String {
     "USD".to_string()
@@ -274,6 +420,42 @@ mod tests {
         assert_eq!(price.aliases.len(), 2);
     }
 
+    #[test]
+    fn test_model_price_tier_selection() {
+        let mut price = ModelPrice::new(
+            "google".to_string(),
+            1.25,
+            10.0,
+            None,
+            None,
+            Some(0.31),
+            None,
+            vec![],
+        );
+        // 无 tiers 时保持原价格
+        assert_eq!(price.for_prompt_tokens(1_000_000).input_price_per_1m, 1.25);
+
+        let tier = |min_input_tokens: i64, input: f64| PriceTier {
+            min_input_tokens,
+            input_price_per_1m: input,
+            output_price_per_1m: input * 8.0,
+            cache_write_price_per_1m: None,
+            cache_write_1h_price_per_1m: None,
+            cache_read_price_per_1m: None,
+        };
+        // 档位顺序无关，选择阈值不超过提示 Token 总数的最高档位
+        price.tiers = vec![tier(500_000, 4.0), tier(200_000, 2.5)];
+
+        assert_eq!(price.for_prompt_tokens(199_999).input_price_per_1m, 1.25);
+        assert_eq!(price.for_prompt_tokens(200_000).input_price_per_1m, 2.5);
+        assert_eq!(price.for_prompt_tokens(200_000).output_price_per_1m, 20.0);
+        assert_eq!(price.for_prompt_tokens(600_000).input_price_per_1m, 4.0);
+        assert_eq!(
+            price.for_prompt_tokens(600_000).cache_read_price_per_1m,
+            Some(0.31)
+        );
+    }
+
     #[test]
     fn test_inherited_model_creation() {
         let inherited = InheritedModel::new(
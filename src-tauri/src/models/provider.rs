@@ -18,9 +18,18 @@ pub struct CheckinConfig {
     /// start == end 或 start > end 时视为全天 (0-23)
     #[serde(default)]
     pub checkin_hour_end: u8,
+    /// 每天签到次数（默认 1）
+    #[serde(default = "default_checkins_per_day")]
+    pub checkins_per_day: u8,
+    /// 当天已签到次数（仅在 last_checkin_at 为本地时区当天时有效）
+    #[serde(default)]
+    pub today_checkin_count: u8,
     /// 下次计划签到时间 (Unix timestamp)，由调度器生成随机时间
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_checkin_at: Option<i64>,
+    /// 当天剩余的计划签到时间点（升序，next_checkin_at 之后依次执行）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_checkin_times: Vec<i64>,
     /// 最后签到时间
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_checkin_at: Option<i64>,
@@ -38,6 +47,10 @@ pub struct CheckinConfig {
     pub total_quota: i64,
 }
 
+fn default_checkins_per_day() -> u8 {
+    1
+}
+
 impl CheckinConfig {
     /// 获取有效的签到时间范围 (start_hour, end_hour)
     /// start < end 时返回实际范围，否则返回全天 (0, 23)
@@ -57,7 +70,10 @@ impl Default for CheckinConfig {
             endpoint: "/api/user/checkin".to_string(),
             checkin_hour_start: 0,
             checkin_hour_end: 0,
+            checkins_per_day: default_checkins_per_day(),
+            today_checkin_count: 0,
             next_checkin_at: None,
+            pending_checkin_times: Vec::new(),
             last_checkin_at: None,
             last_checkin_status: None,
             last_checkin_message: None,
@@ -105,6 +121,13 @@ pub struct ProviderStore {
     pub providers: Vec<Provider>,
     /// 最后更新时间
     pub updated_at: i64,
+    /// 自动签到全局总开关（关闭时忽略各供应商的签到开关）
+    #[serde(default = "default_checkin_enabled")]
+    pub checkin_enabled: bool,
+}
+
+fn default_checkin_enabled() -> bool {
+    true
 }
 
 impl Default for ProviderStore {
@@ -126,6 +149,7 @@ impl Default for ProviderStore {
                 checkin_config: None,
             }],
             updated_at: now,
+            checkin_enabled: true,
         }
     }
 }
@@ -142,6 +166,12 @@ mod tests {
         assert_eq!(store.providers[0].id, "duckcoding");
         assert_eq!(store.providers[0].name, "DuckCoding");
         assert!(store.providers[0].is_default);
+        assert!(store.checkin_enabled);
+
+        // 旧版本数据缺少总开关字段时默认开启
+        let legacy: ProviderStore =
+            serde_json::from_str(r#"{"version":1,"providers":[],"updated_at":0}"#).unwrap();
+        assert!(legacy.checkin_enabled);
     }
 
     #[test]
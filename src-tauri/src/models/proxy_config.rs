@@ -1,8 +1,10 @@
 //! 透明代理配置数据模型
 
+use crate::utils::mask_secret;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
 /// 单个工具的透明代理配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +40,139 @@ pub struct ToolProxyConfig {
     /// Tavily API Key（用于本地搜索，可选，无则降级 DuckDuckGo）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tavily_api_key: Option<String>,
+    /// 跳过上游 TLS 证书验证（仅用于自签名证书的私有上游，存在中间人攻击风险）
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+    /// 最大并发请求数（None 表示不限制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<usize>,
+    /// 会话优先级（会话备注标签或显示 ID -> 优先级，数值越大越优先，仅在并发受限时生效）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub session_priorities: HashMap<String, u8>,
+    /// 上游失败最大重试次数（仅连接错误与 502/503/504 响应）
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// 重试基础退避间隔（毫秒，按 2 的幂次递增）
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// 上游完全不可用时返回降级响应（默认关闭）
+    #[serde(default)]
+    pub fallback_response_enabled: bool,
+    /// 降级响应提示文本（None 时使用默认提示）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+    /// 请求体与非流式响应体的大小上限（字节，SSE 日志收集同样以此为上限）
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: u64,
+    /// 请求体缺少 max_tokens 时注入的默认值（None 表示不注入）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_max_tokens: Option<u32>,
+    /// 上游为 OpenAI 兼容接口时，将 Anthropic Messages 请求/响应与 Chat Completions 互转（仅 Claude Code）
+    #[serde(default)]
+    pub openai_compat_enabled: bool,
+    /// 流式响应内容过滤规则（按顺序对 SSE 文本 delta 做正则替换）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_filters: Vec<ContentFilterRule>,
+    /// 额外剥离的客户端 header（如 cookie，大小写不敏感）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub header_blocklist: Vec<String>,
+    /// 设置后仅透传列出的客户端 header（白名单模式，None 表示透传全部）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_passthrough_override: Option<Vec<String>>,
+    /// 按模型的每日调用次数上限（模型名 -> 次数，超限拒绝该模型请求，跨天重置）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_daily_limits: HashMap<String, u32>,
+    /// 上游请求超时（秒，非流式请求从发送到读完响应体的总时长，超时返回 504）
+    #[serde(default = "default_upstream_timeout_secs")]
+    pub upstream_timeout_secs: u64,
+    /// SSE 流空闲超时（秒，等待响应头或两次数据之间的最长间隔，超时后结束流）
+    #[serde(default = "default_stream_idle_timeout_secs")]
+    pub stream_idle_timeout_secs: u64,
+    /// 上游连接池每个主机保留的最大空闲连接数（None 表示不限制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// 上游连接池空闲连接的保留时长（秒，默认 90）
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    /// 是否转发 count_tokens 请求到上游（默认拦截并返回 403）
+    #[serde(default)]
+    pub allow_count_tokens: bool,
+    /// 备用上游（主上游连接失败或返回 5xx 时按顺序故障转移）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upstreams: Vec<Upstream>,
+    /// 上游返回过载错误（529 / overloaded_error）时是否按映射降级模型重试一次
+    #[serde(default)]
+    pub model_downgrade_enabled: bool,
+    /// 模型降级映射（原模型 -> 降级模型，如 opus -> sonnet）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_downgrades: HashMap<String, String>,
+    /// 慢请求阈值（毫秒，耗时超过阈值的请求按采样率保存调试样本，None 表示关闭）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_request_threshold_ms: Option<u64>,
+    /// 慢请求采样率（0-1，默认 1 即全部保存）
+    #[serde(default = "default_slow_capture_sample_rate")]
+    pub slow_capture_sample_rate: f64,
+    /// 是否对超大请求体 gzip 压缩后发往上游（仅上游通过 Accept-Encoding 声明支持时生效）
+    #[serde(default)]
+    pub request_compression_enabled: bool,
+    /// 触发请求体压缩的最小大小（字节，默认 512KB）
+    #[serde(default = "default_request_compression_min_bytes")]
+    pub request_compression_min_bytes: usize,
+    /// 响应状态码映射（上游状态码 -> 返回给客户端的状态码，如非标准 4xx -> 429）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub status_code_mappings: HashMap<u16, u16>,
+}
+
+/// 备用上游
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Upstream {
+    /// 名称（命中该上游时作为日志的 config_name，为空时使用上游 host）
+    #[serde(default)]
+    pub name: String,
+    pub base_url: String,
+    pub api_key: String,
+}
+
+/// 内容过滤规则（正则替换）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentFilterRule {
+    /// 匹配的正则表达式
+    pub pattern: String,
+    /// 替换文本（支持 `$1` 等捕获组引用）
+    #[serde(default)]
+    pub replacement: String,
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    300
+}
+
+fn default_max_body_bytes() -> u64 {
+    50 * 1024 * 1024
+}
+
+fn default_upstream_timeout_secs() -> u64 {
+    60
+}
+
+fn default_stream_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    90
+}
+
+fn default_slow_capture_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_request_compression_min_bytes() -> usize {
+    512 * 1024
 }
 
 impl ToolProxyConfig {
@@ -58,6 +193,33 @@ impl ToolProxyConfig {
             original_amp_settings: None,
             original_amp_secrets: None,
             tavily_api_key: None,
+            danger_accept_invalid_certs: false,
+            max_concurrent_requests: None,
+            session_priorities: HashMap::new(),
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            fallback_response_enabled: false,
+            fallback_message: None,
+            max_body_bytes: default_max_body_bytes(),
+            default_max_tokens: None,
+            openai_compat_enabled: false,
+            response_filters: Vec::new(),
+            header_blocklist: Vec::new(),
+            header_passthrough_override: None,
+            model_daily_limits: HashMap::new(),
+            upstream_timeout_secs: default_upstream_timeout_secs(),
+            stream_idle_timeout_secs: default_stream_idle_timeout_secs(),
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            allow_count_tokens: false,
+            upstreams: Vec::new(),
+            model_downgrade_enabled: false,
+            model_downgrades: HashMap::new(),
+            slow_request_threshold_ms: None,
+            slow_capture_sample_rate: default_slow_capture_sample_rate(),
+            request_compression_enabled: false,
+            request_compression_min_bytes: default_request_compression_min_bytes(),
+            status_code_mappings: HashMap::new(),
         }
     }
 
@@ -71,6 +233,38 @@ impl ToolProxyConfig {
             _ => 8787,
         }
     }
+
+    /// 返回密钥脱敏后的副本（用于排障展示）
+    pub fn masked(&self) -> Self {
+        let mut config = self.clone();
+        for key in [
+            &mut config.local_api_key,
+            &mut config.real_api_key,
+            &mut config.tavily_api_key,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *key = mask_secret(key);
+        }
+        for upstream in &mut config.upstreams {
+            upstream.api_key = mask_secret(&upstream.api_key);
+        }
+        if let Some(secrets) = config.original_amp_secrets.as_mut() {
+            mask_json_strings(secrets);
+        }
+        config
+    }
+}
+
+/// 递归脱敏 JSON 中的所有字符串值
+fn mask_json_strings(value: &mut Value) {
+    match value {
+        Value::String(s) => *s = mask_secret(s),
+        Value::Array(items) => items.iter_mut().for_each(mask_json_strings),
+        Value::Object(map) => map.values_mut().for_each(mask_json_strings),
+        _ => {}
+    }
 }
 
 /// proxy.json 顶层结构
@@ -150,3 +344,26 @@ impl Default for ProxyStore {
 pub struct ProxyMetadata {
     pub last_updated: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_hides_upstream_api_keys() {
+        let mut config = ToolProxyConfig::new(8787);
+        config.real_api_key = Some("sk-real-1234567890".to_string());
+        config.upstreams = vec![Upstream {
+            name: "backup".to_string(),
+            base_url: "https://backup.example.com".to_string(),
+            api_key: "sk-backup-1234567890".to_string(),
+        }];
+
+        let masked = config.masked();
+        assert_eq!(masked.real_api_key.as_deref(), Some("sk-r...7890"));
+        assert_eq!(masked.upstreams[0].api_key, "sk-b...7890");
+        assert_eq!(masked.upstreams[0].base_url, "https://backup.example.com");
+        // 原配置不受影响
+        assert_eq!(config.upstreams[0].api_key, "sk-backup-1234567890");
+    }
+}
@@ -100,6 +100,51 @@ pub struct TokenLog {
     /// 使用的价格模板ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pricing_template_id: Option<String>,
+
+    /// 结束原因：end_turn, max_tokens, tool_use, stop_sequence 等（失败请求为None）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+
+    /// 请求体原始字节数
+    #[serde(default)]
+    pub request_bytes: i64,
+
+    /// 响应体原始字节数（SSE 为收集到的流数据总量）
+    #[serde(default)]
+    pub response_bytes: i64,
+
+    /// 请求中的图片数量（多模态请求）
+    #[serde(default)]
+    pub image_count: i64,
+
+    /// 请求中的图片字节数（base64 解码后）
+    #[serde(default)]
+    pub image_bytes: i64,
+
+    /// 实际使用的上游标识（base_url 的 host[:port]）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+
+    /// 上游过载降级重试前的原始模型（未降级时为None）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downgraded_from: Option<String>,
+
+    /// 请求分类（coding / chat / embedding / image / other，按 endpoint 与模型自动判断）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    /// 首字节延迟（毫秒，从发起上游请求到收到响应头；上游失败时为None）
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttfb_ms: Option<i64>,
+
+    /// 成本是否为兜底估算（模型未在价格模板中定义，按同 provider 默认档位计价）
+    #[serde(default)]
+    pub is_estimated: bool,
 }
 
 impl TokenLog {
@@ -159,9 +204,69 @@ impl TokenLog {
             reasoning_price,
             total_cost,
             pricing_template_id,
+            stop_reason: None,
+            request_bytes: 0,
+            response_bytes: 0,
+            image_count: 0,
+            image_bytes: 0,
+            upstream: None,
+            downgraded_from: None,
+            category: None,
+            ttfb_ms: None,
+            is_estimated: false,
         }
     }
 
+    /// 设置结束原因
+    pub fn with_stop_reason(mut self, stop_reason: Option<String>) -> Self {
+        self.stop_reason = stop_reason;
+        self
+    }
+
+    /// 设置请求/响应体字节数
+    pub fn with_body_bytes(mut self, request_bytes: i64, response_bytes: i64) -> Self {
+        self.request_bytes = request_bytes;
+        self.response_bytes = response_bytes;
+        self
+    }
+
+    /// 设置请求中的图片数量与字节数
+    pub fn with_image_stats(mut self, image_count: i64, image_bytes: i64) -> Self {
+        self.image_count = image_count;
+        self.image_bytes = image_bytes;
+        self
+    }
+
+    /// 设置实际使用的上游标识
+    pub fn with_upstream(mut self, upstream: Option<String>) -> Self {
+        self.upstream = upstream;
+        self
+    }
+
+    /// 设置降级前的原始模型
+    pub fn with_downgraded_from(mut self, downgraded_from: Option<String>) -> Self {
+        self.downgraded_from = downgraded_from;
+        self
+    }
+
+    /// 设置请求分类
+    pub fn with_category(mut self, category: Option<String>) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// 设置首字节延迟
+    pub fn with_ttfb_ms(mut self, ttfb_ms: Option<i64>) -> Self {
+        self.ttfb_ms = ttfb_ms;
+        self
+    }
+
+    /// 设置成本是否为兜底估算
+    pub fn with_estimated(mut self, is_estimated: bool) -> Self {
+        self.is_estimated = is_estimated;
+        self
+    }
+
     /// 计算总Token数量
     pub fn total_tokens(&self) -> i64 {
         self.input_tokens + self.output_tokens
@@ -172,12 +277,53 @@ impl TokenLog {
         self.cache_creation_tokens + self.cache_read_tokens
     }
 
+    /// 计算总传输字节数
+    pub fn total_bytes(&self) -> i64 {
+        self.request_bytes + self.response_bytes
+    }
+
     /// 是否成功
     pub fn is_success(&self) -> bool {
         self.request_status == "success"
     }
 }
 
+#[cfg(any(test, feature = "test-support"))]
+impl TokenLog {
+    /// 测试日志：claude-3 成功 JSON 请求（100 输入 / 50 输出，无价格）
+    ///
+    /// 其余字段通过结构体更新语法按需覆盖：`TokenLog { model: .., ..TokenLog::fixture(..) }`
+    pub fn fixture(tool_type: &str, timestamp: i64) -> Self {
+        Self::new(
+            tool_type.to_string(),
+            timestamp,
+            "127.0.0.1".to_string(),
+            "session".to_string(),
+            "default".to_string(),
+            "claude-3".to_string(),
+            None,
+            100,
+            50,
+            0,
+            0,
+            0,
+            0,
+            "success".to_string(),
+            "json".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            0.0,
+            None,
+        )
+    }
+}
+
 /// 会话统计数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionStats {
@@ -199,6 +345,10 @@ pub struct SessionStats {
 
     /// 请求总数
     pub request_count: i64,
+
+    /// 流式响应的生成速率（输出 Token / 响应时长，tokens/sec；无有效流式记录时为 None）
+    #[serde(default)]
+    pub tokens_per_second: Option<f64>,
 }
 
 impl SessionStats {
@@ -211,9 +361,16 @@ impl SessionStats {
             total_cache_read: 0,
             total_reasoning: 0,
             request_count: 0,
+            tokens_per_second: None,
         }
     }
 
+    /// 按输出 Token 与响应时长（毫秒）计算生成速率
+    pub fn output_rate(output_tokens: i64, duration_ms: i64) -> Option<f64> {
+        (output_tokens > 0 && duration_ms > 0)
+            .then(|| output_tokens as f64 * 1000.0 / duration_ms as f64)
+    }
+
     /// 计算总Token数量
     pub fn total_tokens(&self) -> i64 {
         self.total_input + self.total_output
@@ -248,6 +405,10 @@ pub struct TokenStatsQuery {
 
     /// 分页：每页大小
     pub page_size: u32,
+
+    /// 仅返回失败请求（request_status 为 failed 或 error_type 为 parse_error）
+    #[serde(default)]
+    pub errors_only: bool,
 }
 
 impl Default for TokenStatsQuery {
@@ -260,10 +421,44 @@ impl Default for TokenStatsQuery {
             end_time: None,
             page: 0,
             page_size: 20,
+            errors_only: false,
         }
     }
 }
 
+/// Token 日志导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// 逗号分隔（可直接用 Excel 打开）
+    Csv,
+    /// JSON Lines（每行一个 JSON 对象）
+    Jsonl,
+}
+
+/// Token 日志导入格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// 带表头的 CSV
+    Csv,
+    /// JSON 数组或 JSON Lines
+    Json,
+}
+
+/// Token 日志导入结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// 解析出的记录总数（含无效记录）
+    pub total: usize,
+    /// 成功导入条数
+    pub imported: usize,
+    /// 因重复跳过的条数
+    pub duplicates: usize,
+    /// 无法解析而跳过的条数
+    pub invalid: usize,
+}
+
 /// 分页查询结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenLogsPage {
@@ -321,6 +516,12 @@ mod tests {
         assert_eq!(log.response_time_ms, Some(1500));
         assert_eq!(log.total_cost, 0.011235);
         assert_eq!(log.pricing_template_id, Some("builtin_claude".to_string()));
+        assert_eq!(log.total_bytes(), 0);
+
+        let log = log.with_body_bytes(2048, 512);
+        assert_eq!(log.request_bytes, 2048);
+        assert_eq!(log.response_bytes, 512);
+        assert_eq!(log.total_bytes(), 2560);
     }
 
     #[test]
@@ -332,12 +533,21 @@ mod tests {
             total_cache_read: 2000,
             total_reasoning: 0, // 新增字段
             request_count: 10,
+            tokens_per_second: None,
         };
 
         assert_eq!(stats.total_tokens(), 15000);
         assert_eq!(stats.total_cache_tokens(), 3000);
     }
 
+    #[test]
+    fn test_output_rate() {
+        assert_eq!(SessionStats::output_rate(500, 10_000), Some(50.0));
+        assert_eq!(SessionStats::output_rate(150, 1_500), Some(100.0));
+        assert_eq!(SessionStats::output_rate(500, 0), None);
+        assert_eq!(SessionStats::output_rate(0, 1_000), None);
+    }
+
     #[test]
     fn test_query_default() {
         let query = TokenStatsQuery::default();
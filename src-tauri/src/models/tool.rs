@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// 工具状态
@@ -41,6 +42,16 @@ pub enum InstallMethod {
     Other,    // 其他（不支持APP内快捷更新）
 }
 
+/// 支持全局安装的 Node 包管理器（安装、卸载、更新命令见 `services::tool::package_manager`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
 impl Tool {
     /// 获取所有工具
     pub fn all() -> Vec<Tool> {
@@ -257,6 +268,9 @@ pub struct ToolInstance {
     pub tool_type: ToolType,
     /// 安装方式（npm, brew, official）- 用于自动选择更新方法
     pub install_method: Option<InstallMethod>,
+    /// Node 包管理器（安装方式为 Npm 时生效，None 视为 npm）- 用于选择卸载/更新/回滚命令
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<PackageManager>,
     /// 是否已安装
     pub installed: bool,
     /// 版本号
@@ -271,6 +285,9 @@ pub struct ToolInstance {
     pub ssh_config: Option<SSHConfig>,
     /// 是否为内置实例（内置的本地工具实例）
     pub is_builtin: bool,
+    /// 自定义环境变量（执行 --version / 更新命令时注入）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_env: HashMap<String, String>,
     /// 创建时间（Unix timestamp）
     pub created_at: i64,
     /// 更新时间（Unix timestamp）
@@ -293,6 +310,7 @@ impl ToolInstance {
             tool_name: tool.name.clone(),
             tool_type: ToolType::Local,
             install_method: None, // 需要后续检测
+            package_manager: None,
             installed,
             version,
             install_path,
@@ -300,6 +318,7 @@ impl ToolInstance {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: true,
+            custom_env: Default::default(),
             created_at: now,
             updated_at: now,
         }
@@ -325,6 +344,7 @@ impl ToolInstance {
             tool_name,
             tool_type: ToolType::WSL,
             install_method: None, // WSL 环境通常是 npm
+            package_manager: None,
             installed,
             version,
             install_path,
@@ -332,6 +352,7 @@ impl ToolInstance {
             wsl_distro: Some(distro_name),
             ssh_config: None,
             is_builtin: false,
+            custom_env: Default::default(),
             created_at: now,
             updated_at: now,
         }
@@ -359,6 +380,7 @@ impl ToolInstance {
             tool_name,
             tool_type: ToolType::SSH,
             install_method: None, // SSH 远程环境
+            package_manager: None,
             installed,
             version,
             install_path,
@@ -366,6 +388,7 @@ impl ToolInstance {
             wsl_distro: None,
             ssh_config: Some(ssh_config),
             is_builtin: false,
+            custom_env: Default::default(),
             created_at: now,
             updated_at: now,
         }
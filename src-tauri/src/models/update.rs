@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// 更新信息
@@ -116,4 +117,8 @@ pub struct UpdateUrls {
 
     // 通用包（如果有的话）
     pub universal: Option<String>, // 跨平台通用包
+
+    /// 安装包 SHA256（安装包 URL -> 十六进制校验和），用于校验下载结果与复用本地缓存
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sha256: HashMap<String, String>,
 }
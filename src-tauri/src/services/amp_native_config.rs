@@ -7,6 +7,7 @@
 //! Windows: %USERPROFILE%\.config\amp\... 和 %USERPROFILE%\.local\share\amp\...
 
 use crate::data::DataManager;
+use crate::services::audit_log;
 use anyhow::{anyhow, Result};
 use serde_json::Value;
 use std::path::PathBuf;
@@ -123,6 +124,7 @@ pub fn apply_proxy_config(proxy_url: &str, local_api_key: &str) -> Result<()> {
     secrets_obj.insert(key_name, Value::String(local_api_key.to_string()));
     jm.write(&secrets_path, &secrets)?;
 
+    audit_log::record("apply_proxy_config", "amp-code", None);
     tracing::info!(
         proxy_url = %proxy_url,
         "已应用 AMP Code 代理配置"
@@ -157,6 +159,7 @@ pub fn restore_amp_config(backup: &AmpConfigBackup) -> Result<()> {
         tracing::debug!("已删除 secrets.json（原本不存在）");
     }
 
+    audit_log::record("restore_config", "amp-code", None);
     tracing::info!("已完整还原 AMP Code 配置");
 
     Ok(())
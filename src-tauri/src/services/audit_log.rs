@@ -0,0 +1,210 @@
+// Audit Log
+//
+// 配置变更审计日志：记录 Profile 保存/激活/删除等写操作，
+// 以 JSON Lines 追加到 `~/.duckcoding/audit.jsonl`。
+// 由执行切换的服务层（ProfileManager、AMP 原生配置）记录，界面、托盘菜单与代理启停的切换都会留痕
+
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 审计日志文件名
+pub const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+/// 从文件末尾向前读取时每次读取的块大小
+const READ_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// 单条审计记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// 操作名称，如 "configure_api" / "switch_profile" / "delete_profile"
+    pub action: String,
+    pub tool_id: String,
+    pub profile: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(action: &str, tool_id: &str, profile: Option<&str>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            action: action.to_string(),
+            tool_id: tool_id.to_string(),
+            profile: profile.map(str::to_string),
+        }
+    }
+}
+
+/// 默认审计日志路径
+pub fn audit_log_path() -> Result<PathBuf> {
+    let dir = config_dir().map_err(|e| anyhow::anyhow!(e))?;
+    Ok(dir.join(AUDIT_LOG_FILE))
+}
+
+/// 追加一条审计记录到指定文件
+pub fn append_entry(path: &Path, entry: &AuditEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("创建审计日志目录失败")?;
+    }
+    let line = serde_json::to_string(entry).context("序列化审计记录失败")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("打开审计日志失败: {}", path.display()))?;
+    writeln!(file, "{line}").context("写入审计日志失败")?;
+    Ok(())
+}
+
+/// 读取最近的 `limit` 条审计记录（按时间倒序，跳过无法解析的行）
+///
+/// 从文件末尾按块向前读取，取够 `limit` 条即停止，不随日志增长把整个文件读入内存
+pub fn read_recent(path: &Path, limit: usize) -> Result<Vec<AuditEntry>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("读取审计日志失败: {}", path.display())),
+    };
+    let mut pos = file.metadata().context("读取审计日志失败")?.len();
+    let mut entries = Vec::new();
+    // 上一块开头尚不完整的行，与更早的数据拼接后再解析
+    let mut carry = Vec::new();
+
+    while pos > 0 && entries.len() < limit {
+        let size = READ_BLOCK_SIZE.min(pos);
+        pos -= size;
+        let mut block = vec![0; size as usize];
+        file.seek(SeekFrom::Start(pos))
+            .and_then(|_| file.read_exact(&mut block))
+            .context("读取审计日志失败")?;
+        block.extend_from_slice(&carry);
+
+        let complete_from = if pos == 0 {
+            0
+        } else {
+            match block.iter().position(|&b| b == b'\n') {
+                Some(index) => index + 1,
+                None => {
+                    carry = block;
+                    continue;
+                }
+            }
+        };
+        entries.extend(
+            block[complete_from..]
+                .split(|&b| b == b'\n')
+                .rev()
+                .filter_map(|line| serde_json::from_slice::<AuditEntry>(line).ok())
+                .take(limit - entries.len()),
+        );
+        block.truncate(complete_from);
+        carry = block;
+    }
+    Ok(entries)
+}
+
+/// 记录一次配置变更到默认审计日志（失败仅告警，不影响业务操作）
+pub fn record(action: &str, tool_id: &str, profile: Option<&str>) {
+    match audit_log_path() {
+        Ok(path) => record_to(&path, action, tool_id, profile),
+        Err(e) => {
+            tracing::warn!(action = %action, tool_id = %tool_id, error = ?e, "写入审计日志失败")
+        }
+    }
+}
+
+/// 记录一次配置变更到指定审计日志（失败仅告警，不影响业务操作）
+pub fn record_to(path: &Path, action: &str, tool_id: &str, profile: Option<&str>) {
+    let entry = AuditEntry::new(action, tool_id, profile);
+    if let Err(e) = append_entry(path, &entry) {
+        tracing::warn!(action = %action, tool_id = %tool_id, error = ?e, "写入审计日志失败");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_read_recent() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+
+        append_entry(
+            &path,
+            &AuditEntry::new("configure_api", "claude-code", Some("work")),
+        )
+        .unwrap();
+        append_entry(
+            &path,
+            &AuditEntry::new("switch_profile", "claude-code", Some("home")),
+        )
+        .unwrap();
+        append_entry(
+            &path,
+            &AuditEntry::new("delete_profile", "codex", Some("old")),
+        )
+        .unwrap();
+
+        let entries = read_recent(&path, 2).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, "delete_profile");
+        assert_eq!(entries[0].tool_id, "codex");
+        assert_eq!(entries[1].action, "switch_profile");
+        assert_eq!(entries[1].profile.as_deref(), Some("home"));
+
+        assert_eq!(read_recent(&path, 10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_read_recent_missing_file_and_bad_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        assert!(read_recent(&path, 10).unwrap().is_empty());
+
+        append_entry(&path, &AuditEntry::new("configure_api", "gemini-cli", None)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let entries = read_recent(&path, 10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].profile, None);
+    }
+
+    #[test]
+    fn test_read_recent_across_blocks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        // 记录总大小超过多个读取块，行会跨越块边界
+        let long_profile = "p".repeat(1000);
+        let total = 3 * READ_BLOCK_SIZE as usize / 1000;
+        for i in 0..total {
+            let tool = format!("tool-{i}");
+            append_entry(
+                &path,
+                &AuditEntry::new("switch_profile", &tool, Some(&long_profile)),
+            )
+            .unwrap();
+        }
+
+        let recent = read_recent(&path, 5).unwrap();
+        let tools: Vec<String> = recent.iter().map(|e| e.tool_id.clone()).collect();
+        let expected: Vec<String> = (total - 5..total)
+            .rev()
+            .map(|i| format!("tool-{i}"))
+            .collect();
+        assert_eq!(tools, expected);
+
+        let all = read_recent(&path, usize::MAX).unwrap();
+        assert_eq!(all.len(), total);
+        assert_eq!(all.last().unwrap().tool_id, "tool-0");
+        assert!(all
+            .iter()
+            .all(|e| e.profile.as_deref() == Some(long_profile.as_str())));
+    }
+}
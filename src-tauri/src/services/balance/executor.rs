@@ -0,0 +1,512 @@
+// Balance Executor - 余额监控执行引擎
+//
+// 按每个配置的 interval_sec 周期性请求 endpoint，执行 extractor_script 提取余额，
+// 结果缓存在内存中并通过 `balance-updated` 事件推送给前端；
+// 余额跌破告警阈值时额外发出 `balance-low-alert` 事件
+
+use crate::core::http::get_global_client;
+use crate::models::BalanceConfig;
+use crate::services::balance::BalanceManager;
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::RwLock;
+
+/// 余额更新事件名
+pub const BALANCE_UPDATED_EVENT: &str = "balance-updated";
+
+/// 低余额告警事件名
+pub const BALANCE_LOW_ALERT_EVENT: &str = "balance-low-alert";
+
+/// 未配置 timeout_ms 时的默认请求超时（毫秒）
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/// 调度检查周期（秒）
+const TICK_SECS: u64 = 5;
+
+/// 提取器脚本的循环迭代上限（防止死循环阻塞线程）
+const SCRIPT_LOOP_LIMIT: u64 = 1_000_000;
+
+/// 提取器返回的余额信息（字段与前端 BalanceResult 一致）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceResult {
+    pub plan_name: Option<String>,
+    pub remaining: Option<f64>,
+    pub used: Option<f64>,
+    pub total: Option<f64>,
+    pub unit: String,
+    pub expires_at: Option<String>,
+}
+
+/// 单个配置最近一次查询的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub config_id: String,
+    /// 最近一次成功提取的余额（查询失败时保留上次结果）
+    pub result: Option<BalanceResult>,
+    /// 最近一次查询的错误（成功时为 None）
+    pub error: Option<String>,
+    /// 最近一次查询时间（Unix 时间戳，毫秒）
+    pub fetched_at: i64,
+}
+
+/// 低余额告警事件载荷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceLowAlert {
+    pub config_id: String,
+    pub name: String,
+    pub remaining: f64,
+    pub threshold: f64,
+}
+
+/// 检查本次查询结果是否需要低余额告警（跨越阈值去抖由 BalanceManager 持久化）
+pub fn check_low_balance(
+    config: &BalanceConfig,
+    snapshot: &BalanceSnapshot,
+) -> Option<BalanceLowAlert> {
+    let threshold = config.alert_threshold?;
+    // 查询失败时快照保留的是上次结果，不参与告警判断
+    if snapshot.error.is_some() {
+        return None;
+    }
+    let remaining = snapshot.result.as_ref()?.remaining?;
+
+    let should_alert = BalanceManager::new()
+        .and_then(|m| m.record_balance(&config.id, remaining, Some(threshold)))
+        .unwrap_or_else(|e| {
+            tracing::error!(config_id = %config.id, error = ?e, "记录余额告警状态失败");
+            false
+        });
+
+    should_alert.then(|| BalanceLowAlert {
+        config_id: config.id.clone(),
+        name: config.name.clone(),
+        remaining,
+        threshold,
+    })
+}
+
+/// 推送余额更新事件，必要时推送低余额告警
+pub fn emit_balance_events(
+    app_handle: &AppHandle,
+    config: &BalanceConfig,
+    snapshot: &BalanceSnapshot,
+) {
+    if let Err(e) = app_handle.emit(BALANCE_UPDATED_EVENT, snapshot) {
+        tracing::error!(error = ?e, "发送余额更新事件失败");
+    }
+    if let Some(alert) = check_low_balance(config, snapshot) {
+        tracing::info!(
+            config_id = %alert.config_id,
+            remaining = alert.remaining,
+            threshold = alert.threshold,
+            "余额低于告警阈值"
+        );
+        if let Err(e) = app_handle.emit(BALANCE_LOW_ALERT_EVENT, &alert) {
+            tracing::error!(error = ?e, "发送低余额告警事件失败");
+        }
+    }
+}
+
+/// 执行提取器脚本
+///
+/// 脚本需定义 `extractor(response)` 函数，与前端执行方式一致
+pub fn run_extractor(script: &str, response: &Value) -> Result<BalanceResult> {
+    // 返回值在脚本内序列化为 JSON 字符串，由 serde_json 解析（undefined 字段自然被忽略）
+    let source = format!(
+        "(function (response) {{\n'use strict';\n{script}\nreturn JSON.stringify(extractor(response));\n}})({response});"
+    );
+
+    let mut context = boa_engine::Context::default();
+    context
+        .runtime_limits_mut()
+        .set_loop_iteration_limit(SCRIPT_LOOP_LIMIT);
+
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&source))
+        .map_err(|e| anyhow!("提取器执行失败: {e}"))?;
+    let json = value
+        .as_string()
+        .map(|s| s.to_std_string_escaped())
+        .ok_or_else(|| anyhow!("提取器必须返回一个对象"))?;
+
+    normalize_result(&serde_json::from_str(&json).context("提取器返回值无法解析")?)
+}
+
+/// 标准化提取器返回值（非数字的额度字段视为缺失，单位默认 USD）
+fn normalize_result(value: &Value) -> Result<BalanceResult> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| anyhow!("提取器必须返回一个对象"))?;
+    let number = |key: &str| obj.get(key).and_then(|v| v.as_f64());
+    let string = |key: &str| {
+        obj.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+
+    Ok(BalanceResult {
+        plan_name: string("planName"),
+        remaining: number("remaining"),
+        used: number("used"),
+        total: number("total"),
+        unit: string("unit").unwrap_or_else(|| "USD".to_string()),
+        expires_at: string("expiresAt"),
+    })
+}
+
+/// 请求配置的端点并提取余额
+pub async fn fetch_balance(client: &Client, config: &BalanceConfig) -> Result<BalanceResult> {
+    let mut request = match config.method.to_uppercase().as_str() {
+        "GET" => client.get(&config.endpoint),
+        "POST" => client.post(&config.endpoint),
+        other => anyhow::bail!("不支持的 HTTP 方法: {other}，仅支持 GET 和 POST"),
+    };
+
+    for (key, value) in config.static_headers.iter().flatten() {
+        request = request.header(key, value);
+    }
+    if let Some(api_key) = config.api_key.as_deref().filter(|k| !k.is_empty()) {
+        request = request.header("Authorization", format!("Bearer {api_key}"));
+    }
+    let timeout_ms = config.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    request = request.timeout(Duration::from_millis(timeout_ms));
+
+    let response = request.send().await.map_err(|e| {
+        if e.is_timeout() {
+            anyhow!("请求超时（{timeout_ms}ms）")
+        } else {
+            anyhow!("请求 API 失败: {e}")
+        }
+    })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        anyhow::bail!("API 请求失败 ({status}): {error_text}");
+    }
+
+    let data: Value = response.json().await.context("解析响应 JSON 失败")?;
+
+    // boa Context 不是 Send，放到阻塞线程中执行
+    let script = config.extractor_script.clone();
+    tokio::task::spawn_blocking(move || run_extractor(&script, &data))
+        .await
+        .context("提取器任务异常退出")?
+}
+
+/// 余额监控执行器
+pub struct BalanceExecutor {
+    snapshots: Arc<RwLock<HashMap<String, BalanceSnapshot>>>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl Default for BalanceExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BalanceExecutor {
+    pub fn new() -> Self {
+        Self {
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// 获取所有配置的最近查询结果
+    pub async fn snapshots(&self) -> Vec<BalanceSnapshot> {
+        self.snapshots.read().await.values().cloned().collect()
+    }
+
+    /// 立即查询一次并缓存结果
+    pub async fn refresh(&self, config: &BalanceConfig) -> BalanceSnapshot {
+        Self::refresh_with(&self.snapshots, config).await
+    }
+
+    async fn refresh_with(
+        snapshots: &RwLock<HashMap<String, BalanceSnapshot>>,
+        config: &BalanceConfig,
+    ) -> BalanceSnapshot {
+        let outcome = match get_global_client() {
+            Ok(client) => fetch_balance(&client, config).await,
+            Err(e) => Err(anyhow!("创建 HTTP 客户端失败: {e}")),
+        };
+        Self::store_outcome(snapshots, &config.id, outcome).await
+    }
+
+    /// 写入查询结果（失败时保留上次成功的余额）
+    async fn store_outcome(
+        snapshots: &RwLock<HashMap<String, BalanceSnapshot>>,
+        config_id: &str,
+        outcome: Result<BalanceResult>,
+    ) -> BalanceSnapshot {
+        let mut snapshots = snapshots.write().await;
+        let previous = snapshots.get(config_id).and_then(|s| s.result.clone());
+        let snapshot = match outcome {
+            Ok(result) => BalanceSnapshot {
+                config_id: config_id.to_string(),
+                result: Some(result),
+                error: None,
+                fetched_at: chrono::Utc::now().timestamp_millis(),
+            },
+            Err(e) => {
+                tracing::warn!(config_id = %config_id, error = %e, "余额查询失败");
+                BalanceSnapshot {
+                    config_id: config_id.to_string(),
+                    result: previous,
+                    error: Some(e.to_string()),
+                    fetched_at: chrono::Utc::now().timestamp_millis(),
+                }
+            }
+        };
+        snapshots.insert(config_id.to_string(), snapshot.clone());
+        snapshot
+    }
+
+    /// 配置是否到期需要刷新（未设置间隔或未保存 API Key 的配置由前端手动刷新）
+    fn is_due(config: &BalanceConfig, last: Option<&BalanceSnapshot>, now_ms: i64) -> bool {
+        let interval_sec = match config.interval_sec {
+            Some(sec) if sec > 0 => sec,
+            _ => return false,
+        };
+        if config.api_key.as_deref().unwrap_or("").is_empty() {
+            return false;
+        }
+        last.is_none_or(|s| now_ms - s.fetched_at >= i64::from(interval_sec) * 1000)
+    }
+
+    /// 启动后台执行任务（每次检查时重新加载配置，增删改无需重启）
+    pub async fn start(&self, app_handle: AppHandle) {
+        let mut running = self.running.write().await;
+        if *running {
+            tracing::warn!("余额监控任务已在运行");
+            return;
+        }
+        *running = true;
+        drop(running);
+
+        let snapshots = self.snapshots.clone();
+        let running = self.running.clone();
+
+        tokio::spawn(async move {
+            tracing::info!("余额监控任务已启动");
+            let mut interval = tokio::time::interval(Duration::from_secs(TICK_SECS));
+
+            loop {
+                interval.tick().await;
+
+                if !*running.read().await {
+                    tracing::info!("余额监控任务已停止");
+                    break;
+                }
+
+                let configs = match BalanceManager::new().and_then(|m| m.list_configs()) {
+                    Ok(configs) => configs,
+                    Err(e) => {
+                        tracing::error!(error = ?e, "加载余额监控配置失败");
+                        continue;
+                    }
+                };
+
+                let now_ms = chrono::Utc::now().timestamp_millis();
+                let due: Vec<BalanceConfig> = {
+                    let cached = snapshots.read().await;
+                    configs
+                        .into_iter()
+                        .filter(|c| Self::is_due(c, cached.get(&c.id), now_ms))
+                        .collect()
+                };
+
+                let updates = futures_util::future::join_all(
+                    due.iter()
+                        .map(|config| Self::refresh_with(&snapshots, config)),
+                )
+                .await;
+
+                for (config, snapshot) in due.iter().zip(updates) {
+                    emit_balance_events(&app_handle, config, &snapshot);
+                }
+            }
+        });
+    }
+
+    /// 停止后台执行任务
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockUpstream};
+
+    const EXTRACTOR: &str = r#"const extractor = (response) => ({
+  planName: response.data?.name || 'Unknown',
+  remaining: response.data?.total_available / 500000,
+  used: response.data?.total_used / 500000,
+  unit: 'USD',
+});"#;
+
+    /// mock 余额接口地址
+    fn usage_url(upstream: &MockUpstream) -> String {
+        format!("{}/api/usage/token", upstream.url())
+    }
+
+    fn config(endpoint: String, script: &str, timeout_ms: Option<u64>) -> BalanceConfig {
+        BalanceConfig {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            endpoint,
+            method: "GET".to_string(),
+            static_headers: Some(HashMap::from([(
+                "X-Custom".to_string(),
+                "static".to_string(),
+            )])),
+            extractor_script: script.to_string(),
+            interval_sec: Some(60),
+            timeout_ms,
+            save_api_key: true,
+            api_key: Some("sk-test".to_string()),
+            alert_threshold: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_run_extractor_normalizes_result() {
+        let response = serde_json::json!({
+            "data": { "name": "Pro", "total_available": 1000000, "total_used": 500000 }
+        });
+        let result = run_extractor(EXTRACTOR, &response).unwrap();
+        assert_eq!(result.plan_name.as_deref(), Some("Pro"));
+        assert_eq!(result.remaining, Some(2.0));
+        assert_eq!(result.used, Some(1.0));
+        assert_eq!(result.total, None);
+        assert_eq!(result.unit, "USD");
+
+        // 非对象返回值与脚本异常都返回错误
+        assert!(run_extractor("const extractor = () => 42;", &response).is_err());
+        assert!(run_extractor(
+            "const extractor = () => { throw new Error('boom'); };",
+            &response
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("boom"));
+        // 死循环受迭代上限保护
+        assert!(run_extractor("const extractor = () => { while (true) {} };", &response).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_balance_with_mock_server() {
+        let upstream = MockUpstream::respond(MockResponse::json(
+            "200 OK",
+            r#"{"data":{"name":"Pro","total_available":250000,"total_used":0}}"#,
+        ))
+        .await;
+
+        let url = usage_url(&upstream);
+        let result = fetch_balance(&Client::new(), &config(url, EXTRACTOR, Some(2000)))
+            .await
+            .unwrap();
+        assert_eq!(result.plan_name.as_deref(), Some("Pro"));
+        assert_eq!(result.remaining, Some(0.5));
+
+        // 携带静态请求头与 API Key
+        let request = upstream.requests().remove(0);
+        assert_eq!(request.header("x-custom"), Some("static"));
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_balance_error_paths() {
+        // 非 200 响应
+        let upstream = MockUpstream::respond(MockResponse::json(
+            "401 Unauthorized",
+            r#"{"error":"bad key"}"#,
+        ))
+        .await;
+        let err = fetch_balance(
+            &Client::new(),
+            &config(usage_url(&upstream), EXTRACTOR, Some(2000)),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("401"));
+
+        // 超时
+        let upstream = MockUpstream::respond(
+            MockResponse::json("200 OK", "{}").delayed(Duration::from_millis(500)),
+        )
+        .await;
+        let err = fetch_balance(
+            &Client::new(),
+            &config(usage_url(&upstream), EXTRACTOR, Some(50)),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("超时"));
+
+        // 脚本异常
+        let upstream = MockUpstream::respond(MockResponse::json("200 OK", "{}")).await;
+        let err = fetch_balance(
+            &Client::new(),
+            &config(
+                usage_url(&upstream),
+                "const extractor = (r) => r.data.name;",
+                Some(2000),
+            ),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("提取器执行失败"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_keeps_previous_result() {
+        let executor = BalanceExecutor::new();
+        let ok = normalize_result(&serde_json::json!({ "remaining": 3.5 })).unwrap();
+
+        BalanceExecutor::store_outcome(&executor.snapshots, "a", Ok(ok.clone())).await;
+        let snapshot =
+            BalanceExecutor::store_outcome(&executor.snapshots, "a", Err(anyhow!("HTTP 500")))
+                .await;
+
+        assert_eq!(snapshot.result, Some(ok));
+        assert_eq!(snapshot.error.as_deref(), Some("HTTP 500"));
+        assert_eq!(executor.snapshots().await.len(), 1);
+    }
+
+    #[test]
+    fn test_is_due() {
+        let cfg = config("http://localhost".to_string(), EXTRACTOR, None);
+        assert!(BalanceExecutor::is_due(&cfg, None, 0));
+
+        let last = BalanceSnapshot {
+            config_id: "test".to_string(),
+            result: None,
+            error: None,
+            fetched_at: 1_000,
+        };
+        assert!(!BalanceExecutor::is_due(&cfg, Some(&last), 30_000));
+        assert!(BalanceExecutor::is_due(&cfg, Some(&last), 61_000));
+
+        let mut manual = cfg.clone();
+        manual.interval_sec = Some(0);
+        assert!(!BalanceExecutor::is_due(&manual, None, 0));
+        let mut no_key = cfg;
+        no_key.api_key = None;
+        assert!(!BalanceExecutor::is_due(&no_key, None, 0));
+    }
+}
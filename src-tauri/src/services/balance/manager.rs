@@ -5,6 +5,7 @@
 use crate::data::DataManager;
 use crate::models::{BalanceConfig, BalanceStore};
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// 余额监控管理器
@@ -110,6 +111,7 @@ impl BalanceManager {
         if store.configs.len() == original_len {
             anyhow::bail!("未找到配置: {}", id);
         }
+        store.last_alerted.remove(id);
 
         self.save_store(&store)?;
 
@@ -141,6 +143,7 @@ impl BalanceManager {
         let store = BalanceStore {
             version: 1,
             configs,
+            last_alerted: HashMap::new(),
         };
 
         self.save_store(&store)?;
@@ -148,6 +151,31 @@ impl BalanceManager {
         Ok(())
     }
 
+    /// 记录一次抓取到的余额，返回是否需要发出低余额告警
+    ///
+    /// 仅在余额从阈值以上跌破阈值的那一刻返回 true；
+    /// 余额回升到阈值及以上后清除告警状态，再次跌破时重新告警
+    pub fn record_balance(
+        &self,
+        config_id: &str,
+        remaining: f64,
+        threshold: Option<f64>,
+    ) -> Result<bool> {
+        let mut store = self.load_store()?;
+        let last_alerted = store.last_alerted.get(config_id).copied().unwrap_or(false);
+        let (alerted, should_alert) = next_alert_state(last_alerted, remaining, threshold);
+
+        if alerted != last_alerted {
+            if alerted {
+                store.last_alerted.insert(config_id.to_string(), true);
+            } else {
+                store.last_alerted.remove(config_id);
+            }
+            self.save_store(&store)?;
+        }
+        Ok(should_alert)
+    }
+
     /// 获取文件路径（用于测试）
     #[cfg(test)]
     pub fn file_path(&self) -> &PathBuf {
@@ -155,6 +183,15 @@ impl BalanceManager {
     }
 }
 
+/// 低余额告警状态机：返回 (新的已告警状态, 是否需要告警)
+fn next_alert_state(last_alerted: bool, remaining: f64, threshold: Option<f64>) -> (bool, bool) {
+    match threshold {
+        Some(threshold) if remaining < threshold => (true, !last_alerted),
+        // 未设置阈值或已回升：清除告警状态
+        _ => (false, false),
+    }
+}
+
 impl Default for BalanceManager {
     fn default() -> Self {
         Self::new().expect("无法创建 BalanceManager")
@@ -164,7 +201,6 @@ impl Default for BalanceManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use tempfile::TempDir;
 
     fn create_test_manager() -> (BalanceManager, TempDir) {
@@ -194,6 +230,7 @@ mod tests {
             timeout_ms: Some(5000),
             save_api_key: false,
             api_key: None,
+            alert_threshold: None,
             created_at: 0,
             updated_at: 0,
         }
@@ -316,4 +353,51 @@ mod tests {
         let store = manager.load_store().unwrap();
         assert_eq!(store.configs.len(), 3);
     }
+
+    #[test]
+    fn test_next_alert_state() {
+        // 跌破阈值：首次告警
+        assert_eq!(next_alert_state(false, 4.0, Some(5.0)), (true, true));
+        // 持续低于阈值：不重复告警
+        assert_eq!(next_alert_state(true, 3.0, Some(5.0)), (true, false));
+        // 回升到阈值（含等于）：清除状态
+        assert_eq!(next_alert_state(true, 5.0, Some(5.0)), (false, false));
+        assert_eq!(next_alert_state(false, 8.0, Some(5.0)), (false, false));
+        // 未设置阈值：从不告警
+        assert_eq!(next_alert_state(true, 1.0, None), (false, false));
+    }
+
+    #[test]
+    fn test_record_balance_debounces_alerts() {
+        let (manager, _temp) = create_test_manager();
+        let mut config = create_test_config("test-1", "Test Config");
+        config.alert_threshold = Some(10.0);
+        manager.add_config(config).unwrap();
+
+        let threshold = Some(10.0);
+        assert!(!manager.record_balance("test-1", 20.0, threshold).unwrap());
+        // 跨越阈值告警一次
+        assert!(manager.record_balance("test-1", 9.0, threshold).unwrap());
+        assert!(!manager.record_balance("test-1", 5.0, threshold).unwrap());
+        assert_eq!(
+            manager.load_store().unwrap().last_alerted.get("test-1"),
+            Some(&true)
+        );
+
+        // 告警状态持久化：新实例读取同一文件仍不重复告警
+        let reloaded = BalanceManager {
+            data_manager: DataManager::new(),
+            file_path: manager.file_path().clone(),
+        };
+        assert!(!reloaded.record_balance("test-1", 4.0, threshold).unwrap());
+
+        // 回升后再次跌破重新告警
+        assert!(!manager.record_balance("test-1", 15.0, threshold).unwrap());
+        assert!(manager.load_store().unwrap().last_alerted.is_empty());
+        assert!(manager.record_balance("test-1", 8.0, threshold).unwrap());
+
+        // 删除配置时清理告警状态
+        manager.delete_config("test-1").unwrap();
+        assert!(manager.load_store().unwrap().last_alerted.is_empty());
+    }
 }
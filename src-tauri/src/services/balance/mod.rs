@@ -1,7 +1,13 @@
 // Balance Service Module
 //
-// 余额监控配置管理服务
+// 余额监控配置管理与后台执行服务
 
+mod executor;
 mod manager;
 
+pub use executor::{
+    check_low_balance, emit_balance_events, fetch_balance, run_extractor, BalanceExecutor,
+    BalanceLowAlert, BalanceResult, BalanceSnapshot, BALANCE_LOW_ALERT_EVENT,
+    BALANCE_UPDATED_EVENT,
+};
 pub use manager::BalanceManager;
@@ -0,0 +1,273 @@
+// Checkin History
+//
+// 签到流水：每次签到（自动或手动）的结果追加到 checkin_history.json，
+// 与 providers.json 位于同一目录；超出保留天数或条数上限的旧记录在写入时清理。
+// 写入经临时文件 + rename 完成；文件损坏时备份为 .corrupt-<时间戳> 后重新开始记录
+
+use super::CheckinResponse;
+use crate::models::provider::Provider;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 签到流水文件名
+pub const CHECKIN_HISTORY_FILE: &str = "checkin_history.json";
+
+/// 最多保留的记录条数
+const MAX_HISTORY_ENTRIES: usize = 2000;
+
+/// 最多保留的天数
+const MAX_HISTORY_DAYS: i64 = 90;
+
+/// 串行化读写（调度器与手动签到可能同时写入）
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 单条签到记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckinRecord {
+    /// 签到时间（Unix 时间戳，秒）
+    pub timestamp: i64,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_awarded: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl CheckinRecord {
+    /// 根据签到结果构建记录（请求异常时以错误信息作为消息）
+    pub fn from_result(
+        provider: &Provider,
+        result: &Result<CheckinResponse>,
+        timestamp: i64,
+    ) -> Self {
+        let (success, quota_awarded, message) = match result {
+            Ok(response) => (
+                response.success,
+                response.data.as_ref().and_then(|d| d.quota_awarded),
+                response.message.clone(),
+            ),
+            Err(e) => (false, None, Some(e.to_string())),
+        };
+        Self {
+            timestamp,
+            provider_id: provider.id.clone(),
+            provider_name: provider.name.clone(),
+            success,
+            quota_awarded,
+            message,
+        }
+    }
+}
+
+/// 签到流水存储
+pub struct CheckinHistory {
+    path: PathBuf,
+    max_entries: usize,
+}
+
+impl CheckinHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_entries: MAX_HISTORY_ENTRIES,
+        }
+    }
+
+    /// 与供应商存储文件同目录的签到流水
+    pub fn beside(store_path: &Path) -> Self {
+        let dir = store_path.parent().unwrap_or_else(|| Path::new("."));
+        Self::new(dir.join(CHECKIN_HISTORY_FILE))
+    }
+
+    /// 追加一条记录，并按保留天数与条数上限清理旧记录
+    pub fn append(&self, record: CheckinRecord) -> Result<()> {
+        let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut records = self.load()?;
+        let cutoff = record.timestamp - MAX_HISTORY_DAYS * 24 * 3600;
+        records.retain(|r| r.timestamp >= cutoff);
+        records.push(record);
+        if records.len() > self.max_entries {
+            let overflow = records.len() - self.max_entries;
+            records.drain(..overflow);
+        }
+
+        self.save(&records)
+    }
+
+    /// 分页查询签到记录（按时间倒序，可按供应商过滤）
+    pub fn query(
+        &self,
+        provider_id: Option<&str>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<CheckinRecord>> {
+        let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut records: Vec<CheckinRecord> = self
+            .load()?
+            .into_iter()
+            .filter(|r| match provider_id {
+                Some(id) => r.provider_id == id,
+                None => true,
+            })
+            .collect();
+        records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(records.into_iter().skip(offset).take(limit).collect())
+    }
+
+    fn load(&self) -> Result<Vec<CheckinRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("读取签到流水失败: {}", self.path.display()))?;
+        match serde_json::from_str(&content) {
+            Ok(records) => Ok(records),
+            Err(e) => {
+                // 损坏的文件移到备份位置，之后的读写从空流水开始，不再反复失败
+                let backup = self.path.with_file_name(format!(
+                    "{}.corrupt-{}",
+                    CHECKIN_HISTORY_FILE,
+                    chrono::Utc::now().timestamp()
+                ));
+                fs::rename(&self.path, &backup)
+                    .with_context(|| format!("备份损坏的签到流水失败: {}", backup.display()))?;
+                tracing::warn!(
+                    backup = %backup.display(),
+                    error = %e,
+                    "签到流水解析失败，已备份并重置"
+                );
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// 写入同目录临时文件后 rename 替换，写入中断时不会损坏原文件
+    fn save(&self, records: &[CheckinRecord]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("创建签到流水目录失败")?;
+        }
+        let content = serde_json::to_string_pretty(records).context("序列化签到流水失败")?;
+        let tmp_path = self
+            .path
+            .with_file_name(format!(".{}.tmp", CHECKIN_HISTORY_FILE));
+        let result = fs::write(&tmp_path, content)
+            .with_context(|| format!("写入签到流水临时文件失败: {}", tmp_path.display()))
+            .and_then(|_| {
+                fs::rename(&tmp_path, &self.path)
+                    .with_context(|| format!("写入签到流水失败: {}", self.path.display()))
+            });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
+    }
+}
+
+/// 记录一次签到结果（失败仅告警，不影响签到流程）
+pub fn record(store_path: &Path, record: CheckinRecord) {
+    let provider_id = record.provider_id.clone();
+    if let Err(e) = CheckinHistory::beside(store_path).append(record) {
+        tracing::warn!(provider_id = %provider_id, error = ?e, "写入签到流水失败");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn make_record(provider_id: &str, timestamp: i64, success: bool) -> CheckinRecord {
+        CheckinRecord {
+            timestamp,
+            provider_id: provider_id.to_string(),
+            provider_name: provider_id.to_uppercase(),
+            success,
+            quota_awarded: success.then_some(500),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let dir = TempDir::new().unwrap();
+        let history = CheckinHistory::beside(&dir.path().join("providers.json"));
+        assert!(history.query(None, 0, 10).unwrap().is_empty());
+
+        let now = chrono::Utc::now().timestamp();
+        history.append(make_record("a", now - 300, true)).unwrap();
+        history.append(make_record("b", now - 200, false)).unwrap();
+        history.append(make_record("a", now - 100, true)).unwrap();
+
+        let all = history.query(None, 0, 10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].timestamp, now - 100);
+
+        // 按供应商过滤并分页
+        let page = history.query(Some("a"), 1, 1).unwrap();
+        assert_eq!(page, vec![make_record("a", now - 300, true)]);
+        assert!(history.query(Some("a"), 2, 10).unwrap().is_empty());
+        assert!(!history.query(Some("b"), 0, 10).unwrap()[0].success);
+    }
+
+    #[test]
+    fn test_append_prunes_old_and_overflow() {
+        let dir = TempDir::new().unwrap();
+        let history = CheckinHistory {
+            path: dir.path().join(CHECKIN_HISTORY_FILE),
+            max_entries: 5,
+        };
+        let now = chrono::Utc::now().timestamp();
+
+        // 超过保留天数的记录在下次写入时清理
+        history
+            .append(make_record(
+                "a",
+                now - (MAX_HISTORY_DAYS + 1) * 24 * 3600,
+                true,
+            ))
+            .unwrap();
+        history.append(make_record("a", now, true)).unwrap();
+        assert_eq!(history.query(None, 0, 10).unwrap().len(), 1);
+
+        // 超过条数上限时丢弃最旧的记录
+        for i in 0..5 {
+            history.append(make_record("b", now + i, true)).unwrap();
+        }
+        let all = history.query(None, 0, usize::MAX).unwrap();
+        assert_eq!(all.len(), 5);
+        assert!(all.iter().all(|r| r.provider_id == "b"));
+    }
+
+    #[test]
+    fn test_corrupt_history_backed_up_and_reset() {
+        let dir = TempDir::new().unwrap();
+        let history = CheckinHistory::beside(&dir.path().join("providers.json"));
+        fs::write(&history.path, "{not json").unwrap();
+
+        // 损坏的文件被备份，查询与追加均恢复正常
+        assert!(history.query(None, 0, 10).unwrap().is_empty());
+        let now = chrono::Utc::now().timestamp();
+        history.append(make_record("a", now, true)).unwrap();
+        assert_eq!(history.query(None, 0, 10).unwrap().len(), 1);
+
+        let names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        let backup = names
+            .iter()
+            .find(|n| n.starts_with("checkin_history.json.corrupt-"))
+            .expect("应保留损坏文件的备份");
+        assert_eq!(
+            fs::read_to_string(dir.path().join(backup)).unwrap(),
+            "{not json"
+        );
+        // 不残留临时文件
+        assert!(!names.iter().any(|n| n.ends_with(".tmp")));
+    }
+}
@@ -0,0 +1,577 @@
+// Checkin Service
+//
+// 供应商签到服务：执行签到、随机时间调度、重试逻辑
+
+pub mod history;
+
+use crate::core::http::get_global_client;
+use crate::models::provider::{CheckinConfig, Provider};
+use crate::services::provider_manager::ProviderManager;
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Timelike};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 供应商级签到锁（调度器与手动签到共用，防止同一供应商并发签到）
+static CHECKIN_LOCKS: once_cell::sync::Lazy<
+    std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+> = once_cell::sync::Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// 获取指定供应商的签到锁
+pub fn provider_lock(provider_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+    let mut locks = CHECKIN_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    locks
+        .entry(provider_id.to_string())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+        .clone()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckinResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<CheckinData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckinData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota_awarded: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkin_date: Option<String>,
+}
+
+/// 执行签到
+pub async fn perform_checkin(provider: &Provider) -> Result<CheckinResponse> {
+    let config = provider
+        .checkin_config
+        .as_ref()
+        .ok_or_else(|| anyhow!("签到配置不存在"))?;
+
+    let base_url = provider
+        .api_address
+        .as_ref()
+        .unwrap_or(&provider.website_url);
+    let url = format!("{}{}", base_url.trim_end_matches('/'), config.endpoint);
+
+    // 复用全局客户端（应用全局代理配置），签到请求单独限定 30 秒超时
+    let client = get_global_client()?;
+
+    let response = client
+        .post(&url)
+        .timeout(Duration::from_secs(30))
+        .header("Authorization", format!("Bearer {}", provider.access_token))
+        .header("New-Api-User", &provider.user_id)
+        .header("Content-Type", "application/json")
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(anyhow!("签到失败 ({}): {}", status, error_text));
+    }
+
+    let result: CheckinResponse = response.json().await?;
+    Ok(result)
+}
+
+/// 检查是否需要签到（基于 next_checkin_at 时间戳）
+pub fn should_checkin(config: &CheckinConfig) -> bool {
+    if !config.enabled {
+        return false;
+    }
+
+    // 今天已签到则跳过
+    if checked_in_today(config) {
+        return false;
+    }
+
+    // 检查是否到达计划签到时间
+    if let Some(next_at) = config.next_checkin_at {
+        let now = chrono::Utc::now().timestamp();
+        return now >= next_at;
+    }
+
+    // 无计划时间，由调度器生成
+    false
+}
+
+/// 检查是否需要为今天生成签到计划（或推进到下一个计划时间点）
+pub fn needs_schedule(config: &CheckinConfig) -> bool {
+    config.enabled && !checked_in_today(config) && config.next_checkin_at.is_none()
+}
+
+/// 检查今天的签到次数是否已达上限
+fn checked_in_today(config: &CheckinConfig) -> bool {
+    checkins_today(config) >= config.checkins_per_day.max(1)
+}
+
+/// 当天已签到次数（last_checkin_at 不在本地时区当天时视为 0，跨天自动归零）
+pub fn checkins_today(config: &CheckinConfig) -> u8 {
+    match config.last_checkin_at {
+        Some(last) if local_date(last) == Local::now().date_naive() => config.today_checkin_count,
+        _ => 0,
+    }
+}
+
+/// 时间戳对应的本地日期
+fn local_date(timestamp: i64) -> NaiveDate {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local)
+        .date_naive()
+}
+
+/// 在配置的时间范围内为指定日期生成 `checkins_per_day` 个随机签到时间戳（升序）
+///
+/// 时间范围按次数均分为若干段，多次签到时每段只在中间一半内取值，
+/// 保证相邻两次签到至少间隔半段时长、互不相邻
+pub fn generate_checkin_time(config: &CheckinConfig, date: NaiveDate) -> Vec<i64> {
+    let (start_hour, end_hour) = config.effective_range();
+    let mut rng = rand::thread_rng();
+
+    let range_start = start_hour as u32 * 60;
+    let range_end = end_hour as u32 * 60 + 59;
+    let total = range_end - range_start + 1;
+    let count = (config.checkins_per_day.max(1) as u32).min(total);
+    let segment = total / count;
+
+    (0..count)
+        .map(|i| {
+            let minutes = if count == 1 {
+                rng.gen_range(range_start..=range_end)
+            } else {
+                let segment_start = range_start + i * segment;
+                let low = segment_start + segment / 4;
+                let high = (segment_start + segment * 3 / 4).saturating_sub(1).max(low);
+                rng.gen_range(low..=high)
+            };
+            local_timestamp(date, minutes)
+        })
+        .collect()
+}
+
+/// 将指定日期的第 `minutes` 分钟转换为本地时间戳
+fn local_timestamp(date: NaiveDate, minutes: u32) -> i64 {
+    let time = NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0).unwrap_or_default();
+    let datetime = date.and_time(time);
+
+    Local
+        .from_local_datetime(&datetime)
+        .single()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| {
+            // 时区转换失败时使用 UTC
+            datetime.and_utc().timestamp()
+        })
+}
+
+/// 推进当天的签到计划，返回下次签到时间
+///
+/// 优先取当天剩余的计划时间点；没有时重新生成一组，并扣除今天已签到的次数。
+/// 已过期的计划时间点只保留最近的一个并设为 `now`（补签一次），其余丢弃；
+/// 下次签到与当天上次签到至少间隔半段时长，避免启动晚于计划时连续触发
+pub fn schedule_next_checkin(
+    config: &mut CheckinConfig,
+    today: NaiveDate,
+    now: i64,
+) -> Option<i64> {
+    // 丢弃跨天遗留的计划时间点
+    config
+        .pending_checkin_times
+        .retain(|ts| local_date(*ts) == today);
+
+    if config.pending_checkin_times.is_empty() {
+        let remaining = config
+            .checkins_per_day
+            .max(1)
+            .saturating_sub(checkins_today(config)) as usize;
+        let times = generate_checkin_time(config, today);
+        let skip = times.len().saturating_sub(remaining);
+        config.pending_checkin_times = times.into_iter().skip(skip).collect();
+    }
+
+    // 过期时间点仅保留最近一个用于补签
+    let overdue = config
+        .pending_checkin_times
+        .iter()
+        .filter(|ts| **ts <= now)
+        .count();
+    if overdue > 1 {
+        config.pending_checkin_times.drain(..overdue - 1);
+    }
+
+    if config.pending_checkin_times.is_empty() {
+        return None;
+    }
+    let mut next = config.pending_checkin_times.remove(0).max(now);
+    if let Some(last) = config
+        .last_checkin_at
+        .filter(|last| local_date(*last) == today)
+    {
+        next = next.max(last + min_checkin_gap_secs(config));
+    }
+    config.next_checkin_at = Some(next);
+    Some(next)
+}
+
+/// 同一天相邻两次签到的最小间隔（秒）：时间范围按次数均分后的半段时长
+fn min_checkin_gap_secs(config: &CheckinConfig) -> i64 {
+    let (start_hour, end_hour) = config.effective_range();
+    let total = (end_hour as i64 - start_hour as i64 + 1) * 60;
+    let count = (config.checkins_per_day.max(1) as i64).min(total);
+    total / count / 2 * 60
+}
+
+/// 记录一次成功签到：当天次数 +1（跨天从 1 开始计），清除 next_checkin_at 以推进到下一个计划时间点
+pub fn record_checkin_success(config: &mut CheckinConfig, now: i64) {
+    config.today_checkin_count = checkins_today(config).saturating_add(1);
+    config.last_checkin_at = Some(now);
+    config.next_checkin_at = None;
+    config.total_checkins += 1;
+}
+
+/// 将签到响应写回统计字段（失败时仅记录状态与消息，不改变签到计划）
+pub fn apply_checkin_response(config: &mut CheckinConfig, response: &CheckinResponse, now: i64) {
+    config.last_checkin_message = response.message.clone();
+    if !response.success {
+        config.last_checkin_status = Some("failed".to_string());
+        return;
+    }
+    record_checkin_success(config, now);
+    config.last_checkin_status = Some("success".to_string());
+    if let Some(quota) = response.data.as_ref().and_then(|d| d.quota_awarded) {
+        config.total_quota += quota;
+    }
+}
+
+/// 立即为指定供应商执行一次签到并写回统计
+///
+/// 与调度器共用供应商级签到锁，签到进行中时直接返回错误
+pub async fn checkin_now(manager: &ProviderManager, provider_id: &str) -> Result<CheckinResponse> {
+    let lock = provider_lock(provider_id);
+    let _guard = lock
+        .try_lock()
+        .map_err(|_| anyhow!("供应商 {} 正在签到中，请稍后再试", provider_id))?;
+
+    // 调度器使用独立的 ProviderManager 实例写入，读取前丢弃缓存
+    manager.clear_cache();
+    let provider = manager
+        .list_providers()?
+        .into_iter()
+        .find(|p| p.id == provider_id)
+        .ok_or_else(|| anyhow!("供应商不存在: {}", provider_id))?;
+    match &provider.checkin_config {
+        None => return Err(anyhow!("供应商 {} 未配置签到", provider.name)),
+        Some(config) if !config.enabled => {
+            return Err(anyhow!("供应商 {} 未启用签到", provider.name))
+        }
+        Some(_) => {}
+    }
+
+    let result = perform_checkin(&provider).await;
+    let now = chrono::Utc::now().timestamp();
+    history::record(
+        manager.store_path(),
+        history::CheckinRecord::from_result(&provider, &result, now),
+    );
+
+    let mut updated = provider.clone();
+    if let Some(config) = updated.checkin_config.as_mut() {
+        match &result {
+            Ok(response) => apply_checkin_response(config, response, now),
+            Err(e) => {
+                config.last_checkin_status = Some("failed".to_string());
+                config.last_checkin_message = Some(e.to_string());
+            }
+        }
+    }
+    manager.update_provider(&provider.id, updated)?;
+
+    result
+}
+
+/// 在当天剩余范围内生成重试时间（距当前至少 10 分钟）
+/// 范围不足时返回 None（今天不再重试，明天再来）
+pub fn generate_retry_time(config: &CheckinConfig) -> Option<i64> {
+    let now = Local::now();
+    let (_, end_hour) = config.effective_range();
+
+    // 最早重试时间：当前时间 + 10 分钟
+    let min_retry = now + chrono::Duration::minutes(10);
+    let min_hour = min_retry.hour();
+    let min_minute = min_retry.minute();
+
+    // 范围结束时间为 end_hour:59
+    // 如果最早重试时间已超过范围结束，返回 None
+    if min_hour > end_hour as u32 || (min_hour == end_hour as u32 && min_minute > 59) {
+        return None;
+    }
+
+    let mut rng = rand::thread_rng();
+
+    // 在 min_retry 到 end_hour:59 之间随机选取
+    let start_minutes = min_hour * 60 + min_minute;
+    let end_minutes = end_hour as u32 * 60 + 59;
+
+    if start_minutes >= end_minutes {
+        return None;
+    }
+
+    let random_minutes = rng.gen_range(start_minutes..=end_minutes);
+    let retry_hour = random_minutes / 60;
+    let retry_minute = random_minutes % 60;
+
+    let date = now.date_naive();
+    let time = NaiveTime::from_hms_opt(retry_hour, retry_minute, 0)?;
+    let datetime = date.and_time(time);
+
+    Local
+        .from_local_datetime(&datetime)
+        .single()
+        .map(|dt| dt.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_config(enabled: bool, start: u8, end: u8) -> CheckinConfig {
+        CheckinConfig {
+            enabled,
+            endpoint: "/api/user/checkin".to_string(),
+            checkin_hour_start: start,
+            checkin_hour_end: end,
+            checkins_per_day: 1,
+            today_checkin_count: 0,
+            next_checkin_at: None,
+            pending_checkin_times: Vec::new(),
+            last_checkin_at: None,
+            last_checkin_status: None,
+            last_checkin_message: None,
+            total_checkins: 0,
+            total_quota: 0,
+        }
+    }
+
+    #[test]
+    fn test_apply_checkin_response() {
+        let mut config = make_config(true, 0, 0);
+        config.next_checkin_at = Some(100);
+        let now = chrono::Utc::now().timestamp();
+
+        // 失败只记录状态与消息，保留签到计划
+        let failed = CheckinResponse {
+            success: false,
+            message: Some("今日已签到".to_string()),
+            data: None,
+        };
+        apply_checkin_response(&mut config, &failed, now);
+        assert_eq!(config.last_checkin_status.as_deref(), Some("failed"));
+        assert_eq!(config.next_checkin_at, Some(100));
+        assert_eq!(config.total_checkins, 0);
+
+        let succeeded = CheckinResponse {
+            success: true,
+            message: Some("ok".to_string()),
+            data: Some(CheckinData {
+                quota_awarded: Some(500),
+                checkin_date: None,
+            }),
+        };
+        apply_checkin_response(&mut config, &succeeded, now);
+        assert_eq!(config.last_checkin_status.as_deref(), Some("success"));
+        assert_eq!(config.last_checkin_message.as_deref(), Some("ok"));
+        assert_eq!(config.next_checkin_at, None);
+        assert_eq!(config.today_checkin_count, 1);
+        assert_eq!(config.total_quota, 500);
+    }
+
+    #[test]
+    fn test_should_checkin_disabled() {
+        let config = make_config(false, 0, 0);
+        assert!(!should_checkin(&config));
+    }
+
+    #[test]
+    fn test_should_checkin_no_schedule() {
+        let config = make_config(true, 0, 0);
+        // 无 next_checkin_at，应返回 false
+        assert!(!should_checkin(&config));
+    }
+
+    #[test]
+    fn test_should_checkin_with_past_schedule() {
+        let mut config = make_config(true, 0, 0);
+        // 设置过去的时间
+        config.next_checkin_at = Some(chrono::Utc::now().timestamp() - 100);
+        assert!(should_checkin(&config));
+    }
+
+    #[test]
+    fn test_should_checkin_with_future_schedule() {
+        let mut config = make_config(true, 0, 0);
+        // 设置未来的时间
+        config.next_checkin_at = Some(chrono::Utc::now().timestamp() + 3600);
+        assert!(!should_checkin(&config));
+    }
+
+    #[test]
+    fn test_needs_schedule() {
+        let config = make_config(true, 0, 0);
+        assert!(needs_schedule(&config));
+
+        let disabled = make_config(false, 0, 0);
+        assert!(!needs_schedule(&disabled));
+
+        let mut scheduled = make_config(true, 0, 0);
+        scheduled.next_checkin_at = Some(12345);
+        assert!(!needs_schedule(&scheduled));
+    }
+
+    #[test]
+    fn test_generate_checkin_time_in_range() {
+        let config = make_config(true, 9, 12);
+        let date = Local::now().date_naive();
+
+        // 生成 100 次，确保都在范围内
+        for _ in 0..100 {
+            let times = generate_checkin_time(&config, date);
+            assert_eq!(times.len(), 1);
+            let ts = times[0];
+            let dt = chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
+                .unwrap()
+                .with_timezone(&Local);
+            let hour = dt.hour();
+            assert!((9..=12).contains(&hour), "hour {} not in range 9-12", hour);
+        }
+    }
+
+    #[test]
+    fn test_generate_checkin_time_full_day() {
+        let config = make_config(true, 0, 0); // start == end → 全天
+        let date = Local::now().date_naive();
+        let ts = generate_checkin_time(&config, date)[0];
+        let dt = chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        assert!((0..=23).contains(&dt.hour()));
+    }
+
+    #[test]
+    fn test_generate_multiple_checkin_times_not_adjacent() {
+        let mut config = make_config(true, 9, 12);
+        config.checkins_per_day = 2;
+        let date = Local::now().date_naive();
+
+        for _ in 0..100 {
+            let times = generate_checkin_time(&config, date);
+            assert_eq!(times.len(), 2);
+            assert!(times[0] < times[1]);
+            // 范围 4 小时均分两段，相邻时间点至少间隔半段（60 分钟）
+            assert!(times[1] - times[0] >= 60 * 60);
+            for ts in times {
+                let hour = chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
+                    .unwrap()
+                    .with_timezone(&Local)
+                    .hour();
+                assert!((9..=12).contains(&hour), "hour {} not in range 9-12", hour);
+            }
+        }
+    }
+
+    #[test]
+    fn test_checkin_count_resets_across_day() {
+        let mut config = make_config(true, 0, 0);
+        config.checkins_per_day = 2;
+        let now = chrono::Utc::now().timestamp();
+
+        // 今天已签到 2 次：达到上限
+        config.today_checkin_count = 2;
+        config.last_checkin_at = Some(now);
+        assert_eq!(checkins_today(&config), 2);
+        assert!(!needs_schedule(&config));
+
+        // 上次签到在昨天：计数归零，重新计划
+        config.last_checkin_at = Some(now - 2 * 24 * 3600);
+        assert_eq!(checkins_today(&config), 0);
+        assert!(needs_schedule(&config));
+
+        record_checkin_success(&mut config, now);
+        assert_eq!(config.today_checkin_count, 1);
+        assert!(needs_schedule(&config));
+    }
+
+    #[test]
+    fn test_schedule_next_checkin_twice_a_day() {
+        let mut config = make_config(true, 0, 0);
+        config.checkins_per_day = 2;
+        let today = Local::now().date_naive();
+        let now = chrono::Utc::now().timestamp();
+
+        // 首次计划：生成两个时间点，取第一个，剩余一个待执行
+        let first = schedule_next_checkin(&mut config, today, now).unwrap();
+        assert!(first >= now);
+        assert_eq!(config.next_checkin_at, Some(first));
+        assert_eq!(config.pending_checkin_times.len(), 1);
+
+        record_checkin_success(&mut config, now);
+        assert!(needs_schedule(&config));
+
+        // 第二次计划直接取剩余时间点
+        let second = schedule_next_checkin(&mut config, today, now).unwrap();
+        assert_eq!(config.next_checkin_at, Some(second));
+        assert!(config.pending_checkin_times.is_empty());
+
+        record_checkin_success(&mut config, now);
+        assert_eq!(config.today_checkin_count, 2);
+        assert!(!needs_schedule(&config));
+        assert!(!should_checkin(&config));
+
+        // 跨天遗留的计划时间点被丢弃
+        let mut stale = make_config(true, 0, 0);
+        stale.pending_checkin_times = vec![now - 2 * 24 * 3600];
+        schedule_next_checkin(&mut stale, today, now).unwrap();
+        assert!(stale.next_checkin_at.unwrap() >= now - 24 * 3600);
+    }
+
+    #[test]
+    fn test_schedule_next_checkin_skips_overdue_slots() {
+        let mut config = make_config(true, 0, 0);
+        config.checkins_per_day = 3;
+        let today = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let now = local_timestamp(today, 12 * 60);
+        let gap = min_checkin_gap_secs(&config);
+        assert_eq!(gap, 240 * 60);
+
+        // 启动晚于前两个计划时间：只补签一次，另一个过期时间点被丢弃
+        config.pending_checkin_times = vec![now - 7200, now - 3600, now + 60];
+        let first = schedule_next_checkin(&mut config, today, now).unwrap();
+        assert_eq!(first, now);
+        assert_eq!(config.pending_checkin_times, vec![now + 60]);
+
+        // 补签后下一个时间点离得太近：推迟到最小间隔之后
+        record_checkin_success(&mut config, now);
+        let second = schedule_next_checkin(&mut config, today, now).unwrap();
+        assert_eq!(second, now + gap);
+        assert!(config.pending_checkin_times.is_empty());
+    }
+
+    #[test]
+    fn test_effective_range() {
+        let config = make_config(true, 9, 12);
+        assert_eq!(config.effective_range(), (9, 12));
+
+        let full_day = make_config(true, 0, 0);
+        assert_eq!(full_day.effective_range(), (0, 23));
+
+        let reversed = make_config(true, 12, 9);
+        assert_eq!(reversed.effective_range(), (0, 23));
+    }
+}
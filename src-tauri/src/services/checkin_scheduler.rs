@@ -1,6 +1,6 @@
 // Checkin Scheduler
 //
-// 签到定时任务调度器：每分钟检查，随机时间签到，失败自动重试
+// 签到定时任务调度器：每分钟检查，随机时间签到（支持每天多次），失败自动重试
 
 use crate::models::provider::Provider;
 use crate::services::{checkin, provider_manager::ProviderManager};
@@ -72,6 +72,14 @@ impl CheckinScheduler {
     ) -> anyhow::Result<()> {
         let providers = {
             let manager = provider_manager.read().await;
+            // 命令层使用独立的 ProviderManager 实例写入，这里每轮重新读取文件
+            manager.clear_cache();
+
+            // 全局总开关优先于各供应商的签到开关
+            if !manager.is_checkin_enabled()? {
+                return Ok(());
+            }
+
             let all: Vec<Provider> = manager.list_providers()?;
             all.into_iter()
                 .filter(|p| p.checkin_config.as_ref().is_some_and(|c| c.enabled))
@@ -82,25 +90,21 @@ impl CheckinScheduler {
             return Ok(());
         }
 
-        // 阶段 1：为缺少计划时间的供应商生成随机签到时间
+        // 阶段 1：为缺少计划时间的供应商生成随机签到时间（或推进到当天下一个计划时间点）
         for provider in &providers {
             if let Some(config) = &provider.checkin_config {
                 if checkin::needs_schedule(config) {
                     let today = Local::now().date_naive();
-                    let scheduled_time = checkin::generate_checkin_time(config, today);
                     let now = chrono::Utc::now().timestamp();
 
-                    // 如果生成的时间已过，直接设为当前时间（立即执行）
-                    let final_time = if scheduled_time < now {
-                        now
-                    } else {
-                        scheduled_time
-                    };
-
                     let mut updated = provider.clone();
-                    if let Some(c) = &mut updated.checkin_config {
-                        c.next_checkin_at = Some(final_time);
-                    }
+                    let Some(final_time) = updated
+                        .checkin_config
+                        .as_mut()
+                        .and_then(|c| checkin::schedule_next_checkin(c, today, now))
+                    else {
+                        continue;
+                    };
 
                     let manager = provider_manager.write().await;
                     if let Err(e) = manager.update_provider(&provider.id, updated) {
@@ -135,26 +139,59 @@ impl CheckinScheduler {
 
         // 阶段 2：执行到期的签到
         for provider in providers_to_checkin {
+            // 手动签到进行中则跳过，下一轮再检查
+            let lock = checkin::provider_lock(&provider.id);
+            let Ok(_guard) = lock.try_lock() else {
+                tracing::debug!("供应商 {} 正在手动签到，跳过本轮", provider.name);
+                continue;
+            };
+
+            // 持锁后重新读取，避免与刚完成的手动签到重复执行
+            let provider = {
+                let manager = provider_manager.read().await;
+                manager.clear_cache();
+                match manager
+                    .list_providers()?
+                    .into_iter()
+                    .find(|p| p.id == provider.id)
+                {
+                    Some(p)
+                        if p.checkin_config
+                            .as_ref()
+                            .is_some_and(checkin::should_checkin) =>
+                    {
+                        p
+                    }
+                    _ => continue,
+                }
+            };
+
             tracing::info!("开始为供应商 {} 执行自动签到", provider.name);
 
-            match checkin::perform_checkin(&provider).await {
+            let result = checkin::perform_checkin(&provider).await;
+            let store_path = provider_manager.read().await.store_path().to_path_buf();
+            checkin::history::record(
+                &store_path,
+                checkin::history::CheckinRecord::from_result(
+                    &provider,
+                    &result,
+                    chrono::Utc::now().timestamp(),
+                ),
+            );
+
+            match result {
                 Ok(response) => {
                     if response.success {
                         tracing::info!("供应商 {} 签到成功: {:?}", provider.name, response.message);
 
-                        // 更新签到统计，清除 next_checkin_at
+                        // 更新签到统计，清除 next_checkin_at（下一轮推进到当天下一个计划时间点）
                         let mut updated = provider.clone();
                         if let Some(config) = &mut updated.checkin_config {
-                            config.next_checkin_at = None;
-                            config.last_checkin_at = Some(chrono::Utc::now().timestamp());
-                            config.last_checkin_status = Some("success".to_string());
-                            config.last_checkin_message = response.message.clone();
-                            config.total_checkins += 1;
-                            if let Some(data) = response.data {
-                                if let Some(quota) = data.quota_awarded {
-                                    config.total_quota += quota;
-                                }
-                            }
+                            checkin::apply_checkin_response(
+                                config,
+                                &response,
+                                chrono::Utc::now().timestamp(),
+                            );
                         }
 
                         let manager = provider_manager.write().await;
@@ -220,3 +257,179 @@ impl CheckinScheduler {
         Self::check_and_checkin(&self.provider_manager).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::provider::CheckinConfig;
+    use crate::test_support::{MockResponse, MockUpstream};
+
+    #[tokio::test]
+    async fn test_global_switch_skips_all_providers() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ProviderManager::with_store_path(dir.path().join("providers.json"));
+
+        let mut provider = manager.list_providers().unwrap()[0].clone();
+        provider.id = "auto".to_string();
+        provider.is_default = false;
+        provider.checkin_config = Some(CheckinConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        manager.create_provider(provider).unwrap();
+        manager.set_checkin_enabled(false).unwrap();
+
+        let provider_manager = Arc::new(RwLock::new(manager));
+        let scheduler = CheckinScheduler::new(provider_manager.clone());
+        scheduler.run_once().await.unwrap();
+
+        // 总开关关闭时不生成签到计划
+        let manager = provider_manager.read().await;
+        let config = manager
+            .list_providers()
+            .unwrap()
+            .into_iter()
+            .find(|p| p.id == "auto")
+            .and_then(|p| p.checkin_config)
+            .unwrap();
+        assert_eq!(config.next_checkin_at, None);
+    }
+
+    /// 启动 mock 签到接口
+    async fn spawn_checkin_server() -> MockUpstream {
+        MockUpstream::respond(MockResponse::json(
+            "200 OK",
+            r#"{"success":true,"message":"ok","data":{"quota_awarded":100}}"#,
+        ))
+        .await
+    }
+
+    async fn load_checkin_config(
+        provider_manager: &Arc<RwLock<ProviderManager>>,
+        id: &str,
+    ) -> CheckinConfig {
+        provider_manager
+            .read()
+            .await
+            .list_providers()
+            .unwrap()
+            .into_iter()
+            .find(|p| p.id == id)
+            .and_then(|p| p.checkin_config)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_checkin_twice_a_day() {
+        let upstream = spawn_checkin_server().await;
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ProviderManager::with_store_path(dir.path().join("providers.json"));
+
+        // 两个计划时间点均已到期：每轮执行一次
+        let now = chrono::Utc::now().timestamp();
+        let mut provider = manager.list_providers().unwrap()[0].clone();
+        provider.id = "twice".to_string();
+        provider.is_default = false;
+        provider.api_address = Some(upstream.url());
+        provider.checkin_config = Some(CheckinConfig {
+            enabled: true,
+            checkins_per_day: 2,
+            next_checkin_at: Some(now - 1),
+            pending_checkin_times: vec![now - 1],
+            ..Default::default()
+        });
+        manager.create_provider(provider).unwrap();
+
+        let provider_manager = Arc::new(RwLock::new(manager));
+        let scheduler = CheckinScheduler::new(provider_manager.clone());
+
+        scheduler.run_once().await.unwrap();
+        let config = load_checkin_config(&provider_manager, "twice").await;
+        assert_eq!(config.today_checkin_count, 1);
+        assert_eq!(config.next_checkin_at, None);
+        assert_eq!(config.pending_checkin_times.len(), 1);
+
+        // 第二轮：推进到剩余的计划时间点并签到
+        scheduler.run_once().await.unwrap();
+        let config = load_checkin_config(&provider_manager, "twice").await;
+        assert_eq!(config.today_checkin_count, 2);
+        assert_eq!(config.total_checkins, 2);
+        assert_eq!(config.total_quota, 200);
+        assert!(config.pending_checkin_times.is_empty());
+
+        // 已达当天上限：不再计划、不再签到
+        scheduler.run_once().await.unwrap();
+        let config = load_checkin_config(&provider_manager, "twice").await;
+        assert_eq!(config.next_checkin_at, None);
+        assert_eq!(upstream.hits(), 2);
+
+        // 每次签到均写入流水
+        let history = checkin::history::CheckinHistory::beside(&dir.path().join("providers.json"));
+        let records = history.query(Some("twice"), 0, 10).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|r| r.success && r.quota_awarded == Some(100)));
+    }
+
+    #[tokio::test]
+    async fn test_checkin_now_shares_provider_lock() {
+        let upstream = spawn_checkin_server().await;
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ProviderManager::with_store_path(dir.path().join("providers.json"));
+
+        let now = chrono::Utc::now().timestamp();
+        let mut provider = manager.list_providers().unwrap()[0].clone();
+        provider.id = "manual".to_string();
+        provider.is_default = false;
+        provider.api_address = Some(upstream.url());
+        provider.checkin_config = Some(CheckinConfig {
+            enabled: true,
+            next_checkin_at: Some(now - 1),
+            ..Default::default()
+        });
+        manager.create_provider(provider.clone()).unwrap();
+
+        let provider_manager = Arc::new(RwLock::new(manager));
+        let scheduler = CheckinScheduler::new(provider_manager.clone());
+
+        // 持有签到锁期间：调度器跳过，手动签到直接报错
+        let lock = checkin::provider_lock("manual");
+        let guard = lock.lock().await;
+        scheduler.run_once().await.unwrap();
+        let err = checkin::checkin_now(&*provider_manager.read().await, "manual")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("正在签到中"));
+        assert_eq!(upstream.hits(), 0);
+        drop(guard);
+
+        // 手动签到写回统计，之后调度器不再重复签到
+        let response = checkin::checkin_now(&*provider_manager.read().await, "manual")
+            .await
+            .unwrap();
+        assert!(response.success);
+        let config = load_checkin_config(&provider_manager, "manual").await;
+        assert_eq!(config.total_checkins, 1);
+        assert_eq!(config.total_quota, 100);
+        assert_eq!(config.last_checkin_status.as_deref(), Some("success"));
+        assert_eq!(config.last_checkin_message.as_deref(), Some("ok"));
+
+        scheduler.run_once().await.unwrap();
+        assert_eq!(upstream.hits(), 1);
+
+        // 未启用签到时返回明确错误
+        let mut disabled = provider;
+        disabled.id = "disabled".to_string();
+        disabled.checkin_config = Some(CheckinConfig::default());
+        provider_manager
+            .read()
+            .await
+            .create_provider(disabled)
+            .unwrap();
+        let err = checkin::checkin_now(&*provider_manager.read().await, "disabled")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("未启用签到"));
+    }
+}
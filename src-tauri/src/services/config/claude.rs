@@ -105,6 +105,7 @@ pub fn save_claude_settings(settings: &Value, extra_config: Option<&Value>) -> R
         .json_uncached()
         .write(&config_path, settings)
         .context("写入 Claude Code 配置失败")?;
+    super::drift::mark_internal_write(&config_path);
 
     if let Some(extra) = extra_config {
         if !extra.is_object() {
@@ -12,6 +12,15 @@ use std::fs;
 use toml;
 use toml_edit::DocumentMut;
 
+const AUTH_API_KEY: &str = "OPENAI_API_KEY";
+const AUTH_TOKENS_KEY: &str = "tokens";
+/// config.toml 中选择认证方式的字段
+const PREFERRED_AUTH_METHOD_KEY: &str = "preferred_auth_method";
+/// API Key 认证
+pub const AUTH_MODE_API_KEY: &str = "apikey";
+/// ChatGPT 登录认证
+pub const AUTH_MODE_CHATGPT: &str = "chatgpt";
+
 /// Codex 配置管理器
 pub struct CodexConfigManager;
 
@@ -24,7 +33,11 @@ impl ToolConfigManager for CodexConfigManager {
     }
 
     fn save_settings(payload: &Self::Payload) -> Result<()> {
-        save_codex_settings(&payload.config, payload.auth_token.clone())
+        save_codex_settings(
+            &payload.config,
+            payload.auth_token.clone(),
+            payload.auth_mode.clone(),
+        )
     }
 
     fn get_schema() -> Result<Value> {
@@ -57,20 +70,20 @@ pub fn read_codex_settings() -> Result<CodexSettingsPayload> {
         Value::Object(Map::new())
     };
 
-    let auth_token = if auth_path.exists() {
-        let auth = manager
+    let auth = if auth_path.exists() {
+        manager
             .json_uncached()
             .read(&auth_path)
-            .context("读取 Codex auth.json 失败")?;
-        auth.get("OPENAI_API_KEY")
-            .and_then(|s| s.as_str().map(|s| s.to_string()))
+            .context("读取 Codex auth.json 失败")?
     } else {
-        None
+        Value::Object(Map::new())
     };
 
     Ok(CodexSettingsPayload {
+        auth_token: read_auth_api_key(&auth),
+        auth_mode: resolve_auth_mode(&config_value, &auth),
+        chatgpt_logged_in: has_chatgpt_tokens(&auth),
         config: config_value,
-        auth_token,
     })
 }
 
@@ -80,11 +93,16 @@ pub fn read_codex_settings() -> Result<CodexSettingsPayload> {
 ///
 /// * `config` - 配置对象（将保存到 config.toml）
 /// * `auth_token` - 可选的 OpenAI API Key（将保存到 auth.json）
+/// * `auth_mode` - 可选的认证方式（`apikey` / `chatgpt`，写入 config.toml 的 `preferred_auth_method`）
 ///
 /// # Errors
 ///
-/// 当配置不是有效对象或写入失败时返回错误
-pub fn save_codex_settings(config: &Value, auth_token: Option<String>) -> Result<()> {
+/// 当配置不是有效对象、认证方式不可用或写入失败时返回错误
+pub fn save_codex_settings(
+    config: &Value,
+    auth_token: Option<String>,
+    auth_mode: Option<String>,
+) -> Result<()> {
     if !config.is_object() {
         anyhow::bail!("Codex 配置必须是对象结构");
     }
@@ -94,6 +112,31 @@ pub fn save_codex_settings(config: &Value, auth_token: Option<String>) -> Result
     let auth_path = tool.config_dir.join("auth.json");
     let manager = DataManager::new();
 
+    // 先整理 auth.json，认证方式不可用时不写入任何文件
+    let auth_data = if auth_token.is_some() || auth_mode.is_some() {
+        let mut auth_data = if auth_path.exists() {
+            manager
+                .json_uncached()
+                .read(&auth_path)
+                .unwrap_or(Value::Object(Map::new()))
+        } else {
+            Value::Object(Map::new())
+        };
+
+        if let Some(token) = &auth_token {
+            if auth_mode.as_deref() == Some(AUTH_MODE_CHATGPT) {
+                anyhow::bail!("ChatGPT 登录认证不使用 API Key");
+            }
+            write_auth_api_key(&mut auth_data, token)?;
+        }
+        if let Some(mode) = &auth_mode {
+            apply_auth_mode(&mut auth_data, mode)?;
+        }
+        Some(auth_data)
+    } else {
+        None
+    };
+
     fs::create_dir_all(&tool.config_dir).context("创建 Codex 配置目录失败")?;
 
     // 读取现有 TOML 文档以保留注释和格式
@@ -114,36 +157,100 @@ pub fn save_codex_settings(config: &Value, auth_token: Option<String>) -> Result
 
     // 合并配置，保留注释
     merge_toml_tables(existing_doc.as_table_mut(), new_doc.as_table());
+    if let Some(mode) = &auth_mode {
+        existing_doc.insert(PREFERRED_AUTH_METHOD_KEY, toml_edit::value(mode.as_str()));
+    }
 
     manager
         .toml()
         .write(&config_path, &existing_doc)
         .context("写入 Codex config.toml 失败")?;
+    super::drift::mark_internal_write(&config_path);
 
-    // 保存认证令牌
-    if let Some(token) = auth_token {
-        let mut auth_data = if auth_path.exists() {
-            manager
-                .json_uncached()
-                .read(&auth_path)
-                .unwrap_or(Value::Object(Map::new()))
-        } else {
-            Value::Object(Map::new())
-        };
-
-        if let Value::Object(ref mut obj) = auth_data {
-            obj.insert("OPENAI_API_KEY".to_string(), Value::String(token));
-        }
-
+    if let Some(auth_data) = auth_data {
         manager
             .json_uncached()
             .write(&auth_path, &auth_data)
             .context("写入 Codex auth.json 失败")?;
+        super::drift::mark_internal_write(&auth_path);
     }
 
     Ok(())
 }
 
+// ==================== 认证方式 ====================
+//
+// auth.json 仅包含 Codex 定义的字段：
+// - `OPENAI_API_KEY`：API Key 认证使用的 Key
+// - `tokens`：`codex login` 使用 ChatGPT 登录后保存的 Token
+//
+// 两者并存时由 config.toml 的 `preferred_auth_method`（`apikey` / `chatgpt`）决定使用哪种认证方式
+
+/// 读取 auth.json 中的 API Key
+pub fn read_auth_api_key(auth: &Value) -> Option<String> {
+    auth.get(AUTH_API_KEY)
+        .and_then(|v| v.as_str())
+        .filter(|key| !key.is_empty())
+        .map(String::from)
+}
+
+/// auth.json 中是否保存了 ChatGPT 登录 Token
+pub fn has_chatgpt_tokens(auth: &Value) -> bool {
+    auth.get(AUTH_TOKENS_KEY).is_some_and(|v| v.is_object())
+}
+
+/// 当前生效的认证方式
+///
+/// 优先读取 config.toml 的 `preferred_auth_method`，未设置时按 auth.json 内容推断
+pub fn resolve_auth_mode(config: &Value, auth: &Value) -> Option<String> {
+    config
+        .get(PREFERRED_AUTH_METHOD_KEY)
+        .and_then(|v| v.as_str())
+        .filter(|mode| [AUTH_MODE_API_KEY, AUTH_MODE_CHATGPT].contains(mode))
+        .map(String::from)
+        .or_else(|| {
+            if read_auth_api_key(auth).is_some() {
+                Some(AUTH_MODE_API_KEY.to_string())
+            } else if has_chatgpt_tokens(auth) {
+                Some(AUTH_MODE_CHATGPT.to_string())
+            } else {
+                None
+            }
+        })
+}
+
+/// 写入 API Key（保留 ChatGPT 登录 Token）
+pub fn write_auth_api_key(auth: &mut Value, api_key: &str) -> Result<()> {
+    auth.as_object_mut()
+        .ok_or_else(|| anyhow!("auth.json 格式错误：不是对象"))?
+        .insert(AUTH_API_KEY.to_string(), Value::String(api_key.to_string()));
+    Ok(())
+}
+
+/// 按认证方式整理 auth.json
+///
+/// - `apikey`：需要 auth.json 中已有 API Key，原样保留
+/// - `chatgpt`：需要已使用 ChatGPT 登录；该方式不使用 API Key，与 `codex login` 一致置为 null
+pub fn apply_auth_mode(auth: &mut Value, mode: &str) -> Result<()> {
+    match mode {
+        AUTH_MODE_API_KEY => {
+            if read_auth_api_key(auth).is_none() {
+                anyhow::bail!("auth.json 中没有 API Key，无法使用 API Key 认证");
+            }
+        }
+        AUTH_MODE_CHATGPT => {
+            if !has_chatgpt_tokens(auth) {
+                anyhow::bail!("尚未使用 ChatGPT 登录，请先运行 codex login");
+            }
+            auth.as_object_mut()
+                .ok_or_else(|| anyhow!("auth.json 格式错误：不是对象"))?
+                .insert(AUTH_API_KEY.to_string(), Value::Null);
+        }
+        other => anyhow::bail!("不支持的认证方式: {}", other),
+    }
+    Ok(())
+}
+
 /// 获取 Codex 配置 JSON Schema
 ///
 /// # Returns
@@ -166,6 +273,77 @@ pub fn get_codex_schema() -> Result<Value> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_write_api_key_keeps_chatgpt_tokens() {
+        let mut auth = json!({"OPENAI_API_KEY": "sk-old"});
+        assert_eq!(read_auth_api_key(&auth).as_deref(), Some("sk-old"));
+        assert!(!has_chatgpt_tokens(&auth));
+
+        write_auth_api_key(&mut auth, "sk-new").unwrap();
+        assert_eq!(auth, json!({"OPENAI_API_KEY": "sk-new"}));
+
+        let mut auth = json!({
+            "OPENAI_API_KEY": null,
+            "tokens": {"access_token": "at-1"},
+            "last_refresh": "2025-01-01T00:00:00Z"
+        });
+        assert!(read_auth_api_key(&auth).is_none());
+        write_auth_api_key(&mut auth, "sk-new").unwrap();
+        assert_eq!(auth["OPENAI_API_KEY"], "sk-new");
+        assert_eq!(auth["tokens"]["access_token"], "at-1");
+        assert_eq!(auth["last_refresh"], "2025-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_resolve_auth_mode() {
+        let api_key_auth = json!({"OPENAI_API_KEY": "sk"});
+        let chatgpt_auth = json!({"OPENAI_API_KEY": null, "tokens": {"access_token": "at"}});
+        let both = json!({"OPENAI_API_KEY": "sk", "tokens": {"access_token": "at"}});
+        let empty_config = json!({});
+
+        assert_eq!(
+            resolve_auth_mode(&empty_config, &api_key_auth).as_deref(),
+            Some(AUTH_MODE_API_KEY)
+        );
+        assert_eq!(
+            resolve_auth_mode(&empty_config, &chatgpt_auth).as_deref(),
+            Some(AUTH_MODE_CHATGPT)
+        );
+        assert!(resolve_auth_mode(&empty_config, &json!({})).is_none());
+
+        // config.toml 显式指定时优先，无效值按 auth.json 推断
+        let config = json!({"preferred_auth_method": "chatgpt"});
+        assert_eq!(
+            resolve_auth_mode(&config, &both).as_deref(),
+            Some(AUTH_MODE_CHATGPT)
+        );
+        let config = json!({"preferred_auth_method": "other"});
+        assert_eq!(
+            resolve_auth_mode(&config, &both).as_deref(),
+            Some(AUTH_MODE_API_KEY)
+        );
+    }
+
+    #[test]
+    fn test_apply_auth_mode() {
+        // API Key 认证保留已有 Key
+        let mut auth = json!({"OPENAI_API_KEY": "sk", "tokens": {"access_token": "at"}});
+        apply_auth_mode(&mut auth, AUTH_MODE_API_KEY).unwrap();
+        assert_eq!(auth["OPENAI_API_KEY"], "sk");
+        assert_eq!(auth["tokens"]["access_token"], "at");
+
+        // ChatGPT 登录认证不使用 Key
+        apply_auth_mode(&mut auth, AUTH_MODE_CHATGPT).unwrap();
+        assert!(auth["OPENAI_API_KEY"].is_null());
+        assert_eq!(auth["tokens"]["access_token"], "at");
+
+        // 缺少对应凭据或认证方式未知时报错
+        assert!(apply_auth_mode(&mut auth, AUTH_MODE_API_KEY).is_err());
+        assert!(apply_auth_mode(&mut json!({"OPENAI_API_KEY": "sk"}), AUTH_MODE_CHATGPT).is_err());
+        assert!(apply_auth_mode(&mut auth, "accounts").is_err());
+    }
 
     #[test]
     #[ignore = "需要使用 ProfileManager API 重写"]
@@ -0,0 +1,166 @@
+// 原生配置漂移检测
+//
+// 用户手动编辑或 CLI 自身更新原生配置后，桌面端显示的激活 Profile 会与实际不一致。
+// 本模块记录每个原生配置文件最近一次已知内容的哈希：本程序写入后立即登记，
+// 监听到文件事件时与登记值比对，哈希一致说明是自身写入（或内容未变），不视为漂移。
+// 另提供尾沿防抖器，把一次保存触发的多个文件事件合并为一次处理。
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 漂移事件名称
+pub const CONFIG_DRIFT_EVENT: &str = "config-drift-detected";
+
+/// 文件事件防抖间隔
+pub const DRIFT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 原生配置漂移事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDriftEvent {
+    /// 工具 ID
+    pub tool_id: String,
+    /// 发生变更的配置文件路径
+    pub path: String,
+    /// 检测到变更的时间
+    pub changed_at: DateTime<Utc>,
+}
+
+/// 已知配置文件内容哈希（路径 -> SHA256）
+static KNOWN_HASHES: once_cell::sync::Lazy<Mutex<HashMap<PathBuf, String>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 计算内容哈希
+fn content_hash(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+/// 读取文件当前哈希（文件不存在时为 None）
+fn file_hash(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|content| content_hash(&content))
+}
+
+/// 登记本程序写入的配置文件，后续该内容触发的文件事件不会被视为漂移
+pub fn mark_internal_write(path: &Path) {
+    match file_hash(path) {
+        Some(hash) => {
+            KNOWN_HASHES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(path.to_path_buf(), hash);
+        }
+        None => {
+            KNOWN_HASHES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(path);
+        }
+    }
+}
+
+/// 比对文件当前内容与已知哈希，内容变化时更新登记并返回 true
+///
+/// 首次见到的文件只登记不报告，避免监听启动时误报
+pub fn check_and_update(path: &Path) -> bool {
+    let current = file_hash(path);
+    let mut known = KNOWN_HASHES.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = known.get(path).cloned();
+
+    match current {
+        Some(hash) => {
+            known.insert(path.to_path_buf(), hash.clone());
+            previous.is_some_and(|prev| prev != hash)
+        }
+        None => known.remove(path).is_some(),
+    }
+}
+
+/// 尾沿防抖：同一路径在静默 `delay` 之后才交付处理
+pub struct Debouncer {
+    delay: Duration,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// 记录一次文件事件（刷新该路径的静默计时）
+    pub fn push(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path, now);
+    }
+
+    /// 取出已静默超过防抖间隔的路径
+    pub fn take_ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= self.delay)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_internal_write_is_not_drift() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, r#"{"env":{}}"#).unwrap();
+
+        // 首次见到只登记
+        assert!(!check_and_update(&path));
+
+        // 本程序写入并登记后，文件事件不视为漂移
+        fs::write(&path, r#"{"env":{"A":"1"}}"#).unwrap();
+        mark_internal_write(&path);
+        assert!(!check_and_update(&path));
+
+        // 外部修改视为漂移，且只报告一次
+        fs::write(&path, r#"{"env":{"A":"2"}}"#).unwrap();
+        assert!(check_and_update(&path));
+        assert!(!check_and_update(&path));
+
+        // 删除文件也视为漂移
+        fs::remove_file(&path).unwrap();
+        assert!(check_and_update(&path));
+    }
+
+    #[test]
+    fn test_debouncer_merges_bursts() {
+        let mut debouncer = Debouncer::new(DRIFT_DEBOUNCE);
+        let path = PathBuf::from("/tmp/settings.json");
+        let start = Instant::now();
+
+        debouncer.push(path.clone(), start);
+        debouncer.push(path.clone(), start + Duration::from_millis(300));
+        assert!(debouncer
+            .take_ready(start + Duration::from_millis(600))
+            .is_empty());
+
+        let ready = debouncer.take_ready(start + Duration::from_millis(800));
+        assert_eq!(ready, vec![path]);
+        assert!(debouncer
+            .take_ready(start + Duration::from_millis(2000))
+            .is_empty());
+    }
+}
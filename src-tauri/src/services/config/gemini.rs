@@ -87,6 +87,7 @@ pub fn save_gemini_settings(settings: &Value, env: &GeminiEnvPayload) -> Result<
         .json_uncached()
         .write(&settings_path, settings)
         .context("写入 Gemini CLI 配置失败")?;
+    super::drift::mark_internal_write(&settings_path);
 
     let mut env_pairs = read_env_pairs(&env_path)?;
     env_pairs.insert("GEMINI_API_KEY".to_string(), env.api_key.clone());
@@ -161,7 +162,9 @@ fn write_env_pairs(path: &Path, pairs: &HashMap<String, String>) -> Result<()> {
     manager
         .env()
         .write(path, pairs)
-        .map_err(|e| anyhow::anyhow!(e))
+        .map_err(|e| anyhow::anyhow!(e))?;
+    super::drift::mark_internal_write(path);
+    Ok(())
 }
 
 #[cfg(test)]
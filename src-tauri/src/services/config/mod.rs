@@ -10,6 +10,7 @@
 //! - `codex`: Codex 配置管理
 //! - `gemini`: Gemini CLI 配置管理
 //! - `watcher`: 外部变更检测与文件监听
+//! - `drift`: 原生配置漂移检测（内容哈希与防抖）
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -18,6 +19,7 @@ use serde_json::Value;
 // 模块声明
 pub mod claude;
 pub mod codex;
+pub mod drift;
 pub mod gemini;
 pub mod types;
 pub mod utils;
@@ -27,6 +29,7 @@ pub mod watcher;
 pub use types::*;
 
 // 重导出 watcher 函数
+pub use drift::{mark_internal_write, ConfigDriftEvent, CONFIG_DRIFT_EVENT};
 pub use watcher::{initialize_snapshots, start_watcher, ExternalConfigChange};
 
 /// 统一的工具配置管理接口
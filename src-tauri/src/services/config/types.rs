@@ -10,6 +10,12 @@ pub struct CodexSettingsPayload {
     pub config: Value,
     #[serde(rename = "authToken")]
     pub auth_token: Option<String>,
+    /// 当前生效的认证方式（`apikey` / `chatgpt`，无任何凭据时为 None）
+    #[serde(rename = "authMode", default)]
+    pub auth_mode: Option<String>,
+    /// 是否已使用 ChatGPT 登录（auth.json 中保存了 tokens）
+    #[serde(rename = "chatgptLoggedIn", default)]
+    pub chatgpt_logged_in: bool,
 }
 
 /// Claude Code 配置 Payload
@@ -5,7 +5,9 @@
 // 2. 监听配置文件变更（notify）
 // 3. 检测变更并发送事件到前端
 // 4. Block/Allow 操作在 commands 层实现
+// 5. 基于内容哈希识别自身写入，外部修改时发送配置漂移事件
 
+use super::drift::{self, ConfigDriftEvent, Debouncer, CONFIG_DRIFT_EVENT, DRIFT_DEBOUNCE};
 use crate::data::changelogs::ConfigChangeRecord;
 use crate::models::config::{ConfigWatchConfig, WatchMode};
 use crate::models::Tool;
@@ -473,30 +475,29 @@ pub fn start_watcher(app_handle: AppHandle) -> Result<()> {
         notify::Config::default().with_poll_interval(Duration::from_secs(scan_interval)),
     )?;
 
-    // 监听所有工具的配置目录
+    // 监听所有工具的配置目录，并登记当前内容哈希作为漂移检测基线
     for tool in &tools {
         if tool.config_dir.exists() {
             watcher.watch(&tool.config_dir, RecursiveMode::NonRecursive)?;
             tracing::debug!("开始监听配置目录: {}", tool.config_dir.display());
         }
+        for filename in tool.config_files() {
+            drift::check_and_update(&tool.config_dir.join(filename));
+        }
     }
 
     // 后台线程处理变更
     let running_clone = running.clone();
     thread::spawn(move || {
-        let mut last_check = std::collections::HashMap::new();
+        // 尾沿防抖：同一路径静默 500ms 后才处理，一次保存产生的多个事件只处理一次
+        let mut debouncer = Debouncer::new(DRIFT_DEBOUNCE);
 
         while running_clone.load(Ordering::Relaxed) {
-            if let Ok(path) = rx.recv_timeout(Duration::from_millis(500)) {
-                // 防抖：同一路径 500ms 内只处理一次
-                let now = std::time::Instant::now();
-                if let Some(last) = last_check.get(&path) {
-                    if now.duration_since(*last) < Duration::from_millis(500) {
-                        continue;
-                    }
-                }
-                last_check.insert(path.clone(), now);
+            if let Ok(path) = rx.recv_timeout(Duration::from_millis(100)) {
+                debouncer.push(path, Instant::now());
+            }
 
+            for path in debouncer.take_ready(Instant::now()) {
                 // 检测变更
                 if let Err(e) = handle_file_change(&path, &app_handle) {
                     tracing::error!("处理配置变更失败: {}", e);
@@ -545,6 +546,19 @@ fn handle_file_change(path: &Path, app_handle: &AppHandle) -> Result<()> {
         });
 
         if is_tool_config {
+            // 内容与已知哈希一致：自身写入或内容未变化
+            if !drift::check_and_update(path) {
+                tracing::debug!(tool_id = %tool.id, "配置内容未发生外部变化，跳过");
+                if let Err(error) = save_snapshot_for_tool(&tool) {
+                    tracing::warn!(
+                        error = ?error,
+                        tool_id = %tool.id,
+                        "内部写入后刷新配置快照失败"
+                    );
+                }
+                break;
+            }
+
             if is_external_detection_suppressed(&tool.id) {
                 tracing::debug!(tool_id = %tool.id, "检测到内部写入，跳过外部变更通知");
                 if let Err(error) = save_snapshot_for_tool(&tool) {
@@ -557,6 +571,16 @@ fn handle_file_change(path: &Path, app_handle: &AppHandle) -> Result<()> {
                 break;
             }
 
+            // 通知前端原生配置已被外部修改，激活 Profile 可能与实际不一致
+            app_handle.emit(
+                CONFIG_DRIFT_EVENT,
+                ConfigDriftEvent {
+                    tool_id: tool.id.clone(),
+                    path: path.to_string_lossy().to_string(),
+                    changed_at: chrono::Utc::now(),
+                },
+            )?;
+
             // 检测变更
             if let Some(change) = detect_tool_change(&tool, watch_config)? {
                 tracing::info!(
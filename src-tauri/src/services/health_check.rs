@@ -0,0 +1,418 @@
+// Health Check
+//
+// 工具健康巡检：定期检查工具安装、代理运行、原生配置有效性，
+// 汇总为健康报告并通过 `health-report` 事件推送给前端
+
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::models::{Tool, ToolStatus};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::tool::ToolRegistry;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Mutex, RwLock};
+
+/// 健康报告事件名
+pub const HEALTH_REPORT_EVENT: &str = "health-report";
+
+/// 健康等级（前端映射为绿/黄/红）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthLevel {
+    Green,
+    Yellow,
+    Red,
+}
+
+/// 单项巡检结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckItem {
+    /// 检查类别："tool" | "proxy" | "config"
+    pub category: String,
+    /// 工具 ID
+    pub tool_id: String,
+    pub level: HealthLevel,
+    pub message: String,
+}
+
+impl HealthCheckItem {
+    fn new(category: &str, tool_id: &str, level: HealthLevel, message: impl Into<String>) -> Self {
+        Self {
+            category: category.to_string(),
+            tool_id: tool_id.to_string(),
+            level,
+            message: message.into(),
+        }
+    }
+}
+
+/// 健康报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub checked_at: DateTime<Utc>,
+    /// 总体等级（取所有检查项中最差的等级）
+    pub overall: HealthLevel,
+    pub items: Vec<HealthCheckItem>,
+}
+
+impl HealthReport {
+    pub fn from_items(items: Vec<HealthCheckItem>) -> Self {
+        let overall = items
+            .iter()
+            .map(|item| item.level)
+            .max()
+            .unwrap_or(HealthLevel::Green);
+        Self {
+            checked_at: Utc::now(),
+            overall,
+            items,
+        }
+    }
+}
+
+// ==================== 巡检规则 ====================
+
+/// 检查工具安装状态
+pub fn evaluate_tool(status: &ToolStatus) -> HealthCheckItem {
+    if status.installed {
+        let version = status.version.as_deref().unwrap_or("未知版本");
+        HealthCheckItem::new(
+            "tool",
+            &status.id,
+            HealthLevel::Green,
+            format!("{} 已安装（{}）", status.name, version),
+        )
+    } else {
+        HealthCheckItem::new(
+            "tool",
+            &status.id,
+            HealthLevel::Yellow,
+            format!("{} 未安装", status.name),
+        )
+    }
+}
+
+/// 检查透明代理状态（未启用的代理返回 None）
+pub fn evaluate_proxy(
+    tool_id: &str,
+    config: &ToolProxyConfig,
+    running: bool,
+) -> Option<HealthCheckItem> {
+    if !config.enabled {
+        return None;
+    }
+
+    // amp-code 动态路由到其他工具的 Profile，不需要 real_* 字段
+    let missing_upstream =
+        tool_id != "amp-code" && (config.real_api_key.is_none() || config.real_base_url.is_none());
+
+    let item = if config.local_api_key.is_none() || missing_upstream {
+        HealthCheckItem::new(
+            "proxy",
+            tool_id,
+            HealthLevel::Red,
+            "代理配置不完整（缺少保护密钥或上游配置）",
+        )
+    } else if running {
+        HealthCheckItem::new(
+            "proxy",
+            tool_id,
+            HealthLevel::Green,
+            format!("代理运行中（端口 {}）", config.port),
+        )
+    } else if config.auto_start {
+        HealthCheckItem::new(
+            "proxy",
+            tool_id,
+            HealthLevel::Red,
+            "代理已配置自启动但未运行",
+        )
+    } else {
+        HealthCheckItem::new("proxy", tool_id, HealthLevel::Yellow, "代理已启用但未运行")
+    };
+
+    Some(item)
+}
+
+/// 检查原生配置文件是否可解析
+pub fn evaluate_config_file(tool_id: &str, path: &Path, dirty: bool) -> HealthCheckItem {
+    if !path.exists() {
+        return HealthCheckItem::new(
+            "config",
+            tool_id,
+            HealthLevel::Yellow,
+            format!("配置文件不存在: {}", path.display()),
+        );
+    }
+
+    let parse_result = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str::<serde_json::Value>(&content)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Some("toml") => content
+                .parse::<toml_edit::DocumentMut>()
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            _ => Ok(()),
+        });
+
+    match parse_result {
+        Err(e) => HealthCheckItem::new(
+            "config",
+            tool_id,
+            HealthLevel::Red,
+            format!("配置文件无效: {}", e),
+        ),
+        Ok(()) if dirty => HealthCheckItem::new(
+            "config",
+            tool_id,
+            HealthLevel::Yellow,
+            "配置文件已被外部修改，与激活的 Profile 不一致",
+        ),
+        Ok(()) => HealthCheckItem::new("config", tool_id, HealthLevel::Green, "配置文件有效"),
+    }
+}
+
+// ==================== 巡检任务 ====================
+
+/// 工具健康巡检器
+pub struct HealthInspector {
+    tool_registry: Arc<Mutex<ToolRegistry>>,
+    proxy_manager: Arc<ProxyManager>,
+    latest: Arc<RwLock<Option<HealthReport>>>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl HealthInspector {
+    pub fn new(tool_registry: Arc<Mutex<ToolRegistry>>, proxy_manager: Arc<ProxyManager>) -> Self {
+        Self {
+            tool_registry,
+            proxy_manager,
+            latest: Arc::new(RwLock::new(None)),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// 获取最近一次巡检报告
+    pub async fn latest_report(&self) -> Option<HealthReport> {
+        self.latest.read().await.clone()
+    }
+
+    /// 立即执行一次巡检并缓存结果
+    pub async fn run_once(&self) -> HealthReport {
+        let report = Self::inspect(&self.tool_registry, &self.proxy_manager).await;
+        *self.latest.write().await = Some(report.clone());
+        report
+    }
+
+    /// 启动后台巡检任务（`interval_secs` 为巡检周期）
+    pub async fn start(&self, app_handle: AppHandle, interval_secs: u64) {
+        let mut running = self.running.write().await;
+        if *running {
+            tracing::warn!("健康巡检任务已在运行");
+            return;
+        }
+        *running = true;
+        drop(running);
+
+        let tool_registry = self.tool_registry.clone();
+        let proxy_manager = self.proxy_manager.clone();
+        let latest = self.latest.clone();
+        let running = self.running.clone();
+        let interval_secs = interval_secs.max(10);
+
+        tokio::spawn(async move {
+            tracing::info!("健康巡检任务已启动（{}秒间隔）", interval_secs);
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+            loop {
+                interval.tick().await;
+
+                if !*running.read().await {
+                    tracing::info!("健康巡检任务已停止");
+                    break;
+                }
+
+                let report = Self::inspect(&tool_registry, &proxy_manager).await;
+                tracing::debug!(overall = ?report.overall, "健康巡检完成");
+                *latest.write().await = Some(report.clone());
+
+                if let Err(e) = app_handle.emit(HEALTH_REPORT_EVENT, &report) {
+                    tracing::error!(error = ?e, "发送健康报告事件失败");
+                }
+            }
+        });
+    }
+
+    /// 停止后台巡检任务
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+    }
+
+    async fn inspect(
+        tool_registry: &Arc<Mutex<ToolRegistry>>,
+        proxy_manager: &Arc<ProxyManager>,
+    ) -> HealthReport {
+        let mut items = Vec::new();
+
+        // 1. 工具安装状态
+        let statuses = {
+            let registry = tool_registry.lock().await;
+            registry.get_local_tool_status().await
+        };
+        match statuses {
+            Ok(statuses) => items.extend(statuses.iter().map(evaluate_tool)),
+            Err(e) => tracing::warn!(error = ?e, "巡检读取工具状态失败"),
+        }
+
+        // 2. 透明代理状态
+        let running_status = proxy_manager.get_all_status().await;
+        match ProxyConfigManager::new().and_then(|mgr| mgr.load_proxy_store()) {
+            Ok(store) => {
+                for tool_id in ["claude-code", "codex", "gemini-cli", "amp-code"] {
+                    if let Some(config) = store.get_config(tool_id) {
+                        let running = running_status.get(tool_id).copied().unwrap_or(false);
+                        items.extend(evaluate_proxy(tool_id, config, running));
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error = ?e, "巡检读取代理配置失败"),
+        }
+
+        // 3. 原生配置有效性
+        let profile_manager = ProfileManager::new().ok();
+        for tool in Tool::all() {
+            let dirty = profile_manager
+                .as_ref()
+                .and_then(|pm| pm.get_active_state(&tool.id).ok().flatten())
+                .map(|active| active.dirty)
+                .unwrap_or(false);
+            for filename in tool.config_files() {
+                let path = tool.config_dir.join(&filename);
+                items.push(evaluate_config_file(&tool.id, &path, dirty));
+            }
+        }
+
+        HealthReport::from_items(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn complete_proxy_config() -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(8787);
+        config.enabled = true;
+        config.local_api_key = Some("local".to_string());
+        config.real_api_key = Some("real".to_string());
+        config.real_base_url = Some("https://api.example.com".to_string());
+        config
+    }
+
+    #[test]
+    fn test_evaluate_tool() {
+        let installed = ToolStatus {
+            id: "codex".to_string(),
+            name: "CodeX".to_string(),
+            installed: true,
+            version: Some("1.0.0".to_string()),
+        };
+        assert_eq!(evaluate_tool(&installed).level, HealthLevel::Green);
+
+        let missing = ToolStatus {
+            installed: false,
+            version: None,
+            ..installed
+        };
+        assert_eq!(evaluate_tool(&missing).level, HealthLevel::Yellow);
+    }
+
+    #[test]
+    fn test_evaluate_proxy() {
+        // 未启用：不产生检查项
+        assert!(evaluate_proxy("claude-code", &ToolProxyConfig::new(8787), false).is_none());
+
+        let config = complete_proxy_config();
+        let level = |config: &ToolProxyConfig, running| {
+            evaluate_proxy("claude-code", config, running)
+                .unwrap()
+                .level
+        };
+        assert_eq!(level(&config, true), HealthLevel::Green);
+        assert_eq!(level(&config, false), HealthLevel::Yellow);
+
+        let mut auto_start = config.clone();
+        auto_start.auto_start = true;
+        assert_eq!(level(&auto_start, false), HealthLevel::Red);
+
+        let mut incomplete = config.clone();
+        incomplete.real_base_url = None;
+        assert_eq!(level(&incomplete, true), HealthLevel::Red);
+
+        // amp-code 不要求 real_* 字段
+        let mut amp = ToolProxyConfig::new(8790);
+        amp.enabled = true;
+        amp.local_api_key = Some("local".to_string());
+        assert_eq!(
+            evaluate_proxy("amp-code", &amp, true).unwrap().level,
+            HealthLevel::Green
+        );
+    }
+
+    #[test]
+    fn test_evaluate_config_file() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let missing = temp_dir.path().join("settings.json");
+        assert_eq!(
+            evaluate_config_file("claude-code", &missing, false).level,
+            HealthLevel::Yellow
+        );
+
+        let valid = temp_dir.path().join("valid.json");
+        std::fs::write(&valid, r#"{"env": {}}"#).unwrap();
+        assert_eq!(
+            evaluate_config_file("claude-code", &valid, false).level,
+            HealthLevel::Green
+        );
+        assert_eq!(
+            evaluate_config_file("claude-code", &valid, true).level,
+            HealthLevel::Yellow
+        );
+
+        let invalid_json = temp_dir.path().join("invalid.json");
+        std::fs::write(&invalid_json, "{not json").unwrap();
+        assert_eq!(
+            evaluate_config_file("claude-code", &invalid_json, false).level,
+            HealthLevel::Red
+        );
+
+        let invalid_toml = temp_dir.path().join("config.toml");
+        std::fs::write(&invalid_toml, "model = ").unwrap();
+        assert_eq!(
+            evaluate_config_file("codex", &invalid_toml, false).level,
+            HealthLevel::Red
+        );
+    }
+
+    #[test]
+    fn test_report_overall_is_worst_level() {
+        let report = HealthReport::from_items(vec![
+            HealthCheckItem::new("tool", "codex", HealthLevel::Green, "ok"),
+            HealthCheckItem::new("proxy", "codex", HealthLevel::Yellow, "warn"),
+        ]);
+        assert_eq!(report.overall, HealthLevel::Yellow);
+
+        let empty = HealthReport::from_items(Vec::new());
+        assert_eq!(empty.overall, HealthLevel::Green);
+    }
+}
@@ -193,6 +193,12 @@ impl MigrationManager {
                 startup_enabled: false,
                 config_watch: crate::models::config::ConfigWatchConfig::default(),
                 token_stats_config: crate::models::config::TokenStatsConfig::default(),
+                health_check: crate::models::config::HealthCheckConfig::default(),
+                download_cache: crate::models::config::DownloadCacheConfig::default(),
+                usage_anomaly: crate::models::config::UsageAnomalyConfig::default(),
+                budget: crate::models::config::BudgetConfig::default(),
+                session_ttl_hours: 24,
+                close_behavior: crate::models::config::CloseBehavior::default(),
             });
 
         config.version = Some(new_version.to_string());
@@ -214,6 +220,51 @@ impl MigrationManager {
         migration.execute().await
     }
 
+    /// 检测当前仍有旧数据待迁移的任务（不受配置版本限制，检测失败的迁移跳过）
+    pub async fn check_needed(&self) -> Vec<MigrationTask> {
+        let mut tasks = Vec::new();
+        for migration in &self.migrations {
+            match migration.detect().await {
+                Ok(items) if !items.is_empty() => tasks.push(MigrationTask {
+                    id: migration.id().to_string(),
+                    name: migration.name().to_string(),
+                    target_version: migration.target_version().to_string(),
+                    items,
+                }),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("检测迁移 {} 失败: {}", migration.id(), e),
+            }
+        }
+        tasks
+    }
+
+    /// 执行指定迁移任务并生成报告（执行失败记录在报告中，不返回错误）
+    pub async fn run_task(&self, migration_id: &str) -> Result<MigrationReport> {
+        let migration = self
+            .migrations
+            .iter()
+            .find(|m| m.id() == migration_id)
+            .ok_or_else(|| anyhow::anyhow!("未找到迁移: {}", migration_id))?;
+
+        tracing::info!("手动执行迁移任务: {}", migration.name());
+        let start_time = std::time::Instant::now();
+        let mut result = match migration.execute().await {
+            Ok(result) => result,
+            Err(e) => MigrationResult {
+                migration_id: migration.id().to_string(),
+                success: false,
+                message: format!("迁移失败: {:#}", e),
+                records_migrated: 0,
+                duration_secs: 0.0,
+            },
+        };
+        result.duration_secs = start_time.elapsed().as_secs_f64();
+
+        // 执行后重新检测，列出仍未迁移的项
+        let remaining = migration.detect().await.unwrap_or_default();
+        Ok(MigrationReport { result, remaining })
+    }
+
     /// 获取所有已注册的迁移
     pub fn list_migrations(&self) -> Vec<MigrationInfo> {
         self.migrations
@@ -241,6 +292,24 @@ pub struct MigrationInfo {
     pub target_version: String,
 }
 
+/// 待执行的迁移任务（迁移向导展示用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationTask {
+    pub id: String,
+    pub name: String,
+    pub target_version: String,
+    /// 检测到的待迁移项（如遗留文件路径）
+    pub items: Vec<String>,
+}
+
+/// 迁移任务执行报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub result: MigrationResult,
+    /// 执行后仍未迁移的项（为空表示已全部迁移）
+    pub remaining: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,6 +319,23 @@ mod tests {
         id: String,
         target_version: String,
         should_fail: bool,
+        pending: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl MockMigration {
+        fn new(id: &str, target_version: &str, should_fail: bool) -> Self {
+            Self {
+                id: id.to_string(),
+                target_version: target_version.to_string(),
+                should_fail,
+                pending: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        fn with_pending(self, items: &[&str]) -> Self {
+            *self.pending.lock().unwrap() = items.iter().map(|s| s.to_string()).collect();
+            self
+        }
     }
 
     #[async_trait::async_trait]
@@ -271,14 +357,19 @@ mod tests {
                 anyhow::bail!("模拟失败");
             }
 
+            let migrated = std::mem::take(&mut *self.pending.lock().unwrap());
             Ok(MigrationResult {
                 migration_id: self.id.clone(),
                 success: true,
                 message: "成功".to_string(),
-                records_migrated: 10,
+                records_migrated: migrated.len(),
                 duration_secs: 0.1,
             })
         }
+
+        async fn detect(&self) -> Result<Vec<String>> {
+            Ok(self.pending.lock().unwrap().clone())
+        }
     }
 
     #[tokio::test]
@@ -286,24 +377,51 @@ mod tests {
         let mut manager = MigrationManager::new();
 
         // 注册乱序的迁移
-        manager.register(Arc::new(MockMigration {
-            id: "migration3".to_string(),
-            target_version: "1.4.0".to_string(),
-            should_fail: false,
-        }));
-        manager.register(Arc::new(MockMigration {
-            id: "migration1".to_string(),
-            target_version: "1.3.9".to_string(),
-            should_fail: false,
-        }));
-        manager.register(Arc::new(MockMigration {
-            id: "migration2".to_string(),
-            target_version: "1.3.10".to_string(),
-            should_fail: false,
-        }));
+        manager.register(Arc::new(MockMigration::new("migration3", "1.4.0", false)));
+        manager.register(Arc::new(MockMigration::new("migration1", "1.3.9", false)));
+        manager.register(Arc::new(MockMigration::new("migration2", "1.3.10", false)));
 
         // 迁移应该按版本号排序执行
         // 实际执行需要配置环境，这里只测试注册
         assert_eq!(manager.migrations.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_check_needed_and_run_task() {
+        let mut manager = MigrationManager::new();
+        manager.register(Arc::new(
+            MockMigration::new("legacy_files", "1.5.8", false)
+                .with_pending(&["~/.claude/settings.old.json", "~/.codex/config.old.toml"]),
+        ));
+        manager.register(Arc::new(MockMigration::new("clean", "1.4.0", false)));
+        manager.register(Arc::new(
+            MockMigration::new("broken", "1.5.0", true).with_pending(&["~/.duckcoding/old"]),
+        ));
+
+        // 只列出检测到旧数据的迁移
+        let tasks = manager.check_needed().await;
+        let ids: Vec<_> = tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["legacy_files", "broken"]);
+        assert_eq!(tasks[0].items.len(), 2);
+
+        let report = manager.run_task("legacy_files").await.unwrap();
+        assert!(report.result.success);
+        assert_eq!(report.result.records_migrated, 2);
+        assert!(report.remaining.is_empty());
+
+        // 执行失败记录在报告中，待迁移项保留
+        let report = manager.run_task("broken").await.unwrap();
+        assert!(!report.result.success);
+        assert!(report.result.message.contains("模拟失败"));
+        assert_eq!(report.remaining, vec!["~/.duckcoding/old".to_string()]);
+
+        assert!(manager.run_task("missing").await.is_err());
+        let ids: Vec<_> = manager
+            .check_needed()
+            .await
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec!["broken".to_string()]);
+    }
 }
@@ -30,6 +30,14 @@ pub trait Migration: Send + Sync {
     /// 返回：迁移结果（成功/失败、记录数等）
     async fn execute(&self) -> Result<MigrationResult>;
 
+    /// 检测需要迁移的旧数据（可选实现）
+    ///
+    /// 返回待迁移项描述（如遗留文件路径），空列表表示无需迁移。
+    /// 默认实现：不支持检测，始终返回空列表
+    async fn detect(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     /// 回滚迁移（可选实现）
     ///
     /// 默认实现：不支持回滚
@@ -53,19 +61,9 @@ pub struct MigrationResult {
     pub duration_secs: f64,
 }
 
-/// 版本比较辅助函数
+/// 版本比较辅助函数（semver 优先，无法解析时逐段比较数字）
 pub fn compare_versions(v1: &str, v2: &str) -> Ordering {
-    use semver::Version;
-
-    let version1 = Version::parse(v1).ok();
-    let version2 = Version::parse(v2).ok();
-
-    match (version1, version2) {
-        (Some(ver1), Some(ver2)) => ver1.cmp(&ver2),
-        (Some(_), None) => Ordering::Greater,
-        (None, Some(_)) => Ordering::Less,
-        (None, None) => v1.cmp(v2), // 字符串比较
-    }
+    crate::utils::version::compare_versions(v1, v2)
 }
 
 #[cfg(test)]
@@ -78,5 +76,7 @@ mod tests {
         assert_eq!(compare_versions("1.3.9", "1.3.9"), Ordering::Equal);
         assert_eq!(compare_versions("1.4.0", "1.3.9"), Ordering::Greater);
         assert_eq!(compare_versions("2.0.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.4.0-beta.1", "1.4.0"), Ordering::Less);
     }
 }
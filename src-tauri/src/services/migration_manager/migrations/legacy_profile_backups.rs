@@ -0,0 +1,319 @@
+// 遗留 Profile 备份集中迁移
+//
+// 早期版本把 Profile 备份写在各 CLI 目录（settings.{name}.json、config.{name}.toml +
+// auth.{name}.json、.env.{name}），ProfileV2Migration 在 profiles.json 已存在时整体跳过，
+// 这些文件会一直残留。本迁移把残留备份合并到 ~/.duckcoding/profiles.json
+// （同名 Profile 以中央存储为准），原文件备份到 ~/.duckcoding/backup_native_profiles_<时间>/
+// 后删除，原生目录只保留当前生效配置。重复执行时没有残留文件即为空操作。
+
+use super::profile_v2::{read_claude_backups, read_codex_backups, read_gemini_backups};
+use crate::data::DataManager;
+use crate::services::migration_manager::migration_trait::{Migration, MigrationResult};
+use crate::services::profile_manager::ProfilesStore;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 代理内部 Profile 的保留前缀（与 ProfileManager 保持一致）
+const RESERVED_PREFIX: &str = "dc_proxy_";
+
+/// 遗留 Profile 备份集中迁移（目标版本 1.5.8）
+pub struct LegacyProfileBackupsMigration {
+    home_dir: Option<PathBuf>,
+}
+
+impl Default for LegacyProfileBackupsMigration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LegacyProfileBackupsMigration {
+    pub fn new() -> Self {
+        Self {
+            home_dir: dirs::home_dir(),
+        }
+    }
+
+    /// 执行迁移，返回新增到中央存储的 Profile 数量
+    fn migrate(&self, home_dir: &Path) -> Result<usize> {
+        let claude_dir = home_dir.join(".claude");
+        let codex_dir = home_dir.join(".codex");
+
+        let claude = read_claude_backups(&claude_dir)?;
+        let codex = read_codex_backups(&codex_dir)?;
+        let mut gemini = Vec::new();
+        for dir in gemini_dirs(home_dir) {
+            gemini.push(read_gemini_backups(&dir)?);
+        }
+
+        let legacy_files = legacy_file_paths(home_dir)?;
+        if legacy_files.is_empty() {
+            return Ok(0);
+        }
+
+        // 合并到中央存储（已存在的同名 Profile 保持不变）
+        let duckcoding_dir = home_dir.join(".duckcoding");
+        let profiles_path = duckcoding_dir.join("profiles.json");
+        let manager = DataManager::new();
+        let mut store: ProfilesStore = if profiles_path.exists() {
+            serde_json::from_value(manager.json_uncached().read(&profiles_path)?)
+                .context("反序列化 profiles.json 失败")?
+        } else {
+            ProfilesStore::new()
+        };
+
+        let mut added = merge_missing(&mut store.claude_code, claude);
+        added += merge_missing(&mut store.codex, codex);
+        for profiles in gemini {
+            added += merge_missing(&mut store.gemini_cli, profiles);
+        }
+
+        if added > 0 {
+            store.metadata.last_updated = Utc::now();
+            manager
+                .json_uncached()
+                .write(&profiles_path, &serde_json::to_value(&store)?)
+                .context("写入 profiles.json 失败")?;
+        }
+
+        // 先备份再删除遗留文件
+        let backup_dir = duckcoding_dir.join(format!(
+            "backup_native_profiles_{}",
+            Utc::now().format("%Y%m%d_%H%M%S")
+        ));
+        for path in legacy_files.iter().filter(|p| p.exists()) {
+            let parent = path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let target_dir = backup_dir.join(parent);
+            fs::create_dir_all(&target_dir).context("创建备份目录失败")?;
+            if let Some(file_name) = path.file_name() {
+                fs::copy(path, target_dir.join(file_name))
+                    .with_context(|| format!("备份遗留文件失败: {:?}", path))?;
+            }
+            fs::remove_file(path).with_context(|| format!("删除遗留文件失败: {:?}", path))?;
+            tracing::info!("已迁移并删除遗留 Profile 备份: {:?}", path);
+        }
+
+        tracing::info!("遗留 Profile 备份已备份到: {:?}", backup_dir);
+        Ok(added)
+    }
+}
+
+/// Gemini CLI 的新旧配置目录
+fn gemini_dirs(home_dir: &Path) -> [PathBuf; 2] {
+    [home_dir.join(".gemini"), home_dir.join(".gemini-cli")]
+}
+
+/// 列出各 CLI 目录中残留的 Profile 备份文件
+fn legacy_file_paths(home_dir: &Path) -> Result<Vec<PathBuf>> {
+    let claude_dir = home_dir.join(".claude");
+    let codex_dir = home_dir.join(".codex");
+
+    let mut legacy_files: Vec<PathBuf> = Vec::new();
+    legacy_files.extend(
+        read_claude_backups(&claude_dir)?
+            .keys()
+            .map(|name| claude_dir.join(format!("settings.{}.json", name))),
+    );
+    for name in read_codex_backups(&codex_dir)?.keys() {
+        legacy_files.push(codex_dir.join(format!("config.{}.toml", name)));
+        legacy_files.push(codex_dir.join(format!("auth.{}.json", name)));
+    }
+    for dir in gemini_dirs(home_dir) {
+        legacy_files.extend(
+            read_gemini_backups(&dir)?
+                .keys()
+                .map(|name| dir.join(format!(".env.{}", name))),
+        );
+    }
+    legacy_files.sort();
+    Ok(legacy_files)
+}
+
+/// 把中央存储中不存在的 Profile 合并进去（跳过代理内部保留名称），返回新增数量
+fn merge_missing<T>(target: &mut HashMap<String, T>, source: HashMap<String, T>) -> usize {
+    let mut added = 0;
+    for (name, profile) in source {
+        if name.starts_with(RESERVED_PREFIX) || target.contains_key(&name) {
+            continue;
+        }
+        target.insert(name, profile);
+        added += 1;
+    }
+    added
+}
+
+#[async_trait]
+impl Migration for LegacyProfileBackupsMigration {
+    fn id(&self) -> &str {
+        "legacy_profile_backups_centralize"
+    }
+
+    fn name(&self) -> &str {
+        "遗留 Profile 备份集中迁移"
+    }
+
+    fn target_version(&self) -> &str {
+        "1.5.8"
+    }
+
+    async fn execute(&self) -> Result<MigrationResult> {
+        let start_time = Instant::now();
+        let Some(home_dir) = self.home_dir.as_deref() else {
+            anyhow::bail!("无法获取用户主目录");
+        };
+
+        let count = self
+            .migrate(home_dir)
+            .context("迁移遗留 Profile 备份失败")?;
+
+        Ok(MigrationResult {
+            migration_id: self.id().to_string(),
+            success: true,
+            message: if count > 0 {
+                format!("成功将 {} 个遗留 Profile 备份迁移到 profiles.json", count)
+            } else {
+                "未发现需要迁移的遗留 Profile 备份".to_string()
+            },
+            records_migrated: count,
+            duration_secs: start_time.elapsed().as_secs_f64(),
+        })
+    }
+
+    async fn detect(&self) -> Result<Vec<String>> {
+        let Some(home_dir) = self.home_dir.as_deref() else {
+            return Ok(Vec::new());
+        };
+        Ok(legacy_file_paths(home_dir)?
+            .into_iter()
+            .filter(|path| path.exists())
+            .map(|path| path.display().to_string())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn migration(home: &Path) -> LegacyProfileBackupsMigration {
+        LegacyProfileBackupsMigration {
+            home_dir: Some(home.to_path_buf()),
+        }
+    }
+
+    fn seed_legacy_files(home: &Path) {
+        let claude = home.join(".claude");
+        let codex = home.join(".codex");
+        let gemini = home.join(".gemini");
+        for dir in [&claude, &codex, &gemini] {
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        fs::write(claude.join("settings.json"), r#"{"env":{}}"#).unwrap();
+        fs::write(
+            claude.join("settings.work.json"),
+            r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"sk-claude","ANTHROPIC_BASE_URL":"https://a.example.com"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            codex.join("config.team.toml"),
+            "model_provider = \"team\"\n[model_providers.team]\nbase_url = \"https://b.example.com/v1\"\n",
+        )
+        .unwrap();
+        fs::write(
+            codex.join("auth.team.json"),
+            r#"{"OPENAI_API_KEY":"sk-codex"}"#,
+        )
+        .unwrap();
+        fs::write(gemini.join(".env.home"), "GEMINI_API_KEY=sk-gemini\n").unwrap();
+    }
+
+    fn load_store(home: &Path) -> ProfilesStore {
+        let content = fs::read_to_string(home.join(".duckcoding/profiles.json")).unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrates_legacy_backups_and_removes_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        seed_legacy_files(home);
+
+        // 检测到全部遗留文件（Codex 备份为 config + auth 两个文件）
+        let pending = migration(home).detect().await.unwrap();
+        assert_eq!(pending.len(), 4);
+        assert!(pending.iter().any(|p| p.ends_with("settings.work.json")));
+
+        let result = migration(home).execute().await.unwrap();
+        assert_eq!(result.records_migrated, 3);
+        assert!(migration(home).detect().await.unwrap().is_empty());
+
+        let store = load_store(home);
+        assert_eq!(store.claude_code["work"].api_key, "sk-claude");
+        assert_eq!(store.codex["team"].base_url, "https://b.example.com/v1");
+        assert_eq!(store.gemini_cli["home"].api_key, "sk-gemini");
+
+        // 遗留文件已删除，当前生效配置保留
+        assert!(!home.join(".claude/settings.work.json").exists());
+        assert!(!home.join(".codex/config.team.toml").exists());
+        assert!(!home.join(".codex/auth.team.json").exists());
+        assert!(!home.join(".gemini/.env.home").exists());
+        assert!(home.join(".claude/settings.json").exists());
+
+        // 原文件已备份
+        let backup = fs::read_dir(home.join(".duckcoding"))
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .find(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .starts_with("backup_native_profiles_")
+            })
+            .expect("应生成备份目录");
+        assert!(backup.path().join(".claude/settings.work.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_migration_is_idempotent_and_keeps_existing_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        seed_legacy_files(home);
+
+        // 中央存储中已有同名 Profile，应以中央存储为准
+        let mut store = ProfilesStore::new();
+        let existing = read_claude_backups(&home.join(".claude"))
+            .unwrap()
+            .remove("work")
+            .map(|mut p| {
+                p.api_key = "sk-central".to_string();
+                p
+            })
+            .unwrap();
+        store.claude_code.insert("work".to_string(), existing);
+        fs::create_dir_all(home.join(".duckcoding")).unwrap();
+        fs::write(
+            home.join(".duckcoding/profiles.json"),
+            serde_json::to_string(&store).unwrap(),
+        )
+        .unwrap();
+
+        let first = migration(home).execute().await.unwrap();
+        assert_eq!(first.records_migrated, 2);
+        assert_eq!(load_store(home).claude_code["work"].api_key, "sk-central");
+        assert!(!home.join(".claude/settings.work.json").exists());
+
+        let second = migration(home).execute().await.unwrap();
+        assert_eq!(second.records_migrated, 0);
+        assert_eq!(load_store(home).codex.len(), 1);
+    }
+}
@@ -4,6 +4,7 @@
 
 mod balance_localstorage_to_json;
 mod global_to_providers;
+mod legacy_profile_backups;
 mod pricing_default_templates;
 mod profile_v2;
 mod proxy_config;
@@ -13,6 +14,7 @@ mod sqlite_to_json;
 
 pub use balance_localstorage_to_json::BalanceLocalstorageToJsonMigration;
 pub use global_to_providers::GlobalConfigToProvidersMigration;
+pub use legacy_profile_backups::LegacyProfileBackupsMigration;
 pub use pricing_default_templates::PricingDefaultTemplatesMigration;
 pub use profile_v2::ProfileV2Migration;
 pub use proxy_config::ProxyConfigMigration;
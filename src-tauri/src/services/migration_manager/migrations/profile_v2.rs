@@ -18,7 +18,7 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 // ==================== 迁移专用类型定义 ====================
@@ -153,80 +153,7 @@ impl ProfileV2Migration {
         let Some(home_dir) = dirs::home_dir() else {
             return Ok(HashMap::new());
         };
-        let claude_dir = home_dir.join(".claude");
-
-        if !claude_dir.exists() {
-            return Ok(HashMap::new());
-        }
-
-        let mut profiles = HashMap::new();
-        let manager = DataManager::new();
-
-        for entry in fs::read_dir(&claude_dir).context("读取 .claude 目录失败")? {
-            let entry = entry.context("读取目录项失败")?;
-            let path = entry.path();
-            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
-                continue;
-            };
-
-            // 只处理 settings.{profile}.json，排除 settings.json
-            if name == "settings.json" || !name.starts_with("settings.") || !name.ends_with(".json")
-            {
-                continue;
-            }
-
-            let profile_name = name
-                .trim_start_matches("settings.")
-                .trim_end_matches(".json")
-                .to_string();
-
-            if profile_name.is_empty() || profile_name.starts_with('.') {
-                continue;
-            }
-
-            if let Ok(settings_value) = manager.json_uncached().read(&path) {
-                let api_key = settings_value
-                    .get("ANTHROPIC_AUTH_TOKEN")
-                    .and_then(|v| v.as_str())
-                    .or_else(|| {
-                        settings_value
-                            .get("env")
-                            .and_then(|env| env.get("ANTHROPIC_AUTH_TOKEN"))
-                            .and_then(|v| v.as_str())
-                    })
-                    .unwrap_or("")
-                    .to_string();
-
-                let base_url = settings_value
-                    .get("ANTHROPIC_BASE_URL")
-                    .and_then(|v| v.as_str())
-                    .or_else(|| {
-                        settings_value
-                            .get("env")
-                            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
-                            .and_then(|v| v.as_str())
-                    })
-                    .unwrap_or("")
-                    .to_string();
-
-                if !api_key.is_empty() {
-                    let profile = ClaudeProfile {
-                        api_key,
-                        base_url,
-                        created_at: Utc::now(),
-                        updated_at: Utc::now(),
-                        raw_settings: Some(settings_value),
-                        raw_config_json: None,
-                        source: ProfileSource::Custom,
-                        pricing_template_id: None,
-                    };
-                    profiles.insert(profile_name.clone(), profile);
-                    tracing::info!("已从原始 Claude Code 配置迁移 Profile: {}", profile_name);
-                }
-            }
-        }
-
-        Ok(profiles)
+        read_claude_backups(&home_dir.join(".claude"))
     }
 
     /// 迁移 Codex 原始配置（config.{profile}.toml + auth.{profile}.json）
@@ -234,104 +161,7 @@ impl ProfileV2Migration {
         let Some(home_dir) = dirs::home_dir() else {
             return Ok(HashMap::new());
         };
-        let codex_dir = home_dir.join(".codex");
-
-        if !codex_dir.exists() {
-            return Ok(HashMap::new());
-        }
-
-        let mut profiles = HashMap::new();
-
-        for entry in fs::read_dir(&codex_dir).context("读取 .codex 目录失败")? {
-            let entry = entry.context("读取目录项失败")?;
-            let path = entry.path();
-            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
-                continue;
-            };
-
-            // 只处理 config.{profile}.toml
-            if !name.starts_with("config.") || !name.ends_with(".toml") {
-                continue;
-            }
-
-            let profile_name = name
-                .trim_start_matches("config.")
-                .trim_end_matches(".toml")
-                .to_string();
-
-            if profile_name.is_empty() || profile_name.starts_with('.') {
-                continue;
-            }
-
-            // 必须有配对的 auth.{profile}.json
-            let auth_path = codex_dir.join(format!("auth.{}.json", profile_name));
-            if !auth_path.exists() {
-                continue;
-            }
-
-            // 读取 auth.json 获取 API Key
-            let auth_content = fs::read_to_string(&auth_path).unwrap_or_default();
-            let auth_data: serde_json::Value = serde_json::from_str(&auth_content)
-                .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
-            let api_key = auth_data
-                .get("OPENAI_API_KEY")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            // 读取 config.toml 获取 provider 和 base_url
-            let mut base_url = String::new();
-            let mut provider = None;
-            let raw_config_toml = fs::read_to_string(&path).ok();
-
-            if let Some(ref content) = raw_config_toml {
-                if let Ok(toml::Value::Table(table)) = toml::from_str::<toml::Value>(content) {
-                    provider = table
-                        .get("model_provider")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string());
-
-                    if let Some(toml::Value::Table(providers)) = table.get("model_providers") {
-                        if let Some(provider_name) = provider
-                            .clone()
-                            .or_else(|| providers.keys().next().cloned())
-                        {
-                            if let Some(toml::Value::Table(provider_table)) =
-                                providers.get(&provider_name)
-                            {
-                                if let Some(toml::Value::String(url)) =
-                                    provider_table.get("base_url")
-                                {
-                                    base_url = url.clone();
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            if base_url.is_empty() {
-                base_url = "https://jp.duckcoding.com/v1".to_string();
-            }
-
-            if !api_key.is_empty() {
-                let profile = CodexProfile {
-                    api_key,
-                    base_url,
-                    wire_api: provider.unwrap_or_else(|| "responses".to_string()),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    raw_config_toml,
-                    raw_auth_json: Some(auth_data),
-                    source: ProfileSource::Custom,
-                    pricing_template_id: None,
-                };
-                profiles.insert(profile_name.clone(), profile);
-                tracing::info!("已从原始 Codex 配置迁移 Profile: {}", profile_name);
-            }
-        }
-
-        Ok(profiles)
+        read_codex_backups(&home_dir.join(".codex"))
     }
 
     /// 迁移 Gemini CLI 原始配置（.env.{profile}）
@@ -339,75 +169,7 @@ impl ProfileV2Migration {
         let Some(home_dir) = dirs::home_dir() else {
             return Ok(HashMap::new());
         };
-        let gemini_dir = home_dir.join(".gemini-cli");
-
-        if !gemini_dir.exists() {
-            return Ok(HashMap::new());
-        }
-
-        let mut profiles = HashMap::new();
-
-        for entry in fs::read_dir(&gemini_dir).context("读取 .gemini-cli 目录失败")? {
-            let entry = entry.context("读取目录项失败")?;
-            let path = entry.path();
-            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
-                continue;
-            };
-
-            // 只处理 .env.{profile}
-            if !name.starts_with(".env.") || name.len() <= 5 {
-                continue;
-            }
-
-            let profile_name = name.trim_start_matches(".env.").to_string();
-            if profile_name.is_empty() || profile_name.starts_with('.') {
-                continue;
-            }
-
-            let mut api_key = String::new();
-            let mut base_url = String::new();
-            let mut model: Option<String> = None; // 默认为空，只有文件中有值才设置
-            let raw_env = fs::read_to_string(&path).ok();
-
-            if let Some(ref content) = raw_env {
-                for line in content.lines() {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() || trimmed.starts_with('#') {
-                        continue;
-                    }
-                    if let Some((key, value)) = trimmed.split_once('=') {
-                        match key.trim() {
-                            "GEMINI_API_KEY" => api_key = value.trim().to_string(),
-                            "GOOGLE_GEMINI_BASE_URL" => base_url = value.trim().to_string(),
-                            "GEMINI_MODEL" => model = Some(value.trim().to_string()),
-                            _ => {}
-                        }
-                    }
-                }
-            }
-
-            if base_url.is_empty() {
-                base_url = "https://generativelanguage.googleapis.com".to_string();
-            }
-
-            if !api_key.is_empty() {
-                let profile = GeminiProfile {
-                    api_key,
-                    base_url,
-                    model, // 保留从文件读取的值（可能是 None）
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    raw_settings: None,
-                    raw_env,
-                    source: ProfileSource::Custom,
-                    pricing_template_id: None,
-                };
-                profiles.insert(profile_name.clone(), profile);
-                tracing::info!("已从原始 Gemini CLI 配置迁移 Profile: {}", profile_name);
-            }
-        }
-
-        Ok(profiles)
+        read_gemini_backups(&home_dir.join(".gemini-cli"))
     }
 
     /// 读取旧的 metadata/index.json
@@ -576,6 +338,7 @@ impl ProfileV2Migration {
                                 raw_env,
                                 source: ProfileSource::Custom,
                                 pricing_template_id: None,
+                                auth: None,
                             },
                         ))
                     }
@@ -759,6 +522,23 @@ impl Migration for ProfileV2Migration {
         "1.4.0"
     }
 
+    async fn detect(&self) -> Result<Vec<String>> {
+        let Some(home_dir) = dirs::home_dir() else {
+            return Ok(Vec::new());
+        };
+        let duckcoding_dir = home_dir.join(".duckcoding");
+        // profiles.json 已存在时本迁移整体跳过，残留备份由 LegacyProfileBackupsMigration 处理
+        if duckcoding_dir.join("profiles.json").exists() {
+            return Ok(Vec::new());
+        }
+        Ok(["profiles", "active", "metadata"]
+            .iter()
+            .map(|name| duckcoding_dir.join(name))
+            .filter(|dir| dir.is_dir())
+            .map(|dir| dir.display().to_string())
+            .collect())
+    }
+
     async fn execute(&self) -> Result<MigrationResult> {
         let start_time = Instant::now();
         tracing::info!("开始执行 Profile v2.0 迁移");
@@ -824,6 +604,253 @@ impl Migration for ProfileV2Migration {
     }
 }
 
+// ==================== 原始配置解析 ====================
+
+/// 读取目录中的 Claude Code 备份（settings.{profile}.json）
+pub(super) fn read_claude_backups(dir: &Path) -> Result<HashMap<String, ClaudeProfile>> {
+    if !dir.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let mut profiles = HashMap::new();
+    let manager = DataManager::new();
+
+    for entry in fs::read_dir(dir).context("读取 .claude 目录失败")? {
+        let entry = entry.context("读取目录项失败")?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        // 只处理 settings.{profile}.json，排除 settings.json
+        if name == "settings.json" || !name.starts_with("settings.") || !name.ends_with(".json") {
+            continue;
+        }
+
+        let profile_name = name
+            .trim_start_matches("settings.")
+            .trim_end_matches(".json")
+            .to_string();
+
+        if profile_name.is_empty() || profile_name.starts_with('.') {
+            continue;
+        }
+
+        if let Ok(settings_value) = manager.json_uncached().read(&path) {
+            let api_key = settings_value
+                .get("ANTHROPIC_AUTH_TOKEN")
+                .and_then(|v| v.as_str())
+                .or_else(|| {
+                    settings_value
+                        .get("env")
+                        .and_then(|env| env.get("ANTHROPIC_AUTH_TOKEN"))
+                        .and_then(|v| v.as_str())
+                })
+                .unwrap_or("")
+                .to_string();
+
+            let base_url = settings_value
+                .get("ANTHROPIC_BASE_URL")
+                .and_then(|v| v.as_str())
+                .or_else(|| {
+                    settings_value
+                        .get("env")
+                        .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+                        .and_then(|v| v.as_str())
+                })
+                .unwrap_or("")
+                .to_string();
+
+            if !api_key.is_empty() {
+                let profile = ClaudeProfile {
+                    api_key,
+                    base_url,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    raw_settings: Some(settings_value),
+                    raw_config_json: None,
+                    source: ProfileSource::Custom,
+                    pricing_template_id: None,
+                };
+                profiles.insert(profile_name.clone(), profile);
+                tracing::info!("已从原始 Claude Code 配置迁移 Profile: {}", profile_name);
+            }
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// 读取目录中的 Codex 备份（config.{profile}.toml + auth.{profile}.json）
+pub(super) fn read_codex_backups(dir: &Path) -> Result<HashMap<String, CodexProfile>> {
+    if !dir.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let mut profiles = HashMap::new();
+
+    for entry in fs::read_dir(dir).context("读取 .codex 目录失败")? {
+        let entry = entry.context("读取目录项失败")?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        // 只处理 config.{profile}.toml
+        if !name.starts_with("config.") || !name.ends_with(".toml") {
+            continue;
+        }
+
+        let profile_name = name
+            .trim_start_matches("config.")
+            .trim_end_matches(".toml")
+            .to_string();
+
+        if profile_name.is_empty() || profile_name.starts_with('.') {
+            continue;
+        }
+
+        // 必须有配对的 auth.{profile}.json
+        let auth_path = dir.join(format!("auth.{}.json", profile_name));
+        if !auth_path.exists() {
+            continue;
+        }
+
+        // 读取 auth.json 获取 API Key
+        let auth_content = fs::read_to_string(&auth_path).unwrap_or_default();
+        let auth_data: serde_json::Value = serde_json::from_str(&auth_content)
+            .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
+        let api_key = auth_data
+            .get("OPENAI_API_KEY")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        // 读取 config.toml 获取 provider 和 base_url
+        let mut base_url = String::new();
+        let mut provider = None;
+        let raw_config_toml = fs::read_to_string(&path).ok();
+
+        if let Some(ref content) = raw_config_toml {
+            if let Ok(toml::Value::Table(table)) = toml::from_str::<toml::Value>(content) {
+                provider = table
+                    .get("model_provider")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+
+                if let Some(toml::Value::Table(providers)) = table.get("model_providers") {
+                    if let Some(provider_name) = provider
+                        .clone()
+                        .or_else(|| providers.keys().next().cloned())
+                    {
+                        if let Some(toml::Value::Table(provider_table)) =
+                            providers.get(&provider_name)
+                        {
+                            if let Some(toml::Value::String(url)) = provider_table.get("base_url") {
+                                base_url = url.clone();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if base_url.is_empty() {
+            base_url = "https://jp.duckcoding.com/v1".to_string();
+        }
+
+        if !api_key.is_empty() {
+            let profile = CodexProfile {
+                api_key,
+                base_url,
+                wire_api: provider.unwrap_or_else(|| "responses".to_string()),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_config_toml,
+                raw_auth_json: Some(auth_data),
+                source: ProfileSource::Custom,
+                pricing_template_id: None,
+            };
+            profiles.insert(profile_name.clone(), profile);
+            tracing::info!("已从原始 Codex 配置迁移 Profile: {}", profile_name);
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// 读取目录中的 Gemini CLI 备份（.env.{profile}）
+pub(super) fn read_gemini_backups(dir: &Path) -> Result<HashMap<String, GeminiProfile>> {
+    if !dir.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let mut profiles = HashMap::new();
+
+    for entry in fs::read_dir(dir).context("读取 Gemini 配置目录失败")? {
+        let entry = entry.context("读取目录项失败")?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        // 只处理 .env.{profile}
+        if !name.starts_with(".env.") || name.len() <= 5 {
+            continue;
+        }
+
+        let profile_name = name.trim_start_matches(".env.").to_string();
+        if profile_name.is_empty() || profile_name.starts_with('.') {
+            continue;
+        }
+
+        let mut api_key = String::new();
+        let mut base_url = String::new();
+        let mut model: Option<String> = None; // 默认为空，只有文件中有值才设置
+        let raw_env = fs::read_to_string(&path).ok();
+
+        if let Some(ref content) = raw_env {
+            for line in content.lines() {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+                if let Some((key, value)) = trimmed.split_once('=') {
+                    match key.trim() {
+                        "GEMINI_API_KEY" => api_key = value.trim().to_string(),
+                        "GOOGLE_GEMINI_BASE_URL" => base_url = value.trim().to_string(),
+                        "GEMINI_MODEL" => model = Some(value.trim().to_string()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if base_url.is_empty() {
+            base_url = "https://generativelanguage.googleapis.com".to_string();
+        }
+
+        if !api_key.is_empty() {
+            let profile = GeminiProfile {
+                api_key,
+                base_url,
+                model, // 保留从文件读取的值（可能是 None）
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_settings: None,
+                raw_env,
+                source: ProfileSource::Custom,
+                pricing_template_id: None,
+                auth: None,
+            };
+            profiles.insert(profile_name.clone(), profile);
+            tracing::info!("已从原始 Gemini CLI 配置迁移 Profile: {}", profile_name);
+        }
+    }
+
+    Ok(profiles)
+}
+
 // ==================== 辅助类型 ====================
 
 /// 旧版 metadata/index.json 结构
@@ -244,12 +244,16 @@ fn parse_old_config(value: &Value) -> Result<ToolProxyConfig> {
         .as_object()
         .ok_or_else(|| anyhow::anyhow!("配置不是对象"))?;
 
+    let port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8787) as u16;
+    // 旧版本没有的字段沿用 ToolProxyConfig 的默认值
+    let defaults = ToolProxyConfig::new(port);
+
     Ok(ToolProxyConfig {
         enabled: obj
             .get("enabled")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
-        port: obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8787) as u16,
+        port,
         local_api_key: obj
             .get("local_api_key")
             .and_then(|v| v.as_str())
@@ -292,5 +296,113 @@ fn parse_old_config(value: &Value) -> Result<ToolProxyConfig> {
             .get("tavily_api_key")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string()),
+        danger_accept_invalid_certs: obj
+            .get("danger_accept_invalid_certs")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.danger_accept_invalid_certs),
+        max_concurrent_requests: obj
+            .get("max_concurrent_requests")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize),
+        session_priorities: obj
+            .get("session_priorities")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.session_priorities),
+        max_retries: obj
+            .get("max_retries")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32)
+            .unwrap_or(defaults.max_retries),
+        retry_backoff_ms: obj
+            .get("retry_backoff_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.retry_backoff_ms),
+        fallback_response_enabled: obj
+            .get("fallback_response_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.fallback_response_enabled),
+        fallback_message: obj
+            .get("fallback_message")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        max_body_bytes: obj
+            .get("max_body_bytes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.max_body_bytes),
+        default_max_tokens: obj
+            .get("default_max_tokens")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32),
+        openai_compat_enabled: obj
+            .get("openai_compat_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.openai_compat_enabled),
+        response_filters: obj
+            .get("response_filters")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.response_filters),
+        header_blocklist: obj
+            .get("header_blocklist")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.header_blocklist),
+        header_passthrough_override: obj
+            .get("header_passthrough_override")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        model_daily_limits: obj
+            .get("model_daily_limits")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.model_daily_limits),
+        upstream_timeout_secs: obj
+            .get("upstream_timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.upstream_timeout_secs),
+        stream_idle_timeout_secs: obj
+            .get("stream_idle_timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.stream_idle_timeout_secs),
+        pool_max_idle_per_host: obj
+            .get("pool_max_idle_per_host")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize),
+        pool_idle_timeout_secs: obj
+            .get("pool_idle_timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.pool_idle_timeout_secs),
+        allow_count_tokens: obj
+            .get("allow_count_tokens")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.allow_count_tokens),
+        upstreams: obj
+            .get("upstreams")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.upstreams),
+        model_downgrade_enabled: obj
+            .get("model_downgrade_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.model_downgrade_enabled),
+        model_downgrades: obj
+            .get("model_downgrades")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.model_downgrades),
+        slow_request_threshold_ms: obj
+            .get("slow_request_threshold_ms")
+            .and_then(|v| v.as_u64()),
+        slow_capture_sample_rate: obj
+            .get("slow_capture_sample_rate")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(defaults.slow_capture_sample_rate),
+        request_compression_enabled: obj
+            .get("request_compression_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.request_compression_enabled),
+        request_compression_min_bytes: obj
+            .get("request_compression_min_bytes")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(defaults.request_compression_min_bytes),
+        status_code_mappings: obj
+            .get("status_code_mappings")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(defaults.status_code_mappings),
     })
 }
@@ -56,6 +56,18 @@ impl Migration for SqliteToJsonMigration {
         })
     }
 
+    async fn detect(&self) -> Result<Vec<String>> {
+        let Some(home_dir) = dirs::home_dir() else {
+            return Ok(Vec::new());
+        };
+        let db_path = home_dir.join(".duckcoding").join("tool_instances.db");
+        Ok(if db_path.exists() {
+            vec![db_path.display().to_string()]
+        } else {
+            Vec::new()
+        })
+    }
+
     async fn rollback(&self) -> Result<()> {
         // SQLite → JSON 迁移支持回滚
         tracing::warn!("回滚迁移：恢复 tool_instances.db");
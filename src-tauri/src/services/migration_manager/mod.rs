@@ -6,12 +6,12 @@ mod manager;
 mod migration_trait;
 mod migrations;
 
-pub use manager::MigrationManager;
+pub use manager::{MigrationManager, MigrationReport, MigrationTask};
 pub use migration_trait::{Migration, MigrationResult};
 pub use migrations::{
     BalanceLocalstorageToJsonMigration, GlobalConfigToProvidersMigration,
-    PricingDefaultTemplatesMigration, ProfileV2Migration, ProxyConfigMigration,
-    ProxyConfigSplitMigration, SessionConfigMigration, SqliteToJsonMigration,
+    LegacyProfileBackupsMigration, PricingDefaultTemplatesMigration, ProfileV2Migration,
+    ProxyConfigMigration, ProxyConfigSplitMigration, SessionConfigMigration, SqliteToJsonMigration,
 };
 
 use std::sync::Arc;
@@ -27,6 +27,7 @@ use std::sync::Arc;
 /// - BalanceLocalstorageToJsonMigration (1.4.1) - 余额监控 LocalStorage → JSON 迁移
 /// - GlobalConfigToProvidersMigration (1.5.0) - GlobalConfig 用户信息迁移到 Providers
 /// - PricingDefaultTemplatesMigration (1.5.5) - Codex 默认模板迁移到 builtin_openai
+/// - LegacyProfileBackupsMigration (1.5.8) - 各 CLI 目录残留的 Profile 备份集中到 profiles.json
 pub fn create_migration_manager() -> MigrationManager {
     let mut manager = MigrationManager::new();
 
@@ -39,6 +40,7 @@ pub fn create_migration_manager() -> MigrationManager {
     manager.register(Arc::new(BalanceLocalstorageToJsonMigration::new()));
     manager.register(Arc::new(GlobalConfigToProvidersMigration::new()));
     manager.register(Arc::new(PricingDefaultTemplatesMigration::new()));
+    manager.register(Arc::new(LegacyProfileBackupsMigration::new()));
 
     tracing::debug!(
         "迁移管理器初始化完成，已注册 {} 个迁移",
@@ -12,13 +12,17 @@
 // - new_api: NEW API 客户端服务
 // - token_stats: Token统计和请求记录
 // - checkin: 签到服务
+// - health_check: 工具健康巡检
+// - audit_log: 配置变更审计日志
 
 pub mod amp_native_config; // AMP Code 原生配置管理
+pub mod audit_log; // 配置变更审计日志
 pub mod balance;
 pub mod checkin; // 签到服务
 pub mod checkin_scheduler; // 签到调度器
 pub mod config;
 pub mod dashboard_manager; // 仪表板状态管理
+pub mod health_check; // 工具健康巡检
 pub mod migration_manager;
 pub mod new_api; // NEW API 客户端
 pub mod pricing; // 价格配置管理
@@ -37,6 +41,7 @@ pub use checkin::*;
 pub use checkin_scheduler::CheckinScheduler;
 pub use config::types::*; // 仅导出类型
 pub use dashboard_manager::DashboardManager;
+pub use health_check::HealthInspector;
 pub use migration_manager::{create_migration_manager, MigrationManager};
 pub use new_api::NewApiClient;
 pub use profile_manager::{
@@ -1,4 +1,4 @@
-use crate::models::pricing::{ModelPrice, PricingTemplate};
+use crate::models::pricing::{FallbackPricesConfig, ModelPrice, PricingTemplate};
 use std::collections::HashMap;
 
 /// 生成内置 OpenAI/Codex 价格模板
@@ -286,6 +286,59 @@ pub fn builtin_gemini_official_template() -> PricingTemplate {
     )
 }
 
+/// 生成内置兜底价格配置
+///
+/// 各 provider 取主力中档模型的价格作为默认档位：
+/// claude 对应 Sonnet、openai 对应 GPT-5、gemini 对应 2.5 Pro（≤200K 上下文）
+pub fn builtin_fallback_prices() -> FallbackPricesConfig {
+    let mut prices = HashMap::new();
+
+    prices.insert(
+        "claude".to_string(),
+        ModelPrice::new(
+            "anthropic".to_string(),
+            3.0,
+            15.0,
+            Some(3.75), // Cache write 5m: 3.0 * 1.25
+            Some(6.0),  // Cache write 1h: 3.0 * 2.0
+            Some(0.3),  // Cache read: 3.0 * 0.1
+            None,
+            vec![],
+        ),
+    );
+    prices.insert(
+        "openai".to_string(),
+        ModelPrice::new(
+            "openai".to_string(),
+            1.25,
+            10.0,
+            None,
+            None,
+            Some(0.125),
+            None,
+            vec![],
+        ),
+    );
+    prices.insert(
+        "gemini".to_string(),
+        ModelPrice::new(
+            "google".to_string(),
+            1.25,
+            10.0,
+            None,
+            None,
+            Some(0.31),
+            None,
+            vec![],
+        ),
+    );
+
+    FallbackPricesConfig {
+        enabled: true,
+        prices,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,9 @@
 use crate::data::DataManager;
-use crate::models::pricing::{DefaultTemplatesConfig, ModelPrice, PricingTemplate};
+use crate::models::pricing::{
+    DefaultTemplatesConfig, FallbackPricesConfig, ModelPrice, PriceChange, PricingTemplate,
+};
 use crate::services::pricing::builtin::{
-    builtin_claude_official_template, builtin_gemini_official_template,
+    builtin_claude_official_template, builtin_fallback_prices, builtin_gemini_official_template,
     builtin_openai_official_template,
 };
 use crate::services::pricing::remote_sync::RemoteSyncState;
@@ -13,7 +15,10 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 #[cfg(test)]
-use crate::models::pricing::InheritedModel;
+use crate::models::pricing::{InheritedModel, PriceTier};
+
+/// 价格变更历史最多保留的记录数（超出时丢弃最旧的记录）
+const MAX_PRICE_HISTORY_ENTRIES: usize = 5000;
 
 /// 成本分解结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +49,47 @@ pub struct CostBreakdown {
 
     /// 使用的价格模板 ID
     pub template_id: String,
+
+    /// 是否为兜底估算（模型未在模板中定义，按同 provider 默认档位计价）
+    #[serde(default)]
+    pub is_estimated: bool,
+}
+
+/// 价格模板导出格式的 schema 版本
+pub const TEMPLATE_EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// 价格模板导出格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingTemplateExport {
+    /// 导出格式 schema 版本
+    pub schema_version: u32,
+
+    /// 导出时间（Unix 时间戳，毫秒）
+    pub exported_at: i64,
+
+    /// 模板内容
+    pub template: PricingTemplate,
+}
+
+/// 解析导出的模板 JSON（兼容早期不带版本号的纯模板格式）
+fn parse_template_export(data: &str) -> Result<PricingTemplate> {
+    let value: serde_json::Value =
+        serde_json::from_str(data).context("Failed to parse pricing template JSON")?;
+
+    if value.get("template").is_none() {
+        return serde_json::from_value(value).context("Invalid pricing template data");
+    }
+
+    let export: PricingTemplateExport =
+        serde_json::from_value(value).context("Invalid pricing template export data")?;
+    if export.schema_version == 0 || export.schema_version > TEMPLATE_EXPORT_SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Unsupported template schema version {} (supported: {})",
+            export.schema_version,
+            TEMPLATE_EXPORT_SCHEMA_VERSION
+        ));
+    }
+    Ok(export.template)
 }
 
 lazy_static! {
@@ -67,6 +113,9 @@ pub struct PricingManager {
 
     /// 默认模板配置文件路径
     default_templates_path: PathBuf,
+
+    /// 兜底价格配置文件路径
+    fallback_prices_path: PathBuf,
 }
 
 impl PricingManager {
@@ -86,12 +135,14 @@ impl PricingManager {
         let pricing_dir = base_dir.join("pricing");
         let templates_dir = pricing_dir.join("templates");
         let default_templates_path = pricing_dir.join("default_templates.json");
+        let fallback_prices_path = pricing_dir.join("fallback_prices.json");
 
         Self {
             data_manager,
             pricing_dir,
             templates_dir,
             default_templates_path,
+            fallback_prices_path,
         }
     }
 
@@ -215,6 +266,77 @@ impl PricingManager {
             .with_context(|| format!("Failed to delete template {}", template_id))
     }
 
+    /// 导出价格模板为带 schema 版本号的 JSON 字符串（用于备份与多机共享）
+    pub fn export_template(&self, template_id: &str) -> Result<String> {
+        let template = self.get_template(template_id)?;
+        let export = PricingTemplateExport {
+            schema_version: TEMPLATE_EXPORT_SCHEMA_VERSION,
+            exported_at: chrono::Utc::now().timestamp_millis(),
+            template,
+        };
+        serde_json::to_string_pretty(&export)
+            .with_context(|| format!("Failed to serialize template {}", template_id))
+    }
+
+    /// 从 JSON 字符串导入价格模板
+    ///
+    /// - 校验导出格式的 schema 版本（兼容早期直接导出的模板 JSON）
+    /// - ID 冲突时：`overwrite = false` 报错，`overwrite = true` 覆盖已有用户模板
+    /// - 内置模板（`is_default_preset`）不允许被覆盖，ID 冲突时另存为 `<id>_imported` 新 ID
+    /// - 保留原模板的 `created_at`，导入的模板一律作为用户模板
+    ///
+    /// 返回实际保存的模板（ID 可能已变更）
+    pub fn import_template(&self, data: &str, overwrite: bool) -> Result<PricingTemplate> {
+        let mut template = parse_template_export(data)?;
+
+        let id = template.id.trim().to_string();
+        if id.is_empty() {
+            return Err(anyhow!("Template id cannot be empty"));
+        }
+        if id.contains(['/', '\\']) {
+            return Err(anyhow!("Invalid template id: {}", id));
+        }
+
+        let existing = self.get_template(&id).ok();
+        let targets_builtin =
+            id.starts_with("builtin_") || existing.as_ref().is_some_and(|t| t.is_default_preset);
+
+        template.id = if targets_builtin {
+            self.resolve_import_id(&id)
+        } else if existing.is_some() && !overwrite {
+            return Err(anyhow!(
+                "Template {} already exists, enable overwrite to replace it",
+                id
+            ));
+        } else {
+            id
+        };
+        template.is_default_preset = false;
+        template.updated_at = chrono::Utc::now().timestamp_millis();
+
+        self.save_template(&template)?;
+        Ok(template)
+    }
+
+    /// 为导入的内置模板生成不冲突的新 ID
+    fn resolve_import_id(&self, id: &str) -> String {
+        let exists = |candidate: &str| {
+            self.templates_dir
+                .join(format!("{}.json", candidate))
+                .exists()
+        };
+
+        let base = format!("{}_imported", id);
+        if !exists(&base) {
+            return base;
+        }
+
+        (2..)
+            .map(|n| format!("{}_{}", base, n))
+            .find(|candidate| !exists(candidate))
+            .expect("unbounded suffix search always finds a free id")
+    }
+
     /// 设置工具的默认模板
     pub fn set_default_template(&self, tool_id: &str, template_id: &str) -> Result<()> {
         // 验证模板是否存在
@@ -258,6 +380,32 @@ impl PricingManager {
         serde_json::from_value(value).context("Failed to parse default templates config")
     }
 
+    /// 获取兜底价格配置（未配置时使用内置默认档位）
+    pub fn get_fallback_prices(&self) -> Result<FallbackPricesConfig> {
+        if !self.fallback_prices_path.exists() {
+            return Ok(builtin_fallback_prices());
+        }
+
+        let value = self
+            .data_manager
+            .json()
+            .read(&self.fallback_prices_path)
+            .context("Failed to read fallback prices config")?;
+
+        serde_json::from_value(value).context("Failed to parse fallback prices config")
+    }
+
+    /// 保存兜底价格配置
+    pub fn save_fallback_prices(&self, config: &FallbackPricesConfig) -> Result<()> {
+        let value =
+            serde_json::to_value(config).context("Failed to serialize fallback prices config")?;
+
+        self.data_manager
+            .json()
+            .write(&self.fallback_prices_path, &value)
+            .context("Failed to write fallback prices config")
+    }
+
     /// 加载远程同步状态
     pub fn load_sync_state(&self) -> Result<RemoteSyncState> {
         let state_path = self.pricing_dir.join("remote_sync_state.json");
@@ -286,6 +434,52 @@ impl PricingManager {
             .context("Failed to write remote sync state")
     }
 
+    /// 追加价格变更记录（price_history.json）
+    pub fn record_price_changes(&self, changes: &[PriceChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut history = self.load_price_history()?;
+        history.extend_from_slice(changes);
+        if history.len() > MAX_PRICE_HISTORY_ENTRIES {
+            let overflow = history.len() - MAX_PRICE_HISTORY_ENTRIES;
+            history.drain(..overflow);
+        }
+
+        let value = serde_json::to_value(&history).context("Failed to serialize price history")?;
+        self.data_manager
+            .json()
+            .write(&self.pricing_dir.join("price_history.json"), &value)
+            .context("Failed to write price history")
+    }
+
+    /// 获取指定模型的价格变更历史（按时间倒序）
+    pub fn get_price_history(&self, model: &str) -> Result<Vec<PriceChange>> {
+        let mut history: Vec<PriceChange> = self
+            .load_price_history()?
+            .into_iter()
+            .filter(|change| change.model == model)
+            .collect();
+        history.sort_by(|a, b| b.changed_at.cmp(&a.changed_at));
+        Ok(history)
+    }
+
+    fn load_price_history(&self) -> Result<Vec<PriceChange>> {
+        let path = self.pricing_dir.join("price_history.json");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let value = self
+            .data_manager
+            .json()
+            .read(&path)
+            .context("Failed to read price history")?;
+
+        serde_json::from_value(value).context("Failed to parse price history")
+    }
+
     /// 计算成本（核心方法）
     ///
     /// # 参数
@@ -325,8 +519,24 @@ impl PricingManager {
             self.get_default_template(default_tool_id)?
         };
 
-        // 2. 解析模型价格（别名 → 继承 → 倍率）
-        let model_price = self.resolve_model_price(&template, model)?;
+        // 2. 解析模型价格（别名 → 继承 → 倍率），均未命中时按 provider 默认档位估算
+        let (model_price, is_estimated) = match self.resolve_model_price(&template, model) {
+            Ok(price) => (price, false),
+            Err(e) => match self.resolve_fallback_price(model, tool_id) {
+                Some(price) => {
+                    tracing::debug!(
+                        model = %model,
+                        template_id = %template.id,
+                        "模型未定价，使用兜底价格估算"
+                    );
+                    (price, true)
+                }
+                None => return Err(e),
+            },
+        };
+        // 阶梯定价：按本次请求提示 Token 总数选择档位（input_tokens 不含缓存部分）
+        let model_price =
+            model_price.for_prompt_tokens(input_tokens + cache_creation_tokens + cache_read_tokens);
 
         // 3. 计算各部分价格
         let input_price = input_tokens as f64 * model_price.input_price_per_1m / 1_000_000.0;
@@ -370,9 +580,24 @@ impl PricingManager {
             reasoning_price,
             total_cost,
             template_id: template.id.clone(),
+            is_estimated,
         })
     }
 
+    /// 按模型所属 provider 查找兜底价格（未启用或无法识别 provider 时返回 None）
+    fn resolve_fallback_price(&self, model: &str, tool_id: Option<&str>) -> Option<ModelPrice> {
+        let config = self
+            .get_fallback_prices()
+            .map_err(|e| tracing::warn!("读取兜底价格配置失败: {}", e))
+            .ok()?;
+        if !config.enabled {
+            return None;
+        }
+
+        let provider = infer_provider(model, tool_id)?;
+        config.prices.get(provider).cloned()
+    }
+
     /// 解析模型价格（支持别名、继承、倍率）
     fn resolve_model_price(&self, template: &PricingTemplate, model: &str) -> Result<ModelPrice> {
         // 1. 优先查找自定义模型（直接匹配）
@@ -419,6 +644,11 @@ impl PricingManager {
                                 .map(|p| p * inherited.multiplier),
                             currency: base_price.currency,
                             aliases: base_price.aliases,
+                            tiers: base_price
+                                .tiers
+                                .iter()
+                                .map(|t| t.scaled(inherited.multiplier))
+                                .collect(),
                         });
                     }
                 }
@@ -433,6 +663,32 @@ impl PricingManager {
     }
 }
 
+/// 推断模型所属 provider（claude / openai / gemini）
+///
+/// 优先按模型名识别，无法识别时按工具 ID 推断
+fn infer_provider(model: &str, tool_id: Option<&str>) -> Option<&'static str> {
+    let model = model.to_ascii_lowercase();
+    if model.contains("claude") {
+        return Some("claude");
+    }
+    if model.contains("gemini") {
+        return Some("gemini");
+    }
+    if model.contains("gpt")
+        || model.contains("codex")
+        || ["o1", "o3", "o4"].iter().any(|p| model.starts_with(p))
+    {
+        return Some("openai");
+    }
+
+    match tool_id? {
+        "claude-code" | "amp-code" => Some("claude"),
+        "codex" => Some("openai"),
+        "gemini-cli" => Some("gemini"),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -526,6 +782,228 @@ mod tests {
         assert_eq!(breakdown.template_id, "builtin_claude");
     }
 
+    #[test]
+    fn test_unknown_model_uses_fallback_price() {
+        let (manager, _dir) = create_test_manager();
+
+        // 别名匹配仍优先于兜底（精确价格，不标记为估算）
+        let aliased = manager
+            .calculate_cost(
+                Some("builtin_claude"),
+                None,
+                "claude-sonnet-4-5",
+                1000,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        assert!(!aliased.is_estimated);
+
+        // 未知模型按同 provider 默认档位估算
+        let estimated = manager
+            .calculate_cost(
+                Some("builtin_claude"),
+                None,
+                "claude-future-9",
+                1000,
+                500,
+                0,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        assert!(estimated.is_estimated);
+        assert_eq!(estimated.input_price, 0.003);
+        assert_eq!(estimated.output_price, 0.0075);
+
+        // 模型名无法识别时按工具推断 provider
+        let by_tool = manager
+            .calculate_cost(
+                None,
+                Some("gemini-cli"),
+                "custom-model",
+                1_000_000,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        assert!(by_tool.is_estimated);
+        assert_eq!(by_tool.input_price, 1.25);
+
+        // 关闭兜底后恢复报错
+        let mut config = manager.get_fallback_prices().unwrap();
+        config.enabled = false;
+        manager.save_fallback_prices(&config).unwrap();
+        assert!(manager
+            .calculate_cost(
+                Some("builtin_claude"),
+                None,
+                "claude-future-9",
+                1000,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_tiered_pricing_across_tiers() {
+        let (manager, _dir) = create_test_manager();
+
+        let mut price = ModelPrice::new(
+            "anthropic".to_string(),
+            3.0,
+            15.0,
+            Some(3.75),
+            None,
+            Some(0.3),
+            None,
+            vec![],
+        );
+        price.tiers = vec![PriceTier {
+            min_input_tokens: 200_000,
+            input_price_per_1m: 6.0,
+            output_price_per_1m: 22.5,
+            cache_write_price_per_1m: None,
+            cache_write_1h_price_per_1m: None,
+            cache_read_price_per_1m: Some(0.5),
+        }];
+        let mut custom_models = std::collections::HashMap::new();
+        custom_models.insert("claude-long-context".to_string(), price);
+        let template = PricingTemplate::new(
+            "test_tiered".to_string(),
+            "Test Tiered".to_string(),
+            "Test".to_string(),
+            "1.0".to_string(),
+            vec![],
+            custom_models,
+            vec![],
+            false,
+        );
+        manager.save_template(&template).unwrap();
+
+        let cost_with_cache = |input_tokens: i64, cache_read_tokens: i64| {
+            manager
+                .calculate_cost(
+                    Some("test_tiered"),
+                    None,
+                    "claude-long-context",
+                    input_tokens,
+                    1000,
+                    1000,
+                    0,
+                    cache_read_tokens,
+                    0,
+                )
+                .unwrap()
+        };
+        let cost = |input_tokens: i64| cost_with_cache(input_tokens, 10_000);
+
+        // 低于阈值：使用基础价格
+        let below = cost(100_000);
+        assert_eq!(below.input_price, 0.3);
+        assert_eq!(below.output_price, 0.015);
+        assert_eq!(below.cache_read_price, 0.003);
+
+        // 达到阈值：切换到长上下文档位，未设置的缓存写入价格沿用基础价格
+        let above = cost(200_000);
+        assert_eq!(above.input_price, 1.2);
+        assert_eq!(above.output_price, 0.0225);
+        assert_eq!(above.cache_read_price, 0.005);
+        assert_eq!(above.cache_write_price, 0.00375);
+
+        // 大部分提示命中缓存：未缓存输入很少，但提示总长超过阈值仍按长上下文档位计价
+        let cache_heavy = cost_with_cache(10_000, 250_000);
+        assert_eq!(cache_heavy.input_price, 0.06);
+        assert_eq!(cache_heavy.cache_read_price, 0.125);
+        assert_eq!(cost_with_cache(10_000, 150_000).input_price, 0.03);
+
+        // 继承模型的档位价格同样应用倍率
+        let inherited = PricingTemplate::new(
+            "test_tiered_inherited".to_string(),
+            "Test Tiered Inherited".to_string(),
+            "Test".to_string(),
+            "1.0".to_string(),
+            vec![InheritedModel::new(
+                "claude-long-context".to_string(),
+                "test_tiered".to_string(),
+                2.0,
+            )],
+            Default::default(),
+            vec![],
+            false,
+        );
+        let resolved = manager
+            .resolve_model_price(&inherited, "claude-long-context")
+            .unwrap()
+            .for_prompt_tokens(300_000);
+        assert_eq!(resolved.input_price_per_1m, 12.0);
+        assert_eq!(resolved.output_price_per_1m, 45.0);
+    }
+
+    #[test]
+    fn test_record_and_query_price_history() {
+        let (manager, _dir) = create_test_manager();
+        assert!(manager
+            .get_price_history("claude-sonnet-4-5")
+            .unwrap()
+            .is_empty());
+
+        let price = |input: f64| {
+            ModelPrice::new(
+                "anthropic".to_string(),
+                input,
+                input * 5.0,
+                None,
+                None,
+                None,
+                None,
+                vec![],
+            )
+        };
+        let change = |model: &str, old: Option<f64>, new: f64, at: i64| PriceChange {
+            model: model.to_string(),
+            template_id: "builtin_claude".to_string(),
+            old_price: old.map(price),
+            new_price: price(new),
+            changed_at: at,
+        };
+
+        manager
+            .record_price_changes(&[
+                change("claude-sonnet-4-5", None, 3.0, 1000),
+                change("claude-haiku-4-5", None, 1.0, 1000),
+            ])
+            .unwrap();
+        manager
+            .record_price_changes(&[change("claude-sonnet-4-5", Some(3.0), 2.5, 2000)])
+            .unwrap();
+
+        let history = manager.get_price_history("claude-sonnet-4-5").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].changed_at, 2000);
+        assert_eq!(
+            history[0].old_price.as_ref().unwrap().input_price_per_1m,
+            3.0
+        );
+        assert_eq!(history[0].new_price.input_price_per_1m, 2.5);
+        assert!(history[1].old_price.is_none());
+        assert_eq!(
+            manager.get_price_history("claude-haiku-4-5").unwrap().len(),
+            1
+        );
+    }
+
     #[test]
     fn test_multi_source_inheritance() {
         let (manager, _dir) = create_test_manager();
@@ -644,4 +1122,120 @@ mod tests {
             .to_string()
             .contains("Cannot delete built-in preset template"));
     }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let (manager, _dir) = create_test_manager();
+
+        let mut template = PricingTemplate::new(
+            "team_shared".to_string(),
+            "Team Shared".to_string(),
+            "Shared across machines".to_string(),
+            "1.0".to_string(),
+            vec![],
+            Default::default(),
+            vec!["team".to_string()],
+            false,
+        );
+        template.created_at = 1_700_000_000_000;
+        manager.save_template(&template).unwrap();
+
+        let exported = manager.export_template("team_shared").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&exported).unwrap();
+        assert_eq!(value["schema_version"], TEMPLATE_EXPORT_SCHEMA_VERSION);
+
+        // 在另一台机器（新目录）导入，保留 created_at
+        let (other, _other_dir) = create_test_manager();
+        let imported = other.import_template(&exported, false).unwrap();
+        assert_eq!(imported.id, "team_shared");
+        assert_eq!(imported.name, "Team Shared");
+        assert_eq!(imported.tags, vec!["team".to_string()]);
+        assert_eq!(imported.created_at, 1_700_000_000_000);
+        assert!(other.get_template("team_shared").is_ok());
+
+        // 兼容早期不带版本号的导出格式
+        let legacy = serde_json::to_string(&template).unwrap();
+        let (third, _third_dir) = create_test_manager();
+        assert_eq!(
+            third.import_template(&legacy, false).unwrap().id,
+            "team_shared"
+        );
+    }
+
+    #[test]
+    fn test_import_conflict_requires_overwrite() {
+        let (manager, _dir) = create_test_manager();
+
+        let template = PricingTemplate::new(
+            "relay".to_string(),
+            "Relay".to_string(),
+            "Relay pricing".to_string(),
+            "1.0".to_string(),
+            vec![],
+            Default::default(),
+            vec![],
+            false,
+        );
+        manager.save_template(&template).unwrap();
+
+        let mut changed = manager.get_template("relay").unwrap();
+        changed.name = "Relay v2".to_string();
+        let data = serde_json::to_string(&PricingTemplateExport {
+            schema_version: TEMPLATE_EXPORT_SCHEMA_VERSION,
+            exported_at: 0,
+            template: changed,
+        })
+        .unwrap();
+
+        // overwrite = false 时 ID 冲突报错，原模板不变
+        assert!(manager.import_template(&data, false).is_err());
+        assert_eq!(manager.get_template("relay").unwrap().name, "Relay");
+
+        // overwrite = true 时覆盖
+        let imported = manager.import_template(&data, true).unwrap();
+        assert_eq!(imported.id, "relay");
+        assert_eq!(manager.get_template("relay").unwrap().name, "Relay v2");
+    }
+
+    #[test]
+    fn test_import_cannot_overwrite_builtin_template() {
+        let (manager, _dir) = create_test_manager();
+
+        let mut builtin = manager.get_template("builtin_claude").unwrap();
+        builtin.name = "Hijacked".to_string();
+        let data = serde_json::to_string(&builtin).unwrap();
+
+        // 即使 overwrite = true 也只能另存为新 ID
+        let imported = manager.import_template(&data, true).unwrap();
+        assert_eq!(imported.id, "builtin_claude_imported");
+        assert!(!imported.is_default_preset);
+        let again = manager.import_template(&data, true).unwrap();
+        assert_eq!(again.id, "builtin_claude_imported_2");
+
+        let original = manager.get_template("builtin_claude").unwrap();
+        assert_ne!(original.name, "Hijacked");
+        assert!(original.is_default_preset);
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_data() {
+        let (manager, _dir) = create_test_manager();
+
+        assert!(manager.import_template("not json", false).is_err());
+
+        let mut template = manager.get_template("builtin_openai").unwrap();
+        template.id = "../escape".to_string();
+        let data = serde_json::to_string(&template).unwrap();
+        assert!(manager.import_template(&data, false).is_err());
+
+        // 不支持的 schema 版本
+        let template = manager.get_template("builtin_openai").unwrap();
+        let data = serde_json::to_string(&PricingTemplateExport {
+            schema_version: TEMPLATE_EXPORT_SCHEMA_VERSION + 1,
+            exported_at: 0,
+            template,
+        })
+        .unwrap();
+        assert!(manager.import_template(&data, false).is_err());
+    }
 }
@@ -1,5 +1,5 @@
-use crate::http_client::build_client;
-use crate::models::pricing::{ModelPrice, PricingTemplate};
+use crate::core::http::get_global_client;
+use crate::models::pricing::{ModelPrice, PriceChange, PricingTemplate};
 use crate::services::pricing::PRICING_MANAGER;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,49 @@ pub struct RemoteSyncState {
     pub etag: Option<String>,
     pub last_modified: Option<String>,
     pub last_success_at: Option<i64>,
+    /// 最近一次同步失败的错误信息（成功后清空）
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// 连续失败次数（成功后清零）
+    #[serde(default)]
+    pub consecutive_failures: u32,
+}
+
+impl RemoteSyncState {
+    /// 记录一次成功同步（含 304：本地数据已是最新）
+    pub fn record_success(&mut self, now_ms: i64) {
+        self.last_success_at = Some(now_ms);
+        self.last_error = None;
+        self.consecutive_failures = 0;
+    }
+
+    /// 记录一次失败同步（保留 etag 与上次成功时间）
+    pub fn record_failure(&mut self, error: String) {
+        self.last_error = Some(error);
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+}
+
+/// 价格同步状态（供设置页展示数据是否过期）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingSyncStatus {
+    /// 上次成功同步时间（Unix 时间戳，毫秒）
+    pub last_success_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    /// 距上次成功同步的时长（毫秒，从未成功时为 None）
+    pub elapsed_since_success_ms: Option<i64>,
+}
+
+impl PricingSyncStatus {
+    pub fn from_state(state: &RemoteSyncState, now_ms: i64) -> Self {
+        Self {
+            last_success_at: state.last_success_at,
+            last_error: state.last_error.clone(),
+            consecutive_failures: state.consecutive_failures,
+            elapsed_since_success_ms: state.last_success_at.map(|t| (now_ms - t).max(0)),
+        }
+    }
 }
 
 /// 远程模型定价数据（宽松解析，所有字段可选）
@@ -29,11 +72,26 @@ struct RemoteModelData {
 
 /// 从远程同步最新模型定价数据并更新本地内置模板
 ///
-/// 返回 Ok(true) 表示有更新，Ok(false) 表示无需更新（304）
+/// 返回 Ok(true) 表示有更新，Ok(false) 表示无需更新（304）。
+/// 失败时保留已有模板与上次成功时间，仅累加失败次数并记录错误
 pub async fn sync_remote_prices() -> Result<bool> {
-    let client = build_client().map_err(|e| anyhow::anyhow!(e))?;
+    let mut state = PRICING_MANAGER.load_sync_state().unwrap_or_default();
 
-    let state = PRICING_MANAGER.load_sync_state().unwrap_or_default();
+    let result = fetch_and_apply_remote_prices(&mut state).await;
+    match &result {
+        Ok(_) => state.record_success(chrono::Utc::now().timestamp_millis()),
+        Err(e) => state.record_failure(format!("{:#}", e)),
+    }
+    if let Err(e) = PRICING_MANAGER.save_sync_state(&state) {
+        tracing::warn!("保存远程同步状态失败: {}", e);
+    }
+
+    result
+}
+
+/// 拉取远程定价并写入内置模板（成功时更新 state 中的 etag / last_modified）
+async fn fetch_and_apply_remote_prices(state: &mut RemoteSyncState) -> Result<bool> {
+    let client = get_global_client()?;
 
     let mut request = client.get(REMOTE_URL);
     if let Some(etag) = &state.etag {
@@ -107,56 +165,80 @@ pub async fn sync_remote_prices() -> Result<bool> {
         }
     }
 
-    let mut updated_count = 0;
-
-    // 生成并保存 Anthropic 模板
-    if !anthropic_models.is_empty() {
-        let existing = PRICING_MANAGER.get_template("builtin_claude").ok();
-        let template =
-            build_template_from_remote("anthropic", &anthropic_models, existing.as_ref());
-        PRICING_MANAGER
-            .save_template(&template)
-            .context("保存远程 Anthropic 价格模板失败")?;
-        updated_count += anthropic_models.len();
-        tracing::info!("同步 Anthropic 模型定价：{} 个模型", anthropic_models.len());
+    // 远程数据中没有任何可用定价时视为异常，保留现有模板
+    if anthropic_models.is_empty() && openai_models.is_empty() && gemini_models.is_empty() {
+        anyhow::bail!("远程价格数据中没有可用的模型定价，保留现有模板");
     }
 
-    // 生成并保存 OpenAI 模板
-    if !openai_models.is_empty() {
-        let existing = PRICING_MANAGER.get_template("builtin_openai").ok();
-        let template = build_template_from_remote("openai", &openai_models, existing.as_ref());
-        PRICING_MANAGER
-            .save_template(&template)
-            .context("保存远程 OpenAI 价格模板失败")?;
-        updated_count += openai_models.len();
-        tracing::info!("同步 OpenAI 模型定价：{} 个模型", openai_models.len());
-    }
+    // 先构建全部模板再逐个写入（写入为临时文件 + rename，失败不会损坏已有模板）
+    let templates: Vec<(&str, PricingTemplate, usize)> = [
+        ("anthropic", "builtin_claude", &anthropic_models),
+        ("openai", "builtin_openai", &openai_models),
+        ("gemini", "builtin_gemini", &gemini_models),
+    ]
+    .into_iter()
+    .filter(|(_, _, models)| !models.is_empty())
+    .map(|(provider, template_id, models)| {
+        let existing = PRICING_MANAGER.get_template(template_id).ok();
+        let template = build_template_from_remote(provider, models, existing.as_ref());
+        let changes = diff_template_prices(existing.as_ref(), &template, template.updated_at);
+        (provider, template, changes)
+    })
+    .collect();
 
-    // 生成并保存 Gemini 模板
-    if !gemini_models.is_empty() {
-        let existing = PRICING_MANAGER.get_template("builtin_gemini").ok();
-        let template = build_template_from_remote("gemini", &gemini_models, existing.as_ref());
+    let mut updated_count = 0;
+    for (provider, template, changes) in &templates {
         PRICING_MANAGER
-            .save_template(&template)
-            .context("保存远程 Gemini 价格模板失败")?;
-        updated_count += gemini_models.len();
-        tracing::info!("同步 Gemini 模型定价：{} 个模型", gemini_models.len());
-    }
+            .save_template(template)
+            .with_context(|| format!("保存远程 {} 价格模板失败", provider))?;
+        updated_count += template.custom_models.len();
+        tracing::info!(
+            "同步 {} 模型定价：{} 个模型，{} 个价格变更",
+            provider,
+            template.custom_models.len(),
+            changes.len()
+        );
 
-    // 更新同步状态
-    let new_state = RemoteSyncState {
-        etag: new_etag,
-        last_modified: new_last_modified,
-        last_success_at: Some(chrono::Utc::now().timestamp_millis()),
-    };
-    if let Err(e) = PRICING_MANAGER.save_sync_state(&new_state) {
-        tracing::warn!("保存远程同步状态失败: {}", e);
+        // 价格历史仅用于展示，记录失败不影响同步结果
+        if let Err(e) = PRICING_MANAGER.record_price_changes(changes) {
+            tracing::warn!("记录 {} 价格变更历史失败: {}", provider, e);
+        }
     }
 
+    state.etag = new_etag;
+    state.last_modified = new_last_modified;
+
     tracing::info!("远程价格同步完成，共更新 {} 个模型", updated_count);
     Ok(true)
 }
 
+/// 对比同步前后的模板，生成价格变更记录（新增模型记为旧价为空）
+fn diff_template_prices(
+    existing: Option<&PricingTemplate>,
+    updated: &PricingTemplate,
+    now_ms: i64,
+) -> Vec<PriceChange> {
+    let mut changes: Vec<PriceChange> = updated
+        .custom_models
+        .iter()
+        .filter_map(|(model, new_price)| {
+            let old_price = existing.and_then(|t| t.custom_models.get(model));
+            if old_price.is_some_and(|old| old.same_price(new_price)) {
+                return None;
+            }
+            Some(PriceChange {
+                model: model.clone(),
+                template_id: updated.id.clone(),
+                old_price: old_price.cloned(),
+                new_price: new_price.clone(),
+                changed_at: now_ms,
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.model.cmp(&b.model));
+    changes
+}
+
 /// 从远程数据构建内置价格模板
 fn build_template_from_remote(
     provider: &str,
@@ -212,6 +294,7 @@ fn build_template_from_remote(
 
         let aliases = generate_aliases(key);
 
+        // 阶梯定价暂不从远程同步，仅支持用户手动配置
         let model_price = ModelPrice::new(
             provider.to_string(),
             input_per_1m,
@@ -376,6 +459,87 @@ pub async fn start_sync_scheduler() {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sync_state_tracks_failures() {
+        let mut state = RemoteSyncState {
+            etag: Some("\"abc\"".to_string()),
+            last_success_at: Some(1_000),
+            ..Default::default()
+        };
+
+        state.record_failure("timeout".to_string());
+        state.record_failure("HTTP 502".to_string());
+        assert_eq!(state.consecutive_failures, 2);
+        assert_eq!(state.last_error.as_deref(), Some("HTTP 502"));
+        // 失败不影响上次成功时间与 etag
+        assert_eq!(state.last_success_at, Some(1_000));
+        assert!(state.etag.is_some());
+
+        let status = PricingSyncStatus::from_state(&state, 3_600_000 + 1_000);
+        assert_eq!(status.elapsed_since_success_ms, Some(3_600_000));
+        assert_eq!(status.consecutive_failures, 2);
+
+        state.record_success(5_000);
+        assert_eq!(state.consecutive_failures, 0);
+        assert_eq!(state.last_error, None);
+        assert_eq!(state.last_success_at, Some(5_000));
+
+        // 旧版本状态文件缺少新字段时使用默认值
+        let legacy: RemoteSyncState =
+            serde_json::from_str(r#"{"etag":null,"last_modified":null,"last_success_at":42}"#)
+                .unwrap();
+        assert_eq!(legacy.consecutive_failures, 0);
+        assert_eq!(
+            PricingSyncStatus::from_state(&RemoteSyncState::default(), 0).elapsed_since_success_ms,
+            None
+        );
+    }
+
+    #[test]
+    fn test_diff_template_prices_records_changes() {
+        let remote = |input: f64, output: f64| RemoteModelData {
+            litellm_provider: Some("anthropic".to_string()),
+            input_cost_per_token: Some(input / 1_000_000.0),
+            output_cost_per_token: Some(output / 1_000_000.0),
+            cache_creation_input_token_cost: None,
+            cache_read_input_token_cost: None,
+            reasoning_cost_per_token: None,
+            mode: Some("chat".to_string()),
+        };
+
+        let (sonnet, haiku) = (remote(3.0, 15.0), remote(1.0, 5.0));
+        let before: HashMap<String, &RemoteModelData> = HashMap::from([
+            ("claude-sonnet-4-5".to_string(), &sonnet),
+            ("claude-haiku-4-5".to_string(), &haiku),
+        ]);
+        let existing = build_template_from_remote("anthropic", &before, None);
+
+        // 首次同步：所有模型都记为新增
+        let initial = diff_template_prices(None, &existing, 1_000);
+        assert_eq!(initial.len(), 2);
+        assert!(initial.iter().all(|c| c.old_price.is_none()));
+
+        // sonnet 降价、haiku 不变、新增 opus
+        let (sonnet_new, opus) = (remote(2.5, 12.0), remote(5.0, 25.0));
+        let after: HashMap<String, &RemoteModelData> = HashMap::from([
+            ("claude-sonnet-4-5".to_string(), &sonnet_new),
+            ("claude-haiku-4-5".to_string(), &haiku),
+            ("claude-opus-4-5".to_string(), &opus),
+        ]);
+        let updated = build_template_from_remote("anthropic", &after, Some(&existing));
+        let changes = diff_template_prices(Some(&existing), &updated, 2_000);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].model, "claude-opus-4-5");
+        assert!(changes[0].old_price.is_none());
+        assert_eq!(changes[1].model, "claude-sonnet-4-5");
+        assert_eq!(changes[1].template_id, "builtin_claude");
+        assert_eq!(changes[1].changed_at, 2_000);
+        let old = changes[1].old_price.as_ref().unwrap();
+        assert!((old.input_price_per_1m - 3.0).abs() < 1e-9);
+        assert!((changes[1].new_price.output_price_per_1m - 12.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_generate_aliases_with_date_suffix() {
         let aliases = generate_aliases("claude-sonnet-4-5-20250929");
@@ -0,0 +1,263 @@
+//! 多工具配置一致性检查
+//!
+//! 比对各工具当前激活 Profile 的 base_url 是否指向同一供应商，
+//! 以出现次数最多的供应商域名为基准（并列时按工具顺序取先出现者），报告偏离基准的工具。
+//! 透明代理运行时激活的是内置 `dc_proxy_*` Profile，此时改用代理配置中的真实 base_url。
+
+use super::manager::{ProfileManager, RESERVED_PREFIX};
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// 参与检查的工具（AMP 复用其他工具的 Profile，不单独检查）
+const CHECKED_TOOLS: &[&str] = &["claude-code", "codex", "gemini-cli"];
+
+/// 配置不一致项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inconsistency {
+    pub tool_id: String,
+    /// 当前激活的 Profile 名称
+    pub profile_name: String,
+    pub base_url: String,
+    /// 该工具指向的供应商域名
+    pub provider_domain: String,
+    /// 多数工具指向的供应商域名
+    pub expected_domain: String,
+}
+
+/// 工具当前使用的端点
+#[derive(Debug, Clone)]
+struct ToolEndpoint {
+    tool_id: String,
+    profile_name: String,
+    base_url: String,
+}
+
+impl ProfileManager {
+    /// 检查各工具当前配置是否指向同一供应商，返回不一致的工具列表
+    pub fn check_config_consistency(&self) -> Result<Vec<Inconsistency>> {
+        let endpoints = self.active_endpoints()?;
+        Ok(find_inconsistencies(&endpoints))
+    }
+
+    /// 收集各工具激活 Profile 的 base_url（未激活或无 base_url 的工具跳过）
+    fn active_endpoints(&self) -> Result<Vec<ToolEndpoint>> {
+        let store = self.load_profiles_store()?;
+        let active_store = self.load_active_store()?;
+
+        let mut endpoints = Vec::new();
+        for tool_id in CHECKED_TOOLS {
+            let Some(active) = active_store.get_active(tool_id) else {
+                continue;
+            };
+            let profile_name = active.profile.clone();
+
+            let base_url = if profile_name.starts_with(RESERVED_PREFIX) {
+                proxy_real_base_url(tool_id)
+            } else {
+                match *tool_id {
+                    "claude-code" => store
+                        .claude_code
+                        .get(&profile_name)
+                        .map(|p| p.base_url.clone()),
+                    "codex" => store.codex.get(&profile_name).map(|p| p.base_url.clone()),
+                    _ => store
+                        .gemini_cli
+                        .get(&profile_name)
+                        .map(|p| p.base_url.clone()),
+                }
+            };
+
+            if let Some(base_url) = base_url.filter(|url| !url.trim().is_empty()) {
+                endpoints.push(ToolEndpoint {
+                    tool_id: tool_id.to_string(),
+                    profile_name,
+                    base_url,
+                });
+            }
+        }
+        Ok(endpoints)
+    }
+}
+
+/// 读取透明代理转发的真实 base_url
+fn proxy_real_base_url(tool_id: &str) -> Option<String> {
+    ProxyConfigManager::new()
+        .and_then(|manager| manager.get_config(tool_id))
+        .ok()
+        .flatten()
+        .and_then(|config| config.real_base_url)
+}
+
+/// 提取 base_url 的供应商域名
+///
+/// 取主机名的最后两级（如 `jp.duckcoding.com` → `duckcoding.com`），
+/// 同一供应商的不同线路视为一致；IP 地址保持原样
+fn provider_domain(base_url: &str) -> Option<String> {
+    let trimmed = base_url.trim();
+    let parsed = Url::parse(trimmed)
+        .ok()
+        .filter(|url| url.has_host())
+        .or_else(|| Url::parse(&format!("https://{trimmed}")).ok())?;
+
+    let host = match parsed.host()? {
+        url::Host::Domain(domain) => domain.trim_end_matches('.').to_ascii_lowercase(),
+        ip => return Some(ip.to_string()),
+    };
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() <= 2 {
+        return Some(host);
+    }
+    Some(labels[labels.len() - 2..].join("."))
+}
+
+/// 以多数供应商为基准找出不一致的工具
+fn find_inconsistencies(endpoints: &[ToolEndpoint]) -> Vec<Inconsistency> {
+    let resolved: Vec<(&ToolEndpoint, String)> = endpoints
+        .iter()
+        .filter_map(|endpoint| provider_domain(&endpoint.base_url).map(|d| (endpoint, d)))
+        .collect();
+
+    // 按首次出现顺序统计，保证并列时结果稳定
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for (_, domain) in &resolved {
+        match counts.iter().position(|(d, _)| *d == domain.as_str()) {
+            Some(index) => counts[index].1 += 1,
+            None => counts.push((domain.as_str(), 1)),
+        }
+    }
+    if counts.len() <= 1 {
+        return Vec::new();
+    }
+
+    let mut expected = counts[0];
+    for entry in &counts[1..] {
+        if entry.1 > expected.1 {
+            expected = *entry;
+        }
+    }
+    let expected_domain = expected.0.to_string();
+
+    resolved
+        .iter()
+        .filter(|(_, domain)| *domain != expected_domain)
+        .map(|(endpoint, domain)| Inconsistency {
+            tool_id: endpoint.tool_id.clone(),
+            profile_name: endpoint.profile_name.clone(),
+            base_url: endpoint.base_url.clone(),
+            provider_domain: domain.clone(),
+            expected_domain: expected_domain.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::profile_manager::types::ActiveStore;
+    use tempfile::TempDir;
+
+    fn endpoint(tool_id: &str, base_url: &str) -> ToolEndpoint {
+        ToolEndpoint {
+            tool_id: tool_id.to_string(),
+            profile_name: "default".to_string(),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_provider_domain() {
+        assert_eq!(
+            provider_domain("https://jp.duckcoding.com/v1").as_deref(),
+            Some("duckcoding.com")
+        );
+        assert_eq!(
+            provider_domain("https://DuckCoding.com").as_deref(),
+            Some("duckcoding.com")
+        );
+        // 缺少协议时按 https 解析
+        assert_eq!(
+            provider_domain("api.example.com/v1").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            provider_domain("http://127.0.0.1:3000").as_deref(),
+            Some("127.0.0.1")
+        );
+        assert_eq!(provider_domain(""), None);
+    }
+
+    #[test]
+    fn test_find_inconsistencies() {
+        // 同一供应商的不同线路视为一致
+        let consistent = vec![
+            endpoint("claude-code", "https://jp.duckcoding.com"),
+            endpoint("codex", "https://duckcoding.com/v1"),
+            endpoint("gemini-cli", "https://api.duckcoding.com"),
+        ];
+        assert!(find_inconsistencies(&consistent).is_empty());
+
+        let mismatched = vec![
+            endpoint("claude-code", "https://jp.duckcoding.com"),
+            endpoint("codex", "https://api.other.com/v1"),
+            endpoint("gemini-cli", "https://duckcoding.com"),
+        ];
+        let result = find_inconsistencies(&mismatched);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tool_id, "codex");
+        assert_eq!(result[0].provider_domain, "other.com");
+        assert_eq!(result[0].expected_domain, "duckcoding.com");
+
+        // 并列时以先出现的工具为基准
+        let tie = vec![
+            endpoint("claude-code", "https://duckcoding.com"),
+            endpoint("codex", "https://other.com"),
+        ];
+        let result = find_inconsistencies(&tie);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tool_id, "codex");
+
+        // 单个工具无从比较
+        assert!(find_inconsistencies(&[endpoint("codex", "https://other.com")]).is_empty());
+    }
+
+    #[test]
+    fn test_check_config_consistency_uses_active_profiles() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ProfileManager::with_paths(temp_dir.path());
+
+        manager.save_claude_profile("dc", "sk-1".into(), "https://duckcoding.com".into())?;
+        manager.save_claude_profile("other", "sk-2".into(), "https://other.com".into())?;
+        manager.save_codex_profile(
+            "dc",
+            "sk-3".into(),
+            "https://jp.duckcoding.com/v1".into(),
+            None,
+        )?;
+        manager.save_gemini_profile(
+            "other",
+            "sk-4".into(),
+            "https://api.other.com".into(),
+            None,
+        )?;
+
+        let mut active = ActiveStore::new();
+        active.set_active("claude-code", "dc".to_string());
+        active.set_active("codex", "dc".to_string());
+        active.set_active("gemini-cli", "other".to_string());
+        manager.save_active_store(&active)?;
+
+        let result = manager.check_config_consistency()?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tool_id, "gemini-cli");
+        assert_eq!(result[0].profile_name, "other");
+        assert_eq!(result[0].base_url, "https://api.other.com");
+
+        // 激活的 Profile 不存在时跳过该工具
+        active.set_active("gemini-cli", "missing".to_string());
+        manager.save_active_store(&active)?;
+        assert!(manager.check_config_consistency()?.is_empty());
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 
 use super::types::*;
 use crate::data::DataManager;
+use crate::services::audit_log;
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
 use fs2::FileExt;
@@ -9,10 +10,10 @@ use std::fs::File;
 use std::path::PathBuf;
 
 /// 系统保留的 Profile 名称前缀
-const RESERVED_PREFIX: &str = "dc_proxy_";
+pub(super) const RESERVED_PREFIX: &str = "dc_proxy_";
 
 /// 校验 Profile 名称是否使用保留前缀
-fn validate_profile_name(name: &str) -> Result<()> {
+pub(super) fn validate_profile_name(name: &str) -> Result<()> {
     if name.starts_with(RESERVED_PREFIX) {
         return Err(anyhow!(
             "Profile 名称不能以 '{}' 开头（系统保留前缀）",
@@ -41,6 +42,28 @@ impl ProfileManager {
         })
     }
 
+    /// 使用指定目录创建（测试用）
+    #[cfg(test)]
+    pub(super) fn with_paths(dir: &std::path::Path) -> Self {
+        Self {
+            data_manager: DataManager::new(),
+            profiles_path: dir.join("profiles.json"),
+            active_path: dir.join("active.json"),
+        }
+    }
+
+    /// 记录配置变更审计（写入 profiles.json 所在目录的审计日志）
+    pub(super) fn audit(&self, action: &str, tool_id: &str, profile: Option<&str>) {
+        if let Some(dir) = self.profiles_path.parent() {
+            audit_log::record_to(
+                &dir.join(audit_log::AUDIT_LOG_FILE),
+                action,
+                tool_id,
+                profile,
+            );
+        }
+    }
+
     pub fn load_profiles_store(&self) -> Result<ProfilesStore> {
         if !self.profiles_path.exists() {
             return Ok(ProfilesStore::new());
@@ -142,6 +165,7 @@ impl ProfileManager {
         store.claude_code.insert(name.to_string(), profile);
         store.metadata.last_updated = Utc::now();
         self.save_profiles_store(&store)?;
+        self.audit("configure_api", "claude-code", Some(name));
 
         // 如果当前 profile 已激活，自动重新应用配置
         let active_store = self.load_active_store()?;
@@ -245,6 +269,7 @@ impl ProfileManager {
         store.codex.insert(name.to_string(), profile);
         store.metadata.last_updated = Utc::now();
         self.save_profiles_store(&store)?;
+        self.audit("configure_api", "codex", Some(name));
 
         // 如果当前 profile 已激活，自动重新应用配置
         let active_store = self.load_active_store()?;
@@ -295,10 +320,13 @@ impl ProfileManager {
         base_url: String,
         model: Option<String>,
     ) -> Result<()> {
-        self.save_gemini_profile_with_template(name, api_key, base_url, model, None)
+        self.save_gemini_profile_with_template(name, api_key, base_url, model, None, None)
     }
 
-    /// 保存 Gemini Profile（支持价格模板）
+    /// 保存 Gemini Profile（支持价格模板与认证模式）
+    ///
+    /// `auth` 为 None 时更新模式保留原认证配置、创建模式使用 api-key；
+    /// 非 api-key 模式创建时不要求 API Key 与 Base URL
     pub fn save_gemini_profile_with_template(
         &self,
         name: &str,
@@ -306,9 +334,15 @@ impl ProfileManager {
         base_url: String,
         model: Option<String>,
         pricing_template_id: Option<String>,
+        auth: Option<GeminiAuth>,
     ) -> Result<()> {
         // 保留字校验
         validate_profile_name(name)?;
+        if let Some(auth) = &auth {
+            auth.validate()?;
+        }
+        // api-key 模式不单独存储认证配置
+        let auth = auth.map(|auth| (auth.auth_mode != GeminiAuthMode::ApiKey).then_some(auth));
 
         let mut store = self.load_profiles_store()?;
 
@@ -327,11 +361,15 @@ impl ProfileManager {
             }
             // Phase 6: 更新价格模板 ID（允许清空）
             existing.pricing_template_id = pricing_template_id;
+            if let Some(auth) = auth {
+                existing.auth = auth;
+            }
             existing.updated_at = Utc::now();
             existing.clone()
         } else {
-            // 创建模式：必须有完整数据
-            if api_key.is_empty() || base_url.is_empty() {
+            let auth = auth.flatten();
+            // 创建模式：api-key 模式必须有完整数据
+            if auth.is_none() && (api_key.is_empty() || base_url.is_empty()) {
                 return Err(anyhow!("创建 Profile 时 API Key 和 Base URL 不能为空"));
             }
             GeminiProfile {
@@ -344,12 +382,14 @@ impl ProfileManager {
                 raw_env: None,
                 source: ProfileSource::Custom,
                 pricing_template_id, // Phase 6: 价格模板 ID
+                auth,
             }
         };
 
         store.gemini_cli.insert(name.to_string(), profile);
         store.metadata.last_updated = Utc::now();
         self.save_profiles_store(&store)?;
+        self.audit("configure_api", "gemini-cli", Some(name));
 
         // 如果当前 profile 已激活，自动重新应用配置
         let active_store = self.load_active_store()?;
@@ -404,7 +444,12 @@ impl ProfileManager {
             if name.starts_with(RESERVED_PREFIX) {
                 continue; // 跳过内置 Profile
             }
-            descriptors.push(ProfileDescriptor::from_claude(name, profile, active_claude));
+            descriptors.push(ProfileDescriptor::from_claude(
+                name,
+                profile,
+                active_claude,
+                active_store.get_last_used("claude-code", name),
+            ));
         }
 
         // Codex
@@ -413,7 +458,12 @@ impl ProfileManager {
             if name.starts_with(RESERVED_PREFIX) {
                 continue; // 跳过内置 Profile
             }
-            descriptors.push(ProfileDescriptor::from_codex(name, profile, active_codex));
+            descriptors.push(ProfileDescriptor::from_codex(
+                name,
+                profile,
+                active_codex,
+                active_store.get_last_used("codex", name),
+            ));
         }
 
         // Gemini CLI
@@ -422,7 +472,12 @@ impl ProfileManager {
             if name.starts_with(RESERVED_PREFIX) {
                 continue; // 跳过内置 Profile
             }
-            descriptors.push(ProfileDescriptor::from_gemini(name, profile, active_gemini));
+            descriptors.push(ProfileDescriptor::from_gemini(
+                name,
+                profile,
+                active_gemini,
+                active_store.get_last_used("gemini-cli", name),
+            ));
         }
 
         // HashMap 遍历顺序不稳定：这里做显式排序，保证前端展示稳定
@@ -448,6 +503,18 @@ impl ProfileManager {
         Ok(descriptors)
     }
 
+    /// 列出指定工具的 Profile 描述符（包含最后使用时间）
+    pub fn list_tool_descriptors(&self, tool_id: &str) -> Result<Vec<ProfileDescriptor>> {
+        if !matches!(tool_id, "claude-code" | "codex" | "gemini-cli") {
+            return Err(anyhow!("不支持的工具 ID: {}", tool_id));
+        }
+        Ok(self
+            .list_all_descriptors()?
+            .into_iter()
+            .filter(|d| d.tool_id == tool_id)
+            .collect())
+    }
+
     pub fn list_profiles(&self, tool_id: &str) -> Result<Vec<String>> {
         match tool_id {
             "claude-code" => self.list_claude_profiles(),
@@ -459,7 +526,22 @@ impl ProfileManager {
 
     // ==================== 激活管理 ====================
 
-    pub fn activate_profile(&self, tool_id: &str, profile_name: &str) -> Result<()> {
+    /// 激活 Profile 并应用到原生配置
+    ///
+    /// 原生配置写入失败时已写入的文件会自动回滚，active.json 保持不变；
+    /// 成功后返回切换前后的 Profile 名称。界面、托盘菜单与代理启停的切换都经由这里，统一记录审计
+    pub fn activate_profile(&self, tool_id: &str, profile_name: &str) -> Result<ProfileSwitch> {
+        let switch = self.switch_profile(tool_id, profile_name)?;
+        self.audit("switch_profile", tool_id, Some(profile_name));
+        Ok(switch)
+    }
+
+    /// 切换激活的 Profile（不记录审计，由调用方按实际操作记录）
+    pub(super) fn switch_profile(
+        &self,
+        tool_id: &str,
+        profile_name: &str,
+    ) -> Result<ProfileSwitch> {
         // 验证 Profile 存在
         let store = self.load_profiles_store()?;
         let exists = match tool_id {
@@ -473,14 +555,23 @@ impl ProfileManager {
             return Err(anyhow!("Profile 不存在: {} / {}", tool_id, profile_name));
         }
 
-        // 更新 active.json
         let mut active_store = self.load_active_store()?;
-        active_store.set_active(tool_id, profile_name.to_string());
-        self.save_active_store(&active_store)?;
+        let previous = active_store
+            .get_active(tool_id)
+            .map(|ap| ap.profile.clone());
 
-        // 应用到原生配置文件
+        // 先应用到原生配置文件（失败时已回滚），成功后再更新 active.json
         self.apply_to_native(tool_id, profile_name)?;
 
+        let switch = ProfileSwitch {
+            tool_id: tool_id.to_string(),
+            previous,
+            current: profile_name.to_string(),
+        };
+        active_store.set_active(tool_id, profile_name.to_string());
+        active_store.record_switch(switch.clone());
+        self.save_active_store(&active_store)?;
+
         // 读取应用后的配置并保存快照（为每个工具读取所有配置文件）
         let tool = crate::models::Tool::by_id(tool_id)
             .ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
@@ -520,7 +611,22 @@ impl ProfileManager {
             tracing::debug!("已保存 Profile 快照: {} / {}", tool_id, profile_name);
         }
 
-        Ok(())
+        Ok(switch)
+    }
+
+    /// 撤销最近一次 Profile 切换，恢复切换前的配置
+    ///
+    /// 只支持撤销最近一次：撤销完成后清除切换记录，不能再次撤销
+    pub fn undo_last_switch(&self, tool_id: &str) -> Result<ProfileSwitch> {
+        let previous = self.load_active_store()?.undo_target(tool_id)?;
+        let switch = self.switch_profile(tool_id, &previous)?;
+
+        let mut active_store = self.load_active_store()?;
+        active_store.last_switch.remove(tool_id);
+        self.save_active_store(&active_store)?;
+
+        self.audit("undo_switch_profile", tool_id, Some(&switch.current));
+        Ok(switch)
     }
 
     pub fn get_active_profile_name(&self, tool_id: &str) -> Result<Option<String>> {
@@ -562,7 +668,9 @@ impl ProfileManager {
     }
 
     pub fn capture_from_native(&self, tool_id: &str, profile_name: &str) -> Result<()> {
-        self.capture_profile_from_native(tool_id, profile_name)
+        self.capture_profile_from_native(tool_id, profile_name)?;
+        self.audit("capture_profile", tool_id, Some(profile_name));
+        Ok(())
     }
 
     // ==================== 内部方法（跳过保留字校验） ====================
@@ -700,6 +808,7 @@ impl ProfileManager {
                 raw_env: None,
                 source: ProfileSource::Custom,
                 pricing_template_id: None,
+                auth: None,
             }
         };
 
@@ -796,11 +905,20 @@ impl ProfileManager {
 
     pub fn delete_profile(&self, tool_id: &str, name: &str) -> Result<()> {
         match tool_id {
-            "claude-code" => self.delete_claude_profile(name),
-            "codex" => self.delete_codex_profile(name),
-            "gemini-cli" => self.delete_gemini_profile(name),
-            _ => Err(anyhow!("不支持的工具 ID: {}", tool_id)),
+            "claude-code" => self.delete_claude_profile(name)?,
+            "codex" => self.delete_codex_profile(name)?,
+            "gemini-cli" => self.delete_gemini_profile(name)?,
+            _ => return Err(anyhow!("不支持的工具 ID: {}", tool_id)),
         }
+
+        // 清理使用记录
+        let mut active_store = self.load_active_store()?;
+        if active_store.get_last_used(tool_id, name).is_some() {
+            active_store.remove_last_used(tool_id, name);
+            self.save_active_store(&active_store)?;
+        }
+        self.audit("delete_profile", tool_id, Some(name));
+        Ok(())
     }
 
     // ==================== 快照管理 ====================
@@ -1076,6 +1194,7 @@ mod tests {
                 raw_settings: None,
                 raw_env: None,
                 pricing_template_id: None,
+                auth: None,
             },
         );
 
@@ -1101,4 +1220,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_last_used_recorded_on_activation() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(&temp_dir);
+
+        let mut store = ProfilesStore::new();
+        let created_at = Utc.timestamp_opt(1, 0).single().unwrap();
+        for name in ["used", "switched_away", "never_used"] {
+            store.claude_code.insert(
+                name.to_string(),
+                ClaudeProfile {
+                    api_key: "k".to_string(),
+                    base_url: "u".to_string(),
+                    source: ProfileSource::Custom,
+                    created_at,
+                    updated_at: created_at,
+                    raw_settings: None,
+                    raw_config_json: None,
+                    pricing_template_id: None,
+                },
+            );
+        }
+        manager.save_profiles_store(&store)?;
+
+        let before = Utc::now();
+        let mut active = ActiveStore::new();
+        active.set_active("claude-code", "switched_away".to_string());
+        active.set_active("claude-code", "used".to_string());
+        manager.save_active_store(&active)?;
+
+        let descriptors = manager.list_tool_descriptors("claude-code")?;
+        let find = |name: &str| descriptors.iter().find(|d| d.name == name).unwrap();
+
+        // 切换走的 Profile 仍保留最后使用时间
+        let switched_away = find("switched_away");
+        assert!(!switched_away.is_active);
+        assert!(switched_away.last_used_at.unwrap() >= before);
+
+        let used = find("used");
+        assert!(used.is_active);
+        assert!(used.last_used_at.unwrap() >= switched_away.last_used_at.unwrap());
+
+        assert!(find("never_used").last_used_at.is_none());
+
+        // 删除 Profile 时清理使用记录
+        manager.delete_profile("claude-code", "switched_away")?;
+        let active = manager.load_active_store()?;
+        assert!(active
+            .get_last_used("claude-code", "switched_away")
+            .is_none());
+        assert!(active.get_last_used("claude-code", "used").is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_changes_recorded_in_audit_log() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(&temp_dir);
+
+        manager.save_claude_profile("work", "k".to_string(), "u".to_string())?;
+        manager.delete_profile("claude-code", "work")?;
+
+        // 审计写入 profiles.json 所在目录，与调用入口（界面/托盘/代理）无关
+        let entries = audit_log::read_recent(&temp_dir.path().join(audit_log::AUDIT_LOG_FILE), 10)?;
+        let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["delete_profile", "configure_api"]);
+        assert!(entries
+            .iter()
+            .all(|e| e.tool_id == "claude-code" && e.profile.as_deref() == Some("work")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_active_store_without_last_used_field() {
+        let json = r#"{
+            "version": "2.0.0",
+            "claude-code": null,
+            "codex": null,
+            "gemini-cli": null,
+            "metadata": { "last_updated": "2025-01-01T00:00:00Z" }
+        }"#;
+        let store: ActiveStore = serde_json::from_str(json).unwrap();
+        assert!(store.last_used.is_empty());
+    }
 }
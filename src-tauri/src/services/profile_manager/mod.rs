@@ -4,13 +4,20 @@
 //! - profiles.json: 使用具体类型（ClaudeProfile/CodexProfile/GeminiProfile）
 //! - active.json: 激活状态管理
 
+mod consistency;
 mod manager;
 mod native_config;
+mod native_txn;
+mod recommended;
+mod share;
 pub mod types;
 
+pub use consistency::Inconsistency;
 pub use manager::ProfileManager;
+pub use recommended::{recommended_config, RecommendedConfig, RECOMMENDED_PROFILE_NAME};
+pub use share::{ProfileExport, MASKED_API_KEY, PROFILE_EXPORT_FORMAT, PROFILE_EXPORT_VERSION};
 pub use types::{
     ActiveMetadata, ActiveProfile, ActiveStore, AmpProfileSelection, ClaudeProfile, CodexProfile,
-    GeminiProfile, ProfileDescriptor, ProfileRef, ProfileSource, ProfilesMetadata, ProfilesStore,
-    TokenImportStatus,
+    GeminiAuth, GeminiAuthMode, GeminiProfile, ProfileDescriptor, ProfileRef, ProfileSource,
+    ProfileSwitch, ProfilesMetadata, ProfilesStore, TokenImportStatus,
 };
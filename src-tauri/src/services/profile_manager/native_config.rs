@@ -1,5 +1,6 @@
 //! 原生配置文件同步逻辑（v2.1 - 简化版）
 
+use super::native_txn::{write_all_or_rollback, FileWrite};
 use super::types::*;
 use crate::data::DataManager;
 use crate::models::tool::Tool;
@@ -12,22 +13,25 @@ impl super::manager::ProfileManager {
     pub fn apply_profile_to_native(&self, tool_id: &str, profile_name: &str) -> Result<()> {
         let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
 
-        match tool_id {
+        // 先在内存中生成所有文件内容，再统一事务写入（失败自动回滚）
+        let writes = match tool_id {
             "claude-code" => {
                 let profile = self.get_claude_profile(profile_name)?;
-                apply_claude_native(&tool, &profile)?;
+                render_claude_native(&tool, &profile)?
             }
             "codex" => {
                 let profile = self.get_codex_profile(profile_name)?;
                 // 使用 profile_name 作为 provider 名称
-                apply_codex_native(&tool, &profile, profile_name)?;
+                render_codex_native(&tool, &profile, profile_name)?
             }
             "gemini-cli" => {
                 let profile = self.get_gemini_profile(profile_name)?;
-                apply_gemini_native(&tool, &profile)?;
+                render_gemini_native(&tool, &profile)?
             }
             _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
-        }
+        };
+
+        write_all_or_rollback(&writes)?;
 
         tracing::info!("已应用 Profile: {} / {}", tool_id, profile_name);
         Ok(())
@@ -60,7 +64,7 @@ impl super::manager::ProfileManager {
 
 // ==================== Claude Code ====================
 
-fn apply_claude_native(tool: &Tool, profile: &ClaudeProfile) -> Result<()> {
+fn render_claude_native(tool: &Tool, profile: &ClaudeProfile) -> Result<Vec<FileWrite>> {
     let manager = DataManager::new();
     let settings_path = tool.config_dir.join("settings.json");
 
@@ -90,8 +94,8 @@ fn apply_claude_native(tool: &Tool, profile: &ClaudeProfile) -> Result<()> {
         Value::String(profile.base_url.clone()),
     );
 
-    manager.json_uncached().write(&settings_path, &settings)?;
-    Ok(())
+    let content = serde_json::to_string_pretty(&settings)?;
+    Ok(vec![(settings_path, content)])
 }
 
 fn capture_claude_config(tool: &Tool) -> Result<(String, String)> {
@@ -120,7 +124,11 @@ fn capture_claude_config(tool: &Tool) -> Result<(String, String)> {
 
 // ==================== Codex ====================
 
-fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str) -> Result<()> {
+fn render_codex_native(
+    tool: &Tool,
+    profile: &CodexProfile,
+    provider_name: &str,
+) -> Result<Vec<FileWrite>> {
     let manager = DataManager::new();
     let config_path = tool.config_dir.join("config.toml");
     let auth_path = tool.config_dir.join("auth.json");
@@ -130,7 +138,33 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
     } else {
         toml_edit::DocumentMut::new()
     };
+    apply_codex_profile_to_doc(&mut doc, profile, provider_name)?;
+
+    // 应用 auth.json
+    let mut auth = if auth_path.exists() {
+        manager.json_uncached().read(&auth_path)?
+    } else {
+        serde_json::json!({})
+    };
+
+    // 自定义 provider 设置了 requires_openai_auth，需要 API Key；保留 ChatGPT 登录 Token
+    crate::services::config::codex::write_auth_api_key(&mut auth, &profile.api_key)?;
 
+    Ok(vec![
+        (config_path, doc.to_string()),
+        (auth_path, serde_json::to_string_pretty(&auth)?),
+    ])
+}
+
+/// 将 Profile 合并到现有 config.toml 文档
+///
+/// - `model`、`model_reasoning_effort`、`network_access` 仅在缺失时写入默认值，保留用户手动调整
+/// - `model_provider` 与对应 provider 的 `base_url`/`wire_api` 与切换强相关，始终覆盖
+fn apply_codex_profile_to_doc(
+    doc: &mut toml_edit::DocumentMut,
+    profile: &CodexProfile,
+    provider_name: &str,
+) -> Result<()> {
     let root_table = doc.as_table_mut();
 
     // 设置默认值
@@ -147,6 +181,18 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
     // 设置 model_provider 为 profile_name
     root_table.insert("model_provider", toml_edit::value(provider_name));
 
+    // Profile 使用 API Key 认证：已选择 ChatGPT 登录时切换为 apikey，否则 Key 不会生效
+    if root_table
+        .get("preferred_auth_method")
+        .and_then(|v| v.as_str())
+        .is_some_and(|mode| mode != crate::services::config::codex::AUTH_MODE_API_KEY)
+    {
+        root_table.insert(
+            "preferred_auth_method",
+            toml_edit::value(crate::services::config::codex::AUTH_MODE_API_KEY),
+        );
+    }
+
     // 处理 base_url
     let normalized = profile.base_url.trim_end_matches('/');
     let base_url_with_v1 = if normalized.ends_with("/v1") {
@@ -203,23 +249,6 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
         }
     }
 
-    manager.toml().write(&config_path, &doc)?;
-
-    // 应用 auth.json
-    let mut auth = if auth_path.exists() {
-        manager.json_uncached().read(&auth_path)?
-    } else {
-        serde_json::json!({})
-    };
-
-    auth.as_object_mut()
-        .ok_or_else(|| anyhow!("auth.json 格式错误：不是对象"))?
-        .insert(
-            "OPENAI_API_KEY".to_string(),
-            Value::String(profile.api_key.clone()),
-        );
-    manager.json_uncached().write(&auth_path, &auth)?;
-
     Ok(())
 }
 
@@ -230,11 +259,7 @@ fn capture_codex_config(tool: &Tool) -> Result<(String, String, String)> {
 
     // 读取 API Key
     let auth: Value = manager.json_uncached().read(&auth_path)?;
-    let api_key = auth
-        .get("OPENAI_API_KEY")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
+    let api_key = crate::services::config::codex::read_auth_api_key(&auth).unwrap_or_default();
 
     // 读取当前 model_provider
     let doc = manager.toml().read_document(&config_path)?;
@@ -263,25 +288,122 @@ fn capture_codex_config(tool: &Tool) -> Result<(String, String, String)> {
 
 // ==================== Gemini CLI ====================
 
-fn apply_gemini_native(tool: &Tool, profile: &GeminiProfile) -> Result<()> {
+fn render_gemini_native(tool: &Tool, profile: &GeminiProfile) -> Result<Vec<FileWrite>> {
     let manager = DataManager::new();
     let env_path = tool.config_dir.join(".env");
 
-    manager
-        .env()
-        .set(&env_path, "GEMINI_API_KEY", &profile.api_key)?;
-    manager
-        .env()
-        .set(&env_path, "GOOGLE_GEMINI_BASE_URL", &profile.base_url)?;
+    let settings_path = tool.config_dir.join(&tool.config_file);
+
+    // 逐行替换，保留原有注释与其他变量
+    let mut lines = if env_path.exists() {
+        manager.env().read_raw(&env_path)?
+    } else {
+        Vec::new()
+    };
+    apply_gemini_profile_to_env(&mut lines, profile);
+
+    // settings.json 的认证方式需与 .env 一致
+    let mut settings = if settings_path.exists() {
+        manager.json_uncached().read(&settings_path)?
+    } else {
+        Value::Object(Map::new())
+    };
+    set_selected_auth_type(&mut settings, profile.auth_mode())?;
+
+    Ok(vec![
+        (env_path, lines.join("\n") + "\n"),
+        (settings_path, serde_json::to_string_pretty(&settings)?),
+    ])
+}
+
+/// Vertex 模式专用的环境变量（切换到其他模式时清理）
+const GEMINI_VERTEX_ENV_KEYS: &[&str] = &[
+    "GOOGLE_CLOUD_PROJECT",
+    "GOOGLE_CLOUD_LOCATION",
+    "GOOGLE_GENAI_USE_VERTEXAI",
+];
+
+/// 按认证模式更新 .env 内容
+///
+/// - api-key：写入 GEMINI_API_KEY 与 Base URL
+/// - vertex：写入 GCP 项目与区域，并开启 GOOGLE_GENAI_USE_VERTEXAI
+/// - oauth：不写入凭证（由 Gemini CLI 登录流程管理）
+fn apply_gemini_profile_to_env(lines: &mut Vec<String>, profile: &GeminiProfile) {
+    match profile.auth.as_ref() {
+        Some(auth) if auth.auth_mode == GeminiAuthMode::Vertex => {
+            set_env_line(
+                lines,
+                "GOOGLE_CLOUD_PROJECT",
+                auth.project.as_deref().unwrap_or(""),
+            );
+            set_env_line(
+                lines,
+                "GOOGLE_CLOUD_LOCATION",
+                auth.location.as_deref().unwrap_or(""),
+            );
+            set_env_line(lines, "GOOGLE_GENAI_USE_VERTEXAI", "true");
+        }
+        Some(auth) if auth.auth_mode == GeminiAuthMode::Oauth => {
+            remove_env_lines(lines, GEMINI_VERTEX_ENV_KEYS);
+        }
+        _ => {
+            remove_env_lines(lines, GEMINI_VERTEX_ENV_KEYS);
+            set_env_line(lines, "GEMINI_API_KEY", &profile.api_key);
+            set_env_line(lines, "GOOGLE_GEMINI_BASE_URL", &profile.base_url);
+        }
+    }
 
     // 只在 model 有值时才写入
     if let Some(ref model) = profile.model {
-        manager.env().set(&env_path, "GEMINI_MODEL", model)?;
+        set_env_line(lines, "GEMINI_MODEL", model);
     }
+}
 
+/// 设置 settings.json 中的 `security.auth.selectedType`
+fn set_selected_auth_type(settings: &mut Value, mode: GeminiAuthMode) -> Result<()> {
+    let mut current = settings;
+    for key in ["security", "auth"] {
+        current = current
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("Gemini settings.json 格式错误"))?
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    current
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Gemini settings.json 中 security.auth 格式错误"))?
+        .insert(
+            "selectedType".to_string(),
+            Value::String(mode.selected_type().to_string()),
+        );
     Ok(())
 }
 
+/// 移除 .env 中指定的变量
+fn remove_env_lines(lines: &mut Vec<String>, keys: &[&str]) {
+    lines.retain(|line| {
+        let line = line.trim();
+        line.starts_with('#')
+            || !line
+                .split_once('=')
+                .is_some_and(|(k, _)| keys.contains(&k.trim()))
+    });
+}
+
+/// 设置 .env 中的变量（存在则替换该行，否则追加）
+fn set_env_line(lines: &mut Vec<String>, key: &str, value: &str) {
+    let entry = format!("{}={}", key, value);
+    let existing = lines.iter_mut().find(|line| {
+        let line = line.trim();
+        !line.starts_with('#') && line.split_once('=').is_some_and(|(k, _)| k.trim() == key)
+    });
+
+    match existing {
+        Some(line) => *line = entry,
+        None => lines.push(entry),
+    }
+}
+
 fn capture_gemini_config(tool: &Tool) -> Result<(String, String, String)> {
     let manager = DataManager::new();
     let env_path = tool.config_dir.join(".env");
@@ -308,3 +430,173 @@ fn capture_gemini_config(tool: &Tool) -> Result<(String, String, String)> {
 
     Ok((api_key, base_url, model))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn codex_profile(base_url: &str) -> CodexProfile {
+        CodexProfile {
+            api_key: "sk-test".to_string(),
+            base_url: base_url.to_string(),
+            wire_api: "responses".to_string(),
+            source: ProfileSource::Custom,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_config_toml: None,
+            raw_auth_json: None,
+            pricing_template_id: None,
+        }
+    }
+
+    #[test]
+    fn test_codex_switch_preserves_user_settings() -> Result<()> {
+        let mut doc: toml_edit::DocumentMut = "# 用户注释\nmodel = \"o3\"\nmodel_reasoning_effort = \"low\"\nnetwork_access = \"disabled\"\nmodel_provider = \"old\"\n\n[model_providers.old]\nname = \"old\"\nbase_url = \"https://old.example.com/v1\"\n".parse()?;
+
+        apply_codex_profile_to_doc(&mut doc, &codex_profile("https://a.example.com"), "team")?;
+        apply_codex_profile_to_doc(
+            &mut doc,
+            &codex_profile("https://b.example.com/v1/"),
+            "work",
+        )?;
+
+        // 用户手动调整的字段保持不变
+        assert_eq!(doc["model_reasoning_effort"].as_str(), Some("low"));
+        assert_eq!(doc["model"].as_str(), Some("o3"));
+        assert_eq!(doc["network_access"].as_str(), Some("disabled"));
+        assert!(doc.to_string().contains("# 用户注释"));
+
+        // 与切换强相关的字段被覆盖
+        assert_eq!(doc["model_provider"].as_str(), Some("work"));
+        assert_eq!(
+            doc["model_providers"]["team"]["base_url"].as_str(),
+            Some("https://a.example.com/v1")
+        );
+        assert_eq!(
+            doc["model_providers"]["work"]["base_url"].as_str(),
+            Some("https://b.example.com/v1")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_codex_switch_fills_missing_defaults() -> Result<()> {
+        let mut doc = toml_edit::DocumentMut::new();
+        apply_codex_profile_to_doc(&mut doc, &codex_profile("https://a.example.com"), "team")?;
+        assert_eq!(doc["model"].as_str(), Some("gpt-5-codex"));
+        assert_eq!(doc["model_reasoning_effort"].as_str(), Some("high"));
+        assert_eq!(doc["network_access"].as_str(), Some("enabled"));
+
+        // 已存在的 provider 地址变化时同步更新
+        let mut profile = codex_profile("https://new.example.com");
+        profile.wire_api = "chat".to_string();
+        apply_codex_profile_to_doc(&mut doc, &profile, "team")?;
+        assert_eq!(
+            doc["model_providers"]["team"]["base_url"].as_str(),
+            Some("https://new.example.com/v1")
+        );
+        assert_eq!(
+            doc["model_providers"]["team"]["wire_api"].as_str(),
+            Some("chat")
+        );
+        Ok(())
+    }
+
+    fn gemini_profile(auth: Option<GeminiAuth>) -> GeminiProfile {
+        GeminiProfile {
+            api_key: "gm-key".to_string(),
+            base_url: "https://gemini.example.com".to_string(),
+            model: Some("gemini-2.5-pro".to_string()),
+            source: ProfileSource::Custom,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_settings: None,
+            raw_env: None,
+            pricing_template_id: None,
+            auth,
+        }
+    }
+
+    fn vertex_auth() -> GeminiAuth {
+        GeminiAuth {
+            auth_mode: GeminiAuthMode::Vertex,
+            project: Some("my-project".to_string()),
+            location: Some("us-central1".to_string()),
+        }
+    }
+
+    fn env_value<'a>(lines: &'a [String], key: &str) -> Option<&'a str> {
+        lines.iter().find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            (k == key).then_some(v)
+        })
+    }
+
+    #[test]
+    fn test_gemini_vertex_then_back_to_api_key() {
+        let mut lines = vec!["# 注释".to_string(), "OTHER=1".to_string()];
+
+        apply_gemini_profile_to_env(&mut lines, &gemini_profile(Some(vertex_auth())));
+        assert_eq!(
+            env_value(&lines, "GOOGLE_CLOUD_PROJECT"),
+            Some("my-project")
+        );
+        assert_eq!(
+            env_value(&lines, "GOOGLE_CLOUD_LOCATION"),
+            Some("us-central1")
+        );
+        assert_eq!(env_value(&lines, "GOOGLE_GENAI_USE_VERTEXAI"), Some("true"));
+        assert_eq!(env_value(&lines, "GEMINI_API_KEY"), None);
+
+        // 切换回 api-key 模式：清理 vertex 变量，写入 API Key
+        apply_gemini_profile_to_env(&mut lines, &gemini_profile(None));
+        assert_eq!(env_value(&lines, "GOOGLE_CLOUD_PROJECT"), None);
+        assert_eq!(env_value(&lines, "GOOGLE_CLOUD_LOCATION"), None);
+        assert_eq!(env_value(&lines, "GOOGLE_GENAI_USE_VERTEXAI"), None);
+        assert_eq!(env_value(&lines, "GEMINI_API_KEY"), Some("gm-key"));
+        assert_eq!(env_value(&lines, "GEMINI_MODEL"), Some("gemini-2.5-pro"));
+        assert_eq!(&lines[..2], &["# 注释".to_string(), "OTHER=1".to_string()]);
+
+        // oauth 模式不写入凭证
+        let mut lines = Vec::new();
+        let oauth = GeminiAuth {
+            auth_mode: GeminiAuthMode::Oauth,
+            ..Default::default()
+        };
+        apply_gemini_profile_to_env(&mut lines, &gemini_profile(Some(oauth)));
+        assert_eq!(lines, vec!["GEMINI_MODEL=gemini-2.5-pro".to_string()]);
+    }
+
+    #[test]
+    fn test_gemini_selected_auth_type() -> Result<()> {
+        let mut settings = serde_json::json!({ "ui": { "theme": "dark" } });
+        set_selected_auth_type(&mut settings, GeminiAuthMode::Vertex)?;
+        assert_eq!(settings["security"]["auth"]["selectedType"], "vertex-ai");
+        assert_eq!(settings["ui"]["theme"], "dark");
+
+        set_selected_auth_type(&mut settings, GeminiAuthMode::ApiKey)?;
+        assert_eq!(
+            settings["security"]["auth"]["selectedType"],
+            "gemini-api-key"
+        );
+        set_selected_auth_type(&mut settings, GeminiAuthMode::Oauth)?;
+        assert_eq!(
+            settings["security"]["auth"]["selectedType"],
+            "oauth-personal"
+        );
+
+        assert!(
+            set_selected_auth_type(&mut serde_json::json!([]), GeminiAuthMode::ApiKey).is_err()
+        );
+
+        // Vertex 模式缺少项目或区域时校验失败
+        assert!(vertex_auth().validate().is_ok());
+        let incomplete = GeminiAuth {
+            location: None,
+            ..vertex_auth()
+        };
+        assert!(incomplete.validate().is_err());
+        Ok(())
+    }
+}
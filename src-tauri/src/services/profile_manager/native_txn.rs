@@ -0,0 +1,144 @@
+//! 原生配置文件事务写入
+//!
+//! 切换 Profile 可能需要写多个文件（如 Codex 的 config.toml + auth.json）。
+//! 写入前先把目标文件读入内存作为快照，每个文件通过临时文件 + rename 原子替换，
+//! 任一文件写入失败时用快照回滚已写入的文件，避免原生配置处于半写入状态。
+//! 写入（含回滚）后登记内容哈希，配置监听不会把自身写入误报为外部漂移
+
+use crate::services::config::mark_internal_write;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 一次待写入的文件
+pub(super) type FileWrite = (PathBuf, String);
+
+/// 依次写入所有文件，任一失败则回滚已写入的文件
+pub(super) fn write_all_or_rollback(writes: &[FileWrite]) -> Result<()> {
+    // 快照：不存在的文件记为 None，回滚时删除
+    let snapshots = writes
+        .iter()
+        .map(|(path, _)| {
+            if path.exists() {
+                fs::read(path)
+                    .map(Some)
+                    .with_context(|| format!("读取配置快照失败: {}", path.display()))
+            } else {
+                Ok(None)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for (index, (path, content)) in writes.iter().enumerate() {
+        if let Err(e) = atomic_write(path, content.as_bytes()) {
+            rollback(&writes[..index], &snapshots[..index]);
+            return Err(e.context(format!("写入配置文件失败，已回滚: {}", path.display())));
+        }
+    }
+
+    Ok(())
+}
+
+/// 按快照逆序恢复已写入的文件（尽力而为，失败仅记录日志）
+fn rollback(written: &[FileWrite], snapshots: &[Option<Vec<u8>>]) {
+    for ((path, _), snapshot) in written.iter().zip(snapshots).rev() {
+        let result = match snapshot {
+            Some(original) => atomic_write(path, original),
+            None => fs::remove_file(path).map_err(Into::into),
+        };
+        mark_internal_write(path);
+        match result {
+            Ok(()) => tracing::info!("已回滚配置文件: {}", path.display()),
+            Err(e) => tracing::error!("回滚配置文件失败: {} - {:?}", path.display(), e),
+        }
+    }
+}
+
+/// 写入同目录临时文件后 rename 替换目标文件
+fn atomic_write(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    let result = (|| -> Result<()> {
+        fs::write(&tmp_path, content)
+            .with_context(|| format!("写入临时文件失败: {}", tmp_path.display()))?;
+        set_permissions(&tmp_path)?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("替换配置文件失败: {}", path.display()))?;
+        mark_internal_write(path);
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("设置文件权限失败: {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_all_success() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().join("config.toml");
+        let auth = dir.path().join("auth.json");
+        fs::write(&config, "old").unwrap();
+
+        write_all_or_rollback(&[
+            (config.clone(), "new config".to_string()),
+            (auth.clone(), "new auth".to_string()),
+        ])
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&config).unwrap(), "new config");
+        assert_eq!(fs::read_to_string(&auth).unwrap(), "new auth");
+        assert!(!dir.path().join(".config.toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_second_write_failure_rolls_back_first_file() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().join("config.toml");
+        let created = dir.path().join("settings.json");
+        fs::write(&config, "original config").unwrap();
+
+        // 第二个文件的父路径是普通文件，无法创建目录也无法写入
+        let blocker = dir.path().join("blocker");
+        fs::write(&blocker, "").unwrap();
+        let auth = blocker.join("auth.json");
+
+        let result = write_all_or_rollback(&[
+            (config.clone(), "new config".to_string()),
+            (created.clone(), "{}".to_string()),
+            (auth.clone(), "new auth".to_string()),
+        ]);
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&config).unwrap(), "original config");
+        assert!(!created.exists(), "原本不存在的文件应被删除");
+        assert!(!auth.exists());
+    }
+}
@@ -0,0 +1,216 @@
+//! DuckCoding 官方推荐配置（一键应用）
+//!
+//! 推荐参数内置在应用中，随应用版本更新；用户只需提供 API Key，
+//! 其余参数（base_url、模型、reasoning effort）按推荐值写入 Profile 与原生配置
+
+use super::native_txn::{write_all_or_rollback, FileWrite};
+use super::types::*;
+use crate::data::DataManager;
+use crate::models::tool::{Tool, DUCKCODING_BASE_URL};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 推荐配置保存的 Profile 名称
+pub const RECOMMENDED_PROFILE_NAME: &str = "duckcoding-recommended";
+
+/// 工具推荐配置
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecommendedConfig {
+    pub tool_id: &'static str,
+    pub base_url: &'static str,
+    pub model: Option<&'static str>,
+    pub reasoning_effort: Option<&'static str>,
+    /// Codex wire_api
+    pub wire_api: Option<&'static str>,
+}
+
+/// 内置推荐配置（经 DuckCoding 官方渠道验证）
+const RECOMMENDED_CONFIGS: &[RecommendedConfig] = &[
+    RecommendedConfig {
+        tool_id: "claude-code",
+        base_url: DUCKCODING_BASE_URL,
+        model: Some("claude-sonnet-4-5"),
+        reasoning_effort: None,
+        wire_api: None,
+    },
+    RecommendedConfig {
+        tool_id: "codex",
+        base_url: DUCKCODING_BASE_URL,
+        model: Some("gpt-5-codex"),
+        reasoning_effort: Some("high"),
+        wire_api: Some("responses"),
+    },
+    RecommendedConfig {
+        tool_id: "gemini-cli",
+        base_url: DUCKCODING_BASE_URL,
+        model: Some("gemini-2.5-pro"),
+        reasoning_effort: None,
+        wire_api: None,
+    },
+];
+
+/// 获取工具的推荐配置
+pub fn recommended_config(tool_id: &str) -> Option<&'static RecommendedConfig> {
+    RECOMMENDED_CONFIGS.iter().find(|c| c.tool_id == tool_id)
+}
+
+impl super::manager::ProfileManager {
+    /// 按推荐配置创建（或更新）Profile 并激活
+    pub fn apply_recommended_config(&self, tool_id: &str, api_key: &str) -> Result<ProfileSwitch> {
+        let config = self.save_recommended_profile(tool_id, api_key)?;
+
+        // 先写入模型等 Profile 未覆盖的参数，激活时保留这些字段并生成快照
+        let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+        let writes = render_recommended_native(&tool, config)?;
+        write_all_or_rollback(&writes)?;
+
+        let switch = self.switch_profile(tool_id, RECOMMENDED_PROFILE_NAME)?;
+        self.audit("apply_recommended_config", tool_id, Some(&switch.current));
+        tracing::info!("已应用推荐配置: {}", tool_id);
+        Ok(switch)
+    }
+
+    /// 保存推荐配置对应的 Profile
+    pub(super) fn save_recommended_profile(
+        &self,
+        tool_id: &str,
+        api_key: &str,
+    ) -> Result<&'static RecommendedConfig> {
+        let config =
+            recommended_config(tool_id).ok_or_else(|| anyhow!("工具 {} 暂无推荐配置", tool_id))?;
+        let api_key = api_key.trim();
+        if api_key.is_empty() {
+            return Err(anyhow!("API Key 不能为空"));
+        }
+
+        let api_key = api_key.to_string();
+        let base_url = config.base_url.to_string();
+        match tool_id {
+            "claude-code" => {
+                self.save_claude_profile(RECOMMENDED_PROFILE_NAME, api_key, base_url)?
+            }
+            "codex" => self.save_codex_profile(
+                RECOMMENDED_PROFILE_NAME,
+                api_key,
+                base_url,
+                config.wire_api.map(str::to_string),
+            )?,
+            "gemini-cli" => self.save_gemini_profile(
+                RECOMMENDED_PROFILE_NAME,
+                api_key,
+                base_url,
+                config.model.map(str::to_string),
+            )?,
+            _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
+        }
+        Ok(config)
+    }
+}
+
+/// 生成写入推荐参数后的原生配置（Gemini 的模型由 Profile 写入，无需额外处理）
+fn render_recommended_native(tool: &Tool, config: &RecommendedConfig) -> Result<Vec<FileWrite>> {
+    let manager = DataManager::new();
+    match config.tool_id {
+        "claude-code" => {
+            let settings_path = tool.config_dir.join("settings.json");
+            let mut settings: Value = if settings_path.exists() {
+                manager.json_uncached().read(&settings_path)?
+            } else {
+                serde_json::json!({})
+            };
+            apply_claude_recommended(&mut settings, config)?;
+            Ok(vec![(
+                settings_path,
+                serde_json::to_string_pretty(&settings)?,
+            )])
+        }
+        "codex" => {
+            let config_path = tool.config_dir.join("config.toml");
+            let mut doc = if config_path.exists() {
+                manager.toml().read_document(&config_path)?
+            } else {
+                toml_edit::DocumentMut::new()
+            };
+            apply_codex_recommended(&mut doc, config);
+            Ok(vec![(config_path, doc.to_string())])
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// 写入 Claude Code settings.json 的推荐模型
+fn apply_claude_recommended(settings: &mut Value, config: &RecommendedConfig) -> Result<()> {
+    let obj = settings
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Claude 配置格式错误：settings 不是对象"))?;
+    if let Some(model) = config.model {
+        obj.insert("model".to_string(), Value::String(model.to_string()));
+    }
+    Ok(())
+}
+
+/// 写入 Codex config.toml 的推荐模型与 reasoning effort（覆盖已有值）
+fn apply_codex_recommended(doc: &mut toml_edit::DocumentMut, config: &RecommendedConfig) {
+    let root_table = doc.as_table_mut();
+    if let Some(model) = config.model {
+        root_table.insert("model", toml_edit::value(model));
+    }
+    if let Some(effort) = config.reasoning_effort {
+        root_table.insert("model_reasoning_effort", toml_edit::value(effort));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::manager::ProfileManager;
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_recommended_profile() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ProfileManager::with_paths(temp_dir.path());
+
+        let config = manager.save_recommended_profile("codex", "  sk-codex  ")?;
+        let profile = manager.get_codex_profile(RECOMMENDED_PROFILE_NAME)?;
+        assert_eq!(profile.api_key, "sk-codex");
+        assert_eq!(profile.base_url, config.base_url);
+        assert_eq!(profile.wire_api, "responses");
+
+        manager.save_recommended_profile("gemini-cli", "sk-gemini")?;
+        let profile = manager.get_gemini_profile(RECOMMENDED_PROFILE_NAME)?;
+        assert_eq!(profile.model.as_deref(), Some("gemini-2.5-pro"));
+
+        // 再次应用只更新 Key
+        manager.save_recommended_profile("codex", "sk-new")?;
+        assert_eq!(
+            manager.get_codex_profile(RECOMMENDED_PROFILE_NAME)?.api_key,
+            "sk-new"
+        );
+
+        assert!(manager.save_recommended_profile("codex", " ").is_err());
+        assert!(manager.save_recommended_profile("amp-code", "sk").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_recommended_native_overrides() -> Result<()> {
+        let codex = recommended_config("codex").unwrap();
+        let mut doc: toml_edit::DocumentMut =
+            "# 注释\nmodel = \"gpt-4o\"\nmodel_reasoning_effort = \"low\"\n".parse()?;
+        apply_codex_recommended(&mut doc, codex);
+        let text = doc.to_string();
+        assert!(text.starts_with("# 注释\n"));
+        assert_eq!(doc["model"].as_str(), Some("gpt-5-codex"));
+        assert_eq!(doc["model_reasoning_effort"].as_str(), Some("high"));
+
+        let claude = recommended_config("claude-code").unwrap();
+        let mut settings = serde_json::json!({ "env": { "FOO": "bar" } });
+        apply_claude_recommended(&mut settings, claude)?;
+        assert_eq!(settings["model"], "claude-sonnet-4-5");
+        assert_eq!(settings["env"]["FOO"], "bar");
+        assert!(apply_claude_recommended(&mut serde_json::json!([]), claude).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,470 @@
+//! Profile 导出/导入（单文件分享）
+//!
+//! 导出为统一 JSON 信封，profile 内容按 profiles.json 中的结构原样放入，
+//! Codex 的 `raw_config_toml` 与 Gemini 的 `raw_env` 保留原始文本，不重新序列化。
+//! 脱敏导出（`export_config_sanitized`）额外移除原始配置中的所有密钥字段，仅保留可分享的配置参数
+
+use super::types::*;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 信封格式标识
+pub const PROFILE_EXPORT_FORMAT: &str = "duckcoding-profile";
+
+/// 当前信封版本（导入时接受不高于此版本的文件）
+pub const PROFILE_EXPORT_VERSION: u32 = 1;
+
+/// 脱敏导出时替换 API Key 的占位符
+pub const MASKED_API_KEY: &str = "<YOUR_API_KEY>";
+
+/// 视为密钥的字段名片段（字段名转小写并去掉 `_`/`-` 后匹配）
+const SECRET_FIELD_PATTERNS: &[&str] = &[
+    "apikey",
+    "token",
+    "secret",
+    "password",
+    "authorization",
+    "credential",
+];
+
+/// Profile 导出信封
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileExport {
+    pub format: String,
+    pub version: u32,
+    pub tool_id: String,
+    pub profile_name: String,
+    pub exported_at: DateTime<Utc>,
+    /// API Key 是否已替换为占位符
+    #[serde(default)]
+    pub masked: bool,
+    pub profile: Value,
+}
+
+impl super::manager::ProfileManager {
+    /// 导出 Profile 为 JSON 信封
+    ///
+    /// `mask` 为真时，profile 中所有出现 API Key 的位置（含原始配置文本）替换为占位符
+    pub fn export_profile(&self, tool_id: &str, profile_name: &str, mask: bool) -> Result<String> {
+        let (api_key, mut profile) = match tool_id {
+            "claude-code" => {
+                let p = self.get_claude_profile(profile_name)?;
+                (p.api_key.clone(), serde_json::to_value(p)?)
+            }
+            "codex" => {
+                let p = self.get_codex_profile(profile_name)?;
+                (p.api_key.clone(), serde_json::to_value(p)?)
+            }
+            "gemini-cli" => {
+                let p = self.get_gemini_profile(profile_name)?;
+                (p.api_key.clone(), serde_json::to_value(p)?)
+            }
+            _ => return Err(anyhow!("不支持的工具 ID: {}", tool_id)),
+        };
+
+        if mask && !api_key.is_empty() {
+            mask_secret(&mut profile, &api_key);
+        }
+
+        let envelope = ProfileExport {
+            format: PROFILE_EXPORT_FORMAT.to_string(),
+            version: PROFILE_EXPORT_VERSION,
+            tool_id: tool_id.to_string(),
+            profile_name: profile_name.to_string(),
+            exported_at: Utc::now(),
+            masked: mask,
+            profile,
+        };
+
+        serde_json::to_string_pretty(&envelope).context("序列化 Profile 导出数据失败")
+    }
+
+    /// 导出去除所有密钥的 Profile（供分享配置参数，导入方自行填写 API Key）
+    ///
+    /// 在 `export_profile(.., true)` 基础上：
+    /// - `api_key` 置空，Codex 的 `raw_auth_json`（仅含凭证）整体移除
+    /// - 原始 JSON 配置中字段名像密钥的条目（如 `ANTHROPIC_AUTH_TOKEN`）移除
+    /// - Codex `raw_config_toml` 与 Gemini `raw_env` 中键名像密钥的行移除
+    /// - 来源重置为自定义（不泄露供应商令牌信息）
+    pub fn export_config_sanitized(&self, tool_id: &str, profile_name: &str) -> Result<String> {
+        let data = self.export_profile(tool_id, profile_name, true)?;
+        let mut envelope: ProfileExport =
+            serde_json::from_str(&data).context("解析 Profile 导出数据失败")?;
+
+        if let Value::Object(profile) = &mut envelope.profile {
+            profile.insert("api_key".to_string(), Value::String(String::new()));
+            profile.insert(
+                "source".to_string(),
+                serde_json::json!({ "type": "Custom" }),
+            );
+            profile.remove("raw_auth_json");
+            for field in ["raw_settings", "raw_config_json"] {
+                if let Some(value) = profile.get_mut(field) {
+                    remove_secret_fields(value);
+                }
+            }
+            for field in ["raw_config_toml", "raw_env"] {
+                if let Some(Value::String(text)) = profile.get_mut(field) {
+                    *text = remove_secret_lines(text);
+                }
+            }
+        }
+
+        serde_json::to_string_pretty(&envelope).context("序列化 Profile 导出数据失败")
+    }
+
+    /// 从 JSON 信封导入 Profile，返回最终保存的 Profile 名称
+    ///
+    /// - 信封的 `tool_id` 必须与调用方一致，版本不能高于当前支持版本
+    /// - `new_name` 为空时沿用信封中的名称，已存在同名 Profile 时报错
+    /// - 导入后来源统一标记为自定义，创建/更新时间重置为当前时间
+    pub fn import_profile(
+        &self,
+        tool_id: &str,
+        data: &str,
+        new_name: Option<String>,
+    ) -> Result<String> {
+        let envelope: ProfileExport =
+            serde_json::from_str(data).context("无法解析 Profile 导出文件")?;
+
+        if envelope.format != PROFILE_EXPORT_FORMAT {
+            return Err(anyhow!("不是有效的 Profile 导出文件"));
+        }
+        if envelope.version == 0 || envelope.version > PROFILE_EXPORT_VERSION {
+            return Err(anyhow!(
+                "不支持的导出文件版本: {}（当前支持 {}）",
+                envelope.version,
+                PROFILE_EXPORT_VERSION
+            ));
+        }
+        if envelope.tool_id != tool_id {
+            return Err(anyhow!(
+                "导出文件属于 {}，无法导入到 {}",
+                envelope.tool_id,
+                tool_id
+            ));
+        }
+
+        let name = new_name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| envelope.profile_name.clone());
+        super::manager::validate_profile_name(&name)?;
+
+        let mut store = self.load_profiles_store()?;
+        let now = Utc::now();
+
+        match tool_id {
+            "claude-code" => {
+                if store.claude_code.contains_key(&name) {
+                    return Err(anyhow!("Profile 已存在: {}", name));
+                }
+                let mut profile: ClaudeProfile = serde_json::from_value(envelope.profile)
+                    .context("Claude Profile 数据格式无效")?;
+                profile.source = ProfileSource::Custom;
+                profile.created_at = now;
+                profile.updated_at = now;
+                store.claude_code.insert(name.clone(), profile);
+            }
+            "codex" => {
+                if store.codex.contains_key(&name) {
+                    return Err(anyhow!("Profile 已存在: {}", name));
+                }
+                let mut profile: CodexProfile = serde_json::from_value(envelope.profile)
+                    .context("Codex Profile 数据格式无效")?;
+                profile.source = ProfileSource::Custom;
+                profile.created_at = now;
+                profile.updated_at = now;
+                store.codex.insert(name.clone(), profile);
+            }
+            "gemini-cli" => {
+                if store.gemini_cli.contains_key(&name) {
+                    return Err(anyhow!("Profile 已存在: {}", name));
+                }
+                let mut profile: GeminiProfile = serde_json::from_value(envelope.profile)
+                    .context("Gemini Profile 数据格式无效")?;
+                profile.source = ProfileSource::Custom;
+                profile.created_at = now;
+                profile.updated_at = now;
+                store.gemini_cli.insert(name.clone(), profile);
+            }
+            _ => return Err(anyhow!("不支持的工具 ID: {}", tool_id)),
+        }
+
+        store.metadata.last_updated = now;
+        self.save_profiles_store(&store)?;
+        self.audit("import_profile", tool_id, Some(&name));
+
+        if envelope.masked {
+            tracing::warn!(
+                tool_id = tool_id,
+                profile = %name,
+                "导入的 Profile API Key 已脱敏，需要重新填写"
+            );
+        }
+
+        Ok(name)
+    }
+}
+
+/// 递归替换 JSON 中所有字符串里出现的密钥
+fn mask_secret(value: &mut Value, secret: &str) {
+    match value {
+        Value::String(s) if s.contains(secret) => *s = s.replace(secret, MASKED_API_KEY),
+        Value::Array(items) => items.iter_mut().for_each(|v| mask_secret(v, secret)),
+        Value::Object(map) => map.values_mut().for_each(|v| mask_secret(v, secret)),
+        _ => {}
+    }
+}
+
+/// 字段名是否像密钥（`max_tokens` 等以 tokens 结尾的计数参数除外）
+fn is_secret_field(name: &str) -> bool {
+    let normalized: String = name
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_lowercase();
+    if normalized.contains("tokens") {
+        return false;
+    }
+    SECRET_FIELD_PATTERNS
+        .iter()
+        .any(|pattern| normalized.contains(pattern))
+}
+
+/// 递归移除 JSON 中字段名像密钥的条目
+fn remove_secret_fields(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(remove_secret_fields),
+        Value::Object(map) => {
+            map.retain(|key, _| !is_secret_field(key));
+            map.values_mut().for_each(remove_secret_fields);
+        }
+        _ => {}
+    }
+}
+
+/// 移除 `KEY=VALUE`（.env）或 `key = "value"`（TOML）文本中键名像密钥的行
+fn remove_secret_lines(text: &str) -> String {
+    let mut result: String = text
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            match line.split_once('=') {
+                Some((key, _)) if !line.starts_with('#') => {
+                    !is_secret_field(key.trim().trim_matches('"'))
+                }
+                _ => true,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::manager::ProfileManager;
+    use super::*;
+    use tempfile::TempDir;
+
+    const CODEX_TOML: &str = "# 自定义注释\nmodel = \"gpt-5\"\n\n[model_providers.team]\nbase_url = \"https://api.example.com/v1\"\n";
+
+    fn seed_codex(manager: &ProfileManager) -> Result<()> {
+        let mut store = ProfilesStore::new();
+        store.codex.insert(
+            "team".to_string(),
+            CodexProfile {
+                api_key: "sk-secret".to_string(),
+                base_url: "https://api.example.com/v1".to_string(),
+                wire_api: "responses".to_string(),
+                source: ProfileSource::Custom,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_config_toml: Some(CODEX_TOML.to_string()),
+                raw_auth_json: Some(serde_json::json!({ "OPENAI_API_KEY": "sk-secret" })),
+                pricing_template_id: None,
+            },
+        );
+        manager.save_profiles_store(&store)
+    }
+
+    #[test]
+    fn test_export_import_roundtrip_preserves_raw_text() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ProfileManager::with_paths(temp_dir.path());
+        seed_codex(&manager)?;
+
+        let data = manager.export_profile("codex", "team", false)?;
+        let name = manager.import_profile("codex", &data, Some("team-copy".to_string()))?;
+        assert_eq!(name, "team-copy");
+
+        let imported = manager.get_codex_profile("team-copy")?;
+        assert_eq!(imported.api_key, "sk-secret");
+        assert_eq!(imported.raw_config_toml.as_deref(), Some(CODEX_TOML));
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_masked_replaces_all_key_occurrences() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ProfileManager::with_paths(temp_dir.path());
+        seed_codex(&manager)?;
+
+        let data = manager.export_profile("codex", "team", true)?;
+        assert!(!data.contains("sk-secret"));
+
+        let envelope: ProfileExport = serde_json::from_str(&data)?;
+        assert!(envelope.masked);
+        assert_eq!(envelope.profile["api_key"], MASKED_API_KEY);
+        assert_eq!(
+            envelope.profile["raw_auth_json"]["OPENAI_API_KEY"],
+            MASKED_API_KEY
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_rejects_mismatch_version_and_conflict() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ProfileManager::with_paths(temp_dir.path());
+        seed_codex(&manager)?;
+
+        let data = manager.export_profile("codex", "team", false)?;
+
+        // 工具不一致
+        assert!(manager.import_profile("claude-code", &data, None).is_err());
+
+        // 同名冲突
+        assert!(manager.import_profile("codex", &data, None).is_err());
+
+        // 版本过新
+        let mut envelope: ProfileExport = serde_json::from_str(&data)?;
+        envelope.version = PROFILE_EXPORT_VERSION + 1;
+        let newer = serde_json::to_string(&envelope)?;
+        assert!(manager
+            .import_profile("codex", &newer, Some("other".to_string()))
+            .is_err());
+
+        // 保留前缀
+        assert!(manager
+            .import_profile("codex", &data, Some("dc_proxy_x".to_string()))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_config_sanitized_removes_secrets() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ProfileManager::with_paths(temp_dir.path());
+        seed_codex(&manager)?;
+
+        let mut store = manager.load_profiles_store()?;
+        store.claude_code.insert(
+            "shared".to_string(),
+            ClaudeProfile {
+                api_key: "sk-ant-secret".to_string(),
+                base_url: "https://api.example.com".to_string(),
+                source: ProfileSource::ImportedFromProvider {
+                    provider_id: "p".to_string(),
+                    provider_name: "Provider".to_string(),
+                    remote_token_id: 7,
+                    remote_token_name: "team-token".to_string(),
+                    group: "default".to_string(),
+                    imported_at: 0,
+                },
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_settings: Some(serde_json::json!({
+                    "model": "opus",
+                    "env": {
+                        "ANTHROPIC_AUTH_TOKEN": "sk-ant-other",
+                        "ANTHROPIC_BASE_URL": "https://api.example.com",
+                        "CLAUDE_CODE_MAX_OUTPUT_TOKENS": "32000",
+                    },
+                })),
+                raw_config_json: None,
+                pricing_template_id: None,
+            },
+        );
+        store.gemini_cli.insert(
+            "shared".to_string(),
+            GeminiProfile {
+                api_key: "gm-secret".to_string(),
+                base_url: "https://gemini.example.com".to_string(),
+                model: Some("gemini-2.5-pro".to_string()),
+                source: ProfileSource::Custom,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_settings: None,
+                raw_env: Some(
+                    "GEMINI_API_KEY=gm-secret\nexport GOOGLE_API_KEY=other\nGEMINI_MODEL=gemini-2.5-pro\n"
+                        .to_string(),
+                ),
+                pricing_template_id: None,
+                auth: None,
+            },
+        );
+        manager.save_profiles_store(&store)?;
+
+        // Claude：api_key 置空，env 中的令牌移除，模型与输出上限等参数保留
+        let data = manager.export_config_sanitized("claude-code", "shared")?;
+        assert!(!data.contains("sk-ant"));
+        assert!(!data.contains("team-token"));
+        let envelope: ProfileExport = serde_json::from_str(&data)?;
+        assert!(envelope.masked);
+        assert_eq!(envelope.profile["api_key"], "");
+        let env = &envelope.profile["raw_settings"]["env"];
+        assert!(env.get("ANTHROPIC_AUTH_TOKEN").is_none());
+        assert_eq!(env["CLAUDE_CODE_MAX_OUTPUT_TOKENS"], "32000");
+        assert_eq!(envelope.profile["raw_settings"]["model"], "opus");
+
+        // Codex：auth.json 整体移除，config.toml 原样保留
+        let data = manager.export_config_sanitized("codex", "team")?;
+        assert!(!data.contains("sk-secret"));
+        let envelope: ProfileExport = serde_json::from_str(&data)?;
+        assert!(envelope.profile.get("raw_auth_json").is_none());
+        assert_eq!(envelope.profile["raw_config_toml"], CODEX_TOML);
+
+        // Gemini：.env 中的密钥行移除
+        let data = manager.export_config_sanitized("gemini-cli", "shared")?;
+        assert!(!data.contains("gm-secret") && !data.contains("other"));
+        let envelope: ProfileExport = serde_json::from_str(&data)?;
+        assert_eq!(envelope.profile["raw_env"], "GEMINI_MODEL=gemini-2.5-pro\n");
+
+        // 导入方得到空 API Key 的 Profile，自行填写
+        let name = manager.import_profile("gemini-cli", &data, Some("from-peer".to_string()))?;
+        let imported = manager.get_gemini_profile(&name)?;
+        assert!(imported.api_key.is_empty());
+        assert_eq!(imported.model.as_deref(), Some("gemini-2.5-pro"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_secret_field() {
+        for name in [
+            "api_key",
+            "OPENAI_API_KEY",
+            "ANTHROPIC_AUTH_TOKEN",
+            "apiKey",
+            "client_secret",
+        ] {
+            assert!(is_secret_field(name), "{name}");
+        }
+        for name in [
+            "model",
+            "max_tokens",
+            "MAX_THINKING_TOKENS",
+            "base_url",
+            "selectedAuthType",
+        ] {
+            assert!(!is_secret_field(name), "{name}");
+        }
+    }
+}
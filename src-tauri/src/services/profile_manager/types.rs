@@ -2,6 +2,7 @@
 //!
 //! 设计原则：工具分组即类型，使用具体结构体替代 enum
 
+use crate::utils::mask_secret;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -127,6 +128,80 @@ pub struct GeminiProfile {
     /// 价格模板 ID（用于成本计算）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pricing_template_id: Option<String>,
+    /// 认证配置（None 表示 api-key 模式）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<GeminiAuth>,
+}
+
+impl GeminiProfile {
+    /// 当前认证模式
+    pub fn auth_mode(&self) -> GeminiAuthMode {
+        self.auth
+            .as_ref()
+            .map(|auth| auth.auth_mode)
+            .unwrap_or_default()
+    }
+}
+
+/// Gemini CLI 认证模式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeminiAuthMode {
+    /// API Key（写入 GEMINI_API_KEY）
+    #[default]
+    ApiKey,
+    /// Vertex AI（写入 GOOGLE_CLOUD_PROJECT / GOOGLE_CLOUD_LOCATION）
+    Vertex,
+    /// Google 账号 OAuth 登录
+    Oauth,
+}
+
+impl GeminiAuthMode {
+    /// settings.json 中 `security.auth.selectedType` 对应的值
+    pub fn selected_type(self) -> &'static str {
+        match self {
+            Self::ApiKey => "gemini-api-key",
+            Self::Vertex => "vertex-ai",
+            Self::Oauth => "oauth-personal",
+        }
+    }
+}
+
+/// Gemini CLI 认证配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeminiAuth {
+    #[serde(default)]
+    pub auth_mode: GeminiAuthMode,
+    /// Vertex 模式的 GCP 项目 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Vertex 模式的区域（如 us-central1）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl GeminiAuth {
+    /// 校验认证配置（Vertex 模式必须提供项目与区域）
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.auth_mode == GeminiAuthMode::Vertex {
+            let missing =
+                |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+            if missing(&self.project) || missing(&self.location) {
+                anyhow::bail!("Vertex 认证模式需要填写 GCP 项目 ID 与区域");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Profile 切换结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileSwitch {
+    pub tool_id: String,
+    /// 切换前激活的 Profile（首次激活时为 None）
+    pub previous: Option<String>,
+    /// 切换后激活的 Profile
+    pub current: String,
 }
 
 // ==================== profiles.json 结构 ====================
@@ -205,6 +280,12 @@ pub struct ActiveStore {
     pub codex: Option<ActiveProfile>,
     #[serde(rename = "gemini-cli")]
     pub gemini_cli: Option<ActiveProfile>,
+    /// 各 Profile 最后被激活的时间（tool_id -> profile_name -> 时间）
+    #[serde(default)]
+    pub last_used: HashMap<String, HashMap<String, DateTime<Utc>>>,
+    /// 各工具最近一次 Profile 切换（tool_id -> 切换记录，仅保留最近一次用于撤销）
+    #[serde(default)]
+    pub last_switch: HashMap<String, ProfileSwitch>,
     pub metadata: ActiveMetadata,
 }
 
@@ -215,6 +296,8 @@ impl ActiveStore {
             claude_code: None,
             codex: None,
             gemini_cli: None,
+            last_used: HashMap::new(),
+            last_switch: HashMap::new(),
             metadata: ActiveMetadata {
                 last_updated: Utc::now(),
             },
@@ -240,6 +323,11 @@ impl ActiveStore {
     }
 
     pub fn set_active(&mut self, tool_id: &str, profile_name: String) {
+        self.last_used
+            .entry(tool_id.to_string())
+            .or_default()
+            .insert(profile_name.clone(), Utc::now());
+
         let active = ActiveProfile {
             profile: profile_name,
             switched_at: Utc::now(),
@@ -259,6 +347,53 @@ impl ActiveStore {
         self.metadata.last_updated = Utc::now();
     }
 
+    /// 获取 Profile 最后被激活的时间
+    pub fn get_last_used(&self, tool_id: &str, profile_name: &str) -> Option<DateTime<Utc>> {
+        self.last_used
+            .get(tool_id)
+            .and_then(|profiles| profiles.get(profile_name))
+            .copied()
+    }
+
+    /// 移除 Profile 的使用记录（删除 Profile 时调用）
+    pub fn remove_last_used(&mut self, tool_id: &str, profile_name: &str) {
+        if let Some(profiles) = self.last_used.get_mut(tool_id) {
+            profiles.remove(profile_name);
+        }
+    }
+
+    /// 记录最近一次切换（覆盖之前的记录，重复激活同一 Profile 不记录）
+    pub fn record_switch(&mut self, switch: ProfileSwitch) {
+        if switch.previous.as_deref() == Some(switch.current.as_str()) {
+            return;
+        }
+        self.last_switch.insert(switch.tool_id.clone(), switch);
+    }
+
+    /// 获取撤销最近一次切换需要恢复的 Profile 名称
+    ///
+    /// 无切换记录、首次激活（无前值）或当前激活已不是该次切换的结果时返回错误
+    pub fn undo_target(&self, tool_id: &str) -> anyhow::Result<String> {
+        let switch = self
+            .last_switch
+            .get(tool_id)
+            .ok_or_else(|| anyhow::anyhow!("{} 没有可撤销的配置切换", tool_id))?;
+        let previous = switch
+            .previous
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("{} 切换前未激活任何配置，无法撤销", tool_id))?;
+
+        let current = self.get_active(tool_id).map(|ap| ap.profile.as_str());
+        if current != Some(switch.current.as_str()) {
+            anyhow::bail!(
+                "{} 当前激活配置已变更（{}），无法撤销",
+                tool_id,
+                current.unwrap_or("无")
+            );
+        }
+        Ok(previous)
+    }
+
     pub fn clear_active(&mut self, tool_id: &str) {
         match tool_id {
             "claude-code" => self.claude_code = None,
@@ -312,6 +447,9 @@ pub struct ProfileDescriptor {
     pub is_active: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub switched_at: Option<DateTime<Utc>>,
+    /// 最后被激活的时间（从未激活过为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -319,6 +457,9 @@ pub struct ProfileDescriptor {
     /// 价格模板 ID（用于成本计算）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pricing_template_id: Option<String>,
+    /// Gemini 认证模式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_mode: Option<GeminiAuthMode>,
 }
 
 impl ProfileDescriptor {
@@ -326,6 +467,7 @@ impl ProfileDescriptor {
         name: &str,
         profile: &ClaudeProfile,
         active_profile: Option<&ActiveProfile>,
+        last_used_at: Option<DateTime<Utc>>,
     ) -> Self {
         let is_active = active_profile.map(|ap| ap.profile == name).unwrap_or(false);
         let switched_at = if is_active {
@@ -344,9 +486,11 @@ impl ProfileDescriptor {
             updated_at: profile.updated_at,
             is_active,
             switched_at,
+            last_used_at,
             provider: None,
             model: None,
             pricing_template_id: profile.pricing_template_id.clone(),
+            auth_mode: None,
         }
     }
 
@@ -354,6 +498,7 @@ impl ProfileDescriptor {
         name: &str,
         profile: &CodexProfile,
         active_profile: Option<&ActiveProfile>,
+        last_used_at: Option<DateTime<Utc>>,
     ) -> Self {
         let is_active = active_profile.map(|ap| ap.profile == name).unwrap_or(false);
         let switched_at = if is_active {
@@ -372,9 +517,11 @@ impl ProfileDescriptor {
             updated_at: profile.updated_at,
             is_active,
             switched_at,
+            last_used_at,
             provider: Some(profile.wire_api.clone()), // 前端仍使用 provider 字段名
             model: None,
             pricing_template_id: profile.pricing_template_id.clone(),
+            auth_mode: None,
         }
     }
 
@@ -382,6 +529,7 @@ impl ProfileDescriptor {
         name: &str,
         profile: &GeminiProfile,
         active_profile: Option<&ActiveProfile>,
+        last_used_at: Option<DateTime<Utc>>,
     ) -> Self {
         let is_active = active_profile.map(|ap| ap.profile == name).unwrap_or(false);
         let switched_at = if is_active {
@@ -400,22 +548,24 @@ impl ProfileDescriptor {
             updated_at: profile.updated_at,
             is_active,
             switched_at,
+            last_used_at,
             provider: None,
             model: profile.model.clone(),
             pricing_template_id: profile.pricing_template_id.clone(),
+            auth_mode: Some(profile.auth_mode()),
         }
     }
 }
 
 // ==================== 辅助函数 ====================
 
+/// 脱敏 API Key：保留前后各 4 个字符并附带总长度（按字符截取，兼容非 ASCII）
 fn mask_api_key(key: &str) -> String {
-    if key.len() <= 8 {
-        return "****".to_string();
+    match key.chars().count() {
+        0 => "未设置".to_string(),
+        len if len <= 8 => mask_secret(key),
+        len => format!("{} (共 {} 位)", mask_secret(key), len),
     }
-    let prefix = &key[..4];
-    let suffix = &key[key.len() - 4..];
-    format!("{}...{}", prefix, suffix)
 }
 
 // ==================== 令牌导入状态 ====================
@@ -430,3 +580,93 @@ pub struct TokenImportStatus {
     /// 已导入的 Profile 名称（如果已导入）
     pub imported_profile_name: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_api_key() {
+        assert_eq!(
+            mask_api_key("sk-1234567890abcdefxyz9"),
+            "sk-1...xyz9 (共 23 位)"
+        );
+        // 恰好 8 个字符不显示任何部分
+        assert_eq!(mask_api_key("12345678"), "****");
+        assert_eq!(mask_api_key("123456789"), "1234...6789 (共 9 位)");
+        assert_eq!(mask_api_key(""), "未设置");
+    }
+
+    #[test]
+    fn test_mask_api_key_non_ascii() {
+        // 多字节字符按字符截取，不会在字节边界 panic
+        assert_eq!(mask_api_key("密钥密钥密钥密钥"), "****");
+        assert_eq!(
+            mask_api_key("令牌-abc-中文密钥结尾"),
+            "令牌-a...密钥结尾 (共 13 位)"
+        );
+        assert_eq!(
+            mask_api_key("🔑🔑sk-test-🦆🦆"),
+            "🔑🔑sk...t-🦆🦆 (共 12 位)"
+        );
+    }
+
+    fn switch(tool_id: &str, previous: Option<&str>, current: &str) -> ProfileSwitch {
+        ProfileSwitch {
+            tool_id: tool_id.to_string(),
+            previous: previous.map(str::to_string),
+            current: current.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_undo_target_after_switch() {
+        let mut store = ActiveStore::new();
+        assert!(store.undo_target("codex").is_err());
+
+        store.set_active("codex", "work".to_string());
+        store.record_switch(switch("codex", Some("home"), "work"));
+        assert_eq!(store.undo_target("codex").unwrap(), "home");
+
+        // 仅保留最近一次切换
+        store.set_active("codex", "backup".to_string());
+        store.record_switch(switch("codex", Some("work"), "backup"));
+        assert_eq!(store.undo_target("codex").unwrap(), "work");
+
+        // 重复激活同一 Profile 不覆盖撤销记录
+        store.record_switch(switch("codex", Some("backup"), "backup"));
+        assert_eq!(store.undo_target("codex").unwrap(), "work");
+
+        // 其他工具互不影响
+        assert!(store.undo_target("claude-code").is_err());
+    }
+
+    #[test]
+    fn test_undo_target_rejects_stale_or_first_switch() {
+        let mut store = ActiveStore::new();
+
+        // 首次激活没有前值
+        store.set_active("claude-code", "first".to_string());
+        store.record_switch(switch("claude-code", None, "first"));
+        assert!(store.undo_target("claude-code").is_err());
+
+        // 切换后当前激活又被其他途径修改
+        store.record_switch(switch("claude-code", Some("first"), "second"));
+        store.set_active("claude-code", "third".to_string());
+        let err = store.undo_target("claude-code").unwrap_err().to_string();
+        assert!(err.contains("已变更"));
+    }
+
+    #[test]
+    fn test_active_store_without_last_switch_deserializes() {
+        let json = serde_json::json!({
+            "version": "2.0.0",
+            "claude-code": null,
+            "codex": null,
+            "gemini-cli": null,
+            "metadata": { "last_updated": "2026-01-01T00:00:00Z" }
+        });
+        let store: ActiveStore = serde_json::from_value(json).unwrap();
+        assert!(store.last_switch.is_empty());
+    }
+}
@@ -6,7 +6,7 @@ use crate::data::DataManager;
 use crate::models::provider::{Provider, ProviderStore};
 use crate::utils::config::config_dir;
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 /// 供应商管理器
@@ -31,6 +31,20 @@ impl ProviderManager {
         })
     }
 
+    /// 使用指定存储路径创建实例（用于测试或自定义场景）
+    pub fn with_store_path(store_path: PathBuf) -> Self {
+        Self {
+            data_manager: Arc::new(DataManager::new()),
+            store_path,
+            cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 存储文件路径
+    pub fn store_path(&self) -> &Path {
+        &self.store_path
+    }
+
     /// 读取存储（带缓存）
     pub fn load_store(&self) -> Result<ProviderStore> {
         // 检查缓存
@@ -135,6 +149,43 @@ impl ProviderManager {
         Ok(())
     }
 
+    /// 自动签到全局总开关是否开启
+    pub fn is_checkin_enabled(&self) -> Result<bool> {
+        Ok(self.load_store()?.checkin_enabled)
+    }
+
+    /// 设置自动签到全局总开关（优先于各供应商的签到开关）
+    pub fn set_checkin_enabled(&self, enabled: bool) -> Result<()> {
+        let mut store = self.load_store()?;
+        store.checkin_enabled = enabled;
+        store.updated_at = chrono::Utc::now().timestamp();
+        self.save_store(&store)
+    }
+
+    /// 批量切换所有已配置签到的供应商的签到开关，返回受影响的供应商数量
+    ///
+    /// 未配置签到的供应商不受影响；关闭时同时清除待执行的签到计划
+    pub fn set_checkin_enabled_all(&self, enabled: bool) -> Result<usize> {
+        let mut store = self.load_store()?;
+        let now = chrono::Utc::now().timestamp();
+
+        let mut count = 0;
+        for provider in store.providers.iter_mut() {
+            if let Some(config) = provider.checkin_config.as_mut() {
+                config.enabled = enabled;
+                if !enabled {
+                    config.next_checkin_at = None;
+                }
+                provider.updated_at = now;
+                count += 1;
+            }
+        }
+
+        store.updated_at = now;
+        self.save_store(&store)?;
+        Ok(count)
+    }
+
     /// 清除缓存（用于测试或强制刷新）
     pub fn clear_cache(&self) {
         *self.cache.lock().unwrap() = None;
@@ -165,4 +216,48 @@ mod tests {
         assert_eq!(store.providers.len(), 1);
         assert_eq!(store.providers[0].id, "duckcoding");
     }
+
+    #[test]
+    fn test_checkin_global_switch_and_batch_toggle() {
+        use crate::models::provider::CheckinConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ProviderManager::with_store_path(dir.path().join("providers.json"));
+
+        let mut with_checkin = manager.list_providers().unwrap()[0].clone();
+        with_checkin.id = "with-checkin".to_string();
+        with_checkin.is_default = false;
+        with_checkin.checkin_config = Some(CheckinConfig {
+            enabled: true,
+            next_checkin_at: Some(1_700_000_000),
+            ..Default::default()
+        });
+        manager.create_provider(with_checkin).unwrap();
+
+        // 全局总开关默认开启，可持久化关闭
+        assert!(manager.is_checkin_enabled().unwrap());
+        manager.set_checkin_enabled(false).unwrap();
+        manager.clear_cache();
+        assert!(!manager.is_checkin_enabled().unwrap());
+
+        // 批量关闭只影响已配置签到的供应商，并清除待执行计划
+        assert_eq!(manager.set_checkin_enabled_all(false).unwrap(), 1);
+        manager.clear_cache();
+        let providers = manager.list_providers().unwrap();
+        let config = providers
+            .iter()
+            .find(|p| p.id == "with-checkin")
+            .and_then(|p| p.checkin_config.as_ref())
+            .unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.next_checkin_at, None);
+        assert!(providers
+            .iter()
+            .find(|p| p.id == "duckcoding")
+            .unwrap()
+            .checkin_config
+            .is_none());
+
+        assert_eq!(manager.set_checkin_enabled_all(true).unwrap(), 1);
+    }
 }
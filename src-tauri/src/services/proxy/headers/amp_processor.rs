@@ -9,10 +9,11 @@
 // 5. 直接 LLM 路径 → 按路径/headers/model 判断
 
 use super::{
-    ClaudeHeadersProcessor, CodexHeadersProcessor, GeminiHeadersProcessor, ProcessedRequest,
-    RequestProcessor,
+    ClaudeHeadersProcessor, CodexHeadersProcessor, GeminiHeadersProcessor, HeaderFilter,
+    ProcessedRequest, RequestProcessor,
 };
 use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::log_recorder::RequestLogMeta;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -385,6 +386,7 @@ impl AmpHeadersProcessor {
             target_url,
             headers: new_headers,
             body: body.to_vec().into(),
+            session_profile: None,
         })
     }
 
@@ -509,6 +511,7 @@ impl AmpHeadersProcessor {
             target_url: format!("dc-local://{}", tool_name),
             headers: resp_headers,
             body: body_bytes,
+            session_profile: None,
         })
     }
 
@@ -959,6 +962,7 @@ impl AmpHeadersProcessor {
             target_url: format!("dc-local://{}", tool_name),
             headers,
             body: Bytes::from(body_bytes),
+            session_profile: None,
         })
     }
 
@@ -1105,6 +1109,7 @@ impl RequestProcessor for AmpHeadersProcessor {
         path: &str,
         query: Option<&str>,
         original_headers: &HyperHeaderMap,
+        header_filter: &HeaderFilter,
         body: &[u8],
     ) -> Result<ProcessedRequest> {
         // 0. 工具拦截：webSearch2 / extractWebPageContent
@@ -1209,6 +1214,7 @@ impl RequestProcessor for AmpHeadersProcessor {
                         &llm_path,
                         query,
                         original_headers,
+                        header_filter,
                         &final_body,
                     )
                     .await?;
@@ -1357,6 +1363,7 @@ impl RequestProcessor for AmpHeadersProcessor {
                         &llm_path,
                         query,
                         original_headers,
+                        header_filter,
                         body_to_forward,
                     )
                     .await?;
@@ -1381,6 +1388,7 @@ impl RequestProcessor for AmpHeadersProcessor {
                         &llm_path,
                         query,
                         original_headers,
+                        header_filter,
                         body,
                     )
                     .await?;
@@ -1400,14 +1408,11 @@ impl RequestProcessor for AmpHeadersProcessor {
     /// 但 tool_id 记录为 "amp-code"
     async fn record_request_log(
         &self,
-        client_ip: &str,
-        config_name: &str,
-        proxy_pricing_template_id: Option<&str>,
+        meta: &RequestLogMeta<'_>,
         request_body: &[u8],
         response_status: u16,
         response_body: &[u8],
         is_sse: bool,
-        response_time_ms: Option<i64>,
     ) -> Result<()> {
         use crate::services::proxy::log_recorder::{
             LogRecorder, RequestLogContext, ResponseParser,
@@ -1447,14 +1452,7 @@ impl RequestProcessor for AmpHeadersProcessor {
         };
 
         // 用 inner_tool_id 创建 context（确保 session_id 提取和 logger 选择正确）
-        let mut context = RequestLogContext::from_request(
-            inner_tool_id,
-            config_name,
-            client_ip,
-            proxy_pricing_template_id,
-            request_body,
-            response_time_ms,
-        );
+        let mut context = RequestLogContext::from_meta(inner_tool_id, meta, request_body);
 
         // 覆盖写入日志的 tool_type 为 "amp-code"
         context.override_tool_type = Some("amp-code".to_string());
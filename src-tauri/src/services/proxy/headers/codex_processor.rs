@@ -1,12 +1,12 @@
 // Codex 请求处理器
 
-use super::{ProcessedRequest, RequestProcessor};
+use super::{copy_forward_headers, HeaderFilter, ProcessedRequest, RequestProcessor, SessionRoute};
+use crate::services::proxy::log_recorder::RequestLogMeta;
 use crate::services::session::{SessionEvent, SESSION_MANAGER};
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
 use hyper::HeaderMap as HyperHeaderMap;
-use reqwest::header::HeaderMap as ReqwestHeaderMap;
 
 /// Codex 专用请求处理器
 ///
@@ -35,71 +35,31 @@ impl CodexHeadersProcessor {
         path: &str,
         query: Option<&str>,
         original_headers: &HyperHeaderMap,
+        header_filter: &HeaderFilter,
         body: &[u8],
     ) -> Result<ProcessedRequest> {
-        // 0. 查询会话配置并决定使用哪个 URL 和 API Key
-        let (final_base_url, final_api_key) = if !body.is_empty() {
-            // 尝试解析请求体 JSON 提取 prompt_cache_key
-            if let Ok(json_body) = serde_json::from_slice::<serde_json::Value>(body) {
-                if let Some(session_id) = json_body["prompt_cache_key"].as_str() {
-                    let timestamp = chrono::Utc::now().timestamp();
-
-                    // 查询会话配置
-                    if let Ok(Some((
-                        config_name,
-                        _custom_profile_name,
-                        session_url,
-                        session_api_key,
-                        _session_pricing_template_id,
-                    ))) = SESSION_MANAGER.get_session_config(session_id)
-                    {
-                        // 如果是自定义配置且有 URL 和 API Key，使用数据库的配置
-                        if config_name == "custom"
-                            && !session_url.is_empty()
-                            && !session_api_key.is_empty()
-                        {
-                            // 记录会话事件（使用自定义配置）
-                            if let Err(e) = SESSION_MANAGER.send_event(SessionEvent::NewRequest {
-                                session_id: session_id.to_string(),
-                                tool_id: caller_tool_id.to_string(),
-                                timestamp,
-                            }) {
-                                tracing::warn!("Session 事件发送失败: {}", e);
-                            }
-                            (session_url, session_api_key)
-                        } else {
-                            // 使用全局配置并记录会话
-                            if let Err(e) = SESSION_MANAGER.send_event(SessionEvent::NewRequest {
-                                session_id: session_id.to_string(),
-                                tool_id: caller_tool_id.to_string(),
-                                timestamp,
-                            }) {
-                                tracing::warn!("Session 事件发送失败: {}", e);
-                            }
-                            (base_url.to_string(), api_key.to_string())
-                        }
-                    } else {
-                        // 会话不存在，使用全局配置并记录新会话
-                        if let Err(e) = SESSION_MANAGER.send_event(SessionEvent::NewRequest {
-                            session_id: session_id.to_string(),
-                            tool_id: caller_tool_id.to_string(),
-                            timestamp,
-                        }) {
-                            tracing::warn!("Session 事件发送失败: {}", e);
-                        }
-                        (base_url.to_string(), api_key.to_string())
-                    }
-                } else {
-                    // 没有 prompt_cache_key，使用全局配置
-                    (base_url.to_string(), api_key.to_string())
+        // 0. 查询会话配置：会话覆盖生效时使用会话的 URL 和 API Key
+        let session_id = serde_json::from_slice::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json_body| json_body["prompt_cache_key"].as_str().map(str::to_string));
+        let session_route = match session_id.as_deref() {
+            Some(session_id) => {
+                let route = SessionRoute::lookup(session_id);
+                if let Err(e) = SESSION_MANAGER.send_event(SessionEvent::NewRequest {
+                    session_id: session_id.to_string(),
+                    tool_id: caller_tool_id.to_string(),
+                    timestamp: chrono::Utc::now().timestamp(),
+                }) {
+                    tracing::warn!("Session 事件发送失败: {}", e);
                 }
-            } else {
-                // JSON 解析失败，使用全局配置
-                (base_url.to_string(), api_key.to_string())
+                route
             }
-        } else {
-            // 空 body，使用全局配置
-            (base_url.to_string(), api_key.to_string())
+            // 没有 prompt_cache_key 或请求体无法解析，使用全局配置
+            None => None,
+        };
+        let (final_base_url, final_api_key) = match &session_route {
+            Some(route) => (route.base_url.as_str(), route.api_key.as_str()),
+            None => (base_url, api_key),
         };
 
         // 1. 构建目标 URL（Codex 特殊逻辑：避免 /v1 路径重复）
@@ -116,18 +76,7 @@ impl CodexHeadersProcessor {
         let target_url = format!("{base}{adjusted_path}{query_str}");
 
         // 2. 处理 headers（复制非认证 headers）
-        let mut headers = ReqwestHeaderMap::new();
-        for (name, value) in original_headers.iter() {
-            let name_str = name.as_str();
-            // 跳过认证相关和 Host headers
-            if name_str.eq_ignore_ascii_case("host")
-                || name_str.eq_ignore_ascii_case("authorization")
-                || name_str.eq_ignore_ascii_case("x-api-key")
-            {
-                continue;
-            }
-            headers.insert(name.clone(), value.clone());
-        }
+        let mut headers = copy_forward_headers(original_headers, &[], header_filter);
 
         // 3. 添加真实的 OpenAI API Key（Bearer Token 格式）
         headers.insert(
@@ -148,6 +97,7 @@ impl CodexHeadersProcessor {
             target_url,
             headers,
             body: Bytes::copy_from_slice(body),
+            session_profile: session_route.map(|route| route.profile),
         })
     }
 }
@@ -165,6 +115,7 @@ impl RequestProcessor for CodexHeadersProcessor {
         path: &str,
         query: Option<&str>,
         original_headers: &HyperHeaderMap,
+        header_filter: &HeaderFilter,
         body: &[u8],
     ) -> Result<ProcessedRequest> {
         self.process_outgoing_request_for(
@@ -174,6 +125,7 @@ impl RequestProcessor for CodexHeadersProcessor {
             path,
             query,
             original_headers,
+            header_filter,
             body,
         )
         .await
@@ -205,28 +157,18 @@ impl RequestProcessor for CodexHeadersProcessor {
     /// 使用统一的日志记录架构，自动处理所有错误场景
     async fn record_request_log(
         &self,
-        client_ip: &str,
-        config_name: &str,
-        proxy_pricing_template_id: Option<&str>,
+        meta: &RequestLogMeta<'_>,
         request_body: &[u8],
         response_status: u16,
         response_body: &[u8],
         is_sse: bool,
-        response_time_ms: Option<i64>,
     ) -> Result<()> {
         use crate::services::proxy::log_recorder::{
             LogRecorder, RequestLogContext, ResponseParser,
         };
 
         // 1. 创建请求上下文（一次性提取所有信息）
-        let context = RequestLogContext::from_request(
-            self.tool_id(),
-            config_name,
-            client_ip,
-            proxy_pricing_template_id,
-            request_body,
-            response_time_ms,
-        );
+        let context = RequestLogContext::from_meta(self.tool_id(), meta, request_body);
 
         // 2. 解析响应
         let parsed = ResponseParser::parse(response_body, response_status, is_sse);
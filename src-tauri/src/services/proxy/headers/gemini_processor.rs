@@ -1,11 +1,11 @@
 // Gemini CLI 请求处理器
 
-use super::{ProcessedRequest, RequestProcessor};
+use super::{copy_forward_headers, HeaderFilter, ProcessedRequest, RequestProcessor};
+use crate::services::proxy::log_recorder::RequestLogMeta;
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
 use hyper::HeaderMap as HyperHeaderMap;
-use reqwest::header::HeaderMap as ReqwestHeaderMap;
 
 /// Gemini CLI 专用请求处理器
 ///
@@ -34,6 +34,7 @@ impl RequestProcessor for GeminiHeadersProcessor {
         path: &str,
         query: Option<&str>,
         original_headers: &HyperHeaderMap,
+        header_filter: &HeaderFilter,
         body: &[u8],
     ) -> Result<ProcessedRequest> {
         // 1. 构建目标 URL（标准拼接）
@@ -42,19 +43,8 @@ impl RequestProcessor for GeminiHeadersProcessor {
         let target_url = format!("{base}{path}{query_str}");
 
         // 2. 处理 headers（复制非认证 headers）
-        let mut headers = ReqwestHeaderMap::new();
-        for (name, value) in original_headers.iter() {
-            let name_str = name.as_str();
-            // 跳过认证相关和 Host headers
-            if name_str.eq_ignore_ascii_case("host")
-                || name_str.eq_ignore_ascii_case("x-goog-api-key")
-                || name_str.eq_ignore_ascii_case("authorization")
-                || name_str.eq_ignore_ascii_case("x-api-key")
-            {
-                continue;
-            }
-            headers.insert(name.clone(), value.clone());
-        }
+        let mut headers =
+            copy_forward_headers(original_headers, &["x-goog-api-key"], header_filter);
 
         // 3. 添加真实的 Google API Key
         // Google APIs 通常使用 x-goog-api-key header
@@ -76,11 +66,56 @@ impl RequestProcessor for GeminiHeadersProcessor {
             target_url,
             headers,
             body: Bytes::copy_from_slice(body),
+            session_profile: None,
         })
     }
 
     // Gemini CLI 当前不需要特殊的响应处理
     // 如果未来需要（例如处理配额信息），可以在此实现
+
+    /// 提取模型名称
+    ///
+    /// 标准 Gemini API 的模型在 URL 路径中，仅 Code Assist 请求体带 model 字段
+    fn extract_model(&self, request_body: &[u8]) -> Option<String> {
+        if request_body.is_empty() {
+            return None;
+        }
+
+        serde_json::from_slice::<serde_json::Value>(request_body)
+            .ok()
+            .and_then(|json| {
+                json.get("model")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            })
+    }
+
+    /// Gemini CLI 的请求日志记录实现
+    ///
+    /// 使用统一的日志记录架构，自动处理所有错误场景
+    async fn record_request_log(
+        &self,
+        meta: &RequestLogMeta<'_>,
+        request_body: &[u8],
+        response_status: u16,
+        response_body: &[u8],
+        is_sse: bool,
+    ) -> Result<()> {
+        use crate::services::proxy::log_recorder::{
+            LogRecorder, RequestLogContext, ResponseParser,
+        };
+
+        // 1. 创建请求上下文（一次性提取所有信息）
+        let context = RequestLogContext::from_meta(self.tool_id(), meta, request_body);
+
+        // 2. 解析响应
+        let parsed = ResponseParser::parse(response_body, response_status, is_sse);
+
+        // 3. 记录日志（自动处理成功/失败/解析错误）
+        LogRecorder::record(&context, response_status, parsed).await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +136,7 @@ mod tests {
                 "/v1beta/models/gemini-2.0-flash:generateContent",
                 None,
                 &headers,
+                &HeaderFilter::default(),
                 b"{}",
             )
             .await;
@@ -134,6 +170,7 @@ mod tests {
                 "/v1beta/models/gemini-2.0-flash:generateContent",
                 None,
                 &headers,
+                &HeaderFilter::default(),
                 b"{}",
             )
             .await;
@@ -177,6 +214,7 @@ mod tests {
                 "/v1beta/models/gemini-2.0-flash:generateContent",
                 None,
                 &headers,
+                &HeaderFilter::default(),
                 b"{}",
             )
             .await;
@@ -206,6 +244,7 @@ mod tests {
                 "/v1beta/models/gemini-2.0-flash:generateContent",
                 None,
                 &headers,
+                &HeaderFilter::default(),
                 b"{}",
             )
             .await;
@@ -232,6 +271,7 @@ mod tests {
                 "/v1beta/models/gemini-2.0-flash:generateContent",
                 Some("key=value&foo=bar"),
                 &headers,
+                &HeaderFilter::default(),
                 b"{}",
             )
             .await;
@@ -6,6 +6,10 @@ use bytes::Bytes;
 use hyper::HeaderMap as HyperHeaderMap;
 use reqwest::header::HeaderMap as ReqwestHeaderMap;
 
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::proxy::log_recorder::RequestLogMeta;
+use crate::services::session::{SessionConfig, SESSION_MANAGER};
+
 mod amp_processor;
 mod claude_processor;
 mod codex_processor;
@@ -27,6 +31,181 @@ pub struct ProcessedRequest {
     pub headers: ReqwestHeaderMap,
     /// 处理后的请求体（大多数情况下与原始 body 相同）
     pub body: Bytes,
+    /// 会话覆盖生效时实际使用的配置（None 表示使用代理配置）
+    pub session_profile: Option<SessionProfile>,
+}
+
+impl ProcessedRequest {
+    /// 本请求实际生效的配置名与价格模板（用于日志与成本归属）
+    ///
+    /// 会话覆盖生效时使用会话的配置名，价格模板优先取会话级，未设置时回退到代理级
+    pub fn log_profile(
+        &self,
+        proxy_config_name: &str,
+        proxy_pricing_template_id: Option<&str>,
+    ) -> (String, Option<String>) {
+        match &self.session_profile {
+            Some(profile) => (
+                profile.config_name.clone(),
+                profile
+                    .pricing_template_id
+                    .clone()
+                    .or_else(|| proxy_pricing_template_id.map(String::from)),
+            ),
+            None => (
+                proxy_config_name.to_string(),
+                proxy_pricing_template_id.map(String::from),
+            ),
+        }
+    }
+
+    /// 将请求改写到另一个上游（替换 base_url 前缀与 API Key）
+    ///
+    /// 请求体已在内存中，可安全重放；会话覆盖生效或目标不以 `from_base` 开头时
+    /// （如 amp-code 自行路由）返回 None，不参与故障转移
+    pub fn retarget(
+        &self,
+        from_base: &str,
+        from_key: &str,
+        to_base: &str,
+        to_key: &str,
+    ) -> Option<ProcessedRequest> {
+        let from_base = from_base.trim_end_matches('/');
+        if self.session_profile.is_some() || from_base.is_empty() || from_key.is_empty() {
+            return None;
+        }
+        let rest = self.target_url.strip_prefix(from_base)?;
+
+        let mut headers = self.headers.clone();
+        for value in headers.values_mut() {
+            let Ok(text) = value.to_str() else {
+                continue;
+            };
+            if text.contains(from_key) {
+                *value = text.replace(from_key, to_key).parse().ok()?;
+            }
+        }
+
+        Some(ProcessedRequest {
+            target_url: format!(
+                "{}{}",
+                to_base.trim_end_matches('/'),
+                rest.replace(from_key, to_key)
+            ),
+            headers,
+            body: self.body.clone(),
+            session_profile: None,
+        })
+    }
+}
+
+/// 始终剥离的客户端 header（认证信息由 processor 替换为真实 API Key）
+const ALWAYS_STRIPPED_HEADERS: &[&str] = &["host", "authorization", "x-api-key"];
+
+/// 出站 header 过滤规则（来自代理配置，名称大小写不敏感）
+#[derive(Debug, Clone, Default)]
+pub struct HeaderFilter {
+    /// 额外剥离的 header
+    blocklist: Vec<String>,
+    /// 设置后仅透传列出的 header
+    passthrough: Option<Vec<String>>,
+}
+
+impl HeaderFilter {
+    pub fn new(blocklist: Vec<String>, passthrough: Option<Vec<String>>) -> Self {
+        Self {
+            blocklist,
+            passthrough,
+        }
+    }
+
+    pub fn from_config(config: &ToolProxyConfig) -> Self {
+        Self::new(
+            config.header_blocklist.clone(),
+            config.header_passthrough_override.clone(),
+        )
+    }
+
+    fn allows(&self, name: &str) -> bool {
+        if self.blocklist.iter().any(|b| b.eq_ignore_ascii_case(name)) {
+            return false;
+        }
+        match &self.passthrough {
+            Some(allowed) => allowed.iter().any(|a| a.eq_ignore_ascii_case(name)),
+            None => true,
+        }
+    }
+}
+
+/// 复制客户端 headers 到出站请求
+///
+/// 始终跳过 Host 与认证相关 headers（以及 processor 指定的 `extra_stripped`），
+/// 其余按 `filter` 的黑/白名单决定是否透传
+pub fn copy_forward_headers(
+    original_headers: &HyperHeaderMap,
+    extra_stripped: &[&str],
+    filter: &HeaderFilter,
+) -> ReqwestHeaderMap {
+    let mut headers = ReqwestHeaderMap::new();
+    for (name, value) in original_headers.iter() {
+        let name_str = name.as_str();
+        let stripped = ALWAYS_STRIPPED_HEADERS
+            .iter()
+            .chain(extra_stripped)
+            .any(|s| name_str.eq_ignore_ascii_case(s));
+        if stripped || !filter.allows(name_str) {
+            continue;
+        }
+        headers.insert(name.clone(), value.clone());
+    }
+    headers
+}
+
+/// 会话级配置（会话切换到自定义配置时生效）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionProfile {
+    /// 配置名（自定义 Profile 名称，未命名时为 "custom"）
+    pub config_name: String,
+    pub pricing_template_id: Option<String>,
+}
+
+/// 会话覆盖的路由目标
+pub(crate) struct SessionRoute {
+    pub base_url: String,
+    pub api_key: String,
+    pub profile: SessionProfile,
+}
+
+impl SessionRoute {
+    /// 从会话配置解析路由（仅 custom 且 URL、API Key 均已配置时生效）
+    pub fn from_session_config(config: SessionConfig) -> Option<Self> {
+        let (config_name, custom_profile_name, url, api_key, pricing_template_id) = config;
+        if config_name != "custom" || url.is_empty() || api_key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            base_url: url,
+            api_key,
+            profile: SessionProfile {
+                config_name: custom_profile_name
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or(config_name),
+                pricing_template_id: pricing_template_id.filter(|id| !id.is_empty()),
+            },
+        })
+    }
+
+    /// 查询会话是否覆盖了代理配置
+    pub fn lookup(session_id: &str) -> Option<Self> {
+        match SESSION_MANAGER.get_session_config(session_id) {
+            Ok(Some(config)) => Self::from_session_config(config),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(error = ?e, "查询会话配置失败，使用代理配置");
+                None
+            }
+        }
+    }
 }
 
 /// 请求处理器 trait
@@ -52,11 +231,13 @@ pub trait RequestProcessor: Send + Sync + std::fmt::Debug {
     /// - `path`: 原始请求路径（如 "/v1/messages"）
     /// - `query`: 可选的查询字符串（不包含 "?" 前缀）
     /// - `original_headers`: 客户端发送的原始 headers
+    /// - `header_filter`: 代理配置的 header 黑/白名单
     /// - `body`: 请求体字节数组
     ///
     /// # 返回
     /// - `Ok(ProcessedRequest)`: 处理成功，包含目标 URL、headers 和 body
     /// - `Err`: 处理失败（会中断请求）
+    #[allow(clippy::too_many_arguments)]
     async fn process_outgoing_request(
         &self,
         base_url: &str,
@@ -64,6 +245,7 @@ pub trait RequestProcessor: Send + Sync + std::fmt::Debug {
         path: &str,
         query: Option<&str>,
         original_headers: &HyperHeaderMap,
+        header_filter: &HeaderFilter,
         body: &[u8],
     ) -> Result<ProcessedRequest>;
 
@@ -115,28 +297,21 @@ pub trait RequestProcessor: Send + Sync + std::fmt::Debug {
     /// 因此每个工具需要实现自己的日志记录逻辑。
     ///
     /// # 参数
-    /// - `client_ip`: 客户端 IP 地址
-    /// - `config_name`: 配置名称（"global" 或 Profile 名称）
-    /// - `proxy_pricing_template_id`: 代理配置的价格模板 ID
+    /// - `meta`: 代理层采集的元数据（客户端 IP、配置、上游、耗时等，见 [`RequestLogMeta`]）
     /// - `request_body`: 请求体字节数组
     /// - `response_status`: HTTP 响应状态码
     /// - `response_body`: 响应体字节数组
     /// - `is_sse`: 是否为 SSE 流式响应
-    /// - `response_time_ms`: 响应时间（毫秒）
     ///
     /// # 默认实现
     /// 默认不记录日志（空操作）
-    #[allow(clippy::too_many_arguments)]
     async fn record_request_log(
         &self,
-        _client_ip: &str,
-        _config_name: &str,
-        _proxy_pricing_template_id: Option<&str>,
+        _meta: &RequestLogMeta<'_>,
         _request_body: &[u8],
         _response_status: u16,
         _response_body: &[u8],
         _is_sse: bool,
-        _response_time_ms: Option<i64>,
     ) -> Result<()> {
         Ok(())
     }
@@ -203,6 +378,7 @@ mod tests {
                 "/v1/messages",
                 None,
                 &headers,
+                &HeaderFilter::default(),
                 b"",
             )
             .await
@@ -217,4 +393,141 @@ mod tests {
             .unwrap();
         assert_eq!(auth_header, "Bearer test-api-key");
     }
+
+    #[test]
+    fn test_session_override_profile_attribution() {
+        let session_config = |config_name: &str, profile: Option<&str>, template: Option<&str>| {
+            (
+                config_name.to_string(),
+                profile.map(String::from),
+                "https://session.example.com".to_string(),
+                "sk-session".to_string(),
+                template.map(String::from),
+            )
+        };
+        let processed = |session_profile: Option<SessionProfile>| ProcessedRequest {
+            target_url: String::new(),
+            headers: ReqwestHeaderMap::new(),
+            body: Bytes::new(),
+            session_profile,
+        };
+
+        // 非 custom 或缺少 URL/API Key 时不覆盖
+        assert!(SessionRoute::from_session_config(session_config("global", None, None)).is_none());
+        let mut missing_key = session_config("custom", Some("team"), None);
+        missing_key.3.clear();
+        assert!(SessionRoute::from_session_config(missing_key).is_none());
+
+        // 会话覆盖生效：未设置会话价格模板时也按会话的配置名归属，模板回退到代理级
+        let route =
+            SessionRoute::from_session_config(session_config("custom", Some("team"), Some("")))
+                .unwrap();
+        assert_eq!(route.base_url, "https://session.example.com");
+        assert_eq!(
+            processed(Some(route.profile)).log_profile("proxy-profile", Some("proxy-tpl")),
+            ("team".to_string(), Some("proxy-tpl".to_string()))
+        );
+
+        // 未命名的自定义配置记为 custom，会话价格模板优先
+        let route =
+            SessionRoute::from_session_config(session_config("custom", Some(""), Some("tpl-1")))
+                .unwrap();
+        assert_eq!(
+            processed(Some(route.profile)).log_profile("proxy-profile", Some("proxy-tpl")),
+            ("custom".to_string(), Some("tpl-1".to_string()))
+        );
+
+        // 无会话覆盖时使用代理配置
+        assert_eq!(
+            processed(None).log_profile("proxy-profile", None),
+            ("proxy-profile".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_copy_forward_headers() {
+        let mut original = HyperHeaderMap::new();
+        original.insert("host", "127.0.0.1:8787".parse().unwrap());
+        original.insert("authorization", "Bearer local".parse().unwrap());
+        original.insert("x-api-key", "local".parse().unwrap());
+        original.insert("x-goog-api-key", "local".parse().unwrap());
+        original.insert("anthropic-beta", "tools-2024".parse().unwrap());
+        original.insert("anthropic-version", "2023-06-01".parse().unwrap());
+        original.insert("cookie", "sid=secret".parse().unwrap());
+
+        // 默认：仅剥离 Host 与认证 headers
+        let headers =
+            copy_forward_headers(&original, &["x-goog-api-key"], &HeaderFilter::default());
+        let mut names: Vec<&str> = headers.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["anthropic-beta", "anthropic-version", "cookie"]);
+
+        // 黑名单（大小写不敏感）
+        let filter = HeaderFilter::new(vec!["Cookie".to_string()], None);
+        let headers = copy_forward_headers(&original, &[], &filter);
+        assert!(headers.get("cookie").is_none());
+        assert!(headers.get("x-goog-api-key").is_some());
+
+        // 白名单：只透传列出的 header，认证 headers 即使列出也不透传，黑名单优先
+        let filter = HeaderFilter::new(
+            vec!["anthropic-version".to_string()],
+            Some(vec![
+                "Anthropic-Beta".to_string(),
+                "anthropic-version".to_string(),
+                "authorization".to_string(),
+            ]),
+        );
+        let headers = copy_forward_headers(&original, &[], &filter);
+        let names: Vec<&str> = headers.keys().map(|k| k.as_str()).collect();
+        assert_eq!(names, vec!["anthropic-beta"]);
+    }
+
+    #[test]
+    fn test_retarget_replaces_base_and_key() {
+        let mut headers = ReqwestHeaderMap::new();
+        headers.insert("authorization", "Bearer sk-primary".parse().unwrap());
+        headers.insert("anthropic-version", "2023-06-01".parse().unwrap());
+        let processed = ProcessedRequest {
+            target_url: "https://primary.example.com/v1/messages?beta=true".to_string(),
+            headers,
+            body: Bytes::from_static(b"{}"),
+            session_profile: None,
+        };
+
+        let backup = processed
+            .retarget(
+                "https://primary.example.com/",
+                "sk-primary",
+                "https://backup.example.com/api/",
+                "sk-backup",
+            )
+            .unwrap();
+        assert_eq!(
+            backup.target_url,
+            "https://backup.example.com/api/v1/messages?beta=true"
+        );
+        assert_eq!(backup.headers["authorization"], "Bearer sk-backup");
+        assert_eq!(backup.headers["anthropic-version"], "2023-06-01");
+        assert_eq!(backup.body, processed.body);
+
+        // 目标不是主上游（如会话覆盖或 amp-code 路由）时不改写
+        assert!(processed
+            .retarget("https://other.example.com", "sk-primary", "https://b", "k")
+            .is_none());
+        let session = ProcessedRequest {
+            session_profile: Some(SessionProfile {
+                config_name: "session".to_string(),
+                pricing_template_id: None,
+            }),
+            ..processed
+        };
+        assert!(session
+            .retarget(
+                "https://primary.example.com",
+                "sk-primary",
+                "https://b",
+                "k"
+            )
+            .is_none());
+    }
 }
@@ -0,0 +1,146 @@
+// 请求自动分类
+//
+// 职责：根据请求 endpoint 与模型名为日志打分类标签，供 analytics 按分类聚合
+//
+// 规则（按顺序匹配，命中即返回）：
+// - embedding: endpoint 含 embed（/v1/embeddings、:embedContent 等）或模型名含 embed
+// - image: endpoint 含 /images 或模型名含 image / dall-e / imagen
+// - coding: 模型名含 codex / coder / code，或为编码 Agent 专用接口
+//   （Codex 的 /v1/responses、Gemini Code Assist 的 v1internal）
+// - chat: 对话接口（/v1/messages、chat/completions、generateContent）或可识别模型的请求
+// - other: 其余请求
+
+/// 嵌入向量请求
+pub const CATEGORY_EMBEDDING: &str = "embedding";
+/// 图像生成请求
+pub const CATEGORY_IMAGE: &str = "image";
+/// 编码 Agent / 代码模型请求
+pub const CATEGORY_CODING: &str = "coding";
+/// 普通对话请求
+pub const CATEGORY_CHAT: &str = "chat";
+/// 无法识别的请求
+pub const CATEGORY_OTHER: &str = "other";
+
+/// 编码 Agent 专用接口片段
+const CODING_ENDPOINTS: &[&str] = &["/responses", "v1internal"];
+
+/// 代码模型名片段
+const CODING_MODELS: &[&str] = &["codex", "coder", "code"];
+
+/// 对话接口片段
+const CHAT_ENDPOINTS: &[&str] = &["/messages", "chat/completions", "generatecontent"];
+
+/// 按 endpoint（请求路径）与模型名判断请求分类
+pub fn classify_request(endpoint: &str, model: &str) -> &'static str {
+    let endpoint = endpoint.to_ascii_lowercase();
+    let model = model.to_ascii_lowercase();
+
+    if endpoint.contains("embed") || model.contains("embed") {
+        return CATEGORY_EMBEDDING;
+    }
+    if endpoint.contains("/images")
+        || ["image", "dall-e", "imagen"]
+            .iter()
+            .any(|m| model.contains(m))
+    {
+        return CATEGORY_IMAGE;
+    }
+    if CODING_ENDPOINTS.iter().any(|e| endpoint.contains(e))
+        || CODING_MODELS.iter().any(|m| model.contains(m))
+    {
+        return CATEGORY_CODING;
+    }
+    if CHAT_ENDPOINTS.iter().any(|e| endpoint.contains(e)) || is_known_model(&model) {
+        return CATEGORY_CHAT;
+    }
+    CATEGORY_OTHER
+}
+
+/// 模型名是否可识别（解析失败时日志中的模型为空或 unknown）
+fn is_known_model(model: &str) -> bool {
+    !model.is_empty() && model != "unknown"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_embedding_and_image() {
+        assert_eq!(
+            classify_request("/v1/embeddings", "text-embedding-3-small"),
+            CATEGORY_EMBEDDING
+        );
+        assert_eq!(
+            classify_request(
+                "/v1beta/models/gemini-embedding-001:embedContent",
+                "gemini-embedding-001"
+            ),
+            CATEGORY_EMBEDDING
+        );
+        // 仅模型名可判断时也归为 embedding
+        assert_eq!(
+            classify_request("", "text-embedding-3-large"),
+            CATEGORY_EMBEDDING
+        );
+        assert_eq!(
+            classify_request("/v1/images/generations", "dall-e-3"),
+            CATEGORY_IMAGE
+        );
+        assert_eq!(
+            classify_request(
+                "/v1beta/models/gemini-2.5-flash-image:generateContent",
+                "gemini-2.5-flash-image"
+            ),
+            CATEGORY_IMAGE
+        );
+    }
+
+    #[test]
+    fn test_classify_coding_and_chat() {
+        // Codex Responses API 与 Gemini Code Assist 接口归为 coding
+        assert_eq!(classify_request("/v1/responses", "gpt-5"), CATEGORY_CODING);
+        assert_eq!(
+            classify_request("/v1internal:streamGenerateContent", "gemini-2.5-pro"),
+            CATEGORY_CODING
+        );
+        // 代码模型走对话接口也归为 coding
+        assert_eq!(
+            classify_request("/v1/chat/completions", "qwen3-coder-plus"),
+            CATEGORY_CODING
+        );
+        assert_eq!(
+            classify_request("/v1/messages", "gpt-5-codex"),
+            CATEGORY_CODING
+        );
+
+        assert_eq!(
+            classify_request("/v1/messages", "claude-sonnet-4-5"),
+            CATEGORY_CHAT
+        );
+        assert_eq!(
+            classify_request("/v1/chat/completions", "deepseek-chat"),
+            CATEGORY_CHAT
+        );
+        assert_eq!(
+            classify_request(
+                "/v1beta/models/gemini-2.5-pro:streamGenerateContent",
+                "gemini-2.5-pro"
+            ),
+            CATEGORY_CHAT
+        );
+        // 大小写不敏感
+        assert_eq!(
+            classify_request("/V1/Messages", "Claude-Opus-4-1"),
+            CATEGORY_CHAT
+        );
+    }
+
+    #[test]
+    fn test_classify_other() {
+        assert_eq!(classify_request("", ""), CATEGORY_OTHER);
+        assert_eq!(classify_request("/api/internal", "unknown"), CATEGORY_OTHER);
+        // 未知接口但模型可识别时按对话处理
+        assert_eq!(classify_request("", "claude-haiku-4-5"), CATEGORY_CHAT);
+    }
+}
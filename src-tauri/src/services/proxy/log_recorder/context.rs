@@ -2,9 +2,43 @@
 //
 // 职责：在请求处理早期一次性提取所有必要信息，避免重复解析
 
-use crate::services::session::manager::SESSION_MANAGER;
 use crate::services::session::models::ProxySession;
 
+/// 从上游 URL 提取 `host[:port]` 作为上游标识
+pub fn upstream_host(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    Some(match parsed.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
+/// 代理层采集的请求日志元数据（与请求/响应体一起交给处理器记录）
+#[derive(Debug, Clone, Default)]
+pub struct RequestLogMeta<'a> {
+    /// 客户端 IP 地址
+    pub client_ip: &'a str,
+    /// 配置名称（"global" 或 Profile 名称）
+    pub config_name: &'a str,
+    /// 代理配置的价格模板 ID
+    pub proxy_pricing_template_id: Option<&'a str>,
+    /// 实际转发的响应字节数（SSE 收集超限截断时大于响应体长度）
+    pub response_bytes: usize,
+    /// SSE 流因上游空闲超时提前结束（已收到的部分记录为 partial）
+    pub stream_idle_timeout: bool,
+    /// 响应时间（毫秒）
+    pub response_time_ms: Option<i64>,
+    /// 实际使用的上游 host（按上游统计）
+    pub upstream: Option<&'a str>,
+    /// 过载降级重试前的原始模型（请求体已为降级后的模型）
+    pub downgraded_from: Option<&'a str>,
+    /// 请求路径（用于自动分类）
+    pub endpoint: &'a str,
+    /// 首字节延迟（毫秒，上游请求失败时为 None）
+    pub ttfb_ms: Option<i64>,
+}
+
 /// 请求日志上下文（在请求处理早期提取）
 #[derive(Debug, Clone)]
 pub struct RequestLogContext {
@@ -18,10 +52,18 @@ pub struct RequestLogContext {
     pub request_body: Vec<u8>,               // 保留原始请求体
     pub response_time_ms: Option<i64>,       // 响应时间（毫秒）
     pub override_tool_type: Option<String>,  // 覆盖写入日志的 tool_type（供 AMP 等路由器使用）
+    pub response_bytes: i64,                 // 响应体原始字节数（带宽统计）
+    pub upstream: Option<String>,            // 实际使用的上游 host（按上游统计）
+    pub downgraded_from: Option<String>,     // 过载降级重试前的原始模型
+    pub endpoint: String,                    // 请求路径（用于自动分类）
+    pub ttfb_ms: Option<i64>,                // 首字节延迟（毫秒，上游失败时为 None）
+    pub stream_idle_timeout: bool,           // SSE 流因上游空闲超时提前结束
 }
 
 impl RequestLogContext {
     /// 从请求创建上下文（早期提取，仅解析一次）
+    ///
+    /// `config_name` 与价格模板应为本请求实际生效的配置（由调用方按会话覆盖解析）
     pub fn from_request(
         tool_id: &str,
         config_name: &str,
@@ -30,8 +72,8 @@ impl RequestLogContext {
         request_body: &[u8],
         response_time_ms: Option<i64>,
     ) -> Self {
-        // 提取 session_id（完整）、display_id（用于日志）、model 和 stream（仅解析一次）
-        let (full_session_id, session_id, model, is_stream) = if !request_body.is_empty() {
+        // 提取 session_id（display_id，用于日志）、model 和 stream（仅解析一次）
+        let (session_id, model, is_stream) = if !request_body.is_empty() {
             match serde_json::from_slice::<serde_json::Value>(request_body) {
                 Ok(json) => {
                     // 根据工具类型提取 session_id
@@ -41,6 +83,13 @@ impl RequestLogContext {
                             .as_str()
                             .map(|s| s.to_string())
                             .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+                    } else if tool_id == "gemini-cli" {
+                        // Gemini CLI: 从 Code Assist 请求的 request.session_id 提取
+                        json["request"]["session_id"]
+                            .as_str()
+                            .or_else(|| json["session_id"].as_str())
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
                     } else {
                         // Claude 和其他: 从 metadata.user_id 提取
                         json["metadata"]["user_id"]
@@ -54,67 +103,85 @@ impl RequestLogContext {
 
                     let model = json["model"].as_str().map(|s| s.to_string());
                     let is_stream = json["stream"].as_bool().unwrap_or(false);
-                    (full_session_id, session_id, model, is_stream)
-                }
-                Err(_) => {
-                    let fallback_id = uuid::Uuid::new_v4().to_string();
-                    (fallback_id.clone(), fallback_id.clone(), None, false)
+                    (session_id, model, is_stream)
                 }
+                Err(_) => (uuid::Uuid::new_v4().to_string(), None, false),
             }
         } else {
-            let fallback_id = uuid::Uuid::new_v4().to_string();
-            (fallback_id.clone(), fallback_id.clone(), None, false)
+            (uuid::Uuid::new_v4().to_string(), None, false)
         };
 
-        // 查询会话级别的配置（优先级：会话 > 代理），使用完整 session_id 查询
-        let (config_name, pricing_template_id) =
-            Self::resolve_session_config(&full_session_id, config_name, proxy_pricing_template_id);
-
         Self {
             tool_id: tool_id.to_string(),
             session_id,
-            config_name,
+            config_name: config_name.to_string(),
             client_ip: client_ip.to_string(),
-            pricing_template_id,
+            pricing_template_id: proxy_pricing_template_id.map(str::to_string),
             model,
             is_stream,
             request_body: request_body.to_vec(),
             response_time_ms,
             override_tool_type: None,
+            response_bytes: 0,
+            upstream: None,
+            downgraded_from: None,
+            endpoint: String::new(),
+            ttfb_ms: None,
+            stream_idle_timeout: false,
         }
     }
 
-    /// 解析会话级配置（同时提取 config_name 和 pricing_template_id）
-    fn resolve_session_config(
-        session_id: &str,
-        proxy_config_name: &str,
-        proxy_template_id: Option<&str>,
-    ) -> (String, Option<String>) {
-        // 查询会话配置
-        if let Ok(Some((
-            config_name,
-            custom_profile_name,
-            session_url,
-            session_api_key,
-            session_pricing_template_id,
-        ))) = SESSION_MANAGER.get_session_config(session_id)
-        {
-            // 判断是否为自定义配置：config_name == "custom" 且 URL、API Key、pricing_template_id 都不为空
-            if config_name == "custom"
-                && !session_url.is_empty()
-                && !session_api_key.is_empty()
-                && session_pricing_template_id.is_some()
-            {
-                // 使用会话级配置：custom_profile_name 作为配置名（如果存在）
-                let final_config_name = custom_profile_name.unwrap_or_else(|| "custom".to_string());
-                return (final_config_name, session_pricing_template_id);
-            }
-        }
-
-        // 回退到代理级配置（包括会话存在但不是自定义配置的情况）
-        (
-            proxy_config_name.to_string(),
-            proxy_template_id.map(|s| s.to_string()),
+    /// 从代理层采集的元数据创建上下文
+    pub fn from_meta(tool_id: &str, meta: &RequestLogMeta<'_>, request_body: &[u8]) -> Self {
+        Self::from_request(
+            tool_id,
+            meta.config_name,
+            meta.client_ip,
+            meta.proxy_pricing_template_id,
+            request_body,
+            meta.response_time_ms,
         )
+        .with_response_bytes(meta.response_bytes)
+        .with_upstream(meta.upstream)
+        .with_downgraded_from(meta.downgraded_from)
+        .with_endpoint(meta.endpoint)
+        .with_ttfb_ms(meta.ttfb_ms)
+        .with_stream_idle_timeout(meta.stream_idle_timeout)
+    }
+
+    /// 设置响应体字节数
+    pub fn with_response_bytes(mut self, response_bytes: usize) -> Self {
+        self.response_bytes = response_bytes as i64;
+        self
+    }
+
+    /// 设置实际使用的上游 host
+    pub fn with_upstream(mut self, upstream: Option<&str>) -> Self {
+        self.upstream = upstream.map(String::from);
+        self
+    }
+
+    /// 设置降级前的原始模型（请求体已改写为降级后的模型）
+    pub fn with_downgraded_from(mut self, downgraded_from: Option<&str>) -> Self {
+        self.downgraded_from = downgraded_from.map(String::from);
+        self
+    }
+
+    /// 设置请求路径
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    /// 设置首字节延迟
+    pub fn with_ttfb_ms(mut self, ttfb_ms: Option<i64>) -> Self {
+        self.ttfb_ms = ttfb_ms;
+        self
+    }
+
+    /// 标记 SSE 流因上游空闲超时提前结束
+    pub fn with_stream_idle_timeout(mut self, stream_idle_timeout: bool) -> Self {
+        self.stream_idle_timeout = stream_idle_timeout;
+        self
     }
 }
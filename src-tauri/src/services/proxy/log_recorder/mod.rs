@@ -5,12 +5,18 @@
 // - 解析响应数据（SSE/JSON）
 // - 提取 Token 统计
 // - 计算成本
+// - 统计多模态请求中的图片
+// - 按 endpoint 与模型自动分类
 // - 记录到数据库
 
+mod category;
 mod context;
+mod multimodal;
 mod parser;
 mod recorder;
 
-pub use context::RequestLogContext;
-pub use parser::{ParsedResponse, ResponseParser};
+pub use category::classify_request;
+pub use context::{upstream_host, RequestLogContext, RequestLogMeta};
+pub use multimodal::ImageStats;
+pub use parser::{ParsedResponse, ResponseParser, SseEventBuffer};
 pub use recorder::LogRecorder;
@@ -0,0 +1,192 @@
+// 多模态请求统计
+//
+// 职责：识别请求体中的图片内容，统计图片数量与（base64 解码后的）字节数
+//
+// 支持的格式：
+// - Anthropic: {"type":"image","source":{"type":"base64","data":"..."}}
+// - OpenAI Chat: {"type":"image_url","image_url":{"url":"data:image/png;base64,..."}}
+// - OpenAI Responses: {"type":"input_image","image_url":"data:image/png;base64,..."}
+// - Gemini: {"inline_data":{"mime_type":"image/png","data":"..."}}（含 camelCase 写法）
+//
+// 通过 URL 引用的图片计入数量，字节数记为 0
+
+use serde_json::Value;
+
+/// 请求体中的图片统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageStats {
+    pub count: i64,
+    pub bytes: i64,
+}
+
+impl ImageStats {
+    /// 从原始请求体统计（非 JSON 请求体返回空统计）
+    pub fn from_body(body: &[u8]) -> Self {
+        if body.is_empty() {
+            return Self::default();
+        }
+        match serde_json::from_slice::<Value>(body) {
+            Ok(json) => Self::from_json(&json),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 从已解析的请求 JSON 统计
+    pub fn from_json(json: &Value) -> Self {
+        let mut stats = Self::default();
+        stats.visit(json);
+        stats
+    }
+
+    fn visit(&mut self, value: &Value) {
+        match value {
+            Value::Object(obj) => {
+                if let Some(bytes) = image_bytes(obj) {
+                    self.count += 1;
+                    self.bytes += bytes;
+                    return;
+                }
+                obj.values().for_each(|v| self.visit(v));
+            }
+            Value::Array(items) => items.iter().for_each(|v| self.visit(v)),
+            _ => {}
+        }
+    }
+}
+
+/// 判断对象是否为图片内容块，是则返回图片字节数
+fn image_bytes(obj: &serde_json::Map<String, Value>) -> Option<i64> {
+    match obj.get("type").and_then(|v| v.as_str()) {
+        // Anthropic
+        Some("image") => {
+            let source = obj.get("source")?;
+            let bytes = match source.get("type").and_then(|v| v.as_str()) {
+                Some("base64") => source
+                    .get("data")
+                    .and_then(|v| v.as_str())
+                    .map(base64_decoded_len)
+                    .unwrap_or(0),
+                _ => 0,
+            };
+            return Some(bytes);
+        }
+        // OpenAI Chat / Responses
+        Some("image_url") | Some("input_image") => {
+            let url = match obj.get("image_url")? {
+                Value::String(url) => url.as_str(),
+                other => other.get("url").and_then(|v| v.as_str()).unwrap_or(""),
+            };
+            return Some(data_uri_decoded_len(url));
+        }
+        _ => {}
+    }
+
+    // Gemini
+    let inline = obj.get("inline_data").or_else(|| obj.get("inlineData"))?;
+    let mime_type = inline
+        .get("mime_type")
+        .or_else(|| inline.get("mimeType"))
+        .and_then(|v| v.as_str())?;
+    if !mime_type.starts_with("image/") {
+        return None;
+    }
+    Some(
+        inline
+            .get("data")
+            .and_then(|v| v.as_str())
+            .map(base64_decoded_len)
+            .unwrap_or(0),
+    )
+}
+
+/// data URI（`data:image/png;base64,...`）解码后的字节数，普通 URL 返回 0
+fn data_uri_decoded_len(url: &str) -> i64 {
+    if !url.starts_with("data:") {
+        return 0;
+    }
+    url.split_once(";base64,")
+        .map(|(_, data)| base64_decoded_len(data))
+        .unwrap_or(0)
+}
+
+/// base64 字符串解码后的字节数（无需实际解码）
+fn base64_decoded_len(data: &str) -> i64 {
+    let len = data.trim_end_matches('=').len() as i64;
+    len * 3 / 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_base64_decoded_len() {
+        assert_eq!(base64_decoded_len("aGVsbG8="), 5); // "hello"
+        assert_eq!(base64_decoded_len("aGVsbG8h"), 6); // "hello!"
+        assert_eq!(base64_decoded_len("aGk="), 2); // "hi"
+        assert_eq!(base64_decoded_len(""), 0);
+    }
+
+    #[test]
+    fn test_image_stats_claude_request() {
+        let body = json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "描述这两张图"},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "aGVsbG8="}},
+                    {"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}}
+                ]
+            }]
+        });
+        let stats = ImageStats::from_body(body.to_string().as_bytes());
+        assert_eq!(stats, ImageStats { count: 2, bytes: 5 });
+    }
+
+    #[test]
+    fn test_image_stats_openai_and_gemini_requests() {
+        // Codex（Responses API）与 Chat Completions
+        let body = json!({
+            "input": [{
+                "role": "user",
+                "content": [
+                    {"type": "input_text", "text": "hi"},
+                    {"type": "input_image", "image_url": "data:image/jpeg;base64,aGVsbG8h"}
+                ]
+            }],
+            "messages": [{
+                "content": [{"type": "image_url", "image_url": {"url": "data:image/png;base64,aGk="}}]
+            }]
+        });
+        assert_eq!(
+            ImageStats::from_json(&body),
+            ImageStats { count: 2, bytes: 8 }
+        );
+
+        // Gemini：非图片的 inline_data（如 PDF）不计入
+        let body = json!({
+            "contents": [{
+                "parts": [
+                    {"text": "hi"},
+                    {"inlineData": {"mimeType": "image/webp", "data": "aGVsbG8="}},
+                    {"inline_data": {"mime_type": "image/png", "data": "aGk="}},
+                    {"inline_data": {"mime_type": "application/pdf", "data": "aGVsbG8="}}
+                ]
+            }]
+        });
+        assert_eq!(
+            ImageStats::from_json(&body),
+            ImageStats { count: 2, bytes: 7 }
+        );
+    }
+
+    #[test]
+    fn test_image_stats_plain_requests() {
+        let body = json!({"messages": [{"role": "user", "content": "image_url"}]});
+        assert_eq!(ImageStats::from_json(&body), ImageStats::default());
+        assert_eq!(ImageStats::from_body(b"not json"), ImageStats::default());
+        assert_eq!(ImageStats::from_body(b""), ImageStats::default());
+    }
+}
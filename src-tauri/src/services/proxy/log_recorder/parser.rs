@@ -1,6 +1,9 @@
 // 响应解析层
 //
 // 职责：安全解析响应数据，区分 SSE 流式和 JSON 非流式，永不 panic
+//
+// SSE 按字节缓冲重组：上游分片边界可能落在事件、行甚至多字节字符中间，
+// 只有遇到空行（事件分隔符）才把完整事件交给下游解析
 
 use serde_json::Value;
 
@@ -23,6 +26,67 @@ pub enum ParsedResponse {
 
 pub struct ResponseParser;
 
+/// SSE 事件缓冲区
+///
+/// 跨 chunk 保留未结束的行与未结束的事件，按空行（`\n\n` / `\r\n\r\n`）重组完整事件，
+/// 返回每个事件的 data 内容（多行 data 以 `\n` 连接）
+#[derive(Debug, Default)]
+pub struct SseEventBuffer {
+    /// 尚未遇到换行的字节
+    pending: Vec<u8>,
+    /// 当前事件已收到的 data 行
+    data: Vec<String>,
+}
+
+impl SseEventBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一个 chunk，返回其中已完整结束的事件
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            self.feed_line(&line[..line.len() - 1], &mut events);
+        }
+        events
+    }
+
+    /// 流结束：将缺少结尾空行的最后一个事件一并返回
+    pub fn finish(mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.feed_line(&line, &mut events);
+        }
+        self.dispatch(&mut events);
+        events
+    }
+
+    fn feed_line(&mut self, line: &[u8], events: &mut Vec<String>) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            self.dispatch(events);
+            return;
+        }
+        // 仅关心 data 字段（event / id / 注释行忽略），冒号后的单个空格可选
+        if let Some(value) = line.strip_prefix(b"data:") {
+            let value = value.strip_prefix(b" ").unwrap_or(value);
+            self.data.push(String::from_utf8_lossy(value).into_owned());
+        }
+    }
+
+    fn dispatch(&mut self, events: &mut Vec<String>) {
+        if !self.data.is_empty() {
+            events.push(self.data.join("\n"));
+            self.data.clear();
+        }
+    }
+}
+
 impl ResponseParser {
     /// 安全解析响应（区分 SSE/JSON，永不 panic）
     pub fn parse(response_body: &[u8], response_status: u16, is_sse: bool) -> ParsedResponse {
@@ -50,13 +114,10 @@ impl ResponseParser {
     /// data: {"type":"message_delta","delta":{...},"usage":{...}}
     /// ```
     fn parse_sse(response_body: &[u8]) -> ParsedResponse {
-        let body_str = String::from_utf8_lossy(response_body);
-        let data_lines: Vec<String> = body_str
-            .lines()
-            .filter(|line| line.starts_with("data: "))
-            .map(|line| line.trim_start_matches("data: ").to_string())
-            .filter(|line| !line.is_empty() && line != "[DONE]") // 过滤空行和结束标记
-            .collect();
+        let mut buffer = SseEventBuffer::new();
+        let mut data_lines = buffer.push(response_body);
+        data_lines.extend(buffer.finish());
+        data_lines.retain(|data| !data.trim().is_empty() && data != "[DONE]"); // 过滤空事件和结束标记
 
         if data_lines.is_empty() {
             // SSE 流为空或仅包含无效数据
@@ -82,3 +143,60 @@ impl ResponseParser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_buffer_reassembles_split_event() {
+        let mut buffer = SseEventBuffer::new();
+        let first = buffer.push(b"event: message_delta\ndata: {\"type\":\"message_de");
+        assert!(first.is_empty());
+
+        let second = buffer.push(b"lta\",\"usage\":{\"output_tokens\":42}}\n\n");
+        assert_eq!(
+            second,
+            vec![r#"{"type":"message_delta","usage":{"output_tokens":42}}"#.to_string()]
+        );
+        assert!(buffer.finish().is_empty());
+    }
+
+    #[test]
+    fn test_event_buffer_handles_crlf_multiline_and_utf8_split() {
+        let mut buffer = SseEventBuffer::new();
+        // 多字节字符（“你”= E4 BD A0）被切在两个 chunk 之间
+        let mut events = buffer.push(b"data: {\"text\":\"\xE4\xBD");
+        events.extend(buffer.push(b"\xA0\"}\r\n\r\n"));
+        // 同一事件的多行 data 以换行连接；冒号后无空格同样识别
+        events.extend(buffer.push(b": ping\r\ndata:{\"a\":\ndata: 1}\r\n\r\n"));
+        // 缺少结尾空行的最后一个事件在 finish 时返回
+        events.extend(buffer.push(b"data: [DONE]"));
+        events.extend(buffer.finish());
+
+        assert_eq!(
+            events,
+            vec![
+                r#"{"text":"你"}"#.to_string(),
+                "{\"a\":\n1}".to_string(),
+                "[DONE]".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sse_filters_done_and_reports_empty() {
+        let body = b"data: {\"type\":\"ping\"}\n\ndata: [DONE]\n\n";
+        match ResponseParser::parse(body, 200, true) {
+            ParsedResponse::Sse { data_lines } => {
+                assert_eq!(data_lines, vec![r#"{"type":"ping"}"#.to_string()]);
+            }
+            other => panic!("应解析为 SSE 响应: {:?}", other),
+        }
+
+        assert!(matches!(
+            ResponseParser::parse(b": keep-alive\n\n", 200, true),
+            ParsedResponse::ParseError { .. }
+        ));
+    }
+}
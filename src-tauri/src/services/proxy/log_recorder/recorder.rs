@@ -2,8 +2,9 @@
 //
 // 职责：统一的日志记录接口，处理成功/失败/解析错误等所有场景
 
-use super::{ParsedResponse, RequestLogContext};
-use crate::services::token_stats::logger::create_logger;
+use super::{classify_request, ImageStats, ParsedResponse, RequestLogContext};
+use crate::models::token_stats::TokenLog;
+use crate::services::token_stats::logger::{create_logger, LogStatus};
 use crate::services::token_stats::manager::TokenStatsManager;
 use anyhow::Result;
 use hyper::StatusCode;
@@ -28,8 +29,8 @@ impl LogRecorder {
             // HTTP 2xx/3xx 或无状态码，根据解析结果处理
             match parsed {
                 ParsedResponse::Sse { data_lines } => {
-                    // SSE 成功响应
-                    Self::record_sse_success(context, data_lines).await
+                    // SSE 成功响应（空闲超时时为部分响应）
+                    Self::record_sse_success(context, data_lines, context.stream_idle_timeout).await
                 }
                 ParsedResponse::Json { data } => {
                     // JSON 成功响应
@@ -52,30 +53,34 @@ impl LogRecorder {
     }
 
     /// 记录 SSE 成功响应
+    ///
+    /// `partial` 为 true 时表示上游空闲超时、流提前结束，按已收到的数据记录为 partial
     async fn record_sse_success(
         context: &RequestLogContext,
         data_lines: Vec<String>,
+        partial: bool,
     ) -> Result<()> {
-        let logger = create_logger(&context.tool_id)?;
-
-        match logger.log_sse_response(
-            &context.request_body,
-            data_lines,
-            context.session_id.clone(),
-            context.config_name.clone(),
-            context.client_ip.clone(),
-            context.response_time_ms,
-        ) {
+        match Self::build_sse_log(context, data_lines, partial) {
             Ok(log) => {
                 Self::write_log(context, log);
                 tracing::debug!(
                     tool_id = %context.tool_id,
                     session_id = %context.session_id,
+                    partial = partial,
                     "SSE 流式响应记录成功"
                 );
                 Ok(())
             }
+            Err(e) if partial => {
+                // 超时前未收到可统计的数据，按上游错误记录
+                Self::record_upstream_error(
+                    context,
+                    &format!("上游 SSE 流空闲超时，未提取到 Token 信息: {}", e),
+                )
+                .await
+            }
             Err(e) => {
+                let logger = create_logger(&context.tool_id)?;
                 tracing::error!(
                     tool_id = %context.tool_id,
                     session_id = %context.session_id,
@@ -100,6 +105,29 @@ impl LogRecorder {
         }
     }
 
+    /// 从 SSE 数据构建日志，部分响应标记为 partial 并记录原因
+    fn build_sse_log(
+        context: &RequestLogContext,
+        data_lines: Vec<String>,
+        partial: bool,
+    ) -> Result<TokenLog> {
+        let logger = create_logger(&context.tool_id)?;
+        let mut log = logger.log_sse_response(
+            &context.request_body,
+            data_lines,
+            context.session_id.clone(),
+            context.config_name.clone(),
+            context.client_ip.clone(),
+            context.response_time_ms,
+        )?;
+        if partial {
+            log.request_status = LogStatus::Partial.as_str().to_string();
+            log.error_type = Some("stream_idle_timeout".to_string());
+            log.error_detail = Some("上游 SSE 流空闲超时，仅记录已收到的部分".to_string());
+        }
+        Ok(log)
+    }
+
     /// 记录 JSON 成功响应
     async fn record_json_success(
         context: &RequestLogContext,
@@ -238,10 +266,101 @@ impl LogRecorder {
     }
 
     /// 写入日志，如果 context 指定了 override_tool_type 则覆盖 tool_type
-    fn write_log(context: &RequestLogContext, mut log: crate::models::token_stats::TokenLog) {
+    ///
+    /// 同时填入请求/响应体字节数、请求中的图片统计、上游标识、降级标记、请求分类与首字节延迟
+    fn write_log(context: &RequestLogContext, log: TokenLog) {
+        let images = ImageStats::from_body(&context.request_body);
+        let category = classify_request(&context.endpoint, &log.model);
+        let mut log = log
+            .with_body_bytes(context.request_body.len() as i64, context.response_bytes)
+            .with_image_stats(images.count, images.bytes)
+            .with_upstream(context.upstream.clone())
+            .with_downgraded_from(context.downgraded_from.clone())
+            .with_category(Some(category.to_string()))
+            .with_ttfb_ms(context.ttfb_ms);
         if let Some(ref tid) = context.override_tool_type {
             log.tool_type = tid.clone();
         }
         TokenStatsManager::get().write_log(log);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::proxy::log_recorder::{ResponseParser, SseEventBuffer};
+
+    fn context() -> RequestLogContext {
+        let body = br#"{"model":"claude-sonnet-4-5","stream":true,"messages":[]}"#;
+        RequestLogContext::from_request(
+            "claude-code",
+            "default",
+            "127.0.0.1",
+            None,
+            body,
+            Some(500),
+        )
+    }
+
+    fn data_lines(events: &[&str]) -> Vec<String> {
+        events.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_build_sse_log_partial_after_idle_timeout() {
+        // 只收到 message_start 与部分 delta，缺少 message_delta / message_stop
+        let lines = data_lines(&[
+            r#"{"type":"message_start","message":{"id":"msg_partial","usage":{"input_tokens":120,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+        ]);
+
+        let log = LogRecorder::build_sse_log(&context(), lines.clone(), true).unwrap();
+        assert_eq!(log.request_status, "partial");
+        assert_eq!(log.error_type.as_deref(), Some("stream_idle_timeout"));
+        assert_eq!(log.input_tokens, 120);
+        assert_eq!(log.message_id.as_deref(), Some("msg_partial"));
+
+        // 非超时结束时仍记录为 success
+        let log = LogRecorder::build_sse_log(&context(), lines, false).unwrap();
+        assert_eq!(log.request_status, "success");
+        assert!(log.error_type.is_none());
+    }
+
+    #[test]
+    fn test_build_sse_log_with_event_split_across_chunks() {
+        // message_delta 在 JSON 中间被切成两个 chunk
+        let chunks: [&[u8]; 3] = [
+            b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_split\",\"usage\":{\"input_tokens\":120,\"output_tokens\":1}}}\n\n",
+            b"event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_",
+            b"turn\"},\"usage\":{\"output_tokens\":42}}\n\nevent: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        ];
+
+        let mut buffer = SseEventBuffer::new();
+        let mut lines = Vec::new();
+        for chunk in chunks {
+            lines.extend(buffer.push(chunk));
+        }
+        lines.extend(buffer.finish());
+        assert_eq!(lines.len(), 3);
+
+        let log = LogRecorder::build_sse_log(&context(), lines, false).unwrap();
+        assert_eq!(log.message_id.as_deref(), Some("msg_split"));
+        assert_eq!(log.input_tokens, 120);
+        assert_eq!(log.output_tokens, 42);
+        assert_eq!(log.stop_reason.as_deref(), Some("end_turn"));
+
+        // 整体解析拼接后的响应体结果一致
+        let body = chunks.concat();
+        let ParsedResponse::Sse { data_lines } = ResponseParser::parse(&body, 200, true) else {
+            panic!("应解析为 SSE 响应");
+        };
+        let log = LogRecorder::build_sse_log(&context(), data_lines, false).unwrap();
+        assert_eq!(log.output_tokens, 42);
+    }
+
+    #[test]
+    fn test_build_sse_log_partial_without_usage_fails() {
+        let lines = data_lines(&[r#"{"type":"ping"}"#]);
+        assert!(LogRecorder::build_sse_log(&context(), lines, true).is_err());
+    }
+}
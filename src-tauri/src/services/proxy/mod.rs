@@ -15,5 +15,5 @@ pub use headers::{create_request_processor, ProcessedRequest, RequestProcessor};
 #[allow(deprecated)]
 pub use headers::create_headers_processor;
 pub use proxy_instance::ProxyInstance;
-pub use proxy_manager::ProxyManager;
+pub use proxy_manager::{ProxyBindMode, ProxyManager, ProxyStatus};
 pub use proxy_service::ProxyService;
@@ -7,7 +7,7 @@
 
 use anyhow::{Context, Result};
 use bytes::Bytes;
-use http_body_util::{BodyExt, Full};
+use http_body_util::Full;
 use hyper::body::{Frame, Incoming};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
@@ -15,15 +15,35 @@ use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
+use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 
-use super::headers::RequestProcessor;
+use super::headers::{HeaderFilter, RequestProcessor};
+use super::log_recorder::{upstream_host, RequestLogMeta};
 use super::utils::body::{box_body, BoxBody};
-use super::utils::{error_responses, loop_detector};
+use super::utils::priority_limiter::PriorityLimiter;
+use super::utils::stream_tap::{self, StreamEnd};
+use super::utils::{
+    alert_aggregator, body_limit, content_filter, error_responses, failover, fallback_response,
+    loop_detector, max_tokens, model_downgrade, model_quota, openai_compat, request_compression,
+    request_history, retry, slow_capture, status_mapping, upstream_client, upstream_probe,
+};
 use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::session::models::ProxySession;
+use crate::services::session::SESSION_MANAGER;
+
+/// Anthropic count_tokens 接口路径
+const COUNT_TOKENS_PATH: &str = "/v1/messages/count_tokens";
+
+/// 代理健康检查路径（`?upstream=1` 时附带上游探测）
+const HEALTH_CHECK_PATH: &str = "/__health";
+
+/// 切换端口或停止监听后，等待进行中连接完成的最长时间
+const DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 
 /// 单个代理实例
 pub struct ProxyInstance {
@@ -32,6 +52,14 @@ pub struct ProxyInstance {
     processor: Arc<dyn RequestProcessor>,
     server_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
     cancel_token: CancellationToken,
+    /// 当前监听的令牌（取消后停止接受新连接并排空已有连接）
+    listener_token: Arc<RwLock<CancellationToken>>,
+    /// 并发限制器（配置了 max_concurrent_requests 时启用，修改上限需重启代理）
+    limiter: Option<Arc<PriorityLimiter>>,
+    /// 运行标记（start 成功后置位，stop 时清除）
+    running: AtomicBool,
+    /// 最近请求摘要（仅元数据，固定容量）
+    request_history: request_history::RequestHistory,
 }
 
 impl ProxyInstance {
@@ -41,15 +69,25 @@ impl ProxyInstance {
         config: ToolProxyConfig,
         processor: Box<dyn RequestProcessor>,
     ) -> Self {
+        let limiter = config.max_concurrent_requests.map(PriorityLimiter::new);
         Self {
             tool_id,
+            limiter,
             config: Arc::new(RwLock::new(config)),
             processor: Arc::from(processor),
             server_handle: Arc::new(RwLock::new(None)),
             cancel_token: CancellationToken::new(),
+            listener_token: Arc::new(RwLock::new(CancellationToken::new())),
+            running: AtomicBool::new(false),
+            request_history: request_history::RequestHistory::default(),
         }
     }
 
+    /// 最近经过代理的请求摘要（最新的在前）
+    pub fn recent_requests(&self, limit: usize) -> Vec<request_history::RequestSummary> {
+        self.request_history.recent(limit)
+    }
+
     /// 启动代理服务
     pub async fn start(&self) -> Result<()> {
         // 检查是否已经在运行
@@ -70,16 +108,7 @@ impl ProxyInstance {
             );
         }
 
-        // 绑定地址
-        let addr = if config.allow_public {
-            SocketAddr::from(([0, 0, 0, 0], config.port))
-        } else {
-            SocketAddr::from(([127, 0, 0, 1], config.port))
-        };
-
-        let listener = TcpListener::bind(addr)
-            .await
-            .context(format!("绑定端口 {} 失败", config.port))?;
+        let (listener, addr) = bind_listener(&config).await?;
 
         tracing::info!(
             tool_id = %self.tool_id,
@@ -88,57 +117,100 @@ impl ProxyInstance {
             "透明代理启动成功"
         );
 
+        let generation = self.cancel_token.child_token();
+        let handle = self.spawn_server(listener, config.port, generation.clone());
+
+        // 保存服务器句柄
+        {
+            let mut h = self.server_handle.write().await;
+            *h = Some(handle);
+        }
+        *self.listener_token.write().await = generation;
+        self.running.store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// 启动监听循环
+    ///
+    /// `generation` 取消后停止接受新连接，已有连接优雅关闭（处理完进行中的请求），
+    /// 全部连接结束（或排空超时）后任务退出；`cancel_token` 取消时立即中断所有连接
+    fn spawn_server(
+        &self,
+        listener: TcpListener,
+        port: u16,
+        generation: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
         let config_clone = Arc::clone(&self.config);
         let processor_clone = Arc::clone(&self.processor);
-        let port = config.port;
+        let limiter_clone = self.limiter.clone();
+        let history_clone = self.request_history.clone();
         let tool_id = self.tool_id.clone();
         let cancel_token = self.cancel_token.clone();
 
-        // 启动服务器
-        let handle = tokio::spawn(async move {
+        tokio::spawn(async move {
+            let mut connections = JoinSet::new();
+
             loop {
                 tokio::select! {
-                    _ = cancel_token.cancelled() => {
-                        tracing::debug!(tool_id = %tool_id, "代理服务器收到取消信号");
+                    _ = generation.cancelled() => {
+                        tracing::debug!(tool_id = %tool_id, port = port, "代理监听收到停止信号");
                         break;
                     }
+                    // 回收已结束的连接任务
+                    Some(_) = connections.join_next(), if !connections.is_empty() => {}
                     result = listener.accept() => {
                         match result {
                             Ok((stream, _addr)) => {
                                 let config = Arc::clone(&config_clone);
                                 let processor = Arc::clone(&processor_clone);
+                                let limiter = limiter_clone.clone();
+                                let history = history_clone.clone();
                                 let tool_id_inner = tool_id.clone();
                                 let tool_id_for_error = tool_id.clone();
                                 let conn_cancel = cancel_token.clone();
+                                let conn_drain = generation.clone();
 
-                                tokio::spawn(async move {
+                                connections.spawn(async move {
                                     let io = TokioIo::new(stream);
                                     let service = service_fn(move |req| {
                                         let config = Arc::clone(&config);
                                         let processor = Arc::clone(&processor);
+                                        let limiter = limiter.clone();
+                                        let history = history.clone();
                                         let tool_id = tool_id_inner.clone();
                                         async move {
-                                            handle_request(req, config, processor, port, &tool_id).await
+                                            handle_request(req, config, processor, limiter, history, port, &tool_id).await
                                         }
                                     });
 
                                     let conn = http1::Builder::new().serve_connection(io, service);
                                     tokio::pin!(conn);
-
-                                    // 使用 select 在连接完成或取消时退出
-                                    tokio::select! {
-                                        _ = conn_cancel.cancelled() => {
-                                            tracing::debug!(tool_id = %tool_id_for_error, "连接被取消");
-                                        }
-                                        result = &mut conn => {
-                                            if let Err(err) = result {
-                                                if !err.is_incomplete_message() {
-                                                    tracing::error!(
-                                                        tool_id = %tool_id_for_error,
-                                                        error = ?err,
-                                                        "处理连接失败"
-                                                    );
+                                    let mut draining = false;
+
+                                    // 强制取消优先于排空；排空时等待当前请求完成后关闭连接
+                                    loop {
+                                        tokio::select! {
+                                            biased;
+                                            _ = conn_cancel.cancelled() => {
+                                                tracing::debug!(tool_id = %tool_id_for_error, "连接被取消");
+                                                break;
+                                            }
+                                            _ = conn_drain.cancelled(), if !draining => {
+                                                conn.as_mut().graceful_shutdown();
+                                                draining = true;
+                                            }
+                                            result = conn.as_mut() => {
+                                                if let Err(err) = result {
+                                                    if !err.is_incomplete_message() {
+                                                        tracing::error!(
+                                                            tool_id = %tool_id_for_error,
+                                                            error = ?err,
+                                                            "处理连接失败"
+                                                        );
+                                                    }
                                                 }
+                                                break;
                                             }
                                         }
                                     }
@@ -174,20 +246,29 @@ impl ProxyInstance {
                     }
                 }
             }
-        });
 
-        // 保存服务器句柄
-        {
-            let mut h = self.server_handle.write().await;
-            *h = Some(handle);
-        }
-
-        Ok(())
+            // 停止接受新连接后立即释放端口，再排空进行中的连接
+            drop(listener);
+            if !connections.is_empty() {
+                tracing::info!(
+                    tool_id = %tool_id,
+                    port = port,
+                    connections = connections.len(),
+                    "等待进行中的连接处理完成"
+                );
+                let drain = async { while connections.join_next().await.is_some() {} };
+                if tokio::time::timeout(DRAIN_TIMEOUT, drain).await.is_err() {
+                    tracing::warn!(tool_id = %tool_id, port = port, "连接排空超时，强制关闭");
+                    connections.abort_all();
+                }
+            }
+        })
     }
 
     /// 停止代理服务
     pub async fn stop(&self) -> Result<()> {
         // 1. 发送取消信号给所有连接
+        self.running.store(false, Ordering::SeqCst);
         self.cancel_token.cancel();
 
         // 2. 等待服务器任务结束
@@ -213,65 +294,161 @@ impl ProxyInstance {
 
     /// 检查服务是否在运行
     pub fn is_running(&self) -> bool {
-        // 使用 blocking 方式读取，因为这是同步方法
-        // 在实际使用中，ProxyManager 会使用异步版本
-        false // 临时实现，将在异步上下文中使用 try_read
+        self.running.load(Ordering::SeqCst)
     }
 
-    /// 异步检查是否运行
+    /// 异步检查是否运行（与 `is_running` 一致，保留供异步调用方使用）
     pub async fn is_running_async(&self) -> bool {
-        let handle = self.server_handle.read().await;
-        handle.is_some()
+        self.is_running()
+    }
+
+    /// 当前生效的配置快照（含热更新后的值）
+    pub async fn config_snapshot(&self) -> ToolProxyConfig {
+        self.config.read().await.clone()
     }
 
     /// 更新配置（无需重启）
+    ///
+    /// 端口变化时平滑切换：先在新端口启动监听，成功后旧端口停止接受新连接，
+    /// 处理完进行中的请求再关闭；新端口绑定失败时保持旧监听和旧配置不变
     pub async fn update_config(&self, new_config: ToolProxyConfig) -> Result<()> {
+        let (old_port, old_allow_public) = {
+            let config = self.config.read().await;
+            (config.port, config.allow_public)
+        };
+
+        if self.is_running_async().await {
+            if new_config.port != old_port {
+                self.switch_listener(&new_config).await?;
+            } else if new_config.allow_public != old_allow_public {
+                tracing::warn!(
+                    tool_id = %self.tool_id,
+                    "监听地址变更需重启代理后生效"
+                );
+            }
+        }
+
         let mut config = self.config.write().await;
         *config = new_config;
         tracing::info!(tool_id = %self.tool_id, "透明代理配置已更新");
         Ok(())
     }
+
+    /// 切换到新端口监听，旧端口进入排空模式
+    async fn switch_listener(&self, new_config: &ToolProxyConfig) -> Result<()> {
+        let (listener, addr) = bind_listener(new_config).await?;
+
+        let generation = self.cancel_token.child_token();
+        let handle = self.spawn_server(listener, new_config.port, generation.clone());
+
+        let old_handle = self.server_handle.write().await.replace(handle);
+        let old_generation = std::mem::replace(&mut *self.listener_token.write().await, generation);
+        old_generation.cancel();
+
+        tracing::info!(
+            tool_id = %self.tool_id,
+            addr = %addr,
+            "已在新端口启动监听，旧端口进入排空模式"
+        );
+
+        if let Some(old_handle) = old_handle {
+            let tool_id = self.tool_id.clone();
+            tokio::spawn(async move {
+                let _ = old_handle.await;
+                tracing::info!(tool_id = %tool_id, "旧端口监听已关闭");
+            });
+        }
+
+        Ok(())
+    }
 }
 
-/// 处理单个请求
+/// 按配置绑定监听地址
+async fn bind_listener(config: &ToolProxyConfig) -> Result<(TcpListener, SocketAddr)> {
+    let addr = if config.allow_public {
+        SocketAddr::from(([0, 0, 0, 0], config.port))
+    } else {
+        SocketAddr::from(([127, 0, 0, 1], config.port))
+    };
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .context(format!("绑定端口 {} 失败", config.port))?;
+    Ok((listener, addr))
+}
+
+/// 处理单个请求（处理结束后记录请求摘要）
 async fn handle_request(
     req: Request<Incoming>,
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
+    limiter: Option<Arc<PriorityLimiter>>,
+    history: request_history::RequestHistory,
     own_port: u16,
     tool_id: &str,
 ) -> Result<Response<BoxBody>, Infallible> {
-    match handle_request_inner(req, config, processor, own_port, tool_id).await {
-        Ok(res) => Ok(res),
+    let started = std::time::Instant::now();
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let mut upstream_url = None;
+
+    let response = match handle_request_inner(
+        req,
+        config,
+        processor,
+        limiter,
+        own_port,
+        tool_id,
+        &mut upstream_url,
+    )
+    .await
+    {
+        Ok(res) => res,
         Err(e) => {
             tracing::error!(
                 tool_id = %tool_id,
                 error = ?e,
                 "请求处理失败"
             );
-            Ok(error_responses::internal_error(&e.to_string()))
+            // 上游请求错误已在转发处按类型上报，这里只上报代理自身的处理失败
+            if e.downcast_ref::<reqwest::Error>().is_none() {
+                alert_aggregator::report(
+                    tool_id,
+                    alert_aggregator::kind::REQUEST_FAILED,
+                    &e.to_string(),
+                );
+            }
+            error_responses::internal_error(&e.to_string())
         }
-    }
+    };
+
+    history.record(request_history::RequestSummary {
+        timestamp,
+        method,
+        path,
+        status: response.status().as_u16(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        upstream_url,
+    });
+    Ok(response)
 }
 
+/// `upstream_url` 在请求转发后写入实际命中的上游 URL（不含查询参数）
 async fn handle_request_inner(
     req: Request<Incoming>,
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
+    limiter: Option<Arc<PriorityLimiter>>,
     own_port: u16,
     tool_id: &str,
+    upstream_url: &mut Option<String>,
 ) -> Result<Response<BoxBody>> {
     // 记录请求开始时间（用于计算响应时间）
     let start_time = std::time::Instant::now();
 
     // 获取配置
-    let proxy_config = {
-        let cfg = config.read().await;
-        if cfg.real_api_key.is_none() || cfg.real_base_url.is_none() {
-            return Ok(error_responses::configuration_missing(tool_id));
-        }
-        cfg.clone()
-    };
+    let proxy_config = config.read().await.clone();
 
     // 验证本地 API Key
     // 支持多种鉴权方式：authorization (Bearer), x-api-key, x-goog-api-key
@@ -297,30 +474,24 @@ async fn handle_request_inner(
         }
     }
 
+    // 健康检查（配置不完整时也可访问，用于区分本地与上游问题）
+    if req.uri().path() == HEALTH_CHECK_PATH {
+        return health_check_response(&proxy_config, tool_id, req.uri().query()).await;
+    }
+
+    if proxy_config.real_api_key.is_none() || proxy_config.real_base_url.is_none() {
+        return Ok(error_responses::configuration_missing(tool_id));
+    }
+
     // 提取请求信息（先借用，避免与后续的 collect 冲突）
     let path = req.uri().path().to_string();
     let query = req.uri().query().map(|s| s.to_string());
     let method = req.method().clone();
     let headers = req.headers().clone();
 
-    // 拦截 count_tokens 接口，不转发到上游，直接返回权限错误
-    if path == "/v1/messages/count_tokens" {
-        tracing::warn!("拦截 count_tokens 请求，返回权限错误");
-        let error_response = serde_json::json!({
-            "type": "error",
-            "error": {
-                "type": "permission_error",
-                "message": "count_tokens endpoint is not enabled for this channel. Please enable it in channel settings."
-            }
-        });
-        let response_body = serde_json::to_string(&error_response)
-            .unwrap_or_else(|_| r#"{"type":"error","error":{"type":"internal_error","message":"Failed to serialize error response"}}"#.to_string());
-
-        return Response::builder()
-            .status(StatusCode::FORBIDDEN)
-            .header("content-type", "application/json")
-            .body(box_body(Full::new(Bytes::from(response_body))))
-            .map_err(|e| anyhow::anyhow!("Failed to build count_tokens error response: {}", e));
+    // 未开启 count_tokens 转发时直接返回权限错误
+    if let Some(response) = intercept_count_tokens(&path, &proxy_config) {
+        return Ok(response);
     }
 
     // 提取客户端IP（用于日志记录）
@@ -339,13 +510,66 @@ async fn handle_request_inner(
         .map(|s| s.trim_end_matches('/'))
         .unwrap_or("");
 
-    // 读取请求体（消费 req）
+    // 读取请求体（消费 req，超过 max_body_bytes 时返回 413）
+    let max_body_bytes = proxy_config.max_body_bytes;
     let body_bytes = if method != Method::GET && method != Method::HEAD {
-        req.collect().await?.to_bytes()
+        match body_limit::collect_body_limited(req.into_body(), max_body_bytes as usize).await? {
+            Some(bytes) => bytes,
+            None => {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    limit = max_body_bytes,
+                    "请求体超过大小上限"
+                );
+                return Ok(error_responses::payload_too_large(max_body_bytes));
+            }
+        }
     } else {
         Bytes::new()
     };
 
+    // 请求体缺少 max_tokens 时注入默认值（count_tokens 不接受该字段）
+    let body_bytes = match proxy_config.default_max_tokens {
+        Some(default_max_tokens) if path != COUNT_TOKENS_PATH => {
+            max_tokens::inject_default_max_tokens(&body_bytes, default_max_tokens)
+                .unwrap_or(body_bytes)
+        }
+        _ => body_bytes,
+    };
+
+    // 按模型的每日调用配额（超限只拒绝该模型；count_tokens 探测请求不计入配额）
+    // 先预占名额，上游成功响应后确认，其余情况在 reservation 释放时退还
+    let mut quota_reservation = None;
+    if !proxy_config.model_daily_limits.is_empty() && path != COUNT_TOKENS_PATH {
+        let model = processor
+            .extract_model(&body_bytes)
+            .or_else(|| model_quota::model_from_path(&path));
+        if let Some(model) = model {
+            match model_quota::reserve(tool_id, &model, &proxy_config.model_daily_limits) {
+                Ok(reservation) => quota_reservation = reservation,
+                Err(limit) => {
+                    tracing::warn!(
+                        tool_id = %tool_id,
+                        model = %model,
+                        limit = limit,
+                        "模型今日调用次数已达上限，拒绝请求"
+                    );
+                    return Ok(error_responses::model_quota_exceeded(&model, limit));
+                }
+            }
+        }
+    }
+
+    // 并发受限时按会话优先级排队（permit 持有到响应结束）
+    let permit = match &limiter {
+        Some(limiter) => {
+            let priority =
+                resolve_request_priority(tool_id, &body_bytes, &proxy_config.session_priorities);
+            Some(limiter.acquire(priority).await)
+        }
+        None => None,
+    };
+
     // 使用 RequestProcessor 统一处理请求（URL + headers + body）
     // amp-code 忽略传入的 base/api_key，在内部通过 amp_selection 获取
     let processed = processor
@@ -355,6 +579,7 @@ async fn handle_request_inner(
             &path,
             query.as_deref(),
             &headers,
+            &HeaderFilter::from_config(&proxy_config),
             &body_bytes,
         )
         .await
@@ -380,11 +605,63 @@ async fn handle_request_inner(
             .unwrap());
     }
 
+    // OpenAI 兼容上游：Messages 请求转换为 Chat Completions（日志仍按 Anthropic 格式记录）
+    let convert_openai = proxy_config.openai_compat_enabled
+        && tool_id == "claude-code"
+        && path == openai_compat::ANTHROPIC_MESSAGES_PATH;
+    let log_request_body = processed.body.clone();
+    // 日志与成本归属：按本请求实际生效的配置记录（会话覆盖优先于代理配置）
+    let (config_name, proxy_pricing_template_id) = processed.log_profile(
+        proxy_config
+            .real_profile_name
+            .as_deref()
+            .unwrap_or("default"),
+        proxy_config.pricing_template_id.as_deref(),
+    );
+    let processed = if convert_openai {
+        let mut processed = processed;
+        processed.body = openai_compat::anthropic_to_openai_request(&processed.body)
+            .context("转换 OpenAI 兼容请求失败")?;
+        processed.target_url = openai_compat::rewrite_target_url(&processed.target_url);
+        processed.headers.remove("content-length");
+        processed
+    } else {
+        processed
+    };
+
     // 回环检测
     if loop_detector::is_proxy_loop(&processed.target_url, own_port) {
         return Ok(error_responses::proxy_loop_detected(tool_id));
     }
 
+    // 备用上游：由主上游请求改写目标与 API Key（会话覆盖与 amp-code 路由不参与故障转移）
+    let primary_api_key = proxy_config.real_api_key.as_deref().unwrap_or("");
+    let backups: Vec<failover::BackupTarget> = proxy_config
+        .upstreams
+        .iter()
+        .filter_map(|backup| {
+            let retargeted =
+                processed.retarget(base, primary_api_key, &backup.base_url, &backup.api_key)?;
+            if loop_detector::is_proxy_loop(&retargeted.target_url, own_port) {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    base_url = %backup.base_url,
+                    "备用上游指向本地代理，已跳过"
+                );
+                return None;
+            }
+            let name = if backup.name.is_empty() {
+                upstream_host(&backup.base_url).unwrap_or_else(|| backup.base_url.clone())
+            } else {
+                backup.name.clone()
+            };
+            Some(failover::BackupTarget {
+                name,
+                processed: retargeted,
+            })
+        })
+        .collect();
+
     tracing::debug!(
         tool_id = %tool_id,
         method = %method,
@@ -394,31 +671,92 @@ async fn handle_request_inner(
     );
 
     // 构建上游请求（使用处理后的信息）
-    let mut reqwest_builder = reqwest::Client::new().request(method.clone(), &processed.target_url);
+    let client = upstream_client::upstream_client(tool_id, &proxy_config)?;
 
-    // 应用处理后的 headers
-    for (name, value) in processed.headers.iter() {
-        reqwest_builder = reqwest_builder.header(name, value);
-    }
+    // 从请求体中判断是否为流式请求（SSE 请求返回响应头后不再按状态码重试）
+    let is_sse_request = serde_json::from_slice::<serde_json::Value>(&processed.body)
+        .ok()
+        .and_then(|json| json.get("stream").and_then(|v| v.as_bool()))
+        .unwrap_or(false);
+    let retry_policy = retry::RetryPolicy::from_config(&proxy_config, is_sse_request);
+
+    // 发送请求（连接错误与 502/503/504 按指数退避重试，仍失败时按顺序切换备用上游）
+    let upstream_started = std::time::Instant::now();
+    let (hit, upstream_result) = failover::send_with_failover(
+        &client,
+        &method,
+        &processed,
+        &backups,
+        retry_policy,
+        request_compression::RequestCompressionPolicy::from_config(&proxy_config),
+        tool_id,
+    )
+    .await;
+
+    // 实际命中的上游：日志 config_name 与上游标识按命中的上游记录
+    let hit_backup = hit.checked_sub(1).and_then(|i| backups.get(i));
+    *upstream_url = Some(
+        request_history::strip_query(
+            &hit_backup
+                .map_or(&processed, |backup| &backup.processed)
+                .target_url,
+        )
+        .to_string(),
+    );
+    let (config_name, upstream) = match hit_backup {
+        Some(backup) => (
+            backup.name.clone(),
+            upstream_host(&backup.processed.target_url),
+        ),
+        None => (config_name, upstream_host(&processed.target_url)),
+    };
 
-    // 添加请求体
-    if !processed.body.is_empty() {
-        reqwest_builder = reqwest_builder.body(processed.body.to_vec());
+    // 上游过载：按配置降级模型后向命中的上游重试一次（日志按降级后的模型记录并标记原模型）
+    let (upstream_result, downgrade) = model_downgrade::retry_with_downgrade(
+        &client,
+        &method,
+        hit_backup.map_or(&processed, |backup| &backup.processed),
+        upstream_result,
+        &proxy_config,
+        retry_policy,
+        tool_id,
+    )
+    .await;
+    let (log_request_body, downgraded_from) = match downgrade {
+        Some(downgrade) => (
+            model_downgrade::rewrite_model(&log_request_body, &downgrade.to)
+                .unwrap_or(log_request_body),
+            Some(downgrade.from),
+        ),
+        None => (log_request_body, None),
+    };
+    let slow_capture_policy = slow_capture::SlowCapturePolicy::from_config(&proxy_config);
+
+    // 模型配额仅在上游成功响应时计入，请求失败或返回错误状态码时退还
+    if let Some(reservation) = quota_reservation {
+        if matches!(&upstream_result, Ok(res) if res.status().is_success()) {
+            reservation.commit();
+        }
     }
 
-    // 发送请求
-    let upstream_res = match reqwest_builder.send().await {
+    // 首字节延迟：从发起上游请求到收到响应头（含重试与故障转移耗时）
+    let ttfb_ms = upstream_result
+        .as_ref()
+        .ok()
+        .map(|_| upstream_started.elapsed().as_millis() as i64);
+
+    let upstream_res = match upstream_result {
         Ok(res) => res,
         Err(e) => {
             // 上游请求失败，记录错误到数据库
             let processor_clone = Arc::clone(&processor);
             let client_ip_clone = client_ip.clone();
-            let config_name_clone = proxy_config
-                .real_profile_name
-                .clone()
-                .unwrap_or_else(|| "default".to_string());
-            let proxy_pricing_template_id_clone = proxy_config.pricing_template_id.clone();
-            let request_body_clone = processed.body.clone();
+            let config_name_clone = config_name.clone();
+            let proxy_pricing_template_id_clone = proxy_pricing_template_id.clone();
+            let request_body_clone = log_request_body.clone();
+            let upstream_clone = upstream.clone();
+            let downgraded_from_clone = downgraded_from.clone();
+            let endpoint_clone = path.clone();
             // 展开完整错误链（reqwest 的 source chain 包含底层原因如 DNS/TLS/超时等）
             let error_msg = {
                 let mut msg = e.to_string();
@@ -429,30 +767,62 @@ async fn handle_request_inner(
                 }
                 msg
             };
-
-            // 从请求体中判断是否为流式请求
-            let is_sse = serde_json::from_slice::<serde_json::Value>(&processed.body)
-                .ok()
-                .and_then(|json| json.get("stream").and_then(|v| v.as_bool()))
-                .unwrap_or(false);
+            alert_aggregator::report(
+                tool_id,
+                alert_aggregator::classify_upstream_error(&e),
+                &error_msg,
+            );
 
             tokio::spawn(async move {
                 // 调用 record_request_log，传递 response_status=0 标记为上游失败
+                let meta = RequestLogMeta {
+                    client_ip: &client_ip_clone,
+                    config_name: &config_name_clone,
+                    proxy_pricing_template_id: proxy_pricing_template_id_clone.as_deref(),
+                    response_time_ms: Some(start_time.elapsed().as_millis() as i64),
+                    upstream: upstream_clone.as_deref(),
+                    downgraded_from: downgraded_from_clone.as_deref(),
+                    endpoint: &endpoint_clone,
+                    ttfb_ms: None, // 未收到响应头
+                    ..Default::default()
+                };
                 let _ = processor_clone
                     .record_request_log(
-                        &client_ip_clone,
-                        &config_name_clone,
-                        proxy_pricing_template_id_clone.as_deref(),
+                        &meta,
                         &request_body_clone,
-                        0,      // response_status=0 标记上游请求失败
-                        &[],    // 空响应体
-                        is_sse, // 从请求体提取
-                        Some(start_time.elapsed().as_millis() as i64),
+                        0,              // response_status=0 标记上游请求失败
+                        &[],            // 空响应体
+                        is_sse_request, // 从请求体提取
                     )
                     .await;
             });
 
-            return Err(anyhow::anyhow!("上游请求失败: {}", error_msg));
+            // 所有重试均失败：按配置返回降级响应
+            if proxy_config.fallback_response_enabled {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    error = %error_msg,
+                    "上游不可用，返回降级响应"
+                );
+                return Ok(fallback_response::build(
+                    tool_id,
+                    proxy_config.fallback_message.as_deref(),
+                    &log_request_body,
+                ));
+            }
+
+            if e.is_timeout() {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    timeout_secs = proxy_config.upstream_timeout_secs,
+                    "上游响应超时"
+                );
+                return Ok(error_responses::gateway_timeout(
+                    proxy_config.upstream_timeout_secs,
+                ));
+            }
+
+            return Err(anyhow::Error::new(e).context(format!("上游请求失败: {}", error_msg)));
         }
     };
 
@@ -460,6 +830,53 @@ async fn handle_request_inner(
     let status = StatusCode::from_u16(upstream_res.status().as_u16())
         .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
 
+    // 重试与故障转移后上游仍返回 502/503/504：按配置返回降级响应（日志记录上游原始响应）
+    if proxy_config.fallback_response_enabled && retry::is_retryable_status(status.as_u16()) {
+        tracing::warn!(
+            tool_id = %tool_id,
+            status = status.as_u16(),
+            "上游持续返回错误状态码，返回降级响应"
+        );
+        let response_body = body_limit::collect_stream_limited(
+            upstream_res.bytes_stream(),
+            max_body_bytes as usize,
+        )
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+        let request_body_clone = log_request_body.clone();
+        let response_status = status.as_u16();
+        tokio::spawn(async move {
+            let meta = RequestLogMeta {
+                client_ip: &client_ip,
+                config_name: &config_name,
+                proxy_pricing_template_id: proxy_pricing_template_id.as_deref(),
+                response_bytes: response_body.len(),
+                response_time_ms: Some(start_time.elapsed().as_millis() as i64),
+                upstream: upstream.as_deref(),
+                downgraded_from: downgraded_from.as_deref(),
+                endpoint: &path,
+                ttfb_ms,
+                ..Default::default()
+            };
+            let _ = processor
+                .record_request_log(
+                    &meta,
+                    &request_body_clone,
+                    response_status,
+                    &response_body,
+                    false, // is_sse
+                )
+                .await;
+        });
+        return Ok(fallback_response::build(
+            tool_id,
+            proxy_config.fallback_message.as_deref(),
+            &log_request_body,
+        ));
+    }
+
     // 检查是否是 SSE 流
     let is_sse = upstream_res
         .headers()
@@ -468,142 +885,191 @@ async fn handle_request_inner(
         .map(|v| v.contains("text/event-stream"))
         .unwrap_or(false);
 
-    let mut response = Response::builder().status(status);
+    // 流式内容过滤（规则非法时不过滤，保存配置时已校验）
+    let response_filter = if is_sse {
+        match content_filter::ContentFilter::compile(&proxy_config.response_filters) {
+            Ok(filter) => filter.map(Arc::new),
+            Err(e) => {
+                tracing::error!(tool_id = %tool_id, error = ?e, "内容过滤规则编译失败");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // 非标准状态码按配置映射后返回客户端（日志仍记录上游原始状态码）
+    let client_status = status_mapping::map_status(&proxy_config.status_code_mappings, status);
+    if client_status != status {
+        tracing::debug!(
+            tool_id = %tool_id,
+            upstream_status = status.as_u16(),
+            client_status = client_status.as_u16(),
+            "按配置映射响应状态码"
+        );
+    }
+
+    let mut response = Response::builder().status(client_status);
 
-    // 复制响应 headers
+    // 复制响应 headers（格式转换或内容过滤后响应体长度会变化，不复制 content-length）
+    let rewrites_body = convert_openai || response_filter.is_some();
     for (name, value) in upstream_res.headers().iter() {
+        if rewrites_body && name.as_str() == "content-length" {
+            continue;
+        }
         response = response.header(name.as_str(), value.as_bytes());
     }
 
     if is_sse {
         tracing::debug!(tool_id = %tool_id, "SSE 流式响应");
 
-        // SSE 流式响应：收集响应体并调用 processor.record_request_log
+        // SSE 流式响应：边转发边收集，流结束后调用 processor.record_request_log
         use futures_util::StreamExt;
-        use std::sync::{Arc, Mutex};
 
         use super::headers::strip_mcp_name_prefix_bytes;
 
-        let config_name = proxy_config
-            .real_profile_name
-            .clone()
-            .unwrap_or_else(|| "default".to_string());
-
-        let proxy_pricing_template_id = proxy_config.pricing_template_id.clone();
+        // OpenAI 兼容上游先转换为 Anthropic SSE，再交给日志收集
+        let upstream_stream = if convert_openai {
+            openai_compat::convert_stream(upstream_res.bytes_stream()).boxed()
+        } else {
+            upstream_res.bytes_stream().boxed()
+        };
 
-        // 使用 Arc<Mutex<Vec>> 在流处理过程中收集数据
-        let sse_chunks = Arc::new(Mutex::new(Vec::new()));
-        let sse_chunks_clone = Arc::clone(&sse_chunks);
+        // 内容过滤在日志收集之前，日志中记录的同样是脱敏后的内容
+        let upstream_stream = match response_filter {
+            Some(filter) => content_filter::filter_stream(upstream_stream, filter).boxed(),
+            None => upstream_stream,
+        };
 
-        // 创建一个通道,在流完全消费后触发统计
-        let (stream_end_tx, stream_end_rx) = tokio::sync::oneshot::channel::<()>();
+        // 上游空闲超时：正常结束流而不是中断连接，已收到的部分记录为 partial
+        let (upstream_stream, idle_timed_out) =
+            stream_tap::end_on_idle_timeout(upstream_stream, reqwest::Error::is_timeout);
 
-        let stream = upstream_res.bytes_stream();
+        // 包装上游流：正常结束、异常终止或客户端断开时通过 oneshot 交出已收集的数据
+        let (tapped_stream, stream_end_rx) =
+            stream_tap::tap(upstream_stream, max_body_bytes as usize);
 
         // amp-code 需要移除工具名前缀
         let is_amp_code = tool_id == "amp-code";
 
-        // 拦截流数据并收集
-        let mapped_stream = stream
-            .map(move |result| {
-                match &result {
-                    Ok(chunk) => {
-                        if let Ok(mut chunks) = sse_chunks_clone.lock() {
-                            chunks.push(chunk.clone());
-                        }
+        let mapped_stream = tapped_stream.map(move |result| {
+            result
+                .map(|bytes| {
+                    if is_amp_code {
+                        Frame::data(strip_mcp_name_prefix_bytes(&bytes))
+                    } else {
+                        Frame::data(bytes)
                     }
-                    Err(_) => {
-                        // 流错误 - 注意: stream_completed_clone 已被移除,不再需要通知
-                    }
-                }
-
-                result
-                    .map(|bytes| {
-                        if is_amp_code {
-                            Frame::data(strip_mcp_name_prefix_bytes(&bytes))
-                        } else {
-                            Frame::data(bytes)
-                        }
-                    })
-                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
-            })
-            // 在流的最后一个元素之后插入完成信号
-            .chain(futures_util::stream::once(async move {
-                // 发送流完成信号
-                let _ = stream_end_tx.send(());
-                tracing::debug!("SSE 流已完全消费完毕,发送完成信号");
-                // 返回一个永远不会被使用的 Err,会被下游过滤掉
-                Err(Box::new(std::io::Error::other("__stream_end_marker__"))
-                    as Box<dyn std::error::Error + Send + Sync>)
-            }))
-            // 过滤掉结束标记
-            .filter(
-                |item: &Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>| {
-                    let is_end_marker = item
-                        .as_ref()
-                        .err()
-                        .and_then(|e| e.downcast_ref::<std::io::Error>())
-                        .map(|e| e.to_string().contains("__stream_end_marker__"))
-                        .unwrap_or(false);
-                    futures_util::future::ready(!is_end_marker)
-                },
-            );
+                })
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+        });
 
         // 在流真正结束后异步记录日志
         let processor_clone = Arc::clone(&processor);
         let client_ip_clone = client_ip.clone();
-        let request_body_clone = processed.body.clone();
+        let request_body_clone = log_request_body.clone();
         let response_status = status.as_u16();
         let start_time_clone = start_time;
         let proxy_pricing_template_id_clone = proxy_pricing_template_id.clone();
+        let tool_id_owned = tool_id.to_string();
 
         tokio::spawn(async move {
-            // 等待流完全消费的信号(无超时,真正等待流结束)
-            match stream_end_rx.await {
-                Ok(_) => {
-                    tracing::debug!("✓ 收到 SSE 流完成信号,流已完全消费");
-                }
-                Err(_) => {
-                    tracing::warn!("✗ 未收到 SSE 流完成信号(sender 被 drop),可能流被提前取消");
-                }
-            }
+            // 并发名额随流结束释放
+            let _permit = permit;
 
-            // 小延迟确保最后的 chunk 写入完成(异步锁竞争)
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-
-            let chunks = match sse_chunks.lock() {
-                Ok(guard) => guard.clone(),
-                Err(e) => {
-                    tracing::error!(error = ?e, "获取 SSE chunks 锁失败");
+            // 等待流结束通知（发送端随流一起丢弃时也会通知，不会悬挂）
+            let outcome = match stream_end_rx.await {
+                Ok(outcome) => outcome,
+                Err(_) => {
+                    tracing::error!(tool_id = %tool_id_owned, "未收到 SSE 流结束通知");
                     return;
                 }
             };
 
-            tracing::debug!(
-                chunks_count = chunks.len(),
-                "开始处理 SSE chunks 进行 token 统计"
-            );
-
-            // 将所有 chunk 合并为完整响应体
-            let mut full_data = Vec::new();
-            for chunk in &chunks {
-                full_data.extend_from_slice(chunk);
+            if outcome.truncated {
+                tracing::warn!(
+                    tool_id = %tool_id_owned,
+                    limit = max_body_bytes,
+                    "SSE 流超过收集上限，仅按开头与末尾数据统计"
+                );
             }
 
-            // 计算响应时间(从请求开始到流完全消费的总时间)
+            // 上游异常终止时以 response_status=0 记录为 upstream_error，
+            // 空闲超时单独标记，按已收到的部分记录为 partial
+            let stream_idle_timeout = matches!(outcome.end, StreamEnd::Completed)
+                && idle_timed_out.load(Ordering::SeqCst);
+            let log_status = match &outcome.end {
+                StreamEnd::Completed if stream_idle_timeout => {
+                    tracing::warn!(
+                        tool_id = %tool_id_owned,
+                        bytes = outcome.data.len(),
+                        "上游 SSE 流空闲超时，按已收到的部分记录"
+                    );
+                    response_status
+                }
+                StreamEnd::Completed => {
+                    tracing::debug!(
+                        tool_id = %tool_id_owned,
+                        bytes = outcome.data.len(),
+                        "SSE 流已完全消费"
+                    );
+                    response_status
+                }
+                StreamEnd::Aborted => {
+                    tracing::warn!(
+                        tool_id = %tool_id_owned,
+                        bytes = outcome.data.len(),
+                        "客户端提前断开 SSE 流，按已收到的部分记录"
+                    );
+                    response_status
+                }
+                StreamEnd::Errored(error) => {
+                    tracing::warn!(
+                        tool_id = %tool_id_owned,
+                        bytes = outcome.data.len(),
+                        error = %error,
+                        "上游 SSE 流异常终止"
+                    );
+                    0
+                }
+            };
+
+            // 计算响应时间(从请求开始到流结束的总时间)
             let response_time_ms = start_time_clone.elapsed().as_millis() as i64;
 
+            // 慢请求按采样率保存样本
+            slow_capture::maybe_capture(
+                slow_capture_policy,
+                slow_capture::SlowRequestCapture::new(
+                    &tool_id_owned,
+                    response_time_ms as u64,
+                    log_status,
+                    upstream.as_deref(),
+                    &request_body_clone,
+                    &outcome.data,
+                ),
+            );
+
             // 调用工具特定的日志记录
+            let meta = RequestLogMeta {
+                client_ip: &client_ip_clone,
+                config_name: &config_name,
+                proxy_pricing_template_id: proxy_pricing_template_id_clone.as_deref(),
+                response_bytes: outcome.total_bytes,
+                stream_idle_timeout,
+                response_time_ms: Some(response_time_ms),
+                upstream: upstream.as_deref(),
+                downgraded_from: downgraded_from.as_deref(),
+                endpoint: &path,
+                ttfb_ms,
+            };
             if let Err(e) = processor_clone
                 .record_request_log(
-                    &client_ip_clone,
-                    &config_name,
-                    proxy_pricing_template_id_clone.as_deref(),
+                    &meta,
                     &request_body_clone,
-                    response_status,
-                    &full_data,
+                    log_status,
+                    &outcome.data,
                     true, // is_sse
-                    Some(response_time_ms),
                 )
                 .await
             {
@@ -614,8 +1080,74 @@ async fn handle_request_inner(
         let body = http_body_util::StreamBody::new(mapped_stream);
         Ok(response.body(box_body(body)).unwrap())
     } else {
-        // 普通响应：读取响应体并调用 processor.record_request_log
-        let body_bytes = upstream_res.bytes().await.context("读取响应体失败")?;
+        // 普通响应：读取响应体并调用 processor.record_request_log（超过 max_body_bytes 时返回 413）
+        if upstream_res
+            .content_length()
+            .is_some_and(|len| len > max_body_bytes)
+        {
+            tracing::warn!(tool_id = %tool_id, limit = max_body_bytes, "上游响应体超过大小上限");
+            return Ok(error_responses::payload_too_large(max_body_bytes));
+        }
+        let body_bytes = match body_limit::collect_stream_limited(
+            upstream_res.bytes_stream(),
+            max_body_bytes as usize,
+        )
+        .await
+        {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => {
+                tracing::warn!(tool_id = %tool_id, limit = max_body_bytes, "上游响应体超过大小上限");
+                return Ok(error_responses::payload_too_large(max_body_bytes));
+            }
+            Err(e) if e.is_timeout() => {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    timeout_secs = proxy_config.upstream_timeout_secs,
+                    "读取上游响应体超时"
+                );
+                alert_aggregator::report(
+                    tool_id,
+                    alert_aggregator::kind::UPSTREAM_TIMEOUT,
+                    &e.to_string(),
+                );
+                let processor_clone = Arc::clone(&processor);
+                let request_body_clone = log_request_body.clone();
+                tokio::spawn(async move {
+                    // response_status=0 标记为上游失败
+                    let meta = RequestLogMeta {
+                        client_ip: &client_ip,
+                        config_name: &config_name,
+                        proxy_pricing_template_id: proxy_pricing_template_id.as_deref(),
+                        response_time_ms: Some(start_time.elapsed().as_millis() as i64),
+                        upstream: upstream.as_deref(),
+                        downgraded_from: downgraded_from.as_deref(),
+                        endpoint: &path,
+                        ttfb_ms,
+                        ..Default::default()
+                    };
+                    let _ = processor_clone
+                        .record_request_log(&meta, &request_body_clone, 0, &[], false)
+                        .await;
+                });
+                return Ok(error_responses::gateway_timeout(
+                    proxy_config.upstream_timeout_secs,
+                ));
+            }
+            Err(e) => return Err(anyhow::Error::new(e).context("读取响应体失败")),
+        };
+
+        // OpenAI 兼容上游：响应体转换回 Anthropic 格式（无法解析时原样返回）
+        let body_bytes = if convert_openai {
+            match openai_compat::openai_to_anthropic_response(&body_bytes) {
+                Ok(converted) => converted,
+                Err(e) => {
+                    tracing::warn!(tool_id = %tool_id, error = ?e, "OpenAI 兼容响应转换失败");
+                    body_bytes
+                }
+            }
+        } else {
+            body_bytes
+        };
 
         // amp-code 需要清理响应体中的工具名前缀
         let final_body = if tool_id == "amp-code" {
@@ -624,34 +1156,49 @@ async fn handle_request_inner(
             body_bytes.clone()
         };
 
-        // 获取配置名称
-        let config_name = proxy_config
-            .real_profile_name
-            .clone()
-            .unwrap_or_else(|| "default".to_string());
-
-        let proxy_pricing_template_id = proxy_config.pricing_template_id.clone();
-
         // 异步记录日志
         let processor_clone = Arc::clone(&processor);
         let client_ip_clone = client_ip.clone();
-        let request_body_clone = processed.body.clone();
+        let request_body_clone = log_request_body.clone();
         let response_body_clone = body_bytes.clone();
         let response_status = status.as_u16();
         let response_time_ms = start_time.elapsed().as_millis() as i64; // 计算响应时间
+        let tool_id_owned = tool_id.to_string();
 
         tokio::spawn(async move {
+            // 慢请求按采样率保存样本
+            slow_capture::maybe_capture(
+                slow_capture_policy,
+                slow_capture::SlowRequestCapture::new(
+                    &tool_id_owned,
+                    response_time_ms as u64,
+                    response_status,
+                    upstream.as_deref(),
+                    &request_body_clone,
+                    &response_body_clone,
+                ),
+            );
+
             // 调用工具特定的日志记录
+            let meta = RequestLogMeta {
+                client_ip: &client_ip_clone,
+                config_name: &config_name,
+                proxy_pricing_template_id: proxy_pricing_template_id.as_deref(),
+                response_bytes: response_body_clone.len(),
+                response_time_ms: Some(response_time_ms),
+                upstream: upstream.as_deref(),
+                downgraded_from: downgraded_from.as_deref(),
+                endpoint: &path,
+                ttfb_ms,
+                ..Default::default()
+            };
             if let Err(e) = processor_clone
                 .record_request_log(
-                    &client_ip_clone,
-                    &config_name,
-                    proxy_pricing_template_id.as_deref(),
+                    &meta,
                     &request_body_clone,
                     response_status,
                     &response_body_clone,
                     false, // is_sse
-                    Some(response_time_ms),
                 )
                 .await
             {
@@ -664,3 +1211,116 @@ async fn handle_request_inner(
             .unwrap())
     }
 }
+
+/// count_tokens 拦截：未开启 `allow_count_tokens` 时不转发到上游，返回 403
+fn intercept_count_tokens(path: &str, proxy_config: &ToolProxyConfig) -> Option<Response<BoxBody>> {
+    if path != COUNT_TOKENS_PATH || proxy_config.allow_count_tokens {
+        return None;
+    }
+    tracing::warn!("拦截 count_tokens 请求，返回权限错误");
+    Some(error_responses::count_tokens_forbidden())
+}
+
+/// 构建健康检查响应
+///
+/// 返回本地配置状态；查询参数带 `upstream` 时额外探测上游连通性
+async fn health_check_response(
+    proxy_config: &ToolProxyConfig,
+    tool_id: &str,
+    query: Option<&str>,
+) -> Result<Response<BoxBody>> {
+    let configured = proxy_config.real_api_key.is_some() && proxy_config.real_base_url.is_some();
+    let probe_upstream = query.is_some_and(|q| {
+        q.split('&')
+            .any(|pair| matches!(pair, "upstream" | "upstream=1" | "upstream=true"))
+    });
+
+    let upstream = match (&proxy_config.real_base_url, probe_upstream) {
+        (Some(base_url), true) => {
+            let client = upstream_client::upstream_client(tool_id, proxy_config)?;
+            Some(upstream_probe::probe(&client, base_url).await)
+        }
+        _ => None,
+    };
+    let status = match &upstream {
+        _ if !configured => "unconfigured",
+        Some(health) if !health.healthy => "upstream_unhealthy",
+        _ => "ok",
+    };
+
+    let body = serde_json::json!({
+        "status": status,
+        "tool_id": tool_id,
+        "port": proxy_config.port,
+        "configured": configured,
+        "upstream": upstream,
+    });
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "application/json")
+        .body(box_body(Full::new(Bytes::from(body.to_string()))))
+        .map_err(|e| anyhow::anyhow!("Failed to build health check response: {}", e))
+}
+
+/// 解析请求的调度优先级
+///
+/// 从请求体提取会话 ID（Codex 为 prompt_cache_key，其他工具为 metadata.user_id），
+/// 依次按会话备注标签、显示 ID 匹配 `session_priorities`，未匹配时为 0
+fn resolve_request_priority(
+    tool_id: &str,
+    body: &[u8],
+    priorities: &std::collections::HashMap<String, u8>,
+) -> u8 {
+    if priorities.is_empty() || body.is_empty() {
+        return 0;
+    }
+
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(body) else {
+        return 0;
+    };
+    let session_id = if tool_id == "codex" {
+        json["prompt_cache_key"].as_str()
+    } else {
+        json["metadata"]["user_id"].as_str()
+    };
+    let Some(session_id) = session_id else {
+        return 0;
+    };
+
+    let note = SESSION_MANAGER
+        .get_session(session_id)
+        .ok()
+        .flatten()
+        .and_then(|session| session.note);
+    let display_id = ProxySession::extract_display_id(session_id);
+
+    note.as_deref()
+        .and_then(|tag| priorities.get(tag))
+        .or_else(|| priorities.get(&display_id))
+        .copied()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_tokens_forwarded_when_allowed() {
+        let mut config = ToolProxyConfig::new(8787);
+        config.allow_count_tokens = true;
+        assert!(intercept_count_tokens(COUNT_TOKENS_PATH, &config).is_none());
+    }
+
+    #[test]
+    fn test_count_tokens_blocked_by_default() {
+        let config = ToolProxyConfig::new(8787);
+        assert!(!config.allow_count_tokens);
+
+        let response = intercept_count_tokens(COUNT_TOKENS_PATH, &config).unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // 其他路径不受影响
+        assert!(intercept_count_tokens("/v1/messages", &config).is_none());
+    }
+}
@@ -6,14 +6,51 @@
 // - 确保端口不冲突
 
 use anyhow::{Context, Result};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::headers::create_request_processor;
 use super::proxy_instance::ProxyInstance;
+use super::utils::request_history::RequestSummary;
+use super::utils::startup_probe;
 use crate::models::proxy_config::ToolProxyConfig;
 
+/// 代理监听模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyBindMode {
+    /// 监听 0.0.0.0（允许局域网访问）
+    Public,
+    /// 仅监听 127.0.0.1
+    Local,
+}
+
+/// 代理运行状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProxyStatus {
+    pub tool_id: String,
+    pub running: bool,
+    pub port: u16,
+    pub bind_mode: ProxyBindMode,
+}
+
+impl ProxyStatus {
+    pub fn from_config(tool_id: &str, running: bool, config: &ToolProxyConfig) -> Self {
+        Self {
+            tool_id: tool_id.to_string(),
+            running,
+            port: config.port,
+            bind_mode: if config.allow_public {
+                ProxyBindMode::Public
+            } else {
+                ProxyBindMode::Local
+            },
+        }
+    }
+}
+
 /// 代理管理器
 pub struct ProxyManager {
     instances: Arc<RwLock<HashMap<String, ProxyInstance>>>,
@@ -62,16 +99,18 @@ impl ProxyManager {
         let processor = create_request_processor(tool_id).context("创建请求处理器失败")?;
 
         // 创建并启动代理实例
-        let instance = ProxyInstance::new(tool_id.to_string(), config, processor);
+        let instance = ProxyInstance::new(tool_id.to_string(), config.clone(), processor);
         instance
             .start()
             .await
             .context(format!("启动 {tool_id} 代理失败"))?;
 
-        // 存入 HashMap
+        // 存入 HashMap，并在持有写锁期间登记启动探测，确保与 stop_proxy 的 clear 有序
         {
             let mut instances = self.instances.write().await;
             instances.insert(tool_id.to_string(), instance);
+            // 异步校验上游可达性（不阻塞启动）
+            startup_probe::spawn_startup_check(tool_id, &config);
         }
 
         Ok(())
@@ -88,6 +127,7 @@ impl ProxyManager {
     pub async fn stop_proxy(&self, tool_id: &str) -> Result<()> {
         let mut instances = self.instances.write().await;
 
+        startup_probe::clear(tool_id);
         if let Some(instance) = instances.remove(tool_id) {
             instance
                 .stop()
@@ -106,6 +146,7 @@ impl ProxyManager {
         let tool_ids: Vec<String> = instances.keys().cloned().collect();
 
         for tool_id in tool_ids {
+            startup_probe::clear(&tool_id);
             if let Some(instance) = instances.remove(&tool_id) {
                 if let Err(e) = instance.stop().await {
                     tracing::error!(
@@ -125,16 +166,26 @@ impl ProxyManager {
         let instances = self.instances.read().await;
         instances
             .get(tool_id)
-            .map(|i| {
-                // 使用 blocking 获取状态（在异步上下文中）
-                // 实际实现中应该使用异步版本
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Handle::current().block_on(async { i.is_running_async().await })
-                })
-            })
+            .map(ProxyInstance::is_running)
             .unwrap_or(false)
     }
 
+    /// 获取已创建代理实例的运行状态（端口与监听模式取运行中的配置，按 tool_id 排序）
+    pub async fn get_proxy_status(&self) -> Vec<ProxyStatus> {
+        let instances = self.instances.read().await;
+        let mut statuses = Vec::with_capacity(instances.len());
+        for (tool_id, instance) in instances.iter() {
+            let config = instance.config_snapshot().await;
+            statuses.push(ProxyStatus::from_config(
+                tool_id,
+                instance.is_running(),
+                &config,
+            ));
+        }
+        statuses.sort_by(|a, b| a.tool_id.cmp(&b.tool_id));
+        statuses
+    }
+
     /// 获取所有工具的代理运行状态
     ///
     /// # 返回
@@ -151,6 +202,28 @@ impl ProxyManager {
         status_map
     }
 
+    /// 获取运行中代理实例的当前配置快照（未运行时返回 None）
+    pub async fn runtime_config(&self, tool_id: &str) -> Option<ToolProxyConfig> {
+        let instances = self.instances.read().await;
+        let instance = instances.get(tool_id)?;
+        if !instance.is_running_async().await {
+            return None;
+        }
+        Some(instance.config_snapshot().await)
+    }
+
+    /// 获取代理实例最近的请求摘要（最新的在前，实例不存在时返回 None）
+    pub async fn recent_requests(
+        &self,
+        tool_id: &str,
+        limit: usize,
+    ) -> Option<Vec<RequestSummary>> {
+        let instances = self.instances.read().await;
+        instances
+            .get(tool_id)
+            .map(|instance| instance.recent_requests(limit))
+    }
+
     /// 更新指定工具的代理配置（无需重启）
     pub async fn update_config(&self, tool_id: &str, config: ToolProxyConfig) -> Result<()> {
         let instances = self.instances.read().await;
@@ -193,5 +266,172 @@ mod tests {
         assert!(status.is_empty());
     }
 
-    // 更多测试需要 mock 或集成测试环境
+    #[tokio::test]
+    async fn test_instance_running_flag_follows_start_and_stop() {
+        let port = free_port().await;
+        let processor = create_request_processor("codex").unwrap();
+        let instance =
+            ProxyInstance::new("codex".to_string(), ToolProxyConfig::new(port), processor);
+        assert!(!instance.is_running());
+
+        instance.start().await.unwrap();
+        assert!(instance.is_running());
+
+        instance.stop().await.unwrap();
+        assert!(!instance.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_get_proxy_status() {
+        let manager = ProxyManager::new();
+        let port = free_port().await;
+        let mut config = ToolProxyConfig::new(port);
+        config.allow_public = false;
+
+        manager.start_proxy("gemini-cli", config).await.unwrap();
+        assert!(manager.is_running("gemini-cli").await);
+        assert_eq!(
+            manager.get_proxy_status().await,
+            vec![ProxyStatus {
+                tool_id: "gemini-cli".to_string(),
+                running: true,
+                port,
+                bind_mode: ProxyBindMode::Local,
+            }]
+        );
+
+        manager.stop_proxy("gemini-cli").await.unwrap();
+        assert!(!manager.is_running("gemini-cli").await);
+        assert!(manager.get_proxy_status().await.is_empty());
+    }
+
+    /// 获取一个当前空闲的本地端口
+    async fn free_port() -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    /// 发送一个简单请求并返回响应状态行
+    async fn request_status_line(port: u16) -> std::io::Result<String> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port)).await?;
+        stream
+            .write_all(b"GET /v1/models HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response.lines().next().unwrap_or_default().to_string())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_update_config_switches_port_gracefully() {
+        let manager = ProxyManager::new();
+        let old_port = free_port().await;
+        let new_port = free_port().await;
+
+        manager
+            .start_proxy("claude-code", ToolProxyConfig::new(old_port))
+            .await
+            .unwrap();
+        assert!(request_status_line(old_port)
+            .await
+            .unwrap()
+            .starts_with("HTTP/1.1"));
+
+        // 保持一个空闲的 keep-alive 连接，切换后应被优雅关闭而不是阻塞排空
+        let idle = tokio::net::TcpStream::connect(("127.0.0.1", old_port))
+            .await
+            .unwrap();
+
+        manager
+            .update_config("claude-code", ToolProxyConfig::new(new_port))
+            .await
+            .unwrap();
+
+        // 新端口立即可用
+        assert!(request_status_line(new_port)
+            .await
+            .unwrap()
+            .starts_with("HTTP/1.1"));
+
+        // 旧端口排空后关闭
+        let mut closed = false;
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", old_port))
+                .await
+                .is_err()
+            {
+                closed = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(closed, "旧端口应停止监听");
+        drop(idle);
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_runtime_config_snapshot() {
+        let manager = ProxyManager::new();
+        let port = free_port().await;
+        assert!(manager.runtime_config("gemini-cli").await.is_none());
+
+        let mut config = ToolProxyConfig::new(port);
+        config.real_api_key = Some("sk-real-1234567890".to_string());
+        config.local_api_key = Some("short".to_string());
+        manager
+            .start_proxy("gemini-cli", config.clone())
+            .await
+            .unwrap();
+
+        // 热更新后的值应体现在快照中
+        config.max_retries = 5;
+        config.real_base_url = Some("https://api.example.com".to_string());
+        manager.update_config("gemini-cli", config).await.unwrap();
+
+        let snapshot = manager.runtime_config("gemini-cli").await.unwrap();
+        assert_eq!(snapshot.port, port);
+        assert_eq!(snapshot.max_retries, 5);
+        assert_eq!(
+            snapshot.real_base_url.as_deref(),
+            Some("https://api.example.com")
+        );
+
+        let masked = snapshot.masked();
+        assert_eq!(masked.real_api_key.as_deref(), Some("sk-r...7890"));
+        assert_eq!(masked.local_api_key.as_deref(), Some("****"));
+        assert_eq!(masked.tavily_api_key, None);
+
+        manager.stop_all().await.unwrap();
+        assert!(manager.runtime_config("gemini-cli").await.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_update_config_keeps_old_port_when_bind_fails() {
+        let manager = ProxyManager::new();
+        let port = free_port().await;
+        let occupied = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let occupied_port = occupied.local_addr().unwrap().port();
+
+        manager
+            .start_proxy("codex", ToolProxyConfig::new(port))
+            .await
+            .unwrap();
+
+        let result = manager
+            .update_config("codex", ToolProxyConfig::new(occupied_port))
+            .await;
+        assert!(result.is_err());
+
+        // 旧端口继续服务
+        assert!(request_status_line(port)
+            .await
+            .unwrap()
+            .starts_with("HTTP/1.1"));
+
+        manager.stop_all().await.unwrap();
+    }
 }
@@ -232,6 +232,12 @@ mod tests {
             startup_enabled: false,
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            health_check: crate::models::config::HealthCheckConfig::default(),
+            download_cache: crate::models::config::DownloadCacheConfig::default(),
+            usage_anomaly: crate::models::config::UsageAnomalyConfig::default(),
+            budget: crate::models::config::BudgetConfig::default(),
+            session_ttl_hours: 24,
+            close_behavior: crate::models::config::CloseBehavior::default(),
         };
 
         let url = ProxyService::build_proxy_url(&config);
@@ -263,6 +269,12 @@ mod tests {
             startup_enabled: false,
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            health_check: crate::models::config::HealthCheckConfig::default(),
+            download_cache: crate::models::config::DownloadCacheConfig::default(),
+            usage_anomaly: crate::models::config::UsageAnomalyConfig::default(),
+            budget: crate::models::config::BudgetConfig::default(),
+            session_ttl_hours: 24,
+            close_behavior: crate::models::config::CloseBehavior::default(),
         };
 
         let url = ProxyService::build_proxy_url(&config);
@@ -297,6 +309,12 @@ mod tests {
             startup_enabled: false,
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            health_check: crate::models::config::HealthCheckConfig::default(),
+            download_cache: crate::models::config::DownloadCacheConfig::default(),
+            usage_anomaly: crate::models::config::UsageAnomalyConfig::default(),
+            budget: crate::models::config::BudgetConfig::default(),
+            session_ttl_hours: 24,
+            close_behavior: crate::models::config::CloseBehavior::default(),
         };
 
         let url = ProxyService::build_proxy_url(&config);
@@ -0,0 +1,305 @@
+//! 代理告警聚合
+//!
+//! 同一工具、同一错误类型在时间窗口内只告警一次，窗口内后续的相同错误
+//! 仅计数，窗口结束后合并为一条带计数的告警，避免错误风暴刷屏。
+//! 后台任务按窗口周期刷新过期窗口，即使之后不再出错，被抑制的计数也会及时输出
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 默认聚合窗口
+pub const DEFAULT_ALERT_WINDOW: Duration = Duration::from_secs(60);
+
+/// 全局代理告警聚合器
+static PROXY_ALERTS: Lazy<Mutex<AlertAggregator>> =
+    Lazy::new(|| Mutex::new(AlertAggregator::new(DEFAULT_ALERT_WINDOW)));
+
+/// 后台刷新任务是否已启动
+static FLUSH_TASK_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 错误类型
+pub mod kind {
+    /// 连接上游失败
+    pub const UPSTREAM_CONNECT: &str = "upstream_connect";
+    /// 上游请求超时
+    pub const UPSTREAM_TIMEOUT: &str = "upstream_timeout";
+    /// 其他上游请求错误
+    pub const UPSTREAM_REQUEST: &str = "upstream_request";
+    /// 代理内部处理失败
+    pub const REQUEST_FAILED: &str = "request_failed";
+}
+
+/// 按 reqwest 错误归类告警类型
+pub fn classify_upstream_error(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        kind::UPSTREAM_TIMEOUT
+    } else if error.is_connect() {
+        kind::UPSTREAM_CONNECT
+    } else {
+        kind::UPSTREAM_REQUEST
+    }
+}
+
+/// 待输出的告警
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    pub tool_id: String,
+    pub kind: String,
+    /// 最近一次错误的信息
+    pub message: String,
+    /// 本条告警合并的错误次数
+    pub count: u64,
+}
+
+/// 单个 (工具, 错误类型) 的窗口状态
+#[derive(Debug)]
+struct AlertWindow {
+    started_at: Instant,
+    /// 窗口内被抑制（尚未告警）的次数
+    suppressed: u64,
+    last_message: String,
+}
+
+/// 告警聚合器（时间由调用方传入，便于测试）
+#[derive(Debug)]
+pub struct AlertAggregator {
+    window: Duration,
+    windows: HashMap<(String, String), AlertWindow>,
+}
+
+impl AlertAggregator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// 记录一次错误
+    ///
+    /// 窗口内首次出现时立即返回告警；窗口内重复出现只计数并返回 `None`；
+    /// 窗口过期后再次出现时，返回合并了此前被抑制次数的告警并开启新窗口
+    pub fn record(
+        &mut self,
+        tool_id: &str,
+        kind: &str,
+        message: &str,
+        now: Instant,
+    ) -> Option<Alert> {
+        let key = (tool_id.to_string(), kind.to_string());
+
+        if let Some(window) = self.windows.get_mut(&key) {
+            if now.duration_since(window.started_at) < self.window {
+                window.suppressed += 1;
+                window.last_message = message.to_string();
+                return None;
+            }
+        }
+
+        let previous = self.windows.insert(
+            key,
+            AlertWindow {
+                started_at: now,
+                suppressed: 0,
+                last_message: message.to_string(),
+            },
+        );
+
+        Some(Alert {
+            tool_id: tool_id.to_string(),
+            kind: kind.to_string(),
+            message: message.to_string(),
+            count: 1 + previous.map(|w| w.suppressed).unwrap_or(0),
+        })
+    }
+
+    /// 取出已过期且有被抑制次数的窗口，合并为告警（不会丢失窗口内的计数）
+    pub fn drain_expired(&mut self, now: Instant) -> Vec<Alert> {
+        let window = self.window;
+        let expired: Vec<_> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| now.duration_since(w.started_at) >= window)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|key| {
+                let w = self.windows.remove(&key)?;
+                (w.suppressed > 0).then(|| Alert {
+                    tool_id: key.0,
+                    kind: key.1,
+                    message: w.last_message,
+                    count: w.suppressed,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 上报一次代理错误，按聚合结果输出告警日志
+pub fn report(tool_id: &str, kind: &str, message: &str) {
+    ensure_flush_task();
+
+    let now = Instant::now();
+    let alerts = {
+        let mut aggregator = PROXY_ALERTS.lock().unwrap_or_else(|e| e.into_inner());
+        let mut alerts = aggregator.drain_expired(now);
+        alerts.extend(aggregator.record(tool_id, kind, message, now));
+        alerts
+    };
+    emit(alerts);
+}
+
+/// 输出已过期窗口中被抑制的告警
+fn flush_expired() {
+    let alerts = PROXY_ALERTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .drain_expired(Instant::now());
+    emit(alerts);
+}
+
+/// 首次上报时启动后台刷新任务（每个窗口周期检查一次过期窗口）
+fn ensure_flush_task() {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    if FLUSH_TASK_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    handle.spawn(async {
+        let mut interval = tokio::time::interval(DEFAULT_ALERT_WINDOW);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            flush_expired();
+        }
+    });
+}
+
+fn emit(alerts: Vec<Alert>) {
+    for alert in alerts {
+        tracing::error!(
+            tool_id = %alert.tool_id,
+            kind = %alert.kind,
+            count = alert.count,
+            error = %alert.message,
+            "代理告警"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_error_alerts_immediately_and_repeats_are_suppressed() {
+        let mut aggregator = AlertAggregator::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        let first = aggregator.record("claude-code", kind::UPSTREAM_CONNECT, "refused", t0);
+        assert_eq!(first.map(|a| a.count), Some(1));
+
+        for i in 1..=5 {
+            let alert = aggregator.record(
+                "claude-code",
+                kind::UPSTREAM_CONNECT,
+                "refused",
+                t0 + Duration::from_secs(i),
+            );
+            assert!(alert.is_none());
+        }
+    }
+
+    #[test]
+    fn test_expired_window_merges_suppressed_count() {
+        let mut aggregator = AlertAggregator::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        aggregator.record("codex", kind::UPSTREAM_TIMEOUT, "timeout 1", t0);
+        aggregator.record(
+            "codex",
+            kind::UPSTREAM_TIMEOUT,
+            "timeout 2",
+            t0 + Duration::from_secs(10),
+        );
+        aggregator.record(
+            "codex",
+            kind::UPSTREAM_TIMEOUT,
+            "timeout 3",
+            t0 + Duration::from_secs(20),
+        );
+
+        let merged = aggregator
+            .record(
+                "codex",
+                kind::UPSTREAM_TIMEOUT,
+                "timeout 4",
+                t0 + Duration::from_secs(61),
+            )
+            .unwrap();
+        assert_eq!(merged.count, 3);
+        assert_eq!(merged.message, "timeout 4");
+    }
+
+    #[test]
+    fn test_different_kinds_and_tools_are_independent() {
+        let mut aggregator = AlertAggregator::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        assert!(aggregator
+            .record("codex", kind::UPSTREAM_CONNECT, "a", t0)
+            .is_some());
+        assert!(aggregator
+            .record("codex", kind::UPSTREAM_TIMEOUT, "b", t0)
+            .is_some());
+        assert!(aggregator
+            .record("gemini-cli", kind::UPSTREAM_CONNECT, "c", t0)
+            .is_some());
+        assert!(aggregator
+            .record("codex", kind::UPSTREAM_CONNECT, "d", t0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_drain_expired_flushes_only_suppressed_windows() {
+        let mut aggregator = AlertAggregator::new(Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        aggregator.record("codex", kind::UPSTREAM_CONNECT, "a", t0);
+        aggregator.record(
+            "codex",
+            kind::UPSTREAM_CONNECT,
+            "b",
+            t0 + Duration::from_secs(1),
+        );
+        aggregator.record("claude-code", kind::REQUEST_FAILED, "c", t0);
+
+        assert!(aggregator
+            .drain_expired(t0 + Duration::from_secs(30))
+            .is_empty());
+
+        let flushed = aggregator.drain_expired(t0 + Duration::from_secs(60));
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].tool_id, "codex");
+        assert_eq!(flushed[0].count, 1);
+        assert_eq!(flushed[0].message, "b");
+
+        // 已清空的窗口再次出现时重新立即告警
+        let alert = aggregator
+            .record(
+                "codex",
+                kind::UPSTREAM_CONNECT,
+                "e",
+                t0 + Duration::from_secs(61),
+            )
+            .unwrap();
+        assert_eq!(alert.count, 1);
+    }
+}
@@ -0,0 +1,73 @@
+//! 请求体/响应体大小上限
+//!
+//! 读取完整 body 前做上限检查，避免异常的大请求或大响应导致内存耗尽
+
+use anyhow::{anyhow, Result};
+use bytes::{Bytes, BytesMut};
+use futures_util::{Stream, StreamExt};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use hyper::body::Body;
+
+/// 读取请求体，超过 `limit` 字节时返回 `None`
+pub async fn collect_body_limited<B>(body: B, limit: usize) -> Result<Option<Bytes>>
+where
+    B: Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    match Limited::new(body, limit).collect().await {
+        Ok(collected) => Ok(Some(collected.to_bytes())),
+        Err(e) if e.downcast_ref::<LengthLimitError>().is_some() => Ok(None),
+        Err(e) => Err(anyhow!("读取请求体失败: {}", e)),
+    }
+}
+
+/// 读取响应字节流，超过 `limit` 字节时返回 `None`（立即停止读取）
+pub async fn collect_stream_limited<S, E>(stream: S, limit: usize) -> Result<Option<Bytes>, E>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    futures_util::pin_mut!(stream);
+    let mut buf = BytesMut::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(Some(buf.freeze()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::Full;
+
+    #[tokio::test]
+    async fn test_collect_body_within_limit() {
+        let body = Full::new(Bytes::from_static(b"hello"));
+        let collected = collect_body_limited(body, 5).await.unwrap();
+        assert_eq!(collected, Some(Bytes::from_static(b"hello")));
+    }
+
+    #[tokio::test]
+    async fn test_collect_body_over_limit() {
+        let body = Full::new(Bytes::from(vec![0u8; 1024]));
+        assert!(collect_body_limited(body, 1023).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_collect_stream_limited() {
+        let chunks = || {
+            futures_util::stream::iter(vec![
+                Ok::<_, std::io::Error>(Bytes::from_static(b"abc")),
+                Ok(Bytes::from_static(b"def")),
+            ])
+        };
+
+        let collected = collect_stream_limited(chunks(), 6).await.unwrap();
+        assert_eq!(collected, Some(Bytes::from_static(b"abcdef")));
+
+        assert!(collect_stream_limited(chunks(), 5).await.unwrap().is_none());
+    }
+}
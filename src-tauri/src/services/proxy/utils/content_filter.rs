@@ -0,0 +1,616 @@
+//! 流式响应内容过滤
+//!
+//! 按配置的正则规则对 SSE 中的文本 delta 做替换（脱敏），再转发给客户端。
+//! 支持 Anthropic `delta.text`、OpenAI `choices[].delta.content`、
+//! Responses API `delta` 与 Gemini `candidates[].content.parts[].text`。
+//! 同一内容块的连续 delta 会拼接匹配：末尾可能与后续 delta 拼出匹配的事件暂扣，
+//! 直到确定不会被切分（或内容块结束）再输出，替换结果放在匹配起始所在的 delta 中
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use regex::{Regex, RegexSet};
+use serde_json::Value;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::models::proxy_config::ContentFilterRule;
+
+/// 跨 delta 匹配时至少暂扣的末尾字符数（规则本身更长时按最长规则计）
+const MIN_HOLDBACK_CHARS: usize = 64;
+
+/// 编译后的内容过滤器
+#[derive(Debug)]
+pub struct ContentFilter {
+    /// 所有规则的联合匹配，用于快速跳过无需处理的行
+    set: RegexSet,
+    rules: Vec<(Regex, String)>,
+    /// 流式输出时暂扣的末尾字符数（不短于最长的规则）
+    holdback_chars: usize,
+}
+
+impl ContentFilter {
+    /// 编译过滤规则（无规则时返回 None，任一正则非法时返回错误）
+    pub fn compile(rules: &[ContentFilterRule]) -> Result<Option<Self>> {
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        let compiled = rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|re| (re, rule.replacement.clone()))
+                    .with_context(|| format!("内容过滤正则非法: {}", rule.pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let set = RegexSet::new(rules.iter().map(|rule| &rule.pattern))
+            .context("编译内容过滤规则失败")?;
+        let holdback_chars = rules
+            .iter()
+            .map(|rule| rule.pattern.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(MIN_HOLDBACK_CHARS);
+        Ok(Some(Self {
+            set,
+            rules: compiled,
+            holdback_chars,
+        }))
+    }
+
+    /// 对文本依次应用所有规则，返回替换后的文本（无匹配时返回 None）
+    pub fn apply(&self, text: &str) -> Option<String> {
+        if !self.set.is_match(text) {
+            return None;
+        }
+        let mut out = text.to_string();
+        for (re, replacement) in &self.rules {
+            if let std::borrow::Cow::Owned(replaced) = re.replace_all(&out, replacement.as_str()) {
+                out = replaced;
+            }
+        }
+        Some(out)
+    }
+
+    /// 处理单行 SSE 数据（含行尾），仅在文本 delta 被替换时重新序列化
+    fn filter_line(&self, line: &[u8]) -> Option<Vec<u8>> {
+        let text = std::str::from_utf8(line).ok()?;
+        let data = text.strip_prefix("data:")?;
+        // 预筛：原始行无匹配时直接透传，避免解析 JSON
+        // （含转义字符时原文与解码后文本不一致，不做预筛）
+        if !data.contains('\\') && !self.set.is_match(data) {
+            return None;
+        }
+        let body = data.trim_end_matches(['\r', '\n']);
+        let ending = &data[body.len()..];
+        let mut value: Value = serde_json::from_str(body.trim()).ok()?;
+        if !self.filter_value(&mut value) {
+            return None;
+        }
+        Some(format!("data: {value}{ending}").into_bytes())
+    }
+
+    /// 替换 JSON 事件中已知位置的文本 delta，返回是否有改动
+    fn filter_value(&self, value: &mut Value) -> bool {
+        let mut changed = false;
+        for_each_text(value, |_, text| changed |= self.replace(text));
+        changed
+    }
+
+    /// 对同一内容块的多段连续文本应用所有规则
+    ///
+    /// 匹配按拼接后的整体计算，替换结果放在匹配起始所在的分段，被匹配的其余部分从各分段中移除
+    fn apply_across(&self, mut texts: Vec<String>) -> Vec<String> {
+        for (re, replacement) in &self.rules {
+            let joined = texts.concat();
+            if !re.is_match(&joined) {
+                continue;
+            }
+            let bounds = segment_bounds(&texts);
+            let mut out = vec![String::new(); texts.len()];
+            let mut last = 0;
+            for caps in re.captures_iter(&joined) {
+                let Some(m) = caps.get(0) else {
+                    continue;
+                };
+                copy_segments(&joined, last..m.start(), &bounds, &mut out);
+                let owner = bounds
+                    .iter()
+                    .position(|bound| m.start() < bound.end)
+                    .unwrap_or(bounds.len() - 1);
+                caps.expand(replacement, &mut out[owner]);
+                last = m.end();
+            }
+            copy_segments(&joined, last..joined.len(), &bounds, &mut out);
+            texts = out;
+        }
+        texts
+    }
+
+    /// 计算连续文本中可以安全输出的前几段：末尾 `holdback_chars` 个字符内的分段暂扣，
+    /// 且输出边界不能落在任何匹配中间（匹配延伸到末尾时可能随后续 delta 继续增长）
+    fn releasable_segments(&self, texts: &[String]) -> usize {
+        let joined = texts.concat();
+        let Some((cutoff, _)) = joined.char_indices().rev().nth(self.holdback_chars - 1) else {
+            return 0;
+        };
+        let matches: Vec<Range<usize>> = self
+            .rules
+            .iter()
+            .flat_map(|(re, _)| re.find_iter(&joined).map(|m| m.range()))
+            .collect();
+        segment_bounds(texts)
+            .iter()
+            .rposition(|bound| {
+                bound.end <= cutoff
+                    && !matches
+                        .iter()
+                        .any(|m| m.start < bound.end && bound.end < m.end)
+            })
+            .map_or(0, |index| index + 1)
+    }
+
+    fn replace(&self, text: &mut String) -> bool {
+        match self.apply(text) {
+            Some(replaced) if replaced != *text => {
+                *text = replaced;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 遍历 JSON 事件中已知位置的文本 delta，回调参数为（内容块标识, 文本）
+fn for_each_text(value: &mut Value, mut f: impl FnMut(String, &mut String)) {
+    // Anthropic: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"..."}}
+    // Responses API: {"type":"response.output_text.delta","output_index":0,"content_index":0,"delta":"..."}
+    let block = format!(
+        "delta/{}/{}/{}",
+        value["index"], value["output_index"], value["content_index"]
+    );
+    match value.get_mut("delta") {
+        Some(Value::String(text)) => f(block, text),
+        Some(delta) => {
+            if let Some(Value::String(text)) = delta.get_mut("text") {
+                f(block, text);
+            }
+        }
+        None => {}
+    }
+
+    // OpenAI Chat Completions: {"choices":[{"delta":{"content":"..."}}]}
+    if let Some(choices) = value.get_mut("choices").and_then(Value::as_array_mut) {
+        for (i, choice) in choices.iter_mut().enumerate() {
+            if let Some(Value::String(text)) = choice.pointer_mut("/delta/content") {
+                f(format!("choices/{i}"), text);
+            }
+        }
+    }
+
+    // Gemini: {"candidates":[{"content":{"parts":[{"text":"..."}]}}]}
+    if let Some(candidates) = value.get_mut("candidates").and_then(Value::as_array_mut) {
+        for (i, candidate) in candidates.iter_mut().enumerate() {
+            let Some(parts) = candidate
+                .pointer_mut("/content/parts")
+                .and_then(Value::as_array_mut)
+            else {
+                continue;
+            };
+            for (j, part) in parts.iter_mut().enumerate() {
+                if let Some(Value::String(text)) = part.get_mut("text") {
+                    f(format!("candidates/{i}/parts/{j}"), text);
+                }
+            }
+        }
+    }
+}
+
+/// 各分段在拼接文本中的字节范围
+fn segment_bounds(texts: &[String]) -> Vec<Range<usize>> {
+    let mut start = 0;
+    texts
+        .iter()
+        .map(|text| {
+            let bound = start..start + text.len();
+            start = bound.end;
+            bound
+        })
+        .collect()
+}
+
+/// 将拼接文本中 `range` 的内容按原分段归属追加到对应输出
+fn copy_segments(joined: &str, range: Range<usize>, bounds: &[Range<usize>], out: &mut [String]) {
+    for (bound, text) in bounds.iter().zip(out.iter_mut()) {
+        let start = range.start.max(bound.start);
+        let end = range.end.min(bound.end);
+        if start < end {
+            text.push_str(&joined[start..end]);
+        }
+    }
+}
+
+/// 按空行切分完整的 SSE 事件，返回事件列表与已切分的字节数（其后为未结束的事件）
+fn split_events(data: &[u8]) -> (Vec<&[u8]>, usize) {
+    let mut events = Vec::new();
+    let (mut start, mut pos) = (0, 0);
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        pos += line.len();
+        if line == b"\n" || line == b"\r\n" {
+            events.push(&data[start..pos]);
+            start = pos;
+        }
+    }
+    (events, start)
+}
+
+/// 暂扣的文本 delta 事件
+#[derive(Debug)]
+struct HeldDelta {
+    /// 原始事件字节
+    event: Vec<u8>,
+    /// `data:` 行（不含行尾）在事件中的位置
+    data_line: Range<usize>,
+    value: Value,
+    /// 原始文本
+    text: String,
+}
+
+impl HeldDelta {
+    /// 解析只含单个文本 delta 的事件，返回（内容块标识, 暂扣事件）
+    fn parse(event: &[u8]) -> Option<(String, Self)> {
+        let raw = std::str::from_utf8(event).ok()?;
+        let mut data_line = None;
+        let mut pos = 0;
+        for line in raw.split_inclusive('\n') {
+            if line.starts_with("data:") {
+                // 多行 data 的事件不做拼接匹配
+                if data_line.is_some() {
+                    return None;
+                }
+                data_line = Some(pos..pos + line.trim_end_matches(['\r', '\n']).len());
+            }
+            pos += line.len();
+        }
+        let data_line = data_line?;
+        let mut value: Value =
+            serde_json::from_str(raw[data_line.clone()]["data:".len()..].trim()).ok()?;
+
+        let mut texts = Vec::new();
+        for_each_text(&mut value, |block, text| texts.push((block, text.clone())));
+        if texts.len() != 1 {
+            return None;
+        }
+        let (block, text) = texts.pop()?;
+        Some((
+            block,
+            Self {
+                event: event.to_vec(),
+                data_line,
+                value,
+                text,
+            },
+        ))
+    }
+
+    /// 以替换后的文本输出事件（文本未变时原样输出）
+    fn into_bytes(self, text: String) -> Vec<u8> {
+        if text == self.text {
+            return self.event;
+        }
+        let mut value = self.value;
+        for_each_text(&mut value, |_, slot| *slot = text.clone());
+        let mut out = self.event[..self.data_line.start].to_vec();
+        out.extend_from_slice(format!("data: {value}").as_bytes());
+        out.extend_from_slice(&self.event[self.data_line.end..]);
+        out
+    }
+}
+
+/// SSE 流式过滤器
+///
+/// 按事件缓冲上游数据（chunk 可能在任意位置切分），完整事件才做过滤；
+/// 同一内容块的文本 delta 拼接匹配，非 `data:` 行与未命中规则的行原样透传
+#[derive(Debug)]
+pub struct StreamContentFilter {
+    filter: Arc<ContentFilter>,
+    buffer: Vec<u8>,
+    /// 暂扣的文本 delta 事件（同一内容块）及其内容块标识
+    held: Vec<HeldDelta>,
+    held_block: Option<String>,
+}
+
+impl StreamContentFilter {
+    pub fn new(filter: Arc<ContentFilter>) -> Self {
+        Self {
+            filter,
+            buffer: Vec::new(),
+            held: Vec::new(),
+            held_block: None,
+        }
+    }
+
+    /// 处理一段上游数据，返回可转发的数据（不完整的事件与暂扣的 delta 留待下次处理）
+    pub fn push(&mut self, chunk: &[u8]) -> Bytes {
+        self.buffer.extend_from_slice(chunk);
+        let (events, complete) = split_events(&self.buffer);
+        if events.is_empty() {
+            return Bytes::new();
+        }
+        let events: Vec<Vec<u8>> = events.into_iter().map(<[u8]>::to_vec).collect();
+        self.buffer.drain(..complete);
+
+        let mut out = Vec::new();
+        for event in events {
+            self.process_event(&event, &mut out);
+        }
+        Bytes::from(out)
+    }
+
+    /// 上游流结束，输出剩余数据
+    pub fn finish(&mut self) -> Bytes {
+        let rest = std::mem::take(&mut self.buffer);
+        let mut out = Vec::new();
+        if !rest.is_empty() {
+            self.process_event(&rest, &mut out);
+        }
+        self.release_held(true, &mut out);
+        Bytes::from(out)
+    }
+
+    fn process_event(&mut self, event: &[u8], out: &mut Vec<u8>) {
+        match HeldDelta::parse(event) {
+            Some((block, held)) => {
+                // 切换到新的内容块：先输出上一块暂扣的全部 delta
+                if self.held_block.as_deref() != Some(block.as_str()) {
+                    self.release_held(true, out);
+                    self.held_block = Some(block);
+                }
+                self.held.push(held);
+                self.release_held(false, out);
+            }
+            None => {
+                // 非文本 delta 事件（如 content_block_stop）意味着内容块结束
+                self.release_held(true, out);
+                out.extend_from_slice(&self.filter_lines(event.to_vec()));
+            }
+        }
+    }
+
+    /// 输出暂扣的 delta：`all` 为 false 时只输出不会与后续 delta 拼出匹配的部分
+    fn release_held(&mut self, all: bool, out: &mut Vec<u8>) {
+        let texts: Vec<String> = self.held.iter().map(|held| held.text.clone()).collect();
+        let count = if all {
+            texts.len()
+        } else {
+            self.filter.releasable_segments(&texts)
+        };
+        if count == 0 {
+            return;
+        }
+
+        let filtered = self
+            .filter
+            .apply_across(texts.into_iter().take(count).collect());
+        for (held, text) in self.held.drain(..count).zip(filtered) {
+            out.extend_from_slice(&held.into_bytes(text));
+        }
+        if self.held.is_empty() {
+            self.held_block = None;
+        }
+    }
+
+    fn filter_lines(&self, data: Vec<u8>) -> Bytes {
+        if data.is_empty() {
+            return Bytes::new();
+        }
+        let mut out: Option<Vec<u8>> = None;
+        let mut start = 0;
+        for line in data.split_inclusive(|&b| b == b'\n') {
+            if let Some(filtered) = self.filter.filter_line(line) {
+                // 首次改动时才复制此前的原始数据
+                let buf = out.get_or_insert_with(|| data[..start].to_vec());
+                buf.extend_from_slice(&filtered);
+            } else if let Some(buf) = out.as_mut() {
+                buf.extend_from_slice(line);
+            }
+            start += line.len();
+        }
+        Bytes::from(out.unwrap_or(data))
+    }
+}
+
+/// 包装上游 SSE 字节流，输出过滤后的字节流
+///
+/// 上游出错时透传错误并结束
+pub fn filter_stream<S, E>(
+    inner: S,
+    filter: Arc<ContentFilter>,
+) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    futures_util::stream::unfold(
+        (Box::pin(inner), StreamContentFilter::new(filter), false),
+        |(mut inner, mut filter, done)| async move {
+            if done {
+                return None;
+            }
+            match inner.next().await {
+                Some(Ok(bytes)) => {
+                    let filtered = filter.push(&bytes);
+                    Some((Ok(filtered), (inner, filter, false)))
+                }
+                Some(Err(e)) => Some((Err(e), (inner, filter, true))),
+                None => Some((Ok(filter.finish()), (inner, filter, true))),
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(rules: &[(&str, &str)]) -> Arc<ContentFilter> {
+        let rules: Vec<ContentFilterRule> = rules
+            .iter()
+            .map(|(pattern, replacement)| ContentFilterRule {
+                pattern: pattern.to_string(),
+                replacement: replacement.to_string(),
+            })
+            .collect();
+        Arc::new(ContentFilter::compile(&rules).unwrap().unwrap())
+    }
+
+    fn run(filter: Arc<ContentFilter>, chunks: &[&[u8]]) -> String {
+        let mut stream = StreamContentFilter::new(filter);
+        let mut out = Vec::new();
+        for chunk in chunks {
+            out.extend_from_slice(&stream.push(chunk));
+        }
+        out.extend_from_slice(&stream.finish());
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_compile_rules() {
+        assert!(ContentFilter::compile(&[]).unwrap().is_none());
+        let invalid = vec![ContentFilterRule {
+            pattern: "(unclosed".to_string(),
+            replacement: String::new(),
+        }];
+        assert!(ContentFilter::compile(&invalid).is_err());
+
+        let f = filter(&[(r"sk-[A-Za-z0-9]+", "sk-***"), (r"\d{11}", "[PHONE]")]);
+        assert_eq!(
+            f.apply("key sk-abc123 tel 13800138000").as_deref(),
+            Some("key sk-*** tel [PHONE]")
+        );
+        assert_eq!(f.apply("nothing here"), None);
+    }
+
+    #[test]
+    fn test_filter_anthropic_delta_split_across_chunks() {
+        let f = filter(&[(r"sk-[A-Za-z0-9]+", "sk-***")]);
+        let output = run(
+            f,
+            &[
+                b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,",
+                b"\"delta\":{\"type\":\"text_delta\",\"text\":\"use sk-secret1 now\"}}\n\n",
+            ],
+        );
+        assert!(output.starts_with("event: content_block_delta\n"));
+        assert!(output.contains("use sk-*** now"));
+        assert!(!output.contains("sk-secret1"));
+        assert!(output.ends_with("}\n\n"));
+    }
+
+    #[test]
+    fn test_filter_secret_split_across_deltas() {
+        let f = filter(&[(r"sk-[A-Za-z0-9]+", "sk-***")]);
+        let delta = |text: &str| {
+            format!(
+                "event: content_block_delta\ndata: {{\"type\":\"content_block_delta\",\"index\":0,\"delta\":{{\"type\":\"text_delta\",\"text\":\"{text}\"}}}}\n\n"
+            )
+        };
+        let (first, second, third) = (delta("key sk-sec"), delta("ret1"), delta(" done"));
+        let stop =
+            "event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n";
+
+        let mut stream = StreamContentFilter::new(f);
+        // 末尾可能与后续 delta 拼出匹配：暂扣，直到内容块结束
+        assert!(stream.push(first.as_bytes()).is_empty());
+        assert!(stream.push(second.as_bytes()).is_empty());
+        assert!(stream.push(third.as_bytes()).is_empty());
+        let output = String::from_utf8(stream.push(stop.as_bytes()).to_vec()).unwrap();
+
+        // 替换结果放在匹配起始的 delta，后续 delta 中被匹配的部分移除，事件顺序不变
+        assert!(!output.contains("sec") && !output.contains("ret1"));
+        let texts: Vec<&str> = output
+            .match_indices("\"text\":\"")
+            .map(|(i, m)| {
+                let rest = &output[i + m.len()..];
+                &rest[..rest.find('"').unwrap()]
+            })
+            .collect();
+        assert_eq!(texts, vec!["key sk-***", "", " done"]);
+        assert!(output.ends_with(stop));
+        assert!(stream.finish().is_empty());
+    }
+
+    #[test]
+    fn test_release_held_deltas_outside_holdback_window() {
+        let f = filter(&[("secret", "******")]);
+        let delta = |text: &str| format!("data: {{\"delta\":{{\"text\":\"{text}\"}}}}\n\n");
+        let long = "a".repeat(MIN_HOLDBACK_CHARS);
+
+        let mut stream = StreamContentFilter::new(f);
+        assert!(stream.push(delta(&long).as_bytes()).is_empty());
+        // 第一段已超出暂扣窗口且不在匹配中间：原样输出
+        let released = stream.push(delta(&long).as_bytes());
+        assert_eq!(released.as_ref(), delta(&long).as_bytes());
+        // 末尾的部分匹配继续暂扣
+        assert!(stream.push(delta("sec").as_bytes()).is_empty());
+        let rest = String::from_utf8(stream.finish().to_vec()).unwrap();
+        assert!(!rest.contains("secret"));
+        assert!(rest.ends_with("\"text\":\"sec\"}}\n\n"));
+    }
+
+    #[test]
+    fn test_filter_openai_responses_and_gemini() {
+        let f = filter(&[("secret", "******")]);
+
+        let openai = run(
+            f.clone(),
+            &[b"data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"a secret\"}}]}\r\n\r\n"],
+        );
+        assert!(openai.contains("\"content\":\"a ******\""));
+        assert!(openai.ends_with("}\r\n\r\n"));
+
+        let responses = run(
+            f.clone(),
+            &[b"data: {\"type\":\"response.output_text.delta\",\"delta\":\"my secret\"}\n\n"],
+        );
+        assert!(responses.contains("\"delta\":\"my ******\""));
+
+        let gemini = run(
+            f,
+            &[b"data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"top secret\"}]}}]}\n\n"],
+        );
+        assert!(gemini.contains("\"text\":\"top ******\""));
+
+        // 上游将非 ASCII 字符转义为 \uXXXX 时同样生效
+        let escaped = run(
+            filter(&[("密钥", "**")]),
+            &[b"data: {\"delta\":{\"text\":\"\\u5bc6\\u94a5abc\"}}\n"],
+        );
+        assert!(escaped.contains("\"text\":\"**abc\""));
+    }
+
+    #[test]
+    fn test_passthrough_unmatched_and_non_text_fields() {
+        let f = filter(&[("secret", "******")]);
+        let input: &[u8] = b"event: ping\ndata: {\"type\":\"ping\"}\n\n: comment secret\n\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"secret\"}}\n\ndata: [DONE]\n\n";
+        // 未命中文本 delta 的行保持逐字节一致
+        assert_eq!(run(f, &[input]).as_bytes(), input);
+    }
+
+    #[tokio::test]
+    async fn test_filter_stream_flushes_tail() {
+        let f = filter(&[("secret", "******")]);
+        let source = futures_util::stream::iter(vec![
+            Ok::<_, String>(Bytes::from_static(b"data: {\"delta\":{\"text\":\"sec")),
+            Ok(Bytes::from_static(b"ret\"}}")),
+        ]);
+        let chunks: Vec<_> = filter_stream(source, f).collect().await;
+        let output: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|c| c.unwrap().to_vec())
+            .collect();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "data: {\"delta\":{\"text\":\"******\"}}"
+        );
+    }
+}
@@ -37,6 +37,70 @@ pub fn proxy_loop_detected(tool_id: &str) -> Response<BoxBody> {
         .unwrap()
 }
 
+/// 请求体或响应体超过大小上限
+pub fn payload_too_large(limit_bytes: u64) -> Response<BoxBody> {
+    Response::builder()
+        .status(StatusCode::PAYLOAD_TOO_LARGE)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(format!(
+            r#"{{
+  "error": "PAYLOAD_TOO_LARGE",
+  "message": "请求体或响应体超过大小上限",
+  "details": "当前上限为 {limit_bytes} 字节，可在代理设置中调整 max_body_bytes"
+}}"#
+        )))))
+        .unwrap()
+}
+
+/// 上游响应超时
+pub fn gateway_timeout(timeout_secs: u64) -> Response<BoxBody> {
+    Response::builder()
+        .status(StatusCode::GATEWAY_TIMEOUT)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(format!(
+            r#"{{
+  "error": "UPSTREAM_TIMEOUT",
+  "message": "上游在 {timeout_secs} 秒内未完成响应",
+  "details": "可在代理设置中调整 upstream_timeout_secs"
+}}"#
+        )))))
+        .unwrap()
+}
+
+/// 模型当日调用次数超过配额
+pub fn model_quota_exceeded(model: &str, limit: u32) -> Response<BoxBody> {
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            serde_json::json!({
+                "error": "MODEL_QUOTA_EXCEEDED",
+                "message": format!("模型 {model} 今日调用次数已达上限"),
+                "details": format!("每日上限为 {limit} 次，次日自动重置，可在代理设置中调整 model_daily_limits"),
+            })
+            .to_string(),
+        ))))
+        .unwrap()
+}
+
+/// count_tokens 接口未开放（Anthropic 错误格式）
+pub fn count_tokens_forbidden() -> Response<BoxBody> {
+    Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            serde_json::json!({
+                "type": "error",
+                "error": {
+                    "type": "permission_error",
+                    "message": "count_tokens endpoint is not enabled for this channel. Please enable it in channel settings."
+                }
+            })
+            .to_string(),
+        ))))
+        .unwrap()
+}
+
 /// 未授权错误
 pub fn unauthorized() -> Response<BoxBody> {
     Response::builder()
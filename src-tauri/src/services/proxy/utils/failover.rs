@@ -0,0 +1,181 @@
+//! 多上游故障转移
+//!
+//! 主上游连接失败或返回 5xx 时，按顺序将同一请求（请求体已在内存中）重放到备用上游。
+//! 每个上游内部仍按 `RetryPolicy` 重试；SSE 响应开始返回事件流后不再切换
+
+use reqwest::{Client, Method, Response};
+
+use super::request_compression::{self, RequestCompressionPolicy};
+use super::retry::{self, RetryPolicy};
+use crate::services::proxy::headers::ProcessedRequest;
+
+/// 备用上游请求
+#[derive(Debug)]
+pub struct BackupTarget {
+    /// 命中时记录到日志的配置名
+    pub name: String,
+    pub processed: ProcessedRequest,
+}
+
+/// 是否应切换到下一个上游
+///
+/// 连接失败（上游尚未处理请求）或返回 5xx 时切换；已开始返回事件流的响应不切换
+pub fn should_failover(result: &reqwest::Result<Response>) -> bool {
+    match result {
+        Ok(res) => {
+            let is_event_stream = res
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.contains("text/event-stream"));
+            res.status().is_server_error() && !is_event_stream
+        }
+        Err(e) => retry::is_retryable_error(e),
+    }
+}
+
+/// 依次向主上游与备用上游发送请求
+///
+/// 返回命中的上游序号（0 为主上游，`i` 为 `backups[i - 1]`）与最后一次请求的结果
+pub async fn send_with_failover(
+    client: &Client,
+    method: &Method,
+    primary: &ProcessedRequest,
+    backups: &[BackupTarget],
+    policy: RetryPolicy,
+    compression: RequestCompressionPolicy,
+    tool_id: &str,
+) -> (usize, reqwest::Result<Response>) {
+    let mut result =
+        request_compression::send(client, method, primary, policy, compression, tool_id).await;
+    let mut hit = 0;
+
+    for (i, backup) in backups.iter().enumerate() {
+        if !should_failover(&result) {
+            break;
+        }
+        tracing::warn!(
+            tool_id = tool_id,
+            status = result.as_ref().ok().map(|res| res.status().as_u16()),
+            error = result.as_ref().err().map(|e| e.to_string()),
+            next = %backup.name,
+            "上游不可用，切换到备用上游"
+        );
+        result = request_compression::send(
+            client,
+            method,
+            &backup.processed,
+            policy,
+            compression,
+            tool_id,
+        )
+        .await;
+        hit = i + 1;
+    }
+
+    (hit, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockUpstream};
+    use bytes::Bytes;
+    use reqwest::header::HeaderMap;
+
+    fn no_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            backoff_ms: 1,
+            retry_on_status: true,
+            timeout: None,
+        }
+    }
+
+    fn processed_for(base: &str, key: &str) -> ProcessedRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", key.parse().unwrap());
+        ProcessedRequest {
+            target_url: format!("{base}/v1/messages"),
+            headers,
+            body: Bytes::from_static(b"{\"model\":\"claude-sonnet-4-5\"}"),
+            session_profile: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_second_upstream_after_503() {
+        let primary_upstream =
+            MockUpstream::respond(MockResponse::status("503 Service Unavailable")).await;
+        let backup_upstream =
+            MockUpstream::respond(MockResponse::body("200 OK", "text/plain", "ok")).await;
+        let (primary_url, backup_url) = (primary_upstream.url(), backup_upstream.url());
+
+        let primary = processed_for(&primary_url, "sk-primary");
+        let backups = vec![BackupTarget {
+            name: "backup".to_string(),
+            processed: primary
+                .retarget(&primary_url, "sk-primary", &backup_url, "sk-backup")
+                .unwrap(),
+        }];
+
+        let (hit, result) = send_with_failover(
+            &Client::new(),
+            &Method::POST,
+            &primary,
+            &backups,
+            no_retry(),
+            RequestCompressionPolicy::default(),
+            "claude-code",
+        )
+        .await;
+
+        let res = result.unwrap();
+        assert_eq!(hit, 1);
+        assert_eq!(res.status().as_u16(), 200);
+        assert_eq!(res.text().await.unwrap(), "ok");
+
+        // 两个上游各收到一次请求，备用上游收到相同请求体与替换后的 Key
+        assert_eq!(primary_upstream.hits(), 1);
+        let backup_requests = backup_upstream.requests();
+        assert_eq!(backup_requests.len(), 1);
+        assert_eq!(backup_requests[0].header("x-api-key"), Some("sk-backup"));
+        assert_eq!(
+            backup_requests[0].body_text(),
+            "{\"model\":\"claude-sonnet-4-5\"}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_on_success_or_event_stream() {
+        let ok = MockUpstream::respond(MockResponse::body("200 OK", "text/plain", "ok")).await;
+        let sse_error = MockUpstream::respond(MockResponse::body(
+            "500 Internal Server Error",
+            "text/event-stream",
+            "",
+        ))
+        .await;
+        let backup = MockUpstream::respond(MockResponse::body("200 OK", "text/plain", "ok")).await;
+        let backup_url = backup.url();
+
+        for primary_url in [ok.url(), sse_error.url()] {
+            let primary = processed_for(&primary_url, "sk-primary");
+            let backups = vec![BackupTarget {
+                name: "backup".to_string(),
+                processed: processed_for(&backup_url, "sk-backup"),
+            }];
+            let (hit, _) = send_with_failover(
+                &Client::new(),
+                &Method::POST,
+                &primary,
+                &backups,
+                no_retry(),
+                RequestCompressionPolicy::default(),
+                "claude-code",
+            )
+            .await;
+            assert_eq!(hit, 0);
+        }
+        assert_eq!(backup.hits(), 0);
+    }
+}
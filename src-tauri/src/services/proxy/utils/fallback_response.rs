@@ -0,0 +1,241 @@
+//! 降级响应
+//!
+//! 上游完全不可用时，按工具协议构造一条预设的助手消息（SSE 或 JSON），
+//! 让客户端展示友好提示而非直接报错
+
+use bytes::Bytes;
+use hyper::{Response, StatusCode};
+use serde_json::{json, Value};
+
+use super::body::{box_body, BoxBody};
+
+/// 默认降级提示
+pub const DEFAULT_FALLBACK_MESSAGE: &str = "上游服务暂时不可用，请稍后重试。";
+
+/// 标记降级响应的响应头
+pub const FALLBACK_HEADER: &str = "x-duckcoding-fallback";
+
+/// 构建降级响应
+///
+/// - `tool_id`: 决定响应协议（claude-code / codex / gemini-cli）
+/// - `message`: 降级提示文本（None 时使用默认提示）
+/// - `request_body`: 原始请求体，用于提取 model 与 stream 字段
+pub fn build(tool_id: &str, message: Option<&str>, request_body: &[u8]) -> Response<BoxBody> {
+    let request: Value = serde_json::from_slice(request_body).unwrap_or(Value::Null);
+    let model = request
+        .get("model")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    let is_sse = request
+        .get("stream")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let text = message
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or(DEFAULT_FALLBACK_MESSAGE);
+
+    let body = render_body(tool_id, model, text, is_sse);
+    let content_type = if is_sse {
+        "text/event-stream"
+    } else {
+        "application/json"
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", content_type)
+        .header(FALLBACK_HEADER, "true")
+        .body(box_body(http_body_util::Full::new(Bytes::from(body))))
+        .unwrap()
+}
+
+/// 按工具协议渲染响应体
+fn render_body(tool_id: &str, model: &str, text: &str, is_sse: bool) -> String {
+    match (tool_id, is_sse) {
+        ("codex", true) => codex_sse(model, text),
+        ("codex", false) => codex_json(model, text).to_string(),
+        ("gemini-cli", true) => format!("data: {}\n\n", gemini_json(text)),
+        ("gemini-cli", false) => gemini_json(text).to_string(),
+        (_, true) => claude_sse(model, text),
+        (_, false) => claude_json(model, text).to_string(),
+    }
+}
+
+fn sse_event(event: &str, data: &Value) -> String {
+    format!("event: {}\ndata: {}\n\n", event, data)
+}
+
+fn claude_json(model: &str, text: &str) -> Value {
+    json!({
+        "id": "msg_duckcoding_fallback",
+        "type": "message",
+        "role": "assistant",
+        "model": model,
+        "content": [{ "type": "text", "text": text }],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 0, "output_tokens": 0 }
+    })
+}
+
+fn claude_sse(model: &str, text: &str) -> String {
+    let mut message = claude_json(model, text);
+    message["content"] = json!([]);
+    message["stop_reason"] = Value::Null;
+
+    [
+        sse_event(
+            "message_start",
+            &json!({ "type": "message_start", "message": message }),
+        ),
+        sse_event(
+            "content_block_start",
+            &json!({
+                "type": "content_block_start",
+                "index": 0,
+                "content_block": { "type": "text", "text": "" }
+            }),
+        ),
+        sse_event(
+            "content_block_delta",
+            &json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": { "type": "text_delta", "text": text }
+            }),
+        ),
+        sse_event(
+            "content_block_stop",
+            &json!({ "type": "content_block_stop", "index": 0 }),
+        ),
+        sse_event(
+            "message_delta",
+            &json!({
+                "type": "message_delta",
+                "delta": { "stop_reason": "end_turn", "stop_sequence": null },
+                "usage": { "output_tokens": 0 }
+            }),
+        ),
+        sse_event("message_stop", &json!({ "type": "message_stop" })),
+    ]
+    .concat()
+}
+
+fn codex_json(model: &str, text: &str) -> Value {
+    json!({
+        "id": "resp_duckcoding_fallback",
+        "object": "response",
+        "status": "completed",
+        "model": model,
+        "output": [{
+            "type": "message",
+            "id": "msg_duckcoding_fallback",
+            "role": "assistant",
+            "status": "completed",
+            "content": [{ "type": "output_text", "text": text, "annotations": [] }]
+        }],
+        "usage": { "input_tokens": 0, "output_tokens": 0, "total_tokens": 0 }
+    })
+}
+
+fn codex_sse(model: &str, text: &str) -> String {
+    let response = codex_json(model, text);
+    let mut created = response.clone();
+    created["status"] = json!("in_progress");
+    created["output"] = json!([]);
+
+    [
+        sse_event(
+            "response.created",
+            &json!({ "type": "response.created", "response": created }),
+        ),
+        sse_event(
+            "response.output_text.delta",
+            &json!({
+                "type": "response.output_text.delta",
+                "item_id": "msg_duckcoding_fallback",
+                "output_index": 0,
+                "content_index": 0,
+                "delta": text
+            }),
+        ),
+        sse_event(
+            "response.completed",
+            &json!({ "type": "response.completed", "response": response }),
+        ),
+    ]
+    .concat()
+}
+
+fn gemini_json(text: &str) -> Value {
+    json!({
+        "candidates": [{
+            "content": { "role": "model", "parts": [{ "text": text }] },
+            "finishReason": "STOP",
+            "index": 0
+        }],
+        "usageMetadata": { "promptTokenCount": 0, "candidatesTokenCount": 0, "totalTokenCount": 0 }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    async fn read_body(response: Response<BoxBody>) -> String {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_claude_json_fallback() {
+        let response = build(
+            "claude-code",
+            Some("服务维护中"),
+            br#"{"model":"claude-sonnet-4-5","stream":false}"#,
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[FALLBACK_HEADER], "true");
+        assert_eq!(response.headers()["content-type"], "application/json");
+
+        let json: Value = serde_json::from_str(&read_body(response).await).unwrap();
+        assert_eq!(json["model"], "claude-sonnet-4-5");
+        assert_eq!(json["content"][0]["text"], "服务维护中");
+        assert_eq!(json["stop_reason"], "end_turn");
+    }
+
+    #[tokio::test]
+    async fn test_claude_sse_fallback() {
+        let response = build("claude-code", None, br#"{"model":"m","stream":true}"#);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        let body = read_body(response).await;
+        assert!(body.starts_with("event: message_start\n"));
+        assert!(body.contains(DEFAULT_FALLBACK_MESSAGE));
+        assert!(body
+            .trim_end()
+            .ends_with(r#"data: {"type":"message_stop"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_codex_fallback() {
+        let body = read_body(build("codex", Some("稍后再试"), br#"{"model":"gpt-5"}"#)).await;
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["object"], "response");
+        assert_eq!(json["output"][0]["content"][0]["text"], "稍后再试");
+
+        let sse = read_body(build("codex", None, br#"{"stream":true}"#)).await;
+        assert!(sse.contains("event: response.completed"));
+    }
+
+    #[tokio::test]
+    async fn test_gemini_fallback_and_blank_message() {
+        let body = read_body(build("gemini-cli", Some("  "), b"not json")).await;
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            json["candidates"][0]["content"]["parts"][0]["text"],
+            DEFAULT_FALLBACK_MESSAGE
+        );
+    }
+}
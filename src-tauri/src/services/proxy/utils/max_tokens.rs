@@ -0,0 +1,51 @@
+//! 请求 max_tokens 默认值注入
+//!
+//! 部分客户端不携带 max_tokens，上游会使用可能过大的默认值甚至直接报错。
+//! 仅处理带 `messages` 的 JSON 请求体（Messages / Chat Completions），已有值保持不变
+
+use bytes::Bytes;
+use serde_json::Value;
+
+/// 请求体缺少 max_tokens 时注入默认值，返回新的请求体；无需修改时返回 None
+pub fn inject_default_max_tokens(body: &[u8], default_max_tokens: u32) -> Option<Bytes> {
+    let mut json: Value = serde_json::from_slice(body).ok()?;
+    let obj = json.as_object_mut()?;
+
+    if !obj.contains_key("messages") || obj.contains_key("max_tokens") {
+        return None;
+    }
+
+    obj.insert("max_tokens".to_string(), Value::from(default_max_tokens));
+    serde_json::to_vec(&json).ok().map(Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_injects_when_missing() {
+        let body = br#"{"model":"claude-sonnet-4","messages":[{"role":"user","content":"hi"}]}"#;
+
+        let injected = inject_default_max_tokens(body, 8192).expect("应注入默认值");
+        let json: Value = serde_json::from_slice(&injected).unwrap();
+
+        assert_eq!(json["max_tokens"], 8192);
+        assert_eq!(json["model"], "claude-sonnet-4");
+    }
+
+    #[test]
+    fn test_keeps_existing_and_skips_other_bodies() {
+        // 已有值不改
+        let body = br#"{"model":"m","max_tokens":1024,"messages":[]}"#;
+        assert!(inject_default_max_tokens(body, 8192).is_none());
+
+        // 非 messages 请求（如 Responses API / Gemini）不处理
+        assert!(inject_default_max_tokens(br#"{"model":"m","input":"hi"}"#, 8192).is_none());
+        assert!(inject_default_max_tokens(br#"{"contents":[]}"#, 8192).is_none());
+
+        // 非 JSON 或空请求体不处理
+        assert!(inject_default_max_tokens(b"", 8192).is_none());
+        assert!(inject_default_max_tokens(b"not json", 8192).is_none());
+    }
+}
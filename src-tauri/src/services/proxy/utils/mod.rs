@@ -2,9 +2,29 @@
 //!
 //! 包含通用的工具函数和类型定义
 
+pub mod alert_aggregator;
 pub mod body;
+pub mod body_limit;
+pub mod content_filter;
 pub mod error_responses;
+pub mod failover;
+pub mod fallback_response;
 pub mod loop_detector;
+pub mod max_tokens;
+pub mod model_downgrade;
+pub mod model_quota;
+pub mod openai_compat;
+pub mod priority_limiter;
+pub mod request_compression;
+pub mod request_history;
+pub mod retry;
+pub mod slow_capture;
+pub mod startup_probe;
+pub mod status_mapping;
+pub mod stream_tap;
+pub mod upstream_check;
+pub mod upstream_client;
+pub mod upstream_probe;
 
 // 重新导出常用类型
 pub use body::{box_body, BoxBody};
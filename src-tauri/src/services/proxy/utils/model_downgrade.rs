@@ -0,0 +1,295 @@
+//! 上游过载时的模型降级重试
+//!
+//! 代理配置开启 `model_downgrade_enabled` 后，上游对请求模型返回过载错误
+//! （HTTP 529 / `overloaded_error`）时，按 `model_downgrades` 映射改写请求体中的 model
+//! 并向同一上游重试一次（如 opus -> sonnet）。仅支持请求体携带 model 字段的请求，
+//! 日志按降级后的模型计费并记录原始模型
+
+use bytes::Bytes;
+use reqwest::{Client, Method, Response};
+use std::collections::HashMap;
+
+use super::retry::{self, RetryPolicy};
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::proxy::headers::ProcessedRequest;
+
+/// Anthropic 过载错误（overloaded_error）使用的状态码
+pub const OVERLOADED_STATUS: u16 = 529;
+
+/// 一次生效的模型降级
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Downgrade {
+    /// 原始模型
+    pub from: String,
+    /// 降级后的模型
+    pub to: String,
+}
+
+/// 上游响应是否为过载错误
+pub fn is_overloaded(result: &reqwest::Result<Response>) -> bool {
+    matches!(result, Ok(res) if res.status().as_u16() == OVERLOADED_STATUS)
+}
+
+/// 查找模型的降级目标
+///
+/// 优先精确匹配；否则取模型名包含的最长映射键（如键 `opus` 匹配 `claude-opus-4-1`）。
+/// 降级目标与原模型相同时视为未配置
+pub fn downgrade_target<'a>(
+    model: &str,
+    downgrades: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    let target = downgrades.get(model).or_else(|| {
+        downgrades
+            .iter()
+            .filter(|(key, _)| !key.is_empty() && model.contains(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, target)| target)
+    })?;
+    (!target.is_empty() && target != model).then_some(target.as_str())
+}
+
+/// 读取请求体中的 model 字段
+pub fn request_model(body: &[u8]) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    json.get("model")?.as_str().map(str::to_string)
+}
+
+/// 将请求体中的 model 改写为指定模型（请求体不是 JSON 或不含 model 时返回 None）
+pub fn rewrite_model(body: &[u8], model: &str) -> Option<Bytes> {
+    let mut json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let obj = json.as_object_mut()?;
+    if !obj.get("model")?.is_string() {
+        return None;
+    }
+    obj.insert("model".to_string(), serde_json::Value::from(model));
+    serde_json::to_vec(&json).ok().map(Bytes::from)
+}
+
+/// 上游过载时按配置降级模型并重试一次
+///
+/// `request` 为实际命中的上游请求。未开启降级、响应不是过载错误或模型没有降级映射时
+/// 原样返回 `result`；发生降级时返回重试结果与降级信息
+pub async fn retry_with_downgrade(
+    client: &Client,
+    method: &Method,
+    request: &ProcessedRequest,
+    result: reqwest::Result<Response>,
+    config: &ToolProxyConfig,
+    policy: RetryPolicy,
+    tool_id: &str,
+) -> (reqwest::Result<Response>, Option<Downgrade>) {
+    if !config.model_downgrade_enabled || !is_overloaded(&result) {
+        return (result, None);
+    }
+    let Some(from) = request_model(&request.body) else {
+        return (result, None);
+    };
+    let Some(to) = downgrade_target(&from, &config.model_downgrades) else {
+        return (result, None);
+    };
+    let Some(body) = rewrite_model(&request.body, to) else {
+        return (result, None);
+    };
+
+    tracing::warn!(
+        tool_id = tool_id,
+        from = %from,
+        to = %to,
+        "上游过载，降级模型后重试"
+    );
+
+    // 请求体长度已变化，由 reqwest 重新计算 content-length
+    let mut headers = request.headers.clone();
+    headers.remove("content-length");
+    let result = retry::send_with_retry(
+        client,
+        method,
+        &request.target_url,
+        &headers,
+        &body,
+        policy,
+        tool_id,
+    )
+    .await;
+
+    (
+        result,
+        Some(Downgrade {
+            from,
+            to: to.to_string(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockUpstream};
+    use reqwest::header::HeaderMap;
+
+    fn no_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            backoff_ms: 1,
+            retry_on_status: true,
+            timeout: None,
+        }
+    }
+
+    fn downgrades() -> HashMap<String, String> {
+        HashMap::from([
+            ("opus".to_string(), "claude-sonnet-4-5".to_string()),
+            ("claude-opus-4-1".to_string(), "claude-sonnet-4".to_string()),
+            ("gpt-5".to_string(), "gpt-5".to_string()),
+        ])
+    }
+
+    /// 启动 mock 上游：请求体包含 `overloaded_model` 时返回 529，否则返回 200
+    async fn spawn_mock_upstream(overloaded_model: &'static str) -> MockUpstream {
+        MockUpstream::start(move |_, request| {
+            if request.body_text().contains(overloaded_model) {
+                MockResponse::json(
+                    "529 Overloaded",
+                    r#"{"type":"error","error":{"type":"overloaded_error"}}"#,
+                )
+            } else {
+                MockResponse::json("200 OK", "{}")
+            }
+        })
+        .await
+    }
+
+    fn request_for(url: &str, model: &str) -> ProcessedRequest {
+        let body = format!(r#"{{"model":"{model}","max_tokens":16,"messages":[]}}"#);
+        let mut headers = HeaderMap::new();
+        headers.insert("content-length", body.len().to_string().parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+        ProcessedRequest {
+            target_url: format!("{url}/v1/messages"),
+            headers,
+            body: Bytes::from(body),
+            session_profile: None,
+        }
+    }
+
+    fn config(enabled: bool) -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(8787);
+        config.model_downgrade_enabled = enabled;
+        config.model_downgrades = downgrades();
+        config
+    }
+
+    #[test]
+    fn test_downgrade_target() {
+        let map = downgrades();
+        // 精确匹配优先
+        assert_eq!(
+            downgrade_target("claude-opus-4-1", &map),
+            Some("claude-sonnet-4")
+        );
+        // 按包含的映射键匹配
+        assert_eq!(
+            downgrade_target("claude-opus-4-5-20251101", &map),
+            Some("claude-sonnet-4-5")
+        );
+        assert_eq!(downgrade_target("claude-sonnet-4-5", &map), None);
+        // 目标与原模型相同视为未配置
+        assert_eq!(downgrade_target("gpt-5", &map), None);
+    }
+
+    #[test]
+    fn test_rewrite_model() {
+        let body = br#"{"model":"claude-opus-4-1","stream":true}"#;
+        let rewritten = rewrite_model(body, "claude-sonnet-4-5").unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&rewritten).unwrap();
+        assert_eq!(json["model"], "claude-sonnet-4-5");
+        assert_eq!(json["stream"], true);
+
+        assert!(rewrite_model(br#"{"contents":[]}"#, "x").is_none());
+        assert!(rewrite_model(b"not json", "x").is_none());
+        assert_eq!(request_model(body).as_deref(), Some("claude-opus-4-1"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_downgrade_on_overloaded() {
+        let upstream = spawn_mock_upstream("claude-opus").await;
+        let url = upstream.url();
+        let client = Client::new();
+        let request = request_for(&url, "claude-opus-4-5");
+
+        let first = retry::send_with_retry(
+            &client,
+            &Method::POST,
+            &request.target_url,
+            &request.headers,
+            &request.body,
+            no_retry(),
+            "claude-code",
+        )
+        .await;
+        assert!(is_overloaded(&first));
+
+        let (result, downgrade) = retry_with_downgrade(
+            &client,
+            &Method::POST,
+            &request,
+            first,
+            &config(true),
+            no_retry(),
+            "claude-code",
+        )
+        .await;
+
+        assert_eq!(result.unwrap().status().as_u16(), 200);
+        assert_eq!(
+            downgrade,
+            Some(Downgrade {
+                from: "claude-opus-4-5".to_string(),
+                to: "claude-sonnet-4-5".to_string(),
+            })
+        );
+        let requests = upstream.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1]
+            .body_text()
+            .contains(r#""model":"claude-sonnet-4-5""#));
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_downgrade_disabled_or_unmapped() {
+        let upstream = spawn_mock_upstream("claude").await;
+        let url = upstream.url();
+        let client = Client::new();
+
+        // 默认关闭：过载响应原样返回，不重试
+        for (config, model) in [
+            (config(false), "claude-opus-4-5"),
+            (config(true), "claude-haiku-4-5"),
+        ] {
+            let request = request_for(&url, model);
+            let first = retry::send_with_retry(
+                &client,
+                &Method::POST,
+                &request.target_url,
+                &request.headers,
+                &request.body,
+                no_retry(),
+                "claude-code",
+            )
+            .await;
+            let (result, downgrade) = retry_with_downgrade(
+                &client,
+                &Method::POST,
+                &request,
+                first,
+                &config,
+                no_retry(),
+                "claude-code",
+            )
+            .await;
+            assert_eq!(result.unwrap().status().as_u16(), OVERLOADED_STATUS);
+            assert!(downgrade.is_none());
+        }
+        assert_eq!(upstream.hits(), 2);
+    }
+}
@@ -0,0 +1,234 @@
+//! 按模型的每日调用次数配额
+//!
+//! 代理配置 `model_daily_limits` 为某个模型设置每日调用上限，超限后拒绝该模型的请求，
+//! 其他模型不受影响。计数按本地日期跨天清零，仅保存在内存中（重启代理应用后重新计数）
+//!
+//! 请求转发前先预占名额（避免并发请求超出上限），上游成功响应后确认；
+//! 上游失败或返回错误状态码时退还，不占用当日次数
+
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 全局配额计数器（按工具 + 模型计数）
+static MODEL_QUOTA: Lazy<ModelQuota> = Lazy::new(ModelQuota::new);
+
+struct QuotaState {
+    day: Option<NaiveDate>,
+    counts: HashMap<(String, String), u32>,
+}
+
+/// 按模型的每日调用计数器
+pub struct ModelQuota {
+    state: Mutex<QuotaState>,
+}
+
+impl ModelQuota {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(QuotaState {
+                day: None,
+                counts: HashMap::new(),
+            }),
+        }
+    }
+
+    /// 尝试占用一次调用名额（未达到上限时计数并返回 true）
+    pub fn try_consume(&self, tool_id: &str, model: &str, limit: u32, today: NaiveDate) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.day != Some(today) {
+            state.day = Some(today);
+            state.counts.clear();
+        }
+
+        let count = state
+            .counts
+            .entry((tool_id.to_string(), model.to_string()))
+            .or_insert(0);
+        if *count >= limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    /// 退还一次调用名额（已跨天时计数已清零，不再退还）
+    pub fn refund(&self, tool_id: &str, model: &str, day: NaiveDate) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.day != Some(day) {
+            return;
+        }
+        if let Some(count) = state
+            .counts
+            .get_mut(&(tool_id.to_string(), model.to_string()))
+        {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// 当日已调用次数
+    pub fn used(&self, tool_id: &str, model: &str, today: NaiveDate) -> u32 {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.day != Some(today) {
+            return 0;
+        }
+        state
+            .counts
+            .get(&(tool_id.to_string(), model.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Default for ModelQuota {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 已预占的调用名额，未调用 [`QuotaReservation::commit`] 即释放时自动退还
+pub struct QuotaReservation {
+    tool_id: String,
+    model: String,
+    day: NaiveDate,
+    committed: bool,
+}
+
+impl QuotaReservation {
+    /// 确认计入配额（上游成功响应后调用）
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for QuotaReservation {
+    fn drop(&mut self) {
+        if !self.committed {
+            MODEL_QUOTA.refund(&self.tool_id, &self.model, self.day);
+        }
+    }
+}
+
+/// 检查并预占模型配额
+///
+/// 返回 `Err(limit)` 表示该模型当日已达上限；模型未配置上限时不计数，返回 `Ok(None)`
+pub fn reserve(
+    tool_id: &str,
+    model: &str,
+    limits: &HashMap<String, u32>,
+) -> Result<Option<QuotaReservation>, u32> {
+    let Some(&limit) = limits.get(model) else {
+        return Ok(None);
+    };
+    let today = chrono::Local::now().date_naive();
+    if !MODEL_QUOTA.try_consume(tool_id, model, limit, today) {
+        return Err(limit);
+    }
+    Ok(Some(QuotaReservation {
+        tool_id: tool_id.to_string(),
+        model: model.to_string(),
+        day: today,
+        committed: false,
+    }))
+}
+
+/// 从 URL 路径提取模型名（Gemini 标准 API：`/v1beta/models/{model}:generateContent`）
+pub fn model_from_path(path: &str) -> Option<String> {
+    let rest = path.split("/models/").nth(1)?;
+    let model = rest.split([':', '/']).next()?;
+    (!model.is_empty()).then(|| model.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_per_model_and_daily_reset() {
+        let quota = ModelQuota::new();
+        let day1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+
+        assert!(quota.try_consume("claude-code", "claude-opus-4", 2, day1));
+        assert!(quota.try_consume("claude-code", "claude-opus-4", 2, day1));
+        // 超限后拒绝该模型
+        assert!(!quota.try_consume("claude-code", "claude-opus-4", 2, day1));
+        assert_eq!(quota.used("claude-code", "claude-opus-4", day1), 2);
+
+        // 其他模型、其他工具的同名模型不受影响
+        assert!(quota.try_consume("claude-code", "claude-sonnet-4", 2, day1));
+        assert!(quota.try_consume("codex", "claude-opus-4", 2, day1));
+
+        // 跨天重置
+        assert_eq!(quota.used("claude-code", "claude-opus-4", day2), 0);
+        assert!(quota.try_consume("claude-code", "claude-opus-4", 2, day2));
+        assert_eq!(quota.used("claude-code", "claude-sonnet-4", day2), 0);
+
+        // 上限为 0 时直接禁用该模型
+        assert!(!quota.try_consume("claude-code", "blocked", 0, day2));
+    }
+
+    #[test]
+    fn test_refund_restores_quota_same_day_only() {
+        let quota = ModelQuota::new();
+        let day1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+
+        assert!(quota.try_consume("claude-code", "claude-opus-4", 1, day1));
+        quota.refund("claude-code", "claude-opus-4", day1);
+        assert_eq!(quota.used("claude-code", "claude-opus-4", day1), 0);
+        assert!(quota.try_consume("claude-code", "claude-opus-4", 1, day1));
+
+        // 跨天后退还前一天的名额不影响当日计数
+        assert!(quota.try_consume("claude-code", "claude-opus-4", 1, day2));
+        quota.refund("claude-code", "claude-opus-4", day1);
+        assert_eq!(quota.used("claude-code", "claude-opus-4", day2), 1);
+
+        // 未计数的模型退还不会出现负数
+        quota.refund("claude-code", "unused", day2);
+        assert_eq!(quota.used("claude-code", "unused", day2), 0);
+    }
+
+    #[test]
+    fn test_reserve_commit_and_refund_on_drop() {
+        let limits = HashMap::from([("quota-test-model".to_string(), 1)]);
+
+        // 未确认的名额释放时退还
+        let reservation = reserve("quota-test", "quota-test-model", &limits).unwrap();
+        assert!(reservation.is_some());
+        assert!(matches!(
+            reserve("quota-test", "quota-test-model", &limits),
+            Err(1)
+        ));
+        drop(reservation);
+
+        // 确认后计入配额
+        reserve("quota-test", "quota-test-model", &limits)
+            .unwrap()
+            .unwrap()
+            .commit();
+        assert!(matches!(
+            reserve("quota-test", "quota-test-model", &limits),
+            Err(1)
+        ));
+
+        // 未配置上限的模型不计数
+        for _ in 0..3 {
+            assert!(matches!(
+                reserve("quota-test", "other-model", &limits),
+                Ok(None)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_model_from_path() {
+        assert_eq!(
+            model_from_path("/v1beta/models/gemini-2.5-pro:streamGenerateContent"),
+            Some("gemini-2.5-pro".to_string())
+        );
+        assert_eq!(model_from_path("/v1/messages"), None);
+        assert_eq!(model_from_path("/v1beta/models/"), None);
+    }
+}
@@ -0,0 +1,727 @@
+//! Anthropic ↔ OpenAI 格式转换
+//!
+//! 上游为 OpenAI 兼容接口时，将 Claude Code 发出的 Messages 请求转换为
+//! Chat Completions 请求，并把响应（JSON 或 SSE）反向转换为 Messages 格式。
+//! 仅覆盖文本、图片、工具调用等常用字段，thinking 等 Anthropic 专有内容会被丢弃
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use serde_json::{json, Map, Value};
+
+/// Anthropic Messages 接口路径
+pub const ANTHROPIC_MESSAGES_PATH: &str = "/v1/messages";
+
+/// OpenAI Chat Completions 接口路径
+pub const OPENAI_CHAT_COMPLETIONS_PATH: &str = "/v1/chat/completions";
+
+/// 将目标 URL 中的 Messages 路径替换为 Chat Completions 路径（保留查询参数）
+pub fn rewrite_target_url(target_url: &str) -> String {
+    match target_url.rfind(ANTHROPIC_MESSAGES_PATH) {
+        Some(pos) => format!(
+            "{}{}{}",
+            &target_url[..pos],
+            OPENAI_CHAT_COMPLETIONS_PATH,
+            &target_url[pos + ANTHROPIC_MESSAGES_PATH.len()..]
+        ),
+        None => target_url.to_string(),
+    }
+}
+
+// ==================== 请求转换 ====================
+
+/// 将 Anthropic Messages 请求体转换为 OpenAI Chat Completions 请求体
+pub fn anthropic_to_openai_request(body: &[u8]) -> Result<Bytes> {
+    let request: Value = serde_json::from_slice(body).context("请求体不是合法的 JSON")?;
+    let obj = request.as_object().context("请求体不是 JSON 对象")?;
+
+    let mut out = Map::new();
+    if let Some(model) = obj.get("model") {
+        out.insert("model".to_string(), model.clone());
+    }
+
+    // system 提示转为首条 system 消息
+    let mut messages = Vec::new();
+    if let Some(system) = obj.get("system") {
+        let text = join_text(system);
+        if !text.is_empty() {
+            messages.push(json!({ "role": "system", "content": text }));
+        }
+    }
+    for message in obj
+        .get("messages")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        convert_message(message, &mut messages);
+    }
+    out.insert("messages".to_string(), Value::Array(messages));
+
+    for key in ["max_tokens", "temperature", "top_p"] {
+        if let Some(value) = obj.get(key) {
+            out.insert(key.to_string(), value.clone());
+        }
+    }
+    if let Some(stop) = obj.get("stop_sequences") {
+        out.insert("stop".to_string(), stop.clone());
+    }
+    if let Some(user_id) = obj.get("metadata").and_then(|m| m.get("user_id")) {
+        out.insert("user".to_string(), user_id.clone());
+    }
+
+    if let Some(tools) = obj.get("tools").and_then(|v| v.as_array()) {
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|tool| {
+                let mut function = json!({
+                    "name": tool["name"],
+                    "parameters": tool
+                        .get("input_schema")
+                        .cloned()
+                        .unwrap_or_else(|| json!({ "type": "object" })),
+                });
+                if let Some(description) = tool.get("description") {
+                    function["description"] = description.clone();
+                }
+                json!({ "type": "function", "function": function })
+            })
+            .collect();
+        if !tools.is_empty() {
+            out.insert("tools".to_string(), Value::Array(tools));
+        }
+    }
+    if let Some(choice) = obj.get("tool_choice").and_then(convert_tool_choice) {
+        out.insert("tool_choice".to_string(), choice);
+    }
+
+    if obj.get("stream").and_then(|v| v.as_bool()).unwrap_or(false) {
+        out.insert("stream".to_string(), Value::Bool(true));
+        // 流式响应默认不带 usage，需显式请求以便统计 Token
+        out.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
+    }
+
+    Ok(Bytes::from(serde_json::to_vec(&Value::Object(out))?))
+}
+
+/// 转换单条消息（tool_result 块拆分为独立的 tool 消息）
+fn convert_message(message: &Value, out: &mut Vec<Value>) {
+    let role = message["role"].as_str().unwrap_or("user");
+    let blocks = match &message["content"] {
+        Value::String(text) => {
+            out.push(json!({ "role": role, "content": text }));
+            return;
+        }
+        Value::Array(blocks) => blocks,
+        _ => return,
+    };
+
+    if role == "assistant" {
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block["type"].as_str() {
+                Some("text") => text.push_str(block["text"].as_str().unwrap_or("")),
+                Some("tool_use") => tool_calls.push(json!({
+                    "id": block["id"],
+                    "type": "function",
+                    "function": {
+                        "name": block["name"],
+                        "arguments": block
+                            .get("input")
+                            .map(|input| input.to_string())
+                            .unwrap_or_else(|| "{}".to_string()),
+                    }
+                })),
+                _ => {}
+            }
+        }
+        let mut msg = json!({
+            "role": "assistant",
+            "content": if text.is_empty() { Value::Null } else { Value::String(text) },
+        });
+        if !tool_calls.is_empty() {
+            msg["tool_calls"] = Value::Array(tool_calls);
+        }
+        out.push(msg);
+        return;
+    }
+
+    let mut parts = Vec::new();
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("text") => parts.push(json!({ "type": "text", "text": block["text"] })),
+            Some("image") => {
+                let source = &block["source"];
+                let url = match source["type"].as_str() {
+                    Some("base64") => format!(
+                        "data:{};base64,{}",
+                        source["media_type"].as_str().unwrap_or("image/png"),
+                        source["data"].as_str().unwrap_or("")
+                    ),
+                    _ => source["url"].as_str().unwrap_or("").to_string(),
+                };
+                parts.push(json!({ "type": "image_url", "image_url": { "url": url } }));
+            }
+            Some("tool_result") => out.push(json!({
+                "role": "tool",
+                "tool_call_id": block["tool_use_id"],
+                "content": join_text(&block["content"]),
+            })),
+            _ => {}
+        }
+    }
+    if !parts.is_empty() {
+        out.push(json!({ "role": role, "content": parts }));
+    }
+}
+
+/// 转换 tool_choice（auto / any / tool / none）
+fn convert_tool_choice(choice: &Value) -> Option<Value> {
+    match choice["type"].as_str()? {
+        "auto" => Some(json!("auto")),
+        "any" => Some(json!("required")),
+        "none" => Some(json!("none")),
+        "tool" => Some(json!({ "type": "function", "function": { "name": choice["name"] } })),
+        _ => None,
+    }
+}
+
+/// 拼接字符串或文本块数组中的文本
+fn join_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+// ==================== 响应转换 ====================
+
+/// 将 OpenAI finish_reason 映射为 Anthropic stop_reason
+fn map_finish_reason(reason: Option<&str>) -> &'static str {
+    match reason {
+        Some("length") => "max_tokens",
+        Some("tool_calls" | "function_call") => "tool_use",
+        _ => "end_turn",
+    }
+}
+
+/// 将 OpenAI usage 映射为 Anthropic usage（input_tokens 不含缓存命中部分）
+fn map_usage(usage: &Value) -> Value {
+    let prompt = usage["prompt_tokens"].as_i64().unwrap_or(0);
+    let cached = usage["prompt_tokens_details"]["cached_tokens"]
+        .as_i64()
+        .unwrap_or(0);
+    json!({
+        "input_tokens": (prompt - cached).max(0),
+        "output_tokens": usage["completion_tokens"].as_i64().unwrap_or(0),
+        "cache_read_input_tokens": cached,
+    })
+}
+
+/// 将 OpenAI Chat Completions 非流式响应体（含错误响应）转换为 Anthropic Messages 格式
+pub fn openai_to_anthropic_response(body: &[u8]) -> Result<Bytes> {
+    let response: Value = serde_json::from_slice(body).context("响应体不是合法的 JSON")?;
+
+    if let Some(error) = response.get("error") {
+        let converted = json!({
+            "type": "error",
+            "error": {
+                "type": error["type"].as_str().unwrap_or("api_error"),
+                "message": error["message"].as_str().unwrap_or("upstream error"),
+            }
+        });
+        return Ok(Bytes::from(converted.to_string()));
+    }
+
+    let choice = &response["choices"][0];
+    let message = &choice["message"];
+
+    let mut content = Vec::new();
+    if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+        content.push(json!({ "type": "text", "text": text }));
+    }
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+        content.push(json!({
+            "type": "tool_use",
+            "id": call["id"],
+            "name": call["function"]["name"],
+            "input": serde_json::from_str::<Value>(arguments).unwrap_or_else(|_| json!({})),
+        }));
+    }
+
+    let converted = json!({
+        "id": response["id"],
+        "type": "message",
+        "role": "assistant",
+        "model": response["model"],
+        "content": content,
+        "stop_reason": map_finish_reason(choice["finish_reason"].as_str()),
+        "stop_sequence": null,
+        "usage": map_usage(&response["usage"]),
+    });
+    Ok(Bytes::from(converted.to_string()))
+}
+
+/// 当前打开的内容块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenBlock {
+    Text,
+    /// OpenAI tool_calls 中的 index
+    Tool(u64),
+}
+
+/// OpenAI SSE → Anthropic SSE 流式转换器
+///
+/// 按行缓冲上游数据（chunk 可能在任意位置切分），每个完整的 `data:` 行
+/// 转换为对应的 Anthropic 事件；收到 `[DONE]` 或流结束时补齐收尾事件
+#[derive(Debug, Default)]
+pub struct OpenAiStreamConverter {
+    buffer: Vec<u8>,
+    started: bool,
+    finished: bool,
+    /// 下一个内容块的 index
+    next_index: u64,
+    open_block: Option<OpenBlock>,
+    finish_reason: Option<String>,
+    usage: Option<Value>,
+}
+
+impl OpenAiStreamConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一段上游数据，返回转换后的 Anthropic SSE 数据（可能为空）
+    pub fn push(&mut self, chunk: &[u8]) -> Bytes {
+        self.buffer.extend_from_slice(chunk);
+        let mut out = String::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.handle_line(line.trim(), &mut out);
+        }
+        Bytes::from(out)
+    }
+
+    /// 上游流结束，输出剩余数据与收尾事件
+    pub fn finish(&mut self) -> Bytes {
+        let mut out = String::new();
+        let rest = std::mem::take(&mut self.buffer);
+        self.handle_line(String::from_utf8_lossy(&rest).trim(), &mut out);
+        self.close(&mut out);
+        Bytes::from(out)
+    }
+
+    fn handle_line(&mut self, line: &str, out: &mut String) {
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            return;
+        };
+        if data == "[DONE]" {
+            self.close(out);
+            return;
+        }
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            tracing::warn!("无法解析 OpenAI SSE 数据块");
+            return;
+        };
+        self.handle_chunk(&chunk, out);
+    }
+
+    fn handle_chunk(&mut self, chunk: &Value, out: &mut String) {
+        if self.finished {
+            return;
+        }
+        if !self.started {
+            self.started = true;
+            push_event(
+                out,
+                "message_start",
+                &json!({
+                    "type": "message_start",
+                    "message": {
+                        "id": chunk["id"],
+                        "type": "message",
+                        "role": "assistant",
+                        "model": chunk["model"],
+                        "content": [],
+                        "stop_reason": null,
+                        "stop_sequence": null,
+                        "usage": { "input_tokens": 0, "output_tokens": 0 },
+                    }
+                }),
+            );
+        }
+
+        if chunk["usage"].is_object() {
+            self.usage = Some(chunk["usage"].clone());
+        }
+
+        let Some(choice) = chunk["choices"].get(0) else {
+            return;
+        };
+        let delta = &choice["delta"];
+
+        if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+            if self.open_block != Some(OpenBlock::Text) {
+                self.open(OpenBlock::Text, json!({ "type": "text", "text": "" }), out);
+            }
+            push_event(
+                out,
+                "content_block_delta",
+                &json!({
+                    "type": "content_block_delta",
+                    "index": self.next_index - 1,
+                    "delta": { "type": "text_delta", "text": text },
+                }),
+            );
+        }
+
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let tool_index = call["index"].as_u64().unwrap_or(0);
+            if self.open_block != Some(OpenBlock::Tool(tool_index)) {
+                self.open(
+                    OpenBlock::Tool(tool_index),
+                    json!({
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": call["function"]["name"],
+                        "input": {},
+                    }),
+                    out,
+                );
+            }
+            if let Some(arguments) = call["function"]["arguments"]
+                .as_str()
+                .filter(|a| !a.is_empty())
+            {
+                push_event(
+                    out,
+                    "content_block_delta",
+                    &json!({
+                        "type": "content_block_delta",
+                        "index": self.next_index - 1,
+                        "delta": { "type": "input_json_delta", "partial_json": arguments },
+                    }),
+                );
+            }
+        }
+
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.finish_reason = Some(reason.to_string());
+        }
+    }
+
+    /// 关闭当前块并打开新块
+    fn open(&mut self, block: OpenBlock, content_block: Value, out: &mut String) {
+        self.close_block(out);
+        push_event(
+            out,
+            "content_block_start",
+            &json!({
+                "type": "content_block_start",
+                "index": self.next_index,
+                "content_block": content_block,
+            }),
+        );
+        self.open_block = Some(block);
+        self.next_index += 1;
+    }
+
+    fn close_block(&mut self, out: &mut String) {
+        if self.open_block.take().is_some() {
+            push_event(
+                out,
+                "content_block_stop",
+                &json!({ "type": "content_block_stop", "index": self.next_index - 1 }),
+            );
+        }
+    }
+
+    /// 输出 message_delta 与 message_stop（只输出一次；上游未产生任何数据时不输出）
+    fn close(&mut self, out: &mut String) {
+        if self.finished || !self.started {
+            return;
+        }
+        self.finished = true;
+        self.close_block(out);
+        let usage = self
+            .usage
+            .as_ref()
+            .map(map_usage)
+            .unwrap_or_else(|| json!({ "output_tokens": 0 }));
+        push_event(
+            out,
+            "message_delta",
+            &json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": map_finish_reason(self.finish_reason.as_deref()),
+                    "stop_sequence": null,
+                },
+                "usage": usage,
+            }),
+        );
+        push_event(out, "message_stop", &json!({ "type": "message_stop" }));
+    }
+}
+
+/// 包装上游 OpenAI SSE 字节流，输出 Anthropic SSE 字节流
+///
+/// 上游正常结束时追加收尾事件；上游出错时透传错误并结束
+pub fn convert_stream<S, E>(inner: S) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    futures_util::stream::unfold(
+        (Box::pin(inner), OpenAiStreamConverter::new(), false),
+        |(mut inner, mut converter, done)| async move {
+            if done {
+                return None;
+            }
+            match inner.next().await {
+                Some(Ok(bytes)) => {
+                    let converted = converter.push(&bytes);
+                    Some((Ok(converted), (inner, converter, false)))
+                }
+                Some(Err(e)) => Some((Err(e), (inner, converter, true))),
+                None => Some((Ok(converter.finish()), (inner, converter, true))),
+            }
+        },
+    )
+}
+
+fn push_event(out: &mut String, event: &str, data: &Value) {
+    out.push_str(&format!("event: {}\ndata: {}\n\n", event, data));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析 Anthropic SSE 输出为 (event, data) 列表
+    fn parse_events(sse: &[u8]) -> Vec<(String, Value)> {
+        String::from_utf8_lossy(sse)
+            .split("\n\n")
+            .filter(|e| !e.trim().is_empty())
+            .map(|e| {
+                let mut lines = e.lines();
+                let event = lines.next().unwrap().trim_start_matches("event: ");
+                let data = lines.next().unwrap().trim_start_matches("data: ");
+                (event.to_string(), serde_json::from_str(data).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rewrite_target_url() {
+        assert_eq!(
+            rewrite_target_url("https://api.example.com/v1/messages?beta=true"),
+            "https://api.example.com/v1/chat/completions?beta=true"
+        );
+        assert_eq!(
+            rewrite_target_url("https://api.example.com/other"),
+            "https://api.example.com/other"
+        );
+    }
+
+    #[test]
+    fn test_request_conversion() {
+        let body = json!({
+            "model": "gpt-4o",
+            "max_tokens": 1024,
+            "system": [{ "type": "text", "text": "You are helpful" }],
+            "stream": true,
+            "stop_sequences": ["END"],
+            "metadata": { "user_id": "user_1" },
+            "tools": [{
+                "name": "read_file",
+                "description": "Read a file",
+                "input_schema": { "type": "object", "properties": { "path": { "type": "string" } } }
+            }],
+            "tool_choice": { "type": "any" },
+            "messages": [
+                { "role": "user", "content": "hi" },
+                { "role": "assistant", "content": [
+                    { "type": "thinking", "thinking": "..." },
+                    { "type": "text", "text": "Let me look" },
+                    { "type": "tool_use", "id": "call_1", "name": "read_file", "input": { "path": "a.txt" } }
+                ]},
+                { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "call_1", "content": "file body" },
+                    { "type": "text", "text": "thanks" }
+                ]}
+            ]
+        });
+
+        let converted = anthropic_to_openai_request(body.to_string().as_bytes()).unwrap();
+        let json: Value = serde_json::from_slice(&converted).unwrap();
+
+        assert_eq!(json["model"], "gpt-4o");
+        assert_eq!(json["max_tokens"], 1024);
+        assert_eq!(json["stop"], json!(["END"]));
+        assert_eq!(json["user"], "user_1");
+        assert_eq!(json["stream_options"]["include_usage"], true);
+        assert_eq!(json["tool_choice"], "required");
+        assert_eq!(json["tools"][0]["function"]["name"], "read_file");
+        assert_eq!(json["tools"][0]["function"]["parameters"]["type"], "object");
+
+        let messages = json["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 5);
+        assert_eq!(
+            messages[0],
+            json!({ "role": "system", "content": "You are helpful" })
+        );
+        assert_eq!(messages[1], json!({ "role": "user", "content": "hi" }));
+        assert_eq!(messages[2]["content"], "Let me look");
+        assert_eq!(messages[2]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"]["arguments"],
+            r#"{"path":"a.txt"}"#
+        );
+        assert_eq!(
+            messages[3],
+            json!({ "role": "tool", "tool_call_id": "call_1", "content": "file body" })
+        );
+        assert_eq!(messages[4]["content"][0]["text"], "thanks");
+    }
+
+    #[test]
+    fn test_json_response_conversion() {
+        let body = json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": "Reading",
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": { "name": "read_file", "arguments": "{\"path\":\"a.txt\"}" }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": 20,
+                "prompt_tokens_details": { "cached_tokens": 40 }
+            }
+        });
+
+        let converted = openai_to_anthropic_response(body.to_string().as_bytes()).unwrap();
+        let json: Value = serde_json::from_slice(&converted).unwrap();
+
+        assert_eq!(json["type"], "message");
+        assert_eq!(json["id"], "chatcmpl-1");
+        assert_eq!(json["stop_reason"], "tool_use");
+        assert_eq!(
+            json["content"][0],
+            json!({ "type": "text", "text": "Reading" })
+        );
+        assert_eq!(json["content"][1]["type"], "tool_use");
+        assert_eq!(json["content"][1]["input"]["path"], "a.txt");
+        assert_eq!(json["usage"]["input_tokens"], 60);
+        assert_eq!(json["usage"]["output_tokens"], 20);
+        assert_eq!(json["usage"]["cache_read_input_tokens"], 40);
+
+        // 错误响应转换为 Anthropic 错误格式
+        let error = br#"{"error":{"message":"bad key","type":"invalid_request_error"}}"#;
+        let json: Value =
+            serde_json::from_slice(&openai_to_anthropic_response(error).unwrap()).unwrap();
+        assert_eq!(json["type"], "error");
+        assert_eq!(json["error"]["message"], "bad key");
+    }
+
+    #[test]
+    fn test_stream_conversion_with_split_chunks() {
+        let upstream = concat!(
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o\",\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o\",\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o\",\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"pa\"}}]}}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o\",\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"th\\\":1}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o\",\"choices\":[],\"usage\":{\"prompt_tokens\":10,\"completion_tokens\":5}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        // 按固定长度切分，模拟 chunk 在任意位置断开
+        let mut converter = OpenAiStreamConverter::new();
+        let mut output = Vec::new();
+        for piece in upstream.as_bytes().chunks(37) {
+            output.extend_from_slice(&converter.push(piece));
+        }
+        output.extend_from_slice(&converter.finish());
+
+        let events = parse_events(&output);
+        let names: Vec<&str> = events.iter().map(|(e, _)| e.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+
+        assert_eq!(events[0].1["message"]["id"], "chatcmpl-1");
+        assert_eq!(events[2].1["delta"]["text"], "Hel");
+        assert_eq!(events[5].1["index"], 1);
+        assert_eq!(events[5].1["content_block"]["name"], "read_file");
+        assert_eq!(events[7].1["delta"]["partial_json"], "th\":1}");
+        assert_eq!(events[9].1["delta"]["stop_reason"], "tool_use");
+        assert_eq!(events[9].1["usage"]["input_tokens"], 10);
+        assert_eq!(events[9].1["usage"]["output_tokens"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_convert_stream_appends_closing_events() {
+        let source = futures_util::stream::iter(vec![Ok::<_, String>(Bytes::from_static(
+            b"data: {\"id\":\"c\",\"model\":\"m\",\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\n\n",
+        ))]);
+
+        let chunks: Vec<_> = convert_stream(source).collect().await;
+        let output: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|c| c.unwrap().to_vec())
+            .collect();
+
+        let events = parse_events(&output);
+        assert_eq!(events.first().unwrap().0, "message_start");
+        assert_eq!(events.last().unwrap().0, "message_stop");
+    }
+
+    #[test]
+    fn test_stream_without_done_closes_on_finish() {
+        let mut converter = OpenAiStreamConverter::new();
+        let mut output = converter
+            .push(b"data: {\"id\":\"c\",\"model\":\"m\",\"choices\":[{\"delta\":{\"content\":\"hi\"},\"finish_reason\":\"length\"}]}\n\n")
+            .to_vec();
+        output.extend_from_slice(&converter.finish());
+        // 重复 finish 不会重复输出收尾事件
+        assert!(converter.finish().is_empty());
+
+        let events = parse_events(&output);
+        let last = &events[events.len() - 2];
+        assert_eq!(last.0, "message_delta");
+        assert_eq!(last.1["delta"]["stop_reason"], "max_tokens");
+        assert_eq!(events.last().unwrap().0, "message_stop");
+    }
+}
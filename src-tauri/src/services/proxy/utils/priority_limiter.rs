@@ -0,0 +1,201 @@
+//! 优先级并发限制器
+//!
+//! 代理并发受限时，按会话优先级调度等待中的请求：
+//! - 有空闲名额时直接放行
+//! - 名额耗尽时进入等待队列，优先级高者先获得名额，同优先级按到达顺序
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// 等待中的请求
+struct Waiter {
+    priority: u8,
+    seq: u64,
+    tx: oneshot::Sender<PriorityPermit>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 优先级高者在堆顶；同优先级时序号小（先到）者在堆顶
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+struct LimiterState {
+    available: usize,
+    next_seq: u64,
+    waiters: BinaryHeap<Waiter>,
+}
+
+/// 优先级并发限制器
+pub struct PriorityLimiter {
+    state: Mutex<LimiterState>,
+}
+
+impl PriorityLimiter {
+    /// 创建限制器（`capacity` 为最大并发数，至少为 1）
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(LimiterState {
+                available: capacity.max(1),
+                next_seq: 0,
+                waiters: BinaryHeap::new(),
+            }),
+        })
+    }
+
+    /// 按优先级获取并发名额（数值越大优先级越高）
+    pub async fn acquire(self: &Arc<Self>, priority: u8) -> PriorityPermit {
+        let rx = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.available > 0 {
+                state.available -= 1;
+                return PriorityPermit::new(self);
+            }
+
+            let (tx, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiters.push(Waiter { priority, seq, tx });
+            rx
+        };
+
+        // 名额由 release 直接移交；若当前 future 在移交后被取消，
+        // 通道中的 permit 随 receiver 一起 drop，名额会被再次释放
+        match rx.await {
+            Ok(permit) => permit,
+            // sender 仅在限制器释放名额时被消费，正常不会走到这里
+            Err(_) => PriorityPermit::new(self),
+        }
+    }
+
+    /// 当前排队中的请求数
+    pub fn waiting(&self) -> usize {
+        self.state
+            .lock()
+            .map(|s| s.waiters.len())
+            .unwrap_or_default()
+    }
+
+    /// 释放名额：移交给优先级最高的等待者，无人等待则归还
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while let Some(waiter) = state.waiters.pop() {
+            match waiter.tx.send(PriorityPermit::new(self)) {
+                Ok(()) => return,
+                // 等待者已取消（客户端断开）：撤销该 permit 并移交下一个
+                Err(mut permit) => {
+                    permit.limiter.take();
+                }
+            }
+        }
+        state.available += 1;
+    }
+}
+
+/// 并发名额（drop 时自动释放）
+pub struct PriorityPermit {
+    limiter: Option<Arc<PriorityLimiter>>,
+}
+
+impl PriorityPermit {
+    fn new(limiter: &Arc<PriorityLimiter>) -> Self {
+        Self {
+            limiter: Some(Arc::clone(limiter)),
+        }
+    }
+}
+
+impl Drop for PriorityPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_acquire_within_capacity() {
+        let limiter = PriorityLimiter::new(2);
+        let _a = limiter.acquire(0).await;
+        let _b = limiter.acquire(0).await;
+        assert_eq!(limiter.waiting(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_high_priority_served_first() {
+        let limiter = PriorityLimiter::new(1);
+        let held = limiter.acquire(0).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        // 依次入队：低、低、高
+        for (label, priority) in [("low-1", 0u8), ("low-2", 0u8), ("high", 10u8)] {
+            let limiter = Arc::clone(&limiter);
+            let order = Arc::clone(&order);
+            handles.push(tokio::spawn(async move {
+                let _permit = limiter.acquire(priority).await;
+                order.lock().unwrap().push(label);
+            }));
+            // 确保入队顺序确定
+            while limiter.waiting() < handles.len() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+
+        drop(held);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec!["high", "low-1", "low-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_does_not_leak_permit() {
+        let limiter = PriorityLimiter::new(1);
+        let held = limiter.acquire(0).await;
+
+        // 高优先级等待者在获得名额前被取消
+        let cancelled = {
+            let limiter = Arc::clone(&limiter);
+            tokio::spawn(async move {
+                let _permit = limiter.acquire(5).await;
+            })
+        };
+        while limiter.waiting() < 1 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(held);
+
+        // 名额应归还，新的请求可以立即获得
+        let permit = tokio::time::timeout(Duration::from_millis(100), limiter.acquire(0)).await;
+        assert!(permit.is_ok());
+    }
+}
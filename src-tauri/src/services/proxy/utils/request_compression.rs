@@ -0,0 +1,399 @@
+//! 上游请求体压缩
+//!
+//! 开启 `request_compression_enabled` 后，超过 `request_compression_min_bytes` 的请求体
+//! 以 gzip 压缩并设置 `Content-Encoding: gzip` 后发往上游。
+//!
+//! 仅在上游声明支持时启用：上游响应中的 `Accept-Encoding` 头（RFC 7694）表明其接受的
+//! 请求体编码，按上游 host 记录；未声明过的上游始终发送原始请求体。
+//! 压缩请求被拒绝（415 Unsupported Media Type）时记为不支持，并以原始请求体重发一次。
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH};
+use reqwest::{Client, Method, Response};
+
+use super::retry::{self, RetryPolicy};
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::proxy::headers::ProcessedRequest;
+use crate::services::proxy::log_recorder::upstream_host;
+
+/// 全局上游编码支持记录
+static UPSTREAM_ENCODINGS: Lazy<UpstreamEncodings> = Lazy::new(UpstreamEncodings::new);
+
+/// 请求体压缩策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RequestCompressionPolicy {
+    pub enabled: bool,
+    /// 触发压缩的最小请求体大小（字节）
+    pub min_bytes: usize,
+}
+
+impl RequestCompressionPolicy {
+    /// 从代理配置构建压缩策略
+    pub fn from_config(config: &ToolProxyConfig) -> Self {
+        Self {
+            enabled: config.request_compression_enabled,
+            min_bytes: config.request_compression_min_bytes,
+        }
+    }
+
+    /// 请求体是否满足压缩条件（不含上游是否支持的判断）
+    pub fn applies_to(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        self.enabled
+            && !body.is_empty()
+            && body.len() >= self.min_bytes
+            && !headers.contains_key(CONTENT_ENCODING)
+    }
+}
+
+/// 各上游是否接受 gzip 请求体（按 host[:port] 记录）
+pub struct UpstreamEncodings {
+    gzip_support: Mutex<HashMap<String, bool>>,
+}
+
+impl UpstreamEncodings {
+    pub fn new() -> Self {
+        Self {
+            gzip_support: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 上游是否已声明接受 gzip 请求体
+    pub fn supports_gzip(&self, url: &str) -> bool {
+        let Some(host) = upstream_host(url) else {
+            return false;
+        };
+        self.gzip_support
+            .lock()
+            .map(|support| support.get(&host).copied().unwrap_or(false))
+            .unwrap_or(false)
+    }
+
+    /// 根据上游响应更新支持状态
+    pub fn observe(&self, url: &str, status: u16, headers: &HeaderMap, compressed: bool) {
+        let supported = if compressed && status == 415 {
+            Some(false)
+        } else {
+            accepts_gzip(headers)
+        };
+        let (Some(supported), Some(host)) = (supported, upstream_host(url)) else {
+            return;
+        };
+        if let Ok(mut support) = self.gzip_support.lock() {
+            if support.insert(host.clone(), supported) != Some(supported) {
+                tracing::debug!(upstream = %host, supported, "更新上游 gzip 请求体支持状态");
+            }
+        }
+    }
+}
+
+impl Default for UpstreamEncodings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析响应中的 `Accept-Encoding`，判断是否接受 gzip 请求体
+///
+/// 返回 None 表示上游未声明；空值表示不接受任何编码
+pub fn accepts_gzip(headers: &HeaderMap) -> Option<bool> {
+    let value = headers.get(ACCEPT_ENCODING)?.to_str().ok()?;
+    let accepted = value.split(',').any(|item| {
+        let mut parts = item.split(';');
+        let coding = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let quality = parts
+            .find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                if !name.trim().eq_ignore_ascii_case("q") {
+                    return None;
+                }
+                value.trim().parse::<f32>().ok()
+            })
+            .unwrap_or(1.0);
+        matches!(coding.as_str(), "gzip" | "x-gzip" | "*") && quality > 0.0
+    });
+    Some(accepted)
+}
+
+/// gzip 压缩请求体
+pub fn gzip(body: &[u8]) -> std::io::Result<Bytes> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body)?;
+    Ok(Bytes::from(encoder.finish()?))
+}
+
+/// 发送上游请求（满足条件时压缩请求体），使用全局上游编码记录
+pub async fn send(
+    client: &Client,
+    method: &Method,
+    processed: &ProcessedRequest,
+    policy: RetryPolicy,
+    compression: RequestCompressionPolicy,
+    tool_id: &str,
+) -> reqwest::Result<Response> {
+    send_with_encodings(
+        client,
+        method,
+        processed,
+        policy,
+        compression,
+        &UPSTREAM_ENCODINGS,
+        tool_id,
+    )
+    .await
+}
+
+async fn send_with_encodings(
+    client: &Client,
+    method: &Method,
+    processed: &ProcessedRequest,
+    policy: RetryPolicy,
+    compression: RequestCompressionPolicy,
+    encodings: &UpstreamEncodings,
+    tool_id: &str,
+) -> reqwest::Result<Response> {
+    let url = processed.target_url.as_str();
+
+    if compression.applies_to(&processed.headers, &processed.body) && encodings.supports_gzip(url) {
+        match gzip(&processed.body) {
+            Ok(compressed) => {
+                let mut headers = processed.headers.clone();
+                headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                headers.remove(CONTENT_LENGTH);
+                tracing::debug!(
+                    tool_id = tool_id,
+                    original_bytes = processed.body.len(),
+                    compressed_bytes = compressed.len(),
+                    "上游请求体已 gzip 压缩"
+                );
+
+                let result = retry::send_with_retry(
+                    client,
+                    method,
+                    url,
+                    &headers,
+                    &compressed,
+                    policy,
+                    tool_id,
+                )
+                .await;
+                let rejected = result
+                    .as_ref()
+                    .is_ok_and(|res| res.status().as_u16() == 415);
+                if let Ok(res) = &result {
+                    encodings.observe(url, res.status().as_u16(), res.headers(), true);
+                }
+                if !rejected {
+                    return result;
+                }
+                tracing::warn!(
+                    tool_id = tool_id,
+                    "上游拒绝 gzip 请求体，改用原始请求体重发"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(tool_id = tool_id, error = %e, "请求体压缩失败，发送原始请求体");
+            }
+        }
+    }
+
+    let result = retry::send_with_retry(
+        client,
+        method,
+        url,
+        &processed.headers,
+        &processed.body,
+        policy,
+        tool_id,
+    )
+    .await;
+    if let Ok(res) = &result {
+        encodings.observe(url, res.status().as_u16(), res.headers(), false);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockUpstream};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn no_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            backoff_ms: 1,
+            retry_on_status: true,
+            timeout: None,
+        }
+    }
+
+    fn enabled(min_bytes: usize) -> RequestCompressionPolicy {
+        RequestCompressionPolicy {
+            enabled: true,
+            min_bytes,
+        }
+    }
+
+    fn headers_with(name: &'static str, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    fn processed_for(base: &str, body: &[u8]) -> ProcessedRequest {
+        ProcessedRequest {
+            target_url: format!("{base}/v1/messages"),
+            headers: HeaderMap::new(),
+            body: Bytes::copy_from_slice(body),
+            session_profile: None,
+        }
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert_eq!(accepts_gzip(&HeaderMap::new()), None);
+        assert_eq!(
+            accepts_gzip(&headers_with("accept-encoding", "gzip, br")),
+            Some(true)
+        );
+        assert_eq!(
+            accepts_gzip(&headers_with("accept-encoding", "br;q=1, GZIP;q=0.5")),
+            Some(true)
+        );
+        assert_eq!(
+            accepts_gzip(&headers_with("accept-encoding", "*")),
+            Some(true)
+        );
+        assert_eq!(
+            accepts_gzip(&headers_with("accept-encoding", "gzip;q=0")),
+            Some(false)
+        );
+        assert_eq!(
+            accepts_gzip(&headers_with("accept-encoding", "identity")),
+            Some(false)
+        );
+        assert_eq!(
+            accepts_gzip(&headers_with("accept-encoding", "")),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_policy_applies_to() {
+        let body = vec![b'a'; 100];
+        assert!(enabled(100).applies_to(&HeaderMap::new(), &body));
+        assert!(!enabled(101).applies_to(&HeaderMap::new(), &body));
+        assert!(!RequestCompressionPolicy::default().applies_to(&HeaderMap::new(), &body));
+        // 客户端已编码的请求体不再压缩
+        assert!(!enabled(0).applies_to(&headers_with("content-encoding", "br"), &body));
+        assert!(!enabled(0).applies_to(&HeaderMap::new(), &[]));
+    }
+
+    #[test]
+    fn test_upstream_encodings_observe() {
+        let encodings = UpstreamEncodings::new();
+        let url = "https://api.example.com/v1/messages";
+        assert!(!encodings.supports_gzip(url));
+
+        // 未声明 Accept-Encoding 的响应不改变状态
+        encodings.observe(url, 200, &HeaderMap::new(), false);
+        assert!(!encodings.supports_gzip(url));
+
+        encodings.observe(url, 200, &headers_with("accept-encoding", "gzip"), false);
+        assert!(encodings.supports_gzip(url));
+        // 按 host 记录，同一上游的其他路径共享状态
+        assert!(encodings.supports_gzip("https://api.example.com/v1/other"));
+        assert!(!encodings.supports_gzip("https://other.example.com/v1/messages"));
+
+        // 压缩请求被 415 拒绝后记为不支持
+        encodings.observe(url, 415, &HeaderMap::new(), true);
+        assert!(!encodings.supports_gzip(url));
+    }
+
+    #[tokio::test]
+    async fn test_compresses_only_after_upstream_declares_support() {
+        let upstream = MockUpstream::respond(MockResponse::raw(
+            "HTTP/1.1 200 OK\r\nAccept-Encoding: gzip\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        ))
+        .await;
+        let encodings = UpstreamEncodings::new();
+        let body = br#"{"model":"claude-sonnet-4-5","messages":[]}"#.repeat(50);
+        let processed = processed_for(&upstream.url(), &body);
+
+        for _ in 0..2 {
+            let res = send_with_encodings(
+                &Client::new(),
+                &Method::POST,
+                &processed,
+                no_retry(),
+                enabled(64),
+                &encodings,
+                "claude-code",
+            )
+            .await
+            .unwrap();
+            assert_eq!(res.status().as_u16(), 200);
+        }
+
+        let received = upstream.requests();
+        assert_eq!(received.len(), 2);
+        // 首次请求时上游尚未声明支持，发送原始请求体
+        assert_eq!(received[0].header("content-encoding"), None);
+        assert_eq!(received[0].body, body);
+        // 上游声明支持后压缩发送，解压后与原始请求体一致
+        assert_eq!(received[1].header("content-encoding"), Some("gzip"));
+        assert!(received[1].body.len() < body.len());
+        let mut decoded = Vec::new();
+        GzDecoder::new(received[1].body.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, body);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_plain_body_on_415() {
+        // gzip 请求返回 415，普通请求返回 200
+        let upstream = MockUpstream::start(|_, request| match request.header("content-encoding") {
+            Some("gzip") => MockResponse::status("415 Unsupported Media Type"),
+            _ => MockResponse::body("200 OK", "text/plain", "ok"),
+        })
+        .await;
+        let encodings = UpstreamEncodings::new();
+        let processed = processed_for(&upstream.url(), &[b'x'; 256]);
+        // 模拟上游曾声明支持 gzip
+        encodings.observe(
+            &processed.target_url,
+            200,
+            &headers_with("accept-encoding", "gzip"),
+            false,
+        );
+
+        let res = send_with_encodings(
+            &Client::new(),
+            &Method::POST,
+            &processed,
+            no_retry(),
+            enabled(64),
+            &encodings,
+            "claude-code",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(res.status().as_u16(), 200);
+        assert!(!encodings.supports_gzip(&processed.target_url));
+        let received = upstream.requests();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].header("content-encoding"), Some("gzip"));
+        assert_eq!(received[1].header("content-encoding"), None);
+        assert_eq!(received[1].body, vec![b'x'; 256]);
+    }
+}
@@ -0,0 +1,120 @@
+//! 代理访问记录
+//!
+//! 每个代理实例在内存中保留最近 `DEFAULT_CAPACITY` 条请求摘要（方法、路径、状态码、耗时、上游 URL），
+//! 便于排查中转站问题。只记录元数据，不保存请求体与响应体；容量满后丢弃最旧的记录
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// 默认保留的请求摘要条数
+pub const DEFAULT_CAPACITY: usize = 200;
+
+/// 单条请求摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequestSummary {
+    /// 请求时间（毫秒时间戳）
+    pub timestamp: i64,
+    pub method: String,
+    /// 请求路径（不含查询参数）
+    pub path: String,
+    /// 返回给客户端的状态码
+    pub status: u16,
+    /// 从收到请求到返回响应头的耗时（毫秒，流式响应不含后续传输时间）
+    pub duration_ms: u64,
+    /// 实际命中的上游 URL（不含查询参数；未转发到上游时为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_url: Option<String>,
+}
+
+/// 固定容量的请求摘要环形缓冲区（可跨连接任务共享）
+#[derive(Debug, Clone)]
+pub struct RequestHistory {
+    entries: Arc<Mutex<VecDeque<RequestSummary>>>,
+    capacity: usize,
+}
+
+impl Default for RequestHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl RequestHistory {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// 追加一条记录（已满时丢弃最旧的记录）
+    pub fn record(&self, summary: RequestSummary) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(summary);
+    }
+
+    /// 最近的 `limit` 条记录（最新的在前）
+    pub fn recent(&self, limit: usize) -> Vec<RequestSummary> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// 去掉 URL 中的查询参数（部分上游通过 `?key=` 传递 API Key）
+pub fn strip_query(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(timestamp: i64) -> RequestSummary {
+        RequestSummary {
+            timestamp,
+            method: "POST".to_string(),
+            path: "/v1/messages".to_string(),
+            status: 200,
+            duration_ms: 10,
+            upstream_url: Some("https://api.example.com/v1/messages".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_drops_oldest_when_full() {
+        let history = RequestHistory::new(3);
+        for ts in 1..=5 {
+            history.record(summary(ts));
+        }
+
+        let timestamps: Vec<i64> = history.recent(10).iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![5, 4, 3]);
+        assert_eq!(history.recent(2).len(), 2);
+        assert_eq!(history.recent(2)[0].timestamp, 5);
+    }
+
+    #[test]
+    fn test_shared_across_clones() {
+        let history = RequestHistory::default();
+        let cloned = history.clone();
+        cloned.record(summary(1));
+        assert_eq!(history.recent(DEFAULT_CAPACITY), vec![summary(1)]);
+    }
+
+    #[test]
+    fn test_strip_query() {
+        assert_eq!(
+            strip_query("https://example.com/v1/models/x:generate?key=secret"),
+            "https://example.com/v1/models/x:generate"
+        );
+        assert_eq!(
+            strip_query("https://example.com/v1"),
+            "https://example.com/v1"
+        );
+    }
+}
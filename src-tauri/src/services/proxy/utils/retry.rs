@@ -0,0 +1,286 @@
+//! 上游请求重试
+//!
+//! 对连接错误和 502/503/504 响应按指数退避重试。
+//! 请求体必须已完整读入内存（`Bytes`），每次重试重新构建请求。
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, Response};
+
+use crate::models::proxy_config::ToolProxyConfig;
+
+/// 单次退避的最大间隔，避免配置过大导致请求长时间挂起
+const MAX_BACKOFF_MS: u64 = 10_000;
+
+/// 重试策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最大重试次数（不含首次请求）
+    pub max_retries: u32,
+    /// 基础退避间隔（毫秒）
+    pub backoff_ms: u64,
+    /// 是否对 502/503/504 响应重试（SSE 请求已返回响应头时不重试）
+    pub retry_on_status: bool,
+    /// 单次请求总超时（含读取响应体；SSE 请求为 None，由客户端空闲超时约束）
+    pub timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// 从代理配置构建重试策略
+    pub fn from_config(config: &ToolProxyConfig, is_sse: bool) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            backoff_ms: config.retry_backoff_ms,
+            retry_on_status: !is_sse,
+            timeout: (!is_sse).then(|| Duration::from_secs(config.upstream_timeout_secs)),
+        }
+    }
+
+    /// 第 `attempt` 次重试（从 0 开始）前的等待时间
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor).min(MAX_BACKOFF_MS))
+    }
+}
+
+/// 是否为可重试的上游状态码
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 502..=504)
+}
+
+/// 是否为可重试的请求错误（仅连接阶段失败，此时上游尚未处理请求）
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect()
+}
+
+/// 发送上游请求，失败时按策略重试
+///
+/// 返回最后一次请求的结果：成功响应、不可重试的响应，或最后一次错误
+pub async fn send_with_retry(
+    client: &Client,
+    method: &Method,
+    url: &str,
+    headers: &HeaderMap,
+    body: &Bytes,
+    policy: RetryPolicy,
+    tool_id: &str,
+) -> reqwest::Result<Response> {
+    let mut attempt = 0u32;
+    loop {
+        let mut builder = client.request(method.clone(), url).headers(headers.clone());
+        if !body.is_empty() {
+            builder = builder.body(body.clone());
+        }
+        if let Some(timeout) = policy.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        let result = builder.send().await;
+        let can_retry = attempt < policy.max_retries;
+
+        match result {
+            Ok(res)
+                if can_retry
+                    && policy.retry_on_status
+                    && is_retryable_status(res.status().as_u16()) =>
+            {
+                let delay = policy.delay_for(attempt);
+                tracing::warn!(
+                    tool_id = tool_id,
+                    status = res.status().as_u16(),
+                    attempt = attempt + 1,
+                    max_retries = policy.max_retries,
+                    delay_ms = delay.as_millis() as u64,
+                    "上游返回可重试状态码，准备重试"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) if can_retry && is_retryable_error(&e) => {
+                let delay = policy.delay_for(attempt);
+                tracing::warn!(
+                    tool_id = tool_id,
+                    error = %e,
+                    attempt = attempt + 1,
+                    max_retries = policy.max_retries,
+                    delay_ms = delay.as_millis() as u64,
+                    "上游连接失败，准备重试"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            other => return other,
+        }
+
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{closed_url, MockResponse, MockUpstream};
+
+    fn policy(max_retries: u32, retry_on_status: bool) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            backoff_ms: 1,
+            retry_on_status,
+            timeout: None,
+        }
+    }
+
+    /// 启动 mock 上游：前 `failures` 次返回 503，之后返回 200
+    async fn spawn_mock_upstream(failures: usize) -> MockUpstream {
+        MockUpstream::start(move |seq, _| {
+            if seq < failures {
+                MockResponse::status("503 Service Unavailable")
+            } else {
+                MockResponse::body("200 OK", "text/plain", "ok")
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test_is_retryable_status() {
+        assert!(is_retryable_status(502));
+        assert!(is_retryable_status(503));
+        assert!(is_retryable_status(504));
+        assert!(!is_retryable_status(500));
+        assert!(!is_retryable_status(429));
+        assert!(!is_retryable_status(200));
+    }
+
+    #[test]
+    fn test_delay_for_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            backoff_ms: 300,
+            retry_on_status: true,
+            timeout: None,
+        };
+        assert_eq!(policy.delay_for(0), Duration::from_millis(300));
+        assert_eq!(policy.delay_for(1), Duration::from_millis(600));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(1200));
+        assert_eq!(policy.delay_for(63), Duration::from_millis(MAX_BACKOFF_MS));
+        assert_eq!(policy.delay_for(100), Duration::from_millis(MAX_BACKOFF_MS));
+    }
+
+    #[test]
+    fn test_retry_config_defaults() {
+        let config: ToolProxyConfig =
+            serde_json::from_str(r#"{"enabled": true, "port": 8787}"#).unwrap();
+        assert_eq!(config.max_retries, 2);
+        assert_eq!(config.retry_backoff_ms, 300);
+
+        let policy = RetryPolicy::from_config(&config, true);
+        assert!(!policy.retry_on_status);
+        assert_eq!(policy.timeout, None);
+
+        let policy = RetryPolicy::from_config(&config, false);
+        assert_eq!(policy.timeout, Some(Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let upstream = spawn_mock_upstream(2).await;
+        let res = send_with_retry(
+            &Client::new(),
+            &Method::POST,
+            &format!("{}/v1/messages", upstream.url()),
+            &HeaderMap::new(),
+            &Bytes::from_static(b"{\"model\":\"test\"}"),
+            policy(2, true),
+            "claude-code",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(res.status().as_u16(), 200);
+        assert_eq!(upstream.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let upstream = spawn_mock_upstream(10).await;
+        let res = send_with_retry(
+            &Client::new(),
+            &Method::POST,
+            &format!("{}/v1/messages", upstream.url()),
+            &HeaderMap::new(),
+            &Bytes::new(),
+            policy(1, true),
+            "claude-code",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(res.status().as_u16(), 503);
+        assert_eq!(upstream.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sse_does_not_retry_on_status() {
+        let upstream = spawn_mock_upstream(1).await;
+        let res = send_with_retry(
+            &Client::new(),
+            &Method::POST,
+            &format!("{}/v1/messages", upstream.url()),
+            &HeaderMap::new(),
+            &Bytes::from_static(b"{\"stream\":true}"),
+            policy(2, false),
+            "claude-code",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(res.status().as_u16(), 503);
+        assert_eq!(upstream.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_error_is_retried_then_returned() {
+        let err = send_with_retry(
+            &Client::new(),
+            &Method::GET,
+            &closed_url().await,
+            &HeaderMap::new(),
+            &Bytes::new(),
+            policy(2, false),
+            "codex",
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.is_connect());
+    }
+
+    #[tokio::test]
+    async fn test_slow_upstream_times_out_without_retry() {
+        // 慢上游：收到请求后迟迟不返回响应
+        let upstream =
+            MockUpstream::respond(MockResponse::status("200 OK").delayed(Duration::from_secs(5)))
+                .await;
+
+        let mut slow_policy = policy(2, true);
+        slow_policy.timeout = Some(Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        let err = send_with_retry(
+            &Client::new(),
+            &Method::POST,
+            &format!("{}/v1/messages", upstream.url()),
+            &HeaderMap::new(),
+            &Bytes::from_static(b"{\"model\":\"test\"}"),
+            slow_policy,
+            "claude-code",
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        // 上游可能已在处理请求，超时不重试
+        assert_eq!(upstream.hits(), 1);
+    }
+}
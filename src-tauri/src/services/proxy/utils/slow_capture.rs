@@ -0,0 +1,328 @@
+//! 慢请求自动采样
+//!
+//! 代理配置设置 `slow_request_threshold_ms` 后，耗时超过阈值的请求按 `slow_capture_sample_rate`
+//! 采样保存到 `~/.duckcoding/debug_captures/`（请求体、响应体开头、状态码与耗时），
+//! 无需全量捕获即可保留慢请求样本用于分析。目录内最多保留 `MAX_CAPTURE_FILES` 个样本
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::models::proxy_config::ToolProxyConfig;
+
+/// 样本目录名（位于配置目录下）
+pub const CAPTURE_DIR_NAME: &str = "debug_captures";
+
+/// 最多保留的样本文件数（超出时删除最旧的）
+pub const MAX_CAPTURE_FILES: usize = 100;
+
+/// 请求体 / 响应体各自保留的最大字节数
+pub const MAX_CAPTURED_BODY_BYTES: usize = 64 * 1024;
+
+/// 全局采样计数器（所有工具共享）
+static SAMPLER: Lazy<SlowSampler> = Lazy::new(SlowSampler::new);
+
+/// 慢请求采样策略（从代理配置提取，可在异步任务间复制）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlowCapturePolicy {
+    /// 慢请求阈值（毫秒，None 表示关闭）
+    pub threshold_ms: Option<u64>,
+    /// 采样率（0-1，1 表示全部慢请求都保存）
+    pub sample_rate: f64,
+}
+
+impl SlowCapturePolicy {
+    pub fn from_config(config: &ToolProxyConfig) -> Self {
+        Self {
+            threshold_ms: config.slow_request_threshold_ms,
+            sample_rate: config.slow_capture_sample_rate,
+        }
+    }
+
+    /// 请求耗时是否达到慢请求阈值
+    pub fn is_slow(&self, elapsed_ms: u64) -> bool {
+        self.threshold_ms
+            .is_some_and(|threshold| threshold > 0 && elapsed_ms >= threshold)
+    }
+}
+
+/// 慢请求采样器
+pub struct SlowSampler {
+    seen: AtomicU64,
+}
+
+impl SlowSampler {
+    pub fn new() -> Self {
+        Self {
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// 按采样率决定本次慢请求是否保存（确定性采样：采样率 0.1 时保存第 1、11、21… 个）
+    pub fn should_sample(&self, rate: f64) -> bool {
+        let rate = rate.clamp(0.0, 1.0);
+        if rate <= 0.0 {
+            return false;
+        }
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * rate).ceil() > (n * rate).ceil()
+    }
+}
+
+impl Default for SlowSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 慢请求样本
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowRequestCapture {
+    pub tool_id: String,
+    /// 采样时间（毫秒时间戳）
+    pub captured_at: i64,
+    /// 请求耗时（毫秒）
+    pub elapsed_ms: u64,
+    /// 响应状态码（0 表示上游失败）
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 请求体（超过上限时截断）
+    pub request_body: String,
+    /// 响应体开头（SSE 为收集到的事件流，超过上限时截断）
+    pub response_body: String,
+    /// 请求体或响应体是否被截断
+    pub truncated: bool,
+}
+
+impl SlowRequestCapture {
+    pub fn new(
+        tool_id: &str,
+        elapsed_ms: u64,
+        status: u16,
+        upstream: Option<&str>,
+        request_body: &[u8],
+        response_body: &[u8],
+    ) -> Self {
+        let model = serde_json::from_slice::<serde_json::Value>(request_body)
+            .ok()
+            .and_then(|json| json.get("model")?.as_str().map(str::to_string));
+        let (request_body, request_truncated) = truncated_text(request_body);
+        let (response_body, response_truncated) = truncated_text(response_body);
+        Self {
+            tool_id: tool_id.to_string(),
+            captured_at: chrono::Utc::now().timestamp_millis(),
+            elapsed_ms,
+            status,
+            upstream: upstream.map(str::to_string),
+            model,
+            request_body,
+            response_body,
+            truncated: request_truncated || response_truncated,
+        }
+    }
+}
+
+fn truncated_text(body: &[u8]) -> (String, bool) {
+    let truncated = body.len() > MAX_CAPTURED_BODY_BYTES;
+    let body = &body[..body.len().min(MAX_CAPTURED_BODY_BYTES)];
+    (String::from_utf8_lossy(body).into_owned(), truncated)
+}
+
+/// 请求结束后调用：慢请求按采样率保存样本（写入失败仅记录警告）
+pub fn maybe_capture(policy: SlowCapturePolicy, capture: SlowRequestCapture) {
+    if !policy.is_slow(capture.elapsed_ms) {
+        return;
+    }
+    let dir = match crate::utils::config_dir() {
+        Ok(dir) => dir.join(CAPTURE_DIR_NAME),
+        Err(e) => {
+            tracing::warn!(error = %e, "获取配置目录失败，跳过慢请求采样");
+            return;
+        }
+    };
+    match capture_if_sampled(&SAMPLER, policy, &dir, &capture) {
+        Ok(Some(path)) => tracing::info!(
+            tool_id = %capture.tool_id,
+            elapsed_ms = capture.elapsed_ms,
+            path = %path.display(),
+            "已保存慢请求样本"
+        ),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = ?e, "保存慢请求样本失败"),
+    }
+}
+
+/// 慢请求且命中采样时写入样本，返回样本文件路径
+fn capture_if_sampled(
+    sampler: &SlowSampler,
+    policy: SlowCapturePolicy,
+    dir: &Path,
+    capture: &SlowRequestCapture,
+) -> Result<Option<PathBuf>> {
+    if !policy.is_slow(capture.elapsed_ms) || !sampler.should_sample(policy.sample_rate) {
+        return Ok(None);
+    }
+    let path = write_capture(dir, capture)?;
+    prune_captures(dir, MAX_CAPTURE_FILES)?;
+    Ok(Some(path))
+}
+
+/// 写入样本文件（内容可能含提示词，Unix 下权限为 0600）
+fn write_capture(dir: &Path, capture: &SlowRequestCapture) -> Result<PathBuf> {
+    fs::create_dir_all(dir).context("创建慢请求样本目录失败")?;
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let path = dir.join(format!(
+        "slow_{}_{}_{}.json",
+        capture.tool_id,
+        capture.captured_at,
+        &id[..8]
+    ));
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).context("创建慢请求样本文件失败")?;
+    file.write_all(&serde_json::to_vec_pretty(capture)?)?;
+    Ok(path)
+}
+
+/// 仅保留最新的 `keep` 个样本（按修改时间排序）
+fn prune_captures(dir: &Path, keep: usize) -> Result<()> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("slow_") && name.ends_with(".json"))
+        })
+        .collect();
+    if files.len() <= keep {
+        return Ok(());
+    }
+    files.sort_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+    for path in &files[..files.len() - keep] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn policy(threshold_ms: Option<u64>, sample_rate: f64) -> SlowCapturePolicy {
+        SlowCapturePolicy {
+            threshold_ms,
+            sample_rate,
+        }
+    }
+
+    fn capture(elapsed_ms: u64) -> SlowRequestCapture {
+        SlowRequestCapture::new(
+            "claude-code",
+            elapsed_ms,
+            200,
+            Some("api.example.com"),
+            br#"{"model":"claude-sonnet-4-5","messages":[]}"#,
+            b"data: {}\n\n",
+        )
+    }
+
+    fn capture_files(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_should_sample_rate() {
+        let sampler = SlowSampler::new();
+        let sampled: Vec<bool> = (0..6).map(|_| sampler.should_sample(0.5)).collect();
+        assert_eq!(sampled, vec![true, false, true, false, true, false]);
+
+        let sampler = SlowSampler::new();
+        assert_eq!((0..20).filter(|_| sampler.should_sample(0.1)).count(), 2);
+
+        let sampler = SlowSampler::new();
+        assert!((0..5).all(|_| sampler.should_sample(1.0)));
+        assert!(!(0..5).any(|_| sampler.should_sample(0.0)));
+    }
+
+    #[test]
+    fn test_slow_requests_are_sampled() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(CAPTURE_DIR_NAME);
+        let sampler = SlowSampler::new();
+
+        // 未设置阈值（默认关闭）或未达到阈值时不采样
+        assert!(
+            capture_if_sampled(&sampler, policy(None, 1.0), &dir, &capture(60_000))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            capture_if_sampled(&sampler, policy(Some(5_000), 1.0), &dir, &capture(4_999))
+                .unwrap()
+                .is_none()
+        );
+        assert!(!dir.exists());
+
+        // 采样率 0.5：4 个慢请求保存 2 个
+        let saved = (0..4)
+            .filter_map(|_| {
+                capture_if_sampled(&sampler, policy(Some(5_000), 0.5), &dir, &capture(8_000))
+                    .unwrap()
+            })
+            .count();
+        assert_eq!(saved, 2);
+        let files = capture_files(&dir);
+        assert_eq!(files.len(), 2);
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(&files[0]).unwrap()).unwrap();
+        assert_eq!(json["tool_id"], "claude-code");
+        assert_eq!(json["elapsed_ms"], 8_000);
+        assert_eq!(json["model"], "claude-sonnet-4-5");
+        assert_eq!(json["upstream"], "api.example.com");
+        assert_eq!(json["truncated"], false);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&files[0]).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_capture_truncates_and_prunes() {
+        let body = vec![b'a'; MAX_CAPTURED_BODY_BYTES + 10];
+        let capture = SlowRequestCapture::new("codex", 10_000, 0, None, &body, b"");
+        assert!(capture.truncated);
+        assert_eq!(capture.request_body.len(), MAX_CAPTURED_BODY_BYTES);
+        assert!(capture.model.is_none());
+
+        let temp = TempDir::new().unwrap();
+        for _ in 0..5 {
+            write_capture(temp.path(), &capture).unwrap();
+        }
+        fs::write(temp.path().join("notes.txt"), "keep").unwrap();
+        prune_captures(temp.path(), 3).unwrap();
+        let files = capture_files(temp.path());
+        assert_eq!(files.len(), 4);
+        assert!(temp.path().join("notes.txt").exists());
+    }
+}
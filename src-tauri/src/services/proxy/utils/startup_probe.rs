@@ -0,0 +1,287 @@
+//! 代理启动后的上游可达性校验
+//!
+//! 代理启动成功后异步探测一次上游 base_url，不阻塞启动流程。结果通过广播通道发布，
+//! 由应用层转发为 `proxy-upstream-status` 事件，前端据此提示"代理已启动但上游不可达"。
+//! 自启动代理可能早于事件转发就绪，因此同时保留各工具最近一次结果供补发。
+//! 每次启动探测分配一个代次，代理停止（`clear`）后旧代次的探测结果直接丢弃
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use super::{upstream_client, upstream_probe};
+use crate::models::proxy_config::ToolProxyConfig;
+use upstream_probe::UpstreamHealth;
+
+/// 代理上游状态事件名称
+pub const PROXY_UPSTREAM_STATUS_EVENT: &str = "proxy-upstream-status";
+
+/// 广播通道容量
+const CHANNEL_CAPACITY: usize = 16;
+
+/// 全局状态发布中心
+static STATUS_HUB: Lazy<StatusHub> = Lazy::new(StatusHub::new);
+
+/// 代理启动后的上游状态（`proxy-upstream-status` 事件载荷）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProxyUpstreamStatus {
+    pub tool_id: String,
+    /// 代理监听端口
+    pub port: u16,
+    pub upstream: UpstreamHealth,
+    /// 面向用户的提示文案
+    pub message: String,
+}
+
+impl ProxyUpstreamStatus {
+    pub fn from_health(tool_id: &str, port: u16, upstream: UpstreamHealth) -> Self {
+        let message = if !upstream.reachable {
+            "代理已启动但上游不可达".to_string()
+        } else if !upstream.healthy {
+            match upstream.status_code {
+                Some(code) => format!("代理已启动但上游异常（HTTP {code}）"),
+                None => "代理已启动但上游异常".to_string(),
+            }
+        } else {
+            "代理已启动，上游连接正常".to_string()
+        };
+        Self {
+            tool_id: tool_id.to_string(),
+            port,
+            upstream,
+            message,
+        }
+    }
+}
+
+struct StatusHub {
+    sender: broadcast::Sender<ProxyUpstreamStatus>,
+    latest: Mutex<HashMap<String, ProxyUpstreamStatus>>,
+    /// 进行中的启动探测代次（工具 ID → 代次），停止代理时移除
+    generations: Mutex<HashMap<String, u64>>,
+    next_generation: AtomicU64,
+}
+
+impl StatusHub {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            latest: Mutex::new(HashMap::new()),
+            generations: Mutex::new(HashMap::new()),
+            next_generation: AtomicU64::new(1),
+        }
+    }
+}
+
+/// 订阅上游状态更新
+pub fn subscribe() -> broadcast::Receiver<ProxyUpstreamStatus> {
+    STATUS_HUB.sender.subscribe()
+}
+
+/// 各工具最近一次的上游状态
+pub fn latest_statuses() -> Vec<ProxyUpstreamStatus> {
+    STATUS_HUB
+        .latest
+        .lock()
+        .map(|latest| latest.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// 清除工具的上游状态（代理停止后调用），进行中的启动探测结果将被丢弃
+pub fn clear(tool_id: &str) {
+    let mut generations = STATUS_HUB
+        .generations
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    generations.remove(tool_id);
+    if let Ok(mut latest) = STATUS_HUB.latest.lock() {
+        latest.remove(tool_id);
+    }
+}
+
+/// 为工具登记新的启动探测代次
+fn begin(tool_id: &str) -> u64 {
+    let generation = STATUS_HUB.next_generation.fetch_add(1, Ordering::Relaxed);
+    STATUS_HUB
+        .generations
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(tool_id.to_string(), generation);
+    generation
+}
+
+/// 发布探测结果；代理已停止或已重新启动（代次不匹配）时丢弃
+fn publish(status: ProxyUpstreamStatus, generation: u64) -> bool {
+    // 持有代次锁直到写入 latest，避免与 clear 交错
+    let generations = STATUS_HUB
+        .generations
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if generations.get(&status.tool_id) != Some(&generation) {
+        tracing::debug!(tool_id = %status.tool_id, "代理已停止，丢弃过期的启动探测结果");
+        return false;
+    }
+    if let Ok(mut latest) = STATUS_HUB.latest.lock() {
+        latest.insert(status.tool_id.clone(), status.clone());
+    }
+    drop(generations);
+    // 无订阅者时发送失败，状态已保留在 latest 中
+    let _ = STATUS_HUB.sender.send(status);
+    true
+}
+
+/// 探测代理配置的上游（未配置 base_url 时返回 None）
+pub async fn check_upstream(
+    tool_id: &str,
+    config: &ToolProxyConfig,
+) -> Option<ProxyUpstreamStatus> {
+    let base_url = config
+        .real_base_url
+        .as_deref()
+        .filter(|url| !url.trim().is_empty())?;
+
+    let client = match upstream_client::upstream_client(tool_id, config) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!(tool_id = %tool_id, error = ?e, "创建上游客户端失败，跳过启动探测");
+            return None;
+        }
+    };
+    let health = upstream_probe::probe(&client, base_url).await;
+    Some(ProxyUpstreamStatus::from_health(
+        tool_id,
+        config.port,
+        health,
+    ))
+}
+
+/// 后台探测上游并发布结果
+pub fn spawn_startup_check(tool_id: &str, config: &ToolProxyConfig) {
+    let generation = begin(tool_id);
+    let tool_id = tool_id.to_string();
+    let config = config.clone();
+    tokio::spawn(async move {
+        let Some(status) = check_upstream(&tool_id, &config).await else {
+            return;
+        };
+        if status.upstream.healthy {
+            tracing::debug!(tool_id = %tool_id, "启动探测：上游连接正常");
+        } else {
+            tracing::warn!(
+                tool_id = %tool_id,
+                status_code = ?status.upstream.status_code,
+                error = ?status.upstream.error,
+                "{}",
+                status.message
+            );
+        }
+        publish(status, generation);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::closed_url;
+
+    fn health(reachable: bool, healthy: bool, status_code: Option<u16>) -> UpstreamHealth {
+        UpstreamHealth {
+            host: Some("api.example.com".to_string()),
+            reachable,
+            healthy,
+            status_code,
+            latency_ms: 10,
+            error: None,
+            checked_at: 0,
+            cached: false,
+        }
+    }
+
+    fn config_with_base_url(base_url: Option<&str>) -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(8787);
+        config.real_base_url = base_url.map(str::to_string);
+        config
+    }
+
+    #[test]
+    fn test_status_message() {
+        let status = ProxyUpstreamStatus::from_health("codex", 8788, health(false, false, None));
+        assert_eq!(status.message, "代理已启动但上游不可达");
+        assert_eq!(status.port, 8788);
+
+        let status =
+            ProxyUpstreamStatus::from_health("codex", 8788, health(true, false, Some(502)));
+        assert_eq!(status.message, "代理已启动但上游异常（HTTP 502）");
+
+        let status = ProxyUpstreamStatus::from_health("codex", 8788, health(true, true, Some(401)));
+        assert_eq!(status.message, "代理已启动，上游连接正常");
+
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["tool_id"], "codex");
+        assert_eq!(json["upstream"]["reachable"], true);
+    }
+
+    #[tokio::test]
+    async fn test_check_upstream_unreachable() {
+        // 未配置 base_url 时不探测
+        assert!(check_upstream("codex", &config_with_base_url(None))
+            .await
+            .is_none());
+
+        let closed_url = closed_url().await;
+        let status = check_upstream("codex", &config_with_base_url(Some(&closed_url)))
+            .await
+            .unwrap();
+        assert!(!status.upstream.reachable);
+        assert_eq!(status.message, "代理已启动但上游不可达");
+    }
+
+    #[tokio::test]
+    async fn test_spawn_startup_check_publishes_event() {
+        let tool_id = "startup-probe-test";
+        let mut receiver = subscribe();
+
+        let closed_url = closed_url().await;
+        spawn_startup_check(tool_id, &config_with_base_url(Some(&closed_url)));
+
+        let status = loop {
+            let status = tokio::time::timeout(std::time::Duration::from_secs(10), receiver.recv())
+                .await
+                .expect("等待探测事件超时")
+                .unwrap();
+            if status.tool_id == tool_id {
+                break status;
+            }
+        };
+        assert!(!status.upstream.reachable);
+        assert_eq!(status.message, "代理已启动但上游不可达");
+        assert!(latest_statuses().iter().any(|s| s.tool_id == tool_id));
+
+        clear(tool_id);
+        assert!(!latest_statuses().iter().any(|s| s.tool_id == tool_id));
+    }
+
+    #[test]
+    fn test_publish_discards_stale_generation() {
+        let tool_id = "startup-probe-stale";
+        let status = ProxyUpstreamStatus::from_health(tool_id, 8790, health(false, false, None));
+
+        // 探测完成前代理已停止
+        let generation = begin(tool_id);
+        clear(tool_id);
+        assert!(!publish(status.clone(), generation));
+        assert!(!latest_statuses().iter().any(|s| s.tool_id == tool_id));
+
+        // 代理重启后旧探测结果同样丢弃，仅发布最新代次
+        let stale = begin(tool_id);
+        let current = begin(tool_id);
+        assert!(!publish(status.clone(), stale));
+        assert!(publish(status, current));
+        assert!(latest_statuses().iter().any(|s| s.tool_id == tool_id));
+        clear(tool_id);
+    }
+}
@@ -0,0 +1,71 @@
+//! 上游响应状态码映射
+//!
+//! 部分上游使用非标准状态码（如用 430 表示限流），客户端无法识别并正确重试。
+//! 按配置将上游状态码改写为标准状态码后返回给客户端，日志中仍记录上游原始状态码
+
+use anyhow::{bail, Result};
+use hyper::StatusCode;
+use std::collections::HashMap;
+
+/// 校验映射配置（源与目标均需为合法 HTTP 状态码 100-599）
+pub fn validate(mappings: &HashMap<u16, u16>) -> Result<()> {
+    for (from, to) in mappings {
+        for code in [from, to] {
+            if !(100..=599).contains(code) {
+                bail!(
+                    "状态码映射 {} -> {} 无效：状态码需在 100-599 之间",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 按映射改写上游状态码，未配置或目标非法时保持原状态码
+pub fn map_status(mappings: &HashMap<u16, u16>, status: StatusCode) -> StatusCode {
+    mappings
+        .get(&status.as_u16())
+        .and_then(|code| StatusCode::from_u16(*code).ok())
+        .unwrap_or(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_status() {
+        let mappings = HashMap::from([(430, 429), (520, 502)]);
+
+        assert_eq!(
+            map_status(&mappings, StatusCode::from_u16(430).unwrap()),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            map_status(&mappings, StatusCode::from_u16(520).unwrap()),
+            StatusCode::BAD_GATEWAY
+        );
+        // 未配置的状态码保持不变
+        assert_eq!(map_status(&mappings, StatusCode::OK), StatusCode::OK);
+        assert_eq!(
+            map_status(&HashMap::new(), StatusCode::NOT_FOUND),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&HashMap::new()).is_ok());
+        assert!(validate(&HashMap::from([(430, 429)])).is_ok());
+        assert!(validate(&HashMap::from([(430, 99)])).is_err());
+        assert!(validate(&HashMap::from([(600, 429)])).is_err());
+    }
+
+    #[test]
+    fn test_mappings_deserialize_from_string_keys() {
+        let mappings: HashMap<u16, u16> = serde_json::from_str(r#"{"430": 429}"#).unwrap();
+        assert_eq!(mappings.get(&430), Some(&429));
+    }
+}
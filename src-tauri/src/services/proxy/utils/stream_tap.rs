@@ -0,0 +1,299 @@
+//! SSE 流监听
+//!
+//! 包装上游字节流，在转发的同时收集数据，并在流真正结束时（正常结束、
+//! 上游异常终止或客户端提前断开）通过 oneshot 通知日志任务
+//!
+//! 收集总量超过上限后不再追加中间数据，只保留开头与末尾窗口
+//! （开头含 message_start，末尾含 usage），转发本身不受影响
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use pin_project_lite::pin_project;
+use std::fmt::Display;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::oneshot;
+
+/// 超限后保留的末尾窗口大小
+const TAIL_WINDOW_BYTES: usize = 64 * 1024;
+
+/// SSE 事件分隔符
+const EVENT_SEPARATOR: &[u8] = b"\n\n";
+
+/// 流结束方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// 上游正常结束
+    Completed,
+    /// 上游异常终止（保留错误信息）
+    Errored(String),
+    /// 客户端提前断开，流在结束前被丢弃
+    Aborted,
+}
+
+/// 流结束时交给日志任务的结果
+#[derive(Debug)]
+pub struct StreamOutcome {
+    pub end: StreamEnd,
+    /// 已收到的数据（可能是部分数据；超限时为开头与末尾窗口拼接）
+    pub data: Vec<u8>,
+    /// 是否因超过收集上限而丢弃了中间数据
+    pub truncated: bool,
+    /// 实际流经的总字节数（不受收集上限影响）
+    pub total_bytes: usize,
+}
+
+/// 收集状态，丢弃时若尚未通知则按 `Aborted` 通知
+struct Collector {
+    data: Vec<u8>,
+    tail: Vec<u8>,
+    limit: usize,
+    truncated: bool,
+    total_bytes: usize,
+    tx: Option<oneshot::Sender<StreamOutcome>>,
+}
+
+impl Collector {
+    fn push(&mut self, chunk: &[u8]) {
+        self.total_bytes += chunk.len();
+        if !self.truncated && self.data.len() + chunk.len() <= self.limit {
+            self.data.extend_from_slice(chunk);
+            return;
+        }
+
+        self.truncated = true;
+        self.tail.extend_from_slice(chunk);
+        let window = TAIL_WINDOW_BYTES.min(self.limit);
+        if self.tail.len() > window {
+            let excess = self.tail.len() - window;
+            self.tail.drain(..excess);
+        }
+    }
+
+    fn finish(&mut self, end: StreamEnd) {
+        if let Some(tx) = self.tx.take() {
+            let data = if self.truncated {
+                join_truncated(&self.data, &self.tail)
+            } else {
+                std::mem::take(&mut self.data)
+            };
+            let _ = tx.send(StreamOutcome {
+                end,
+                data,
+                truncated: self.truncated,
+                total_bytes: self.total_bytes,
+            });
+        }
+    }
+}
+
+/// 拼接开头与末尾窗口，两端都按事件边界裁剪，避免产生半截事件
+fn join_truncated(head: &[u8], tail: &[u8]) -> Vec<u8> {
+    let find = |haystack: &[u8]| {
+        haystack
+            .windows(EVENT_SEPARATOR.len())
+            .position(|w| w == EVENT_SEPARATOR)
+    };
+    let rfind = |haystack: &[u8]| {
+        haystack
+            .windows(EVENT_SEPARATOR.len())
+            .rposition(|w| w == EVENT_SEPARATOR)
+    };
+
+    let head_end = rfind(head).map(|i| i + EVENT_SEPARATOR.len()).unwrap_or(0);
+    let tail_start = find(tail)
+        .map(|i| i + EVENT_SEPARATOR.len())
+        .unwrap_or(tail.len());
+
+    let mut data = Vec::with_capacity(head_end + tail.len() - tail_start);
+    data.extend_from_slice(&head[..head_end]);
+    data.extend_from_slice(&tail[tail_start..]);
+    data
+}
+
+impl Drop for Collector {
+    fn drop(&mut self) {
+        self.finish(StreamEnd::Aborted);
+    }
+}
+
+pin_project! {
+    /// 带结束通知的流包装
+    pub struct TappedStream<S> {
+        #[pin]
+        inner: S,
+        collector: Collector,
+    }
+}
+
+/// 包装流，返回包装后的流和结束通知接收端
+///
+/// `max_collect_bytes`: 收集数据的总量上限（超限后只保留开头与末尾窗口）
+pub fn tap<S>(
+    inner: S,
+    max_collect_bytes: usize,
+) -> (TappedStream<S>, oneshot::Receiver<StreamOutcome>) {
+    let (tx, rx) = oneshot::channel();
+    let stream = TappedStream {
+        inner,
+        collector: Collector {
+            data: Vec::new(),
+            tail: Vec::new(),
+            limit: max_collect_bytes,
+            truncated: false,
+            total_bytes: 0,
+            tx: Some(tx),
+        },
+    };
+    (stream, rx)
+}
+
+/// 上游空闲超时时正常结束流（超时错误不再传给下游），其他错误照常传递
+///
+/// 返回的标记在因超时结束时置为 true，供日志任务将已收到的部分记录为 partial
+pub fn end_on_idle_timeout<S, E, F>(
+    inner: S,
+    is_timeout: F,
+) -> (impl Stream<Item = Result<Bytes, E>>, Arc<AtomicBool>)
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    F: Fn(&E) -> bool,
+{
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&timed_out);
+    let stream = inner.take_while(move |result| {
+        let is_idle_timeout = matches!(result, Err(e) if is_timeout(e));
+        if is_idle_timeout {
+            flag.store(true, Ordering::SeqCst);
+        }
+        futures_util::future::ready(!is_idle_timeout)
+    });
+    (stream, timed_out)
+}
+
+impl<S, E> Stream for TappedStream<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Display,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let poll = this.inner.poll_next(cx);
+
+        match &poll {
+            Poll::Ready(Some(Ok(chunk))) => this.collector.push(chunk),
+            Poll::Ready(Some(Err(e))) => this.collector.finish(StreamEnd::Errored(e.to_string())),
+            Poll::Ready(None) => this.collector.finish(StreamEnd::Completed),
+            Poll::Pending => {}
+        }
+
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn chunks(items: Vec<Result<&'static str, &'static str>>) -> Vec<Result<Bytes, String>> {
+        items
+            .into_iter()
+            .map(|r| {
+                r.map(|s| Bytes::from_static(s.as_bytes()))
+                    .map_err(str::to_string)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_completed_stream_notifies_with_full_data() {
+        let source = futures_util::stream::iter(chunks(vec![Ok("data: a\n\n"), Ok("data: b\n\n")]));
+        let (stream, rx) = tap(source, usize::MAX);
+
+        let forwarded: Vec<_> = stream.collect().await;
+        assert_eq!(forwarded.len(), 2);
+
+        let outcome = rx.await.unwrap();
+        assert_eq!(outcome.end, StreamEnd::Completed);
+        assert_eq!(outcome.data, b"data: a\n\ndata: b\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_errored_stream_keeps_partial_data() {
+        let source = futures_util::stream::iter(chunks(vec![Ok("data: a\n\n"), Err("reset")]));
+        let (stream, rx) = tap(source, usize::MAX);
+
+        let _: Vec<_> = stream.collect().await;
+
+        let outcome = rx.await.unwrap();
+        assert_eq!(outcome.end, StreamEnd::Errored("reset".to_string()));
+        assert_eq!(outcome.data, b"data: a\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_dropped_stream_notifies_aborted() {
+        let source = futures_util::stream::iter(chunks(vec![Ok("data: a\n\n"), Ok("data: b\n\n")]));
+        let (mut stream, rx) = tap(source, usize::MAX);
+
+        let first = stream.next().await;
+        assert!(first.is_some());
+        drop(stream);
+
+        let outcome = rx.await.unwrap();
+        assert_eq!(outcome.end, StreamEnd::Aborted);
+        assert_eq!(outcome.data, b"data: a\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_collect_limit_keeps_head_and_tail_events() {
+        let source = futures_util::stream::iter(chunks(vec![
+            Ok("data: start\n\n"),
+            Ok("data: delta-1\n\n"),
+            Ok("data: delta-2\n\n"),
+            Ok("data: usage\n\n"),
+        ]));
+        let (stream, rx) = tap(source, 20);
+
+        let forwarded: Vec<_> = stream.collect().await;
+        assert_eq!(forwarded.len(), 4);
+
+        let outcome = rx.await.unwrap();
+        assert!(outcome.truncated);
+        assert_eq!(outcome.data, b"data: start\n\ndata: usage\n\n");
+        // 总字节数按实际转发量统计，不受截断影响
+        assert_eq!(outcome.total_bytes, 56);
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_ends_stream_with_partial_data() {
+        let source = futures_util::stream::iter(chunks(vec![
+            Ok("data: a\n\n"),
+            Err("timeout"),
+            Ok("data: b\n\n"),
+        ]));
+        let (source, timed_out) = end_on_idle_timeout(source, |e: &String| e == "timeout");
+        let (stream, rx) = tap(source, usize::MAX);
+
+        // 超时后不再向下游传递错误，流正常结束
+        let forwarded: Vec<_> = stream.collect().await;
+        assert_eq!(forwarded.len(), 1);
+        assert!(forwarded[0].is_ok());
+        assert!(timed_out.load(Ordering::SeqCst));
+
+        let outcome = rx.await.unwrap();
+        assert_eq!(outcome.end, StreamEnd::Completed);
+        assert_eq!(outcome.data, b"data: a\n\n");
+
+        // 其他错误照常传递
+        let source = futures_util::stream::iter(chunks(vec![Ok("data: a\n\n"), Err("reset")]));
+        let (source, timed_out) = end_on_idle_timeout(source, |e: &String| e == "timeout");
+        let forwarded: Vec<_> = source.collect().await;
+        assert_eq!(forwarded.len(), 2);
+        assert!(!timed_out.load(Ordering::SeqCst));
+    }
+}
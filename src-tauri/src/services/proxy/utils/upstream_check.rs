@@ -0,0 +1,156 @@
+//! 代理上游连通性检查
+//!
+//! 供前端在启动代理后确认上游可用：使用工具的 RequestProcessor 构造带真实 API Key 的
+//! 轻量请求（模型列表接口），经 `upstream_probe` 发送，在上游健康状态之外
+//! 额外给出 API Key 是否有效
+
+use anyhow::{anyhow, Result};
+use hyper::HeaderMap as HyperHeaderMap;
+use serde::Serialize;
+
+use super::upstream_client;
+use super::upstream_probe::{self, UpstreamHealth};
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::proxy::headers::{create_request_processor, HeaderFilter};
+
+/// 上游检查结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProxyHealthCheck {
+    pub tool_id: String,
+    #[serde(flatten)]
+    pub upstream: UpstreamHealth,
+    /// API Key 是否有效（401/403 为无效，2xx 为有效，其他状态无法判断时为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_valid: Option<bool>,
+}
+
+/// 各工具用于检查的轻量接口
+fn check_path(tool_id: &str) -> Option<&'static str> {
+    match tool_id {
+        "claude-code" | "codex" => Some("/v1/models"),
+        "gemini-cli" => Some("/v1beta/models"),
+        _ => None,
+    }
+}
+
+/// 检查工具代理配置的上游（不使用探测缓存，每次都实际请求）
+pub async fn check_upstream(tool_id: &str, config: &ToolProxyConfig) -> Result<ProxyHealthCheck> {
+    let path = check_path(tool_id).ok_or_else(|| anyhow!("{} 不支持上游检查", tool_id))?;
+    let (Some(base_url), Some(api_key)) = (&config.real_base_url, &config.real_api_key) else {
+        return Err(anyhow!("{} 代理未配置上游地址或 API Key", tool_id));
+    };
+
+    // Claude 模型列表接口要求 anthropic-version
+    let mut headers = HyperHeaderMap::new();
+    if tool_id == "claude-code" {
+        headers.insert("anthropic-version", "2023-06-01".parse()?);
+    }
+
+    let processor = create_request_processor(tool_id)?;
+    let processed = processor
+        .process_outgoing_request(
+            base_url,
+            api_key,
+            path,
+            None,
+            &headers,
+            &HeaderFilter::from_config(config),
+            &[],
+        )
+        .await?;
+
+    let client = upstream_client::upstream_client(tool_id, config)?;
+    let request = client.get(&processed.target_url).headers(processed.headers);
+    let upstream = upstream_probe::send_probe(request, base_url).await;
+
+    Ok(ProxyHealthCheck {
+        tool_id: tool_id.to_string(),
+        api_key_valid: upstream.status_code.and_then(key_validity),
+        upstream,
+    })
+}
+
+/// 根据状态码判断 API Key 是否有效
+fn key_validity(status: u16) -> Option<bool> {
+    match status {
+        401 | 403 => Some(false),
+        200..=299 => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{closed_url, MockResponse, MockUpstream};
+
+    fn config_for(base_url: &str, api_key: &str) -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(8787);
+        config.real_base_url = Some(base_url.to_string());
+        config.real_api_key = Some(api_key.to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn test_check_upstream_key_validity() {
+        let upstream = MockUpstream::respond(MockResponse::status("200 OK")).await;
+        let result = check_upstream("claude-code", &config_for(&upstream.url(), "sk-valid"))
+            .await
+            .unwrap();
+        assert!(result.upstream.reachable);
+        assert!(result.upstream.healthy);
+        assert_eq!(result.upstream.status_code, Some(200));
+        assert_eq!(result.api_key_valid, Some(true));
+
+        // 健康状态字段平铺在检查结果中
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["tool_id"], "claude-code");
+        assert_eq!(json["status_code"], 200);
+        assert_eq!(json["api_key_valid"], true);
+
+        // 请求使用真实 Key 与工具对应的检查接口
+        let request = upstream.requests().remove(0);
+        assert!(request.request_line().starts_with("GET /v1/models "));
+        assert_eq!(request.header("authorization"), Some("Bearer sk-valid"));
+        assert_eq!(request.header("anthropic-version"), Some("2023-06-01"));
+
+        let upstream = MockUpstream::respond(MockResponse::status("401 Unauthorized")).await;
+        let result = check_upstream("codex", &config_for(&upstream.url(), "sk-bad"))
+            .await
+            .unwrap();
+        assert!(result.upstream.reachable);
+        assert_eq!(result.api_key_valid, Some(false));
+        assert_eq!(
+            upstream.requests()[0].header("authorization"),
+            Some("Bearer sk-bad")
+        );
+
+        let upstream = MockUpstream::respond(MockResponse::status("502 Bad Gateway")).await;
+        let result = check_upstream("gemini-cli", &config_for(&upstream.url(), "key"))
+            .await
+            .unwrap();
+        assert_eq!(result.upstream.status_code, Some(502));
+        assert!(!result.upstream.healthy);
+        assert_eq!(result.api_key_valid, None);
+    }
+
+    #[tokio::test]
+    async fn test_check_upstream_unreachable_and_unsupported() {
+        let closed_url = closed_url().await;
+
+        let result = check_upstream("codex", &config_for(&closed_url, "sk"))
+            .await
+            .unwrap();
+        assert!(!result.upstream.reachable);
+        assert!(result.upstream.status_code.is_none());
+        assert!(result.upstream.error.is_some());
+        assert_eq!(result.api_key_valid, None);
+
+        assert!(check_upstream("amp-code", &config_for(&closed_url, "sk"))
+            .await
+            .is_err());
+        assert!(check_upstream("codex", &ToolProxyConfig::new(8787))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! 上游 HTTP 客户端构建
+//!
+//! 根据工具代理配置构建转发上游请求使用的 reqwest::Client，
+//! 按工具缓存复用连接池，影响客户端构建的配置变化时重建
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::models::proxy_config::ToolProxyConfig;
+
+/// 按工具缓存的上游客户端
+static CLIENT_CACHE: Lazy<Mutex<HashMap<String, (ClientKey, reqwest::Client)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 影响客户端构建的配置项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClientKey {
+    danger_accept_invalid_certs: bool,
+    upstream_timeout_secs: u64,
+    stream_idle_timeout_secs: u64,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout_secs: u64,
+}
+
+impl ClientKey {
+    fn from_config(config: &ToolProxyConfig) -> Self {
+        Self {
+            danger_accept_invalid_certs: config.danger_accept_invalid_certs,
+            upstream_timeout_secs: config.upstream_timeout_secs,
+            stream_idle_timeout_secs: config.stream_idle_timeout_secs,
+            pool_max_idle_per_host: config.pool_max_idle_per_host,
+            pool_idle_timeout_secs: config.pool_idle_timeout_secs,
+        }
+    }
+}
+
+/// 获取工具的上游客户端（配置未变化时复用缓存）
+pub fn upstream_client(tool_id: &str, config: &ToolProxyConfig) -> Result<reqwest::Client> {
+    let key = ClientKey::from_config(config);
+    let mut cache = CLIENT_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_key, client)) = cache.get(tool_id) {
+        if *cached_key == key {
+            return Ok(client.clone());
+        }
+    }
+
+    let client = build_upstream_client(config)?;
+    cache.insert(tool_id.to_string(), (key, client.clone()));
+    Ok(client)
+}
+
+/// 按代理配置构建上游客户端
+///
+/// - `danger_accept_invalid_certs`: 跳过 TLS 证书验证（自签名上游）
+/// - `upstream_timeout_secs`: 连接超时（请求总超时按请求设置，见 `RetryPolicy`）
+/// - `stream_idle_timeout_secs`: 读取空闲超时（SSE 流长时间无数据时中断）
+/// - `pool_max_idle_per_host` / `pool_idle_timeout_secs`: 连接池大小与空闲连接保留时长
+pub fn build_upstream_client(config: &ToolProxyConfig) -> Result<reqwest::Client> {
+    if config.danger_accept_invalid_certs {
+        tracing::warn!(
+            port = config.port,
+            "已跳过上游 TLS 证书验证，仅应在信任的私有上游中使用"
+        );
+    }
+
+    build_client(
+        config.danger_accept_invalid_certs,
+        Duration::from_secs(config.upstream_timeout_secs.max(1)),
+        Duration::from_secs(config.stream_idle_timeout_secs.max(1)),
+        PoolOptions::from_config(config),
+    )
+}
+
+/// 连接池参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PoolOptions {
+    max_idle_per_host: usize,
+    idle_timeout: Duration,
+}
+
+impl PoolOptions {
+    fn from_config(config: &ToolProxyConfig) -> Self {
+        Self {
+            max_idle_per_host: config.pool_max_idle_per_host.unwrap_or(usize::MAX),
+            idle_timeout: Duration::from_secs(config.pool_idle_timeout_secs.max(1)),
+        }
+    }
+}
+
+fn build_client(
+    danger_accept_invalid_certs: bool,
+    connect_timeout: Duration,
+    idle_timeout: Duration,
+    pool: PoolOptions,
+) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(connect_timeout)
+        .read_timeout(idle_timeout)
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .pool_idle_timeout(pool.idle_timeout);
+
+    if danger_accept_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder.build().context("构建上游 HTTP 客户端失败")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockUpstream};
+
+    #[test]
+    fn test_danger_accept_invalid_certs_defaults_to_false() {
+        let config: ToolProxyConfig =
+            serde_json::from_str(r#"{"enabled": true, "port": 8787}"#).unwrap();
+        assert!(!config.danger_accept_invalid_certs);
+        assert!(!ToolProxyConfig::new(8787).danger_accept_invalid_certs);
+    }
+
+    #[test]
+    fn test_danger_accept_invalid_certs_roundtrip() {
+        let mut config = ToolProxyConfig::new(8787);
+        config.danger_accept_invalid_certs = true;
+
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: ToolProxyConfig = serde_json::from_str(&json).unwrap();
+        assert!(parsed.danger_accept_invalid_certs);
+    }
+
+    #[test]
+    fn test_build_upstream_client() {
+        let mut config = ToolProxyConfig::new(8787);
+        assert!(build_upstream_client(&config).is_ok());
+
+        config.danger_accept_invalid_certs = true;
+        assert!(build_upstream_client(&config).is_ok());
+    }
+
+    #[test]
+    fn test_timeout_defaults() {
+        let config: ToolProxyConfig =
+            serde_json::from_str(r#"{"enabled": true, "port": 8787}"#).unwrap();
+        assert_eq!(config.upstream_timeout_secs, 60);
+        assert_eq!(config.stream_idle_timeout_secs, 300);
+    }
+
+    #[test]
+    fn test_upstream_client_rebuilt_on_config_change() {
+        let mut config = ToolProxyConfig::new(8787);
+        upstream_client("client-cache-test", &config).unwrap();
+        let cached_key = |tool_id: &str| CLIENT_CACHE.lock().unwrap().get(tool_id).map(|(k, _)| *k);
+        assert_eq!(
+            cached_key("client-cache-test"),
+            Some(ClientKey::from_config(&config))
+        );
+
+        config.stream_idle_timeout_secs = 600;
+        upstream_client("client-cache-test", &config).unwrap();
+        assert_eq!(
+            cached_key("client-cache-test").map(|k| k.stream_idle_timeout_secs),
+            Some(600)
+        );
+    }
+
+    #[test]
+    fn test_pool_defaults() {
+        let config: ToolProxyConfig =
+            serde_json::from_str(r#"{"enabled": true, "port": 8787}"#).unwrap();
+        assert_eq!(config.pool_max_idle_per_host, None);
+        assert_eq!(config.pool_idle_timeout_secs, 90);
+
+        let pool = PoolOptions::from_config(&config);
+        assert_eq!(pool.max_idle_per_host, usize::MAX);
+        assert_eq!(pool.idle_timeout, Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_upstream_clients_are_independent_per_tool() {
+        let mut config_a = ToolProxyConfig::new(8787);
+        config_a.pool_max_idle_per_host = Some(4);
+        config_a.pool_idle_timeout_secs = 30;
+        let config_b = ToolProxyConfig::new(8788);
+
+        upstream_client("pool-test-a", &config_a).unwrap();
+        upstream_client("pool-test-b", &config_b).unwrap();
+        let cached_key = |tool_id: &str| CLIENT_CACHE.lock().unwrap().get(tool_id).map(|(k, _)| *k);
+        assert_eq!(
+            cached_key("pool-test-a").unwrap().pool_max_idle_per_host,
+            Some(4)
+        );
+        assert_eq!(
+            cached_key("pool-test-a").unwrap().pool_idle_timeout_secs,
+            30
+        );
+        assert_eq!(
+            cached_key("pool-test-b").unwrap().pool_max_idle_per_host,
+            None
+        );
+
+        // 修改一个工具的连接池配置只重建该工具的客户端
+        config_a.pool_max_idle_per_host = Some(1);
+        upstream_client("pool-test-a", &config_a).unwrap();
+        assert_eq!(
+            cached_key("pool-test-a").unwrap().pool_max_idle_per_host,
+            Some(1)
+        );
+        assert_eq!(
+            cached_key("pool-test-b"),
+            Some(ClientKey::from_config(&config_b))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pool_max_idle_per_host_applied() {
+        // 支持 keep-alive 的上游（响应不带 Connection: close），统计建立的连接数
+        let upstream = MockUpstream::respond(MockResponse::raw(
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+        ))
+        .await;
+
+        let send_twice = |config: ToolProxyConfig| {
+            let url = upstream.url();
+            async move {
+                let client = build_upstream_client(&config).unwrap();
+                for _ in 0..2 {
+                    let body = client
+                        .get(&url)
+                        .send()
+                        .await
+                        .unwrap()
+                        .bytes()
+                        .await
+                        .unwrap();
+                    assert_eq!(&body[..], b"ok");
+                }
+            }
+        };
+
+        // 默认配置复用空闲连接
+        send_twice(ToolProxyConfig::new(8787)).await;
+        assert_eq!(upstream.connections(), 1);
+
+        // 不保留空闲连接时每次请求都新建连接
+        let mut config = ToolProxyConfig::new(8787);
+        config.pool_max_idle_per_host = Some(0);
+        send_twice(config).await;
+        assert_eq!(upstream.connections(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stream_idle_timeout_on_slow_upstream() {
+        use futures_util::StreamExt;
+
+        // 慢上游：返回响应头和第一个事件后停止发送
+        let upstream = MockUpstream::respond(
+            MockResponse::raw(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n9\r\ndata: a\n\n\r\n",
+            )
+            .stalled(),
+        )
+        .await;
+
+        let client = build_client(
+            false,
+            Duration::from_secs(1),
+            Duration::from_millis(200),
+            PoolOptions::from_config(&ToolProxyConfig::new(8787)),
+        )
+        .unwrap();
+        let res = client.get(upstream.url()).send().await.unwrap();
+        let mut stream = res.bytes_stream();
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(&first[..], b"data: a\n\n");
+
+        let started = std::time::Instant::now();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,162 @@
+//! 上游可达性探测
+//!
+//! 供 `/__health?upstream=1` 使用：对上游 base_url 发送一次 HEAD 请求，
+//! 区分本地代理问题与上游问题。结果按 base_url 缓存，避免健康检查频繁打到上游。
+//! 上游检查（`upstream_check`）复用 [`send_probe`] 发送带 API Key 的探测请求
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::services::proxy::log_recorder::upstream_host;
+
+/// 探测结果缓存时间
+pub const PROBE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 单次探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 全局上游探测器
+static UPSTREAM_PROBER: Lazy<UpstreamProber> = Lazy::new(|| UpstreamProber::new(PROBE_CACHE_TTL));
+
+/// 上游健康状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UpstreamHealth {
+    /// 上游 host[:port]
+    pub host: Option<String>,
+    /// 是否建立连接并收到 HTTP 响应
+    pub reachable: bool,
+    /// 可达且未返回 5xx
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 探测时间（Unix 时间戳，毫秒）
+    pub checked_at: i64,
+    /// 是否来自缓存
+    pub cached: bool,
+}
+
+/// 带缓存的上游探测器
+pub struct UpstreamProber {
+    ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, UpstreamHealth)>>,
+}
+
+impl UpstreamProber {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 探测上游（缓存未过期时直接返回缓存结果）
+    pub async fn probe(&self, client: &reqwest::Client, base_url: &str) -> UpstreamHealth {
+        if let Some(cached) = self.cached(base_url) {
+            return cached;
+        }
+
+        let health = probe_once(client, base_url).await;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.retain(|_, (at, _)| at.elapsed() < self.ttl);
+            cache.insert(base_url.to_string(), (Instant::now(), health.clone()));
+        }
+        health
+    }
+
+    fn cached(&self, base_url: &str) -> Option<UpstreamHealth> {
+        let cache = self.cache.lock().ok()?;
+        let (at, health) = cache.get(base_url)?;
+        (at.elapsed() < self.ttl).then(|| UpstreamHealth {
+            cached: true,
+            ..health.clone()
+        })
+    }
+}
+
+/// 通过全局探测器探测上游
+pub async fn probe(client: &reqwest::Client, base_url: &str) -> UpstreamHealth {
+    UPSTREAM_PROBER.probe(client, base_url).await
+}
+
+/// 发送一次轻量 HEAD 请求（任何 HTTP 响应都视为可达，鉴权失败等 4xx 不影响判断）
+async fn probe_once(client: &reqwest::Client, base_url: &str) -> UpstreamHealth {
+    send_probe(client.head(base_url), base_url).await
+}
+
+/// 发送探测请求并汇总为健康状态（不经过缓存）
+pub async fn send_probe(request: reqwest::RequestBuilder, base_url: &str) -> UpstreamHealth {
+    let started = Instant::now();
+    let result = request.timeout(PROBE_TIMEOUT).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let (status_code, error) = match result {
+        Ok(response) => (Some(response.status().as_u16()), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    UpstreamHealth {
+        host: upstream_host(base_url),
+        reachable: status_code.is_some(),
+        healthy: status_code.is_some_and(|code| code < 500),
+        status_code,
+        latency_ms,
+        error,
+        checked_at: chrono::Utc::now().timestamp_millis(),
+        cached: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{closed_url, MockResponse, MockUpstream};
+
+    #[tokio::test]
+    async fn test_probe_reachable_and_cached() {
+        let upstream = MockUpstream::respond(MockResponse::status("401 Unauthorized")).await;
+        let base_url = upstream.url();
+        let prober = UpstreamProber::new(Duration::from_secs(60));
+        let client = reqwest::Client::new();
+
+        let first = prober.probe(&client, &base_url).await;
+        assert!(first.reachable);
+        assert!(first.healthy);
+        assert_eq!(first.status_code, Some(401));
+        assert!(!first.cached);
+        assert_eq!(first.host, upstream_host(&base_url));
+
+        // 缓存期内不再请求上游
+        let second = prober.probe(&client, &base_url).await;
+        assert!(second.cached);
+        assert_eq!(second.status_code, Some(401));
+        assert_eq!(upstream.hits(), 1);
+
+        // 缓存过期后重新探测
+        let prober = UpstreamProber::new(Duration::ZERO);
+        prober.probe(&client, &base_url).await;
+        prober.probe(&client, &base_url).await;
+        assert_eq!(upstream.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_probe_unhealthy_and_unreachable() {
+        let client = reqwest::Client::new();
+        let prober = UpstreamProber::new(Duration::from_secs(60));
+
+        let upstream = MockUpstream::respond(MockResponse::status("503 Service Unavailable")).await;
+        let health = prober.probe(&client, &upstream.url()).await;
+        assert!(health.reachable);
+        assert!(!health.healthy);
+
+        let health = prober.probe(&client, &closed_url().await).await;
+        assert!(!health.reachable);
+        assert!(!health.healthy);
+        assert!(health.status_code.is_none());
+        assert!(health.error.is_some());
+    }
+}
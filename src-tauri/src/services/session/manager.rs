@@ -18,6 +18,12 @@ use tokio_util::sync::CancellationToken;
 /// 全局取消令牌，用于优雅关闭后台任务
 static CANCELLATION_TOKEN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
 
+/// 默认会话过期时间（24 小时）
+const DEFAULT_SESSION_TTL_HOURS: u64 = 24;
+
+/// 会话列表默认每页数量（page_size 为 0 时使用）
+const DEFAULT_PAGE_SIZE: usize = 20;
+
 /// 会话管理器单例
 pub struct SessionManager {
     manager: Arc<DataManager>,
@@ -123,6 +129,21 @@ impl SessionManager {
                         break;
                     }
                     _ = cleanup_interval.tick() => {
+                        // 删除超过会话过期时间未活动的会话
+                        let now = chrono::Utc::now().timestamp();
+                        match Self::cleanup_expired_sessions_internal(
+                            &manager_clone,
+                            &db_path_clone,
+                            Self::session_ttl_secs(),
+                            now,
+                        ) {
+                            Ok(deleted) if deleted > 0 => {
+                                tracing::info!("已清理过期会话: {} 条", deleted);
+                            }
+                            Ok(_) => {}
+                            Err(e) => tracing::warn!("清理过期会话失败: {}", e),
+                        }
+
                         // 清理三个工具的过期会话
                         for tool_id in &["claude-code", "codex", "gemini-cli"] {
                             let _ = Self::cleanup_old_sessions_internal(
@@ -139,6 +160,41 @@ impl SessionManager {
         });
     }
 
+    /// 读取会话过期时间（秒），配置不可用时使用默认 24 小时
+    fn session_ttl_secs() -> i64 {
+        let hours = crate::utils::config::read_global_config()
+            .ok()
+            .flatten()
+            .map(|config| config.session_ttl_hours)
+            .filter(|hours| *hours > 0)
+            .unwrap_or(DEFAULT_SESSION_TTL_HOURS);
+        (hours * 3600) as i64
+    }
+
+    /// 删除超过 `ttl_secs` 未活动的会话
+    ///
+    /// 活动时间以最后一次 `NewRequest` 写入的 last_seen_at 为准
+    fn cleanup_expired_sessions_internal(
+        manager: &Arc<DataManager>,
+        db_path: &Path,
+        ttl_secs: i64,
+        now: i64,
+    ) -> Result<usize> {
+        let db = manager.sqlite(db_path)?;
+        let cutoff_time = now - ttl_secs;
+
+        let deleted = db.execute(
+            "DELETE FROM claude_proxy_sessions WHERE last_seen_at < ?",
+            &[&cutoff_time.to_string()],
+        )?;
+
+        if deleted > 0 {
+            let _ = db.execute_raw("PRAGMA wal_checkpoint(PASSIVE)");
+        }
+
+        Ok(deleted)
+    }
+
     /// 批量写入事件到数据库
     fn flush_events(manager: &Arc<DataManager>, db_path: &Path, buffer: &mut Vec<SessionEvent>) {
         let mut has_writes = false;
@@ -249,26 +305,52 @@ impl SessionManager {
         tool_id: &str,
         page: usize,
         page_size: usize,
+    ) -> Result<SessionListResponse> {
+        self.list_sessions(Some(tool_id), page, page_size)
+    }
+
+    /// 分页查询会话（公共 API）
+    ///
+    /// `tool_id` 为 None 时返回所有工具的会话；按最后活跃时间降序，
+    /// page 从 1 开始（0 按 1 处理），page_size 为 0 时使用默认值
+    pub fn list_sessions(
+        &self,
+        tool_id: Option<&str>,
+        page: usize,
+        page_size: usize,
     ) -> Result<SessionListResponse> {
         let db = self.manager.sqlite(&self.db_path)?;
+        let page = page.max(1);
+        let page_size = if page_size == 0 {
+            DEFAULT_PAGE_SIZE
+        } else {
+            page_size
+        };
+
+        let (where_clause, mut params): (&str, Vec<String>) = match tool_id {
+            Some(tool_id) => ("WHERE tool_id = ?", vec![tool_id.to_string()]),
+            None => ("", Vec::new()),
+        };
 
         // 查询总数
-        let total_rows = db.query(
-            "SELECT COUNT(*) FROM claude_proxy_sessions WHERE tool_id = ?",
-            &[tool_id],
-        )?;
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM claude_proxy_sessions {}",
+            where_clause
+        );
+        let count_params: Vec<&str> = params.iter().map(String::as_str).collect();
+        let total_rows = db.query(&count_sql, &count_params)?;
         let total = parse_count(&total_rows[0])?;
 
-        // 查询分页数据（按最后活跃时间降序）
-        let offset = (page.saturating_sub(1)) * page_size;
+        // 查询分页数据（按最后活跃时间降序，同一时间按会话 ID 保证分页稳定）
+        let offset = (page - 1).saturating_mul(page_size);
         let sql = format!(
-            "SELECT {} FROM claude_proxy_sessions WHERE tool_id = ? ORDER BY last_seen_at DESC LIMIT ? OFFSET ?",
-            SELECT_SESSION_FIELDS
+            "SELECT {} FROM claude_proxy_sessions {} ORDER BY last_seen_at DESC, session_id ASC LIMIT ? OFFSET ?",
+            SELECT_SESSION_FIELDS, where_clause
         );
-        let rows = db.query(
-            &sql,
-            &[tool_id, &page_size.to_string(), &offset.to_string()],
-        )?;
+        params.push(page_size.to_string());
+        params.push(offset.to_string());
+        let query_params: Vec<&str> = params.iter().map(String::as_str).collect();
+        let rows = db.query(&sql, &query_params)?;
 
         // 转换为 ProxySession
         let sessions = rows
@@ -284,6 +366,17 @@ impl SessionManager {
         })
     }
 
+    /// 获取未过期的活跃会话数量（公共 API，用于调试）
+    pub fn get_active_session_count(&self) -> Result<usize> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        let cutoff_time = chrono::Utc::now().timestamp() - Self::session_ttl_secs();
+        let rows = db.query(
+            "SELECT COUNT(*) FROM claude_proxy_sessions WHERE last_seen_at >= ?",
+            &[&cutoff_time.to_string()],
+        )?;
+        parse_count(&rows[0])
+    }
+
     /// 删除单个会话（公共 API）
     pub fn delete_session(&self, session_id: &str) -> Result<()> {
         let db = self.manager.sqlite(&self.db_path)?;
@@ -503,6 +596,153 @@ mod tests {
         println!("Query 1: {:?}, Query 2: {:?}", duration1, duration2);
     }
 
+    /// 插入指定最后活跃时间的测试会话
+    fn insert_session(manager: &SessionManager, session_id: &str, last_seen_at: i64) {
+        insert_tool_session(manager, session_id, "claude-code", last_seen_at);
+    }
+
+    /// 插入指定工具与最后活跃时间的测试会话
+    fn insert_tool_session(
+        manager: &SessionManager,
+        session_id: &str,
+        tool_id: &str,
+        last_seen_at: i64,
+    ) {
+        let db = manager.manager.sqlite(&manager.db_path).unwrap();
+        let ts = last_seen_at.to_string();
+        db.execute(
+            "INSERT INTO claude_proxy_sessions (
+                session_id, display_id, tool_id, config_name, url, api_key,
+                first_seen_at, last_seen_at, request_count,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, 'global', '', '', ?, ?, 1, ?, ?)",
+            &[session_id, session_id, tool_id, &ts, &ts, &ts, &ts],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_filter_and_order() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+
+        let now = chrono::Utc::now().timestamp();
+        insert_tool_session(&manager, "claude_old", "claude-code", now - 300);
+        insert_tool_session(&manager, "codex_mid", "codex", now - 200);
+        insert_tool_session(&manager, "claude_new", "claude-code", now - 100);
+
+        // 不过滤时返回全部工具，按最后活跃时间降序
+        let all = manager.list_sessions(None, 1, 10).unwrap();
+        assert_eq!(all.total, 3);
+        let ids: Vec<&str> = all.sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["claude_new", "codex_mid", "claude_old"]);
+
+        // 按工具过滤，总数同样按过滤条件统计
+        let claude = manager.list_sessions(Some("claude-code"), 1, 10).unwrap();
+        assert_eq!(claude.total, 2);
+        assert!(claude.sessions.iter().all(|s| s.tool_id == "claude-code"));
+        assert_eq!(claude.sessions[0].session_id, "claude_new");
+        assert_eq!(claude.sessions[0].config_name, "global");
+        assert_eq!(claude.sessions[0].request_count, 1);
+        assert_eq!(claude.sessions[0].first_seen_at, now - 100);
+
+        let none = manager.list_sessions(Some("gemini-cli"), 1, 10).unwrap();
+        assert_eq!(none.total, 0);
+        assert!(none.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_pagination_bounds() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+
+        let now = chrono::Utc::now().timestamp();
+        for i in 0..5 {
+            insert_tool_session(&manager, &format!("session_{i}"), "codex", now - i);
+        }
+
+        let first = manager.list_sessions(None, 1, 2).unwrap();
+        assert_eq!(first.total, 5);
+        assert_eq!(first.sessions.len(), 2);
+        assert_eq!(first.sessions[0].session_id, "session_0");
+
+        // 最后一页不满
+        let last = manager.list_sessions(None, 3, 2).unwrap();
+        assert_eq!(last.sessions.len(), 1);
+        assert_eq!(last.sessions[0].session_id, "session_4");
+
+        // 超出范围的页返回空列表，总数不变
+        let beyond = manager.list_sessions(None, 4, 2).unwrap();
+        assert_eq!(beyond.total, 5);
+        assert!(beyond.sessions.is_empty());
+
+        // page 为 0 按第一页处理，page_size 为 0 使用默认值
+        let zero_page = manager.list_sessions(None, 0, 2).unwrap();
+        assert_eq!(zero_page.page, 1);
+        assert_eq!(zero_page.sessions[0].session_id, "session_0");
+        let zero_size = manager.list_sessions(None, 1, 0).unwrap();
+        assert_eq!(zero_size.page_size, DEFAULT_PAGE_SIZE);
+        assert_eq!(zero_size.sessions.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_expired_sessions() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+
+        let now = chrono::Utc::now().timestamp();
+        let ttl = 24 * 3600;
+        insert_session(&manager, "session_expired", now - ttl - 60);
+        insert_session(&manager, "session_active", now - 60);
+        insert_session(&manager, "session_boundary", now - ttl);
+
+        let deleted = SessionManager::cleanup_expired_sessions_internal(
+            &manager.manager,
+            &manager.db_path,
+            ttl,
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert!(manager.get_session("session_expired").unwrap().is_none());
+        assert!(manager.get_session("session_active").unwrap().is_some());
+        assert!(manager.get_session("session_boundary").unwrap().is_some());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_new_request_keeps_session_alive() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+
+        let now = chrono::Utc::now().timestamp();
+        let ttl = 3600;
+        insert_session(&manager, "session_revived", now - ttl * 2);
+
+        // 新请求刷新最后活跃时间后不应被清理
+        manager
+            .send_event(SessionEvent::NewRequest {
+                session_id: "session_revived".to_string(),
+                tool_id: "claude-code".to_string(),
+                timestamp: now,
+            })
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let deleted = SessionManager::cleanup_expired_sessions_internal(
+            &manager.manager,
+            &manager.db_path,
+            ttl,
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(deleted, 0);
+        let session = manager.get_session("session_revived").unwrap().unwrap();
+        assert_eq!(session.request_count, 2);
+    }
+
     #[tokio::test]
     async fn test_update_session_config() {
         let temp = TempDir::new().expect("create temp dir");
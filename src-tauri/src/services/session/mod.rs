@@ -4,5 +4,6 @@ mod db_utils;
 pub mod manager;
 pub mod models;
 
+pub use db_utils::SessionConfig;
 pub use manager::{shutdown_session_manager, SESSION_MANAGER};
 pub use models::{ProxySession, SessionEvent, SessionListResponse};
@@ -2,6 +2,7 @@
 //!
 //! 提供趋势分析和成本汇总查询功能
 
+use super::db::{build_log_filters, where_sql, LogFilter};
 use crate::data::DataManager;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -22,6 +23,49 @@ pub enum TimeGranularity {
     /// 天粒度
     #[default]
     Day,
+    /// 周粒度（以 UTC 周一 00:00 为起点）
+    Week,
+}
+
+/// Unix 纪元（1970-01-01，周四）到首个周一的偏移（毫秒）
+const WEEK_OFFSET_MS: i64 = 4 * 24 * 60 * 60 * 1000;
+
+impl TimeGranularity {
+    /// 粒度对应的时间间隔（毫秒）
+    pub fn interval_ms(&self) -> i64 {
+        match self {
+            TimeGranularity::FifteenMinutes => 15 * 60 * 1000,
+            TimeGranularity::ThirtyMinutes => 30 * 60 * 1000,
+            TimeGranularity::Hour => 60 * 60 * 1000,
+            TimeGranularity::TwelveHours => 12 * 60 * 60 * 1000,
+            TimeGranularity::Day => 24 * 60 * 60 * 1000,
+            TimeGranularity::Week => 7 * 24 * 60 * 60 * 1000,
+        }
+    }
+
+    /// 时间戳所在时间桶的起点（毫秒）
+    pub fn bucket_start(&self, timestamp: i64) -> i64 {
+        let interval = self.interval_ms();
+        match self {
+            TimeGranularity::Week => {
+                (timestamp - WEEK_OFFSET_MS).div_euclid(interval) * interval + WEEK_OFFSET_MS
+            }
+            _ => timestamp.div_euclid(interval) * interval,
+        }
+    }
+
+    /// 与 `bucket_start` 等价的 SQL 时间分组表达式（向下取整到粒度边界）
+    pub fn bucket_expr(&self) -> String {
+        let interval = self.interval_ms();
+        match self {
+            // 按周分组：先平移到周一起点再取整
+            TimeGranularity::Week => format!(
+                "CAST(((timestamp - {1}) / {0}) * {0} + {1} AS INTEGER)",
+                interval, WEEK_OFFSET_MS
+            ),
+            _ => format!("CAST((timestamp / {0}) * {0} AS INTEGER)", interval),
+        }
+    }
 }
 
 /// 趋势查询参数
@@ -39,10 +83,30 @@ pub struct TrendQuery {
     pub config_name: Option<String>,
     /// 会话 ID 过滤
     pub session_id: Option<String>,
-    /// 时间粒度
+    /// 时间粒度（非趋势类统计复用本结构作为过滤条件时忽略）
+    #[serde(default)]
     pub granularity: TimeGranularity,
 }
 
+impl<'a> From<&'a TrendQuery> for LogFilter<'a> {
+    fn from(query: &'a TrendQuery) -> Self {
+        Self {
+            tool_type: query.tool_type.as_deref(),
+            session_id: query.session_id.as_deref(),
+            config_name: query.config_name.as_deref(),
+            model: query.model.as_deref(),
+            start_time: query.start_time,
+            end_time: query.end_time,
+            errors_only: false,
+        }
+    }
+}
+
+/// 将过滤参数转换为 rusqlite 参数列表
+fn sql_params(params: &[String]) -> Vec<&dyn rusqlite::ToSql> {
+    params.iter().map(|p| p as &dyn rusqlite::ToSql).collect()
+}
+
 /// 趋势数据点
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendDataPoint {
@@ -72,6 +136,15 @@ pub struct TrendDataPoint {
     pub error_count: i64,
     /// 平均响应时间（毫秒）
     pub avg_response_time: Option<f64>,
+    /// 平均首字节延迟（毫秒，未记录 TTFB 的请求不参与平均）
+    #[serde(default)]
+    pub avg_ttfb: Option<f64>,
+    /// 请求体总字节数
+    #[serde(default)]
+    pub request_bytes: i64,
+    /// 响应体总字节数
+    #[serde(default)]
+    pub response_bytes: i64,
 }
 
 /// 成本汇总分组方式
@@ -85,8 +158,21 @@ pub enum CostGroupBy {
     Config,
     /// 按会话分组
     Session,
+    /// 按工具分组
+    Tool,
+    /// 按请求类型分组（`tool_use`：以工具调用结束的请求，`chat`：普通对话请求）
+    RequestKind,
+    /// 按请求分类分组（coding / chat / embedding / image / other，旧数据归为 other）
+    Category,
 }
 
+/// 请求类型分组表达式（各工具的工具调用结束原因均已归一为 `tool_use`）
+const REQUEST_KIND_EXPR: &str =
+    "CASE WHEN stop_reason = 'tool_use' THEN 'tool_use' ELSE 'chat' END";
+
+/// 请求分类分组表达式（迁移前的旧日志没有分类）
+const CATEGORY_EXPR: &str = "COALESCE(NULLIF(category, ''), 'other')";
+
 /// 成本汇总查询参数
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CostSummaryQuery {
@@ -102,10 +188,22 @@ pub struct CostSummaryQuery {
     pub group_by: CostGroupBy,
 }
 
+impl<'a> From<&'a CostSummaryQuery> for LogFilter<'a> {
+    fn from(query: &'a CostSummaryQuery) -> Self {
+        Self {
+            tool_type: query.tool_type.as_deref(),
+            session_id: query.session_id.as_deref(),
+            start_time: query.start_time,
+            end_time: query.end_time,
+            ..Default::default()
+        }
+    }
+}
+
 /// 成本汇总数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CostSummary {
-    /// 分组字段名称（model/config_name/session_id）
+    /// 分组字段名称（model/config_name/session_id/tool_type/请求类型）
     pub group_name: String,
     /// 总成本（USD）
     pub total_cost: f64,
@@ -117,6 +215,158 @@ pub struct CostSummary {
     pub output_tokens: i64,
     /// 平均响应时间（毫秒）
     pub avg_response_time: Option<f64>,
+    /// 平均首字节延迟（毫秒）
+    #[serde(default)]
+    pub avg_ttfb: Option<f64>,
+}
+
+/// 结束原因分布数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopReasonStat {
+    /// 结束原因（未记录时为 unknown）
+    pub stop_reason: String,
+    /// 请求数
+    pub request_count: i64,
+    /// 占比（0-100）
+    pub percentage: f64,
+}
+
+/// 成功率趋势数据点
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SuccessRatePoint {
+    /// 时间戳（毫秒）
+    pub timestamp: i64,
+    /// 请求总数
+    pub request_count: i64,
+    /// 成功请求数
+    pub success_count: i64,
+    /// 失败请求数
+    pub failed_count: i64,
+    /// 成功率（0-100，无请求时为 None）
+    pub success_rate: Option<f64>,
+}
+
+impl SuccessRatePoint {
+    fn new(timestamp: i64, success_count: i64, failed_count: i64) -> Self {
+        let request_count = success_count + failed_count;
+        Self {
+            timestamp,
+            request_count,
+            success_count,
+            failed_count,
+            success_rate: (request_count > 0)
+                .then(|| success_count as f64 * 100.0 / request_count as f64),
+        }
+    }
+}
+
+/// 缓存收益（Prompt Caching ROI）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CacheRoi {
+    /// 缓存写入 Token 总数
+    pub cache_creation_tokens: i64,
+    /// 缓存读取 Token 总数
+    pub cache_read_tokens: i64,
+    /// 缓存写入总成本（USD）
+    pub cache_write_cost: f64,
+    /// 缓存读取实际成本（USD）
+    pub cache_read_cost: f64,
+    /// 缓存读取节省的成本（按普通输入价格计费的差额，USD）
+    pub cache_read_savings: f64,
+    /// 净收益 = 读取节省 - 写入成本（USD，负数表示亏损）
+    pub net_savings: f64,
+    /// 收益率（净收益 / 写入成本 × 100，无写入成本时为 None）
+    pub roi_percentage: Option<f64>,
+}
+
+/// 单个上游的统计数据
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpstreamStat {
+    /// 上游标识（base_url 的 host[:port]，未记录时为 unknown）
+    pub upstream: String,
+    /// 请求总数
+    pub request_count: i64,
+    /// 成功请求数
+    pub success_count: i64,
+    /// 失败请求数
+    pub failed_count: i64,
+    /// 成功率（0-100）
+    pub success_rate: f64,
+    /// 输入 Token 总数
+    pub input_tokens: i64,
+    /// 输出 Token 总数
+    pub output_tokens: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 平均响应时间（毫秒）
+    pub avg_response_time: Option<f64>,
+    /// 平均首字节延迟（毫秒）
+    #[serde(default)]
+    pub avg_ttfb: Option<f64>,
+}
+
+/// 单个客户端 IP 的用量（共享代理场景）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IpUsage {
+    /// 客户端 IP（未记录时为 unknown）
+    pub client_ip: String,
+    /// 请求总数
+    pub request_count: i64,
+    /// 成功请求数
+    pub success_count: i64,
+    /// 输入 Token 总数
+    pub input_tokens: i64,
+    /// 输出 Token 总数
+    pub output_tokens: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 最近一次请求时间（毫秒时间戳）
+    pub last_request_at: i64,
+}
+
+/// 单个模型的缓存节省
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelSavings {
+    pub model: String,
+    /// 命中缓存读取的请求数
+    pub cached_request_count: i64,
+    /// 缓存读取 Token 总数
+    pub cache_read_tokens: i64,
+    /// 节省金额（USD）
+    pub savings: f64,
+}
+
+/// Prompt Caching 累计节省汇总
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SavingsSummary {
+    /// 请求总数
+    pub request_count: i64,
+    /// 命中缓存读取的请求数
+    pub cached_request_count: i64,
+    /// 缓存读取 Token 总数
+    pub cache_read_tokens: i64,
+    /// 实际花费（USD）
+    pub actual_cost: f64,
+    /// 若无缓存本应花费（USD）
+    pub no_cache_cost: f64,
+    /// 累计节省金额（USD）
+    pub total_savings: f64,
+    /// 节省比例（节省 / 无缓存花费 × 100，无花费时为 None）
+    pub savings_percentage: Option<f64>,
+    /// 按模型的节省（按节省金额降序）
+    pub by_model: Vec<ModelSavings>,
+}
+
+/// 单条命中缓存读取的请求的节省
+struct RequestCacheSavings {
+    model: String,
+    cache_read_tokens: i64,
+    savings: f64,
+}
+
+/// 单条请求的缓存读取节省：读取 Token 按完整输入单价应付的费用 - 实际读取成本
+fn request_cache_savings(cache_read_tokens: i64, cache_read_cost: f64, input_rate: f64) -> f64 {
+    (cache_read_tokens as f64 * input_rate - cache_read_cost).max(0.0)
 }
 
 /// Token 统计分析服务
@@ -137,68 +387,10 @@ impl TokenStatsAnalytics {
             .context("Failed to get SQLite manager")?;
 
         // 构建时间分组表达式
-        let time_expr = match query.granularity {
-            TimeGranularity::FifteenMinutes => {
-                // 按15分钟分组：向下取整到最近的15分钟
-                "CAST((timestamp / 900000) * 900000 AS INTEGER)"
-            }
-            TimeGranularity::ThirtyMinutes => {
-                // 按30分钟分组：向下取整到最近的30分钟
-                "CAST((timestamp / 1800000) * 1800000 AS INTEGER)"
-            }
-            TimeGranularity::Hour => {
-                // 按小时分组
-                "CAST((timestamp / 3600000) * 3600000 AS INTEGER)"
-            }
-            TimeGranularity::TwelveHours => {
-                // 按12小时分组
-                "CAST((timestamp / 43200000) * 43200000 AS INTEGER)"
-            }
-            TimeGranularity::Day => {
-                // 按天分组
-                "CAST((timestamp / 86400000) * 86400000 AS INTEGER)"
-            }
-        };
+        let time_expr = query.granularity.bucket_expr();
 
-        // 构建 WHERE 子句
-        let mut where_clauses = Vec::new();
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-        if let Some(start_time) = query.start_time {
-            where_clauses.push("timestamp >= ?");
-            params.push(Box::new(start_time));
-        }
-
-        if let Some(end_time) = query.end_time {
-            where_clauses.push("timestamp <= ?");
-            params.push(Box::new(end_time));
-        }
-
-        if let Some(ref tool_type) = query.tool_type {
-            where_clauses.push("tool_type = ?");
-            params.push(Box::new(tool_type.clone()));
-        }
-
-        if let Some(ref model) = query.model {
-            where_clauses.push("model = ?");
-            params.push(Box::new(model.clone()));
-        }
-
-        if let Some(ref config_name) = query.config_name {
-            where_clauses.push("config_name = ?");
-            params.push(Box::new(config_name.clone()));
-        }
-
-        if let Some(ref session_id) = query.session_id {
-            where_clauses.push("session_id = ?");
-            params.push(Box::new(session_id.clone()));
-        }
-
-        let where_clause = if where_clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_clauses.join(" AND "))
-        };
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
 
         // 构建完整 SQL
         let sql = format!(
@@ -215,7 +407,10 @@ impl TokenStatsAnalytics {
                 SUM(COALESCE(cache_read_price, 0.0)) as cache_read_price,
                 COUNT(*) as request_count,
                 SUM(CASE WHEN request_status = 'error' THEN 1 ELSE 0 END) as error_count,
-                AVG(response_time_ms) as avg_response_time
+                AVG(response_time_ms) as avg_response_time,
+                SUM(request_bytes) as request_bytes,
+                SUM(response_bytes) as response_bytes,
+                AVG(NULLIF(ttfb_ms, '')) as avg_ttfb
             FROM token_logs
             {}
             GROUP BY {}
@@ -224,7 +419,7 @@ impl TokenStatsAnalytics {
         );
 
         // 执行查询
-        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let param_refs = sql_params(&params);
 
         let db_trends = manager.transaction(|tx| {
             let mut stmt = tx.prepare(&sql)?;
@@ -244,6 +439,9 @@ impl TokenStatsAnalytics {
                         request_count: row.get(10)?,
                         error_count: row.get(11)?,
                         avg_response_time: row.get(12)?,
+                        request_bytes: row.get(13)?,
+                        response_bytes: row.get(14)?,
+                        avg_ttfb: row.get(15)?,
                     })
                 })?
                 .collect::<std::result::Result<Vec<_>, _>>()
@@ -278,13 +476,7 @@ impl TokenStatsAnalytics {
         use std::collections::HashMap;
 
         // 计算时间间隔（毫秒）
-        let interval_ms = match granularity {
-            TimeGranularity::FifteenMinutes => 15 * 60 * 1000,
-            TimeGranularity::ThirtyMinutes => 30 * 60 * 1000,
-            TimeGranularity::Hour => 60 * 60 * 1000,
-            TimeGranularity::TwelveHours => 12 * 60 * 60 * 1000,
-            TimeGranularity::Day => 24 * 60 * 60 * 1000,
-        };
+        let interval_ms = granularity.interval_ms();
 
         // 将数据库结果转换为 HashMap 以便快速查找
         let mut data_map: HashMap<i64, TrendDataPoint> = HashMap::new();
@@ -294,7 +486,7 @@ impl TokenStatsAnalytics {
 
         // 生成完整的时间序列
         let mut result = Vec::new();
-        let mut current_time = (start_time / interval_ms) * interval_ms; // 向下取整到粒度边界
+        let mut current_time = granularity.bucket_start(start_time); // 向下取整到粒度边界
 
         while current_time <= end_time {
             let point = if let Some(existing) = data_map.get(&current_time) {
@@ -316,6 +508,9 @@ impl TokenStatsAnalytics {
                     request_count: 0,
                     error_count: 0,
                     avg_response_time: None,
+                    avg_ttfb: None,
+                    request_bytes: 0,
+                    response_bytes: 0,
                 }
             };
             result.push(point);
@@ -336,37 +531,13 @@ impl TokenStatsAnalytics {
             CostGroupBy::Model => "model",
             CostGroupBy::Config => "config_name",
             CostGroupBy::Session => "session_id",
+            CostGroupBy::Tool => "tool_type",
+            CostGroupBy::RequestKind => REQUEST_KIND_EXPR,
+            CostGroupBy::Category => CATEGORY_EXPR,
         };
 
-        // 构建 WHERE 子句
-        let mut where_clauses = Vec::new();
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-        if let Some(start_time) = query.start_time {
-            where_clauses.push("timestamp >= ?");
-            params.push(Box::new(start_time));
-        }
-
-        if let Some(end_time) = query.end_time {
-            where_clauses.push("timestamp <= ?");
-            params.push(Box::new(end_time));
-        }
-
-        if let Some(ref tool_type) = query.tool_type {
-            where_clauses.push("tool_type = ?");
-            params.push(Box::new(tool_type.clone()));
-        }
-
-        if let Some(ref session_id) = query.session_id {
-            where_clauses.push("session_id = ?");
-            params.push(Box::new(session_id.clone()));
-        }
-
-        let where_clause = if where_clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_clauses.join(" AND "))
-        };
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
 
         // 构建完整 SQL
         let sql = format!(
@@ -376,7 +547,8 @@ impl TokenStatsAnalytics {
                 COUNT(*) as request_count,
                 SUM(input_tokens) as input_tokens,
                 SUM(output_tokens) as output_tokens,
-                AVG(response_time_ms) as avg_response_time
+                AVG(response_time_ms) as avg_response_time,
+                AVG(NULLIF(ttfb_ms, '')) as avg_ttfb
             FROM token_logs
             {}
             GROUP BY {}
@@ -385,7 +557,7 @@ impl TokenStatsAnalytics {
         );
 
         // 执行查询
-        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let param_refs = sql_params(&params);
 
         Ok(manager.transaction(|tx| {
             let mut stmt = tx.prepare(&sql)?;
@@ -398,6 +570,7 @@ impl TokenStatsAnalytics {
                         input_tokens: row.get(3)?,
                         output_tokens: row.get(4)?,
                         avg_response_time: row.get(5)?,
+                        avg_ttfb: row.get(6)?,
                     })
                 })?
                 .collect::<std::result::Result<Vec<_>, _>>()
@@ -405,6 +578,446 @@ impl TokenStatsAnalytics {
             Ok(summaries)
         })?)
     }
+
+    /// 查询结束原因分布（仅统计未失败的请求）
+    pub fn query_stop_reason_distribution(
+        &self,
+        query: &TrendQuery,
+    ) -> Result<Vec<StopReasonStat>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (mut where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        where_clauses.push("request_status != 'failed'");
+
+        let sql = format!(
+            "SELECT
+                COALESCE(NULLIF(stop_reason, ''), 'unknown') as reason,
+                COUNT(*) as request_count
+            FROM token_logs
+            {}
+            GROUP BY reason
+            ORDER BY request_count DESC, reason ASC",
+            where_sql(&where_clauses)
+        );
+
+        let param_refs = sql_params(&params);
+
+        let counts: Vec<(String, i64)> = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(param_refs.as_slice(), |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        let total: i64 = counts.iter().map(|(_, count)| count).sum();
+
+        Ok(counts
+            .into_iter()
+            .map(|(stop_reason, request_count)| StopReasonStat {
+                stop_reason,
+                request_count,
+                percentage: if total > 0 {
+                    request_count as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                },
+            })
+            .collect())
+    }
+
+    /// 查询请求成功率趋势
+    ///
+    /// 按 `granularity` 分桶统计成功/失败请求数，`query.granularity` 被忽略；
+    /// 同时指定开始和结束时间时补齐无请求的时间点
+    pub fn query_success_rate_trend(
+        &self,
+        query: &TrendQuery,
+        granularity: TimeGranularity,
+    ) -> Result<Vec<SuccessRatePoint>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let interval_ms = granularity.interval_ms();
+        let time_expr = granularity.bucket_expr();
+
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
+
+        let sql = format!(
+            "SELECT
+                {} as bucket,
+                SUM(CASE WHEN request_status = 'success' THEN 1 ELSE 0 END) as success_count,
+                SUM(CASE WHEN request_status = 'success' THEN 0 ELSE 1 END) as failed_count
+            FROM token_logs
+            {}
+            GROUP BY bucket
+            ORDER BY bucket",
+            time_expr, where_clause
+        );
+
+        let param_refs = sql_params(&params);
+
+        let points: Vec<SuccessRatePoint> = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    Ok(SuccessRatePoint::new(row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        let (Some(start_time), Some(end_time)) = (query.start_time, query.end_time) else {
+            return Ok(points);
+        };
+
+        // 填充缺失的时间点
+        let mut data_map: std::collections::HashMap<i64, SuccessRatePoint> =
+            points.into_iter().map(|p| (p.timestamp, p)).collect();
+        let mut result = Vec::new();
+        let mut current_time = granularity.bucket_start(start_time);
+        while current_time <= end_time {
+            result.push(
+                data_map
+                    .remove(&current_time)
+                    .unwrap_or_else(|| SuccessRatePoint::new(current_time, 0, 0)),
+            );
+            current_time += interval_ms;
+        }
+
+        Ok(result)
+    }
+
+    /// 查询 Prompt Caching 成本收益
+    ///
+    /// 缓存读取节省按请求逐条计算（见 [`Self::query_request_cache_savings`]），
+    /// 与累计节省汇总口径一致
+    pub fn query_cache_roi(&self, query: &TrendQuery) -> Result<CacheRoi> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
+
+        let sql = format!(
+            "SELECT
+                COALESCE(SUM(cache_creation_tokens), 0) as cache_creation_tokens,
+                COALESCE(SUM(cache_read_tokens), 0) as cache_read_tokens,
+                COALESCE(SUM(NULLIF(cache_write_price, '')), 0.0) as cache_write_cost,
+                COALESCE(SUM(NULLIF(cache_read_price, '')), 0.0) as cache_read_cost
+            FROM token_logs
+            {}",
+            where_clause
+        );
+
+        let param_refs = sql_params(&params);
+
+        let mut roi = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let roi = stmt.query_row(param_refs.as_slice(), |row| {
+                Ok(CacheRoi {
+                    cache_creation_tokens: row.get(0)?,
+                    cache_read_tokens: row.get(1)?,
+                    cache_write_cost: row.get(2)?,
+                    cache_read_cost: row.get(3)?,
+                    ..Default::default()
+                })
+            })?;
+            Ok(roi)
+        })?;
+
+        roi.cache_read_savings = self
+            .query_request_cache_savings(query)?
+            .iter()
+            .map(|request| request.savings)
+            .sum();
+        roi.net_savings = roi.cache_read_savings - roi.cache_write_cost;
+        roi.roi_percentage =
+            (roi.cache_write_cost > 0.0).then(|| roi.net_savings / roi.cache_write_cost * 100.0);
+
+        Ok(roi)
+    }
+
+    /// 按上游聚合用量、成功率与延迟（按请求数降序）
+    pub fn query_upstream_stats(&self, query: &TrendQuery) -> Result<Vec<UpstreamStat>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
+
+        let sql = format!(
+            "SELECT
+                COALESCE(NULLIF(upstream, ''), 'unknown') as upstream_name,
+                COUNT(*) as request_count,
+                SUM(CASE WHEN request_status = 'success' THEN 1 ELSE 0 END) as success_count,
+                SUM(CASE WHEN request_status = 'failed' THEN 1 ELSE 0 END) as failed_count,
+                COALESCE(SUM(input_tokens), 0) as input_tokens,
+                COALESCE(SUM(output_tokens), 0) as output_tokens,
+                COALESCE(SUM(total_cost), 0.0) as total_cost,
+                AVG(response_time_ms) as avg_response_time,
+                AVG(NULLIF(ttfb_ms, '')) as avg_ttfb
+            FROM token_logs
+            {}
+            GROUP BY upstream_name
+            ORDER BY request_count DESC, upstream_name ASC",
+            where_clause
+        );
+
+        let param_refs = sql_params(&params);
+
+        Ok(manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let stats = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    let request_count: i64 = row.get(1)?;
+                    let success_count: i64 = row.get(2)?;
+                    Ok(UpstreamStat {
+                        upstream: row.get(0)?,
+                        request_count,
+                        success_count,
+                        failed_count: row.get(3)?,
+                        success_rate: if request_count > 0 {
+                            success_count as f64 * 100.0 / request_count as f64
+                        } else {
+                            0.0
+                        },
+                        input_tokens: row.get(4)?,
+                        output_tokens: row.get(5)?,
+                        total_cost: row.get(6)?,
+                        avg_response_time: row.get(7)?,
+                        avg_ttfb: row.get(8)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(stats)
+        })?)
+    }
+
+    /// 按客户端 IP 聚合请求数与成本（按成本、请求数降序）
+    pub fn query_usage_by_ip(&self, query: &TrendQuery) -> Result<Vec<IpUsage>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
+
+        let sql = format!(
+            "SELECT
+                COALESCE(NULLIF(client_ip, ''), 'unknown') as ip,
+                COUNT(*) as request_count,
+                SUM(CASE WHEN request_status = 'success' THEN 1 ELSE 0 END) as success_count,
+                COALESCE(SUM(input_tokens), 0) as input_tokens,
+                COALESCE(SUM(output_tokens), 0) as output_tokens,
+                COALESCE(SUM(total_cost), 0.0) as total_cost,
+                MAX(timestamp) as last_request_at
+            FROM token_logs
+            {}
+            GROUP BY ip
+            ORDER BY total_cost DESC, request_count DESC, ip ASC",
+            where_clause
+        );
+
+        let param_refs = sql_params(&params);
+
+        Ok(manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let usages = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    Ok(IpUsage {
+                        client_ip: row.get(0)?,
+                        request_count: row.get(1)?,
+                        success_count: row.get(2)?,
+                        input_tokens: row.get(3)?,
+                        output_tokens: row.get(4)?,
+                        total_cost: row.get(5)?,
+                        last_request_at: row.get(6)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(usages)
+        })?)
+    }
+
+    /// 查询 Prompt Caching 累计节省
+    ///
+    /// 节省按请求逐条计算（见 [`Self::query_request_cache_savings`]），再按模型和整体汇总
+    pub fn query_savings_summary(&self, query: &TrendQuery) -> Result<SavingsSummary> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
+
+        let sql = format!(
+            "SELECT COUNT(*) as request_count, COALESCE(SUM(total_cost), 0.0) as total_cost
+            FROM token_logs
+            {}",
+            where_clause
+        );
+
+        let param_refs = sql_params(&params);
+
+        let (request_count, actual_cost): (i64, f64) = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let totals =
+                stmt.query_row(param_refs.as_slice(), |row| Ok((row.get(0)?, row.get(1)?)))?;
+            Ok(totals)
+        })?;
+
+        let mut summary = SavingsSummary {
+            request_count,
+            actual_cost,
+            ..Default::default()
+        };
+        let mut by_model: Vec<ModelSavings> = Vec::new();
+        for request in self.query_request_cache_savings(query)? {
+            summary.cached_request_count += 1;
+            summary.cache_read_tokens += request.cache_read_tokens;
+            summary.total_savings += request.savings;
+
+            match by_model
+                .iter_mut()
+                .find(|entry| entry.model == request.model)
+            {
+                Some(entry) => {
+                    entry.cached_request_count += 1;
+                    entry.cache_read_tokens += request.cache_read_tokens;
+                    entry.savings += request.savings;
+                }
+                None => by_model.push(ModelSavings {
+                    model: request.model,
+                    cached_request_count: 1,
+                    cache_read_tokens: request.cache_read_tokens,
+                    savings: request.savings,
+                }),
+            }
+        }
+
+        by_model.sort_by(|a, b| {
+            b.savings
+                .partial_cmp(&a.savings)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.model.cmp(&b.model))
+        });
+        summary.by_model = by_model;
+        summary.no_cache_cost = summary.actual_cost + summary.total_savings;
+        summary.savings_percentage = (summary.no_cache_cost > 0.0)
+            .then(|| summary.total_savings / summary.no_cache_cost * 100.0);
+
+        Ok(summary)
+    }
+
+    /// 逐条计算命中缓存读取的请求节省的金额（读取部分按完整输入单价估算）
+    ///
+    /// 输入单价优先取该请求自身的 `input_price / input_tokens`（已含模板倍率）；
+    /// 该请求没有普通输入时退回区间内同模型的平均输入单价，仍无法推算时节省记为 0
+    fn query_request_cache_savings(&self, query: &TrendQuery) -> Result<Vec<RequestCacheSavings>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
+
+        // 各模型的平均输入单价
+        let rate_sql = format!(
+            "SELECT
+                model,
+                SUM(CASE WHEN NULLIF(input_price, '') IS NOT NULL THEN input_tokens ELSE 0 END) as priced_input_tokens,
+                COALESCE(SUM(NULLIF(input_price, '')), 0.0) as input_cost
+            FROM token_logs
+            {}
+            GROUP BY model",
+            where_clause
+        );
+
+        // 命中缓存读取的请求明细
+        let cached_clause = if where_clause.is_empty() {
+            "WHERE cache_read_tokens > 0".to_string()
+        } else {
+            format!("{} AND cache_read_tokens > 0", where_clause)
+        };
+        let request_sql = format!(
+            "SELECT
+                model,
+                input_tokens,
+                NULLIF(input_price, '') as input_price,
+                cache_read_tokens,
+                COALESCE(NULLIF(cache_read_price, ''), 0.0) as cache_read_cost
+            FROM token_logs
+            {}",
+            cached_clause
+        );
+
+        let param_refs = sql_params(&params);
+
+        type RateRow = (String, i64, f64);
+        type RequestRow = (String, i64, Option<f64>, i64, f64);
+        let (rate_rows, request_rows): (Vec<RateRow>, Vec<RequestRow>) =
+            manager.transaction(|tx| {
+                let mut stmt = tx.prepare(&rate_sql)?;
+                let rate_rows = stmt
+                    .query_map(param_refs.as_slice(), |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    })?
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(crate::data::DataError::Database)?;
+
+                let mut stmt = tx.prepare(&request_sql)?;
+                let request_rows = stmt
+                    .query_map(param_refs.as_slice(), |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                        ))
+                    })?
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(crate::data::DataError::Database)?;
+                Ok((rate_rows, request_rows))
+            })?;
+
+        let model_rates: std::collections::HashMap<String, f64> = rate_rows
+            .into_iter()
+            .filter(|(_, priced_input, _)| *priced_input > 0)
+            .map(|(model, priced_input, input_cost)| (model, input_cost / priced_input as f64))
+            .collect();
+
+        Ok(request_rows
+            .into_iter()
+            .map(
+                |(model, input_tokens, input_price, cache_read_tokens, cache_read_cost)| {
+                    let request_rate = input_price
+                        .filter(|_| input_tokens > 0)
+                        .map(|price| price / input_tokens as f64);
+                    let savings = request_rate
+                        .or_else(|| model_rates.get(&model).copied())
+                        .map(|rate| request_cache_savings(cache_read_tokens, cache_read_cost, rate))
+                        .unwrap_or(0.0);
+                    RequestCacheSavings {
+                        model,
+                        cache_read_tokens,
+                        savings,
+                    }
+                },
+            )
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -430,33 +1043,19 @@ mod tests {
             .timestamp_millis();
 
         for i in 0..10 {
-            let log = TokenLog::new(
-                "claude_code".to_string(),
-                base_time - (i * 3600 * 1000), // 每小时一条
-                "127.0.0.1".to_string(),
-                "test_session".to_string(),
-                "default".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                Some(format!("msg_{}", i)),
-                100,
-                50,
-                10,
-                0, // cache_creation_1h_tokens
-                20,
-                0, // reasoning_tokens
-                "success".to_string(),
-                "json".to_string(),
-                None,
-                None,
-                Some(100),
-                Some(0.001),
-                Some(0.002),
-                Some(0.0001),
-                Some(0.0002),
-                None, // reasoning_price
-                0.0033,
-                Some("test_template".to_string()),
-            );
+            let log = TokenLog {
+                message_id: Some(format!("msg_{}", i)),
+                cache_creation_tokens: 10,
+                cache_read_tokens: 20,
+                input_price: Some(0.001),
+                output_price: Some(0.002),
+                cache_write_price: Some(0.0001),
+                cache_read_price: Some(0.0002),
+                total_cost: 0.0033,
+                pricing_template_id: Some("test_template".to_string()),
+                // 每小时一条
+                ..TokenLog::fixture("claude_code", base_time - (i * 3600 * 1000))
+            };
             db.insert_log(&log).unwrap();
         }
 
@@ -479,6 +1078,52 @@ mod tests {
         assert_eq!(trends[0].error_count, 0);
     }
 
+    #[test]
+    fn test_query_trends_body_bytes() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_trends_bytes.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        for (i, (request_bytes, response_bytes)) in [(1000, 4000), (500, 2500)].iter().enumerate() {
+            let log = TokenLog {
+                response_type: "sse".to_string(),
+                ..TokenLog::fixture("claude_code", base_time + i as i64 * 1000)
+            }
+            .with_body_bytes(*request_bytes, *response_bytes);
+            db.insert_log(&log).unwrap();
+        }
+
+        // 旧记录（未填字节数）按 0 计入
+        let legacy = TokenLog {
+            input_tokens: 0,
+            output_tokens: 0,
+            request_status: "failed".to_string(),
+            response_type: "unknown".to_string(),
+            error_type: Some("upstream_error".to_string()),
+            ..TokenLog::fixture("claude_code", base_time + 5000)
+        };
+        db.insert_log(&legacy).unwrap();
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let trends = analytics
+            .query_trends(&TrendQuery {
+                granularity: TimeGranularity::Day,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(trends.len(), 1);
+        assert_eq!(trends[0].request_bytes, 1500);
+        assert_eq!(trends[0].response_bytes, 6500);
+        assert_eq!(trends[0].request_count, 3);
+    }
+
     #[test]
     fn test_query_cost_summary() {
         // 创建临时数据库
@@ -495,33 +1140,19 @@ mod tests {
 
         for session_idx in 0..3 {
             for i in 0..5 {
-                let log = TokenLog::new(
-                    "claude_code".to_string(),
-                    base_time - (i * 1000),
-                    "127.0.0.1".to_string(),
-                    format!("session_{}", session_idx),
-                    "default".to_string(),
-                    "claude-sonnet-4-5-20250929".to_string(),
-                    Some(format!("msg_{}_{}", session_idx, i)),
-                    100,
-                    50,
-                    10,
-                    0, // cache_creation_1h_tokens
-                    20,
-                    0, // reasoning_tokens
-                    "success".to_string(),
-                    "json".to_string(),
-                    None,
-                    None,
-                    Some(100),
-                    Some(0.001),
-                    Some(0.002),
-                    Some(0.0001),
-                    Some(0.0002),
-                    None, // reasoning_price
-                    0.0033,
-                    Some("test_template".to_string()),
-                );
+                let log = TokenLog {
+                    session_id: format!("session_{}", session_idx),
+                    message_id: Some(format!("msg_{}_{}", session_idx, i)),
+                    cache_creation_tokens: 10,
+                    cache_read_tokens: 20,
+                    input_price: Some(0.001),
+                    output_price: Some(0.002),
+                    cache_write_price: Some(0.0001),
+                    cache_read_price: Some(0.0002),
+                    total_cost: 0.0033,
+                    pricing_template_id: Some("test_template".to_string()),
+                    ..TokenLog::fixture("claude_code", base_time - (i * 1000))
+                };
                 db.insert_log(&log).unwrap();
             }
         }
@@ -543,4 +1174,604 @@ mod tests {
             assert!((summary.total_cost - 0.0165).abs() < 0.001); // 0.0033 * 5
         }
     }
+
+    /// 插入一条只关心工具、配置、时间与成本的日志
+    fn insert_cost_log(
+        db: &TokenStatsDb,
+        tool_type: &str,
+        config_name: &str,
+        timestamp: i64,
+        total_cost: f64,
+    ) {
+        let log = TokenLog {
+            config_name: config_name.to_string(),
+            total_cost,
+            ..TokenLog::fixture(tool_type, timestamp)
+        };
+        db.insert_log(&log).unwrap();
+    }
+
+    #[test]
+    fn test_query_cost_summary_by_tool_and_weekly_trend() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_cost_by_tool.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let at = |day: u32, hour: u32| {
+            chrono::Utc
+                .with_ymd_and_hms(2026, 1, day, hour, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        };
+        // 2026-01-05 与 2026-01-12 均为周一
+        insert_cost_log(&db, "claude_code", "work", at(6, 10), 0.01);
+        insert_cost_log(&db, "claude_code", "home", at(11, 23), 0.02);
+        insert_cost_log(&db, "codex", "work", at(13, 8), 0.05);
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+
+        let by_tool = analytics
+            .query_cost_summary(&CostSummaryQuery {
+                group_by: CostGroupBy::Tool,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_tool.len(), 2);
+        assert_eq!(by_tool[0].group_name, "codex");
+        assert_eq!(by_tool[0].request_count, 1);
+        assert_eq!(by_tool[1].group_name, "claude_code");
+        assert_eq!(by_tool[1].request_count, 2);
+        assert_eq!(by_tool[1].input_tokens, 200);
+        assert!((by_tool[1].total_cost - 0.03).abs() < 1e-9);
+
+        let by_config = analytics
+            .query_cost_summary(&CostSummaryQuery {
+                group_by: CostGroupBy::Config,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_config[0].group_name, "work");
+        assert!((by_config[0].total_cost - 0.06).abs() < 1e-9);
+
+        // 周粒度：按周一对齐，指定范围时补齐空周
+        let weekly = analytics
+            .query_trends(&TrendQuery {
+                start_time: Some(at(5, 0)),
+                end_time: Some(at(25, 0)),
+                granularity: TimeGranularity::Week,
+                ..Default::default()
+            })
+            .unwrap();
+        let buckets: Vec<_> = weekly
+            .iter()
+            .map(|p| (p.timestamp, p.request_count))
+            .collect();
+        assert_eq!(buckets, vec![(at(5, 0), 2), (at(12, 0), 1), (at(19, 0), 0)]);
+        assert!((weekly[0].total_cost - 0.03).abs() < 1e-9);
+
+        // 按天聚合
+        let daily = analytics
+            .query_trends(&TrendQuery {
+                tool_type: Some("claude_code".to_string()),
+                granularity: TimeGranularity::Day,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            daily.iter().map(|p| p.timestamp).collect::<Vec<_>>(),
+            vec![at(6, 0), at(11, 0)]
+        );
+        assert_eq!(TimeGranularity::Week.bucket_start(at(11, 23)), at(5, 0));
+    }
+
+    #[test]
+    fn test_query_cost_summary_by_request_kind() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_cost_by_request_kind.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        // 多轮工具调用：两次以 tool_use 结束（命中缓存），最后一次 end_turn 给出回复
+        let cases = [
+            (Some("tool_use"), 1000, 80, 900, 0.002),
+            (Some("tool_use"), 1200, 60, 1100, 0.003),
+            (Some("end_turn"), 1500, 400, 1400, 0.01),
+            (None, 300, 20, 0, 0.001),
+        ];
+        for (seq, (stop_reason, input, output, cache_read, cost)) in cases.into_iter().enumerate() {
+            let log = TokenLog {
+                message_id: Some(format!("msg_{}", seq)),
+                input_tokens: input,
+                output_tokens: output,
+                cache_read_tokens: cache_read,
+                response_type: "sse".to_string(),
+                total_cost: cost,
+                ..TokenLog::fixture("claude_code", base_time + seq as i64 * 1000)
+            }
+            .with_stop_reason(stop_reason.map(String::from));
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let by_kind = analytics
+            .query_cost_summary(&CostSummaryQuery {
+                group_by: CostGroupBy::RequestKind,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(by_kind.len(), 2);
+        let chat = by_kind.iter().find(|s| s.group_name == "chat").unwrap();
+        assert_eq!(chat.request_count, 2);
+        assert_eq!(chat.input_tokens, 1800);
+        assert_eq!(chat.output_tokens, 420);
+        assert!((chat.total_cost - 0.011).abs() < 1e-9);
+
+        let tool_use = by_kind.iter().find(|s| s.group_name == "tool_use").unwrap();
+        assert_eq!(tool_use.request_count, 2);
+        assert_eq!(tool_use.input_tokens, 2200);
+        assert_eq!(tool_use.output_tokens, 140);
+        assert!((tool_use.total_cost - 0.005).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_query_cost_summary_by_category() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_cost_by_category.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        // 2 条 coding、1 条 chat、1 条 embedding，1 条迁移前的旧日志（无分类）
+        let cases = [
+            (Some("coding"), 0.02),
+            (Some("coding"), 0.03),
+            (Some("chat"), 0.01),
+            (Some("embedding"), 0.001),
+            (None, 0.005),
+        ];
+        for (seq, (category, cost)) in cases.into_iter().enumerate() {
+            let log = TokenLog {
+                model: "gpt-5-codex".to_string(),
+                total_cost: cost,
+                ..TokenLog::fixture("codex", 1_700_000_000_000 + seq as i64 * 1000)
+            }
+            .with_category(category.map(String::from));
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let by_category = analytics
+            .query_cost_summary(&CostSummaryQuery {
+                group_by: CostGroupBy::Category,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let names: Vec<&str> = by_category.iter().map(|s| s.group_name.as_str()).collect();
+        assert_eq!(names, vec!["coding", "chat", "other", "embedding"]);
+        assert_eq!(by_category[0].request_count, 2);
+        assert!((by_category[0].total_cost - 0.05).abs() < 1e-9);
+        assert_eq!(by_category[2].request_count, 1);
+    }
+
+    #[test]
+    fn test_query_stop_reason_distribution() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_stop_reason.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        // 6 次 end_turn、3 次 max_tokens、1 次未记录、1 次失败
+        let cases = [
+            (Some("end_turn"), "success", 6),
+            (Some("max_tokens"), "success", 3),
+            (None, "success", 1),
+            (None, "failed", 1),
+        ];
+
+        let mut seq = 0;
+        for (stop_reason, status, count) in cases {
+            for _ in 0..count {
+                seq += 1;
+                let log = TokenLog {
+                    message_id: Some(format!("msg_{}", seq)),
+                    request_status: status.to_string(),
+                    response_type: "sse".to_string(),
+                    ..TokenLog::fixture("claude_code", base_time - seq * 1000)
+                }
+                .with_stop_reason(stop_reason.map(String::from));
+                db.insert_log(&log).unwrap();
+            }
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let stats = analytics
+            .query_stop_reason_distribution(&TrendQuery {
+                tool_type: Some("claude_code".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // 失败请求不计入分布
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].stop_reason, "end_turn");
+        assert_eq!(stats[0].request_count, 6);
+        assert!((stats[0].percentage - 60.0).abs() < 0.001);
+        assert_eq!(stats[1].stop_reason, "max_tokens");
+        assert_eq!(stats[1].request_count, 3);
+        assert!((stats[1].percentage - 30.0).abs() < 0.001);
+        assert_eq!(stats[2].stop_reason, "unknown");
+        assert_eq!(stats[2].request_count, 1);
+
+        // 过滤条件不匹配时返回空
+        let empty = analytics
+            .query_stop_reason_distribution(&TrendQuery {
+                tool_type: Some("codex".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_query_success_rate_trend() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_success_rate.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let day_ms = 24 * 3600 * 1000;
+        let day1 = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 0, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        // 第 1 天：3 成功 1 失败；第 2 天无请求；第 3 天：1 失败
+        let records = [
+            (day1 + 1000, "success"),
+            (day1 + 2000, "success"),
+            (day1 + 3000, "success"),
+            (day1 + 4000, "failed"),
+            (day1 + 2 * day_ms + 1000, "failed"),
+        ];
+        for (i, (timestamp, status)) in records.iter().enumerate() {
+            let log = TokenLog {
+                message_id: Some(format!("msg_{}", i)),
+                request_status: status.to_string(),
+                ..TokenLog::fixture("claude_code", *timestamp)
+            };
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let query = TrendQuery {
+            start_time: Some(day1),
+            end_time: Some(day1 + 3 * day_ms - 1),
+            ..Default::default()
+        };
+        let points = analytics
+            .query_success_rate_trend(&query, TimeGranularity::Day)
+            .unwrap();
+
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].timestamp, day1);
+        assert_eq!(points[0].success_count, 3);
+        assert_eq!(points[0].failed_count, 1);
+        assert!((points[0].success_rate.unwrap() - 75.0).abs() < 0.001);
+        // 无请求的时间点成功率为空
+        assert_eq!(points[1].request_count, 0);
+        assert_eq!(points[1].success_rate, None);
+        assert_eq!(points[2].success_rate, Some(0.0));
+
+        // 不指定时间范围时只返回有数据的时间点
+        let sparse = analytics
+            .query_success_rate_trend(&TrendQuery::default(), TimeGranularity::Day)
+            .unwrap();
+        assert_eq!(sparse.len(), 2);
+    }
+
+    #[test]
+    fn test_query_cache_roi() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_cache_roi.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        // 输入单价 $3/1M：第一次写入 10000 Token 缓存（$0.0375），之后两次各读取 10000 Token（各 $0.003）
+        let records = [
+            (1000, 10000, 0, 0.003, 0.0375, 0.0),
+            (1000, 0, 10000, 0.003, 0.0, 0.003),
+            (1000, 0, 10000, 0.003, 0.0, 0.003),
+        ];
+        for (i, (input, creation, read, input_price, write_price, read_price)) in
+            records.iter().enumerate()
+        {
+            let log = TokenLog {
+                message_id: Some(format!("msg_{}", i)),
+                input_tokens: *input,
+                cache_creation_tokens: *creation,
+                cache_read_tokens: *read,
+                input_price: Some(*input_price),
+                output_price: Some(0.00075),
+                cache_write_price: Some(*write_price),
+                cache_read_price: Some(*read_price),
+                total_cost: input_price + 0.00075 + write_price + read_price,
+                pricing_template_id: Some("test_template".to_string()),
+                ..TokenLog::fixture("claude_code", base_time + i as i64 * 1000)
+            };
+            db.insert_log(&log).unwrap();
+        }
+        // 未定价请求（价格存储为空字符串）不参与输入单价推算
+        let unpriced = TokenLog {
+            message_id: Some("msg_unpriced".to_string()),
+            input_tokens: 5000,
+            ..TokenLog::fixture("claude_code", base_time + 3000)
+        };
+        db.insert_log(&unpriced).unwrap();
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let roi = analytics.query_cache_roi(&TrendQuery::default()).unwrap();
+
+        assert_eq!(roi.cache_creation_tokens, 10000);
+        assert_eq!(roi.cache_read_tokens, 20000);
+        assert!((roi.cache_write_cost - 0.0375).abs() < 1e-9);
+        assert!((roi.cache_read_cost - 0.006).abs() < 1e-9);
+        // 读取 20000 Token 按输入价应付 $0.06，实际 $0.006，节省 $0.054
+        assert!((roi.cache_read_savings - 0.054).abs() < 1e-9);
+        assert!((roi.net_savings - 0.0165).abs() < 1e-9);
+        assert!((roi.roi_percentage.unwrap() - 44.0).abs() < 1e-6);
+
+        // 没有缓存写入时收益率为空
+        let empty = analytics
+            .query_cache_roi(&TrendQuery {
+                tool_type: Some("codex".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(empty, CacheRoi::default());
+    }
+
+    #[test]
+    fn test_query_upstream_stats() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_upstream.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        // relay-a：3 成功 1 失败；relay-b：2 成功；旧数据未记录上游 1 条
+        let cases = [
+            (Some("relay-a.example.com"), "success", 200, 3),
+            (Some("relay-a.example.com"), "failed", 1000, 1),
+            (Some("relay-b.example.com:8443"), "success", 100, 2),
+            (None, "success", 300, 1),
+        ];
+
+        let mut seq = 0;
+        for (upstream, status, response_time, count) in cases {
+            for _ in 0..count {
+                seq += 1;
+                let log = TokenLog {
+                    message_id: Some(format!("msg_{}", seq)),
+                    request_status: status.to_string(),
+                    response_time_ms: Some(response_time),
+                    total_cost: 0.01,
+                    ..TokenLog::fixture("claude_code", base_time - seq * 1000)
+                }
+                .with_upstream(upstream.map(String::from))
+                .with_ttfb_ms((status == "success").then_some(response_time / 2));
+                db.insert_log(&log).unwrap();
+            }
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let stats = analytics
+            .query_upstream_stats(&TrendQuery::default())
+            .unwrap();
+
+        assert_eq!(stats.len(), 3);
+
+        let relay_a = &stats[0];
+        assert_eq!(relay_a.upstream, "relay-a.example.com");
+        assert_eq!(relay_a.request_count, 4);
+        assert_eq!(relay_a.success_count, 3);
+        assert_eq!(relay_a.failed_count, 1);
+        assert!((relay_a.success_rate - 75.0).abs() < 0.001);
+        assert_eq!(relay_a.input_tokens, 400);
+        assert!((relay_a.total_cost - 0.04).abs() < 1e-9);
+        assert!((relay_a.avg_response_time.unwrap() - 400.0).abs() < 0.001);
+        // 未记录 TTFB 的失败请求不参与平均
+        assert!((relay_a.avg_ttfb.unwrap() - 100.0).abs() < 0.001);
+
+        let relay_b = &stats[1];
+        assert_eq!(relay_b.upstream, "relay-b.example.com:8443");
+        assert_eq!(relay_b.request_count, 2);
+        assert!((relay_b.success_rate - 100.0).abs() < 0.001);
+
+        assert_eq!(stats[2].upstream, "unknown");
+        assert_eq!(stats[2].request_count, 1);
+
+        // 过滤条件不匹配时返回空
+        let empty = analytics
+            .query_upstream_stats(&TrendQuery {
+                tool_type: Some("codex".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_query_usage_by_ip() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_ip_usage.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+
+        // 192.168.1.10：claude 2 条 + codex 1 条；192.168.1.20：1 条高成本失败请求；未记录 IP 1 条
+        let cases = [
+            ("192.168.1.10", "claude_code", "success", 0.01, 2),
+            ("192.168.1.10", "codex", "success", 0.02, 1),
+            ("192.168.1.20", "claude_code", "failed", 0.10, 1),
+            ("", "claude_code", "success", 0.005, 1),
+        ];
+
+        let mut seq = 0;
+        for (ip, tool, status, cost, count) in cases {
+            for _ in 0..count {
+                seq += 1;
+                let log = TokenLog {
+                    client_ip: ip.to_string(),
+                    message_id: Some(format!("msg_{}", seq)),
+                    request_status: status.to_string(),
+                    total_cost: cost,
+                    ..TokenLog::fixture(tool, base_time + seq * 1000)
+                };
+                db.insert_log(&log).unwrap();
+            }
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let usages = analytics.query_usage_by_ip(&TrendQuery::default()).unwrap();
+
+        assert_eq!(usages.len(), 3);
+
+        // 按成本降序：单条高成本请求的 IP 排在最前
+        assert_eq!(usages[0].client_ip, "192.168.1.20");
+        assert_eq!(usages[0].request_count, 1);
+        assert_eq!(usages[0].success_count, 0);
+
+        let shared = &usages[1];
+        assert_eq!(shared.client_ip, "192.168.1.10");
+        assert_eq!(shared.request_count, 3);
+        assert_eq!(shared.success_count, 3);
+        assert_eq!(shared.input_tokens, 300);
+        assert_eq!(shared.output_tokens, 150);
+        assert!((shared.total_cost - 0.04).abs() < 1e-9);
+        assert_eq!(shared.last_request_at, base_time + 3000);
+
+        assert_eq!(usages[2].client_ip, "unknown");
+
+        // 按工具过滤
+        let codex = analytics
+            .query_usage_by_ip(&TrendQuery {
+                tool_type: Some("codex".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(codex.len(), 1);
+        assert_eq!(codex[0].client_ip, "192.168.1.10");
+        assert_eq!(codex[0].request_count, 1);
+        assert!((codex[0].total_cost - 0.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_request_cache_savings() {
+        // 10000 Token 按 $3/1M 应付 $0.03，实际读取 $0.003
+        assert!((request_cache_savings(10000, 0.003, 0.000003) - 0.027).abs() < 1e-12);
+        assert_eq!(request_cache_savings(0, 0.0, 0.000003), 0.0);
+        // 读取成本异常高于输入价时不计负节省
+        assert_eq!(request_cache_savings(100, 1.0, 0.000003), 0.0);
+    }
+
+    #[test]
+    fn test_query_savings_summary() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_savings.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        // (模型, 输入 Token, 输入价格, 缓存读取 Token, 读取价格, 总成本)
+        let records = [
+            // 自身输入单价 $3/1M：节省 0.03 - 0.003
+            (
+                "claude-sonnet-4-5",
+                1000,
+                Some(0.003),
+                10000,
+                Some(0.003),
+                0.006,
+            ),
+            // 未命中缓存
+            ("claude-sonnet-4-5", 1000, Some(0.003), 0, None, 0.003),
+            // 无普通输入：按同模型平均单价估算
+            ("claude-sonnet-4-5", 0, None, 10000, Some(0.003), 0.003),
+            // 无法推算单价：计入请求数但不计节省
+            ("claude-haiku-4-5", 0, None, 5000, Some(0.0005), 0.0005),
+        ];
+        for (i, (model, input, input_price, read, read_price, total_cost)) in
+            records.iter().enumerate()
+        {
+            let log = TokenLog {
+                model: model.to_string(),
+                message_id: Some(format!("msg_{}", i)),
+                input_tokens: *input,
+                output_tokens: 0,
+                cache_read_tokens: *read,
+                input_price: *input_price,
+                cache_read_price: *read_price,
+                total_cost: *total_cost,
+                ..TokenLog::fixture("claude_code", 1_000_000 + i as i64)
+            };
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let summary = analytics
+            .query_savings_summary(&TrendQuery::default())
+            .unwrap();
+
+        assert_eq!(summary.request_count, 4);
+        assert_eq!(summary.cached_request_count, 3);
+        assert_eq!(summary.cache_read_tokens, 25000);
+        assert!((summary.actual_cost - 0.0125).abs() < 1e-9);
+        assert!((summary.total_savings - 0.054).abs() < 1e-9);
+        assert!((summary.no_cache_cost - 0.0665).abs() < 1e-9);
+        assert!((summary.savings_percentage.unwrap() - 0.054 / 0.0665 * 100.0).abs() < 1e-6);
+
+        assert_eq!(summary.by_model.len(), 2);
+        assert_eq!(summary.by_model[0].model, "claude-sonnet-4-5");
+        assert_eq!(summary.by_model[0].cached_request_count, 2);
+        assert!((summary.by_model[0].savings - 0.054).abs() < 1e-9);
+        assert_eq!(summary.by_model[1].model, "claude-haiku-4-5");
+        assert_eq!(summary.by_model[1].savings, 0.0);
+
+        // 成本收益与累计节省使用同一口径
+        let roi = analytics.query_cache_roi(&TrendQuery::default()).unwrap();
+        assert!((roi.cache_read_savings - summary.total_savings).abs() < 1e-12);
+
+        // 过滤条件不匹配时为空
+        let empty = analytics
+            .query_savings_summary(&TrendQuery {
+                tool_type: Some("codex".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(empty, SavingsSummary::default());
+    }
 }
@@ -0,0 +1,297 @@
+//! 用量异常检测
+//!
+//! 以过去 7 天（历史不足时取已有部分）的平均小时用量为基线，检测当前小时与当日的
+//! 输入 + 输出 Token 是否超过基线的 N 倍（可能是 API Key 泄露被滥用），
+//! 触发 `usage-anomaly` 事件。基线窗口随时间滚动，每次检测时重新计算
+
+use crate::models::config::UsageAnomalyConfig;
+use crate::services::token_stats::db::TokenStatsDb;
+use crate::services::token_stats::TokenStatsManager;
+use anyhow::Result;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 用量异常告警事件名
+pub const USAGE_ANOMALY_EVENT: &str = "usage-anomaly";
+
+/// 检测周期
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// 基线窗口天数
+const BASELINE_DAYS: i64 = 7;
+
+/// 建立基线所需的最少历史小时数
+const MIN_BASELINE_HOURS: i64 = 24;
+
+const HOUR_MS: i64 = 3600 * 1000;
+
+/// 检测窗口
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnomalyScope {
+    /// 当前小时
+    Hour,
+    /// 当日
+    Day,
+}
+
+/// 用量异常告警
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageAnomaly {
+    pub scope: AnomalyScope,
+    /// 窗口起点（毫秒时间戳）
+    pub window_start: i64,
+    /// 窗口内已用 Token
+    pub tokens: i64,
+    /// 窗口对应的基线用量
+    pub baseline: f64,
+    /// 实际用量 / 基线
+    pub ratio: f64,
+}
+
+/// 某一时刻的用量快照
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UsageSnapshot {
+    pub hour_start: i64,
+    pub hour_tokens: i64,
+    pub day_start: i64,
+    pub day_tokens: i64,
+    /// 平均每小时用量（历史不足时为 None，不做检测）
+    pub hourly_baseline: Option<f64>,
+}
+
+impl UsageSnapshot {
+    /// 从日志统计当前用量，并以当前整点前的滚动窗口计算基线
+    pub fn collect(db: &TokenStatsDb, now_ms: i64, day_start_ms: i64) -> Result<Self> {
+        let hour_start = now_ms - now_ms.rem_euclid(HOUR_MS);
+        let hour_tokens = db.sum_tokens_between(hour_start, now_ms + 1)?;
+        let day_tokens = db.sum_tokens_between(day_start_ms, now_ms + 1)?;
+
+        // 基线窗口不早于最早一条日志所在的整点
+        let (_, oldest, _) = db.get_stats_summary()?;
+        let window_start = match oldest {
+            Some(oldest) => {
+                (hour_start - BASELINE_DAYS * 24 * HOUR_MS).max(oldest - oldest.rem_euclid(HOUR_MS))
+            }
+            None => hour_start,
+        };
+        let hours = (hour_start - window_start) / HOUR_MS;
+        let hourly_baseline = if hours >= MIN_BASELINE_HOURS {
+            Some(db.sum_tokens_between(window_start, hour_start)? as f64 / hours as f64)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            hour_start,
+            hour_tokens,
+            day_start: day_start_ms,
+            day_tokens,
+            hourly_baseline,
+        })
+    }
+}
+
+/// 用量突增检测器（同一窗口只告警一次）
+#[derive(Debug)]
+pub struct AnomalyDetector {
+    multiplier: f64,
+    min_tokens: i64,
+    /// 各窗口最近一次告警的窗口起点
+    alerted: HashMap<AnomalyScope, i64>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: &UsageAnomalyConfig) -> Self {
+        Self {
+            multiplier: config.multiplier.max(1.0),
+            min_tokens: config.min_tokens,
+            alerted: HashMap::new(),
+        }
+    }
+
+    /// 检测快照中的突增
+    pub fn detect(&mut self, snapshot: &UsageSnapshot) -> Vec<UsageAnomaly> {
+        let Some(hourly) = snapshot.hourly_baseline else {
+            return Vec::new();
+        };
+        [
+            (
+                AnomalyScope::Hour,
+                snapshot.hour_start,
+                snapshot.hour_tokens,
+                hourly,
+            ),
+            (
+                AnomalyScope::Day,
+                snapshot.day_start,
+                snapshot.day_tokens,
+                hourly * 24.0,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(scope, window_start, tokens, baseline)| {
+            if tokens < self.min_tokens || (tokens as f64) <= baseline * self.multiplier {
+                return None;
+            }
+            if self.alerted.get(&scope) == Some(&window_start) {
+                return None;
+            }
+            self.alerted.insert(scope, window_start);
+            Some(UsageAnomaly {
+                scope,
+                window_start,
+                tokens,
+                baseline,
+                ratio: tokens as f64 / baseline.max(1.0),
+            })
+        })
+        .collect()
+    }
+}
+
+/// 启动用量异常检测后台任务
+pub fn start_usage_anomaly_monitor(app_handle: AppHandle, config: UsageAnomalyConfig) {
+    tauri::async_runtime::spawn(async move {
+        tracing::info!(
+            multiplier = config.multiplier,
+            "用量异常检测任务已启动（{}秒间隔）",
+            CHECK_INTERVAL.as_secs()
+        );
+        let mut detector = AnomalyDetector::new(&config);
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let now = Local::now();
+            let day_start = now
+                .date_naive()
+                .and_hms_opt(0, 0, 0)
+                .and_then(|t| Local.from_local_datetime(&t).earliest())
+                .map(|t| t.timestamp_millis())
+                .unwrap_or_else(|| now.timestamp_millis());
+            let snapshot =
+                match TokenStatsManager::get().usage_snapshot(now.timestamp_millis(), day_start) {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => {
+                        tracing::warn!(error = ?e, "统计用量失败，跳过本次异常检测");
+                        continue;
+                    }
+                };
+
+            for anomaly in detector.detect(&snapshot) {
+                tracing::warn!(
+                    scope = ?anomaly.scope,
+                    tokens = anomaly.tokens,
+                    baseline = anomaly.baseline,
+                    ratio = anomaly.ratio,
+                    "Token 用量异常突增"
+                );
+                if let Err(e) = app_handle.emit(USAGE_ANOMALY_EVENT, &anomaly) {
+                    tracing::error!(error = ?e, "发送用量异常事件失败");
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::TokenLog;
+    use tempfile::TempDir;
+
+    fn make_log(timestamp: i64, input_tokens: i64, output_tokens: i64) -> TokenLog {
+        TokenLog {
+            input_tokens,
+            output_tokens,
+            ..TokenLog::fixture("claude_code", timestamp)
+        }
+    }
+
+    fn config(multiplier: f64, min_tokens: i64) -> UsageAnomalyConfig {
+        UsageAnomalyConfig {
+            enabled: true,
+            multiplier,
+            min_tokens,
+        }
+    }
+
+    #[test]
+    fn test_detect_spike_once_per_window() {
+        let mut detector = AnomalyDetector::new(&config(5.0, 10_000));
+        let hour = 1_700_000_000_000 - 1_700_000_000_000 % HOUR_MS;
+        let snapshot = |hour_start: i64, hour_tokens: i64, day_tokens: i64| UsageSnapshot {
+            hour_start,
+            hour_tokens,
+            day_start: hour - 10 * HOUR_MS,
+            day_tokens,
+            hourly_baseline: Some(2_000.0),
+        };
+
+        // 未超过基线 N 倍不告警
+        assert!(detector.detect(&snapshot(hour, 9_000, 30_000)).is_empty());
+
+        // 当前小时突增
+        let anomalies = detector.detect(&snapshot(hour, 50_000, 70_000));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].scope, AnomalyScope::Hour);
+        assert_eq!(anomalies[0].ratio, 25.0);
+
+        // 同一小时不重复告警，当日累计超过基线后单独告警
+        let anomalies = detector.detect(&snapshot(hour, 60_000, 300_000));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].scope, AnomalyScope::Day);
+        assert_eq!(anomalies[0].baseline, 48_000.0);
+
+        // 进入下一小时后再次突增重新告警
+        let anomalies = detector.detect(&snapshot(hour + HOUR_MS, 60_000, 360_000));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].window_start, hour + HOUR_MS);
+
+        // 低于最低用量或没有基线时不告警
+        let mut detector = AnomalyDetector::new(&config(5.0, 10_000));
+        assert!(detector.detect(&snapshot(hour, 9_999, 9_999)).is_empty());
+        let mut no_baseline = snapshot(hour, 1_000_000, 1_000_000);
+        no_baseline.hourly_baseline = None;
+        assert!(detector.detect(&no_baseline).is_empty());
+    }
+
+    #[test]
+    fn test_collect_rolling_baseline() {
+        let dir = TempDir::new().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("token_stats.db"));
+        db.init_table().unwrap();
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let hour_start = now - now % HOUR_MS;
+
+        // 历史不足 24 小时时不建立基线
+        db.insert_log(&make_log(hour_start - 2 * HOUR_MS, 800, 200))
+            .unwrap();
+        let snapshot = UsageSnapshot::collect(&db, now, hour_start).unwrap();
+        assert_eq!(snapshot.hourly_baseline, None);
+
+        // 过去 48 小时每小时 1000 Token，当前小时突增到 100000
+        for h in 3..=48 {
+            db.insert_log(&make_log(hour_start - h * HOUR_MS, 800, 200))
+                .unwrap();
+        }
+        db.insert_log(&make_log(now, 60_000, 40_000)).unwrap();
+
+        let snapshot = UsageSnapshot::collect(&db, now, hour_start).unwrap();
+        assert_eq!(snapshot.hour_tokens, 100_000);
+        assert_eq!(snapshot.day_tokens, 100_000);
+        // 基线窗口从最早日志所在整点起算：47 条历史分布在 48 小时内
+        assert_eq!(snapshot.hourly_baseline, Some(47_000.0 / 48.0));
+
+        // 当前小时超过基线 5 倍，当日累计尚未超过日基线
+        let anomalies = AnomalyDetector::new(&config(5.0, 10_000)).detect(&snapshot);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].scope, AnomalyScope::Hour);
+    }
+}
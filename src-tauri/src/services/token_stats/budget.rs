@@ -0,0 +1,155 @@
+//! 月度成本目标进度
+//!
+//! 统计本自然月（本地时区）已花费的成本并与全局配置的月度目标对比，
+//! 以本月至今的平均花费速率推算本月总花费与预计超出目标的时间
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// 成本目标进度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetProgress {
+    /// 本期起点（毫秒时间戳）
+    pub period_start: i64,
+    /// 本期终点（毫秒时间戳，不含）
+    pub period_end: i64,
+    /// 本期已花费（USD）
+    pub spent: f64,
+    /// 月度目标（USD，未设置时为 None）
+    pub target: Option<f64>,
+    /// 剩余额度（USD，已超出时为负数）
+    pub remaining: Option<f64>,
+    /// 已花费占目标的百分比
+    pub percent: Option<f64>,
+    /// 按当前速率推算的本期总花费（USD）
+    pub projected_spend: f64,
+    /// 是否已超出目标
+    pub exceeded: bool,
+    /// 按当前速率预计超出目标的时间（毫秒时间戳；本期内不会超出或已超出时为 None）
+    pub projected_overrun_at: Option<i64>,
+}
+
+impl BudgetProgress {
+    /// 根据本期已花费计算进度
+    pub fn calculate(
+        spent: f64,
+        target: Option<f64>,
+        period_start: i64,
+        period_end: i64,
+        now_ms: i64,
+    ) -> Self {
+        // 本期已过时长不足 1 毫秒时不推算速率
+        let elapsed = now_ms.clamp(period_start, period_end) - period_start;
+        let rate = (elapsed > 0).then(|| spent / elapsed as f64);
+        let projected_spend = rate
+            .map(|rate| rate * (period_end - period_start) as f64)
+            .unwrap_or(spent);
+
+        let target = target.filter(|t| *t > 0.0);
+        let exceeded = target.is_some_and(|t| spent >= t);
+        let projected_overrun_at = match (target, rate) {
+            (Some(t), Some(rate)) if !exceeded && rate > 0.0 => {
+                let at = now_ms + ((t - spent) / rate).ceil() as i64;
+                (at < period_end).then_some(at)
+            }
+            _ => None,
+        };
+
+        Self {
+            period_start,
+            period_end,
+            spent,
+            target,
+            remaining: target.map(|t| t - spent),
+            percent: target.map(|t| spent / t * 100.0),
+            projected_spend,
+            exceeded,
+            projected_overrun_at,
+        }
+    }
+}
+
+/// 指定时刻所在自然月的起止时间（毫秒时间戳，终点不含）
+pub fn month_bounds(now: DateTime<Local>) -> (i64, i64) {
+    let first_day = NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap_or(now.date_naive());
+    let next_month = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first_day);
+    (local_midnight_ms(first_day), local_midnight_ms(next_month))
+}
+
+/// 本地时区某日 00:00 的毫秒时间戳
+pub(super) fn local_midnight_ms(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|t| Local.from_local_datetime(&t).earliest())
+        .map(|t| t.timestamp_millis())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: i64 = 24 * 3600 * 1000;
+
+    #[test]
+    fn test_budget_progress_projects_overrun() {
+        // 30 天的周期，第 10 天已花费 50，目标 100：日均 5，预计第 20 天超出
+        let progress = BudgetProgress::calculate(50.0, Some(100.0), 0, 30 * DAY_MS, 10 * DAY_MS);
+        assert_eq!(progress.remaining, Some(50.0));
+        assert_eq!(progress.percent, Some(50.0));
+        assert!((progress.projected_spend - 150.0).abs() < 1e-9);
+        assert!(!progress.exceeded);
+        assert_eq!(progress.projected_overrun_at, Some(20 * DAY_MS));
+
+        // 速率较低，本期内不会超出
+        let progress = BudgetProgress::calculate(20.0, Some(100.0), 0, 30 * DAY_MS, 10 * DAY_MS);
+        assert!((progress.projected_spend - 60.0).abs() < 1e-9);
+        assert_eq!(progress.projected_overrun_at, None);
+    }
+
+    #[test]
+    fn test_budget_progress_exceeded_and_without_target() {
+        let progress = BudgetProgress::calculate(120.0, Some(100.0), 0, 30 * DAY_MS, 10 * DAY_MS);
+        assert!(progress.exceeded);
+        assert_eq!(progress.remaining, Some(-20.0));
+        assert_eq!(progress.percent, Some(120.0));
+        assert_eq!(progress.projected_overrun_at, None);
+
+        // 未设置目标（或目标不大于 0）时只返回花费与推算
+        for target in [None, Some(0.0)] {
+            let progress = BudgetProgress::calculate(10.0, target, 0, 30 * DAY_MS, 10 * DAY_MS);
+            assert_eq!(progress.target, None);
+            assert_eq!(progress.remaining, None);
+            assert_eq!(progress.percent, None);
+            assert!(!progress.exceeded);
+            assert!((progress.projected_spend - 30.0).abs() < 1e-9);
+        }
+
+        // 本期刚开始时不推算速率
+        let progress = BudgetProgress::calculate(0.0, Some(100.0), 0, 30 * DAY_MS, 0);
+        assert_eq!(progress.projected_spend, 0.0);
+        assert_eq!(progress.projected_overrun_at, None);
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let now = Local.with_ymd_and_hms(2025, 12, 15, 10, 30, 0).unwrap();
+        let (start, end) = month_bounds(now);
+        assert_eq!(
+            start,
+            Local
+                .with_ymd_and_hms(2025, 12, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        );
+        assert_eq!(
+            end,
+            Local
+                .with_ymd_and_hms(2026, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        );
+        assert!(start <= now.timestamp_millis() && now.timestamp_millis() < end);
+    }
+}
@@ -0,0 +1,261 @@
+//! Token 统计自定义查询（受限只读）
+//!
+//! 供高级用户对统计库执行自定义 SELECT 查询，多层防护：
+//! 1. 语句校验：仅允许单条 SELECT / WITH 查询（字符串字面量内的内容不参与校验）
+//! 2. 只读连接：以 READ_ONLY 打开数据库并开启 `query_only`
+//! 3. 预编译后再次确认语句为只读
+//! 4. 执行超时：通过 progress handler 在超过时限后中断查询
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 单次查询返回的最大行数
+pub const MAX_QUERY_ROWS: usize = 1000;
+
+/// 单次查询的最长执行时间
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// progress handler 的回调间隔（虚拟机指令数）
+const PROGRESS_HANDLER_OPS: i32 = 10_000;
+
+/// 自定义查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// 是否因超过行数上限被截断
+    pub truncated: bool,
+}
+
+/// 校验 SQL 仅为单条只读查询，返回去掉末尾分号后的语句
+pub fn validate_select(sql: &str) -> Result<&str> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if sql.is_empty() {
+        return Err(anyhow!("查询语句不能为空"));
+    }
+
+    // 写操作由只读连接与预编译后的只读检查拦截，这里只校验语句结构
+    let code = strip_string_literals(sql);
+    if code.contains(';') {
+        return Err(anyhow!("仅允许执行单条查询语句"));
+    }
+
+    let first_word = code
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .find(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase());
+    match first_word.as_deref() {
+        Some("select") | Some("with") => Ok(sql),
+        _ => Err(anyhow!("仅允许 SELECT 查询")),
+    }
+}
+
+/// 将字符串字面量与引号标识符的内容替换为空格（引号本身保留）
+fn strip_string_literals(sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    for c in sql.chars() {
+        match quote {
+            // SQL 以连续两个引号转义引号，等价于先结束再开始字面量
+            Some(q) if c == q => {
+                quote = None;
+                result.push(c);
+            }
+            Some(_) => result.push(' '),
+            None => {
+                if matches!(c, '\'' | '"' | '`') {
+                    quote = Some(c);
+                }
+                result.push(c);
+            }
+        }
+    }
+    result
+}
+
+/// 对统计库执行只读查询（超过 [`QUERY_TIMEOUT`] 时中断）
+pub fn run_readonly_query(db_path: &Path, sql: &str) -> Result<QueryResult> {
+    run_readonly_query_with_timeout(db_path, sql, QUERY_TIMEOUT)
+}
+
+fn run_readonly_query_with_timeout(
+    db_path: &Path,
+    sql: &str,
+    timeout: Duration,
+) -> Result<QueryResult> {
+    let sql = validate_select(sql)?;
+
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .context("打开统计数据库失败")?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.execute_batch("PRAGMA query_only = ON")?;
+
+    // 超过时限后 progress handler 返回 true，SQLite 中断当前语句
+    let deadline = Instant::now() + timeout;
+    conn.progress_handler(
+        PROGRESS_HANDLER_OPS,
+        Some(move || Instant::now() >= deadline),
+    );
+    let timed_out = || Instant::now() >= deadline;
+
+    let mut stmt = conn.prepare(sql).context("查询语句无效")?;
+    if !stmt.readonly() {
+        return Err(anyhow!("仅允许只读查询"));
+    }
+
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    let mut rows = Vec::new();
+    let mut truncated = false;
+
+    let mut cursor = stmt.query([]).context("执行查询失败")?;
+    loop {
+        let row = match cursor.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(_) if timed_out() => {
+                return Err(anyhow!("查询超时（超过 {} 秒）", timeout.as_secs()));
+            }
+            Err(e) => return Err(anyhow::Error::new(e).context("读取查询结果失败")),
+        };
+        if rows.len() >= MAX_QUERY_ROWS {
+            truncated = true;
+            break;
+        }
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(value_to_json))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.push(values);
+    }
+
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+fn value_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        ValueRef::Text(s) => String::from_utf8_lossy(s).into_owned().into(),
+        ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_db(dir: &TempDir) -> std::path::PathBuf {
+        let path = dir.path().join("token_stats.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE token_logs (id INTEGER PRIMARY KEY, model TEXT, total_cost REAL);
+             INSERT INTO token_logs (model, total_cost) VALUES ('claude', 0.5), ('gpt', 1.25);",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_validate_rejects_non_select_statements() {
+        for sql in [
+            "DELETE FROM token_logs",
+            "update token_logs set model = 'x'",
+            "DROP TABLE token_logs",
+            "INSERT INTO token_logs (model) VALUES ('x')",
+            "PRAGMA table_info(token_logs)",
+            "ATTACH DATABASE 'x.db' AS x",
+            "SELECT 1; DROP TABLE token_logs",
+            "SELECT ';'; DROP TABLE token_logs",
+            "   ",
+        ] {
+            assert!(validate_select(sql).is_err(), "应拒绝: {}", sql);
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_select_and_with() {
+        assert_eq!(
+            validate_select("  SELECT model FROM token_logs;  ").unwrap(),
+            "SELECT model FROM token_logs"
+        );
+        assert!(validate_select("WITH t AS (SELECT 1 AS n) SELECT n FROM t").is_ok());
+        assert!(validate_select("select replace(model, 'a', 'b') from token_logs").is_ok());
+        // 字符串字面量中的关键字与分号不影响校验
+        assert!(validate_select(
+            "SELECT model FROM token_logs WHERE model != 'update; drop table' AND model != 'it''s'"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_run_readonly_query_returns_rows() {
+        let dir = TempDir::new().unwrap();
+        let path = setup_db(&dir);
+
+        let result = run_readonly_query(
+            &path,
+            "SELECT model, total_cost FROM token_logs ORDER BY id",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["model", "total_cost"]);
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[1][0], "gpt");
+        assert_eq!(result.rows[1][1], 1.25);
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn test_run_readonly_query_rejects_writes_and_keeps_data() {
+        let dir = TempDir::new().unwrap();
+        let path = setup_db(&dir);
+
+        assert!(run_readonly_query(&path, "DELETE FROM token_logs").is_err());
+        assert!(run_readonly_query(&path, "WITH t AS (SELECT 1) DELETE FROM token_logs").is_err());
+
+        let result = run_readonly_query(&path, "SELECT COUNT(*) FROM token_logs").unwrap();
+        assert_eq!(result.rows[0][0], 2);
+    }
+
+    #[test]
+    fn test_run_readonly_query_truncates_rows() {
+        let dir = TempDir::new().unwrap();
+        let path = setup_db(&dir);
+
+        let result = run_readonly_query(
+            &path,
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n LIMIT 5000) SELECT x FROM n",
+        )
+        .unwrap();
+        assert_eq!(result.rows.len(), MAX_QUERY_ROWS);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn test_run_readonly_query_times_out() {
+        let dir = TempDir::new().unwrap();
+        let path = setup_db(&dir);
+
+        // 无限递归且只取聚合结果，不会因行数上限提前结束
+        let err = run_readonly_query_with_timeout(
+            &path,
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT COUNT(*) FROM n",
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("查询超时"), "{}", err);
+    }
+}
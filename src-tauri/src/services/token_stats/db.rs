@@ -1,8 +1,227 @@
-use crate::data::DataManager;
-use crate::models::token_stats::{SessionStats, TokenLog, TokenLogsPage, TokenStatsQuery};
+use crate::data::{DataError, DataManager};
+use crate::models::token_stats::{
+    ImportSummary, SessionStats, TokenLog, TokenLogsPage, TokenStatsQuery,
+};
+use crate::services::pricing::PricingManager;
+use crate::services::token_stats::logger::billable_output_tokens;
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
+/// 当前 schema 版本（与 `MIGRATIONS` 最后一项一致）
+pub const SCHEMA_VERSION: i64 = 11;
+
+/// v1 建表语句（最初发布的字段，后续字段均由迁移追加）
+const CREATE_TABLE_V1: &str = "CREATE TABLE IF NOT EXISTS token_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tool_type TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    client_ip TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    config_name TEXT NOT NULL,
+    model TEXT NOT NULL,
+    message_id TEXT,
+
+    -- Token 数量
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+
+    -- 请求状态
+    request_status TEXT NOT NULL DEFAULT 'success',
+    response_type TEXT NOT NULL DEFAULT 'unknown',
+    error_type TEXT,
+    error_detail TEXT,
+
+    -- 各部分的价格（USD）
+    input_price REAL,
+    output_price REAL,
+    cache_write_price REAL,
+    cache_read_price REAL,
+
+    -- 总成本（USD）
+    total_cost REAL NOT NULL DEFAULT 0.0,
+
+    -- 响应时间
+    response_time_ms INTEGER,
+
+    -- 价格模板 ID
+    pricing_template_id TEXT,
+
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+)";
+
+/// schema 迁移：为 token_logs 追加列
+struct Migration {
+    version: i64,
+    description: &'static str,
+    /// 追加的列（列名，列定义）；已有数据的默认值由列定义决定
+    columns: &'static [(&'static str, &'static str)],
+}
+
+/// 有序迁移列表（只能在末尾追加，已发布的迁移不可修改）
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 2,
+        description: "reasoning_tokens / reasoning_price",
+        columns: &[
+            ("reasoning_tokens", "INTEGER NOT NULL DEFAULT 0"),
+            ("reasoning_price", "REAL"),
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "cache_creation_1h_tokens（区分 5m/1h 缓存写入）",
+        columns: &[("cache_creation_1h_tokens", "INTEGER NOT NULL DEFAULT 0")],
+    },
+    Migration {
+        version: 4,
+        description: "stop_reason（结束原因分布统计）",
+        columns: &[("stop_reason", "TEXT")],
+    },
+    Migration {
+        version: 5,
+        description: "request_bytes / response_bytes（带宽统计）",
+        columns: &[
+            ("request_bytes", "INTEGER NOT NULL DEFAULT 0"),
+            ("response_bytes", "INTEGER NOT NULL DEFAULT 0"),
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "upstream（按上游统计）",
+        columns: &[("upstream", "TEXT")],
+    },
+    Migration {
+        version: 7,
+        description: "image_count / image_bytes（多模态用量统计）",
+        columns: &[
+            ("image_count", "INTEGER NOT NULL DEFAULT 0"),
+            ("image_bytes", "INTEGER NOT NULL DEFAULT 0"),
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "downgraded_from（过载降级标记）",
+        columns: &[("downgraded_from", "TEXT")],
+    },
+    Migration {
+        version: 9,
+        description: "category（请求自动分类）",
+        columns: &[("category", "TEXT")],
+    },
+    Migration {
+        version: 10,
+        description: "ttfb_ms（首字节延迟）",
+        columns: &[("ttfb_ms", "INTEGER")],
+    },
+    Migration {
+        version: 11,
+        description: "is_estimated（兜底估算成本标记）",
+        columns: &[("is_estimated", "INTEGER NOT NULL DEFAULT 0")],
+    },
+];
+
+/// 在事务中执行单个迁移（已存在的列跳过）并写回版本号
+fn apply_migration(tx: &rusqlite::Transaction, migration: &Migration) -> rusqlite::Result<()> {
+    for (column, definition) in migration.columns {
+        let exists: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('token_logs') WHERE name = ?1",
+            [*column],
+            |row| row.get(0),
+        )?;
+        if exists == 0 {
+            tx.execute_batch(&format!(
+                "ALTER TABLE token_logs ADD COLUMN {} {}",
+                column, definition
+            ))?;
+        }
+    }
+    tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))
+}
+
+/// 日志过滤条件（日志列表与统计分析共用）
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LogFilter<'a> {
+    pub tool_type: Option<&'a str>,
+    pub session_id: Option<&'a str>,
+    pub config_name: Option<&'a str>,
+    pub model: Option<&'a str>,
+    /// 开始时间戳（毫秒）
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒）
+    pub end_time: Option<i64>,
+    /// 仅失败请求（request_status 为 failed 或 error_type 为 parse_error）
+    pub errors_only: bool,
+}
+
+impl<'a> From<&'a TokenStatsQuery> for LogFilter<'a> {
+    fn from(query: &'a TokenStatsQuery) -> Self {
+        Self {
+            tool_type: query.tool_type.as_deref(),
+            session_id: query.session_id.as_deref(),
+            config_name: query.config_name.as_deref(),
+            model: None,
+            start_time: query.start_time,
+            end_time: query.end_time,
+            errors_only: query.errors_only,
+        }
+    }
+}
+
+/// 根据过滤条件构建 WHERE 条件及对应参数（不含分页）
+///
+/// 时间戳以文本参数绑定，与 INTEGER 列比较时 SQLite 会按数值比较
+pub(crate) fn build_log_filters(filter: &LogFilter) -> (Vec<&'static str>, Vec<String>) {
+    let mut where_clauses = Vec::new();
+    let mut params = Vec::new();
+
+    if let Some(tool_type) = filter.tool_type {
+        where_clauses.push("tool_type = ?");
+        params.push(tool_type.to_string());
+    }
+
+    if let Some(session_id) = filter.session_id {
+        where_clauses.push("session_id = ?");
+        params.push(session_id.to_string());
+    }
+
+    if let Some(config_name) = filter.config_name {
+        where_clauses.push("config_name = ?");
+        params.push(config_name.to_string());
+    }
+
+    if let Some(model) = filter.model {
+        where_clauses.push("model = ?");
+        params.push(model.to_string());
+    }
+
+    if let Some(start_time) = filter.start_time {
+        where_clauses.push("timestamp >= ?");
+        params.push(start_time.to_string());
+    }
+
+    if let Some(end_time) = filter.end_time {
+        where_clauses.push("timestamp <= ?");
+        params.push(end_time.to_string());
+    }
+
+    if filter.errors_only {
+        where_clauses.push("(request_status = 'failed' OR error_type = 'parse_error')");
+    }
+
+    (where_clauses, params)
+}
+
+/// 拼接 WHERE 子句（无条件时返回空字符串）
+pub(crate) fn where_sql(where_clauses: &[&str]) -> String {
+    if where_clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", where_clauses.join(" AND "))
+    }
+}
+
 /// Token统计数据库操作层
 pub struct TokenStatsDb {
     db_path: PathBuf,
@@ -25,51 +244,9 @@ impl TokenStatsDb {
             .execute_raw("PRAGMA journal_mode=WAL")
             .context("Failed to enable WAL mode")?;
 
-        // 创建表（Schema v3 - 包含成本分析字段）
+        // 创建表（v1 schema，后续字段由迁移追加）
         manager
-            .execute_raw(
-                "CREATE TABLE IF NOT EXISTS token_logs (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    tool_type TEXT NOT NULL,
-                    timestamp INTEGER NOT NULL,
-                    client_ip TEXT NOT NULL,
-                    session_id TEXT NOT NULL,
-                    config_name TEXT NOT NULL,
-                    model TEXT NOT NULL,
-                    message_id TEXT,
-
-                    -- Token 数量
-                    input_tokens INTEGER NOT NULL DEFAULT 0,
-                    output_tokens INTEGER NOT NULL DEFAULT 0,
-                    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
-                    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
-                    reasoning_tokens INTEGER NOT NULL DEFAULT 0,
-
-                    -- 请求状态
-                    request_status TEXT NOT NULL DEFAULT 'success',
-                    response_type TEXT NOT NULL DEFAULT 'unknown',
-                    error_type TEXT,
-                    error_detail TEXT,
-
-                    -- 各部分的价格（USD）
-                    input_price REAL,
-                    output_price REAL,
-                    cache_write_price REAL,
-                    cache_read_price REAL,
-                    reasoning_price REAL,
-
-                    -- 总成本（USD）
-                    total_cost REAL NOT NULL DEFAULT 0.0,
-
-                    -- 响应时间
-                    response_time_ms INTEGER,
-
-                    -- 价格模板 ID
-                    pricing_template_id TEXT,
-
-                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-                )",
-            )
+            .execute_raw(CREATE_TABLE_V1)
             .context("Failed to create token_logs table")?;
 
         // 创建索引
@@ -123,88 +300,55 @@ impl TokenStatsDb {
             )
             .context("Failed to create tool_model index")?;
 
-        // 数据库迁移：添加 reasoning_tokens 和 reasoning_price 字段（如果不存在）
-        // 这是为了兼容旧版本数据库
-        self.migrate_add_reasoning_fields()?;
-
-        // 数据库迁移：添加 cache_creation_1h_tokens 字段（区分 5m/1h 缓存）
-        self.migrate_add_cache_1h_field()?;
+        // 按 user_version 逐步升级 schema
+        self.migrate()?;
 
         Ok(())
     }
 
-    /// 迁移：添加 reasoning_tokens 和 reasoning_price 字段
-    fn migrate_add_reasoning_fields(&self) -> Result<()> {
+    /// 执行 schema 迁移
+    ///
+    /// 读取 `PRAGMA user_version`，按顺序执行版本号更大的迁移，每个迁移在独立事务中完成并写回版本号。
+    /// 引入版本号之前的数据库 user_version 为 0，但可能已有部分列，因此追加列前先检查是否存在
+    fn migrate(&self) -> Result<()> {
         let manager = DataManager::global()
             .sqlite(&self.db_path)
             .context("Failed to get SQLite manager for migration")?;
 
-        // 检查 reasoning_tokens 字段是否存在
-        let check_query =
-            "SELECT COUNT(*) FROM pragma_table_info('token_logs') WHERE name='reasoning_tokens'";
-        let rows = manager
-            .query(check_query, &[])
-            .context("Failed to check reasoning_tokens column")?;
-
-        let exists = rows
-            .first()
-            .and_then(|row| row.values.first())
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0)
-            > 0;
-
-        if !exists {
-            eprintln!("Migrating database: adding reasoning_tokens and reasoning_price columns");
-
-            // 添加 reasoning_tokens 字段
-            manager
-                .execute_raw(
-                    "ALTER TABLE token_logs ADD COLUMN reasoning_tokens INTEGER NOT NULL DEFAULT 0",
-                )
-                .context("Failed to add reasoning_tokens column")?;
+        let current = self.schema_version()?;
+        if current > SCHEMA_VERSION {
+            eprintln!(
+                "Database schema v{} is newer than supported v{}, skipping migration",
+                current, SCHEMA_VERSION
+            );
+            return Ok(());
+        }
 
-            // 添加 reasoning_price 字段
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            eprintln!(
+                "Migrating database to v{}: {}",
+                migration.version, migration.description
+            );
             manager
-                .execute_raw("ALTER TABLE token_logs ADD COLUMN reasoning_price REAL")
-                .context("Failed to add reasoning_price column")?;
-
-            eprintln!("Database migration completed successfully");
+                .transaction(|tx| apply_migration(tx, migration).map_err(DataError::Database))
+                .with_context(|| format!("Failed to migrate database to v{}", migration.version))?;
         }
 
         Ok(())
     }
 
-    /// 迁移：添加 cache_creation_1h_tokens 字段（区分 5m/1h 缓存写入）
-    fn migrate_add_cache_1h_field(&self) -> Result<()> {
+    /// 当前数据库的 schema 版本（`PRAGMA user_version`）
+    pub fn schema_version(&self) -> Result<i64> {
         let manager = DataManager::global()
             .sqlite(&self.db_path)
-            .context("Failed to get SQLite manager for cache_1h migration")?;
-
-        let check_query = "SELECT COUNT(*) FROM pragma_table_info('token_logs') WHERE name='cache_creation_1h_tokens'";
-        let rows = manager
-            .query(check_query, &[])
-            .context("Failed to check cache_creation_1h_tokens column")?;
-
-        let exists = rows
-            .first()
-            .and_then(|row| row.values.first())
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0)
-            > 0;
-
-        if !exists {
-            eprintln!("Migrating database: adding cache_creation_1h_tokens column");
-
-            manager
-                .execute_raw(
-                    "ALTER TABLE token_logs ADD COLUMN cache_creation_1h_tokens INTEGER NOT NULL DEFAULT 0",
-                )
-                .context("Failed to add cache_creation_1h_tokens column")?;
-
-            eprintln!("Database cache_1h migration completed successfully");
-        }
+            .context("Failed to get SQLite manager")?;
 
-        Ok(())
+        // 不走 query 缓存，迁移过程中版本号会变化
+        let version = manager.transaction(|tx| {
+            tx.query_row("PRAGMA user_version", [], |row| row.get(0))
+                .map_err(DataError::Database)
+        })?;
+        Ok(version)
     }
 
     /// 插入单条日志记录
@@ -247,6 +391,16 @@ impl TokenStatsDb {
                 .unwrap_or_default(),
             log.total_cost.to_string(),
             log.pricing_template_id.clone().unwrap_or_default(),
+            log.stop_reason.clone().unwrap_or_default(),
+            log.request_bytes.to_string(),
+            log.response_bytes.to_string(),
+            log.upstream.clone().unwrap_or_default(),
+            log.image_count.to_string(),
+            log.image_bytes.to_string(),
+            log.downgraded_from.clone().unwrap_or_default(),
+            log.category.clone().unwrap_or_default(),
+            log.ttfb_ms.map(|v| v.to_string()).unwrap_or_default(),
+            i64::from(log.is_estimated).to_string(),
         ];
 
         let params_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
@@ -259,8 +413,9 @@ impl TokenStatsDb {
                     cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens, reasoning_tokens,
                     request_status, response_type, error_type, error_detail,
                     response_time_ms, input_price, output_price, cache_write_price, cache_read_price, reasoning_price,
-                    total_cost, pricing_template_id
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+                    total_cost, pricing_template_id, stop_reason, request_bytes, response_bytes, upstream,
+                    image_count, image_bytes, downgraded_from, category, ttfb_ms, is_estimated
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35)",
                 &params_refs,
             )
             .context("Failed to insert token log")?;
@@ -325,6 +480,16 @@ impl TokenStatsDb {
                 .unwrap_or_default(),
             log.total_cost.to_string(),
             log.pricing_template_id.clone().unwrap_or_default(),
+            log.stop_reason.clone().unwrap_or_default(),
+            log.request_bytes.to_string(),
+            log.response_bytes.to_string(),
+            log.upstream.clone().unwrap_or_default(),
+            log.image_count.to_string(),
+            log.image_bytes.to_string(),
+            log.downgraded_from.clone().unwrap_or_default(),
+            log.category.clone().unwrap_or_default(),
+            log.ttfb_ms.map(|v| v.to_string()).unwrap_or_default(),
+            i64::from(log.is_estimated).to_string(),
         ];
 
         let params_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
@@ -337,8 +502,9 @@ impl TokenStatsDb {
                     cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens, reasoning_tokens,
                     request_status, response_type, error_type, error_detail,
                     response_time_ms, input_price, output_price, cache_write_price, cache_read_price, reasoning_price,
-                    total_cost, pricing_template_id
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+                    total_cost, pricing_template_id, stop_reason, request_bytes, response_bytes, upstream,
+                    image_count, image_bytes, downgraded_from, category, ttfb_ms, is_estimated
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35)",
                 &params_refs,
             )
             .context("Failed to insert token log")?;
@@ -371,7 +537,11 @@ impl TokenStatsDb {
                     COALESCE(SUM(cache_creation_tokens), 0) as total_cache_creation,
                     COALESCE(SUM(cache_read_tokens), 0) as total_cache_read,
                     COALESCE(SUM(reasoning_tokens), 0) as total_reasoning,
-                    COUNT(*) as request_count
+                    COUNT(*) as request_count,
+                    COALESCE(SUM(CASE WHEN response_type = 'sse' AND request_status = 'success'
+                        AND response_time_ms > 0 THEN output_tokens ELSE 0 END), 0) as stream_output,
+                    COALESCE(SUM(CASE WHEN response_type = 'sse' AND request_status = 'success'
+                        AND response_time_ms > 0 THEN response_time_ms ELSE 0 END), 0) as stream_time_ms
                 FROM token_logs
                 WHERE session_id = ?1 AND tool_type = ?2",
                 &[session_id, tool_type],
@@ -387,6 +557,11 @@ impl TokenStatsDb {
             total_cache_read: row.values.get(3).and_then(|v| v.as_i64()).unwrap_or(0),
             total_reasoning: row.values.get(4).and_then(|v| v.as_i64()).unwrap_or(0),
             request_count: row.values.get(5).and_then(|v| v.as_i64()).unwrap_or(0),
+            // 仅统计成功的流式请求，速率 = 输出 Token 总数 / 响应总时长
+            tokens_per_second: SessionStats::output_rate(
+                row.values.get(6).and_then(|v| v.as_i64()).unwrap_or(0),
+                row.values.get(7).and_then(|v| v.as_i64()).unwrap_or(0),
+            ),
         })
     }
 
@@ -397,39 +572,8 @@ impl TokenStatsDb {
             .context("Failed to get SQLite manager")?;
 
         // 构建查询条件
-        let mut where_clauses = Vec::new();
-        let mut params = Vec::new();
-
-        if let Some(ref tool_type) = query.tool_type {
-            where_clauses.push("tool_type = ?");
-            params.push(tool_type.clone());
-        }
-
-        if let Some(ref session_id) = query.session_id {
-            where_clauses.push("session_id = ?");
-            params.push(session_id.clone());
-        }
-
-        if let Some(ref config_name) = query.config_name {
-            where_clauses.push("config_name = ?");
-            params.push(config_name.clone());
-        }
-
-        if let Some(start_time) = query.start_time {
-            where_clauses.push("timestamp >= ?");
-            params.push(start_time.to_string());
-        }
-
-        if let Some(end_time) = query.end_time {
-            where_clauses.push("timestamp <= ?");
-            params.push(end_time.to_string());
-        }
-
-        let where_clause = if where_clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_clauses.join(" AND "))
-        };
+        let (where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        let where_clause = where_sql(&where_clauses);
 
         // 查询总数
         let count_sql = format!("SELECT COUNT(*) FROM token_logs {}", where_clause);
@@ -453,7 +597,8 @@ impl TokenStatsDb {
                     cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens, reasoning_tokens,
                     request_status, response_type, error_type, error_detail,
                     response_time_ms, input_price, output_price, cache_write_price, cache_read_price, reasoning_price,
-                    total_cost, pricing_template_id
+                    total_cost, pricing_template_id, stop_reason, request_bytes, response_bytes, upstream,
+                    image_count, image_bytes, downgraded_from, category, ttfb_ms, is_estimated
              FROM token_logs {}
              ORDER BY timestamp DESC
              LIMIT ? OFFSET ?",
@@ -551,6 +696,36 @@ impl TokenStatsDb {
                         .get(25)
                         .and_then(|v| v.as_str())
                         .map(String::from),
+                    stop_reason: row
+                        .values
+                        .get(26)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
+                    request_bytes: row.values.get(27).and_then(|v| v.as_i64()).unwrap_or(0),
+                    response_bytes: row.values.get(28).and_then(|v| v.as_i64()).unwrap_or(0),
+                    upstream: row
+                        .values
+                        .get(29)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
+                    image_count: row.values.get(30).and_then(|v| v.as_i64()).unwrap_or(0),
+                    image_bytes: row.values.get(31).and_then(|v| v.as_i64()).unwrap_or(0),
+                    downgraded_from: row
+                        .values
+                        .get(32)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
+                    category: row
+                        .values
+                        .get(33)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
+                    ttfb_ms: row.values.get(34).and_then(|v| v.as_i64()),
+                    is_estimated: row.values.get(35).and_then(|v| v.as_i64()).unwrap_or(0) != 0,
                 })
             })
             .collect::<Result<Vec<TokenLog>>>()?;
@@ -563,6 +738,111 @@ impl TokenStatsDb {
         })
     }
 
+    /// 按定价模板重算匹配日志的成本
+    ///
+    /// 指定 `template_id` 时统一使用该模板，否则沿用日志记录时的模板（缺失时回退到工具默认模板）。
+    /// 仅更新各部分价格、total_cost、pricing_template_id 与 is_estimated，token 数保持不变；
+    /// `failed` 状态的日志与无法定价的模型跳过。返回重算条数
+    pub fn recalculate_costs(
+        &self,
+        pricing: &PricingManager,
+        query: &TokenStatsQuery,
+        template_id: Option<&str>,
+    ) -> Result<usize> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (mut where_clauses, params) = build_log_filters(&LogFilter::from(query));
+        where_clauses.push("request_status != 'failed'");
+        let sql = format!(
+            "SELECT id, tool_type, model, input_tokens, output_tokens,
+                    cache_creation_tokens, COALESCE(cache_creation_1h_tokens, 0),
+                    cache_read_tokens, COALESCE(reasoning_tokens, 0), pricing_template_id
+             FROM token_logs {}",
+            where_sql(&where_clauses)
+        );
+        let params_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
+
+        let rows = manager
+            .query(&sql, &params_refs)
+            .context("Failed to query logs for cost recalculation")?;
+
+        let mut updates = Vec::new();
+        for row in &rows {
+            let int = |idx: usize| row.values.get(idx).and_then(|v| v.as_i64()).unwrap_or(0);
+            let text = |idx: usize| {
+                row.values
+                    .get(idx)
+                    .and_then(|v| v.as_str())
+                    .filter(|s| !s.is_empty())
+            };
+            let (Some(tool_type), Some(model)) = (text(1), text(2)) else {
+                continue;
+            };
+
+            let reasoning_tokens = int(8);
+            // Codex 的输出已包含推理部分，与记录时一致扣除后计费
+            let output_tokens = if tool_type == "codex" {
+                billable_output_tokens(int(4), reasoning_tokens)
+            } else {
+                int(4)
+            };
+
+            let breakdown = match pricing.calculate_cost(
+                template_id.or(text(9)),
+                Some(tool_type),
+                model,
+                int(3),
+                output_tokens,
+                int(5),
+                int(6),
+                int(7),
+                reasoning_tokens,
+            ) {
+                Ok(breakdown) => breakdown,
+                Err(e) => {
+                    tracing::debug!(log_id = int(0), model = %model, error = ?e, "重算成本失败，跳过");
+                    continue;
+                }
+            };
+            updates.push((int(0), breakdown));
+        }
+
+        let updated = manager
+            .transaction(|tx| {
+                let mut stmt = tx
+                    .prepare(
+                        "UPDATE token_logs SET
+                            input_price = ?1, output_price = ?2, cache_write_price = ?3,
+                            cache_read_price = ?4, reasoning_price = ?5, total_cost = ?6,
+                            pricing_template_id = ?7, is_estimated = ?8
+                         WHERE id = ?9",
+                    )
+                    .map_err(DataError::Database)?;
+                let mut updated = 0;
+                for (id, breakdown) in &updates {
+                    updated += stmt
+                        .execute(rusqlite::params![
+                            breakdown.input_price,
+                            breakdown.output_price,
+                            breakdown.cache_write_price,
+                            breakdown.cache_read_price,
+                            breakdown.reasoning_price,
+                            breakdown.total_cost,
+                            breakdown.template_id,
+                            breakdown.is_estimated,
+                            id,
+                        ])
+                        .map_err(DataError::Database)?;
+                }
+                Ok(updated)
+            })
+            .context("Failed to update recalculated costs")?;
+
+        Ok(updated)
+    }
+
     /// 清理旧数据
     pub fn cleanup_old_logs(
         &self,
@@ -614,6 +894,45 @@ impl TokenStatsDb {
         Ok(deleted_count)
     }
 
+    /// 删除时间窗 `[start_ts, end_ts]`（毫秒，含端点）内的日志，可按工具类型过滤
+    ///
+    /// 返回删除条数；`start_ts > end_ts` 时返回错误
+    pub fn delete_logs_in_range(
+        &self,
+        start_ts: i64,
+        end_ts: i64,
+        tool_type: Option<String>,
+    ) -> Result<usize> {
+        if start_ts > end_ts {
+            anyhow::bail!("非法时间区间: start_ts({}) > end_ts({})", start_ts, end_ts);
+        }
+
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let mut sql = "DELETE FROM token_logs WHERE timestamp >= ? AND timestamp <= ?".to_string();
+        let mut params = vec![start_ts.to_string(), end_ts.to_string()];
+        if let Some(tool_type) = tool_type {
+            sql.push_str(" AND tool_type = ?");
+            params.push(tool_type);
+        }
+        let params_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
+
+        let deleted = manager
+            .execute(&sql, &params_refs)
+            .context("Failed to delete logs in range")?;
+
+        // 执行 WAL checkpoint 回写主文件
+        if deleted > 0 {
+            manager
+                .execute_raw("PRAGMA wal_checkpoint(TRUNCATE)")
+                .context("Failed to checkpoint WAL")?;
+        }
+
+        Ok(deleted)
+    }
+
     /// 获取数据库统计信息
     pub fn get_stats_summary(&self) -> Result<(i64, Option<i64>, Option<i64>)> {
         let manager = DataManager::global()
@@ -640,6 +959,110 @@ impl TokenStatsDb {
         Ok((total, oldest, newest))
     }
 
+    /// 统计时间区间 `[start_ms, end_ms)` 内的输入 + 输出 Token 总量
+    pub fn sum_tokens_between(&self, start_ms: i64, end_ms: i64) -> Result<i64> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let rows = manager
+            .query(
+                "SELECT COALESCE(SUM(input_tokens + output_tokens), 0)
+                FROM token_logs
+                WHERE timestamp >= ? AND timestamp < ?",
+                &[&start_ms.to_string(), &end_ms.to_string()],
+            )
+            .context("Failed to sum tokens")?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0))
+    }
+
+    /// 统计时间区间 `[start_ms, end_ms)` 内的总成本（USD）
+    pub fn sum_cost_between(&self, start_ms: i64, end_ms: i64) -> Result<f64> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let rows = manager
+            .query(
+                "SELECT COALESCE(SUM(total_cost), 0.0)
+                FROM token_logs
+                WHERE timestamp >= ? AND timestamp < ?",
+                &[&start_ms.to_string(), &end_ms.to_string()],
+            )
+            .context("Failed to sum cost")?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0))
+    }
+
+    /// 批量导入外部日志（跳过已存在的记录），完成后统一执行 checkpoint
+    ///
+    /// 去重规则：有 message_id 时按 (tool_type, message_id) 判断；
+    /// 否则按 (tool_type, timestamp, model, input_tokens, output_tokens) 判断。
+    /// 同一批次内的重复记录同样跳过
+    pub fn import_logs(&self, logs: &[TokenLog]) -> Result<ImportSummary> {
+        let mut summary = ImportSummary {
+            total: logs.len(),
+            ..Default::default()
+        };
+
+        for log in logs {
+            if self.log_exists(log)? {
+                summary.duplicates += 1;
+                continue;
+            }
+            self.insert_log_without_checkpoint(log)?;
+            summary.imported += 1;
+        }
+
+        if summary.imported > 0 {
+            self.force_checkpoint()?;
+        }
+        Ok(summary)
+    }
+
+    /// 判断日志是否已存在（规则见 `import_logs`）
+    fn log_exists(&self, log: &TokenLog) -> Result<bool> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let rows = match log.message_id.as_deref().filter(|id| !id.is_empty()) {
+            Some(message_id) => manager.query(
+                "SELECT COUNT(*) FROM token_logs WHERE tool_type = ?1 AND message_id = ?2",
+                &[&log.tool_type, message_id],
+            ),
+            None => manager.query(
+                "SELECT COUNT(*) FROM token_logs
+                 WHERE tool_type = ?1 AND timestamp = ?2 AND model = ?3
+                   AND input_tokens = ?4 AND output_tokens = ?5",
+                &[
+                    &log.tool_type,
+                    &log.timestamp.to_string(),
+                    &log.model,
+                    &log.input_tokens.to_string(),
+                    &log.output_tokens.to_string(),
+                ],
+            ),
+        }
+        .context("Failed to check duplicate log")?;
+
+        let count = rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+        Ok(count > 0)
+    }
+
     /// 强制执行 WAL checkpoint（手动触发）
     ///
     /// 将 WAL 文件中的所有数据回写到主数据库文件，
@@ -699,37 +1122,124 @@ mod tests {
         assert!(db.init_table().is_ok());
     }
 
+    #[test]
+    fn test_migrate_from_v1_schema() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("legacy_v1.db");
+
+        // 按 v1 schema 建表并写入老数据（user_version 为 0）
+        {
+            let conn = rusqlite::Connection::open(&db_path).unwrap();
+            conn.execute_batch(CREATE_TABLE_V1).unwrap();
+            conn.execute(
+                "INSERT INTO token_logs (
+                    tool_type, timestamp, client_ip, session_id, config_name, model,
+                    input_tokens, output_tokens, request_status, response_type,
+                    total_cost, response_time_ms
+                ) VALUES ('claude_code', 1000, '127.0.0.1', 'legacy', 'default',
+                    'claude-sonnet-4-5', 100, 50, 'success', 'json', 0.5, 1200)",
+                [],
+            )
+            .unwrap();
+        }
+
+        let db = TokenStatsDb::new(db_path);
+        db.init_table().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+
+        // 老数据可读，新增列取默认值
+        let page = db.query_logs(&TokenStatsQuery::default()).unwrap();
+        assert_eq!(page.total, 1);
+        let log = &page.logs[0];
+        assert_eq!(log.session_id, "legacy");
+        assert_eq!(log.input_tokens, 100);
+        assert_eq!(log.response_time_ms, Some(1200));
+        assert_eq!(log.reasoning_tokens, 0);
+        assert_eq!(log.cache_creation_1h_tokens, 0);
+        assert_eq!(log.request_bytes, 0);
+        assert_eq!(log.image_count, 0);
+        assert!(log.stop_reason.is_none());
+        assert!(log.upstream.is_none());
+        assert!(log.category.is_none());
+        assert!(log.ttfb_ms.is_none());
+        assert!(!log.is_estimated);
+
+        // 升级后可正常写入新字段，重复初始化不再迁移
+        let new_log = TokenLog {
+            session_id: "current".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            input_tokens: 10,
+            output_tokens: 5,
+            reasoning_tokens: 3,
+            response_type: "sse".to_string(),
+            response_time_ms: Some(800),
+            total_cost: 0.1,
+            ..TokenLog::fixture("claude_code", 2000)
+        }
+        .with_ttfb_ms(Some(150))
+        .with_estimated(true);
+        db.insert_log(&new_log).unwrap();
+        db.init_table().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+
+        let page = db.query_logs(&TokenStatsQuery::default()).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.logs[0].reasoning_tokens, 3);
+        assert_eq!(page.logs[0].ttfb_ms, Some(150));
+        assert!(page.logs[0].is_estimated);
+        assert!(!page.logs[1].is_estimated);
+    }
+
+    #[test]
+    fn test_migrate_unversioned_db_with_partial_columns() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("legacy_partial.db");
+
+        // 引入版本号之前的数据库：user_version 为 0，但已通过旧逻辑追加过部分列
+        {
+            let conn = rusqlite::Connection::open(&db_path).unwrap();
+            conn.execute_batch(CREATE_TABLE_V1).unwrap();
+            conn.execute_batch(
+                "ALTER TABLE token_logs ADD COLUMN reasoning_tokens INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE token_logs ADD COLUMN reasoning_price REAL;
+                 ALTER TABLE token_logs ADD COLUMN stop_reason TEXT;",
+            )
+            .unwrap();
+        }
+
+        let db = TokenStatsDb::new(db_path);
+        db.init_table().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        assert!(db.query_logs(&TokenStatsQuery::default()).is_ok());
+    }
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert!(versions.windows(2).all(|w| w[1] == w[0] + 1));
+        assert_eq!(versions.first(), Some(&2));
+        assert_eq!(versions.last(), Some(&SCHEMA_VERSION));
+    }
+
     #[test]
     fn test_insert_and_query() {
         let (db, _) = create_test_db();
 
-        let log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "session_123".to_string(),
-            "default".to_string(),
-            "claude-sonnet-4-5-20250929".to_string(),
-            Some("msg_123".to_string()),
-            1000,
-            500,
-            100,
-            0, // cache_creation_1h_tokens
-            200,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let log = TokenLog {
+            session_id: "session_123".to_string(),
+            model: "claude-sonnet-4-5-20250929".to_string(),
+            message_id: Some("msg_123".to_string()),
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_tokens: 100,
+            cache_read_tokens: 200,
+            ..TokenLog::fixture("claude_code", chrono::Utc::now().timestamp_millis())
+        }
+        .with_body_bytes(2048, 512)
+        .with_image_stats(2, 1536)
+        .with_downgraded_from(Some("claude-opus-4-1".to_string()))
+        .with_category(Some("chat".to_string()))
+        .with_ttfb_ms(Some(320));
 
         let id = db.insert_log(&log).unwrap();
         assert!(id > 0);
@@ -739,6 +1249,56 @@ mod tests {
         assert_eq!(stats.total_input, 1000);
         assert_eq!(stats.total_output, 500);
         assert_eq!(stats.request_count, 1);
+
+        // 字节数往返
+        let page = db.query_logs(&TokenStatsQuery::default()).unwrap();
+        assert_eq!(page.logs[0].request_bytes, 2048);
+        assert_eq!(page.logs[0].response_bytes, 512);
+        assert_eq!(page.logs[0].image_count, 2);
+        assert_eq!(page.logs[0].image_bytes, 1536);
+        assert_eq!(
+            page.logs[0].downgraded_from.as_deref(),
+            Some("claude-opus-4-1")
+        );
+        assert_eq!(page.logs[0].category.as_deref(), Some("chat"));
+        assert_eq!(page.logs[0].ttfb_ms, Some(320));
+    }
+
+    #[test]
+    fn test_session_stats_tokens_per_second() {
+        let (db, _) = create_test_db();
+        let make_log =
+            |output_tokens: i64, status: &str, response_type: &str, time_ms: Option<i64>| {
+                TokenLog {
+                    session_id: "session_rate".to_string(),
+                    model: "claude-sonnet-4-5-20250929".to_string(),
+                    output_tokens,
+                    request_status: status.to_string(),
+                    response_type: response_type.to_string(),
+                    response_time_ms: time_ms,
+                    ..TokenLog::fixture("claude_code", chrono::Utc::now().timestamp_millis())
+                }
+            };
+
+        // 无流式记录时没有速率
+        db.insert_log(&make_log(300, "success", "json", Some(1_000)))
+            .unwrap();
+        let stats = db.get_session_stats("claude_code", "session_rate").unwrap();
+        assert_eq!(stats.tokens_per_second, None);
+
+        // 只统计成功且有响应时长的流式请求：(400 + 200) / (4s + 2s) = 100 tokens/sec
+        db.insert_log(&make_log(400, "success", "sse", Some(4_000)))
+            .unwrap();
+        db.insert_log(&make_log(200, "success", "sse", Some(2_000)))
+            .unwrap();
+        db.insert_log(&make_log(50, "error", "sse", Some(10_000)))
+            .unwrap();
+        db.insert_log(&make_log(80, "success", "sse", None))
+            .unwrap();
+
+        let stats = db.get_session_stats("claude_code", "session_rate").unwrap();
+        assert_eq!(stats.request_count, 5);
+        assert_eq!(stats.tokens_per_second, Some(100.0));
     }
 
     #[test]
@@ -747,33 +1307,15 @@ mod tests {
 
         // 插入多条记录
         for i in 0..25 {
-            let log = TokenLog::new(
-                "claude_code".to_string(),
-                chrono::Utc::now().timestamp_millis() + i,
-                "127.0.0.1".to_string(),
-                "session_123".to_string(),
-                "default".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                Some(format!("msg_{}", i)),
-                100,
-                50,
-                10,
-                0, // cache_creation_1h_tokens
-                20,
-                0, // reasoning_tokens
-                "success".to_string(),
-                "sse".to_string(),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None, // reasoning_price
-                0.0,
-                None,
-            );
+            let log = TokenLog {
+                session_id: "session_123".to_string(),
+                model: "claude-sonnet-4-5-20250929".to_string(),
+                message_id: Some(format!("msg_{}", i)),
+                cache_creation_tokens: 10,
+                cache_read_tokens: 20,
+                response_type: "sse".to_string(),
+                ..TokenLog::fixture("claude_code", chrono::Utc::now().timestamp_millis() + i)
+            };
             db.insert_log(&log).unwrap();
         }
 
@@ -803,62 +1345,18 @@ mod tests {
 
         // 插入旧数据和新数据
         let old_timestamp = chrono::Utc::now().timestamp_millis() - (40 * 86400 * 1000); // 40天前
-        let old_log = TokenLog::new(
-            "claude_code".to_string(),
-            old_timestamp,
-            "127.0.0.1".to_string(),
-            "session_old".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            None,
-            100,
-            50,
-            0,
-            0, // cache_creation_1h_tokens
-            0,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let old_log = TokenLog {
+            session_id: "session_old".to_string(),
+            ..TokenLog::fixture("claude_code", old_timestamp)
+        };
         db.insert_log(&old_log).unwrap();
 
-        let new_log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "session_new".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            None,
-            200,
-            100,
-            0,
-            0, // cache_creation_1h_tokens
-            0,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let new_log = TokenLog {
+            session_id: "session_new".to_string(),
+            input_tokens: 200,
+            output_tokens: 100,
+            ..TokenLog::fixture("claude_code", chrono::Utc::now().timestamp_millis())
+        };
         db.insert_log(&new_log).unwrap();
 
         // 清理30天前的数据
@@ -869,4 +1367,239 @@ mod tests {
         let stats = db.get_session_stats("claude_code", "session_new").unwrap();
         assert_eq!(stats.request_count, 1);
     }
+
+    #[test]
+    fn test_delete_logs_in_range() {
+        let dir = tempdir().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("test_token_stats.db"));
+        db.init_table().unwrap();
+
+        let make_log = |tool_type: &str, timestamp: i64| TokenLog {
+            session_id: "session_range".to_string(),
+            ..TokenLog::fixture(tool_type, timestamp)
+        };
+        for ts in [1000, 2000, 3000, 4000] {
+            db.insert_log(&make_log("claude_code", ts)).unwrap();
+        }
+        db.insert_log(&make_log("codex", 2500)).unwrap();
+
+        // 非法区间
+        assert!(db.delete_logs_in_range(3000, 2000, None).is_err());
+
+        // 空区间（无记录）与单点区间
+        assert_eq!(db.delete_logs_in_range(1500, 1999, None).unwrap(), 0);
+        assert_eq!(db.delete_logs_in_range(4000, 4000, None).unwrap(), 1);
+
+        // 两端点均包含，仅删除指定工具
+        assert_eq!(
+            db.delete_logs_in_range(2000, 3000, Some("claude_code".to_string()))
+                .unwrap(),
+            2
+        );
+        let page = db.query_logs(&TokenStatsQuery::default()).unwrap();
+        let mut remaining: Vec<(String, i64)> = page
+            .logs
+            .into_iter()
+            .map(|log| (log.tool_type, log.timestamp))
+            .collect();
+        remaining.sort_by_key(|(_, ts)| *ts);
+        assert_eq!(
+            remaining,
+            vec![
+                ("claude_code".to_string(), 1000),
+                ("codex".to_string(), 2500)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_errors_only() {
+        let (db, _) = create_test_db();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let cases = [
+            ("claude_code", "success", None, 0),
+            ("claude_code", "failed", Some("upstream_error"), 1),
+            ("claude_code", "failed", Some("parse_error"), 2),
+            ("codex", "failed", Some("request_interrupted"), 3),
+        ];
+        for (tool_type, status, error_type, offset) in cases {
+            let log = TokenLog {
+                session_id: "session_errors".to_string(),
+                request_status: status.to_string(),
+                error_type: error_type.map(String::from),
+                error_detail: error_type.map(|t| format!("{t} detail")),
+                ..TokenLog::fixture(tool_type, now + offset)
+            };
+            db.insert_log(&log).unwrap();
+        }
+
+        let query = TokenStatsQuery {
+            tool_type: Some("claude_code".to_string()),
+            errors_only: true,
+            ..Default::default()
+        };
+        let page = db.query_logs(&query).unwrap();
+        assert_eq!(page.total, 2);
+        // 按时间倒序，含错误类型与详情
+        assert_eq!(page.logs[0].error_type.as_deref(), Some("parse_error"));
+        assert_eq!(
+            page.logs[0].error_detail.as_deref(),
+            Some("parse_error detail")
+        );
+        assert_eq!(page.logs[1].error_type.as_deref(), Some("upstream_error"));
+        assert!(page.logs.iter().all(|log| log.timestamp > 0));
+
+        // 未开启时返回全部日志
+        let page = db
+            .query_logs(&TokenStatsQuery {
+                tool_type: Some("claude_code".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(page.total, 3);
+    }
+
+    #[test]
+    fn test_recalculate_costs_after_price_change() {
+        use crate::models::pricing::{ModelPrice, PricingTemplate};
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        let (db, _) = create_test_db();
+        let pricing_dir = tempdir().unwrap();
+        let pricing = PricingManager::new_with_manager(
+            pricing_dir.path().to_path_buf(),
+            Arc::new(DataManager::new()),
+        );
+        pricing.initialize().unwrap();
+
+        let save_template = |input: f64, output: f64| {
+            let mut models = HashMap::new();
+            models.insert(
+                "claude-sonnet-4-5".to_string(),
+                ModelPrice::new(
+                    "anthropic".to_string(),
+                    input,
+                    output,
+                    None,
+                    None,
+                    None,
+                    None,
+                    vec![],
+                ),
+            );
+            let template = PricingTemplate::new(
+                "recalc_test".to_string(),
+                "Recalc Test".to_string(),
+                String::new(),
+                "1.0".to_string(),
+                vec![],
+                models,
+                vec![],
+                false,
+            );
+            pricing.save_template(&template).unwrap();
+        };
+        save_template(3.0, 15.0);
+
+        for (status, cost) in [("success", 0.0), ("failed", 9.9)] {
+            let log = TokenLog {
+                session_id: "session_recalc".to_string(),
+                model: "claude-sonnet-4-5".to_string(),
+                input_tokens: 1000,
+                output_tokens: 500,
+                request_status: status.to_string(),
+                total_cost: cost,
+                ..TokenLog::fixture("claude_code", 1000)
+            };
+            db.insert_log(&log).unwrap();
+        }
+
+        let query = TokenStatsQuery {
+            session_id: Some("session_recalc".to_string()),
+            ..Default::default()
+        };
+        let cost_of = |status: &str| {
+            db.query_logs(&query)
+                .unwrap()
+                .logs
+                .into_iter()
+                .find(|log| log.request_status == status)
+                .unwrap()
+        };
+
+        // 指定模板重算：1000 * $3/1M + 500 * $15/1M
+        let updated = db
+            .recalculate_costs(&pricing, &query, Some("recalc_test"))
+            .unwrap();
+        assert_eq!(updated, 1);
+        let log = cost_of("success");
+        assert!((log.total_cost - 0.0105).abs() < 1e-9);
+        assert_eq!(log.pricing_template_id.as_deref(), Some("recalc_test"));
+
+        // 改价后按日志记录的模板重算，total_cost 随之变化
+        save_template(6.0, 30.0);
+        let updated = db.recalculate_costs(&pricing, &query, None).unwrap();
+        assert_eq!(updated, 1);
+        let log = cost_of("success");
+        assert!((log.total_cost - 0.021).abs() < 1e-9);
+        assert!((log.input_price.unwrap() - 0.006).abs() < 1e-9);
+        assert_eq!(log.input_tokens, 1000);
+        assert_eq!(log.output_tokens, 500);
+
+        // failed 日志保持原值
+        let failed = cost_of("failed");
+        assert!((failed.total_cost - 9.9).abs() < 1e-9);
+        assert_ne!(failed.pricing_template_id.as_deref(), Some("recalc_test"));
+    }
+
+    #[test]
+    fn test_import_logs_dedup() {
+        let (db, _) = create_test_db();
+        let make_log = |timestamp: i64, message_id: Option<&str>, input_tokens: i64| TokenLog {
+            client_ip: String::new(),
+            session_id: String::new(),
+            config_name: "imported".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            message_id: message_id.map(str::to_string),
+            input_tokens,
+            response_type: "unknown".to_string(),
+            total_cost: 0.01,
+            ..TokenLog::fixture("claude_code", timestamp)
+        };
+
+        // 库中已有一条 msg_1
+        db.insert_log(&make_log(1_000, Some("msg_1"), 100)).unwrap();
+
+        let batch = vec![
+            // message_id 已存在（时间不同也视为重复）
+            make_log(9_000, Some("msg_1"), 100),
+            make_log(2_000, Some("msg_2"), 100),
+            // 批次内重复
+            make_log(2_000, Some("msg_2"), 100),
+            // 无 message_id：按时间 + 模型 + token 数去重
+            make_log(3_000, None, 100),
+            make_log(3_000, None, 100),
+            make_log(3_000, None, 200),
+        ];
+        let summary = db.import_logs(&batch).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                total: 6,
+                imported: 3,
+                duplicates: 3,
+                invalid: 0,
+            }
+        );
+
+        // 重复导入同一批次全部跳过
+        let again = db.import_logs(&batch).unwrap();
+        assert_eq!(again.imported, 0);
+        assert_eq!(again.duplicates, 6);
+
+        let page = db.query_logs(&TokenStatsQuery::default()).unwrap();
+        assert_eq!(page.total, 4);
+    }
 }
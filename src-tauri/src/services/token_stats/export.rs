@@ -0,0 +1,171 @@
+//! Token 日志导出
+//!
+//! 将查询到的日志渲染为 CSV 或 JSON Lines，便于导入 Excel 做报销或分析
+
+use crate::models::token_stats::{ExportFormat, TokenLog};
+use anyhow::{Context, Result};
+use chrono::TimeZone;
+use serde::Serialize;
+
+/// CSV 表头（与 `ExportRow` 字段顺序一致）
+const CSV_HEADER: &[&str] = &[
+    "time",
+    "tool_type",
+    "config_name",
+    "model",
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "reasoning_tokens",
+    "total_cost",
+    "request_status",
+];
+
+/// 导出的单行记录
+#[derive(Debug, Serialize)]
+struct ExportRow<'a> {
+    /// 本地时间（YYYY-MM-DD HH:MM:SS）
+    time: String,
+    tool_type: &'a str,
+    config_name: &'a str,
+    model: &'a str,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_creation_tokens: i64,
+    cache_read_tokens: i64,
+    reasoning_tokens: i64,
+    total_cost: f64,
+    request_status: &'a str,
+}
+
+impl<'a> ExportRow<'a> {
+    fn from_log(log: &'a TokenLog) -> Self {
+        let time = chrono::Local
+            .timestamp_millis_opt(log.timestamp)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        Self {
+            time,
+            tool_type: &log.tool_type,
+            config_name: &log.config_name,
+            model: &log.model,
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+            cache_creation_tokens: log.cache_creation_tokens,
+            cache_read_tokens: log.cache_read_tokens,
+            reasoning_tokens: log.reasoning_tokens,
+            total_cost: log.total_cost,
+            request_status: &log.request_status,
+        }
+    }
+
+    fn csv_fields(&self) -> [String; 11] {
+        [
+            self.time.clone(),
+            self.tool_type.to_string(),
+            self.config_name.to_string(),
+            self.model.to_string(),
+            self.input_tokens.to_string(),
+            self.output_tokens.to_string(),
+            self.cache_creation_tokens.to_string(),
+            self.cache_read_tokens.to_string(),
+            self.reasoning_tokens.to_string(),
+            self.total_cost.to_string(),
+            self.request_status.to_string(),
+        ]
+    }
+}
+
+/// 按格式渲染日志
+pub fn render_logs(logs: &[TokenLog], format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Csv => Ok(render_csv(logs)),
+        ExportFormat::Jsonl => render_jsonl(logs),
+    }
+}
+
+/// CSV（RFC 4180，CRLF 换行，带 UTF-8 BOM 以便 Excel 正确识别中文）
+fn render_csv(logs: &[TokenLog]) -> String {
+    let mut out = String::from("\u{feff}");
+    out.push_str(&CSV_HEADER.join(","));
+    out.push_str("\r\n");
+    for log in logs {
+        let fields = ExportRow::from_log(log).csv_fields();
+        let line: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// JSON Lines（每行一个 JSON 对象）
+fn render_jsonl(logs: &[TokenLog]) -> Result<String> {
+    let mut out = String::new();
+    for log in logs {
+        let line =
+            serde_json::to_string(&ExportRow::from_log(log)).context("序列化导出记录失败")?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// 含逗号、引号或换行的字段用双引号包裹，内部引号加倍
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_log(config_name: &str) -> TokenLog {
+        TokenLog {
+            session_id: "session_1".to_string(),
+            config_name: config_name.to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_tokens: 100,
+            cache_read_tokens: 200,
+            response_type: "sse".to_string(),
+            response_time_ms: Some(1200),
+            total_cost: 0.0125,
+            ..TokenLog::fixture("claude_code", 1700000000000)
+        }
+    }
+
+    #[test]
+    fn test_csv_escapes_special_config_name() {
+        let logs = vec![make_log("team \"A\", prod\nbackup"), make_log("plain")];
+        let csv = render_logs(&logs, ExportFormat::Csv).unwrap();
+        let body = csv.strip_prefix('\u{feff}').unwrap();
+        let lines: Vec<&str> = body.split("\r\n").collect();
+
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert!(lines[1].contains(",\"team \"\"A\"\", prod\nbackup\",claude-sonnet-4-5,"));
+        assert!(lines[1].ends_with(",1000,500,100,200,0,0.0125,success"));
+        assert!(lines[2].contains(",plain,claude-sonnet-4-5,"));
+        assert_eq!(lines[3], "");
+    }
+
+    #[test]
+    fn test_jsonl_one_object_per_line() {
+        let logs = vec![make_log("a,b"), make_log("c")];
+        let jsonl = render_logs(&logs, ExportFormat::Jsonl).unwrap();
+        let rows: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["config_name"], "a,b");
+        assert_eq!(rows[1]["input_tokens"], 1000);
+        assert_eq!(rows[1]["total_cost"], 0.0125);
+    }
+}
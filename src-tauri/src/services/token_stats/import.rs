@@ -0,0 +1,347 @@
+//! Token 日志导入
+//!
+//! 解析其他用量统计工具导出的 CSV / JSON 数据，转换为 `TokenLog` 以便迁移历史记录。
+//! 字段名兼容本应用的导出格式（`time`、`tool_type`、`input_tokens` 等），数值字段允许字符串形式
+
+use crate::models::token_stats::{ImportFormat, TokenLog};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// 导入记录缺少工具类型时的默认值
+const DEFAULT_TOOL_TYPE: &str = "claude_code";
+/// 导入记录缺少配置名称时的默认值
+const DEFAULT_CONFIG_NAME: &str = "imported";
+
+/// 外部记录的单行数据
+#[derive(Debug, Deserialize)]
+struct ImportRow {
+    #[serde(alias = "time", alias = "created_at")]
+    timestamp: Value,
+    #[serde(default)]
+    tool_type: String,
+    #[serde(default)]
+    config_name: String,
+    model: String,
+    #[serde(default)]
+    message_id: Option<String>,
+    #[serde(default)]
+    session_id: Option<String>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    input_tokens: i64,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    output_tokens: i64,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    cache_creation_tokens: i64,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    cache_read_tokens: i64,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    reasoning_tokens: i64,
+    #[serde(default, deserialize_with = "lenient_f64")]
+    total_cost: f64,
+    #[serde(default)]
+    request_status: Option<String>,
+}
+
+impl ImportRow {
+    fn into_log(self) -> Result<TokenLog> {
+        let timestamp = parse_timestamp(&self.timestamp)?;
+        if self.model.trim().is_empty() {
+            bail!("缺少模型名称");
+        }
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+
+        Ok(TokenLog::new(
+            or_default(self.tool_type, DEFAULT_TOOL_TYPE),
+            timestamp,
+            String::new(),
+            non_empty(self.session_id).unwrap_or_default(),
+            or_default(self.config_name, DEFAULT_CONFIG_NAME),
+            self.model.trim().to_string(),
+            non_empty(self.message_id),
+            self.input_tokens,
+            self.output_tokens,
+            self.cache_creation_tokens,
+            0, // cache_creation_1h_tokens
+            self.cache_read_tokens,
+            self.reasoning_tokens,
+            non_empty(self.request_status).unwrap_or_else(|| "success".to_string()),
+            "unknown".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None, // reasoning_price
+            self.total_cost,
+            None,
+        ))
+    }
+}
+
+/// 解析外部数据，返回有效日志与无效记录条数
+pub fn parse_logs(format: ImportFormat, data: &str) -> Result<(Vec<TokenLog>, usize)> {
+    let records = match format {
+        ImportFormat::Csv => parse_csv_records(data)?,
+        ImportFormat::Json => parse_json_records(data)?,
+    };
+
+    let mut logs = Vec::with_capacity(records.len());
+    let mut invalid = 0;
+    for (idx, record) in records.into_iter().enumerate() {
+        let parsed = record
+            .and_then(|value| serde_json::from_value::<ImportRow>(value).map_err(Into::into))
+            .and_then(ImportRow::into_log);
+        match parsed {
+            Ok(log) => logs.push(log),
+            Err(e) => {
+                tracing::debug!(record = idx + 1, error = ?e, "导入记录无效，跳过");
+                invalid += 1;
+            }
+        }
+    }
+    Ok((logs, invalid))
+}
+
+/// JSON：支持对象数组或 JSON Lines（本应用导出的 jsonl 可直接导入）
+fn parse_json_records(data: &str) -> Result<Vec<Result<Value>>> {
+    let trimmed = data.trim_start_matches('\u{feff}').trim();
+    if trimmed.starts_with('[') {
+        let values: Vec<Value> = serde_json::from_str(trimmed).context("JSON 数组解析失败")?;
+        return Ok(values.into_iter().map(Ok).collect());
+    }
+
+    Ok(trimmed
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Into::into))
+        .collect())
+}
+
+/// CSV：首行为表头，列名不区分大小写；每行转换为以表头为键的对象
+fn parse_csv_records(data: &str) -> Result<Vec<Result<Value>>> {
+    let mut rows = split_csv_rows(data.trim_start_matches('\u{feff}'))?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or_else(|| anyhow!("CSV 内容为空"))?
+        .into_iter()
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+
+    Ok(rows
+        .filter(|fields| !(fields.len() == 1 && fields[0].trim().is_empty()))
+        .map(|fields| {
+            if fields.len() != header.len() {
+                bail!("列数 {} 与表头列数 {} 不一致", fields.len(), header.len());
+            }
+            let object: Map<String, Value> = header
+                .iter()
+                .cloned()
+                .zip(fields.into_iter().map(Value::String))
+                .collect();
+            Ok(Value::Object(object))
+        })
+        .collect())
+}
+
+/// 按 RFC 4180 切分 CSV（支持双引号包裹、引号转义与字段内换行）
+fn split_csv_rows(data: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        bail!("CSV 引号未闭合");
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// 解析时间：毫秒/秒时间戳、RFC 3339 或本地时间（YYYY-MM-DD HH:MM:SS）
+fn parse_timestamp(value: &Value) -> Result<i64> {
+    let from_epoch = |ts: i64| {
+        // 小于 1e11 视为秒级时间戳
+        if ts.abs() < 100_000_000_000 {
+            ts * 1000
+        } else {
+            ts
+        }
+    };
+
+    match value {
+        Value::Number(n) => n
+            .as_i64()
+            .or_else(|| n.as_f64().map(|f| f as i64))
+            .map(from_epoch)
+            .ok_or_else(|| anyhow!("无效的时间戳: {n}")),
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(ts) = s.parse::<i64>() {
+                return Ok(from_epoch(ts));
+            }
+            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                return Ok(dt.timestamp_millis());
+            }
+            let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .with_context(|| format!("无法识别的时间格式: {s}"))?;
+            Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|dt| dt.timestamp_millis())
+                .ok_or_else(|| anyhow!("无效的本地时间: {s}"))
+        }
+        _ => bail!("缺少时间字段"),
+    }
+}
+
+fn or_default(value: String, default: &str) -> String {
+    let value = value.trim();
+    if value.is_empty() {
+        default.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// 数值或数值字符串（空字符串视为 0）
+fn lenient_number<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(0.0),
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| serde::de::Error::custom("无效的数值")),
+        Value::String(s) if s.trim().is_empty() => Ok(0.0),
+        Value::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| serde::de::Error::custom(format!("无效的数值: {s}"))),
+        other => Err(serde::de::Error::custom(format!("无效的数值: {other}"))),
+    }
+}
+
+fn lenient_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    lenient_number(deserializer).map(|v| v as i64)
+}
+
+fn lenient_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    lenient_number(deserializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::ExportFormat;
+    use crate::services::token_stats::export::render_logs;
+
+    #[test]
+    fn test_parse_csv_with_quoted_fields() {
+        let csv = "\u{feff}Time,tool_type,config_name,model,message_id,input_tokens,output_tokens,total_cost\r\n\
+                   1700000000,codex,\"team \"\"A\"\", prod\",gpt-5,msg_1,100,50,0.01\r\n\
+                   1700000001000,,,claude-sonnet-4-5,,\"1,000\",0,\r\n\
+                   1700000002000,codex,x\r\n";
+        let (logs, invalid) = parse_logs(ImportFormat::Csv, csv).unwrap();
+
+        // 第二行 input_tokens 含千分位逗号无法解析，第三行列数不足
+        assert_eq!(logs.len(), 1);
+        assert_eq!(invalid, 2);
+        assert_eq!(logs[0].timestamp, 1_700_000_000_000);
+        assert_eq!(logs[0].tool_type, "codex");
+        assert_eq!(logs[0].config_name, "team \"A\", prod");
+        assert_eq!(logs[0].message_id.as_deref(), Some("msg_1"));
+        assert_eq!(logs[0].input_tokens, 100);
+        assert_eq!(logs[0].total_cost, 0.01);
+        assert_eq!(logs[0].request_status, "success");
+    }
+
+    #[test]
+    fn test_parse_json_array_and_lines() {
+        let array = r#"[{"timestamp": 1700000000000, "model": "gpt-5", "input_tokens": "10"},
+                        {"timestamp": "2024-01-01T00:00:00Z", "model": "gpt-5"},
+                        {"model": "missing-time"}]"#;
+        let (logs, invalid) = parse_logs(ImportFormat::Json, array).unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(invalid, 1);
+        assert_eq!(logs[0].tool_type, DEFAULT_TOOL_TYPE);
+        assert_eq!(logs[0].config_name, DEFAULT_CONFIG_NAME);
+        assert_eq!(logs[0].input_tokens, 10);
+        assert_eq!(logs[1].timestamp, 1_704_067_200_000);
+
+        let lines = "{\"time\": 1700000000000, \"model\": \"a\"}\n\nnot json\n";
+        let (logs, invalid) = parse_logs(ImportFormat::Json, lines).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(invalid, 1);
+    }
+
+    #[test]
+    fn test_roundtrip_from_export() {
+        let log = TokenLog {
+            session_id: "session_1".to_string(),
+            config_name: "prod, main".to_string(),
+            model: "gpt-5".to_string(),
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_tokens: 100,
+            cache_read_tokens: 200,
+            reasoning_tokens: 30,
+            response_type: "sse".to_string(),
+            total_cost: 0.0125,
+            ..TokenLog::fixture("codex", 1700000000000)
+        };
+
+        for (export, import) in [
+            (ExportFormat::Csv, ImportFormat::Csv),
+            (ExportFormat::Jsonl, ImportFormat::Json),
+        ] {
+            let data = render_logs(std::slice::from_ref(&log), export).unwrap();
+            let (logs, invalid) = parse_logs(import, &data).unwrap();
+            assert_eq!(invalid, 0);
+            assert_eq!(logs.len(), 1);
+            assert_eq!(logs[0].timestamp, log.timestamp);
+            assert_eq!(logs[0].config_name, log.config_name);
+            assert_eq!(logs[0].cache_read_tokens, 200);
+            assert_eq!(logs[0].reasoning_tokens, 30);
+            assert_eq!(logs[0].total_cost, 0.0125);
+        }
+    }
+}
@@ -44,6 +44,7 @@ impl ClaudeLogger {
             reasoning_price,
             total_cost,
             template_id,
+            is_estimated,
         ) = match cost_result {
             Ok(breakdown) => (
                 Some(breakdown.input_price),
@@ -53,10 +54,11 @@ impl ClaudeLogger {
                 Some(breakdown.reasoning_price),
                 breakdown.total_cost,
                 Some(breakdown.template_id),
+                breakdown.is_estimated,
             ),
             Err(e) => {
                 tracing::warn!("Failed to calculate cost: {}", e);
-                (None, None, None, None, None, 0.0, None)
+                (None, None, None, None, None, 0.0, None, false)
             }
         };
 
@@ -86,7 +88,9 @@ impl ClaudeLogger {
             reasoning_price,
             total_cost,
             template_id,
-        ))
+        )
+        .with_stop_reason(token_info.stop_reason)
+        .with_estimated(is_estimated))
     }
 }
 
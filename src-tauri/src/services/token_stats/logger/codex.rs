@@ -10,6 +10,14 @@ use chrono::Utc;
 /// Codex 日志记录器
 pub struct CodexLogger;
 
+/// 计算按输出单价计费的 token 数
+///
+/// OpenAI 的 `output_tokens` 已包含 `reasoning_tokens`，推理部分由定价单独计费，
+/// 需从输出中扣除，避免同一批 token 被计费两次
+pub(crate) fn billable_output_tokens(output_tokens: i64, reasoning_tokens: i64) -> i64 {
+    (output_tokens - reasoning_tokens).max(0)
+}
+
 impl CodexLogger {
     /// 从 TokenInfo 构建 TokenLog
     #[allow(clippy::too_many_arguments)]
@@ -23,13 +31,13 @@ impl CodexLogger {
         response_type: ResponseType,
         status: LogStatus,
     ) -> Result<TokenLog> {
-        // 计算成本
+        // 计算成本（输出扣除推理部分，推理按推理单价计费）
         let cost_result = PRICING_MANAGER.calculate_cost(
             None,          // 使用默认模板
             Some("codex"), // 工具 ID
             &token_info.model,
             token_info.input_tokens,
-            token_info.output_tokens,
+            billable_output_tokens(token_info.output_tokens, token_info.reasoning_tokens),
             token_info.cache_creation_tokens,
             token_info.cache_creation_1h_tokens,
             token_info.cache_read_tokens,
@@ -44,6 +52,7 @@ impl CodexLogger {
             reasoning_price,
             total_cost,
             template_id,
+            is_estimated,
         ) = match cost_result {
             Ok(breakdown) => (
                 Some(breakdown.input_price),
@@ -53,10 +62,11 @@ impl CodexLogger {
                 Some(breakdown.reasoning_price),
                 breakdown.total_cost,
                 Some(breakdown.template_id),
+                breakdown.is_estimated,
             ),
             Err(e) => {
                 tracing::warn!("Failed to calculate cost: {}", e);
-                (None, None, None, None, None, 0.0, None)
+                (None, None, None, None, None, 0.0, None, false)
             }
         };
 
@@ -86,7 +96,9 @@ impl CodexLogger {
             reasoning_price,
             total_cost,
             template_id,
-        ))
+        )
+        .with_stop_reason(token_info.stop_reason)
+        .with_estimated(is_estimated))
     }
 }
 
@@ -229,6 +241,53 @@ mod tests {
         assert_eq!(log.response_type, "sse");
     }
 
+    #[test]
+    fn test_billable_output_tokens() {
+        assert_eq!(billable_output_tokens(500, 200), 300);
+        assert_eq!(billable_output_tokens(15, 0), 15);
+        // 异常数据（推理大于输出）不产生负数
+        assert_eq!(billable_output_tokens(10, 20), 0);
+    }
+
+    #[test]
+    fn test_log_sse_reasoning_not_double_billed() {
+        let logger = CodexLogger;
+        let request_body = r#"{"model":"gpt-5.2-codex","input":[]}"#;
+        let completed = |reasoning: i64| {
+            vec![
+                r#"{"type":"response.created","response":{"id":"resp_r"}}"#.to_string(),
+                format!(
+                    r#"{{"type":"response.completed","response":{{"id":"resp_r","usage":{{"input_tokens":1000,"input_tokens_details":{{"cached_tokens":0}},"output_tokens":500,"output_tokens_details":{{"reasoning_tokens":{}}}}}}}}}"#,
+                    reasoning
+                ),
+            ]
+        };
+        let log_for = |reasoning: i64| {
+            logger
+                .log_sse_response(
+                    request_body.as_bytes(),
+                    completed(reasoning),
+                    "session_r".to_string(),
+                    "default".to_string(),
+                    "127.0.0.1".to_string(),
+                    Some(100),
+                )
+                .unwrap()
+        };
+
+        let with_reasoning = log_for(200);
+        let without_reasoning = log_for(0);
+
+        assert_eq!(with_reasoning.output_tokens, 500);
+        assert_eq!(with_reasoning.reasoning_tokens, 200);
+        // 推理未单独定价时按输出单价计费，总成本应与无推理时一致
+        assert!((with_reasoning.total_cost - without_reasoning.total_cost).abs() < 1e-9);
+        let output_price = with_reasoning.output_price.unwrap_or(0.0);
+        let reasoning_price = with_reasoning.reasoning_price.unwrap_or(0.0);
+        let full_output_price = without_reasoning.output_price.unwrap_or(0.0);
+        assert!((output_price + reasoning_price - full_output_price).abs() < 1e-9);
+    }
+
     #[test]
     fn test_log_json_response() {
         let logger = CodexLogger;
@@ -0,0 +1,264 @@
+//! Gemini CLI 工具的日志记录器
+
+use super::{LogStatus, ResponseType, TokenLogger};
+use crate::models::token_stats::TokenLog;
+use crate::services::pricing::PRICING_MANAGER;
+use crate::services::token_stats::processor::{create_processor, TokenInfo};
+use anyhow::Result;
+use chrono::Utc;
+
+/// Gemini CLI 日志记录器
+pub struct GeminiLogger;
+
+impl GeminiLogger {
+    /// 从 TokenInfo 构建 TokenLog
+    #[allow(clippy::too_many_arguments)]
+    fn build_log(
+        &self,
+        token_info: TokenInfo,
+        session_id: String,
+        config_name: String,
+        client_ip: String,
+        response_time_ms: Option<i64>,
+        response_type: ResponseType,
+        status: LogStatus,
+    ) -> Result<TokenLog> {
+        // 计算成本
+        let cost_result = PRICING_MANAGER.calculate_cost(
+            None,               // 使用默认模板
+            Some("gemini-cli"), // 工具 ID
+            &token_info.model,
+            token_info.input_tokens,
+            token_info.output_tokens,
+            token_info.cache_creation_tokens,
+            token_info.cache_creation_1h_tokens,
+            token_info.cache_read_tokens,
+            token_info.reasoning_tokens,
+        );
+
+        let (
+            input_price,
+            output_price,
+            cache_write_price,
+            cache_read_price,
+            reasoning_price,
+            total_cost,
+            template_id,
+            is_estimated,
+        ) = match cost_result {
+            Ok(breakdown) => (
+                Some(breakdown.input_price),
+                Some(breakdown.output_price),
+                Some(breakdown.cache_write_price),
+                Some(breakdown.cache_read_price),
+                Some(breakdown.reasoning_price),
+                breakdown.total_cost,
+                Some(breakdown.template_id),
+                breakdown.is_estimated,
+            ),
+            Err(e) => {
+                tracing::warn!("Failed to calculate cost: {}", e);
+                (None, None, None, None, None, 0.0, None, false)
+            }
+        };
+
+        Ok(TokenLog::new(
+            self.tool_id().to_string(),
+            Utc::now().timestamp_millis(),
+            client_ip,
+            session_id,
+            config_name,
+            token_info.model,
+            Some(token_info.message_id),
+            token_info.input_tokens,
+            token_info.output_tokens,
+            token_info.cache_creation_tokens,
+            token_info.cache_creation_1h_tokens,
+            token_info.cache_read_tokens,
+            token_info.reasoning_tokens,
+            status.as_str().to_string(),
+            response_type.as_str().to_string(),
+            None, // error_type
+            None, // error_detail
+            response_time_ms,
+            input_price,
+            output_price,
+            cache_write_price,
+            cache_read_price,
+            reasoning_price,
+            total_cost,
+            template_id,
+        )
+        .with_stop_reason(token_info.stop_reason)
+        .with_estimated(is_estimated))
+    }
+}
+
+impl TokenLogger for GeminiLogger {
+    fn tool_id(&self) -> &str {
+        "gemini-cli"
+    }
+
+    fn log_sse_response(
+        &self,
+        request_body: &[u8],
+        sse_chunks: Vec<String>,
+        session_id: String,
+        config_name: String,
+        client_ip: String,
+        response_time_ms: Option<i64>,
+    ) -> Result<TokenLog> {
+        // 使用 processor 提取 TokenInfo
+        let processor = create_processor("gemini-cli")?;
+        let token_info = processor.process_sse_response(request_body, sse_chunks)?;
+
+        // 构建日志（成功状态）
+        self.build_log(
+            token_info,
+            session_id,
+            config_name,
+            client_ip,
+            response_time_ms,
+            ResponseType::Sse,
+            LogStatus::Success,
+        )
+    }
+
+    fn log_json_response(
+        &self,
+        request_body: &[u8],
+        json: &serde_json::Value,
+        session_id: String,
+        config_name: String,
+        client_ip: String,
+        response_time_ms: Option<i64>,
+    ) -> Result<TokenLog> {
+        // 使用 processor 提取 TokenInfo
+        let processor = create_processor("gemini-cli")?;
+        let token_info = processor.process_json_response(request_body, json)?;
+
+        // 构建日志（成功状态）
+        self.build_log(
+            token_info,
+            session_id,
+            config_name,
+            client_ip,
+            response_time_ms,
+            ResponseType::Json,
+            LogStatus::Success,
+        )
+    }
+
+    fn log_failed_request(
+        &self,
+        request_body: &[u8],
+        session_id: String,
+        config_name: String,
+        client_ip: String,
+        response_time_ms: Option<i64>,
+        error_type: String,
+        error_detail: String,
+    ) -> Result<TokenLog> {
+        // 尝试从请求体提取 model
+        let model = serde_json::from_slice::<serde_json::Value>(request_body)
+            .ok()
+            .and_then(|req| {
+                req.get("model")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Ok(TokenLog::new(
+            self.tool_id().to_string(),
+            Utc::now().timestamp_millis(),
+            client_ip,
+            session_id,
+            config_name,
+            model,
+            None, // message_id
+            0,    // input_tokens
+            0,    // output_tokens
+            0,    // cache_creation_tokens
+            0,    // cache_creation_1h_tokens
+            0,    // cache_read_tokens
+            0,    // reasoning_tokens
+            LogStatus::Failed.as_str().to_string(),
+            ResponseType::Unknown.as_str().to_string(),
+            Some(error_type),
+            Some(error_detail),
+            response_time_ms,
+            None, // input_price
+            None, // output_price
+            None, // cache_write_price
+            None, // cache_read_price
+            None, // reasoning_price
+            0.0,  // total_cost
+            None, // pricing_template_id
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::proxy::log_recorder::{ParsedResponse, ResponseParser};
+
+    #[test]
+    fn test_log_sse_response() {
+        let logger = GeminiLogger;
+        // 原始 SSE 响应体，经统一解析层拆分为 data 块
+        let response_body = concat!(
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hi\"}]}}],\"usageMetadata\":{\"promptTokenCount\":100},\"modelVersion\":\"gemini-2.5-pro\",\"responseId\":\"resp_gem\"}\r\n\r\n",
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"!\"}]},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":100,\"candidatesTokenCount\":20,\"totalTokenCount\":120},\"modelVersion\":\"gemini-2.5-pro\",\"responseId\":\"resp_gem\"}\r\n\r\n",
+        );
+        let ParsedResponse::Sse { data_lines } =
+            ResponseParser::parse(response_body.as_bytes(), 200, true)
+        else {
+            panic!("应解析为 SSE 响应");
+        };
+
+        let log = logger
+            .log_sse_response(
+                b"{}",
+                data_lines,
+                "session_gem".to_string(),
+                "default".to_string(),
+                "127.0.0.1".to_string(),
+                Some(300),
+            )
+            .unwrap();
+
+        assert_eq!(log.tool_type, "gemini-cli");
+        assert_eq!(log.model, "gemini-2.5-pro");
+        assert_eq!(log.message_id, Some("resp_gem".to_string()));
+        assert_eq!(log.input_tokens, 100);
+        assert_eq!(log.output_tokens, 20);
+        assert_eq!(log.request_status, "success");
+        assert_eq!(log.response_type, "sse");
+        assert_eq!(log.stop_reason.as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn test_log_failed_request() {
+        let logger = GeminiLogger;
+        let request_body = r#"{"model":"gemini-2.5-flash","request":{}}"#;
+
+        let log = logger
+            .log_failed_request(
+                request_body.as_bytes(),
+                "session_gem".to_string(),
+                "default".to_string(),
+                "127.0.0.1".to_string(),
+                Some(50),
+                "upstream_error".to_string(),
+                "HTTP 429: Too Many Requests".to_string(),
+            )
+            .unwrap();
+
+        assert_eq!(log.tool_type, "gemini-cli");
+        assert_eq!(log.model, "gemini-2.5-flash");
+        assert_eq!(log.request_status, "failed");
+        assert_eq!(log.total_cost, 0.0);
+    }
+}
@@ -4,10 +4,13 @@
 
 mod claude;
 mod codex;
+mod gemini;
 mod types;
 
 pub use claude::ClaudeLogger;
+pub(crate) use codex::billable_output_tokens;
 pub use codex::CodexLogger;
+pub use gemini::GeminiLogger;
 pub use types::{LogStatus, ResponseType};
 
 use crate::models::token_stats::TokenLog;
@@ -91,7 +94,7 @@ pub trait TokenLogger: Send + Sync {
 /// 创建工具日志记录器
 ///
 /// # 参数
-/// - `tool_id`: 工具标识（claude-code/codex）
+/// - `tool_id`: 工具标识（claude-code/codex/gemini-cli）
 ///
 /// # 返回
 /// - Box<dyn TokenLogger>: 对应的日志记录器实例
@@ -99,6 +102,7 @@ pub fn create_logger(tool_id: &str) -> Result<Box<dyn TokenLogger>> {
     match tool_id {
         "claude-code" => Ok(Box::new(ClaudeLogger)),
         "codex" => Ok(Box::new(CodexLogger)),
+        "gemini-cli" => Ok(Box::new(GeminiLogger)),
         _ => Err(anyhow!("Unsupported tool: {}", tool_id)),
     }
 }
@@ -1,5 +1,12 @@
-use crate::models::token_stats::{SessionStats, TokenLog, TokenLogsPage, TokenStatsQuery};
+use crate::models::token_stats::{
+    ExportFormat, ImportFormat, ImportSummary, SessionStats, TokenLog, TokenLogsPage,
+    TokenStatsQuery,
+};
+use crate::services::pricing::PRICING_MANAGER;
+use crate::services::token_stats::anomaly::UsageSnapshot;
+use crate::services::token_stats::budget::{self, BudgetProgress};
 use crate::services::token_stats::db::TokenStatsDb;
+use crate::services::token_stats::{export, import};
 use crate::utils::config_dir;
 use anyhow::Result;
 use once_cell::sync::OnceCell;
@@ -164,6 +171,36 @@ impl TokenStatsManager {
         self.db.query_logs(&query)
     }
 
+    /// 查询工具最近的失败请求（按时间倒序）
+    pub fn query_errors(&self, tool_type: &str, limit: u32) -> Result<Vec<TokenLog>> {
+        let page = self.db.query_logs(&TokenStatsQuery {
+            tool_type: Some(tool_type.to_string()),
+            page_size: limit,
+            errors_only: true,
+            ..Default::default()
+        })?;
+        Ok(page.logs)
+    }
+
+    /// 导出符合筛选条件的全部日志（忽略分页参数）
+    pub fn export_logs(&self, query: TokenStatsQuery, format: ExportFormat) -> Result<String> {
+        let page = self.db.query_logs(&TokenStatsQuery {
+            page: 0,
+            page_size: u32::MAX,
+            ..query
+        })?;
+        export::render_logs(&page.logs, format)
+    }
+
+    /// 导入外部工具的历史日志（CSV / JSON），重复记录跳过
+    pub fn import_logs(&self, format: ImportFormat, data: &str) -> Result<ImportSummary> {
+        let (logs, invalid) = import::parse_logs(format, data)?;
+        let mut summary = self.db.import_logs(&logs)?;
+        summary.total += invalid;
+        summary.invalid = invalid;
+        Ok(summary)
+    }
+
     /// 根据配置清理旧数据
     pub fn cleanup_by_config(
         &self,
@@ -173,11 +210,51 @@ impl TokenStatsManager {
         self.db.cleanup_old_logs(retention_days, max_count)
     }
 
+    /// 删除指定时间窗内的日志（可按工具类型过滤）
+    pub fn delete_logs_in_range(
+        &self,
+        start_ts: i64,
+        end_ts: i64,
+        tool_type: Option<String>,
+    ) -> Result<usize> {
+        self.db.delete_logs_in_range(start_ts, end_ts, tool_type)
+    }
+
+    /// 按定价模板重算匹配日志的成本（跳过 failed 日志），返回重算条数
+    pub fn recalculate_costs(
+        &self,
+        query: &TokenStatsQuery,
+        template_id: Option<&str>,
+    ) -> Result<usize> {
+        self.db
+            .recalculate_costs(&PRICING_MANAGER, query, template_id)
+    }
+
     /// 获取数据库统计摘要
     pub fn get_stats_summary(&self) -> Result<(i64, Option<i64>, Option<i64>)> {
         self.db.get_stats_summary()
     }
 
+    /// 统计当前小时 / 当日用量与滚动基线（用于异常检测）
+    pub fn usage_snapshot(&self, now_ms: i64, day_start_ms: i64) -> Result<UsageSnapshot> {
+        UsageSnapshot::collect(&self.db, now_ms, day_start_ms)
+    }
+
+    /// 计算本月成本目标进度
+    pub fn budget_progress(&self, monthly_target: Option<f64>) -> Result<BudgetProgress> {
+        let now = chrono::Local::now();
+        let (period_start, period_end) = budget::month_bounds(now);
+        let now_ms = now.timestamp_millis();
+        let spent = self.db.sum_cost_between(period_start, now_ms + 1)?;
+        Ok(BudgetProgress::calculate(
+            spent,
+            monthly_target,
+            period_start,
+            period_end,
+            now_ms,
+        ))
+    }
+
     /// 强制执行 WAL checkpoint
     ///
     /// 将所有 WAL 数据回写到主数据库文件，
@@ -207,33 +284,13 @@ mod tests {
         let manager = TokenStatsManager::get();
 
         // 创建测试日志
-        let log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "test_write_session".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            Some("msg_write_test".to_string()),
-            100,
-            50,
-            10,
-            0, // cache_creation_1h_tokens
-            20,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let log = TokenLog {
+            session_id: "test_write_session".to_string(),
+            message_id: Some("msg_write_test".to_string()),
+            cache_creation_tokens: 10,
+            cache_read_tokens: 20,
+            ..TokenLog::fixture("claude_code", chrono::Utc::now().timestamp_millis())
+        };
 
         // 写入日志
         manager.write_log(log);
@@ -247,33 +304,13 @@ mod tests {
         let manager = TokenStatsManager::get();
 
         // 插入测试数据
-        let log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "test_query_session".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            Some("msg_query_test".to_string()),
-            100,
-            50,
-            10,
-            0, // cache_creation_1h_tokens
-            20,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let log = TokenLog {
+            session_id: "test_query_session".to_string(),
+            message_id: Some("msg_query_test".to_string()),
+            cache_creation_tokens: 10,
+            cache_read_tokens: 20,
+            ..TokenLog::fixture("claude_code", chrono::Utc::now().timestamp_millis())
+        };
         manager.db.insert_log(&log).unwrap();
 
         // 查询日志
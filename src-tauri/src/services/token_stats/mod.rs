@@ -3,17 +3,27 @@
 //! 提供透明代理的Token数据统计和请求记录功能。
 
 pub mod analytics;
+pub mod anomaly;
+pub mod budget;
+pub mod custom_query;
 pub mod db;
+pub mod export;
+pub mod import;
 pub mod logger;
 pub mod manager;
 pub mod processor;
+pub mod weekly_report;
 
 #[cfg(test)]
 mod cost_calculation_test;
 
 pub use analytics::{
-    CostGroupBy, CostSummary, CostSummaryQuery, TimeGranularity, TokenStatsAnalytics,
-    TrendDataPoint, TrendQuery,
+    CacheRoi, CostGroupBy, CostSummary, CostSummaryQuery, IpUsage, ModelSavings, SavingsSummary,
+    StopReasonStat, SuccessRatePoint, TimeGranularity, TokenStatsAnalytics, TrendDataPoint,
+    TrendQuery, UpstreamStat,
 };
+pub use budget::BudgetProgress;
+pub use custom_query::QueryResult;
 pub use db::TokenStatsDb;
 pub use manager::{shutdown_token_stats_manager, TokenStatsManager};
+pub use weekly_report::WeeklyReport;
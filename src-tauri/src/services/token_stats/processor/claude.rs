@@ -33,6 +33,7 @@ impl ToolProcessor for ClaudeProcessor {
         let mut cache_creation_tokens = 0i64;
         let mut cache_creation_1h_tokens = 0i64;
         let mut cache_read_tokens = 0i64;
+        let mut stop_reason: Option<String> = None;
 
         for chunk in sse_chunks {
             let data_line = chunk.trim();
@@ -121,8 +122,23 @@ impl ToolProcessor for ClaudeProcessor {
                     }
                 }
                 "message_delta" => {
+                    // 提取结束原因
+                    if let Some(reason) = json
+                        .get("delta")
+                        .and_then(|d| d.get("stop_reason"))
+                        .and_then(|v| v.as_str())
+                    {
+                        stop_reason = Some(reason.to_string());
+                    }
+
                     // message_delta 包含最终的 usage 统计（累加值）
                     if let Some(usage) = json.get("usage") {
+                        // 部分上游（如 OpenAI 兼容转换）仅在结束时给出 input_tokens
+                        input_tokens = usage
+                            .get("input_tokens")
+                            .and_then(|v| v.as_i64())
+                            .unwrap_or(input_tokens);
+
                         // 更新 output_tokens 和缓存统计（这些是最终值）
                         output_tokens = usage
                             .get("output_tokens")
@@ -179,7 +195,8 @@ impl ToolProcessor for ClaudeProcessor {
             cache_creation_1h_tokens,
             cache_read_tokens,
             0, // Claude 不使用 reasoning tokens
-        ))
+        )
+        .with_stop_reason(stop_reason))
     }
 
     fn process_json_response(&self, request_body: &[u8], json: &Value) -> Result<TokenInfo> {
@@ -248,6 +265,11 @@ impl ToolProcessor for ClaudeProcessor {
             .and_then(|v| v.as_i64())
             .unwrap_or(0);
 
+        let stop_reason = json
+            .get("stop_reason")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
         // 4. 构建 TokenInfo
         Ok(TokenInfo::new(
             model,
@@ -258,7 +280,8 @@ impl ToolProcessor for ClaudeProcessor {
             cache_creation_1h_tokens,
             cache_read_tokens,
             0, // Claude 不使用 reasoning tokens
-        ))
+        )
+        .with_stop_reason(stop_reason))
     }
 }
 
@@ -289,6 +312,54 @@ mod tests {
         assert_eq!(result.cache_creation_1h_tokens, 0); // 扁平字段无法区分，全部视为 5m
         assert_eq!(result.cache_read_tokens, 200);
         assert_eq!(result.reasoning_tokens, 0);
+        assert_eq!(result.stop_reason.as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn test_process_sse_tool_use_response() {
+        let processor = ClaudeProcessor;
+        // 工具调用后续轮次：请求携带 tool_result，响应再次以 tool_use 结束
+        let request_body = r#"{"model":"claude-sonnet-4-5-20250929","messages":[{"role":"user","content":"列出文件"},{"role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}]},{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"a.rs"}]}]}"#;
+        let sse_chunks = vec![
+            r#"data: {"type":"message_start","message":{"model":"claude-sonnet-4-5-20250929","id":"msg_tool","type":"message","role":"assistant","content":[],"stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":20,"cache_creation":{"ephemeral_5m_input_tokens":30,"ephemeral_1h_input_tokens":0},"cache_read_input_tokens":5000,"output_tokens":1}}}"#.to_string(),
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#.to_string(),
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"读取文件"}}"#.to_string(),
+            r#"data: {"type":"content_block_stop","index":0}"#.to_string(),
+            r#"data: {"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_2","name":"Read","input":{}}}"#.to_string(),
+            r#"data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"file_path\": \"a.rs\"}"}}"#.to_string(),
+            r#"data: {"type":"content_block_stop","index":1}"#.to_string(),
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null},"usage":{"output_tokens":85}}"#.to_string(),
+            r#"data: {"type":"message_stop"}"#.to_string(),
+        ];
+
+        let result = processor
+            .process_sse_response(request_body.as_bytes(), sse_chunks)
+            .unwrap();
+
+        assert_eq!(result.message_id, "msg_tool");
+        assert_eq!(result.input_tokens, 20);
+        assert_eq!(result.output_tokens, 85); // 含工具调用参数的输出
+        assert_eq!(result.cache_creation_tokens, 30);
+        assert_eq!(result.cache_read_tokens, 5000); // 历史轮次命中缓存
+        assert_eq!(result.stop_reason.as_deref(), Some("tool_use"));
+
+        // 非流式的工具调用响应
+        let json: Value = serde_json::from_str(
+            r#"{
+                "id": "msg_tool_json",
+                "model": "claude-sonnet-4-5-20250929",
+                "content": [{"type": "tool_use", "id": "toolu_3", "name": "Bash", "input": {"command": "ls"}}],
+                "stop_reason": "tool_use",
+                "usage": {"input_tokens": 12, "output_tokens": 40, "cache_read_input_tokens": 800}
+            }"#,
+        )
+        .unwrap();
+        let result = processor
+            .process_json_response(request_body.as_bytes(), &json)
+            .unwrap();
+        assert_eq!(result.output_tokens, 40);
+        assert_eq!(result.cache_read_tokens, 800);
+        assert_eq!(result.stop_reason.as_deref(), Some("tool_use"));
     }
 
     #[test]
@@ -301,6 +372,7 @@ mod tests {
             "type": "message",
             "role": "assistant",
             "content": [{"type": "text", "text": "Hello"}],
+            "stop_reason": "max_tokens",
             "usage": {
                 "input_tokens": 1000,
                 "output_tokens": 500,
@@ -322,6 +394,7 @@ mod tests {
         assert_eq!(result.cache_creation_1h_tokens, 0); // 扁平字段全部视为 5m
         assert_eq!(result.cache_read_tokens, 200);
         assert_eq!(result.reasoning_tokens, 0);
+        assert_eq!(result.stop_reason.as_deref(), Some("max_tokens"));
     }
 
     #[test]
@@ -350,5 +423,6 @@ mod tests {
         assert_eq!(result.cache_creation_tokens, 150); // 50 + 100
         assert_eq!(result.cache_creation_1h_tokens, 100); // 1h 部分
         assert_eq!(result.cache_read_tokens, 200);
+        assert!(result.stop_reason.is_none());
     }
 }
@@ -7,6 +7,115 @@ use serde_json::Value;
 /// Codex 工具处理器
 pub struct CodexProcessor;
 
+/// 从 Responses API 的 response 对象提取结束原因
+///
+/// 统一映射为 Claude 风格的取值，便于跨工具聚合：
+/// - `incomplete` + `max_output_tokens` → `max_tokens`
+/// - `completed` 且输出包含工具调用 → `tool_use`
+/// - `completed` → `end_turn`
+fn extract_stop_reason(response: &Value) -> Option<String> {
+    let status = response.get("status").and_then(|v| v.as_str())?;
+
+    let reason = match status {
+        "incomplete" => {
+            match response
+                .get("incomplete_details")
+                .and_then(|d| d.get("reason"))
+                .and_then(|v| v.as_str())
+            {
+                Some("max_output_tokens") => "max_tokens",
+                Some(other) => other,
+                None => status,
+            }
+        }
+        "completed" => {
+            let has_tool_call = response
+                .get("output")
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items.iter().any(|item| {
+                        item.get("type")
+                            .and_then(|v| v.as_str())
+                            .is_some_and(|t| t.ends_with("_call"))
+                    })
+                })
+                .unwrap_or(false);
+            if has_tool_call {
+                "tool_use"
+            } else {
+                "end_turn"
+            }
+        }
+        other => other,
+    };
+
+    Some(reason.to_string())
+}
+
+/// 是否为 Chat Completions 协议的响应（`chat.completion` / `chat.completion.chunk`）
+fn is_chat_completion(json: &Value) -> bool {
+    json.get("response").is_none()
+        && (json.get("choices").is_some()
+            || json
+                .get("object")
+                .and_then(|v| v.as_str())
+                .is_some_and(|o| o.starts_with("chat.completion")))
+}
+
+/// 从 Chat Completions 的 usage 提取 (新输入, 输出, 缓存读取, 推理)
+///
+/// `prompt_tokens` 包含缓存命中部分，与 Responses API 一致需减去 `cached_tokens`
+fn extract_chat_usage(usage: &Value) -> (i64, i64, i64, i64) {
+    let prompt_tokens = usage
+        .get("prompt_tokens")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let completion_tokens = usage
+        .get("completion_tokens")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let cached_tokens = usage
+        .get("prompt_tokens_details")
+        .and_then(|d| d.get("cached_tokens"))
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let reasoning_tokens = usage
+        .get("completion_tokens_details")
+        .and_then(|d| d.get("reasoning_tokens"))
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+
+    (
+        prompt_tokens - cached_tokens,
+        completion_tokens,
+        cached_tokens,
+        reasoning_tokens,
+    )
+}
+
+/// 将 Chat Completions 的 finish_reason 映射为统一的结束原因
+fn map_chat_finish_reason(reason: &str) -> String {
+    match reason {
+        "stop" => "end_turn",
+        "length" => "max_tokens",
+        "tool_calls" | "function_call" => "tool_use",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 从 Chat Completions 的 choices 中提取 finish_reason
+fn extract_chat_finish_reason(json: &Value) -> Option<String> {
+    json.get("choices")
+        .and_then(|v| v.as_array())
+        .and_then(|choices| {
+            choices
+                .iter()
+                .find_map(|c| c.get("finish_reason").and_then(|v| v.as_str()))
+        })
+        .map(map_chat_finish_reason)
+}
+
 impl ToolProcessor for CodexProcessor {
     fn tool_id(&self) -> &str {
         "codex"
@@ -32,6 +141,10 @@ impl ToolProcessor for CodexProcessor {
         let mut output_tokens = 0i64;
         let mut cache_read_tokens = 0i64;
         let mut reasoning_tokens = 0i64;
+        let mut stop_reason: Option<String> = None;
+        // Chat Completions 协议（部分中转站使用）
+        let mut is_chat = false;
+        let mut chat_usage_found = false;
 
         for chunk in sse_chunks {
             let data_line = chunk.trim();
@@ -73,9 +186,11 @@ impl ToolProcessor for CodexProcessor {
                         }
                     }
                 }
-                "response.completed" => {
+                "response.completed" | "response.incomplete" => {
                     // 提取完整的 usage 统计
                     if let Some(response) = json.get("response") {
+                        stop_reason = extract_stop_reason(response);
+
                         // 更新 response_id（以防 created 事件缺失）
                         if message_id.is_none() {
                             if let Some(id) = response.get("id").and_then(|v| v.as_str()) {
@@ -117,7 +232,7 @@ impl ToolProcessor for CodexProcessor {
                             if reasoning_tokens > 0 {
                                 tracing::info!(
                                     reasoning_tokens = reasoning_tokens,
-                                    "Codex 响应包含 reasoning tokens（暂不计费）"
+                                    "Codex 响应包含 reasoning tokens（已计入 output_tokens）"
                                 );
                             }
 
@@ -132,11 +247,47 @@ impl ToolProcessor for CodexProcessor {
                         }
                     }
                 }
+                _ if is_chat_completion(&json) => {
+                    // 回退：Chat Completions 流式 chunk
+                    is_chat = true;
+
+                    if message_id.is_none() {
+                        message_id = json.get("id").and_then(|v| v.as_str()).map(String::from);
+                    }
+
+                    if let Some(reason) = extract_chat_finish_reason(&json) {
+                        stop_reason = Some(reason);
+                    }
+
+                    // 仅最后一个 chunk 携带 usage（需开启 stream_options.include_usage）
+                    if let Some(usage) = json.get("usage").filter(|u| !u.is_null()) {
+                        (
+                            input_tokens,
+                            output_tokens,
+                            cache_read_tokens,
+                            reasoning_tokens,
+                        ) = extract_chat_usage(usage);
+                        chat_usage_found = true;
+
+                        tracing::debug!(
+                            message_id = ?message_id,
+                            new_input = input_tokens,
+                            cached = cache_read_tokens,
+                            output_tokens = output_tokens,
+                            "Codex chat.completion.chunk usage 提取成功"
+                        );
+                    }
+                }
                 _ => {}
             }
         }
 
         // 3. 验证必需字段
+        if is_chat && !chat_usage_found {
+            anyhow::bail!(
+                "Missing usage in chat completions stream (stream_options.include_usage not enabled?)"
+            );
+        }
         let message_id = message_id.context("Missing response_id in SSE stream")?;
 
         // 4. 构建 TokenInfo
@@ -149,7 +300,8 @@ impl ToolProcessor for CodexProcessor {
             0, // Codex 无 1h 缓存概念
             cache_read_tokens,
             reasoning_tokens,
-        ))
+        )
+        .with_stop_reason(stop_reason))
     }
 
     fn process_json_response(&self, request_body: &[u8], json: &Value) -> Result<TokenInfo> {
@@ -182,6 +334,24 @@ impl ToolProcessor for CodexProcessor {
             .get("usage")
             .context("Missing 'usage' field in response")?;
 
+        // 回退：Chat Completions 格式（顶层 choices + prompt_tokens/completion_tokens）
+        if is_chat_completion(json) || usage.get("prompt_tokens").is_some() {
+            let (input_tokens, output_tokens, cache_read_tokens, reasoning_tokens) =
+                extract_chat_usage(usage);
+
+            return Ok(TokenInfo::new(
+                model,
+                message_id,
+                input_tokens,
+                output_tokens,
+                0, // Codex 不报告 cache_creation_tokens
+                0, // Codex 无 1h 缓存概念
+                cache_read_tokens,
+                reasoning_tokens,
+            )
+            .with_stop_reason(extract_chat_finish_reason(json)));
+        }
+
         // Codex 的 input_tokens 包括缓存的 token
         // 需要减去 cached_tokens 才是真正的新输入
         let total_input_tokens = usage
@@ -211,6 +381,8 @@ impl ToolProcessor for CodexProcessor {
             .and_then(|v| v.as_i64())
             .unwrap_or(0);
 
+        let stop_reason = extract_stop_reason(json);
+
         // 4. 构建 TokenInfo
         Ok(TokenInfo::new(
             model,
@@ -221,7 +393,8 @@ impl ToolProcessor for CodexProcessor {
             0, // Codex 无 1h 缓存概念
             cache_read_tokens,
             reasoning_tokens,
-        ))
+        )
+        .with_stop_reason(stop_reason))
     }
 }
 
@@ -252,6 +425,8 @@ mod tests {
         assert_eq!(result.cache_creation_tokens, 0);
         assert_eq!(result.cache_read_tokens, 10240);
         assert_eq!(result.reasoning_tokens, 0);
+        // 缺少 status 时不推断结束原因
+        assert!(result.stop_reason.is_none());
     }
 
     #[test]
@@ -273,6 +448,32 @@ mod tests {
         assert_eq!(result.reasoning_tokens, 200);
     }
 
+    #[test]
+    fn test_process_sse_with_reasoning_events() {
+        let processor = CodexProcessor;
+        let request_body = r#"{"model":"gpt-5.2-codex","input":[],"reasoning":{"effort":"high"}}"#;
+        // 推理摘要增量事件穿插在流中，usage 仅以 response.completed 为准
+        let sse_chunks = vec![
+            r#"data: {"type":"response.created","response":{"id":"resp_reason"}}"#.to_string(),
+            r#"data: {"type":"response.output_item.added","item":{"type":"reasoning","id":"rs_1"}}"#.to_string(),
+            r#"data: {"type":"response.reasoning_summary_text.delta","item_id":"rs_1","delta":"**Planning**"}"#.to_string(),
+            r#"data: {"type":"response.output_text.delta","delta":"done"}"#.to_string(),
+            r#"data: {"type":"response.completed","response":{"id":"resp_reason","status":"completed","usage":{"input_tokens":2048,"input_tokens_details":{"cached_tokens":1024},"output_tokens":900,"output_tokens_details":{"reasoning_tokens":768},"total_tokens":2948}}}"#.to_string(),
+            "data: [DONE]".to_string(),
+        ];
+
+        let result = processor
+            .process_sse_response(request_body.as_bytes(), sse_chunks)
+            .unwrap();
+
+        assert_eq!(result.message_id, "resp_reason");
+        assert_eq!(result.input_tokens, 1024);
+        assert_eq!(result.cache_read_tokens, 1024);
+        // output_tokens 保持上游原值（已包含推理部分），推理单独记录
+        assert_eq!(result.output_tokens, 900);
+        assert_eq!(result.reasoning_tokens, 768);
+    }
+
     #[test]
     fn test_process_json_response() {
         let processor = CodexProcessor;
@@ -326,4 +527,112 @@ mod tests {
         assert_eq!(result.cache_read_tokens, 0);
         assert_eq!(result.reasoning_tokens, 0);
     }
+
+    #[test]
+    fn test_extract_stop_reason() {
+        let processor = CodexProcessor;
+        let request_body = r#"{"model":"gpt-5.1","messages":[]}"#;
+
+        // 输出被截断
+        let sse_chunks = vec![
+            r#"{"type":"response.incomplete","response":{"id":"resp_trunc","status":"incomplete","incomplete_details":{"reason":"max_output_tokens"},"usage":{"input_tokens":10,"output_tokens":100}}}"#.to_string(),
+        ];
+        let result = processor
+            .process_sse_response(request_body.as_bytes(), sse_chunks)
+            .unwrap();
+        assert_eq!(result.stop_reason.as_deref(), Some("max_tokens"));
+        assert_eq!(result.output_tokens, 100);
+
+        // 工具调用
+        let json: Value = serde_json::from_str(
+            r#"{"id":"resp_tool","model":"gpt-5.1","status":"completed","output":[{"type":"reasoning"},{"type":"function_call","name":"shell"}],"usage":{"input_tokens":10,"output_tokens":5}}"#,
+        )
+        .unwrap();
+        let result = processor
+            .process_json_response(request_body.as_bytes(), &json)
+            .unwrap();
+        assert_eq!(result.stop_reason.as_deref(), Some("tool_use"));
+
+        // 正常结束
+        let json: Value = serde_json::from_str(
+            r#"{"id":"resp_done","model":"gpt-5.1","status":"completed","output":[{"type":"message"}],"usage":{"input_tokens":10,"output_tokens":5}}"#,
+        )
+        .unwrap();
+        let result = processor
+            .process_json_response(request_body.as_bytes(), &json)
+            .unwrap();
+        assert_eq!(result.stop_reason.as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn test_process_chat_completions_sse() {
+        let processor = CodexProcessor;
+        let request_body = r#"{"model":"gpt-4o","messages":[],"stream":true,"stream_options":{"include_usage":true}}"#;
+        let sse_chunks = vec![
+            r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":""},"finish_reason":null}]}"#.to_string(),
+            r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","model":"gpt-4o","choices":[{"index":0,"delta":{"content":"Hi"},"finish_reason":null}]}"#.to_string(),
+            r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"length"}]}"#.to_string(),
+            r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","model":"gpt-4o","choices":[],"usage":{"prompt_tokens":1200,"completion_tokens":64,"prompt_tokens_details":{"cached_tokens":1000},"completion_tokens_details":{"reasoning_tokens":8}}}"#.to_string(),
+            "data: [DONE]".to_string(),
+        ];
+
+        let result = processor
+            .process_sse_response(request_body.as_bytes(), sse_chunks)
+            .unwrap();
+
+        assert_eq!(result.model, "gpt-4o");
+        assert_eq!(result.message_id, "chatcmpl-1");
+        assert_eq!(result.input_tokens, 200); // 1200 - 1000
+        assert_eq!(result.output_tokens, 64);
+        assert_eq!(result.cache_read_tokens, 1000);
+        assert_eq!(result.reasoning_tokens, 8);
+        assert_eq!(result.stop_reason.as_deref(), Some("max_tokens"));
+    }
+
+    #[test]
+    fn test_process_chat_completions_sse_without_usage() {
+        let processor = CodexProcessor;
+        let request_body = r#"{"model":"gpt-4o","messages":[],"stream":true}"#;
+        let sse_chunks = vec![
+            r#"data: {"id":"chatcmpl-2","object":"chat.completion.chunk","choices":[{"index":0,"delta":{"content":"Hi"},"finish_reason":null}]}"#.to_string(),
+            r#"data: {"id":"chatcmpl-2","object":"chat.completion.chunk","choices":[{"index":0,"delta":{},"finish_reason":"stop"}],"usage":null}"#.to_string(),
+            "data: [DONE]".to_string(),
+        ];
+
+        let err = processor
+            .process_sse_response(request_body.as_bytes(), sse_chunks)
+            .unwrap_err();
+        assert!(err.to_string().contains("include_usage"));
+    }
+
+    #[test]
+    fn test_process_chat_completions_json() {
+        let processor = CodexProcessor;
+        let request_body = r#"{"model":"gpt-4o","messages":[]}"#;
+        let json_str = r#"{
+            "id": "chatcmpl-3",
+            "object": "chat.completion",
+            "model": "gpt-4o-2024-08-06",
+            "choices": [{"index": 0, "message": {"role": "assistant", "tool_calls": []}, "finish_reason": "tool_calls"}],
+            "usage": {
+                "prompt_tokens": 300,
+                "completion_tokens": 40,
+                "total_tokens": 340,
+                "prompt_tokens_details": {"cached_tokens": 100}
+            }
+        }"#;
+
+        let json: Value = serde_json::from_str(json_str).unwrap();
+        let result = processor
+            .process_json_response(request_body.as_bytes(), &json)
+            .unwrap();
+
+        assert_eq!(result.model, "gpt-4o-2024-08-06");
+        assert_eq!(result.message_id, "chatcmpl-3");
+        assert_eq!(result.input_tokens, 200); // 300 - 100
+        assert_eq!(result.output_tokens, 40);
+        assert_eq!(result.cache_read_tokens, 100);
+        assert_eq!(result.reasoning_tokens, 0);
+        assert_eq!(result.stop_reason.as_deref(), Some("tool_use"));
+    }
 }
@@ -0,0 +1,220 @@
+//! Gemini CLI 工具的 Token 处理器
+
+use super::{TokenInfo, ToolProcessor};
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Gemini CLI 工具处理器
+///
+/// 流式与非流式响应的每个块都是完整的 `GenerateContentResponse`，
+/// `usageMetadata` 为累计值，以最后一次出现的为准
+pub struct GeminiProcessor;
+
+/// Code Assist 接口会把响应包在 `response` 字段中，统一取出内层对象
+fn unwrap_response(json: &Value) -> &Value {
+    json.get("response").unwrap_or(json)
+}
+
+/// 从 usageMetadata 提取 (新输入, 输出, 缓存读取, 推理)
+///
+/// `promptTokenCount` 包含缓存命中部分，需减去 `cachedContentTokenCount`
+fn extract_usage(usage: &Value) -> (i64, i64, i64, i64) {
+    let count = |key: &str| usage.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+
+    let prompt_tokens = count("promptTokenCount");
+    let cached_tokens = count("cachedContentTokenCount");
+    (
+        prompt_tokens - cached_tokens,
+        count("candidatesTokenCount"),
+        cached_tokens,
+        count("thoughtsTokenCount"),
+    )
+}
+
+/// 从 candidates 提取结束原因，并映射为 Claude 风格的取值
+fn extract_stop_reason(json: &Value) -> Option<String> {
+    let candidate = json
+        .get("candidates")
+        .and_then(|v| v.as_array())
+        .and_then(|c| c.first())?;
+    let reason = candidate.get("finishReason").and_then(|v| v.as_str())?;
+
+    let has_function_call = candidate
+        .get("content")
+        .and_then(|c| c.get("parts"))
+        .and_then(|p| p.as_array())
+        .is_some_and(|parts| parts.iter().any(|p| p.get("functionCall").is_some()));
+
+    let mapped = match reason {
+        "STOP" if has_function_call => "tool_use".to_string(),
+        "STOP" => "end_turn".to_string(),
+        "MAX_TOKENS" => "max_tokens".to_string(),
+        other => other.to_ascii_lowercase(),
+    };
+    Some(mapped)
+}
+
+/// 从请求体提取模型（Code Assist 请求体带 model 字段，标准 API 的模型在 URL 中）
+fn request_model(request_body: &[u8]) -> Option<String> {
+    serde_json::from_slice::<Value>(request_body)
+        .ok()
+        .and_then(|req| req.get("model").and_then(|v| v.as_str()).map(String::from))
+}
+
+impl ToolProcessor for GeminiProcessor {
+    fn tool_id(&self) -> &str {
+        "gemini-cli"
+    }
+
+    fn process_sse_response(
+        &self,
+        request_body: &[u8],
+        sse_chunks: Vec<String>,
+    ) -> Result<TokenInfo> {
+        let mut model: Option<String> = None;
+        let mut message_id: Option<String> = None;
+        let mut usage: Option<(i64, i64, i64, i64)> = None;
+        let mut stop_reason: Option<String> = None;
+
+        for chunk in sse_chunks {
+            let data_line = chunk.trim();
+            let json_str = data_line.strip_prefix("data: ").unwrap_or(data_line);
+            if json_str.is_empty() || json_str == "[DONE]" {
+                continue;
+            }
+
+            let json: Value = match serde_json::from_str(json_str) {
+                Ok(j) => j,
+                Err(e) => {
+                    tracing::warn!("Failed to parse SSE chunk: {}", e);
+                    continue;
+                }
+            };
+            let response = unwrap_response(&json);
+
+            if let Some(version) = response.get("modelVersion").and_then(|v| v.as_str()) {
+                model = Some(version.to_string());
+            }
+            if message_id.is_none() {
+                message_id = response
+                    .get("responseId")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+            }
+            if let Some(reason) = extract_stop_reason(response) {
+                stop_reason = Some(reason);
+            }
+            if let Some(metadata) = response.get("usageMetadata") {
+                usage = Some(extract_usage(metadata));
+            }
+        }
+
+        let (input_tokens, output_tokens, cache_read_tokens, reasoning_tokens) =
+            usage.context("Missing usageMetadata in SSE stream")?;
+        let model = model
+            .or_else(|| request_model(request_body))
+            .context("Missing model in both response and request")?;
+
+        Ok(TokenInfo::new(
+            model,
+            message_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            input_tokens,
+            output_tokens,
+            0, // Gemini 不单独报告缓存写入
+            0,
+            cache_read_tokens,
+            reasoning_tokens,
+        )
+        .with_stop_reason(stop_reason))
+    }
+
+    fn process_json_response(&self, request_body: &[u8], json: &Value) -> Result<TokenInfo> {
+        let response = unwrap_response(json);
+
+        let model = response
+            .get("modelVersion")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| request_model(request_body))
+            .context("Missing model in both response and request")?;
+
+        let usage = response
+            .get("usageMetadata")
+            .context("Missing 'usageMetadata' field in response")?;
+        let (input_tokens, output_tokens, cache_read_tokens, reasoning_tokens) =
+            extract_usage(usage);
+
+        let message_id = response
+            .get("responseId")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        Ok(TokenInfo::new(
+            model,
+            message_id,
+            input_tokens,
+            output_tokens,
+            0, // Gemini 不单独报告缓存写入
+            0,
+            cache_read_tokens,
+            reasoning_tokens,
+        )
+        .with_stop_reason(extract_stop_reason(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_sse_response() {
+        let processor = GeminiProcessor;
+        let sse_chunks = vec![
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hel"}]}}],"usageMetadata":{"promptTokenCount":1200,"totalTokenCount":1200},"modelVersion":"gemini-2.5-pro","responseId":"resp_gem_1"}"#.to_string(),
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"lo"}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":1200,"cachedContentTokenCount":1000,"candidatesTokenCount":30,"thoughtsTokenCount":12,"totalTokenCount":1242},"modelVersion":"gemini-2.5-pro","responseId":"resp_gem_1"}"#.to_string(),
+        ];
+
+        let result = processor.process_sse_response(b"{}", sse_chunks).unwrap();
+
+        assert_eq!(result.model, "gemini-2.5-pro");
+        assert_eq!(result.message_id, "resp_gem_1");
+        assert_eq!(result.input_tokens, 200); // 1200 - 1000
+        assert_eq!(result.output_tokens, 30);
+        assert_eq!(result.cache_read_tokens, 1000);
+        assert_eq!(result.reasoning_tokens, 12);
+        assert_eq!(result.stop_reason.as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn test_process_code_assist_json_response() {
+        let processor = GeminiProcessor;
+        let request_body =
+            r#"{"model":"gemini-2.5-flash","project":"p","request":{"contents":[]}}"#;
+        let json: Value = serde_json::from_str(
+            r#"{"response":{"candidates":[{"content":{"parts":[{"functionCall":{"name":"ls"}}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":50,"candidatesTokenCount":8}}}"#,
+        )
+        .unwrap();
+
+        let result = processor
+            .process_json_response(request_body.as_bytes(), &json)
+            .unwrap();
+
+        assert_eq!(result.model, "gemini-2.5-flash");
+        assert_eq!(result.input_tokens, 50);
+        assert_eq!(result.output_tokens, 8);
+        assert_eq!(result.stop_reason.as_deref(), Some("tool_use"));
+    }
+
+    #[test]
+    fn test_process_sse_without_usage_fails() {
+        let processor = GeminiProcessor;
+        let sse_chunks = vec![
+            r#"{"candidates":[{"content":{"parts":[{"text":"hi"}]}}],"modelVersion":"gemini-2.5-pro"}"#
+                .to_string(),
+        ];
+
+        assert!(processor.process_sse_response(b"{}", sse_chunks).is_err());
+    }
+}
@@ -4,10 +4,12 @@
 
 mod claude;
 mod codex;
+mod gemini;
 mod token_info;
 
 pub use claude::ClaudeProcessor;
 pub use codex::CodexProcessor;
+pub use gemini::GeminiProcessor;
 pub use token_info::TokenInfo;
 
 use anyhow::{anyhow, Result};
@@ -46,7 +48,7 @@ pub trait ToolProcessor: Send + Sync {
 /// 创建工具处理器
 ///
 /// # 参数
-/// - `tool_id`: 工具标识（claude-code/codex）
+/// - `tool_id`: 工具标识（claude-code/codex/gemini-cli）
 ///
 /// # 返回
 /// - Box<dyn ToolProcessor>: 对应的处理器实例
@@ -54,6 +56,7 @@ pub fn create_processor(tool_id: &str) -> Result<Box<dyn ToolProcessor>> {
     match tool_id {
         "claude-code" => Ok(Box::new(ClaudeProcessor)),
         "codex" => Ok(Box::new(CodexProcessor)),
+        "gemini-cli" => Ok(Box::new(GeminiProcessor)),
         _ => Err(anyhow!("Unsupported tool: {}", tool_id)),
     }
 }
@@ -32,6 +32,10 @@ pub struct TokenInfo {
 
     /// 推理 Token 数量
     pub reasoning_tokens: i64,
+
+    /// 结束原因（end_turn / max_tokens / tool_use / stop_sequence 等）
+    #[serde(default)]
+    pub stop_reason: Option<String>,
 }
 
 impl TokenInfo {
@@ -56,9 +60,16 @@ impl TokenInfo {
             cache_creation_1h_tokens,
             cache_read_tokens,
             reasoning_tokens,
+            stop_reason: None,
         }
     }
 
+    /// 设置结束原因
+    pub fn with_stop_reason(mut self, stop_reason: Option<String>) -> Self {
+        self.stop_reason = stop_reason;
+        self
+    }
+
     /// 计算总 Token 数量
     pub fn total_tokens(&self) -> i64 {
         self.input_tokens
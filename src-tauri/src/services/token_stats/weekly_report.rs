@@ -0,0 +1,251 @@
+//! Token 统计周报
+//!
+//! 按本地时区的自然周（周一 00:00 起）汇总每日成本、成本最高的模型与总请求数，
+//! 并与上一周对比计算环比变化
+
+use super::analytics::{CostGroupBy, CostSummaryQuery, TokenStatsAnalytics};
+use super::budget::local_midnight_ms;
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// 周报中列出的模型数量上限
+pub const TOP_MODEL_LIMIT: usize = 5;
+
+/// 周报中单日的成本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyDailyCost {
+    /// 日期（YYYY-MM-DD，本地时区）
+    pub date: String,
+    /// 当日起点（毫秒时间戳）
+    pub start_time: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 请求数
+    pub request_count: i64,
+}
+
+/// 周报中的模型成本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyModelCost {
+    pub model: String,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 请求数
+    pub request_count: i64,
+}
+
+/// 周报
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyReport {
+    /// 本周起点（周一 00:00，毫秒时间戳）
+    pub week_start: i64,
+    /// 本周终点（下周一 00:00，毫秒时间戳，不含）
+    pub week_end: i64,
+    /// 周一至周日每日成本
+    pub daily_costs: Vec<WeeklyDailyCost>,
+    /// 成本最高的模型（按成本降序，最多 `TOP_MODEL_LIMIT` 个）
+    pub top_models: Vec<WeeklyModelCost>,
+    /// 本周总成本（USD）
+    pub total_cost: f64,
+    /// 本周总请求数
+    pub total_requests: i64,
+    /// 上周总成本（USD）
+    pub previous_total_cost: f64,
+    /// 上周总请求数
+    pub previous_total_requests: i64,
+    /// 成本环比变化百分比（上周无花费时为 None）
+    pub cost_change_percent: Option<f64>,
+    /// 请求数环比变化百分比（上周无请求时为 None）
+    pub request_change_percent: Option<f64>,
+}
+
+impl WeeklyReport {
+    /// 生成以 `week_start` 所在周为本周的周报
+    pub fn generate(analytics: &TokenStatsAnalytics, week_start: NaiveDate) -> Result<Self> {
+        let week_start = monday_of(week_start);
+        let next_week = week_start + Duration::days(7);
+        let previous_week = week_start - Duration::days(7);
+
+        let mut daily_costs = Vec::with_capacity(7);
+        for offset in 0..7 {
+            let day = week_start + Duration::days(offset);
+            let start_time = local_midnight_ms(day);
+            let end_time = local_midnight_ms(day + Duration::days(1));
+            let (total_cost, request_count) = period_totals(analytics, start_time, end_time)?;
+            daily_costs.push(WeeklyDailyCost {
+                date: day.format("%Y-%m-%d").to_string(),
+                start_time,
+                total_cost,
+                request_count,
+            });
+        }
+
+        let (start_ms, end_ms) = (local_midnight_ms(week_start), local_midnight_ms(next_week));
+        let models = analytics.query_cost_summary(&CostSummaryQuery {
+            start_time: Some(start_ms),
+            end_time: Some(end_ms - 1),
+            group_by: CostGroupBy::Model,
+            ..Default::default()
+        })?;
+        let total_cost = models.iter().map(|m| m.total_cost).sum();
+        let total_requests = models.iter().map(|m| m.request_count).sum();
+        let top_models = models
+            .into_iter()
+            .take(TOP_MODEL_LIMIT)
+            .map(|m| WeeklyModelCost {
+                model: m.group_name,
+                total_cost: m.total_cost,
+                request_count: m.request_count,
+            })
+            .collect();
+
+        let (previous_total_cost, previous_total_requests) =
+            period_totals(analytics, local_midnight_ms(previous_week), start_ms)?;
+
+        Ok(Self {
+            week_start: start_ms,
+            week_end: end_ms,
+            daily_costs,
+            top_models,
+            total_cost,
+            total_requests,
+            previous_total_cost,
+            previous_total_requests,
+            cost_change_percent: change_percent(total_cost, previous_total_cost),
+            request_change_percent: change_percent(
+                total_requests as f64,
+                previous_total_requests as f64,
+            ),
+        })
+    }
+}
+
+/// 指定日期往前 `week_offset` 周所在周的周一（0 为本周，1 为上周）
+pub fn week_start_date(today: NaiveDate, week_offset: u32) -> NaiveDate {
+    monday_of(today) - Duration::weeks(week_offset as i64)
+}
+
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// 时间段 `[start, end)` 内的总成本与请求数
+fn period_totals(analytics: &TokenStatsAnalytics, start: i64, end: i64) -> Result<(f64, i64)> {
+    let summaries = analytics.query_cost_summary(&CostSummaryQuery {
+        start_time: Some(start),
+        end_time: Some(end - 1),
+        group_by: CostGroupBy::Tool,
+        ..Default::default()
+    })?;
+    Ok((
+        summaries.iter().map(|s| s.total_cost).sum(),
+        summaries.iter().map(|s| s.request_count).sum(),
+    ))
+}
+
+/// 环比变化百分比（上期为 0 时无法计算）
+fn change_percent(current: f64, previous: f64) -> Option<f64> {
+    (previous > 0.0).then(|| (current - previous) / previous * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::TokenLog;
+    use crate::services::token_stats::db::TokenStatsDb;
+    use tempfile::tempdir;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// 本地时间某日 12:00 的毫秒时间戳
+    fn noon_ms(day: NaiveDate) -> i64 {
+        local_midnight_ms(day) + 12 * 3600 * 1000
+    }
+
+    fn make_log(timestamp: i64, model: &str, total_cost: f64) -> TokenLog {
+        TokenLog {
+            model: model.to_string(),
+            total_cost,
+            ..TokenLog::fixture("claude_code", timestamp)
+        }
+    }
+
+    #[test]
+    fn test_week_start_date() {
+        // 2026-03-05 为周四
+        assert_eq!(week_start_date(date(2026, 3, 5), 0), date(2026, 3, 2));
+        assert_eq!(week_start_date(date(2026, 3, 5), 1), date(2026, 2, 23));
+        // 周一与周日属于同一周
+        assert_eq!(week_start_date(date(2026, 3, 2), 0), date(2026, 3, 2));
+        assert_eq!(week_start_date(date(2026, 3, 8), 0), date(2026, 3, 2));
+        assert_eq!(change_percent(150.0, 100.0), Some(50.0));
+        assert_eq!(change_percent(10.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_weekly_report_aggregation() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_weekly_report.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let monday = date(2026, 3, 2);
+        let logs = [
+            // 本周：周一 2 条、周三 1 条、周日 1 条
+            make_log(noon_ms(monday), "claude-opus-4-1", 3.0),
+            make_log(noon_ms(monday), "claude-sonnet-4-5", 1.0),
+            make_log(noon_ms(date(2026, 3, 4)), "claude-opus-4-1", 2.0),
+            make_log(noon_ms(date(2026, 3, 8)), "claude-haiku-4-5", 0.5),
+            // 上周 2 条
+            make_log(noon_ms(date(2026, 2, 24)), "claude-sonnet-4-5", 2.0),
+            make_log(noon_ms(date(2026, 3, 1)), "claude-sonnet-4-5", 2.0),
+            // 下周（不计入）
+            make_log(noon_ms(date(2026, 3, 9)), "claude-opus-4-1", 100.0),
+        ];
+        for log in &logs {
+            db.insert_log(log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let report = WeeklyReport::generate(&analytics, date(2026, 3, 5)).unwrap();
+
+        assert_eq!(report.week_start, local_midnight_ms(monday));
+        assert_eq!(report.week_end, local_midnight_ms(date(2026, 3, 9)));
+        assert_eq!(report.total_requests, 4);
+        assert!((report.total_cost - 6.5).abs() < 1e-9);
+
+        // 每日成本覆盖周一至周日，无请求的日期为 0
+        assert_eq!(report.daily_costs.len(), 7);
+        assert_eq!(report.daily_costs[0].date, "2026-03-02");
+        assert_eq!(report.daily_costs[0].request_count, 2);
+        assert!((report.daily_costs[0].total_cost - 4.0).abs() < 1e-9);
+        assert_eq!(report.daily_costs[1].request_count, 0);
+        assert!((report.daily_costs[2].total_cost - 2.0).abs() < 1e-9);
+        assert_eq!(report.daily_costs[6].date, "2026-03-08");
+        assert_eq!(report.daily_costs[6].request_count, 1);
+
+        // top 模型按成本降序
+        let models: Vec<&str> = report.top_models.iter().map(|m| m.model.as_str()).collect();
+        assert_eq!(
+            models,
+            vec!["claude-opus-4-1", "claude-sonnet-4-5", "claude-haiku-4-5"]
+        );
+        assert_eq!(report.top_models[0].request_count, 2);
+
+        // 环比：成本 6.5 vs 4.0，请求 4 vs 2
+        assert_eq!(report.previous_total_requests, 2);
+        assert!((report.previous_total_cost - 4.0).abs() < 1e-9);
+        assert!((report.cost_change_percent.unwrap() - 62.5).abs() < 1e-9);
+        assert!((report.request_change_percent.unwrap() - 100.0).abs() < 1e-9);
+
+        // 上上周无数据：环比无法计算
+        let report = WeeklyReport::generate(&analytics, date(2026, 2, 23)).unwrap();
+        assert_eq!(report.total_requests, 2);
+        assert_eq!(report.previous_total_requests, 0);
+        assert_eq!(report.cost_change_percent, None);
+        assert_eq!(report.request_change_percent, None);
+    }
+}
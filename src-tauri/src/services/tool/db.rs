@@ -8,6 +8,7 @@ use crate::services::tool::tools_config::{
     LocalToolInstance, SSHToolInstance, ToolsConfig, WSLToolInstance,
 };
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// 工具实例数据库管理（JSON 存储）
@@ -107,7 +108,9 @@ impl ToolInstanceDB {
                     install_path: instance.install_path.clone(),
                     installer_path: instance.installer_path.clone(), // 新增
                     install_method: instance.install_method.clone(),
+                    package_manager: instance.package_manager,
                     is_builtin: instance.is_builtin,
+                    custom_env: instance.custom_env.clone(),
                     created_at: instance.created_at,
                     updated_at: instance.updated_at,
                 });
@@ -122,6 +125,7 @@ impl ToolInstanceDB {
                         install_path: instance.install_path.clone(),
                         install_method: instance.install_method.clone(),
                         is_builtin: instance.is_builtin,
+                        custom_env: instance.custom_env.clone(),
                         created_at: instance.created_at,
                         updated_at: instance.updated_at,
                     });
@@ -137,6 +141,7 @@ impl ToolInstanceDB {
                         install_path: instance.install_path.clone(),
                         install_method: instance.install_method.clone(),
                         is_builtin: instance.is_builtin,
+                        custom_env: instance.custom_env.clone(),
                         created_at: instance.created_at,
                         updated_at: instance.updated_at,
                     });
@@ -185,6 +190,7 @@ impl ToolInstanceDB {
                     local.version = instance.version.clone();
                     local.install_path = instance.install_path.clone();
                     local.install_method = instance.install_method.clone();
+                    local.custom_env = instance.custom_env.clone();
                     local.updated_at = instance.updated_at;
                     true
                 } else {
@@ -201,6 +207,7 @@ impl ToolInstanceDB {
                     wsl.version = instance.version.clone();
                     wsl.install_path = instance.install_path.clone();
                     wsl.install_method = instance.install_method.clone();
+                    wsl.custom_env = instance.custom_env.clone();
                     wsl.updated_at = instance.updated_at;
                     true
                 } else {
@@ -217,6 +224,7 @@ impl ToolInstanceDB {
                     ssh.version = instance.version.clone();
                     ssh.install_path = instance.install_path.clone();
                     ssh.install_method = instance.install_method.clone();
+                    ssh.custom_env = instance.custom_env.clone();
                     ssh.updated_at = instance.updated_at;
                     true
                 } else {
@@ -284,6 +292,22 @@ impl ToolInstanceDB {
         Ok(has_tools)
     }
 
+    /// 设置实例的自定义环境变量（执行 --version / 更新命令时注入）
+    pub fn set_instance_env(
+        &self,
+        instance_id: &str,
+        env: HashMap<String, String>,
+    ) -> Result<ToolInstance> {
+        let mut instance = self
+            .get_instance(instance_id)?
+            .ok_or_else(|| anyhow::anyhow!("实例不存在: {}", instance_id))?;
+
+        instance.custom_env = env;
+        instance.updated_at = chrono::Utc::now().timestamp();
+        self.update_instance(&instance)?;
+        Ok(instance)
+    }
+
     /// 更新或插入实例（upsert）
     pub fn upsert_instance(&self, instance: &ToolInstance) -> Result<()> {
         if self.instance_exists(&instance.instance_id)? {
@@ -350,6 +374,7 @@ impl ToolInstanceDB {
                 tool_name: row.get(2)?,
                 tool_type: ToolType::parse(&tool_type_str).unwrap_or(ToolType::Local),
                 install_method: None, // 旧数据没有 install_method，需要重新检测
+                package_manager: None,
                 installed: installed_int != 0,
                 version: row.get(5)?,
                 install_path: row.get(6)?,
@@ -357,6 +382,7 @@ impl ToolInstanceDB {
                 wsl_distro: row.get(7)?,
                 ssh_config,
                 is_builtin: is_builtin_int != 0,
+                custom_env: Default::default(),
                 created_at: row.get(14)?,
                 updated_at: row.get(15)?,
             })
@@ -409,6 +435,7 @@ mod tests {
             tool_name: "Claude Code".to_string(),
             tool_type: ToolType::Local,
             install_method: Some(InstallMethod::Npm),
+            package_manager: None,
             installed: true,
             version: Some("1.0.0".to_string()),
             install_path: Some("/usr/local/bin/test".to_string()),
@@ -416,6 +443,7 @@ mod tests {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: true,
+            custom_env: Default::default(),
             created_at: 1733299200,
             updated_at: 1733299200,
         };
@@ -5,6 +5,7 @@
 
 use crate::data::DataManager;
 use crate::models::InstallMethod;
+use crate::services::tool::package_manager::PackageManager;
 use crate::utils::CommandExecutor;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -114,6 +115,14 @@ pub trait ToolDetector: Send + Sync {
         force: bool,
     ) -> Result<()>;
 
+    /// 使用指定的 Node 包管理器（npm/pnpm/yarn/bun）全局安装工具
+    async fn install_with_package_manager(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()>;
+
     /// 更新工具
     ///
     /// 参数：
@@ -121,6 +130,38 @@ pub trait ToolDetector: Send + Sync {
     /// - force: 是否强制更新
     async fn update(&self, executor: &CommandExecutor, force: bool) -> Result<()>;
 
+    /// 检查能否自动卸载以指定方式安装的工具（卸载前调用，避免先备份配置再失败）
+    ///
+    /// 默认实现：仅 npm 安装的工具可自动卸载，
+    /// 其他安装方式（官方脚本等）无法可靠定位安装文件，提示用户手动卸载
+    fn check_uninstall(&self, method: &InstallMethod) -> Result<()> {
+        match method {
+            InstallMethod::Npm => Ok(()),
+            InstallMethod::Official => {
+                anyhow::bail!("❌ {} 通过官方脚本安装，请手动卸载", self.tool_name())
+            }
+            _ => anyhow::bail!("❌ {} 不是通过 npm 安装的，请手动卸载", self.tool_name()),
+        }
+    }
+
+    /// 卸载工具
+    ///
+    /// 默认实现：通过 `check_uninstall` 后执行 `npm uninstall -g <package>`
+    async fn uninstall(&self, executor: &CommandExecutor, method: &InstallMethod) -> Result<()> {
+        self.check_uninstall(method)?;
+        self.uninstall_with_package_manager(executor, PackageManager::Npm)
+            .await
+    }
+
+    /// 使用指定的 Node 包管理器（npm/pnpm/yarn/bun）全局卸载工具
+    async fn uninstall_with_package_manager(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+    ) -> Result<()> {
+        manager.uninstall(executor, self.npm_package()).await
+    }
+
     // ==================== 配置管理 ====================
 
     /// 读取工具配置
@@ -240,3 +281,80 @@ pub trait ToolDetector: Send + Sync {
         }
     }
 }
+
+// ==================== npm 安装/卸载辅助 ====================
+
+/// 执行 npm 全局安装命令（逐行上报输出，见 `CommandExecutor::with_output_sink`）
+///
+/// npm 的进度与警告大多输出到 stderr，两个输出流都会上报；失败时区分权限错误（EACCES）
+pub async fn execute_npm_install(executor: &CommandExecutor, command: &str) -> Result<()> {
+    let result = executor.execute_streaming(command).await;
+    if result.success {
+        Ok(())
+    } else {
+        anyhow::bail!(npm_error("安装", &result.stdout, &result.stderr))
+    }
+}
+
+/// 构造 npm 全局卸载命令
+pub fn npm_uninstall_command(package: &str) -> String {
+    format!("npm uninstall -g {}", package)
+}
+
+/// 执行 npm 全局卸载（错误处理与安装一致）
+pub async fn execute_npm_uninstall(executor: &CommandExecutor, package: &str) -> Result<()> {
+    let result = executor
+        .execute_streaming(&npm_uninstall_command(package))
+        .await;
+    if result.success {
+        Ok(())
+    } else {
+        anyhow::bail!(npm_error("卸载", &result.stdout, &result.stderr))
+    }
+}
+
+/// 生成 npm 安装/卸载失败的错误信息
+fn npm_error(action: &str, stdout: &str, stderr: &str) -> String {
+    if stderr.contains("EACCES") || stdout.contains("EACCES") {
+        format!(
+            "❌ npm {}失败：没有写入 npm 全局目录的权限 (EACCES)\n\n\
+             请以管理员权限重试，或将 npm 全局目录设置到用户目录：\n\
+             npm config set prefix ~/.npm-global\n\n{}",
+            action, stderr
+        )
+    } else {
+        format!("❌ npm {}失败\n\n{}", action, stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npm_install_error_detects_permission_denied() {
+        let stderr = "npm ERR! code EACCES\nnpm ERR! syscall mkdir\nnpm ERR! path /usr/local/lib/node_modules";
+        let message = npm_error("安装", "", stderr);
+        assert!(message.contains("权限"));
+        assert!(message.contains("npm config set prefix"));
+        assert!(message.ends_with(stderr));
+
+        let message = npm_error("安装", "", "npm ERR! code ETIMEDOUT");
+        assert_eq!(message, "❌ npm 安装失败\n\nnpm ERR! code ETIMEDOUT");
+
+        let message = npm_error("卸载", "", stderr);
+        assert!(message.starts_with("❌ npm 卸载失败：没有写入 npm 全局目录的权限"));
+    }
+
+    #[test]
+    fn test_npm_uninstall_command() {
+        assert_eq!(
+            npm_uninstall_command("@anthropic-ai/claude-code"),
+            "npm uninstall -g @anthropic-ai/claude-code"
+        );
+        assert_eq!(
+            npm_uninstall_command("@google/gemini-cli"),
+            "npm uninstall -g @google/gemini-cli"
+        );
+    }
+}
@@ -3,6 +3,7 @@
 // Claude Code 工具的检测、安装、配置管理实现
 
 use super::super::detector_trait::ToolDetector;
+use super::super::package_manager::PackageManager;
 use crate::data::DataManager;
 use crate::models::InstallMethod;
 use crate::services::version::{VersionInfo, VersionService};
@@ -111,7 +112,10 @@ impl ToolDetector for ClaudeCodeDetector {
     ) -> Result<()> {
         match method {
             InstallMethod::Official => self.install_official(executor, force).await,
-            InstallMethod::Npm => self.install_npm(executor, force).await,
+            InstallMethod::Npm => {
+                self.install_package(executor, PackageManager::Npm, force)
+                    .await
+            }
             InstallMethod::Brew => {
                 anyhow::bail!("Claude Code 不支持 Homebrew 安装，请使用官方安装或 npm")
             }
@@ -121,6 +125,15 @@ impl ToolDetector for ClaudeCodeDetector {
         }
     }
 
+    async fn install_with_package_manager(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()> {
+        self.install_package(executor, manager, force).await
+    }
+
     async fn update(&self, executor: &CommandExecutor, _force: bool) -> Result<()> {
         // 检测当前安装方法
         let method = self.detect_install_method(executor).await;
@@ -210,12 +223,13 @@ impl ClaudeCodeDetector {
         }
     }
 
-    /// 使用 npm 安装
-    async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
-        if !executor.command_exists_async("npm").await {
-            anyhow::bail!("npm 未安装，请先安装 Node.js");
-        }
-
+    /// 使用 Node 包管理器（npm/pnpm/yarn/bun）全局安装
+    async fn install_package(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()> {
         // 获取推荐版本
         let version_hint = if !force {
             let version_service = VersionService::new();
@@ -235,15 +249,7 @@ impl ClaudeCodeDetector {
             _ => "@anthropic-ai/claude-code@latest".to_string(),
         };
 
-        let command =
-            format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
-        }
+        manager.install(executor, &package_spec).await
     }
 
     /// 使用 npm 更新
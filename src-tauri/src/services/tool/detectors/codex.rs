@@ -3,6 +3,7 @@
 // CodeX 工具的检测、安装、配置管理实现
 
 use super::super::detector_trait::ToolDetector;
+use super::super::package_manager::PackageManager;
 use crate::data::DataManager;
 use crate::models::InstallMethod;
 use crate::services::version::{VersionInfo, VersionService};
@@ -108,7 +109,10 @@ impl ToolDetector for CodeXDetector {
             InstallMethod::Official => {
                 anyhow::bail!("CodeX 官方安装方法尚未实现，请使用 npm 或 Homebrew")
             }
-            InstallMethod::Npm => self.install_npm(executor, force).await,
+            InstallMethod::Npm => {
+                self.install_package(executor, PackageManager::Npm, force)
+                    .await
+            }
             InstallMethod::Brew => self.install_brew(executor).await,
             InstallMethod::Other => {
                 anyhow::bail!("不支持 APP 内安装，请手动安装")
@@ -116,6 +120,15 @@ impl ToolDetector for CodeXDetector {
         }
     }
 
+    async fn install_with_package_manager(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()> {
+        self.install_package(executor, manager, force).await
+    }
+
     async fn update(&self, executor: &CommandExecutor, _force: bool) -> Result<()> {
         let method = self.detect_install_method(executor).await;
 
@@ -186,12 +199,13 @@ impl ToolDetector for CodeXDetector {
 // ==================== 私有实现方法 ====================
 
 impl CodeXDetector {
-    /// 使用 npm 安装
-    async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
-        if !executor.command_exists_async("npm").await {
-            anyhow::bail!("npm 未安装");
-        }
-
+    /// 使用 Node 包管理器（npm/pnpm/yarn/bun）全局安装
+    async fn install_package(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()> {
         let version_hint = if !force {
             let version_service = VersionService::new();
             version_service
@@ -208,15 +222,7 @@ impl CodeXDetector {
             _ => "@openai/codex@latest".to_string(),
         };
 
-        let command =
-            format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
-        }
+        manager.install(executor, &package_spec).await
     }
 
     /// 使用 Homebrew 安装
@@ -3,6 +3,7 @@
 // Gemini CLI 工具的检测、安装、配置管理实现
 
 use super::super::detector_trait::ToolDetector;
+use super::super::package_manager::PackageManager;
 use crate::data::DataManager;
 use crate::models::InstallMethod;
 use crate::services::version::{VersionInfo, VersionService};
@@ -107,7 +108,10 @@ impl ToolDetector for GeminiCLIDetector {
         force: bool,
     ) -> Result<()> {
         match method {
-            InstallMethod::Npm => self.install_npm(executor, force).await,
+            InstallMethod::Npm => {
+                self.install_package(executor, PackageManager::Npm, force)
+                    .await
+            }
             InstallMethod::Brew => self.install_brew(executor).await,
             InstallMethod::Official | InstallMethod::Other => {
                 anyhow::bail!("Gemini CLI 支持 npm 或 brew 安装")
@@ -115,6 +119,15 @@ impl ToolDetector for GeminiCLIDetector {
         }
     }
 
+    async fn install_with_package_manager(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()> {
+        self.install_package(executor, manager, force).await
+    }
+
     async fn update(&self, executor: &CommandExecutor, _force: bool) -> Result<()> {
         // 根据当前安装方式选择更新命令
         let method = self.detect_install_method(executor).await;
@@ -146,12 +159,13 @@ impl ToolDetector for GeminiCLIDetector {
 // ==================== 私有实现方法 ====================
 
 impl GeminiCLIDetector {
-    /// 使用 npm 安装
-    async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
-        if !executor.command_exists_async("npm").await {
-            anyhow::bail!("npm 未安装");
-        }
-
+    /// 使用 Node 包管理器（npm/pnpm/yarn/bun）全局安装
+    async fn install_package(
+        &self,
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        force: bool,
+    ) -> Result<()> {
         let version_hint = if !force {
             let version_service = VersionService::new();
             version_service
@@ -168,15 +182,7 @@ impl GeminiCLIDetector {
             _ => "@google/gemini-cli@latest".to_string(),
         };
 
-        let command =
-            format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
-        }
+        manager.install(executor, &package_spec).await
     }
 
     /// 使用 npm 更新
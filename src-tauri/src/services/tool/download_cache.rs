@@ -0,0 +1,317 @@
+// Download Cache
+//
+// 安装包本地缓存：按 SHA256 内容寻址存储下载文件，
+// 索引记录来源 URL 与最近使用时间，超出容量上限时按 LRU 淘汰
+
+use crate::models::config::DownloadCacheConfig;
+use crate::utils::config::read_global_config;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+/// 缓存索引文件名
+const INDEX_FILE: &str = "index.json";
+
+/// 单个缓存条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 来源 URL
+    url: String,
+    /// 文件大小（字节）
+    size: u64,
+    /// 最近使用时间（Unix 时间戳，毫秒）
+    last_used: i64,
+}
+
+/// 缓存占用信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadCacheUsage {
+    pub entries: usize,
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+}
+
+/// 安装包本地缓存
+pub struct DownloadCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// 串行化索引读写，避免并发下载互相覆盖
+    lock: Mutex<()>,
+}
+
+impl DownloadCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 默认缓存目录（系统缓存目录下的 duckcoding/downloads，无缓存目录时退回用户主目录）
+    pub fn default_dir() -> Result<PathBuf> {
+        let base = dirs::cache_dir()
+            .or_else(dirs::home_dir)
+            .context("无法确定缓存目录：系统缓存目录与用户主目录均不可用")?;
+        Ok(base.join("duckcoding").join("downloads"))
+    }
+
+    /// 根据配置创建缓存（禁用时返回 None）
+    pub fn from_config(config: &DownloadCacheConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(
+            Self::default_dir()?,
+            config.max_size_mb.saturating_mul(1024 * 1024),
+        )))
+    }
+
+    /// 根据全局配置创建缓存
+    pub fn from_global_config() -> Result<Option<Self>> {
+        let config = read_global_config()
+            .ok()
+            .flatten()
+            .map(|cfg| cfg.download_cache)
+            .unwrap_or_default();
+        Self::from_config(&config)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 按校验和查找可复用的缓存文件
+    ///
+    /// 只有提供了期望的 SHA256 才能命中（不按 URL 复用未经校验的文件）；
+    /// 命中后会重新计算文件哈希，不一致的条目视为损坏并移除
+    pub async fn lookup(&self, expected_sha256: &str) -> Result<Option<PathBuf>> {
+        let _guard = self.lock.lock().await;
+        let mut index = self.load_index();
+
+        let key = expected_sha256.to_ascii_lowercase();
+        if !index.contains_key(&key) {
+            return Ok(None);
+        }
+
+        let path = self.dir.join(&key);
+        let actual = match sha256_file(&path) {
+            Ok(sha) => sha,
+            Err(_) => {
+                index.remove(&key);
+                self.save_index(&index)?;
+                return Ok(None);
+            }
+        };
+        if actual != key {
+            tracing::warn!(path = ?path, "缓存文件校验和不匹配，已移除");
+            let _ = fs::remove_file(&path);
+            index.remove(&key);
+            self.save_index(&index)?;
+            return Ok(None);
+        }
+
+        if let Some(entry) = index.get_mut(&key) {
+            entry.last_used = chrono::Utc::now().timestamp_millis();
+        }
+        self.save_index(&index)?;
+        Ok(Some(path))
+    }
+
+    /// 将下载完成的文件存入缓存，返回其 SHA256
+    ///
+    /// 单个文件超过容量上限时不缓存
+    pub async fn store(&self, url: &str, file: &Path) -> Result<String> {
+        let _guard = self.lock.lock().await;
+        let sha = sha256_file(file)?;
+        let size = fs::metadata(file)
+            .with_context(|| format!("读取文件信息失败: {file:?}"))?
+            .len();
+        if size > self.max_bytes {
+            tracing::debug!(
+                size,
+                max_bytes = self.max_bytes,
+                "安装包超过缓存上限，跳过缓存"
+            );
+            return Ok(sha);
+        }
+
+        fs::create_dir_all(&self.dir).context("创建下载缓存目录失败")?;
+        let target = self.dir.join(&sha);
+        if !target.exists() {
+            fs::copy(file, &target).with_context(|| format!("写入下载缓存失败: {target:?}"))?;
+        }
+
+        let mut index = self.load_index();
+        // 同一 URL 只保留最新内容
+        let stale: Vec<String> = index
+            .iter()
+            .filter(|(key, entry)| entry.url == url && **key != sha)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            let _ = fs::remove_file(self.dir.join(&key));
+            index.remove(&key);
+        }
+        index.insert(
+            sha.clone(),
+            CacheEntry {
+                url: url.to_string(),
+                size,
+                last_used: chrono::Utc::now().timestamp_millis(),
+            },
+        );
+        self.evict(&mut index, &sha);
+        self.save_index(&index)?;
+        Ok(sha)
+    }
+
+    /// 当前缓存占用
+    pub async fn usage(&self) -> DownloadCacheUsage {
+        let _guard = self.lock.lock().await;
+        let index = self.load_index();
+        DownloadCacheUsage {
+            entries: index.len(),
+            total_bytes: index.values().map(|e| e.size).sum(),
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    /// 清空缓存，返回释放的字节数
+    pub async fn clear(&self) -> Result<u64> {
+        let _guard = self.lock.lock().await;
+        let index = self.load_index();
+        let freed = index.values().map(|e| e.size).sum();
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .with_context(|| format!("清理下载缓存失败: {:?}", self.dir))?;
+        }
+        Ok(freed)
+    }
+
+    /// 按最近使用时间淘汰，直到总大小不超过上限（保留 `keep`）
+    fn evict(&self, index: &mut HashMap<String, CacheEntry>, keep: &str) {
+        let mut total: u64 = index.values().map(|e| e.size).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        let mut candidates: Vec<(String, i64, u64)> = index
+            .iter()
+            .filter(|(key, _)| key.as_str() != keep)
+            .map(|(key, e)| (key.clone(), e.last_used, e.size))
+            .collect();
+        candidates.sort_by_key(|(_, last_used, _)| *last_used);
+
+        for (key, _, size) in candidates {
+            if total <= self.max_bytes {
+                break;
+            }
+            let _ = fs::remove_file(self.dir.join(&key));
+            index.remove(&key);
+            total = total.saturating_sub(size);
+            tracing::debug!(sha256 = %key, "淘汰下载缓存条目");
+        }
+    }
+
+    fn load_index(&self) -> HashMap<String, CacheEntry> {
+        fs::read_to_string(self.dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_index(&self, index: &HashMap<String, CacheEntry>) -> Result<()> {
+        fs::create_dir_all(&self.dir).context("创建下载缓存目录失败")?;
+        let content = serde_json::to_string_pretty(index).context("序列化缓存索引失败")?;
+        fs::write(self.dir.join(INDEX_FILE), content).context("写入缓存索引失败")
+    }
+}
+
+/// 流式计算文件 SHA256（安装包可能较大，避免一次性读入内存）
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("打开文件失败: {path:?}"))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .with_context(|| format!("读取文件失败: {path:?}"))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_file(dir: &Path, name: &str, content: &[u8]) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_store_and_lookup() {
+        let tmp = TempDir::new().unwrap();
+        let cache = DownloadCache::new(tmp.path().join("cache"), 1024);
+        let file = write_file(tmp.path(), "pkg.bin", b"package-v1");
+
+        let sha = cache
+            .store("https://example.com/pkg.bin", &file)
+            .await
+            .unwrap();
+        assert_eq!(sha, sha256_file(&file).unwrap());
+
+        // 按校验和命中（不区分大小写）
+        let hit = cache.lookup(&sha).await.unwrap();
+        assert_eq!(fs::read(hit.unwrap()).unwrap(), b"package-v1");
+        assert!(cache.lookup(&sha.to_uppercase()).await.unwrap().is_some());
+        assert!(cache.lookup(&"0".repeat(64)).await.unwrap().is_none());
+
+        // 缓存文件被篡改后不再复用
+        fs::write(cache.dir().join(&sha), b"tampered").unwrap();
+        assert!(cache.lookup(&sha).await.unwrap().is_none());
+        assert_eq!(cache.usage().await.entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_evict_and_clear() {
+        let tmp = TempDir::new().unwrap();
+        let cache = DownloadCache::new(tmp.path().join("cache"), 20);
+
+        let a = write_file(tmp.path(), "a", &[b'a'; 10]);
+        let b = write_file(tmp.path(), "b", &[b'b'; 10]);
+        let c = write_file(tmp.path(), "c", &[b'c'; 10]);
+        let sha_a = cache.store("https://example.com/a", &a).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        cache.store("https://example.com/b", &b).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let sha_c = cache.store("https://example.com/c", &c).await.unwrap();
+
+        // 最早使用的 a 被淘汰
+        assert!(cache.lookup(&sha_a).await.unwrap().is_none());
+        assert!(cache.lookup(&sha_c).await.unwrap().is_some());
+        let usage = cache.usage().await;
+        assert_eq!(usage.entries, 2);
+        assert_eq!(usage.total_bytes, 20);
+
+        // 超过上限的单个文件不缓存
+        let big = write_file(tmp.path(), "big", &[b'x'; 30]);
+        let sha_big = cache.store("https://example.com/big", &big).await.unwrap();
+        assert!(cache.lookup(&sha_big).await.unwrap().is_none());
+
+        assert_eq!(cache.clear().await.unwrap(), 20);
+        assert_eq!(cache.usage().await.entries, 0);
+        assert!(!cache.dir().exists());
+    }
+}
@@ -1,5 +1,7 @@
+use super::download_cache::{sha256_file, DownloadCache};
 use anyhow::{Context, Result};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
@@ -16,11 +18,25 @@ pub enum DownloadEvent {
 
 /// 文件下载器
 #[derive(Clone)]
-pub struct FileDownloader {}
+pub struct FileDownloader {
+    /// 安装包本地缓存（None 表示不缓存）
+    cache: Option<Arc<DownloadCache>>,
+}
 
 impl FileDownloader {
     pub fn new() -> Self {
-        Self {}
+        Self { cache: None }
+    }
+
+    /// 创建带本地缓存的下载器
+    pub fn with_cache(cache: DownloadCache) -> Self {
+        Self {
+            cache: Some(Arc::new(cache)),
+        }
+    }
+
+    pub fn cache(&self) -> Option<&DownloadCache> {
+        self.cache.as_deref()
     }
 
     /// 创建 HTTP 客户端（每次调用时动态创建，以确保使用最新的代理配置）
@@ -34,10 +50,84 @@ impl FileDownloader {
         &self,
         url: &str,
         file_path: &PathBuf,
+        progress_callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(DownloadEvent) + Send + 'static,
+    {
+        self.download_with_checksum(url, file_path, None, progress_callback)
+            .await
+    }
+
+    /// 异步下载文件并校验 SHA256
+    ///
+    /// 启用缓存且提供了校验和时优先复用校验和匹配的本地缓存，下载完成后写入缓存
+    pub async fn download_with_checksum<F>(
+        &self,
+        url: &str,
+        file_path: &PathBuf,
+        expected_sha256: Option<&str>,
         mut progress_callback: F,
     ) -> Result<()>
     where
         F: FnMut(DownloadEvent) + Send + 'static,
+    {
+        if let (Some(cache), Some(expected)) = (&self.cache, expected_sha256) {
+            match cache.lookup(expected).await {
+                Ok(Some(cached)) => {
+                    progress_callback(DownloadEvent::Started);
+                    if let Some(parent) = file_path.parent() {
+                        tokio::fs::create_dir_all(parent)
+                            .await
+                            .context("Failed to create download directory")?;
+                    }
+                    let size = tokio::fs::copy(&cached, file_path)
+                        .await
+                        .context("Failed to copy cached package")?;
+                    tracing::info!(url = %url, "复用本地缓存的安装包");
+                    progress_callback(DownloadEvent::Progress(size, size));
+                    progress_callback(DownloadEvent::Completed);
+                    return Ok(());
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!(error = ?e, "读取下载缓存失败，改为直接下载"),
+            }
+        }
+
+        self.download_from_network(url, file_path, &mut progress_callback)
+            .await?;
+
+        if let Some(expected) = expected_sha256 {
+            let actual = sha256_file(file_path)?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                let _ = tokio::fs::remove_file(file_path).await;
+                let message = format!("Checksum mismatch: expected {expected}, got {actual}");
+                progress_callback(DownloadEvent::Failed(message.clone()));
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.store(url, file_path).await {
+                tracing::warn!(error = ?e, "写入下载缓存失败");
+            }
+        }
+
+        // 发送完成事件
+        progress_callback(DownloadEvent::Completed);
+
+        Ok(())
+    }
+
+    /// 从网络下载文件（不发送完成事件，由调用方在校验后发送）
+    async fn download_from_network<F>(
+        &self,
+        url: &str,
+        file_path: &PathBuf,
+        progress_callback: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(DownloadEvent),
     {
         // 发送开始事件
         progress_callback(DownloadEvent::Started);
@@ -120,9 +210,6 @@ impl FileDownloader {
             .await
             .context("Failed to flush downloaded file")?;
 
-        // 发送完成事件
-        progress_callback(DownloadEvent::Completed);
-
         Ok(())
     }
 
@@ -149,3 +236,87 @@ impl Default for FileDownloader {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockUpstream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// 启动 mock 下载服务器，返回固定内容
+    async fn spawn_mock_server(body: &'static [u8]) -> (String, MockUpstream) {
+        let upstream = MockUpstream::respond(MockResponse::body(
+            "200 OK",
+            "application/octet-stream",
+            body,
+        ))
+        .await;
+        (format!("{}/tool-installer.bin", upstream.url()), upstream)
+    }
+
+    #[tokio::test]
+    async fn test_download_reuses_cache_on_checksum_match() {
+        let (url, upstream) = spawn_mock_server(b"installer-content").await;
+        let tmp = TempDir::new().unwrap();
+        let downloader =
+            FileDownloader::with_cache(DownloadCache::new(tmp.path().join("cache"), 1024 * 1024));
+
+        let first = tmp.path().join("first/tool-installer.bin");
+        downloader
+            .download_with_progress(&url, &first, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(upstream.hits(), 1);
+        let sha = sha256_file(&first).unwrap();
+
+        // 未提供校验和时不复用缓存
+        downloader
+            .download_with_progress(&url, &first, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(upstream.hits(), 2);
+
+        // 校验和匹配时直接复用缓存，不再发起请求
+        let second = tmp.path().join("second/tool-installer.bin");
+        let completed = Arc::new(AtomicUsize::new(0));
+        let completed_clone = completed.clone();
+        downloader
+            .download_with_checksum(&url, &second, Some(&sha), move |event| {
+                if matches!(event, DownloadEvent::Completed) {
+                    completed_clone.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(upstream.hits(), 2);
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+        assert_eq!(std::fs::read(&second).unwrap(), b"installer-content");
+
+        // 校验和不匹配时重新下载，且下载结果校验失败
+        let third = tmp.path().join("third/tool-installer.bin");
+        let result = downloader
+            .download_with_checksum(&url, &third, Some(&"0".repeat(64)), |_| {})
+            .await;
+        assert!(result.is_err());
+        assert_eq!(upstream.hits(), 3);
+        assert!(!third.exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_without_cache_always_fetches() {
+        let (url, upstream) = spawn_mock_server(b"installer-content").await;
+        let tmp = TempDir::new().unwrap();
+        let downloader = FileDownloader::new();
+        let path = tmp.path().join("tool-installer.bin");
+
+        for _ in 0..2 {
+            downloader
+                .download_with_progress(&url, &path, |_| {})
+                .await
+                .unwrap();
+        }
+        assert_eq!(upstream.hits(), 2);
+        assert!(downloader.cache().is_none());
+    }
+}
@@ -1,7 +1,10 @@
 use crate::models::{InstallMethod, Tool, ToolInstance, UpdateResult};
-use crate::services::tool::DetectorRegistry;
-use crate::utils::parse_version_string;
-use anyhow::Result;
+use crate::services::tool::{DetectorRegistry, PackageManager};
+use crate::utils::{compare_versions, parse_version_string, CommandExecutor, OutputLineSink};
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
 use tokio::time::{timeout, Duration};
 
 /// 安装服务（新架构：委托给 Detector）
@@ -31,6 +34,49 @@ impl InstallerService {
             .await
     }
 
+    /// 安装工具，并逐行上报安装命令的输出（目前 npm 安装支持实时输出）
+    pub async fn install_with_progress(
+        &self,
+        tool: &Tool,
+        method: &InstallMethod,
+        force: bool,
+        sink: OutputLineSink,
+    ) -> Result<()> {
+        let detector = self
+            .detector_registry
+            .get(&tool.id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
+
+        tracing::info!("使用 Detector 安装工具（上报进度）: {}", tool.name);
+        let executor = self.command_executor.with_output_sink(sink);
+        detector.install(&executor, method, force).await
+    }
+
+    /// 使用指定的 Node 包管理器安装工具，并逐行上报安装输出
+    pub async fn install_with_package_manager(
+        &self,
+        tool: &Tool,
+        manager: PackageManager,
+        force: bool,
+        sink: OutputLineSink,
+    ) -> Result<()> {
+        let detector = self
+            .detector_registry
+            .get(&tool.id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
+
+        tracing::info!("使用 {} 安装工具: {}", manager.as_str(), tool.name);
+        let executor = self.command_executor.with_output_sink(sink);
+        detector
+            .install_with_package_manager(&executor, manager, force)
+            .await
+    }
+
+    /// 检测系统中可用的 Node 包管理器
+    pub async fn detect_package_managers(&self) -> Vec<PackageManager> {
+        PackageManager::detect_available(&self.command_executor).await
+    }
+
     /// 更新工具（委托给 Detector）
     pub async fn update(&self, tool: &Tool, force: bool) -> Result<()> {
         let detector = self
@@ -42,6 +88,110 @@ impl InstallerService {
         detector.update(&self.command_executor, force).await
     }
 
+    /// 检查能否自动卸载工具（委托给 Detector），不执行任何卸载操作
+    pub fn check_uninstall(&self, tool: &Tool, method: &InstallMethod) -> Result<()> {
+        let detector = self
+            .detector_registry
+            .get(&tool.id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
+        detector.check_uninstall(method)
+    }
+
+    /// 卸载工具（委托给 Detector）
+    pub async fn uninstall(&self, tool: &Tool, method: &InstallMethod) -> Result<()> {
+        let detector = self
+            .detector_registry
+            .get(&tool.id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
+
+        tracing::info!("使用 Detector 卸载工具: {}", tool.name);
+        detector.uninstall(&self.command_executor, method).await
+    }
+
+    /// 使用指定的 Node 包管理器卸载工具
+    pub async fn uninstall_with_package_manager(
+        &self,
+        tool: &Tool,
+        manager: PackageManager,
+    ) -> Result<()> {
+        let detector = self
+            .detector_registry
+            .get(&tool.id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
+
+        tracing::info!("使用 {} 卸载工具: {}", manager.as_str(), tool.name);
+        detector
+            .uninstall_with_package_manager(&self.command_executor, manager)
+            .await
+    }
+
+    /// 删除本程序写入的原生生效配置文件（卸载后调用）
+    ///
+    /// 仅删除 `Tool::config_files()` 列出的文件，Profile 及其备份、会话历史等保持不变；
+    /// 返回被删除的文件路径
+    pub fn remove_generated_config(tool: &Tool) -> Result<Vec<PathBuf>> {
+        let mut removed = Vec::new();
+        for path in tool
+            .config_files()
+            .iter()
+            .map(|name| tool.config_dir.join(name))
+        {
+            if path.is_file() {
+                fs::remove_file(&path).with_context(|| format!("删除配置文件失败: {:?}", path))?;
+                removed.push(path);
+            }
+        }
+
+        tracing::info!("{} 已清理 {} 个生效配置文件", tool.name, removed.len());
+        Ok(removed)
+    }
+
+    /// 卸载前备份工具配置
+    ///
+    /// 把工具的全部原生配置文件复制到 `<backups_root>/<tool_id>_<时间>/`，
+    /// 返回备份目录供重装后还原；没有任何配置文件时不创建目录，返回 None
+    pub fn backup_config_before_uninstall(
+        tool: &Tool,
+        backups_root: &Path,
+    ) -> Result<Option<PathBuf>> {
+        let files: Vec<PathBuf> = tool
+            .config_files()
+            .iter()
+            .map(|name| tool.config_dir.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+
+        if files.is_empty() {
+            tracing::info!("{} 没有需要备份的配置文件", tool.name);
+            return Ok(None);
+        }
+
+        let backup_dir = backups_root.join(format!(
+            "{}_{}",
+            tool.id,
+            Local::now().format("%Y%m%d_%H%M%S")
+        ));
+        fs::create_dir_all(&backup_dir).context("创建备份目录失败")?;
+
+        for path in &files {
+            if let Some(file_name) = path.file_name() {
+                fs::copy(path, backup_dir.join(file_name))
+                    .with_context(|| format!("备份配置文件失败: {:?}", path))?;
+            }
+        }
+
+        tracing::info!("{} 配置已备份到: {:?}", tool.name, backup_dir);
+        Ok(Some(backup_dir))
+    }
+
+    /// 卸载前备份工具配置到默认备份目录（`~/.duckcoding/backups`）
+    pub fn backup_config_to_default_dir(tool: &Tool) -> Result<Option<PathBuf>> {
+        let backups_root = crate::utils::config::config_dir()
+            .map_err(|e| anyhow::anyhow!(e))?
+            .join("backups");
+        Self::backup_config_before_uninstall(tool, &backups_root)
+    }
+
     /// 检查工具是否已安装（委托给 Detector）
     pub async fn is_installed(&self, tool: &Tool) -> bool {
         if let Some(detector) = self.detector_registry.get(&tool.id) {
@@ -101,14 +251,22 @@ impl InstallerService {
         // 4. 根据安装方法构建更新命令
         let tool_obj = Tool::by_id(&instance.base_id).ok_or_else(|| anyhow::anyhow!("未知工具"))?;
 
+        // 注入实例自定义环境变量
+        let executor = self.command_executor.with_envs(&instance.custom_env);
+
+        // 记录更新前的版本（npm 更新后校验失败时回滚）
+        let old_version = match &instance.install_path {
+            Some(path) => Self::read_version(&executor, path).await,
+            None => None,
+        }
+        .or_else(|| instance.version.clone());
+
+        // Node 包管理器安装：按实例记录的包管理器构造更新与回滚命令
+        let manager = instance.package_manager.unwrap_or(PackageManager::Npm);
+
         let update_cmd = match install_method {
             InstallMethod::Npm => {
-                let package_name = &tool_obj.npm_package;
-                if force {
-                    format!("{} install -g {} --force", installer_path, package_name)
-                } else {
-                    format!("{} update -g {}", installer_path, package_name)
-                }
+                manager.global_update_command(installer_path, &tool_obj.npm_package, force)
             }
             InstallMethod::Brew => {
                 let tool_id = &instance.base_id;
@@ -123,7 +281,7 @@ impl InstallerService {
         tracing::info!("使用安装器 {} 执行更新: {}", installer_path, update_cmd);
 
         let update_future = {
-            let executor = self.command_executor.clone();
+            let executor = executor.clone();
             let cmd = update_cmd.clone();
             async move { executor.execute_async(&cmd).await }
         };
@@ -138,15 +296,61 @@ impl InstallerService {
                     .as_ref()
                     .ok_or_else(|| anyhow::anyhow!("实例缺少安装路径"))?;
 
-                let version_cmd = format!("{} --version", install_path);
-                let version_result = self.command_executor.execute_async(&version_cmd).await;
-
-                let new_version = if version_result.success {
-                    let raw = version_result.stdout.trim();
-                    Some(parse_version_string(raw))
-                } else {
-                    None
-                };
+                let new_version = Self::read_version(&executor, install_path).await;
+
+                // 5. Node 包管理器：校验新版本可运行且与 registry 最新版本一致，否则回滚到旧版本
+                if *install_method == InstallMethod::Npm {
+                    let package = &tool_obj.npm_package;
+                    let latest =
+                        Self::registry_version(&executor, manager, installer_path, package).await;
+                    if let Err(reason) =
+                        verify_updated_version(new_version.as_deref(), latest.as_deref())
+                    {
+                        tracing::warn!(
+                            tool = %instance.base_id,
+                            reason = %reason,
+                            old_version = ?old_version,
+                            "更新后版本校验失败，尝试回滚"
+                        );
+                        let message = match &old_version {
+                            Some(old) => {
+                                let rollback_cmd =
+                                    manager.install_version_command(installer_path, package, old);
+                                let rollback = timeout(
+                                    Duration::from_secs(120),
+                                    executor.execute_async(&rollback_cmd),
+                                )
+                                .await;
+                                match rollback {
+                                    Ok(result) if result.success => {
+                                        format!("⚠️ 更新后校验失败：{reason}\n已回滚到 {old}")
+                                    }
+                                    Ok(result) => format!(
+                                        "⚠️ 更新后校验失败：{reason}\n回滚到 {old} 失败，请手动执行 `{rollback_cmd}`\n\n{}",
+                                        result.stderr.trim()
+                                    ),
+                                    Err(_) => format!(
+                                        "⚠️ 更新后校验失败：{reason}\n回滚到 {old} 超时（120秒），请手动执行 `{rollback_cmd}`"
+                                    ),
+                                }
+                            }
+                            None => format!(
+                                "⚠️ 更新后校验失败：{reason}\n未能获取更新前的版本，无法自动回滚，请手动重新安装"
+                            ),
+                        };
+
+                        return Ok(UpdateResult {
+                            success: false,
+                            message,
+                            has_update: true,
+                            current_version: Self::read_version(&executor, install_path).await,
+                            latest_version: latest,
+                            mirror_version: None,
+                            mirror_is_stale: None,
+                            tool_id: Some(instance.base_id.clone()),
+                        });
+                    }
+                }
 
                 Ok(UpdateResult {
                     success: true,
@@ -174,6 +378,50 @@ impl InstallerService {
     }
 }
 
+impl InstallerService {
+    /// 执行 `<path> --version` 读取版本号（命令失败时返回 None）
+    async fn read_version(executor: &CommandExecutor, install_path: &str) -> Option<String> {
+        let result = executor
+            .execute_async(&format!("{} --version", install_path))
+            .await;
+        result
+            .success
+            .then(|| parse_version_string(result.stdout.trim()))
+    }
+
+    /// 查询 registry 上的最新版本（查询失败或包管理器不支持查询时返回 None）
+    async fn registry_version(
+        executor: &CommandExecutor,
+        manager: PackageManager,
+        installer_path: &str,
+        package: &str,
+    ) -> Option<String> {
+        let command = manager.registry_version_command(installer_path, package)?;
+        let result = executor.execute_async(&command).await;
+        let version = parse_version_string(result.stdout.trim());
+        (result.success && !version.is_empty()).then_some(version)
+    }
+}
+
+/// 校验更新后的版本
+///
+/// 新版本 `--version` 执行失败，或与 registry 最新版本不一致（如更新命令成功但版本未变）时
+/// 返回失败原因；registry 版本未知时只校验命令可运行
+fn verify_updated_version(
+    new_version: Option<&str>,
+    latest_version: Option<&str>,
+) -> std::result::Result<(), String> {
+    let Some(new_version) = new_version else {
+        return Err("新版本执行 --version 失败".to_string());
+    };
+    match latest_version {
+        Some(latest) if compare_versions(new_version, latest) != std::cmp::Ordering::Equal => Err(
+            format!("当前版本 {new_version} 与 npm registry 最新版本 {latest} 不符"),
+        ),
+        _ => Ok(()),
+    }
+}
+
 impl Default for InstallerService {
     fn default() -> Self {
         Self::new()
@@ -193,6 +441,225 @@ mod tests {
         assert!(service.detector_registry.contains("gemini-cli"));
     }
 
+    #[test]
+    fn test_check_uninstall_by_method() {
+        let service = InstallerService::new();
+        let tool = Tool::claude_code();
+        assert!(service.check_uninstall(&tool, &InstallMethod::Npm).is_ok());
+        let err = service
+            .check_uninstall(&tool, &InstallMethod::Official)
+            .unwrap_err();
+        assert!(err.to_string().contains("请手动卸载"));
+        assert!(service
+            .check_uninstall(&tool, &InstallMethod::Brew)
+            .is_err());
+    }
+
+    #[test]
+    fn test_backup_config_before_uninstall() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut tool = Tool::codex();
+        tool.config_dir = temp_dir.path().join(".codex");
+        let backups_root = temp_dir.path().join("backups");
+
+        // 没有配置文件时不创建备份
+        assert!(
+            InstallerService::backup_config_before_uninstall(&tool, &backups_root)
+                .unwrap()
+                .is_none()
+        );
+        assert!(!backups_root.exists());
+
+        fs::create_dir_all(&tool.config_dir).unwrap();
+        fs::write(tool.config_dir.join("config.toml"), "model = \"gpt-5\"\n").unwrap();
+        fs::write(
+            tool.config_dir.join("auth.json"),
+            r#"{"OPENAI_API_KEY":"sk"}"#,
+        )
+        .unwrap();
+        fs::write(tool.config_dir.join("history.jsonl"), "{}").unwrap();
+
+        let backup_dir = InstallerService::backup_config_before_uninstall(&tool, &backups_root)
+            .unwrap()
+            .expect("应生成备份目录");
+
+        assert!(backup_dir.starts_with(&backups_root));
+        assert!(backup_dir
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("codex_"));
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("config.toml")).unwrap(),
+            "model = \"gpt-5\"\n"
+        );
+        assert!(backup_dir.join("auth.json").exists());
+        // 仅备份配置文件，不包含会话历史等数据
+        assert!(!backup_dir.join("history.jsonl").exists());
+        // 原配置保持不变
+        assert!(tool.config_dir.join("config.toml").exists());
+    }
+
+    #[test]
+    fn test_remove_generated_config_keeps_profile_backups() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut tool = Tool::gemini_cli();
+        tool.config_dir = temp_dir.path().join(".gemini");
+
+        // 配置目录不存在时无需清理
+        assert!(InstallerService::remove_generated_config(&tool)
+            .unwrap()
+            .is_empty());
+
+        fs::create_dir_all(&tool.config_dir).unwrap();
+        fs::write(tool.config_dir.join("settings.json"), "{}").unwrap();
+        fs::write(tool.config_dir.join(".env"), "GEMINI_API_KEY=sk\n").unwrap();
+        fs::write(tool.backup_path("work"), "{}").unwrap();
+
+        let removed = InstallerService::remove_generated_config(&tool).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(!tool.config_dir.join("settings.json").exists());
+        assert!(!tool.config_dir.join(".env").exists());
+        // Profile 备份保留
+        assert!(tool.backup_path("work").exists());
+    }
+
+    #[test]
+    fn test_verify_updated_version() {
+        assert!(verify_updated_version(Some("2.0.1"), Some("2.0.1")).is_ok());
+        // registry 版本未知时只要求命令可运行
+        assert!(verify_updated_version(Some("2.0.1"), None).is_ok());
+        assert!(verify_updated_version(None, Some("2.0.1"))
+            .unwrap_err()
+            .contains("--version"));
+        // 更新命令成功但版本未变
+        assert!(verify_updated_version(Some("2.0.0"), Some("2.0.1"))
+            .unwrap_err()
+            .contains("2.0.1"));
+        assert_eq!(
+            PackageManager::Npm.install_version_command("npm", "@openai/codex", "0.65.0"),
+            "npm install -g @openai/codex@0.65.0"
+        );
+    }
+
+    /// 构造使用假 npm / 假工具脚本的实例，返回实例与 npm 调用记录文件
+    #[cfg(unix)]
+    fn fake_npm_instance(
+        dir: &Path,
+        registry_version: &str,
+        tool_version: &str,
+    ) -> (ToolInstance, PathBuf) {
+        use crate::models::ToolType;
+
+        let log = dir.join("npm.log");
+        let npm = dir.join("npm.sh");
+        fs::write(
+            &npm,
+            format!(
+                "echo \"$@\" >> '{}'\nif [ \"$1\" = view ]; then echo {registry_version}; fi\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        let tool = dir.join("claude.sh");
+        fs::write(&tool, format!("echo '{tool_version} (Claude Code)'\n")).unwrap();
+
+        let instance = ToolInstance {
+            instance_id: "claude-code-local".to_string(),
+            base_id: "claude-code".to_string(),
+            tool_name: "Claude Code".to_string(),
+            tool_type: ToolType::Local,
+            install_method: Some(InstallMethod::Npm),
+            package_manager: None,
+            installed: true,
+            version: Some(tool_version.to_string()),
+            install_path: Some(format!("sh {}", tool.display())),
+            installer_path: Some(format!("sh {}", npm.display())),
+            wsl_distro: None,
+            ssh_config: None,
+            is_builtin: false,
+            custom_env: Default::default(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        (instance, log)
+    }
+
+    /// 更新命令成功但版本未变：与 registry 不符，回滚到旧版本
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_update_instance_rolls_back_when_version_unchanged() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (instance, log) = fake_npm_instance(temp_dir.path(), "2.0.1", "2.0.0");
+
+        let result = InstallerService::new()
+            .update_instance_by_installer(&instance, false)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.has_update);
+        assert!(result.message.contains("2.0.1"));
+        assert!(result.message.contains("已回滚到 2.0.0"));
+        assert_eq!(result.current_version.as_deref(), Some("2.0.0"));
+        assert_eq!(result.latest_version.as_deref(), Some("2.0.1"));
+
+        let calls = fs::read_to_string(&log).unwrap();
+        let calls: Vec<&str> = calls.lines().collect();
+        assert_eq!(
+            calls,
+            vec![
+                "update -g @anthropic-ai/claude-code",
+                "view @anthropic-ai/claude-code version",
+                "install -g @anthropic-ai/claude-code@2.0.0",
+            ]
+        );
+    }
+
+    /// 新版本与 registry 一致：更新成功，不回滚
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_update_instance_succeeds_when_version_matches_registry() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (instance, log) = fake_npm_instance(temp_dir.path(), "2.0.1", "2.0.1");
+
+        let result = InstallerService::new()
+            .update_instance_by_installer(&instance, false)
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.current_version.as_deref(), Some("2.0.1"));
+        let calls = fs::read_to_string(&log).unwrap();
+        assert!(!calls.contains("install -g"));
+    }
+
+    /// pnpm 安装的实例：更新与回滚都使用 pnpm 命令
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_update_instance_uses_recorded_package_manager() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (mut instance, log) = fake_npm_instance(temp_dir.path(), "2.0.1", "2.0.0");
+        instance.package_manager = Some(PackageManager::Pnpm);
+
+        let result = InstallerService::new()
+            .update_instance_by_installer(&instance, false)
+            .await
+            .unwrap();
+        assert!(result.message.contains("已回滚到 2.0.0"));
+
+        let calls = fs::read_to_string(&log).unwrap();
+        let calls: Vec<&str> = calls.lines().collect();
+        assert_eq!(
+            calls,
+            vec![
+                "add -g @anthropic-ai/claude-code@latest",
+                "view @anthropic-ai/claude-code version",
+                "add -g @anthropic-ai/claude-code@2.0.0",
+            ]
+        );
+    }
+
     /// 测试 update_instance_by_installer 方法参数验证
     #[tokio::test]
     async fn test_update_instance_by_installer_validates_installer_path() {
@@ -207,6 +674,7 @@ mod tests {
             tool_name: "Claude Code".to_string(),
             tool_type: ToolType::Local,
             install_method: Some(InstallMethod::Npm),
+            package_manager: None,
             installed: true,
             version: Some("1.0.0".to_string()),
             install_path: Some("/usr/local/bin/claude".to_string()),
@@ -214,6 +682,7 @@ mod tests {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: false,
+            custom_env: Default::default(),
             created_at: 0,
             updated_at: 0,
         };
@@ -241,6 +710,7 @@ mod tests {
             tool_name: "Claude Code".to_string(),
             tool_type: ToolType::Local,
             install_method: Some(InstallMethod::Official),
+            package_manager: None,
             installed: true,
             version: Some("1.0.0".to_string()),
             install_path: Some("/usr/local/bin/claude".to_string()),
@@ -248,6 +718,7 @@ mod tests {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: false,
+            custom_env: Default::default(),
             created_at: 0,
             updated_at: 0,
         };
@@ -268,6 +739,7 @@ mod tests {
             tool_name: "Claude Code".to_string(),
             tool_type: ToolType::Local,
             install_method: Some(InstallMethod::Other),
+            package_manager: None,
             installed: true,
             version: Some("1.0.0".to_string()),
             install_path: Some("/usr/local/bin/claude".to_string()),
@@ -275,6 +747,7 @@ mod tests {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: false,
+            custom_env: Default::default(),
             created_at: 0,
             updated_at: 0,
         };
@@ -5,8 +5,10 @@
 pub mod db;
 pub mod detector_trait;
 pub mod detectors;
+pub mod download_cache;
 pub mod downloader;
 pub mod installer;
+pub mod package_manager;
 pub mod registry;
 pub mod tools_config;
 pub mod version;
@@ -14,8 +16,10 @@ pub mod version;
 pub use db::ToolInstanceDB;
 pub use detector_trait::ToolDetector;
 pub use detectors::{ClaudeCodeDetector, CodeXDetector, DetectorRegistry, GeminiCLIDetector};
+pub use download_cache::{DownloadCache, DownloadCacheUsage};
 pub use downloader::FileDownloader;
 pub use installer::InstallerService;
+pub use package_manager::PackageManager;
 pub use registry::ToolRegistry;
 pub use tools_config::{
     LocalToolInstance, SSHToolInstance, ToolGroup, ToolsConfig, WSLToolInstance,
@@ -0,0 +1,267 @@
+// Node 包管理器
+//
+// npm 之外，越来越多用户使用 pnpm / yarn / bun 管理全局包；用 npm 安装到另一套全局目录后
+// 工具命令可能不在 PATH 中。这里负责检测可用的包管理器并构造对应的全局安装命令
+
+use super::detector_trait::{execute_npm_install, execute_npm_uninstall};
+use crate::utils::CommandExecutor;
+use anyhow::Result;
+
+pub use crate::models::PackageManager;
+
+/// 安装使用的 npm 镜像源
+pub const NPM_MIRROR_REGISTRY: &str = "https://registry.npmmirror.com";
+
+impl PackageManager {
+    /// 全部包管理器（按推荐顺序）
+    pub const ALL: [PackageManager; 4] = [Self::Npm, Self::Pnpm, Self::Yarn, Self::Bun];
+
+    /// 从安装方式字符串解析（npm/pnpm/yarn/bun）
+    pub fn parse(method: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == method)
+    }
+
+    /// 名称，同时也是命令名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Bun => "bun",
+        }
+    }
+
+    /// 构造全局安装命令（`package_spec` 如 `@openai/codex@latest`）
+    pub fn global_install_command(&self, package_spec: &str) -> String {
+        let install = match self {
+            Self::Npm => "npm install -g",
+            Self::Pnpm => "pnpm add -g",
+            Self::Yarn => "yarn global add",
+            Self::Bun => "bun add -g",
+        };
+        format!("{install} {package_spec} --registry {NPM_MIRROR_REGISTRY}")
+    }
+
+    /// 全局安装包（逐行上报输出，npm 的权限错误有专门提示）
+    pub async fn install(&self, executor: &CommandExecutor, package_spec: &str) -> Result<()> {
+        if !executor.command_exists_async(self.as_str()).await {
+            match self {
+                Self::Npm => anyhow::bail!("npm 未安装，请先安装 Node.js"),
+                _ => anyhow::bail!("{} 未安装", self.as_str()),
+            }
+        }
+
+        let command = self.global_install_command(package_spec);
+        if *self == Self::Npm {
+            return execute_npm_install(executor, &command).await;
+        }
+
+        let result = executor.execute_streaming(&command).await;
+        if result.success {
+            Ok(())
+        } else if result.stderr.contains("EACCES") || result.stdout.contains("EACCES") {
+            anyhow::bail!(
+                "❌ {} 安装失败：没有写入全局目录的权限 (EACCES)，请以管理员权限重试\n\n{}",
+                self.as_str(),
+                result.stderr
+            )
+        } else {
+            anyhow::bail!("❌ {} 安装失败\n\n{}", self.as_str(), result.stderr)
+        }
+    }
+
+    /// 从安装器路径推断包管理器（如 `/usr/local/bin/pnpm`、`C:\...\yarn.cmd`），无法识别时返回 None
+    pub fn from_installer_path(path: &str) -> Option<Self> {
+        let file_name = path.rsplit(['/', '\\']).next()?;
+        let stem = file_name.split('.').next()?.to_ascii_lowercase();
+        Self::parse(&stem)
+    }
+
+    /// 从工具可执行文件路径推断安装它的包管理器（按各自的全局 bin 目录布局），
+    /// 位于 npm 全局目录或无法识别时返回 None
+    pub fn from_tool_path(path: &str) -> Option<Self> {
+        let path = path.replace('\\', "/").to_ascii_lowercase();
+        if path.contains("/.bun/") {
+            Some(Self::Bun)
+        } else if path.contains("/pnpm/") {
+            Some(Self::Pnpm)
+        } else if path.contains("/.yarn/") || path.contains("/yarn/") {
+            Some(Self::Yarn)
+        } else {
+            None
+        }
+    }
+
+    /// 构造全局卸载命令
+    pub fn global_uninstall_command(&self, package: &str) -> String {
+        let uninstall = match self {
+            Self::Npm => "npm uninstall -g",
+            Self::Pnpm => "pnpm remove -g",
+            Self::Yarn => "yarn global remove",
+            Self::Bun => "bun remove -g",
+        };
+        format!("{uninstall} {package}")
+    }
+
+    /// 构造更新命令（`installer` 为实例记录的安装器路径）
+    pub fn global_update_command(&self, installer: &str, package: &str, force: bool) -> String {
+        match (self, force) {
+            (Self::Npm, true) => format!("{installer} install -g {package} --force"),
+            (Self::Npm, false) => format!("{installer} update -g {package}"),
+            (Self::Pnpm, true) => format!("{installer} add -g {package}@latest --force"),
+            _ => self.install_version_command(installer, package, "latest"),
+        }
+    }
+
+    /// 构造全局安装指定版本的命令（更新后校验失败时用于回滚）
+    pub fn install_version_command(&self, installer: &str, package: &str, version: &str) -> String {
+        let install = match self {
+            Self::Npm => "install -g",
+            Self::Pnpm | Self::Bun => "add -g",
+            Self::Yarn => "global add",
+        };
+        format!("{installer} {install} {package}@{version}")
+    }
+
+    /// 构造查询 registry 最新版本的命令（bun 没有稳定的查询子命令，返回 None）
+    pub fn registry_version_command(&self, installer: &str, package: &str) -> Option<String> {
+        match self {
+            Self::Npm | Self::Pnpm => Some(format!("{installer} view {package} version")),
+            Self::Yarn => Some(format!("{installer} info {package} version --silent")),
+            Self::Bun => None,
+        }
+    }
+
+    /// 全局卸载包（逐行上报输出，npm 的权限错误有专门提示）
+    pub async fn uninstall(&self, executor: &CommandExecutor, package: &str) -> Result<()> {
+        if !executor.command_exists_async(self.as_str()).await {
+            anyhow::bail!("{} 未安装", self.as_str());
+        }
+        if *self == Self::Npm {
+            return execute_npm_uninstall(executor, package).await;
+        }
+
+        let result = executor
+            .execute_streaming(&self.global_uninstall_command(package))
+            .await;
+        if result.success {
+            Ok(())
+        } else {
+            anyhow::bail!("❌ {} 卸载失败\n\n{}", self.as_str(), result.stderr)
+        }
+    }
+
+    /// 检测系统中可用的包管理器
+    pub async fn detect_available(executor: &CommandExecutor) -> Vec<PackageManager> {
+        let mut available = Vec::new();
+        for manager in Self::ALL {
+            if executor.command_exists_async(manager.as_str()).await {
+                available.push(manager);
+            }
+        }
+        available
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_install_command() {
+        let spec = "@anthropic-ai/claude-code@latest";
+        assert_eq!(
+            PackageManager::Npm.global_install_command(spec),
+            "npm install -g @anthropic-ai/claude-code@latest --registry https://registry.npmmirror.com"
+        );
+        assert_eq!(
+            PackageManager::Pnpm.global_install_command(spec),
+            "pnpm add -g @anthropic-ai/claude-code@latest --registry https://registry.npmmirror.com"
+        );
+        assert_eq!(
+            PackageManager::Yarn.global_install_command(spec),
+            "yarn global add @anthropic-ai/claude-code@latest --registry https://registry.npmmirror.com"
+        );
+        assert_eq!(
+            PackageManager::Bun.global_install_command(spec),
+            "bun add -g @anthropic-ai/claude-code@latest --registry https://registry.npmmirror.com"
+        );
+    }
+
+    #[test]
+    fn test_uninstall_and_update_commands() {
+        let package = "@openai/codex";
+        assert_eq!(
+            PackageManager::Npm.global_uninstall_command(package),
+            "npm uninstall -g @openai/codex"
+        );
+        assert_eq!(
+            PackageManager::Bun.global_uninstall_command(package),
+            "bun remove -g @openai/codex"
+        );
+        assert_eq!(
+            PackageManager::Yarn.global_uninstall_command(package),
+            "yarn global remove @openai/codex"
+        );
+
+        assert_eq!(
+            PackageManager::Npm.global_update_command("/usr/bin/npm", package, false),
+            "/usr/bin/npm update -g @openai/codex"
+        );
+        assert_eq!(
+            PackageManager::Pnpm.global_update_command("/usr/bin/pnpm", package, false),
+            "/usr/bin/pnpm add -g @openai/codex@latest"
+        );
+        assert_eq!(
+            PackageManager::Bun.install_version_command("bun", package, "0.65.0"),
+            "bun add -g @openai/codex@0.65.0"
+        );
+        assert_eq!(
+            PackageManager::Bun.registry_version_command("bun", package),
+            None
+        );
+    }
+
+    #[test]
+    fn test_infer_package_manager_from_paths() {
+        assert_eq!(
+            PackageManager::from_installer_path("/usr/local/bin/pnpm"),
+            Some(PackageManager::Pnpm)
+        );
+        assert_eq!(
+            PackageManager::from_installer_path("C:\\Program Files\\nodejs\\npm.cmd"),
+            Some(PackageManager::Npm)
+        );
+        assert_eq!(
+            PackageManager::from_installer_path("/opt/homebrew/bin/brew"),
+            None
+        );
+
+        assert_eq!(
+            PackageManager::from_tool_path("/home/u/.bun/bin/claude"),
+            Some(PackageManager::Bun)
+        );
+        assert_eq!(
+            PackageManager::from_tool_path("/home/u/.local/share/pnpm/codex"),
+            Some(PackageManager::Pnpm)
+        );
+        assert_eq!(
+            PackageManager::from_tool_path("C:\\Users\\u\\AppData\\Local\\Yarn\\bin\\gemini.cmd"),
+            Some(PackageManager::Yarn)
+        );
+        assert_eq!(
+            PackageManager::from_tool_path("/usr/local/bin/claude"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_package_manager() {
+        assert_eq!(PackageManager::parse("pnpm"), Some(PackageManager::Pnpm));
+        assert_eq!(PackageManager::parse("bun"), Some(PackageManager::Bun));
+        assert_eq!(PackageManager::parse("official"), None);
+        for manager in PackageManager::ALL {
+            assert_eq!(PackageManager::parse(manager.as_str()), Some(manager));
+        }
+    }
+}
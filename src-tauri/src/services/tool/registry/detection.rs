@@ -3,7 +3,7 @@
 //! 负责工具的自动检测、持久化和缓存管理
 
 use super::ToolRegistry;
-use crate::models::{InstallMethod, Tool, ToolInstance, ToolType};
+use crate::models::{InstallMethod, PackageManager, Tool, ToolInstance, ToolType};
 use anyhow::Result;
 
 impl ToolRegistry {
@@ -64,18 +64,30 @@ impl ToolRegistry {
             (None, None, None)
         };
 
+        // 位于 pnpm/yarn/bun 全局目录的工具按对应包管理器管理（`npm list -g` 检测不到这类安装）
+        let package_manager = install_path
+            .as_deref()
+            .and_then(PackageManager::from_tool_path);
+        let install_method = match package_manager {
+            Some(_) => Some(InstallMethod::Npm),
+            None => install_method,
+        };
+        let package_manager = (install_method == Some(InstallMethod::Npm))
+            .then(|| package_manager.unwrap_or(PackageManager::Npm));
+
         // 检测安装器路径（基于安装方法）
         let installer_path = if let (true, Some(method)) = (installed, &install_method) {
             match method {
                 InstallMethod::Npm => {
-                    // 检测 npm 路径：先用 which/where
+                    // 检测包管理器路径：先用 which/where
+                    let manager = package_manager.unwrap_or(PackageManager::Npm).as_str();
                     let npm_detect_cmd = if cfg!(target_os = "windows") {
-                        "where npm"
+                        format!("where {manager}")
                     } else {
-                        "which npm"
+                        format!("which {manager}")
                     };
 
-                    match self.command_executor.execute_async(npm_detect_cmd).await {
+                    match self.command_executor.execute_async(&npm_detect_cmd).await {
                         result if result.success => {
                             let path = result.stdout.lines().next().unwrap_or("").trim();
                             if !path.is_empty() {
@@ -141,6 +153,7 @@ impl ToolRegistry {
             tool_name: tool.name.clone(),
             tool_type: ToolType::Local,
             install_method,
+            package_manager,
             installed,
             version,
             install_path,
@@ -148,6 +161,7 @@ impl ToolRegistry {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: true,
+            custom_env: Default::default(),
             created_at: now,
             updated_at: now,
         }
@@ -1,11 +1,12 @@
 //! 工具实例管理模块
 //!
-//! 负责工具实例的添加、删除操作（Local/WSL/SSH）
+//! 负责工具实例的添加、删除及环境变量配置（Local/WSL/SSH）
 
 use super::ToolRegistry;
-use crate::models::{InstallMethod, SSHConfig, Tool, ToolInstance, ToolType};
-use crate::utils::WSLExecutor;
+use crate::models::{InstallMethod, PackageManager, SSHConfig, Tool, ToolInstance, ToolType};
+use crate::utils::{ToolCandidate, WSLExecutor};
 use anyhow::Result;
+use std::collections::{HashMap, HashSet};
 
 impl ToolRegistry {
     /// 添加WSL工具实例
@@ -81,6 +82,38 @@ impl ToolRegistry {
         Ok(instance)
     }
 
+    /// 扫描系统中已安装的工具并批量导入为本地实例
+    ///
+    /// 通过 PATH 及常见安装目录扫描该工具的所有可执行文件并获取版本，
+    /// 已登记过的路径会跳过，返回本次新建的实例
+    pub async fn scan_and_import_instances(&self, base_id: &str) -> Result<Vec<ToolInstance>> {
+        let tool =
+            Tool::by_id(base_id).ok_or_else(|| anyhow::anyhow!("未知的工具ID: {}", base_id))?;
+
+        let candidates = self.scan_tool_candidates(base_id).await?;
+
+        let db = self.db.write().await;
+        let existing = db.get_all_instances()?;
+        let instances = build_imported_instances(
+            &tool,
+            &candidates,
+            &existing,
+            chrono::Utc::now().timestamp(),
+        );
+        for instance in &instances {
+            db.add_instance(instance)?;
+        }
+        drop(db);
+
+        tracing::info!(
+            tool_id = %base_id,
+            scanned = candidates.len(),
+            imported = instances.len(),
+            "批量导入工具实例完成"
+        );
+        Ok(instances)
+    }
+
     /// 删除工具实例（仅限SSH类型）
     pub async fn delete_instance(&self, instance_id: &str) -> Result<()> {
         let db = self.db.write().await;
@@ -107,6 +140,27 @@ impl ToolRegistry {
         Ok(())
     }
 
+    /// 设置实例的自定义环境变量
+    ///
+    /// 变量名不能为空、不能包含 `=` 或空白字符，且不允许覆盖 PATH
+    pub async fn set_instance_env(
+        &self,
+        instance_id: &str,
+        env: HashMap<String, String>,
+    ) -> Result<ToolInstance> {
+        for key in env.keys() {
+            if key.is_empty() || key.contains('=') || key.chars().any(char::is_whitespace) {
+                return Err(anyhow::anyhow!("无效的环境变量名: {:?}", key));
+            }
+            if key.eq_ignore_ascii_case("PATH") {
+                return Err(anyhow::anyhow!("不允许覆盖 PATH 环境变量"));
+            }
+        }
+
+        let db = self.db.write().await;
+        db.set_instance_env(instance_id, env)
+    }
+
     /// 添加手动配置的工具实例
     ///
     /// # 参数
@@ -184,12 +238,14 @@ impl ToolRegistry {
         // 5. 创建 ToolInstance（使用时间戳确保唯一性）
         let now = chrono::Utc::now().timestamp();
         let instance_id = format!("{}-local-{}", tool_id, now);
+        let package_manager = node_package_manager(&install_method, installer_path.as_deref());
         let instance = ToolInstance {
             instance_id: instance_id.clone(),
             base_id: tool_id.to_string(),
             tool_name: tool_name.to_string(),
             tool_type: ToolType::Local,
             install_method: Some(install_method),
+            package_manager,
             installed: true,
             version: Some(version.clone()),
             install_path: Some(path.to_string()),
@@ -197,6 +253,7 @@ impl ToolRegistry {
             wsl_distro: None,
             ssh_config: None,
             is_builtin: false,
+            custom_env: Default::default(),
             created_at: now,
             updated_at: now,
         };
@@ -214,8 +271,137 @@ impl ToolRegistry {
     }
 }
 
+/// Node 包管理器安装时，按安装器路径确定具体的包管理器（无法识别时视为 npm）
+fn node_package_manager(
+    install_method: &InstallMethod,
+    installer_path: Option<&str>,
+) -> Option<PackageManager> {
+    (*install_method == InstallMethod::Npm).then(|| {
+        installer_path
+            .and_then(PackageManager::from_installer_path)
+            .unwrap_or(PackageManager::Npm)
+    })
+}
+
+/// 由扫描候选构建待导入的本地实例（跳过已登记或重复的路径，实例 ID 不与已有实例冲突）
+fn build_imported_instances(
+    tool: &Tool,
+    candidates: &[ToolCandidate],
+    existing: &[ToolInstance],
+    now: i64,
+) -> Vec<ToolInstance> {
+    let mut known_paths: HashSet<&str> = existing
+        .iter()
+        .filter(|inst| inst.tool_type == ToolType::Local)
+        .filter_map(|inst| inst.install_path.as_deref())
+        .collect();
+    let mut used_ids: HashSet<String> = existing
+        .iter()
+        .map(|inst| inst.instance_id.clone())
+        .collect();
+
+    let mut instances = Vec::new();
+    for candidate in candidates {
+        if !known_paths.insert(candidate.tool_path.as_str()) {
+            continue;
+        }
+
+        let base_instance_id = format!("{}-local-{}", tool.id, now);
+        let mut instance_id = base_instance_id.clone();
+        let mut seq = 2;
+        while used_ids.contains(&instance_id) {
+            instance_id = format!("{}-{}", base_instance_id, seq);
+            seq += 1;
+        }
+        used_ids.insert(instance_id.clone());
+
+        instances.push(ToolInstance {
+            instance_id,
+            base_id: tool.id.clone(),
+            tool_name: tool.name.clone(),
+            tool_type: ToolType::Local,
+            install_method: Some(candidate.install_method.clone()),
+            package_manager: node_package_manager(
+                &candidate.install_method,
+                candidate.installer_path.as_deref(),
+            ),
+            installed: true,
+            version: Some(candidate.version.clone()),
+            install_path: Some(candidate.tool_path.clone()),
+            installer_path: candidate.installer_path.clone(),
+            wsl_distro: None,
+            ssh_config: None,
+            is_builtin: false,
+            custom_env: Default::default(),
+            created_at: now,
+            updated_at: now,
+        });
+    }
+    instances
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn candidate(path: &str, version: &str) -> ToolCandidate {
+        ToolCandidate {
+            tool_path: path.to_string(),
+            installer_path: Some("/usr/local/bin/npm".to_string()),
+            install_method: InstallMethod::Npm,
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_imported_instances() {
+        let tool = Tool::by_id("claude-code").unwrap();
+        let now = 1_700_000_000;
+
+        // 内置实例已登记 /usr/local/bin/claude
+        let builtin = ToolInstance::from_tool_local(
+            &tool,
+            true,
+            Some("2.0.0".to_string()),
+            Some("/usr/local/bin/claude".to_string()),
+        );
+        let candidates = vec![
+            candidate("/usr/local/bin/claude", "2.0.0"),
+            candidate("/opt/homebrew/bin/claude", "2.0.1"),
+            candidate("/home/me/.npm-global/bin/claude", "1.9.0"),
+            candidate("/opt/homebrew/bin/claude", "2.0.1"),
+        ];
+
+        let instances = build_imported_instances(&tool, &candidates, &[builtin], now);
+        assert_eq!(instances.len(), 2);
+
+        assert_eq!(
+            instances[0].install_path.as_deref(),
+            Some("/opt/homebrew/bin/claude")
+        );
+        assert_eq!(instances[0].version.as_deref(), Some("2.0.1"));
+        assert_eq!(
+            instances[0].instance_id,
+            format!("claude-code-local-{}", now)
+        );
+        assert_eq!(instances[0].tool_type, ToolType::Local);
+        assert!(instances[0].installed);
+        assert!(!instances[0].is_builtin);
+        assert_eq!(instances[0].install_method, Some(InstallMethod::Npm));
+        assert_eq!(instances[0].package_manager, Some(PackageManager::Npm));
+
+        // 同一秒内导入的多个实例 ID 递增
+        assert_eq!(
+            instances[1].instance_id,
+            format!("claude-code-local-{}-2", now)
+        );
+        assert_eq!(instances[1].version.as_deref(), Some("1.9.0"));
+
+        // 再次导入时已登记的路径全部跳过
+        let again = build_imported_instances(&tool, &candidates, &instances, now);
+        assert!(again.is_empty());
+    }
+
     #[test]
     fn test_tool_name_mapping() {
         // 这个测试验证 add_tool_instance 中的工具名称映射逻辑
@@ -34,8 +34,9 @@ impl ToolRegistry {
             .find(|inst| inst.instance_id == instance_id && inst.tool_type == ToolType::Local)
             .ok_or_else(|| anyhow::anyhow!("未找到实例: {}", instance_id))?;
 
-        // 2. 根据安装方法选择更新方式
+        // 2. 根据安装方法选择更新方式（注入实例自定义环境变量）
         let install_method = instance.install_method.clone();
+        let executor = self.command_executor.with_envs(&instance.custom_env);
 
         let result = match install_method {
             Some(InstallMethod::Npm) | Some(InstallMethod::Brew) => {
@@ -59,24 +60,31 @@ impl ToolRegistry {
                 );
 
                 // 执行 Detector 的 update 方法
-                detector.update(&self.command_executor, force).await?;
+                detector.update(&executor, force).await?;
 
                 // 更新成功，获取新版本
                 let new_version = if let Some(path) = &instance.install_path {
                     let version_cmd = format!("{} --version", path);
-                    let version_result = self.command_executor.execute_async(&version_cmd).await;
+                    let version_result = executor.execute_async(&version_cmd).await;
                     if version_result.success {
                         Some(parse_version_string(version_result.stdout.trim()))
                     } else {
                         None
                     }
                 } else {
-                    detector.get_version(&self.command_executor).await
+                    detector.get_version(&executor).await
+                };
+
+                // 官方脚本安装不记录可回滚的版本：校验失败时仅提示手动处理
+                let message = if new_version.is_some() {
+                    "✅ 更新成功！".to_string()
+                } else {
+                    "⚠️ 更新完成，但无法获取新版本号（--version 执行失败）。官方脚本等安装方式不支持自动回滚，请检查后手动重新安装".to_string()
                 };
 
                 UpdateResult {
                     success: true,
-                    message: "✅ 更新成功！".to_string(),
+                    message,
                     has_update: false,
                     current_version: new_version.clone(),
                     latest_version: new_version,
@@ -128,7 +136,11 @@ impl ToolRegistry {
             let version_cmd = format!("{} --version", path);
             tracing::info!("实例 {} 版本检查命令: {:?}", instance_id, version_cmd);
 
-            let result = self.command_executor.execute_async(&version_cmd).await;
+            let result = self
+                .command_executor
+                .with_envs(&instance.custom_env)
+                .execute_async(&version_cmd)
+                .await;
 
             if result.success {
                 let raw_version = result.stdout.trim();
@@ -216,7 +228,11 @@ impl ToolRegistry {
                 let version_cmd = format!("{} --version", path);
                 tracing::info!("工具 {} 版本检查: {:?}", instance.tool_name, version_cmd);
 
-                let result = self.command_executor.execute_async(&version_cmd).await;
+                let result = self
+                    .command_executor
+                    .with_envs(&instance.custom_env)
+                    .execute_async(&version_cmd)
+                    .await;
 
                 if result.success {
                     let raw_version = result.stdout.trim();
@@ -2,8 +2,9 @@
 //
 // 用于版本控制和多端同步的工具配置文件
 
-use crate::models::{InstallMethod, SSHConfig, ToolInstance, ToolType};
+use crate::models::{InstallMethod, PackageManager, SSHConfig, ToolInstance, ToolType};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// tools.json 根配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,7 +45,11 @@ pub struct LocalToolInstance {
     #[serde(default)]
     pub installer_path: Option<String>, // 安装器路径（如 npm/brew 路径）
     pub install_method: Option<InstallMethod>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<PackageManager>, // Node 包管理器（npm/pnpm/yarn/bun）
     pub is_builtin: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_env: HashMap<String, String>, // 自定义环境变量
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -59,6 +64,8 @@ pub struct WSLToolInstance {
     pub install_path: Option<String>,
     pub install_method: Option<InstallMethod>,
     pub is_builtin: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_env: HashMap<String, String>, // 自定义环境变量
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -73,6 +80,8 @@ pub struct SSHToolInstance {
     pub install_path: Option<String>,
     pub install_method: Option<InstallMethod>,
     pub is_builtin: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_env: HashMap<String, String>, // 自定义环境变量
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -123,6 +132,7 @@ impl ToolsConfig {
                     tool_name: tool_group.name.clone(),
                     tool_type: ToolType::Local,
                     install_method: local.install_method.clone(),
+                    package_manager: local.package_manager,
                     installed: local.installed,
                     version: local.version.clone(),
                     install_path: local.install_path.clone(),
@@ -130,6 +140,7 @@ impl ToolsConfig {
                     wsl_distro: None,
                     ssh_config: None,
                     is_builtin: local.is_builtin,
+                    custom_env: local.custom_env.clone(),
                     created_at: local.created_at,
                     updated_at: local.updated_at,
                 });
@@ -143,6 +154,7 @@ impl ToolsConfig {
                     tool_name: tool_group.name.clone(),
                     tool_type: ToolType::WSL,
                     install_method: wsl.install_method.clone(),
+                    package_manager: None,
                     installed: wsl.installed,
                     version: wsl.version.clone(),
                     install_path: wsl.install_path.clone(),
@@ -150,6 +162,7 @@ impl ToolsConfig {
                     wsl_distro: Some(wsl.distro_name.clone()),
                     ssh_config: None,
                     is_builtin: wsl.is_builtin,
+                    custom_env: wsl.custom_env.clone(),
                     created_at: wsl.created_at,
                     updated_at: wsl.updated_at,
                 });
@@ -163,6 +176,7 @@ impl ToolsConfig {
                     tool_name: tool_group.name.clone(),
                     tool_type: ToolType::SSH,
                     install_method: ssh.install_method.clone(),
+                    package_manager: None,
                     installed: ssh.installed,
                     version: ssh.version.clone(),
                     install_path: ssh.install_path.clone(),
@@ -170,6 +184,7 @@ impl ToolsConfig {
                     wsl_distro: None,
                     ssh_config: Some(ssh.ssh_config.clone()),
                     is_builtin: ssh.is_builtin,
+                    custom_env: ssh.custom_env.clone(),
                     created_at: ssh.created_at,
                     updated_at: ssh.updated_at,
                 });
@@ -220,7 +235,9 @@ impl ToolsConfig {
                             install_path: instance.install_path,
                             installer_path: instance.installer_path, // 新增
                             install_method: instance.install_method,
+                            package_manager: instance.package_manager,
                             is_builtin: instance.is_builtin,
+                            custom_env: instance.custom_env,
                             created_at: instance.created_at,
                             updated_at: instance.updated_at,
                         });
@@ -235,6 +252,7 @@ impl ToolsConfig {
                                 install_path: instance.install_path,
                                 install_method: instance.install_method,
                                 is_builtin: instance.is_builtin,
+                                custom_env: instance.custom_env,
                                 created_at: instance.created_at,
                                 updated_at: instance.updated_at,
                             });
@@ -250,6 +268,7 @@ impl ToolsConfig {
                                 install_path: instance.install_path,
                                 install_method: instance.install_method,
                                 is_builtin: instance.is_builtin,
+                                custom_env: instance.custom_env,
                                 created_at: instance.created_at,
                                 updated_at: instance.updated_at,
                             });
@@ -290,9 +309,14 @@ mod tests {
             installed: true,
             version: Some("2.0.5".to_string()),
             install_path: Some("/usr/local/bin/claude".to_string()),
-            installer_path: Some("/usr/local/bin/npm".to_string()),
+            installer_path: Some("/home/u/.local/share/pnpm/pnpm".to_string()),
             install_method: Some(InstallMethod::Npm),
+            package_manager: Some(PackageManager::Pnpm),
             is_builtin: true,
+            custom_env: HashMap::from([(
+                "HTTPS_PROXY".to_string(),
+                "http://127.0.0.1:7890".to_string(),
+            )]),
             created_at: 1733299200,
             updated_at: 1733299200,
         });
@@ -302,6 +326,7 @@ mod tests {
         assert_eq!(instances.len(), 1);
         assert_eq!(instances[0].instance_id, "claude-code-local");
         assert_eq!(instances[0].install_method, Some(InstallMethod::Npm));
+        assert_eq!(instances[0].package_manager, Some(PackageManager::Pnpm));
 
         // 转换回 ToolsConfig
         let config2 = ToolsConfig::from_instances(instances);
@@ -311,5 +336,36 @@ mod tests {
             config2.tools[0].local_tools[0].install_method,
             Some(InstallMethod::Npm)
         );
+        assert_eq!(
+            config2.tools[0].local_tools[0].package_manager,
+            Some(PackageManager::Pnpm)
+        );
+        assert_eq!(
+            config2.tools[0].local_tools[0]
+                .custom_env
+                .get("HTTPS_PROXY")
+                .map(String::as_str),
+            Some("http://127.0.0.1:7890")
+        );
+    }
+
+    #[test]
+    fn test_custom_env_defaults_to_empty() {
+        let json = r#"{
+            "instance_id": "claude-code-local",
+            "installed": true,
+            "version": null,
+            "install_path": null,
+            "install_method": null,
+            "is_builtin": true,
+            "created_at": 0,
+            "updated_at": 0
+        }"#;
+        let local: LocalToolInstance = serde_json::from_str(json).unwrap();
+        assert!(local.custom_env.is_empty());
+
+        // 空环境变量不写入 tools.json
+        let value = serde_json::to_value(&local).unwrap();
+        assert!(value.get("custom_env").is_none());
     }
 }
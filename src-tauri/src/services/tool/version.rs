@@ -2,6 +2,7 @@ use crate::models::Tool;
 use crate::services::tool::DetectorRegistry;
 use crate::utils::CommandExecutor;
 use anyhow::Result;
+#[cfg(test)]
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
@@ -180,24 +181,19 @@ impl VersionService {
             .ok_or_else(|| anyhow::anyhow!("工具 {tool_id} 不在镜像站 API 中"))
     }
 
-    /// 比较版本号
+    /// 比较版本号（semver 优先，无法解析时逐段比较数字）
     fn compare_versions(installed: Option<&str>, latest: &str) -> bool {
-        let latest_semver = Self::parse_version(latest);
-
-        match (installed, latest_semver) {
-            (None, _) => false, // 未安装不算"有更新"
-            (Some(installed_str), Some(latest_version)) => {
-                if let Some(installed_version) = Self::parse_version(installed_str) {
-                    installed_version < latest_version
-                } else {
-                    installed_str.trim() != latest.trim()
-                }
+        match installed {
+            None => false, // 未安装不算"有更新"
+            Some(installed) => {
+                crate::utils::version::compare_versions(installed, latest)
+                    == std::cmp::Ordering::Less
             }
-            (Some(installed_str), None) => installed_str.trim() != latest.trim(),
         }
     }
 
     /// 解析版本号为可比较的 semver::Version 对象
+    #[cfg(test)]
     fn parse_version(version: &str) -> Option<Version> {
         crate::utils::version::parse_version(version)
     }
@@ -366,5 +362,11 @@ mod tests {
             "rust-v0.55.0"
         ));
         assert!(!VersionService::compare_versions(None, "1.0.0"));
+        assert!(VersionService::compare_versions(
+            Some("1.2.3-rc.1"),
+            "1.2.3"
+        ));
+        assert!(VersionService::compare_versions(Some("1.9.0"), "1.10.0"));
+        assert!(VersionService::compare_versions(Some("1.2.3.4"), "1.2.3.5"));
     }
 }
@@ -3,8 +3,10 @@ use crate::models::update::{
     UpdateApiResponse, UpdateInfo, UpdateStatus, UpdateUrls,
 };
 use crate::services::downloader::{DownloadEvent, FileDownloader};
+use crate::services::tool::{DownloadCache, DownloadCacheUsage};
 use anyhow::{anyhow, Context, Result};
 #[cfg(target_os = "macos")]
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -23,6 +25,8 @@ pub struct UpdateService {
     download_task: Arc<Mutex<Option<DownloadTask>>>,
     downloader: FileDownloader,
     update_dir: PathBuf,
+    /// 最近一次检查更新得到的安装包校验和（URL -> SHA256）
+    checksums: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl UpdateService {
@@ -33,11 +37,22 @@ impl UpdateService {
             current_version,
             status: Arc::new(RwLock::new(UpdateStatus::Idle)),
             download_task: Arc::new(Mutex::new(None)),
-            downloader: FileDownloader::new(),
+            // 缓存配置在服务创建时读取，修改后重启生效
+            downloader: match DownloadCache::from_global_config() {
+                Ok(Some(cache)) => FileDownloader::with_cache(cache),
+                Ok(None) => FileDownloader::new(),
+                Err(e) => {
+                    tracing::warn!(error = %e, "下载缓存不可用，更新包将直接下载");
+                    FileDownloader::new()
+                }
+            },
+            // 无缓存目录与主目录时退回系统临时目录
             update_dir: dirs::cache_dir()
-                .unwrap_or_else(|| dirs::home_dir().expect("No home directory"))
+                .or_else(dirs::home_dir)
+                .unwrap_or_else(std::env::temp_dir)
                 .join("duckcoding")
                 .join("updates"),
+            checksums: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -92,6 +107,7 @@ impl UpdateService {
 
         // 获取对应平台的更新URL
         let update_url = self.get_platform_update_url(&api_response.update);
+        *self.checksums.write().await = api_response.update.sha256.clone();
 
         // 获取文件大小
         let file_size = if let Some(url) = &update_url {
@@ -209,11 +225,15 @@ impl UpdateService {
         *self.download_task.lock().await = Some(task);
         *self.status.write().await = UpdateStatus::Downloading;
 
-        // 开始下载
+        // 开始下载（有校验和时校验下载结果，并仅在校验和匹配时复用本地缓存）
         let downloader = self.downloader.clone();
+        let expected_sha256 = self.checksums.read().await.get(url).cloned();
+        if expected_sha256.is_none() {
+            tracing::warn!(url = %url, "更新信息未提供安装包校验和，跳过校验与缓存复用");
+        }
 
         let result = downloader
-            .download_with_progress(url, &file_path, move |event| {
+            .download_with_checksum(url, &file_path, expected_sha256.as_deref(), move |event| {
                 match event {
                     DownloadEvent::Started => {
                         // 可以发送初始进度
@@ -323,6 +343,22 @@ impl UpdateService {
         Ok(())
     }
 
+    /// 获取安装包缓存占用（未启用缓存时返回 None）
+    pub async fn download_cache_usage(&self) -> Option<DownloadCacheUsage> {
+        match self.downloader.cache() {
+            Some(cache) => Some(cache.usage().await),
+            None => None,
+        }
+    }
+
+    /// 清空安装包缓存，返回释放的字节数
+    pub async fn clear_download_cache(&self) -> Result<u64> {
+        match self.downloader.cache() {
+            Some(cache) => cache.clear().await,
+            None => Ok(0),
+        }
+    }
+
     /// 获取当前更新状态
     pub async fn get_status(&self) -> UpdateStatus {
         self.status.read().await.clone()
@@ -400,8 +436,7 @@ impl UpdateService {
     // 私有辅助方法
 
     fn compare_versions(&self, current: &str, latest: &str) -> bool {
-        // 简单的版本比较，实际项目中应该使用semver库
-        current != latest
+        crate::utils::version::compare_versions(current, latest) == std::cmp::Ordering::Less
     }
 
     fn extract_filename_from_url(&self, url: &str) -> Result<String> {
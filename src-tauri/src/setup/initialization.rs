@@ -1,4 +1,5 @@
 use duckcoding::core::init_logger;
+use duckcoding::core::startup_timing::StartupTimer;
 use duckcoding::services::profile_manager::ProfileManager;
 use duckcoding::services::proxy_config_manager::ProxyConfigManager;
 use duckcoding::utils::config::read_global_config;
@@ -151,34 +152,42 @@ async fn auto_start_proxies(
 
 /// 执行所有启动初始化任务
 ///
-/// 按顺序执行：日志 → Profile → 迁移 → 标记过期日志 → 工具注册表 → 代理管理器
+/// 按顺序执行：日志 → Profile → 迁移 → 标记过期日志 → 工具注册表 → 代理管理器，
+/// 各阶段耗时汇总为启动性能报告（见 `get_startup_timings`）
 pub async fn initialize_app() -> Result<InitializationContext, Box<dyn std::error::Error>> {
+    let mut timer = StartupTimer::new();
+
     // 1. 初始化日志
-    init_logging()?;
+    timer.measure("init_logging", init_logging)?;
 
     // 2. 初始化内置 Profile
-    if let Err(e) = initialize_proxy_profiles() {
+    if let Err(e) = timer.measure("proxy_profiles", initialize_proxy_profiles) {
         tracing::warn!(error = ?e, "初始化内置 Profile 失败");
     }
 
     // 3. 执行数据迁移
-    run_migrations().await?;
+    timer.measure_async("migrations", run_migrations()).await?;
 
     // 4. 标记未处理的配置变更日志为已过期
-    if let Err(e) = mark_expired_change_logs() {
+    if let Err(e) = timer.measure("expire_change_logs", mark_expired_change_logs) {
         tracing::warn!(error = ?e, "标记过期日志失败");
     }
 
     // 5. 创建工具注册表
-    let tool_registry = ToolRegistry::new().await.expect("无法创建工具注册表");
+    let tool_registry = timer
+        .measure_async("tool_registry", ToolRegistry::new())
+        .await
+        .expect("无法创建工具注册表");
 
     // 6. 创建 ProfileManager 单例
-    let profile_manager = Arc::new(tokio::sync::RwLock::new(
-        ProfileManager::new().expect("初始化 ProfileManager 失败"),
-    ));
+    let profile_manager = timer.measure("profile_manager", || {
+        Arc::new(tokio::sync::RwLock::new(
+            ProfileManager::new().expect("初始化 ProfileManager 失败"),
+        ))
+    });
 
     // 7. 创建代理管理器并异步启动自启动代理
-    let proxy_manager = Arc::new(ProxyManager::new());
+    let proxy_manager = timer.measure("proxy_manager", || Arc::new(ProxyManager::new()));
     let proxy_manager_for_auto_start = proxy_manager.clone();
     let profile_manager_for_auto_start = profile_manager.clone();
     tauri::async_runtime::spawn(async move {
@@ -194,6 +203,8 @@ pub async fn initialize_app() -> Result<InitializationContext, Box<dyn std::erro
         duckcoding::services::pricing::remote_sync::start_sync_scheduler().await;
     });
 
+    timer.finish();
+
     Ok(InitializationContext {
         proxy_manager,
         tool_registry: Arc::new(TokioMutex::new(tool_registry)),
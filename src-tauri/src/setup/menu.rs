@@ -11,6 +11,11 @@ use tauri::{
     AppHandle, Emitter, Manager, Runtime,
 };
 
+use super::proxy_menu::{
+    focus_and_navigate, is_supported_proxy_tool, load_proxy_running_states, proxy_page_path,
+    proxy_tool_menu_label, should_navigate_to_proxy_page_for_start_error, PROXY_MENU_PREFIX,
+    SUPPORTED_MENU_TOOLS,
+};
 use crate::commands::profile_commands::ProfileManagerState;
 use crate::commands::proxy_commands::{
     start_tool_proxy_internal, stop_tool_proxy_internal, update_proxy_from_profile_internal,
@@ -24,12 +29,8 @@ use duckcoding::services::proxy_config_manager::ProxyConfigManager;
 
 /// Profile 菜单项 ID 前缀
 const PROFILE_MENU_PREFIX: &str = "profile:";
-/// 透明代理菜单项 ID 前缀
-const PROXY_MENU_PREFIX: &str = "proxy:";
 /// 菜单中最多展示的 Profile 数
 const MAX_MENU_PROFILE_COUNT: usize = 10;
-/// 支持在菜单栏展示的工具
-const SUPPORTED_MENU_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ProxyMenuAction<'a> {
@@ -39,32 +40,6 @@ enum ProxyMenuAction<'a> {
     Config(&'a str, &'a str),
 }
 
-/// 工具显示名称
-fn tool_display_name(tool_id: &str) -> &'static str {
-    match tool_id {
-        "claude-code" => "Claude Code",
-        "codex" => "Codex",
-        "gemini-cli" => "Gemini CLI",
-        _ => "Unknown",
-    }
-}
-
-fn is_supported_proxy_tool(tool_id: &str) -> bool {
-    SUPPORTED_MENU_TOOLS.contains(&tool_id)
-}
-
-fn proxy_page_path(tool_id: &str) -> String {
-    format!("/transparent-proxy/{tool_id}")
-}
-
-fn proxy_tool_menu_label(tool_id: &str, is_running: bool) -> String {
-    if is_running {
-        format!("{} · 运行中", tool_display_name(tool_id))
-    } else {
-        format!("{} · 已停止", tool_display_name(tool_id))
-    }
-}
-
 /// 解析菜单项 ID，提取工具 ID 和 Profile 名称
 ///
 /// 格式: `profile:{tool_id}:{profile_name}`
@@ -346,28 +321,6 @@ fn create_tray_menu<R: Runtime>(
     builder.build()
 }
 
-fn focus_and_navigate<R: Runtime>(app: &AppHandle<R>, path: &str) {
-    super::focus_main_window(app);
-    if let Err(err) = app.emit("navigate-to", path) {
-        tracing::error!(error = ?err, path = %path, "菜单导航事件发送失败");
-    }
-}
-
-async fn load_proxy_running_states<R: Runtime>(app: &AppHandle<R>) -> HashMap<String, bool> {
-    let proxy_state = app.state::<ProxyManagerState>();
-    let current_statuses = proxy_state.manager.get_all_status().await;
-
-    SUPPORTED_MENU_TOOLS
-        .iter()
-        .map(|tool_id| {
-            (
-                (*tool_id).to_string(),
-                current_statuses.get(*tool_id).copied().unwrap_or(false),
-            )
-        })
-        .collect()
-}
-
 async fn build_tray_menu_for_app<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
     let profile_state = app.state::<ProfileManagerState>();
     let profile_manager = profile_state.manager.read().await;
@@ -408,14 +361,6 @@ fn has_required_proxy_fields(config: &ToolProxyConfig) -> bool {
         && config.real_profile_name.is_some()
 }
 
-fn should_navigate_to_proxy_page_for_start_error(error: &str) -> bool {
-    error.contains("代理配置不存在")
-        || error.contains("透明代理未启用")
-        || error.contains("保护密钥未设置")
-        || error.contains("真实 API Key 或 Base URL 未设置")
-        || error.contains("内置 Profile 不存在")
-}
-
 fn ensure_proxy_enabled_for_auto_start(tool_id: &str) -> Result<(), String> {
     let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
     let mut config = proxy_config_mgr
@@ -647,8 +592,13 @@ fn handle_profile_activation<R: Runtime>(app: &AppHandle<R>, tool_id: &str, prof
     let state = app.state::<ProfileManagerState>();
     let manager = state.manager.blocking_read();
     match manager.activate_profile(tool_id, profile_name) {
-        Ok(()) => {
-            tracing::info!(tool_id = %tool_id, profile = %profile_name, "从菜单激活 Profile");
+        Ok(switch) => {
+            tracing::info!(
+                tool_id = %tool_id,
+                previous = ?switch.previous,
+                profile = %profile_name,
+                "从菜单激活 Profile"
+            );
             if let Err(e) = refresh_app_menu_internal(app) {
                 tracing::error!(error = ?e, "刷新菜单失败");
             }
@@ -657,6 +607,7 @@ fn handle_profile_activation<R: Runtime>(app: &AppHandle<R>, tool_id: &str, prof
                 serde_json::json!({
                     "tool_id": tool_id,
                     "profile_name": profile_name,
+                    "previous_profile": switch.previous,
                 }),
             );
         }
@@ -671,7 +622,9 @@ fn refresh_app_menu_internal<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()
     tauri::async_runtime::block_on(refresh_app_menu_internal_async(app))
 }
 
-async fn refresh_app_menu_internal_async<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+pub(crate) async fn refresh_app_menu_internal_async<R: Runtime>(
+    app: &AppHandle<R>,
+) -> tauri::Result<()> {
     let menu = build_tray_menu_for_app(app).await?;
 
     // 获取托盘图标并更新菜单
@@ -727,19 +680,6 @@ mod tests {
         assert_eq!(parse_proxy_menu_id("other:config:codex:test"), None);
     }
 
-    #[test]
-    fn test_should_navigate_to_proxy_page_for_start_error() {
-        assert!(should_navigate_to_proxy_page_for_start_error(
-            "内置 Profile 不存在，请先保存代理配置: dc_proxy_codex"
-        ));
-        assert!(should_navigate_to_proxy_page_for_start_error(
-            "真实 API Key 或 Base URL 未设置"
-        ));
-        assert!(!should_navigate_to_proxy_page_for_start_error(
-            "codex 代理已在运行"
-        ));
-    }
-
     #[test]
     fn test_has_required_proxy_fields() {
         let mut config = ToolProxyConfig::new(8788);
@@ -753,12 +693,4 @@ mod tests {
 
         assert!(has_required_proxy_fields(&config));
     }
-
-    #[test]
-    fn test_tool_display_name() {
-        assert_eq!(tool_display_name("claude-code"), "Claude Code");
-        assert_eq!(tool_display_name("codex"), "Codex");
-        assert_eq!(tool_display_name("gemini-cli"), "Gemini CLI");
-        assert_eq!(tool_display_name("unknown"), "Unknown");
-    }
 }
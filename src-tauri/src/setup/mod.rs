@@ -1,6 +1,9 @@
 // 托盘菜单和窗口管理
 pub mod tray;
 
+// 托盘与菜单栏共用的透明代理菜单辅助
+pub mod proxy_menu;
+
 // 启动初始化逻辑
 pub mod initialization;
 
@@ -0,0 +1,105 @@
+//! 托盘菜单与 macOS 菜单栏共用的透明代理菜单辅助函数
+
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::commands::proxy_commands::ProxyManagerState;
+
+/// 透明代理菜单项 ID 前缀
+pub(crate) const PROXY_MENU_PREFIX: &str = "proxy:";
+/// 支持在菜单中展示的工具
+pub(crate) const SUPPORTED_MENU_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
+
+/// 工具显示名称
+pub(crate) fn tool_display_name(tool_id: &str) -> &'static str {
+    match tool_id {
+        "claude-code" => "Claude Code",
+        "codex" => "Codex",
+        "gemini-cli" => "Gemini CLI",
+        _ => "Unknown",
+    }
+}
+
+pub(crate) fn is_supported_proxy_tool(tool_id: &str) -> bool {
+    SUPPORTED_MENU_TOOLS.contains(&tool_id)
+}
+
+pub(crate) fn proxy_page_path(tool_id: &str) -> String {
+    format!("/transparent-proxy/{tool_id}")
+}
+
+pub(crate) fn proxy_tool_menu_label(tool_id: &str, is_running: bool) -> String {
+    if is_running {
+        format!("{} · 运行中", tool_display_name(tool_id))
+    } else {
+        format!("{} · 已停止", tool_display_name(tool_id))
+    }
+}
+
+pub(crate) fn should_navigate_to_proxy_page_for_start_error(error: &str) -> bool {
+    error.contains("代理配置不存在")
+        || error.contains("透明代理未启用")
+        || error.contains("保护密钥未设置")
+        || error.contains("真实 API Key 或 Base URL 未设置")
+        || error.contains("内置 Profile 不存在")
+}
+
+pub(crate) fn focus_and_navigate<R: Runtime>(app: &AppHandle<R>, path: &str) {
+    super::focus_main_window(app);
+    if let Err(err) = app.emit("navigate-to", path) {
+        tracing::error!(error = ?err, path = %path, "菜单导航事件发送失败");
+    }
+}
+
+/// 读取菜单中各工具代理的运行状态
+pub(crate) async fn load_proxy_running_states<R: Runtime>(
+    app: &AppHandle<R>,
+) -> HashMap<String, bool> {
+    let proxy_state = app.state::<ProxyManagerState>();
+    let current_statuses = proxy_state.manager.get_all_status().await;
+
+    SUPPORTED_MENU_TOOLS
+        .iter()
+        .map(|tool_id| {
+            (
+                (*tool_id).to_string(),
+                current_statuses.get(*tool_id).copied().unwrap_or(false),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_navigate_to_proxy_page_for_start_error() {
+        assert!(should_navigate_to_proxy_page_for_start_error(
+            "内置 Profile 不存在，请先保存代理配置: dc_proxy_codex"
+        ));
+        assert!(should_navigate_to_proxy_page_for_start_error(
+            "真实 API Key 或 Base URL 未设置"
+        ));
+        assert!(!should_navigate_to_proxy_page_for_start_error(
+            "codex 代理已在运行"
+        ));
+    }
+
+    #[test]
+    fn test_tool_display_name() {
+        assert_eq!(tool_display_name("claude-code"), "Claude Code");
+        assert_eq!(tool_display_name("codex"), "Codex");
+        assert_eq!(tool_display_name("gemini-cli"), "Gemini CLI");
+        assert_eq!(tool_display_name("unknown"), "Unknown");
+    }
+
+    #[test]
+    fn test_proxy_tool_menu_label() {
+        assert_eq!(proxy_tool_menu_label("codex", true), "Codex · 运行中");
+        assert_eq!(
+            proxy_tool_menu_label("gemini-cli", false),
+            "Gemini CLI · 已停止"
+        );
+    }
+}
@@ -1,30 +1,150 @@
-use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewWindow};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
 
+#[cfg(not(target_os = "macos"))]
+use std::collections::HashMap;
 #[cfg(not(target_os = "macos"))]
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    Emitter,
+};
+
+#[cfg(not(target_os = "macos"))]
+use super::proxy_menu::{
+    focus_and_navigate, is_supported_proxy_tool, load_proxy_running_states, proxy_page_path,
+    proxy_tool_menu_label, should_navigate_to_proxy_page_for_start_error, PROXY_MENU_PREFIX,
+    SUPPORTED_MENU_TOOLS,
+};
+#[cfg(not(target_os = "macos"))]
+use crate::commands::profile_commands::ProfileManagerState;
+#[cfg(not(target_os = "macos"))]
+use crate::commands::proxy_commands::{
+    start_tool_proxy_internal, stop_tool_proxy_internal, ProxyManagerState,
 };
 
+/// 系统托盘 ID（刷新菜单时按 ID 查找）
+#[cfg(not(target_os = "macos"))]
+const TRAY_ID: &str = "main";
+
+/// 解析托盘代理开关菜单项 ID
+///
+/// 格式: `proxy:{tool_id}`
+#[cfg(not(target_os = "macos"))]
+fn parse_tray_proxy_menu_id(id: &str) -> Option<&str> {
+    let tool_id = id.strip_prefix(PROXY_MENU_PREFIX)?;
+    is_supported_proxy_tool(tool_id).then_some(tool_id)
+}
+
 /// 创建系统托盘菜单
+///
+/// 每个工具一个代理开关项，勾选表示代理运行中，点击切换启动/停止
 #[cfg(not(target_os = "macos"))]
-pub fn create_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
-    let show_item = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?;
-    let check_update_item = MenuItem::with_id(app, "check_update", "检查更新", true, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-
-    let menu = Menu::with_items(
-        app,
-        &[
-            &show_item,
-            &PredefinedMenuItem::separator(app)?,
-            &check_update_item,
-            &PredefinedMenuItem::separator(app)?,
-            &quit_item,
-        ],
-    )?;
-
-    Ok(menu)
+pub fn create_tray_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    running_states: &HashMap<String, bool>,
+) -> tauri::Result<Menu<R>> {
+    let mut builder = MenuBuilder::new(app)
+        .item(&MenuItem::with_id(
+            app,
+            "show",
+            "显示窗口",
+            true,
+            None::<&str>,
+        )?)
+        .separator()
+        .item(&MenuItem::with_id(
+            app,
+            "proxy_title",
+            "透明代理（点击启动/停止）",
+            false,
+            None::<&str>,
+        )?);
+
+    for tool_id in SUPPORTED_MENU_TOOLS {
+        let is_running = running_states.get(tool_id).copied().unwrap_or(false);
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{}{}", PROXY_MENU_PREFIX, tool_id),
+            proxy_tool_menu_label(tool_id, is_running),
+            true,
+            is_running,
+            None::<&str>,
+        )?;
+        builder = builder.item(&item);
+    }
+
+    builder
+        .separator()
+        .item(&MenuItem::with_id(
+            app,
+            "check_update",
+            "检查更新",
+            true,
+            None::<&str>,
+        )?)
+        .separator()
+        .item(&MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?)
+        .build()
+}
+
+/// 按当前代理运行状态重建托盘菜单
+#[cfg(not(target_os = "macos"))]
+async fn refresh_system_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let running_states = load_proxy_running_states(app).await;
+    let menu = create_tray_menu(app, &running_states)?;
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(menu))?;
+    }
+    Ok(())
+}
+
+/// 刷新托盘菜单（macOS 下为菜单栏图标菜单），代理状态变化后调用
+pub async fn refresh_tray_menu<R: Runtime>(app: &AppHandle<R>) {
+    #[cfg(target_os = "macos")]
+    let result = super::menu::refresh_app_menu_internal_async(app).await;
+    #[cfg(not(target_os = "macos"))]
+    let result = refresh_system_tray_menu(app).await;
+
+    if let Err(error) = result {
+        tracing::error!(error = ?error, "刷新托盘菜单失败");
+    }
+}
+
+/// 切换工具代理（运行中则停止，否则启动），完成后刷新托盘菜单
+#[cfg(not(target_os = "macos"))]
+fn toggle_proxy_from_tray<R: Runtime>(app: &AppHandle<R>, tool_id: &str) {
+    let app_handle = app.clone();
+    let tool_id = tool_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let proxy_state = app_handle.state::<ProxyManagerState>();
+        let profile_state = app_handle.state::<ProfileManagerState>();
+
+        if proxy_state.manager.is_running(&tool_id).await {
+            match stop_tool_proxy_internal(&tool_id, &proxy_state, &profile_state).await {
+                Ok(message) => {
+                    tracing::info!(tool_id = %tool_id, message = %message, "从托盘停止透明代理成功");
+                }
+                Err(error) => {
+                    tracing::error!(error = %error, tool_id = %tool_id, "从托盘停止透明代理失败");
+                }
+            }
+        } else {
+            match start_tool_proxy_internal(&tool_id, &proxy_state, &profile_state).await {
+                Ok(message) => {
+                    tracing::info!(tool_id = %tool_id, message = %message, "从托盘启动透明代理成功");
+                }
+                Err(error) => {
+                    tracing::error!(error = %error, tool_id = %tool_id, "从托盘启动透明代理失败");
+                    if should_navigate_to_proxy_page_for_start_error(&error) {
+                        focus_and_navigate(&app_handle, &proxy_page_path(&tool_id));
+                    }
+                }
+            }
+        }
+
+        // 失败时也刷新，恢复被点击切换的勾选状态
+        refresh_tray_menu(&app_handle).await;
+    });
 }
 
 /// 聚焦主窗口
@@ -90,15 +210,20 @@ pub fn restore_window_state<R: Runtime>(window: &WebviewWindow<R>) {
 /// 设置系统托盘（包含事件处理）
 #[cfg(not(target_os = "macos"))]
 pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
-    let tray_menu = create_tray_menu(app.handle())?;
+    let running_states = tauri::async_runtime::block_on(load_proxy_running_states(app.handle()));
+    let tray_menu = create_tray_menu(app.handle(), &running_states)?;
     let app_handle2 = app.handle().clone();
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&tray_menu)
         .show_menu_on_left_click(false)
         .on_menu_event(move |app, event| {
             tracing::debug!(event_id = ?event.id, "托盘菜单事件");
+            if let Some(tool_id) = parse_tray_proxy_menu_id(event.id.as_ref()) {
+                toggle_proxy_from_tray(app, tool_id);
+                return;
+            }
             match event.id.as_ref() {
                 "show" => {
                     tracing::info!("从托盘显示窗口");
@@ -139,28 +264,47 @@ pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     Ok(())
 }
 
-const CLOSE_CONFIRM_EVENT: &str = "duckcoding://request-close-action";
-
-/// 设置窗口关闭处理（最小化到托盘而不是退出，跨平台）
+/// 设置窗口关闭处理（跨平台，按全局配置的 `close_behavior` 最小化、退出或询问）
 pub fn setup_window_close_handler<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         let window_clone = window.clone();
 
         window.on_window_event(move |event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                tracing::info!("窗口关闭请求 - 提示用户选择操作");
-                // 阻止默认关闭行为
+                // 阻止默认关闭行为，每次关闭时读取配置以便设置即时生效
                 api.prevent_close();
-                if let Err(err) = window_clone.emit(CLOSE_CONFIRM_EVENT, ()) {
-                    tracing::error!(
-                        error = ?err,
-                        "发送关闭确认事件失败，降级为隐藏窗口"
-                    );
-                    ::duckcoding::ui::hide_window_to_tray(&window_clone);
-                }
+                let behavior = ::duckcoding::utils::config::read_global_config()
+                    .ok()
+                    .flatten()
+                    .map(|cfg| cfg.close_behavior)
+                    .unwrap_or_default();
+                tracing::info!(behavior = ?behavior, "窗口关闭请求");
+                ::duckcoding::ui::handle_close_request(&window_clone, behavior);
             }
         });
     }
 
     Ok(())
 }
+
+#[cfg(all(test, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tray_proxy_menu_id() {
+        assert_eq!(
+            parse_tray_proxy_menu_id("proxy:claude-code"),
+            Some("claude-code")
+        );
+        assert_eq!(parse_tray_proxy_menu_id("proxy:codex"), Some("codex"));
+        assert_eq!(
+            parse_tray_proxy_menu_id("proxy:gemini-cli"),
+            Some("gemini-cli")
+        );
+        assert_eq!(parse_tray_proxy_menu_id("proxy:amp-code"), None);
+        assert_eq!(parse_tray_proxy_menu_id("proxy:start:codex"), None);
+        assert_eq!(parse_tray_proxy_menu_id("proxy:"), None);
+        assert_eq!(parse_tray_proxy_menu_id("show"), None);
+    }
+}
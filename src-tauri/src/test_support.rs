@@ -0,0 +1,254 @@
+// 测试辅助 - 本地 mock HTTP 上游
+//
+// 基于原始 TCP 的极简 HTTP/1.1 服务，供各模块测试共用：
+// - 按 Content-Length 读取完整请求并记录
+// - 由处理函数按请求决定响应，可延迟或写出后挂起连接
+// - 响应不带 `Connection: close` 时保持连接，支持 keep-alive 复用
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 挂起连接的时长（远大于测试中的超时设置）
+const STALL_DURATION: Duration = Duration::from_secs(5);
+
+/// mock 上游收到的一次请求
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// 请求行与请求头（不含末尾空行）
+    pub head: String,
+    /// 请求体原始字节
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    /// 请求行，如 `GET /v1/models HTTP/1.1`
+    pub fn request_line(&self) -> &str {
+        self.head.lines().next().unwrap_or_default()
+    }
+
+    /// 按名称（忽略大小写）查找请求头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then_some(value.trim())
+        })
+    }
+
+    /// 请求体文本
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+/// mock 上游的响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    raw: Vec<u8>,
+    delay: Duration,
+    stall: bool,
+}
+
+impl MockResponse {
+    /// 原样写出的完整 HTTP 响应
+    pub fn raw(raw: impl Into<Vec<u8>>) -> Self {
+        Self {
+            raw: raw.into(),
+            delay: Duration::ZERO,
+            stall: false,
+        }
+    }
+
+    /// 空响应体，如 `MockResponse::status("401 Unauthorized")`
+    pub fn status(status_line: &str) -> Self {
+        Self::raw(format!(
+            "HTTP/1.1 {status_line}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ))
+    }
+
+    /// 带响应体
+    pub fn body(status_line: &str, content_type: &str, body: impl AsRef<[u8]>) -> Self {
+        let body = body.as_ref();
+        let mut raw = format!(
+            "HTTP/1.1 {status_line}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(body);
+        Self::raw(raw)
+    }
+
+    /// JSON 响应体
+    pub fn json(status_line: &str, body: &str) -> Self {
+        Self::body(status_line, "application/json", body)
+    }
+
+    /// 延迟后再写出响应
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// 写出响应后挂起连接（不再发送数据也不关闭），模拟慢上游
+    pub fn stalled(mut self) -> Self {
+        self.stall = true;
+        self
+    }
+
+    fn keep_alive(&self) -> bool {
+        !String::from_utf8_lossy(&self.raw)
+            .to_ascii_lowercase()
+            .contains("connection: close")
+    }
+}
+
+type Handler = dyn Fn(usize, &MockRequest) -> MockResponse + Send + Sync;
+
+/// 本地 mock HTTP 上游
+pub struct MockUpstream {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl MockUpstream {
+    /// 启动 mock 上游，处理函数收到请求序号（从 0 开始）与请求，返回响应
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(usize, &MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(AtomicUsize::new(0));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let server_requests = Arc::clone(&requests);
+        let server_connections = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                server_connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve_connection(
+                    socket,
+                    Arc::clone(&server_requests),
+                    Arc::clone(&handler),
+                ));
+            }
+        });
+
+        Self {
+            addr,
+            requests,
+            connections,
+        }
+    }
+
+    /// 启动对每个请求都返回相同响应的 mock 上游
+    pub async fn respond(response: MockResponse) -> Self {
+        Self::start(move |_, _| response.clone()).await
+    }
+
+    /// 根地址，如 `http://127.0.0.1:12345`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 监听端口
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// 已收到的请求
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 已收到的请求数
+    pub fn hits(&self) -> usize {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// 已建立的连接数
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+/// 返回一个连接会被拒绝的本地地址（绑定后立即释放端口）
+pub async fn closed_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+async fn serve_connection(
+    mut socket: TcpStream,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    handler: Arc<Handler>,
+) {
+    let mut pending = Vec::new();
+    while let Some(request) = read_request(&mut socket, &mut pending).await {
+        let response = {
+            let mut requests = requests.lock().unwrap_or_else(|e| e.into_inner());
+            let response = handler(requests.len(), &request);
+            requests.push(request);
+            response
+        };
+
+        if !response.delay.is_zero() {
+            tokio::time::sleep(response.delay).await;
+        }
+        if socket.write_all(&response.raw).await.is_err() {
+            return;
+        }
+        if response.stall {
+            tokio::time::sleep(STALL_DURATION).await;
+            return;
+        }
+        if !response.keep_alive() {
+            let _ = socket.shutdown().await;
+            return;
+        }
+    }
+}
+
+/// 读取一个完整请求（请求头 + Content-Length 指定的请求体），连接关闭时返回 None
+///
+/// `pending` 保存已读到但属于下一个请求的数据
+async fn read_request(socket: &mut TcpStream, pending: &mut Vec<u8>) -> Option<MockRequest> {
+    let mut buf = [0u8; 4096];
+    loop {
+        if let Some(pos) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&pending[..pos]).to_string();
+            let request = MockRequest {
+                head,
+                body: Vec::new(),
+            };
+            let content_length = request
+                .header("content-length")
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(0);
+            let body_start = pos + 4;
+            if pending.len() - body_start >= content_length {
+                let body = pending[body_start..body_start + content_length].to_vec();
+                pending.drain(..body_start + content_length);
+                return Some(MockRequest { body, ..request });
+            }
+        }
+
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => pending.extend_from_slice(&buf[..n]),
+        }
+    }
+}
@@ -3,7 +3,9 @@ pub mod tray;
 pub mod window;
 
 // 导出窗口管理函数
-pub use window::{focus_main_window, hide_window_to_tray, restore_window_state};
+pub use window::{
+    focus_main_window, handle_close_request, hide_window_to_tray, restore_window_state, CloseTarget,
+};
 
 // 导出托盘管理函数
 pub use tray::create_tray_menu;
@@ -1,3 +1,4 @@
+use crate::models::config::CloseBehavior;
 use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
 
 /// 聚焦并显示主窗口
@@ -118,3 +119,116 @@ pub fn hide_window_to_tray<R: Runtime>(window: &WebviewWindow<R>) {
         tracing::debug!("macOS Dock 图标已隐藏");
     }
 }
+
+/// 关闭请求的执行目标（抽象窗口操作，便于测试关闭行为）
+pub trait CloseTarget {
+    /// 隐藏到托盘
+    fn hide_to_tray(&self);
+    /// 退出应用
+    fn exit_app(&self);
+    /// 请求前端弹出关闭确认框
+    fn request_confirm(&self) -> tauri::Result<()>;
+}
+
+impl<R: Runtime> CloseTarget for WebviewWindow<R> {
+    fn hide_to_tray(&self) {
+        hide_window_to_tray(self);
+    }
+
+    fn exit_app(&self) {
+        self.app_handle().exit(0);
+    }
+
+    fn request_confirm(&self) -> tauri::Result<()> {
+        super::emit_close_confirm(self.app_handle())
+    }
+}
+
+/// 按配置的关闭行为处理窗口关闭请求（调用方需先阻止默认关闭）
+///
+/// `ask` 时发送确认事件失败则降级为隐藏到托盘，避免窗口无法关闭
+pub fn handle_close_request<T: CloseTarget>(target: &T, behavior: CloseBehavior) {
+    match behavior {
+        CloseBehavior::Minimize => target.hide_to_tray(),
+        CloseBehavior::Exit => {
+            tracing::info!("按配置直接退出应用");
+            target.exit_app();
+        }
+        CloseBehavior::Ask => {
+            if let Err(err) = target.request_confirm() {
+                tracing::error!(error = ?err, "发送关闭确认事件失败，降级为隐藏窗口");
+                target.hide_to_tray();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct MockTarget {
+        calls: RefCell<Vec<&'static str>>,
+        confirm_fails: bool,
+    }
+
+    impl CloseTarget for MockTarget {
+        fn hide_to_tray(&self) {
+            self.calls.borrow_mut().push("hide");
+        }
+
+        fn exit_app(&self) {
+            self.calls.borrow_mut().push("exit");
+        }
+
+        fn request_confirm(&self) -> tauri::Result<()> {
+            self.calls.borrow_mut().push("confirm");
+            if self.confirm_fails {
+                Err(tauri::Error::Io(std::io::Error::other("emit failed")))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_exit_behavior_calls_exit() {
+        let target = MockTarget::default();
+        handle_close_request(&target, CloseBehavior::Exit);
+        assert_eq!(*target.calls.borrow(), vec!["exit"]);
+    }
+
+    #[test]
+    fn test_minimize_and_ask_behavior() {
+        let target = MockTarget::default();
+        handle_close_request(&target, CloseBehavior::Minimize);
+        assert_eq!(*target.calls.borrow(), vec!["hide"]);
+
+        let target = MockTarget::default();
+        handle_close_request(&target, CloseBehavior::Ask);
+        assert_eq!(*target.calls.borrow(), vec!["confirm"]);
+
+        // 确认事件发送失败时降级为隐藏
+        let target = MockTarget {
+            confirm_fails: true,
+            ..Default::default()
+        };
+        handle_close_request(&target, CloseBehavior::Ask);
+        assert_eq!(*target.calls.borrow(), vec!["confirm", "hide"]);
+    }
+
+    #[test]
+    fn test_close_behavior_serde() {
+        assert_eq!(CloseBehavior::default(), CloseBehavior::Minimize);
+        assert_eq!(
+            serde_json::from_str::<CloseBehavior>(r#""exit""#).unwrap(),
+            CloseBehavior::Exit
+        );
+        assert_eq!(
+            serde_json::to_string(&CloseBehavior::Ask).unwrap(),
+            r#""ask""#
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use super::platform::PlatformInfo;
+use std::collections::HashMap;
 use std::io;
-use std::process::{Command, Output};
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -34,19 +37,48 @@ impl CommandResult {
     }
 }
 
+/// 命令输出逐行回调（用于安装进度上报）
+pub type OutputLineSink = Arc<dyn Fn(&str) + Send + Sync>;
+
 /// 命令执行器
 #[derive(Clone)]
 pub struct CommandExecutor {
     platform: PlatformInfo,
+    /// 额外注入的环境变量（如工具实例配置的自定义环境变量）
+    envs: Vec<(String, String)>,
+    /// `execute_streaming` 读取到每行输出时的回调
+    output_sink: Option<OutputLineSink>,
 }
 
 impl CommandExecutor {
     pub fn new() -> Self {
         CommandExecutor {
             platform: PlatformInfo::current(),
+            envs: Vec::new(),
+            output_sink: None,
         }
     }
 
+    /// 返回逐行上报 `execute_streaming` 输出的执行器副本
+    pub fn with_output_sink(&self, sink: OutputLineSink) -> Self {
+        let mut executor = self.clone();
+        executor.output_sink = Some(sink);
+        executor
+    }
+
+    /// 返回注入了额外环境变量的执行器副本
+    ///
+    /// PATH 仍由增强 PATH 决定，不会被覆盖
+    pub fn with_envs(&self, envs: &HashMap<String, String>) -> Self {
+        let mut executor = self.clone();
+        executor.envs.extend(
+            envs.iter()
+                .filter(|(key, _)| !key.trim().is_empty() && !key.eq_ignore_ascii_case("PATH"))
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        executor
+    }
+
     /// 执行命令（使用增强的 PATH）
     ///
     /// 智能重试策略：
@@ -85,6 +117,7 @@ impl CommandExecutor {
                 Command::new("cmd")
                     .args(["/C", command_str])
                     .creation_flags(0x08000000) // CREATE_NO_WINDOW
+                    .envs(self.envs.clone())
                     .env("PATH", path_env)
                     .output()
             }
@@ -92,12 +125,14 @@ impl CommandExecutor {
             {
                 Command::new("cmd")
                     .args(["/C", command_str])
+                    .envs(self.envs.clone())
                     .env("PATH", path_env)
                     .output()
             }
         } else {
             Command::new("sh")
                 .args(["-c", command_str])
+                .envs(self.envs.clone())
                 .env("PATH", path_env)
                 .output()
         };
@@ -175,19 +210,95 @@ impl CommandExecutor {
     /// 执行命令（异步）
     pub async fn execute_async(&self, command_str: &str) -> CommandResult {
         let command_str = command_str.to_string();
-        let platform = self.platform.clone();
+        let executor = self.clone();
+
+        tokio::task::spawn_blocking(move || executor.execute(&command_str))
+            .await
+            .unwrap_or_else(|e| CommandResult {
+                success: false,
+                stdout: String::new(),
+                stderr: format!("任务执行失败: {e}"),
+                exit_code: None,
+            })
+    }
 
-        tokio::task::spawn_blocking(move || {
-            let executor = CommandExecutor { platform };
-            executor.execute(&command_str)
-        })
-        .await
-        .unwrap_or_else(|e| CommandResult {
-            success: false,
-            stdout: String::new(),
-            stderr: format!("任务执行失败: {e}"),
-            exit_code: None,
-        })
+    /// 执行命令并逐行读取 stdout/stderr（使用增强的 PATH）
+    ///
+    /// 两个输出流并发读取，每读到一行即交给 `output_sink`（未设置时仅收集），
+    /// 命令结束后返回与 `execute` 相同格式的结果
+    pub async fn execute_streaming(&self, command_str: &str) -> CommandResult {
+        let enhanced_path = self.platform.build_enhanced_path();
+
+        let mut command = if self.platform.is_windows {
+            let mut command = tokio::process::Command::new("cmd");
+            command.args(["/C", command_str]);
+            #[cfg(target_os = "windows")]
+            command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+            command
+        } else {
+            let mut command = tokio::process::Command::new("sh");
+            command.args(["-c", command_str]);
+            command
+        };
+        command
+            .envs(self.envs.clone())
+            .env("PATH", &enhanced_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => return CommandResult::from_error(e),
+        };
+
+        let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+            return CommandResult::from_error(io::Error::other("无法读取命令输出"));
+        };
+        // 按字节读取行再做有损解码：输出含非 UTF-8 字节（如 Windows 下 GBK 编码的提示）时
+        // 不能停止读取，否则管道写满后子进程阻塞，wait 永远等不到退出
+        let mut stdout = BufReader::new(stdout);
+        let mut stderr = BufReader::new(stderr);
+        let (mut stdout_pending, mut stderr_pending) = (Vec::new(), Vec::new());
+        let mut stdout_buf = Vec::new();
+        let mut stderr_buf = Vec::new();
+        let (mut stdout_done, mut stderr_done) = (false, false);
+
+        while !(stdout_done && stderr_done) {
+            tokio::select! {
+                line = next_lossy_line(&mut stdout, &mut stdout_pending), if !stdout_done => match line {
+                    Ok(Some(line)) => {
+                        self.report_line(&line);
+                        stdout_buf.push(line);
+                    }
+                    _ => stdout_done = true,
+                },
+                line = next_lossy_line(&mut stderr, &mut stderr_pending), if !stderr_done => match line {
+                    Ok(Some(line)) => {
+                        self.report_line(&line);
+                        stderr_buf.push(line);
+                    }
+                    _ => stderr_done = true,
+                },
+            }
+        }
+
+        match child.wait().await {
+            Ok(status) => CommandResult {
+                success: status.success(),
+                stdout: stdout_buf.join("\n").trim().to_string(),
+                stderr: stderr_buf.join("\n").trim().to_string(),
+                exit_code: status.code(),
+            },
+            Err(e) => CommandResult::from_error(e),
+        }
+    }
+
+    fn report_line(&self, line: &str) {
+        if let Some(sink) = &self.output_sink {
+            sink(line);
+        }
     }
 
     /// 检查命令是否存在
@@ -239,6 +350,24 @@ impl CommandExecutor {
     }
 }
 
+/// 读取下一行（去掉行尾换行符），非 UTF-8 字节按有损方式解码；读到 EOF 时返回 None
+///
+/// `pending` 在调用之间保留未读完的行，保证在 `select!` 中被取消时不丢数据
+async fn next_lossy_line<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    pending: &mut Vec<u8>,
+) -> io::Result<Option<String>> {
+    let read = reader.read_until(b'\n', pending).await?;
+    if read == 0 && pending.is_empty() {
+        return Ok(None);
+    }
+    let line = String::from_utf8_lossy(pending)
+        .trim_end_matches(['\r', '\n'])
+        .to_string();
+    pending.clear();
+    Ok(Some(line))
+}
+
 impl Default for CommandExecutor {
     fn default() -> Self {
         Self::new()
@@ -279,4 +408,78 @@ mod tests {
 
         assert!(result.success);
     }
+
+    #[tokio::test]
+    async fn test_execute_with_injected_envs() {
+        let mut envs = HashMap::new();
+        envs.insert("DUCKCODING_TEST_ENV".to_string(), "injected".to_string());
+        let executor = CommandExecutor::new().with_envs(&envs);
+
+        let command = if cfg!(windows) {
+            "echo %DUCKCODING_TEST_ENV%"
+        } else {
+            "echo $DUCKCODING_TEST_ENV"
+        };
+
+        let result = executor.execute_async(command).await;
+        assert!(result.success);
+        assert_eq!(result.stdout, "injected");
+
+        // 原执行器不受影响
+        let plain = CommandExecutor::new().execute(command);
+        assert_ne!(plain.stdout, "injected");
+    }
+
+    #[test]
+    fn test_with_envs_keeps_enhanced_path() {
+        let mut envs = HashMap::new();
+        envs.insert("PATH".to_string(), "/nonexistent".to_string());
+        envs.insert(" ".to_string(), "ignored".to_string());
+        let executor = CommandExecutor::new().with_envs(&envs);
+
+        assert!(executor.envs.is_empty());
+        assert!(executor.execute("echo still_works").success);
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_execute_streaming_reports_stdout_and_stderr_lines() {
+        let lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let lines_clone = Arc::clone(&lines);
+        let executor = CommandExecutor::new().with_output_sink(Arc::new(move |line: &str| {
+            lines_clone.lock().unwrap().push(line.to_string());
+        }));
+
+        let result = executor
+            .execute_streaming("echo out1; echo err1 >&2; echo out2; exit 3")
+            .await;
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stdout, "out1\nout2");
+        assert_eq!(result.stderr, "err1");
+
+        // stdout 与 stderr 的每一行都实时上报
+        let mut reported = lines.lock().unwrap().clone();
+        reported.sort();
+        assert_eq!(reported, vec!["err1", "out1", "out2"]);
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_execute_streaming_drains_non_utf8_output() {
+        // 非 UTF-8 行之后还有大量输出，必须持续读取才能让子进程正常退出
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            CommandExecutor::new().execute_streaming(
+                "printf 'bad \\377\\376 line\\n'; seq 1 20000; printf 'tail' >&2",
+            ),
+        )
+        .await
+        .expect("非 UTF-8 输出不应导致读取卡死");
+
+        assert!(result.success);
+        assert!(result.stdout.starts_with("bad \u{FFFD}\u{FFFD} line\n1\n"));
+        assert!(result.stdout.ends_with("\n20000"));
+        assert_eq!(result.stderr, "tail");
+    }
 }
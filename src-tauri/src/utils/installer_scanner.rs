@@ -61,6 +61,8 @@ pub fn scan_installer_paths(tool_path: &str) -> Vec<InstallerCandidate> {
         ("yarn", InstallMethod::Npm),
         ("yarn.cmd", InstallMethod::Npm),
         ("yarn.exe", InstallMethod::Npm),
+        ("bun", InstallMethod::Npm),
+        ("bun.exe", InstallMethod::Npm),
         ("brew", InstallMethod::Brew),
     ];
 
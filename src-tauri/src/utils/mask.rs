@@ -0,0 +1,25 @@
+//! 敏感信息脱敏工具
+
+/// 脱敏密钥：保留前后各 4 个字符（按字符截取，兼容非 ASCII），不超过 8 个字符时完全隐藏
+pub fn mask_secret(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let prefix: String = chars[..4].iter().collect();
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("{prefix}...{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_secret() {
+        assert_eq!(mask_secret(""), "****");
+        assert_eq!(mask_secret("12345678"), "****");
+        assert_eq!(mask_secret("123456789"), "1234...6789");
+        assert_eq!(mask_secret("令牌-abc-中文密钥结尾"), "令牌-a...密钥结尾");
+    }
+}
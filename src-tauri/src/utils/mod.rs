@@ -3,6 +3,8 @@ pub mod command;
 pub mod config;
 pub mod file_helpers;
 pub mod installer_scanner;
+pub mod mask;
+pub mod permissions;
 pub mod platform;
 pub mod precision;
 pub mod version;
@@ -13,6 +15,8 @@ pub use command::*;
 pub use config::*;
 pub use file_helpers::*;
 pub use installer_scanner::*;
+pub use mask::*;
+pub use permissions::*;
 pub use platform::*;
 pub use version::*;
 pub use wsl_executor::*;
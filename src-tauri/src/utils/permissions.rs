@@ -0,0 +1,244 @@
+//! 配置文件权限修复
+//!
+//! `~/.duckcoding` 下的部分文件保存 API Key 等敏感信息。通过 DataManager 写入的 JSON
+//! 已设置为 0600，但旧版本遗留、手动编辑或 SQLite 创建的文件可能权限过宽。
+//! 这里统一收紧为仅当前用户可访问：Unix 设为 0600，Windows 通过 icacls
+//! 移除继承的 ACL 并仅授权当前用户
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 含敏感信息的文件（相对配置目录）
+pub const SENSITIVE_FILES: &[&str] = &[
+    "config.json",
+    "proxy.json",
+    "profiles.json",
+    "providers.json",
+    "balance.json",
+    "tools.json",
+    "sessions.db",
+];
+
+/// 含敏感信息的目录（目录内文件全部处理）
+pub const SENSITIVE_DIRS: &[&str] = &["backups", "debug_captures"];
+
+/// SQLite 附属文件后缀（与数据库内容相同，需同样保护）
+const SQLITE_SIDECARS: &[&str] = &["-wal", "-shm"];
+
+/// 修复 `~/.duckcoding` 下敏感文件的权限
+///
+/// 返回实际修改了权限的文件
+pub fn fix_sensitive_file_permissions() -> Result<Vec<PathBuf>> {
+    let dir = super::config::config_dir().map_err(anyhow::Error::msg)?;
+    Ok(fix_sensitive_file_permissions_in(&dir))
+}
+
+/// 修复指定配置目录下敏感文件的权限（单个文件失败时记录警告并继续）
+pub fn fix_sensitive_file_permissions_in(dir: &Path) -> Vec<PathBuf> {
+    sensitive_paths(dir)
+        .into_iter()
+        .filter(|path| match restrict_to_owner(path) {
+            Ok(changed) => changed,
+            Err(e) => {
+                tracing::warn!(path = ?path, error = ?e, "修复配置文件权限失败");
+                false
+            }
+        })
+        .collect()
+}
+
+/// 列出配置目录下存在的敏感文件
+fn sensitive_paths(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for name in SENSITIVE_FILES {
+        let path = dir.join(name);
+        if name.ends_with(".db") {
+            for suffix in SQLITE_SIDECARS {
+                paths.push(dir.join(format!("{name}{suffix}")));
+            }
+        }
+        paths.push(path);
+    }
+    for name in SENSITIVE_DIRS {
+        collect_files(&dir.join(name), &mut paths);
+    }
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    paths
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, paths);
+        } else {
+            paths.push(path);
+        }
+    }
+}
+
+/// 将文件权限收紧为仅当前用户可读写，返回是否做了修改
+#[cfg(unix)]
+fn restrict_to_owner(path: &Path) -> std::io::Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode();
+    if mode & 0o077 == 0 {
+        return Ok(false);
+    }
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(true)
+}
+
+/// 将文件 ACL 收紧为仅当前用户完全控制（移除继承的 ACL），已满足时不做修改
+#[cfg(windows)]
+fn restrict_to_owner(path: &Path) -> std::io::Result<bool> {
+    let user = std::env::var("USERNAME").map_err(std::io::Error::other)?;
+    let current = run_icacls(path, &[])?;
+    if is_owner_only_acl(&current, &path.display().to_string(), &user) {
+        return Ok(false);
+    }
+    run_icacls(path, &["/inheritance:r", "/grant:r", &format!("{user}:F")])?;
+    Ok(true)
+}
+
+#[cfg(windows)]
+fn run_icacls(path: &Path, args: &[&str]) -> std::io::Result<String> {
+    use std::os::windows::process::CommandExt;
+
+    let output = std::process::Command::new("icacls")
+        .arg(path)
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 解析 `icacls <path>` 输出，判断 ACL 是否仅包含当前用户的完全控制权限（且无继承项）
+///
+/// 输出格式：首行为 `<path> <ACE>`，后续每行一个缩进的 ACE，空行后为统计信息
+#[cfg(any(windows, test))]
+fn is_owner_only_acl(icacls_output: &str, path: &str, user: &str) -> bool {
+    let Some(rest) = icacls_output.trim_start().strip_prefix(path) else {
+        return false;
+    };
+    let aces: Vec<&str> = rest
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.is_empty())
+        .collect();
+
+    let [ace] = aces.as_slice() else {
+        return false;
+    };
+    let Some((principal, rights)) = ace.split_once(":(") else {
+        return false;
+    };
+    let account = principal.rsplit('\\').next().unwrap_or(principal);
+    account.eq_ignore_ascii_case(user) && rights == "F)"
+}
+
+#[cfg(not(any(unix, windows)))]
+fn restrict_to_owner(_path: &Path) -> std::io::Result<bool> {
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sensitive_paths() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        for name in [
+            "config.json",
+            "sessions.db",
+            "sessions.db-wal",
+            "token_stats.db",
+        ] {
+            fs::write(dir.join(name), "{}").unwrap();
+        }
+        fs::create_dir_all(dir.join("backups/codex_20250101_000000")).unwrap();
+        fs::write(dir.join("backups/codex_20250101_000000/auth.json"), "{}").unwrap();
+
+        let paths = sensitive_paths(dir);
+        assert_eq!(
+            paths,
+            vec![
+                dir.join("backups/codex_20250101_000000/auth.json"),
+                dir.join("config.json"),
+                dir.join("sessions.db"),
+                dir.join("sessions.db-wal"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fix_sensitive_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let mode_of = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let write_with_mode = |name: &str, mode: u32| {
+            let path = dir.join(name);
+            fs::write(&path, "{}").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            path
+        };
+
+        let profiles = write_with_mode("profiles.json", 0o644);
+        let balance = write_with_mode("balance.json", 0o666);
+        let config = write_with_mode("config.json", 0o600);
+        let proxy = write_with_mode("proxy.json", 0o644);
+        let token_stats = write_with_mode("token_stats.db", 0o644);
+
+        let fixed = fix_sensitive_file_permissions_in(dir);
+        assert_eq!(
+            fixed,
+            vec![balance.clone(), profiles.clone(), proxy.clone()]
+        );
+        assert_eq!(mode_of(&profiles), 0o600);
+        assert_eq!(mode_of(&balance), 0o600);
+        assert_eq!(mode_of(&config), 0o600);
+        assert_eq!(mode_of(&proxy), 0o600);
+        // 不含敏感信息的文件保持不变
+        assert_eq!(mode_of(&token_stats), 0o644);
+
+        // 再次执行无需修改
+        assert!(fix_sensitive_file_permissions_in(dir).is_empty());
+    }
+
+    #[test]
+    fn test_is_owner_only_acl() {
+        let path = r"C:\Users\alice\.duckcoding\proxy.json";
+        let restricted = format!(
+            "{path} DESKTOP-1\\alice:(F)\r\n\r\nSuccessfully processed 1 files; Failed processing 0 files\r\n"
+        );
+        assert!(is_owner_only_acl(&restricted, path, "alice"));
+        assert!(is_owner_only_acl(&restricted, path, "ALICE"));
+        assert!(!is_owner_only_acl(&restricted, path, "bob"));
+
+        // 继承的 ACL 或多个授权项需要修复
+        let inherited =
+            format!("{path} DESKTOP-1\\alice:(I)(F)\r\n\r\nSuccessfully processed 1 files\r\n");
+        assert!(!is_owner_only_acl(&inherited, path, "alice"));
+        let shared = format!(
+            "{path} DESKTOP-1\\alice:(F)\r\n     BUILTIN\\Users:(RX)\r\n\r\nSuccessfully processed 1 files\r\n"
+        );
+        assert!(!is_owner_only_acl(&shared, path, "alice"));
+    }
+}
@@ -4,10 +4,30 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 use semver::Version;
+use std::cmp::Ordering;
 
-/// 版本号正则表达式（支持语义化版本）
-static VERSION_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"v?(\d+\.\d+\.\d+(?:-[\w.]+)?)").expect("版本正则表达式无效"));
+/// 版本号正则表达式（至少三段数字，支持预发布标识与构建元数据，如 1.2.3-rc.1+build.5、1.2.3.4）
+static VERSION_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"v?(\d+(?:\.\d+){2,}(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)")
+        .expect("版本正则表达式无效")
+});
+
+/// 从命令输出中提取版本号（未找到标准版本号时返回 None）
+///
+/// # Examples
+///
+/// ```
+/// use duckcoding::utils::version::extract_version;
+///
+/// assert_eq!(extract_version("codex-cli 0.65.0-alpha.1").as_deref(), Some("0.65.0-alpha.1"));
+/// assert_eq!(extract_version("command not found"), None);
+/// ```
+pub fn extract_version(output: &str) -> Option<String> {
+    VERSION_REGEX
+        .captures(output)?
+        .get(1)
+        .map(|m| m.as_str().to_string())
+}
 
 /// 解析版本号字符串，处理多种常见格式
 ///
@@ -17,6 +37,7 @@ static VERSION_REGEX: Lazy<Regex> =
 /// - "2.0.61 (Claude Code)" -> "2.0.61"
 /// - "codex-cli 0.65.0" -> "0.65.0"
 /// - "1.2.3-beta.1" -> "1.2.3-beta.1"
+/// - "1.2.3+build.5" -> "1.2.3+build.5"
 ///
 /// # 实现策略
 /// 1. 使用正则表达式提取标准语义化版本号（优先）
@@ -92,6 +113,48 @@ pub fn parse_version(raw: &str) -> Option<Version> {
     Version::parse(&version_str).ok()
 }
 
+/// 比较两个版本号
+///
+/// 两者都能解析为 semver 时按语义化版本优先级比较（预发布版本低于正式版，忽略构建元数据）；
+/// 否则回退到逐段数字比较（如 `1.2.3.4`，缺少的段视为 0，每段只取开头的数字）
+///
+/// # Examples
+///
+/// ```
+/// use duckcoding::utils::version::compare_versions;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(compare_versions("1.2.3-rc.1", "1.2.3"), Ordering::Less);
+/// assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+/// ```
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(va), Some(vb)) => va.cmp_precedence(&vb),
+        _ => compare_segments(&parse_version_string(a), &parse_version_string(b)),
+    }
+}
+
+/// 逐段比较版本号中的数字
+fn compare_segments(a: &str, b: &str) -> Ordering {
+    let segments = |v: &str| -> Vec<u64> {
+        v.split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    let (sa, sb) = (segments(a), segments(b));
+    (0..sa.len().max(sb.len()))
+        .map(|i| {
+            let x = sa.get(i).copied().unwrap_or(0);
+            let y = sb.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ord| ord.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +189,64 @@ mod tests {
         assert_eq!(parse_version_string("2.0.0-rc.2"), "2.0.0-rc.2");
     }
 
+    #[test]
+    fn test_parse_version_with_build_metadata_and_extra_segments() {
+        assert_eq!(parse_version_string("1.2.3+build.5"), "1.2.3+build.5");
+        assert_eq!(
+            parse_version_string("v2.0.0-rc-1+sha.abc"),
+            "2.0.0-rc-1+sha.abc"
+        );
+        assert_eq!(parse_version_string("tool 1.2.3.4"), "1.2.3.4");
+        assert!(parse_version("1.2.3.4").is_none());
+    }
+
+    #[test]
+    fn test_extract_version() {
+        assert_eq!(
+            extract_version("2.0.61-beta.2 (Claude Code)").as_deref(),
+            Some("2.0.61-beta.2")
+        );
+        assert_eq!(extract_version("v1.2.3").as_deref(), Some("1.2.3"));
+        assert_eq!(extract_version("not installed"), None);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        // 预发布版本低于正式版
+        assert_eq!(compare_versions("1.2.3-rc.1", "1.2.3"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.2.3-beta.2", "1.2.3-rc.1"),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_versions("1.2.3-beta.10", "1.2.3-beta.9"),
+            Ordering::Greater
+        );
+        // 多位数字按数值比较
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        // 构建元数据不参与比较
+        assert_eq!(
+            compare_versions("1.2.3+build.1", "1.2.3+build.2"),
+            Ordering::Equal
+        );
+        // 前缀与括号格式
+        assert_eq!(compare_versions("rust-v0.55.0", "0.55.0"), Ordering::Equal);
+        assert_eq!(
+            compare_versions("2.0.61 (Claude Code)", "2.0.62"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_compare_versions_fallback() {
+        // 非法 semver 时逐段比较
+        assert_eq!(compare_versions("1.2.3.4", "1.2.3.5"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.3.10", "1.2.3.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2.3.0", "1.2.3"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.4", "1.2.3.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+    }
+
     #[test]
     fn test_parse_complex_version() {
         assert_eq!(
@@ -251,9 +251,7 @@ impl WSLExecutor {
 
     /// 从输出中提取版本号
     fn extract_version(&self, output: &str) -> Option<String> {
-        // 匹配版本号格式: v1.2.3 或 1.2.3
-        let re = regex::Regex::new(r"v?(\d+\.\d+\.\d+(?:-[\w.]+)?)").ok()?;
-        re.captures(output)?.get(1).map(|m| m.as_str().to_string())
+        super::version::extract_version(output)
     }
 
     /// 检测工具的完整信息（安装状态、版本、路径）使用默认发行版
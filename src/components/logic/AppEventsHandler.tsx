@@ -5,8 +5,14 @@ import { useToast } from '@/hooks/use-toast';
 import { useAppEvents } from '@/hooks/useAppEvents';
 import { useCloseAction } from '@/hooks/useCloseAction';
 import { CloseActionDialog } from '@/components/dialogs/CloseActionDialog';
-import type { UpdateInfo, CloseAction } from '@/lib/tauri-commands';
+import type {
+  UpdateInfo,
+  CloseAction,
+  BalanceLowAlert,
+  ProxyUpstreamStatus,
+} from '@/lib/tauri-commands';
 import type { ToolType } from '@/types/token-stats';
+import type { ConfigDriftEvent } from '@/types/config-watch';
 import type { TabType } from '@/contexts/AppContext.types';
 
 export function AppEventsHandler() {
@@ -63,7 +69,6 @@ export function AppEventsHandler() {
       }
     },
     onRefreshTools: refreshTools,
-    executeCloseAction,
   });
 
   // Additional Event Listeners
@@ -129,6 +134,45 @@ export function AppEventsHandler() {
       },
     );
 
+    // 监听原生配置被外部修改（激活 Profile 可能与实际配置不一致）
+    const unlistenConfigDrift = listen<ConfigDriftEvent>('config-drift-detected', (event) => {
+      const { tool_id, path } = event.payload;
+      toast({
+        title: '检测到配置被外部修改',
+        description: `${tool_id} 的 ${path} 已变更，当前激活的 Profile 可能与实际配置不一致`,
+      });
+    });
+
+    // 监听低余额告警（后端已做跨越阈值去抖，每次跌破只触发一次）
+    const unlistenBalanceLow = listen<BalanceLowAlert>('balance-low-alert', (event) => {
+      const { name, remaining, threshold } = event.payload;
+      const title = '余额不足提醒';
+      const description = `${name} 当前余额 ${remaining} 已低于告警阈值 ${threshold}`;
+      toast({ variant: 'destructive', title, description });
+      if ('Notification' in window) {
+        const notify = () => new Notification(title, { body: description });
+        if (Notification.permission === 'granted') {
+          notify();
+        } else if (Notification.permission !== 'denied') {
+          Notification.requestPermission().then((permission) => {
+            if (permission === 'granted') notify();
+          });
+        }
+      }
+    });
+
+    // 监听代理启动后的上游探测结果（仅在上游不可达或异常时提示）
+    const unlistenUpstream = listen<ProxyUpstreamStatus>('proxy-upstream-status', (event) => {
+      const { tool_id, port, upstream, message } = event.payload;
+      if (upstream.healthy) return;
+      const detail = upstream.error ? `：${upstream.error}` : '';
+      toast({
+        variant: 'destructive',
+        title: message,
+        description: `${tool_id} 代理（端口 ${port}）无法正常访问上游 ${upstream.host ?? ''}${detail}`,
+      });
+    });
+
     // 监听菜单栏导航事件
     const unlistenNavigateTo = listen<string>('navigate-to', (event) => {
       const path = event.payload;
@@ -152,6 +196,9 @@ export function AppEventsHandler() {
       unlistenOpenSettings.then((fn) => fn());
       unlistenAppNavigate.then((fn) => fn());
       unlistenProfileActivated.then((fn) => fn());
+      unlistenConfigDrift.then((fn) => fn());
+      unlistenBalanceLow.then((fn) => fn());
+      unlistenUpstream.then((fn) => fn());
       unlistenNavigateTo.then((fn) => fn());
     };
   }, [
@@ -173,7 +220,7 @@ export function AppEventsHandler() {
       onClose={closeDialog}
       onRememberChange={setRememberCloseChoice}
       onExecuteAction={(action: CloseAction, remember: boolean) =>
-        executeCloseAction(action, remember, false)
+        executeCloseAction(action, remember)
       }
     />
   );
@@ -1,9 +1,8 @@
 import { useEffect } from 'react';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
-import type { CloseAction } from '@/lib/tauri-commands';
 
+// 仅在全局配置 close_behavior 为 ask 时由后端发出
 const CLOSE_EVENT = 'duckcoding://request-close-action';
-const CLOSE_PREFERENCE_KEY = 'duckcoding.closePreference';
 const SINGLE_INSTANCE_EVENT = 'single-instance';
 
 interface SingleInstancePayload {
@@ -33,7 +32,6 @@ interface AppEventsOptions {
   onNavigateToSettings: (detail?: { tab?: string }) => void;
   onNavigateToTransparentProxy: (detail?: { toolId?: string }) => void;
   onRefreshTools: () => void;
-  executeCloseAction: (action: CloseAction, remember: boolean, autoTriggered: boolean) => void;
 }
 
 export function useAppEvents(options: AppEventsOptions) {
@@ -46,7 +44,6 @@ export function useAppEvents(options: AppEventsOptions) {
     onNavigateToSettings,
     onNavigateToTransparentProxy,
     onRefreshTools,
-    executeCloseAction,
   } = options;
 
   // 监听窗口关闭事件
@@ -59,21 +56,6 @@ export function useAppEvents(options: AppEventsOptions) {
     let disposed = false;
 
     listen(CLOSE_EVENT, () => {
-      if (typeof window !== 'undefined') {
-        try {
-          const savedPreference = window.localStorage.getItem(
-            CLOSE_PREFERENCE_KEY,
-          ) as CloseAction | null;
-
-          if (savedPreference === 'minimize' || savedPreference === 'quit') {
-            executeCloseAction(savedPreference, true, true);
-            return;
-          }
-        } catch (storageError) {
-          console.warn('读取关闭偏好失败:', storageError);
-        }
-      }
-
       onCloseRequest();
     })
       .then((fn) => {
@@ -93,7 +75,7 @@ export function useAppEvents(options: AppEventsOptions) {
         unlisten();
       }
     };
-  }, [onCloseRequest, executeCloseAction]);
+  }, [onCloseRequest]);
 
   // 监听单例应用事件
   useEffect(() => {
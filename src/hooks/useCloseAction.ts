@@ -1,7 +1,13 @@
-import { useState, useCallback } from 'react';
-import { applyCloseAction, type CloseAction } from '@/lib/tauri-commands';
+import { useState, useCallback, useEffect } from 'react';
+import {
+  applyCloseAction,
+  getGlobalConfig,
+  saveGlobalConfig,
+  type CloseAction,
+} from '@/lib/tauri-commands';
 
-const CLOSE_PREFERENCE_KEY = 'duckcoding.closePreference';
+// 旧版本保存在 localStorage 中的关闭偏好（现已迁移到全局配置 close_behavior）
+const LEGACY_CLOSE_PREFERENCE_KEY = 'duckcoding.closePreference';
 
 const isTauriEnvironment = () => {
   if (typeof window === 'undefined') {
@@ -16,14 +22,48 @@ const isTauriEnvironment = () => {
   );
 };
 
+// 将旧版 localStorage 关闭偏好一次性写入全局配置，成功后删除旧键
+const migrateLegacyClosePreference = async () => {
+  const legacy = window.localStorage.getItem(LEGACY_CLOSE_PREFERENCE_KEY);
+  if (!legacy) {
+    return;
+  }
+
+  if (legacy === 'minimize' || legacy === 'quit') {
+    const config = await getGlobalConfig();
+    if (!config) {
+      // 全局配置尚未创建，保留旧键等下次启动再迁移
+      return;
+    }
+    // 后端已有明确选择时以后端为准
+    if (!config.close_behavior || config.close_behavior === 'ask') {
+      await saveGlobalConfig({
+        ...config,
+        close_behavior: legacy === 'quit' ? 'exit' : 'minimize',
+      });
+    }
+  }
+
+  window.localStorage.removeItem(LEGACY_CLOSE_PREFERENCE_KEY);
+};
+
 export function useCloseAction(onError: (message: string) => void) {
   const [closeDialogOpen, setCloseDialogOpen] = useState(false);
   const [rememberCloseChoice, setRememberCloseChoice] = useState(false);
   const [closeActionLoading, setCloseActionLoading] = useState<CloseAction | null>(null);
 
-  // 执行窗口关闭动作
+  useEffect(() => {
+    if (!isTauriEnvironment()) {
+      return;
+    }
+    migrateLegacyClosePreference().catch((error) => {
+      console.error('迁移关闭偏好失败:', error);
+    });
+  }, []);
+
+  // 执行窗口关闭动作（remember 时由后端写入全局配置 close_behavior）
   const executeCloseAction = useCallback(
-    async (action: CloseAction, remember = false, autoTriggered = false) => {
+    async (action: CloseAction, remember = false) => {
       if (!isTauriEnvironment()) {
         setCloseDialogOpen(false);
         return;
@@ -31,32 +71,14 @@ export function useCloseAction(onError: (message: string) => void) {
 
       setCloseActionLoading(action);
       try {
-        await applyCloseAction(action);
-
-        if (typeof window !== 'undefined') {
-          try {
-            if (remember) {
-              window.localStorage.setItem(CLOSE_PREFERENCE_KEY, action);
-            } else if (!autoTriggered) {
-              window.localStorage.removeItem(CLOSE_PREFERENCE_KEY);
-            }
-          } catch (storageError) {
-            console.warn('保存关闭偏好失败:', storageError);
-          }
-        }
+        await applyCloseAction(action, remember);
       } catch (error) {
         console.error('执行窗口操作失败:', error);
         onError(error instanceof Error ? error.message : '请稍后重试，或从系统托盘退出/展开窗口');
-
-        if (!autoTriggered) {
-          setCloseDialogOpen(true);
-        }
       } finally {
         setCloseActionLoading(null);
-        if (!autoTriggered) {
-          setCloseDialogOpen(false);
-          setRememberCloseChoice(false);
-        }
+        setCloseDialogOpen(false);
+        setRememberCloseChoice(false);
       }
     },
     [onError],
@@ -2,7 +2,23 @@
  * Token 统计分析相关 Tauri 命令
  */
 import { invoke } from '@tauri-apps/api/core';
-import type { TrendQuery, TrendDataPoint, CostSummary } from '@/types/analytics';
+import type {
+  CacheRoi,
+  TrendQuery,
+  TrendDataPoint,
+  CostSummary,
+  CostSummaryQuery,
+  GroupedCostSummary,
+  IpUsage,
+  SavingsSummary,
+  StopReasonStat,
+  StatsFilter,
+  StatsQueryResult,
+  SuccessRatePoint,
+  TimeGranularity,
+  UpstreamStat,
+  WeeklyReport,
+} from '@/types/analytics';
 
 /**
  * 查询 Token 使用趋势数据
@@ -34,3 +50,97 @@ export async function queryCostSummary(
     sessionId,
   });
 }
+
+/**
+ * 按工具/模型/配置/会话分组查询成本汇总
+ * @param query 查询参数（含分组方式）
+ * @returns 按总成本降序的分组汇总
+ */
+export async function getCostSummary(query: CostSummaryQuery): Promise<GroupedCostSummary[]> {
+  return await invoke<GroupedCostSummary[]>('get_cost_summary', { query });
+}
+
+/**
+ * 按时间桶（小时/天/周等）查询成本趋势
+ * @param query 查询参数
+ * @returns 按时间排序的趋势数据点（指定时间范围时补齐空桶）
+ */
+export async function getCostTrend(query: TrendQuery): Promise<TrendDataPoint[]> {
+  return await invoke<TrendDataPoint[]>('get_cost_trend', { query });
+}
+
+/**
+ * 查询请求结束原因分布
+ * @param query 查询参数
+ * @returns 按请求数降序的结束原因分布
+ */
+export async function queryStopReasonDistribution(query: StatsFilter): Promise<StopReasonStat[]> {
+  return await invoke<StopReasonStat[]>('query_stop_reason_distribution', { query });
+}
+
+/**
+ * 查询请求成功率趋势
+ * @param query 过滤条件（granularity 字段被忽略）
+ * @param granularity 时间粒度
+ * @returns 按时间排序的成功率数据点数组
+ */
+export async function getSuccessRateTrend(
+  query: TrendQuery,
+  granularity: TimeGranularity,
+): Promise<SuccessRatePoint[]> {
+  return await invoke<SuccessRatePoint[]>('get_success_rate_trend', { query, granularity });
+}
+
+/**
+ * 查询 Prompt Caching 成本收益
+ * @param query 查询参数
+ * @returns 缓存写入成本、读取节省与净收益
+ */
+export async function getCacheRoi(query: StatsFilter): Promise<CacheRoi> {
+  return await invoke<CacheRoi>('get_cache_roi', { query });
+}
+
+/**
+ * 查询按上游聚合的用量、成功率与延迟
+ * @param query 查询参数
+ * @returns 按请求数降序的上游统计
+ */
+export async function getUpstreamStats(query: StatsFilter): Promise<UpstreamStat[]> {
+  return await invoke<UpstreamStat[]>('get_upstream_stats', { query });
+}
+
+/**
+ * 查询按客户端 IP 聚合的用量（共享代理场景）
+ * @param query 查询参数
+ * @returns 按成本降序的各 IP 请求数与成本
+ */
+export async function getUsageByIp(query: StatsFilter): Promise<IpUsage[]> {
+  return await invoke<IpUsage[]>('get_usage_by_ip', { query });
+}
+
+/**
+ * 查询 Prompt Caching 累计节省（对比无缓存时的花费）
+ * @param query 查询参数
+ * @returns 实际花费、无缓存花费、累计节省及按模型明细
+ */
+export async function getSavingsSummary(query: StatsFilter): Promise<SavingsSummary> {
+  return await invoke<SavingsSummary>('get_savings_summary', { query });
+}
+
+/**
+ * 生成指定周的 Token 统计周报
+ * @param weekOffset 往前数的周数（0 为本周，1 为上周）
+ * @returns 每日成本、top 模型、总请求数与环比上周变化
+ */
+export async function getWeeklyReport(weekOffset = 0): Promise<WeeklyReport> {
+  return await invoke<WeeklyReport>('get_weekly_report', { weekOffset });
+}
+
+/**
+ * 对 Token 统计库执行自定义只读查询（仅允许单条 SELECT）
+ * @param sql 查询语句
+ * @returns 列名与行数据（最多 1000 行）
+ */
+export async function runStatsQuery(sql: string): Promise<StatsQueryResult> {
+  return await invoke<StatsQueryResult>('run_stats_query', { sql });
+}
@@ -2,7 +2,7 @@
 // 负责余额配置的 CRUD 和数据迁移
 
 import { invoke } from '@tauri-apps/api/core';
-import type { BalanceStore, BalanceConfigBackend } from './types';
+import type { BalanceStore, BalanceConfigBackend, BalanceSnapshot } from './types';
 import type { BalanceConfig } from '@/pages/BalancePage/types';
 
 /**
@@ -20,6 +20,7 @@ function toFrontendConfig(backend: BalanceConfigBackend): BalanceConfig {
     timeoutMs: backend.timeout_ms,
     saveApiKey: backend.save_api_key,
     apiKey: backend.api_key,
+    alertThreshold: backend.alert_threshold,
     createdAt: backend.created_at,
     updatedAt: backend.updated_at,
   };
@@ -40,6 +41,7 @@ function toBackendConfig(frontend: BalanceConfig): BalanceConfigBackend {
     timeout_ms: frontend.timeoutMs,
     save_api_key: frontend.saveApiKey ?? false,
     api_key: frontend.apiKey,
+    alert_threshold: frontend.alertThreshold,
     created_at: frontend.createdAt,
     updated_at: frontend.updatedAt,
   };
@@ -86,3 +88,18 @@ export async function migrateBalanceFromLocalstorage(configs: BalanceConfig[]):
     configs: configs.map(toBackendConfig),
   });
 }
+
+/**
+ * 获取后台执行引擎缓存的最近查询结果
+ */
+export async function getBalanceSnapshots(): Promise<BalanceSnapshot[]> {
+  return invoke<BalanceSnapshot[]>('get_balance_snapshots');
+}
+
+/**
+ * 立即在后端查询指定配置的余额
+ * 结果同时通过 `balance-updated` 事件广播
+ */
+export async function refreshBalance(id: string): Promise<BalanceSnapshot> {
+  return invoke<BalanceSnapshot>('refresh_balance', { id });
+}
@@ -5,6 +5,7 @@ import { invoke } from '@tauri-apps/api/core';
 import type {
   GlobalConfig,
   ClaudeSettingsPayload,
+  CodexAuthMode,
   CodexSettingsPayload,
   GeminiSettingsPayload,
   GeminiEnvConfig,
@@ -31,6 +32,14 @@ export async function getGlobalConfig(): Promise<GlobalConfig | null> {
   return await invoke<GlobalConfig | null>('get_global_config');
 }
 
+/**
+ * 修复 ~/.duckcoding 下含敏感信息文件的权限（应用启动时也会自动执行）
+ * @returns 实际修改了权限的文件路径
+ */
+export async function fixConfigPermissions(): Promise<string[]> {
+  return await invoke<string[]>('fix_config_permissions');
+}
+
 /**
  * 获取当前代理配置字符串
  */
@@ -55,6 +64,13 @@ export async function testProxyRequest(
   return await invoke<TestProxyResult>('test_proxy_request', { testUrl, proxyConfig });
 }
 
+/**
+ * 使用已保存的全局代理配置测试代理连通性
+ */
+export async function testProxy(): Promise<TestProxyResult> {
+  return await invoke<TestProxyResult>('test_proxy');
+}
+
 // ==================== Claude Code 配置 ====================
 
 /**
@@ -113,12 +129,14 @@ export async function getCodexSettings(): Promise<CodexSettingsPayload> {
 
 /**
  * 保存 Codex 配置
+ * @param authMode 指定时切换认证方式（写入 config.toml 的 preferred_auth_method）
  */
 export async function saveCodexSettings(
   settings: JsonObject,
   authToken?: string | null,
+  authMode?: CodexAuthMode | null,
 ): Promise<void> {
-  return await invoke<void>('save_codex_settings', { settings, authToken });
+  return await invoke<void>('save_codex_settings', { settings, authToken, authMode });
 }
 
 /**
@@ -203,3 +221,27 @@ export async function getStartupConfig(): Promise<boolean> {
 export async function updateStartupConfig(enabled: boolean): Promise<void> {
   return await invoke<void>('update_startup_config', { enabled });
 }
+
+/**
+ * 启动阶段耗时
+ */
+export interface StartupPhase {
+  name: string; // 阶段名称（init_logging / migrations / tool_registry 等）
+  duration_ms: number; // 耗时（毫秒）
+}
+
+/**
+ * 启动性能报告
+ */
+export interface StartupReport {
+  phases: StartupPhase[]; // 各阶段耗时（按执行顺序）
+  total_ms: number; // 启动总耗时（毫秒）
+  slowest_phase: string | null; // 耗时最长的阶段
+}
+
+/**
+ * 获取本次启动的性能报告（各初始化阶段耗时）
+ */
+export async function getStartupTimings(): Promise<StartupReport> {
+  return await invoke<StartupReport>('get_startup_timings');
+}
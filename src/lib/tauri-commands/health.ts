@@ -0,0 +1,19 @@
+// 健康巡检命令模块
+// 负责获取工具健康报告、手动触发巡检
+
+import { invoke } from '@tauri-apps/api/core';
+import type { HealthReport } from '@/types/health';
+
+/**
+ * 获取最近一次健康报告（尚未巡检时立即执行一次）
+ */
+export async function getHealthReport(): Promise<HealthReport> {
+  return invoke<HealthReport>('get_health_report');
+}
+
+/**
+ * 立即执行一次健康巡检
+ */
+export async function runHealthCheck(): Promise<HealthReport> {
+  return invoke<HealthReport>('run_health_check');
+}
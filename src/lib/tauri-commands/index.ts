@@ -34,6 +34,12 @@ export * from './token-stats';
 // 余额监控
 export * from './balance';
 
+// 健康巡检
+export * from './health';
+
+// 配置迁移向导
+export * from './migration';
+
 // 更新管理
 export * from './update';
 
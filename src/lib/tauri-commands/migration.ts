@@ -0,0 +1,19 @@
+// 配置迁移向导命令模块
+// 负责检测待迁移的旧配置、执行迁移
+
+import { invoke } from '@tauri-apps/api/core';
+import type { MigrationReport, MigrationTask } from './types';
+
+/**
+ * 检测仍有旧配置待迁移的任务（旧备份文件格式、旧目录结构等）
+ */
+export async function checkMigrationNeeded(): Promise<MigrationTask[]> {
+  return invoke<MigrationTask[]>('check_migration_needed');
+}
+
+/**
+ * 执行指定迁移任务，返回迁移报告
+ */
+export async function runMigration(taskId: string): Promise<MigrationReport> {
+  return invoke<MigrationReport>('run_migration', { taskId });
+}
@@ -21,7 +21,8 @@ export async function getRecommendedPackageFormat(): Promise<PackageFormatInfo>
 /**
  * 应用窗口关闭动作
  * @param action - 关闭动作（minimize: 最小化到托盘, quit: 退出应用）
+ * @param remember - 记住选择（写入全局配置 close_behavior，之后关闭不再询问）
  */
-export async function applyCloseAction(action: CloseAction): Promise<void> {
-  return await invoke<void>('handle_close_action', { action });
+export async function applyCloseAction(action: CloseAction, remember = false): Promise<void> {
+  return await invoke<void>('handle_close_action', { action, remember });
 }
@@ -5,7 +5,13 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { PricingTemplate, PricingToolId } from '@/types/pricing';
+import type {
+  FallbackPricesConfig,
+  PriceChange,
+  PricingSyncStatus,
+  PricingTemplate,
+  PricingToolId,
+} from '@/types/pricing';
 
 /**
  * 列出所有价格模板
@@ -73,3 +79,67 @@ export async function setDefaultTemplate(toolId: PricingToolId, templateId: stri
 export async function getDefaultTemplate(toolId: PricingToolId): Promise<PricingTemplate> {
   return invoke('get_default_template', { toolId });
 }
+
+/**
+ * 导出价格模板为 JSON 字符串
+ *
+ * @param templateId - 模板 ID
+ * @returns 带 schema 版本号的模板 JSON
+ */
+export async function exportPricingTemplate(templateId: string): Promise<string> {
+  return invoke('export_pricing_template', { templateId });
+}
+
+/**
+ * 从 JSON 字符串导入价格模板
+ *
+ * @param data - 模板 JSON（带 schema 版本号的导出格式）
+ * @param overwrite - ID 冲突时是否覆盖已有用户模板（默认 false，冲突时报错）
+ * @returns 实际保存的模板
+ *
+ * @note
+ * - 不允许覆盖内置预设模板，冲突时另存为 `<id>_imported`
+ */
+export async function importPricingTemplate(
+  data: string,
+  overwrite?: boolean,
+): Promise<PricingTemplate> {
+  return invoke('import_pricing_template', { data, overwrite });
+}
+
+/**
+ * 获取未知模型的兜底价格配置
+ *
+ * @returns 各 provider（claude / openai / gemini）的默认档位价格
+ */
+export async function getPricingFallbackPrices(): Promise<FallbackPricesConfig> {
+  return invoke('get_pricing_fallback_prices');
+}
+
+/**
+ * 保存未知模型的兜底价格配置
+ *
+ * @param config - 兜底价格配置（enabled 为 false 时未知模型不计价）
+ */
+export async function savePricingFallbackPrices(config: FallbackPricesConfig): Promise<void> {
+  return invoke('save_pricing_fallback_prices', { config });
+}
+
+/**
+ * 获取远程价格同步状态
+ *
+ * @returns 上次成功同步时间、最近错误、连续失败次数及距今时长
+ */
+export async function getPricingSyncStatus(): Promise<PricingSyncStatus> {
+  return invoke('get_pricing_sync_status');
+}
+
+/**
+ * 获取指定模型的价格变更历史
+ *
+ * @param model - 模型 ID
+ * @returns 远程同步产生的价格变更记录（按时间倒序）
+ */
+export async function getPriceHistory(model: string): Promise<PriceChange[]> {
+  return invoke('get_price_history', { model });
+}
@@ -3,6 +3,7 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId } from './types';
+import type { AuditEntry, Inconsistency, ProfileSwitch } from '@/types/profile';
 
 // ==================== 旧版 Profile 管理 ====================
 
@@ -30,6 +31,13 @@ export async function pmListToolProfiles(toolId: ToolId): Promise<string[]> {
   return invoke<string[]>('pm_list_tool_profiles', { toolId });
 }
 
+/**
+ * 列出指定工具的 Profile 详情（包含最后使用时间）
+ */
+export async function pmListToolProfileDetails(toolId: ToolId): Promise<ProfileDescriptor[]> {
+  return invoke<ProfileDescriptor[]>('pm_list_tool_profile_details', { toolId });
+}
+
 /**
  * 获取指定 Profile 的完整数据
  */
@@ -56,10 +64,24 @@ export async function pmDeleteProfile(toolId: ToolId, name: string): Promise<voi
 }
 
 /**
- * 激活 Profile（切换）
+ * 激活 Profile（切换），返回切换前后的 Profile 名称
+ */
+export async function pmActivateProfile(toolId: ToolId, name: string): Promise<ProfileSwitch> {
+  return invoke<ProfileSwitch>('pm_activate_profile', { toolId, name });
+}
+
+/**
+ * 撤销最近一次 Profile 切换，恢复切换前的配置（仅支持撤销一次）
  */
-export async function pmActivateProfile(toolId: ToolId, name: string): Promise<void> {
-  return invoke<void>('pm_activate_profile', { toolId, name });
+export async function undoLastSwitch(tool: ToolId): Promise<ProfileSwitch> {
+  return invoke<ProfileSwitch>('undo_last_switch', { tool });
+}
+
+/**
+ * 按 DuckCoding 推荐配置一键配置工具（只需填写 API Key），返回切换前后的 Profile 名称
+ */
+export async function applyRecommendedConfig(tool: ToolId, apiKey: string): Promise<ProfileSwitch> {
+  return invoke<ProfileSwitch>('apply_recommended_config', { tool, apiKey });
 }
 
 /**
@@ -83,6 +105,49 @@ export async function pmCaptureFromNative(toolId: ToolId, name: string): Promise
   return invoke<void>('pm_capture_from_native', { toolId, name });
 }
 
+/**
+ * 导出 Profile 为可分享的 JSON 文本（mask 为 true 时隐藏 API Key）
+ */
+export async function exportProfile(
+  tool: ToolId,
+  profile: string,
+  mask: boolean,
+): Promise<string> {
+  return invoke<string>('export_profile', { tool, profile, mask });
+}
+
+/**
+ * 导出去除所有密钥字段的 Profile 配置（分享给他人，导入后自行填写 API Key）
+ */
+export async function exportConfigSanitized(tool: ToolId, profile: string): Promise<string> {
+  return invoke<string>('export_config_sanitized', { tool, profile });
+}
+
+/**
+ * 检查各工具当前配置是否指向同一供应商，返回不一致的工具
+ */
+export async function checkConfigConsistency(): Promise<Inconsistency[]> {
+  return invoke<Inconsistency[]>('check_config_consistency');
+}
+
+/**
+ * 从导出的 JSON 文本导入 Profile，返回最终保存的 Profile 名称
+ */
+export async function importProfile(
+  tool: ToolId,
+  data: string,
+  newName?: string,
+): Promise<string> {
+  return invoke<string>('import_profile', { tool, data, newName: newName ?? null });
+}
+
+/**
+ * 获取最近的配置变更审计记录（按时间倒序）
+ */
+export async function getAuditLog(limit?: number): Promise<AuditEntry[]> {
+  return invoke<AuditEntry[]>('get_audit_log', { limit: limit ?? null });
+}
+
 /**
  * 从 Profile 更新代理配置（不激活 Profile）
  */
@@ -115,7 +180,7 @@ export async function pmSaveAmpSelection(input: {
 // ==================== 菜单管理 ====================
 
 /**
- * 刷新应用菜单栏（仅 macOS）
+ * 刷新应用菜单栏（macOS）或系统托盘菜单（其他平台）
  */
 export async function refreshAppMenu(): Promise<void> {
   return invoke<void>('refresh_app_menu');
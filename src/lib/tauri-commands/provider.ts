@@ -2,7 +2,14 @@
 // 负责供应商的 CRUD、验证
 
 import { invoke } from '@tauri-apps/api/core';
-import type { Provider, _ProviderFormData, ProviderValidationResult, ApiInfo } from './types';
+import type {
+  Provider,
+  _ProviderFormData,
+  ProviderValidationResult,
+  ApiInfo,
+  CheckinResponse,
+  CheckinRecord,
+} from './types';
 
 /**
  * 列出所有供应商
@@ -32,6 +39,47 @@ export async function deleteProvider(id: string): Promise<void> {
   return invoke<void>('delete_provider', { id });
 }
 
+/**
+ * 获取自动签到全局总开关状态
+ */
+export async function getCheckinEnabled(): Promise<boolean> {
+  return invoke<boolean>('get_checkin_enabled');
+}
+
+/**
+ * 设置自动签到全局总开关（优先于各供应商的签到开关）
+ */
+export async function setCheckinEnabled(enabled: boolean): Promise<void> {
+  return invoke<void>('set_checkin_enabled', { enabled });
+}
+
+/**
+ * 批量切换所有供应商的签到开关
+ * @returns 受影响的供应商数量
+ */
+export async function setCheckinEnabledAll(enabled: boolean): Promise<number> {
+  return invoke<number>('set_checkin_enabled_all', { enabled });
+}
+
+/**
+ * 立即为指定供应商执行一次签到（需已启用签到，与自动签到互斥），结果写回签到统计
+ */
+export async function checkinNow(providerId: string): Promise<CheckinResponse> {
+  return invoke<CheckinResponse>('checkin_now', { providerId });
+}
+
+/**
+ * 分页查询签到流水（按时间倒序）
+ * @param providerId 仅查询指定供应商（缺省查询全部）
+ */
+export async function getCheckinHistory(
+  providerId: string | null,
+  limit: number,
+  offset = 0,
+): Promise<CheckinRecord[]> {
+  return invoke<CheckinRecord[]>('get_checkin_history', { providerId, limit, offset });
+}
+
 /**
  * 验证供应商配置（检查 API 连通性，获取用户名）
  */
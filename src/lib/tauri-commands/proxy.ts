@@ -2,7 +2,14 @@
 // 负责透明代理的启动、停止、状态查询和配置管理
 
 import { invoke } from '@tauri-apps/api/core';
-import type { AllProxyStatus, ToolProxyConfig, ToolId } from './types';
+import type {
+  AllProxyStatus,
+  ProxyHealthCheck,
+  ProxyRequestSummary,
+  ProxyStatus,
+  ToolProxyConfig,
+  ToolId,
+} from './types';
 
 // ==================== 多工具透明代理 API（新架构）====================
 
@@ -30,6 +37,13 @@ export async function getAllProxyStatus(): Promise<AllProxyStatus> {
   return await invoke<AllProxyStatus>('get_all_proxy_status');
 }
 
+/**
+ * 获取各工具代理的运行状态、端口与监听模式（未运行的工具取已保存配置）
+ */
+export async function getProxyStatus(): Promise<ProxyStatus[]> {
+  return await invoke<ProxyStatus[]>('get_proxy_status');
+}
+
 /**
  * 获取指定工具的代理配置
  */
@@ -37,6 +51,23 @@ export async function getProxyConfig(toolId: ToolId): Promise<ToolProxyConfig |
   return await invoke<ToolProxyConfig | null>('get_proxy_config', { toolId });
 }
 
+/**
+ * 获取运行中代理实例的当前配置快照（含热更新后的值，密钥已脱敏）
+ */
+export async function getRuntimeProxyConfig(tool: ToolId): Promise<ToolProxyConfig> {
+  return await invoke<ToolProxyConfig>('get_runtime_proxy_config', { tool });
+}
+
+/**
+ * 获取代理最近的请求摘要（最新的在前，默认 50 条，代理未运行时报错）
+ */
+export async function getProxyRecentRequests(
+  toolId: ToolId,
+  limit?: number,
+): Promise<ProxyRequestSummary[]> {
+  return await invoke<ProxyRequestSummary[]>('get_proxy_recent_requests', { toolId, limit });
+}
+
 /**
  * 更新指定工具的代理配置
  */
@@ -44,6 +75,13 @@ export async function updateProxyConfig(toolId: ToolId, config: ToolProxyConfig)
   return await invoke<void>('update_proxy_config', { toolId, config });
 }
 
+/**
+ * 检查指定工具代理配置的上游连通性（10 秒超时），并根据 401/403 判断 API Key 是否有效
+ */
+export async function proxyHealthCheck(toolId: ToolId): Promise<ProxyHealthCheck> {
+  return await invoke<ProxyHealthCheck>('proxy_health_check', { toolId });
+}
+
 /**
  * 获取所有工具的代理配置
  */
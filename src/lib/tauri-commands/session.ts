@@ -22,6 +22,31 @@ export async function getSessionList(
   });
 }
 
+/**
+ * 分页查询会话（按最后活跃时间降序）
+ * @param toolId - 工具 ID，为 null 时返回所有工具的会话
+ * @param page - 页码（从 1 开始）
+ * @param pageSize - 每页数量
+ */
+export async function listSessions(
+  toolId: string | null,
+  page: number,
+  pageSize: number,
+): Promise<SessionListResponse> {
+  return await invoke<SessionListResponse>('list_sessions', {
+    toolId,
+    page,
+    pageSize,
+  });
+}
+
+/**
+ * 获取未过期的活跃会话数量（调试用）
+ */
+export async function getActiveSessionCount(): Promise<number> {
+  return await invoke<number>('get_active_session_count');
+}
+
 /**
  * 删除单个会话
  * @param sessionId - 完整的会话 ID
@@ -3,9 +3,14 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  BudgetProgress,
   SessionStats,
+  TokenLog,
   TokenStatsQuery,
   TokenLogsPage,
+  ExportFormat,
+  ImportFormat,
+  ImportSummary,
   TokenStatsConfig,
   DatabaseSummary,
 } from '@/types/token-stats';
@@ -34,6 +39,54 @@ export async function queryTokenLogs(queryParams: TokenStatsQuery): Promise<Toke
   });
 }
 
+/**
+ * 查询工具最近的失败请求日志（按时间倒序）
+ * @param tool - 工具类型
+ * @param limit - 返回条数（默认 50）
+ * @returns 失败请求日志（含 error_type / error_detail / 时间）
+ */
+export async function queryErrors(tool: string, limit?: number): Promise<TokenLog[]> {
+  return await invoke<TokenLog[]>('query_errors', { tool, limit });
+}
+
+/**
+ * 获取本月成本目标进度（目标在全局配置 budget.monthly_target 中设置）
+ * @returns 已花费、目标、剩余及按当前速率预计超出目标的时间
+ */
+export async function getBudgetProgress(): Promise<BudgetProgress> {
+  return await invoke<BudgetProgress>('get_budget_progress');
+}
+
+/**
+ * 导出 Token 日志到指定文件（导出全部匹配记录，忽略分页参数）
+ * @param query - 筛选条件（工具类型、配置、时间范围等）
+ * @param format - 导出格式（csv / jsonl）
+ * @param path - 用户选择的保存路径
+ */
+export async function exportTokenLogs(
+  query: TokenStatsQuery,
+  format: ExportFormat,
+  path: string,
+): Promise<void> {
+  return await invoke<void>('export_token_logs', {
+    query,
+    format,
+    path,
+  });
+}
+
+/**
+ * 从其他用量统计工具导入历史日志（按 message_id / 时间去重）
+ * @param format - 数据格式（csv / json，json 支持数组与 JSON Lines）
+ * @param data - 文件内容
+ */
+export async function importTokenLogs(
+  format: ImportFormat,
+  data: string,
+): Promise<ImportSummary> {
+  return await invoke<ImportSummary>('import_token_logs', { format, data });
+}
+
 /**
  * 手动清理旧日志
  * @param retentionDays - 保留天数（可选，未提供则使用配置）
@@ -47,6 +100,43 @@ export async function cleanupTokenLogs(retentionDays?: number, maxCount?: number
   });
 }
 
+/**
+ * 删除指定时间范围内的日志（含两端点）
+ * @param startTs - 开始时间戳（毫秒）
+ * @param endTs - 结束时间戳（毫秒），不能早于开始时间
+ * @param toolType - 仅删除指定工具的日志（可选）
+ * @returns 删除的日志条数
+ */
+export async function deleteTokenLogsRange(
+  startTs: number,
+  endTs: number,
+  toolType?: string,
+): Promise<number> {
+  return await invoke<number>('delete_token_logs_range', {
+    startTs,
+    endTs,
+    toolType: toolType ?? null,
+  });
+}
+
+/**
+ * 按当前（或指定）定价模板重算匹配日志的成本
+ *
+ * 仅更新价格与总成本，Token 数不变；失败请求的日志不参与重算
+ * @param query - 日志筛选条件（忽略分页参数）
+ * @param templateId - 使用的定价模板 ID（可选，默认沿用日志记录时的模板）
+ * @returns 重算的日志条数
+ */
+export async function recalculateCosts(
+  query: TokenStatsQuery,
+  templateId?: string,
+): Promise<number> {
+  return await invoke<number>('recalculate_costs', {
+    query,
+    templateId: templateId ?? null,
+  });
+}
+
 /**
  * 获取数据库统计摘要
  * @returns 数据库摘要信息（总日志数、最早/最新时间戳）
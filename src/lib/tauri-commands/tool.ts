@@ -37,9 +37,9 @@ export async function checkNodeEnvironment(): Promise<NodeEnvironment> {
 }
 
 /**
- * 安装工具
+ * 安装工具（安装输出通过 `install-progress` 事件逐行推送，载荷见 InstallProgress）
  * @param tool - 工具 ID
- * @param method - 安装方法（npm/brew/official）
+ * @param method - 安装方法（npm/pnpm/yarn/bun/brew/official）
  * @param force - 是否强制安装
  */
 export async function installTool(
@@ -50,6 +50,34 @@ export async function installTool(
   return await invoke<InstallResult>('install_tool', { tool, method, force });
 }
 
+/**
+ * 检测系统中可用的 Node 包管理器，前端只展示实际可用的安装方式
+ * @returns 可用的包管理器（npm/pnpm/yarn/bun）
+ */
+export async function detectPackageManagers(): Promise<string[]> {
+  return await invoke<string[]>('detect_package_managers');
+}
+
+/**
+ * 卸载工具（仅支持 npm/pnpm/yarn/bun 安装方式，其他方式会返回手动卸载提示）
+ * 卸载前自动备份生效配置到 ~/.duckcoding/backups（备份失败则中止），路径见 backup_path；
+ * 成功后会删除工具的生效配置文件（Profile 备份保留），output 列出备份目录与被删除的文件
+ * @param tool - 工具 ID
+ * @param method - 安装方法（npm/pnpm/yarn/bun/brew/official/other）
+ */
+export async function uninstallTool(tool: string, method: string): Promise<InstallResult> {
+  return await invoke<InstallResult>('uninstall_tool', { tool, method });
+}
+
+/**
+ * 卸载工具前备份其全部配置到 ~/.duckcoding/backups
+ * @param tool - 工具 ID
+ * @returns 备份目录路径（没有配置文件时为 null）
+ */
+export async function backupToolConfigBeforeUninstall(tool: string): Promise<string | null> {
+  return await invoke<string | null>('backup_tool_config_before_uninstall', { tool });
+}
+
 /**
  * 检查工具更新（旧版本）
  * @deprecated 请使用 checkUpdateForInstance
@@ -156,6 +184,18 @@ export async function deleteToolInstance(instanceId: string): Promise<void> {
   return await invoke<void>('delete_tool_instance', { instanceId });
 }
 
+/**
+ * 设置工具实例的自定义环境变量（执行 --version / 更新命令时注入）
+ * @param instanceId - 实例ID
+ * @param env - 环境变量（变量名 -> 值，不允许包含 PATH）
+ */
+export async function setToolInstanceEnv(
+  instanceId: string,
+  env: Record<string, string>,
+): Promise<ToolInstance> {
+  return await invoke<ToolInstance>('set_tool_instance_env', { instanceId, env });
+}
+
 /**
  * 验证用户指定的工具路径是否有效
  * @param toolId - 工具ID
@@ -206,6 +246,15 @@ export async function addManualToolInstance(
   });
 }
 
+/**
+ * 扫描系统已安装的工具并批量导入为实例（已登记的路径会跳过）
+ * @param baseId - 工具ID
+ * @returns 本次新建的实例列表
+ */
+export async function scanAndImportInstances(baseId: string): Promise<ToolInstance[]> {
+  return await invoke<ToolInstance[]>('scan_and_import_instances', { baseId });
+}
+
 /**
  * 检测单个工具但不保存（仅用于预览）
  * @param toolId - 工具ID
@@ -2,6 +2,8 @@
 // 集中管理所有 Tauri 命令相关的类型定义，避免循环依赖
 
 import type { SSHConfig } from '@/types/tool-management';
+import type { HealthCheckConfig } from '@/types/health';
+import type { BalanceResult } from '@/pages/BalancePage/types';
 import type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId } from '@/types/profile';
 import type {
   Provider,
@@ -9,6 +11,8 @@ import type {
   _ProviderFormData,
   ProviderValidationResult,
   ApiInfo,
+  CheckinResponse,
+  CheckinRecord,
 } from '@/types/provider';
 
 // 重新导出 Profile 相关类型供其他模块使用
@@ -18,7 +22,15 @@ export type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId };
 export type { SSHConfig };
 
 // 重新导出供应商管理类型
-export type { Provider, ProviderStore, _ProviderFormData, ProviderValidationResult, ApiInfo };
+export type {
+  Provider,
+  ProviderStore,
+  _ProviderFormData,
+  ProviderValidationResult,
+  ApiInfo,
+  CheckinResponse,
+  CheckinRecord,
+};
 
 export interface ToolStatus {
   mirrorIsStale: boolean;
@@ -35,6 +47,13 @@ export interface InstallResult {
   success: boolean;
   message: string;
   output: string;
+  backup_path?: string; // 卸载前的配置备份目录（仅卸载时返回）
+}
+
+// 安装进度（`install-progress` 事件载荷，每行安装命令输出一条）
+export interface InstallProgress {
+  tool: string;
+  line: string;
 }
 
 export interface UpdateResult {
@@ -79,6 +98,43 @@ export interface GlobalConfig {
   external_poll_interval_ms?: number;
   // 单实例模式开关（默认 true，仅生产环境生效）
   single_instance_enabled?: boolean;
+  // 工具健康巡检配置
+  health_check?: HealthCheckConfig;
+  // 安装包下载缓存配置
+  download_cache?: DownloadCacheConfig;
+  // 用量异常检测配置
+  usage_anomaly?: UsageAnomalyConfig;
+  // 成本目标配置
+  budget?: BudgetConfig;
+  // 代理会话过期时间（小时，默认 24）
+  session_ttl_hours?: number;
+  // 关闭主窗口时的行为（默认 minimize）
+  close_behavior?: CloseBehavior;
+}
+
+// 成本目标配置
+export interface BudgetConfig {
+  monthly_target: number | null; // 月度成本目标（USD）
+}
+
+// 用量异常检测配置
+export interface UsageAnomalyConfig {
+  enabled: boolean;
+  multiplier: number; // 超过基线的倍数阈值
+  min_tokens: number; // 最低告警用量
+}
+
+// 安装包下载缓存配置
+export interface DownloadCacheConfig {
+  enabled: boolean;
+  max_size_mb: number;
+}
+
+// 安装包缓存占用
+export interface DownloadCacheUsage {
+  entries: number;
+  total_bytes: number;
+  max_bytes: number;
 }
 
 export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
@@ -166,6 +222,9 @@ export interface PackageFormatInfo {
 
 export type CloseAction = 'minimize' | 'quit';
 
+// 关闭主窗口时的行为：最小化到托盘 / 直接退出 / 每次询问
+export type CloseBehavior = 'minimize' | 'exit' | 'ask';
+
 export interface JsonObject {
   [key: string]: JsonValue;
 }
@@ -174,9 +233,16 @@ export type JsonValue = string | number | boolean | null | JsonObject | JsonValu
 
 export type JsonSchema = Record<string, unknown>;
 
+// Codex 认证方式：API Key / ChatGPT 登录
+export type CodexAuthMode = 'apikey' | 'chatgpt';
+
 export interface CodexSettingsPayload {
   config: JsonObject;
   authToken: string | null;
+  // 当前生效的认证方式（无任何凭据时为 null）
+  authMode?: CodexAuthMode | null;
+  // 是否已使用 ChatGPT 登录（auth.json 中保存了 tokens）
+  chatgptLoggedIn?: boolean;
 }
 
 export interface GeminiEnvConfig {
@@ -224,6 +290,66 @@ export interface ToolProxyConfig {
   session_endpoint_config_enabled: boolean; // 工具级：是否允许会话自定义端点
   auto_start: boolean; // 应用启动时自动运行代理（默认关闭）
   tavily_api_key?: string | null; // Tavily API Key（用于本地搜索，可选）
+  danger_accept_invalid_certs?: boolean; // 跳过上游 TLS 证书验证（自签名证书，存在安全风险）
+  max_concurrent_requests?: number | null; // 最大并发请求数（缺省不限制）
+  session_priorities?: Record<string, number>; // 会话标签/显示 ID -> 优先级（越大越优先）
+  max_retries?: number; // 上游失败最大重试次数（默认 2，仅连接错误与 502/503/504）
+  retry_backoff_ms?: number; // 重试基础退避间隔毫秒（默认 300，指数递增）
+  fallback_response_enabled?: boolean; // 上游完全不可用时返回降级响应（默认关闭）
+  fallback_message?: string | null; // 降级响应提示文本（缺省使用默认提示）
+  max_body_bytes?: number; // 请求体/非流式响应体大小上限（字节，默认 50MB）
+  default_max_tokens?: number | null; // 请求缺少 max_tokens 时注入的默认值（缺省不注入）
+  openai_compat_enabled?: boolean; // 上游为 OpenAI 兼容接口时转换 Anthropic 请求/响应格式（仅 Claude Code）
+  response_filters?: ContentFilterRule[]; // 流式响应内容过滤规则（按顺序对文本 delta 做正则替换）
+  header_blocklist?: string[]; // 额外剥离的客户端 header（如 cookie，大小写不敏感）
+  header_passthrough_override?: string[] | null; // 设置后仅透传列出的客户端 header（白名单模式）
+  model_daily_limits?: Record<string, number>; // 按模型的每日调用次数上限（超限拒绝该模型请求，跨天重置）
+  upstream_timeout_secs?: number; // 上游请求超时（秒，默认 60，非流式超时返回 504）
+  stream_idle_timeout_secs?: number; // SSE 流空闲超时（秒，默认 300，超时后结束流并记录 upstream_error）
+  pool_max_idle_per_host?: number | null; // 上游连接池每个主机的最大空闲连接数（未设置表示不限制）
+  pool_idle_timeout_secs?: number; // 上游连接池空闲连接保留时长（秒，默认 90）
+  allow_count_tokens?: boolean; // 转发 count_tokens 请求到上游（默认拦截并返回 403）
+  upstreams?: Upstream[]; // 备用上游（主上游连接失败或返回 5xx 时按顺序故障转移）
+  model_downgrade_enabled?: boolean; // 上游过载（529）时按映射降级模型重试一次（默认关闭）
+  model_downgrades?: Record<string, string>; // 模型降级映射（原模型 -> 降级模型，如 opus -> sonnet）
+  slow_request_threshold_ms?: number | null; // 慢请求阈值（毫秒，超过时按采样率保存调试样本到 debug_captures，默认关闭）
+  slow_capture_sample_rate?: number; // 慢请求采样率（0-1，默认 1）
+  request_compression_enabled?: boolean; // 超大请求体 gzip 压缩后发往上游（仅上游声明支持时生效，默认关闭）
+  request_compression_min_bytes?: number; // 触发请求体压缩的最小大小（字节，默认 512KB）
+  status_code_mappings?: Record<string, number>; // 响应状态码映射（上游状态码 -> 返回客户端的状态码，如非标准 4xx -> 429）
+}
+
+// 上游探测结果
+export interface UpstreamHealth {
+  host: string | null;
+  reachable: boolean; // 是否收到 HTTP 响应
+  healthy: boolean; // 可达且未返回 5xx
+  status_code?: number;
+  latency_ms: number;
+  error?: string;
+  checked_at: number; // Unix 时间戳（毫秒）
+  cached: boolean;
+}
+
+// 代理启动后的上游状态（`proxy-upstream-status` 事件载荷）
+export interface ProxyUpstreamStatus {
+  tool_id: string;
+  port: number;
+  upstream: UpstreamHealth;
+  message: string;
+}
+
+// 备用上游（name 为空时日志按上游 host 记录）
+export interface Upstream {
+  name: string;
+  base_url: string;
+  api_key: string;
+}
+
+// 内容过滤规则（正则替换，replacement 支持 $1 等捕获组引用）
+export interface ContentFilterRule {
+  pattern: string;
+  replacement: string;
 }
 
 export interface TransparentProxyStatus {
@@ -231,6 +357,30 @@ export interface TransparentProxyStatus {
   port: number;
 }
 
+// 代理上游检查结果（上游探测结果附加 API Key 有效性）
+export interface ProxyHealthCheck extends UpstreamHealth {
+  tool_id: string;
+  api_key_valid?: boolean; // 401/403 为 false，2xx 为 true，其他状态无法判断
+}
+
+// 代理运行状态（含端口与监听模式）
+export interface ProxyStatus {
+  tool_id: string;
+  running: boolean;
+  port: number;
+  bind_mode: 'public' | 'local'; // public：0.0.0.0，local：127.0.0.1
+}
+
+// 代理请求摘要（仅元数据，每个代理实例保留最近 200 条）
+export interface ProxyRequestSummary {
+  timestamp: number; // 请求时间（毫秒）
+  method: string;
+  path: string; // 不含查询参数
+  status: number; // 返回给客户端的状态码
+  duration_ms: number; // 收到请求到返回响应头的耗时
+  upstream_url?: string; // 实际命中的上游 URL（未转发到上游时缺省）
+}
+
 // 多工具代理状态映射
 export type AllProxyStatus = Record<string, TransparentProxyStatus>;
 
@@ -280,6 +430,7 @@ export interface InstallerCandidate {
 export interface BalanceStore {
   version: number;
   configs: BalanceConfigBackend[];
+  last_alerted?: Record<string, boolean>;
 }
 
 // 后端 BalanceConfig 格式（snake_case）
@@ -294,6 +445,7 @@ export interface BalanceConfigBackend {
   timeout_ms?: number;
   save_api_key: boolean;
   api_key?: string;
+  alert_threshold?: number;
   created_at: number;
   updated_at: number;
 }
@@ -301,6 +453,22 @@ export interface BalanceConfigBackend {
 // 前端 BalanceConfig 格式（camelCase）- 从 BalancePage 导入
 export type { BalanceConfig } from '@/pages/BalancePage/types';
 
+// 后台余额查询结果（result 为前端 BalanceResult 格式）
+export interface BalanceSnapshot {
+  config_id: string;
+  result: BalanceResult | null;
+  error: string | null;
+  fetched_at: number;
+}
+
+// 低余额告警事件载荷（balance-low-alert）
+export interface BalanceLowAlert {
+  config_id: string;
+  name: string;
+  remaining: number;
+  threshold: number;
+}
+
 // AMP 用户信息
 export interface AmpUserInfo {
   id: string;
@@ -308,3 +476,26 @@ export interface AmpUserInfo {
   name: string | null;
   username: string | null;
 }
+
+// 待执行的迁移任务
+export interface MigrationTask {
+  id: string;
+  name: string;
+  target_version: string;
+  items: string[]; // 检测到的待迁移项（如遗留文件路径）
+}
+
+// 单个迁移的执行结果
+export interface MigrationResult {
+  migration_id: string;
+  success: boolean;
+  message: string;
+  records_migrated: number;
+  duration_secs: number;
+}
+
+// 迁移任务执行报告
+export interface MigrationReport {
+  result: MigrationResult;
+  remaining: string[]; // 执行后仍未迁移的项（为空表示已全部迁移）
+}
@@ -2,7 +2,7 @@
 // 负责应用程序的自动更新检查、下载、安装和回滚
 
 import { invoke } from '@tauri-apps/api/core';
-import type { UpdateInfo } from './types';
+import type { DownloadCacheUsage, UpdateInfo } from './types';
 
 /**
  * 检查应用更新
@@ -42,6 +42,21 @@ export async function rollbackAppUpdate(): Promise<void> {
   return await invoke<void>('rollback_app_update');
 }
 
+/**
+ * 获取安装包缓存占用（未启用缓存时返回 null）
+ */
+export async function getDownloadCacheUsage(): Promise<DownloadCacheUsage | null> {
+  return await invoke<DownloadCacheUsage | null>('get_download_cache_usage');
+}
+
+/**
+ * 清空安装包缓存
+ * @returns 释放的字节数
+ */
+export async function clearDownloadCache(): Promise<number> {
+  return await invoke<number>('clear_download_cache');
+}
+
 /**
  * 获取当前应用版本
  */
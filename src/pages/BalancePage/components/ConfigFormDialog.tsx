@@ -60,6 +60,7 @@ export function ConfigFormDialog({ open, initial, onClose, onSubmit }: ConfigFor
         extractorScript: initial.extractorScript,
         intervalSec: initial.intervalSec ?? 0,
         timeoutMs: initial.timeoutMs ?? 30000,
+        alertThreshold: initial.alertThreshold,
         apiKey: initial.apiKey ?? '', // 编辑时加载已保存的 API Key
         saveApiKey: initial.saveApiKey ?? true, // 编辑时加载保存状态，默认 true
       });
@@ -233,6 +234,23 @@ export function ConfigFormDialog({ open, initial, onClose, onSubmit }: ConfigFor
                 }
               />
             </div>
+            <div className="space-y-2">
+              <Label htmlFor="alertThreshold">低余额告警阈值</Label>
+              <Input
+                id="alertThreshold"
+                type="number"
+                min={0}
+                step="any"
+                value={values.alertThreshold ?? ''}
+                onChange={(e) =>
+                  setValues((v) => ({
+                    ...v,
+                    alertThreshold: e.target.value === '' ? undefined : Number(e.target.value),
+                  }))
+                }
+                placeholder="留空表示不告警"
+              />
+            </div>
           </div>
 
           <div className="space-y-3">
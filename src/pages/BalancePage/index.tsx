@@ -59,6 +59,7 @@ export function BalancePage() {
         extractorScript: values.extractorScript,
         intervalSec: values.intervalSec ?? 0,
         timeoutMs: values.timeoutMs,
+        alertThreshold: values.alertThreshold,
         saveApiKey: values.saveApiKey ?? true, // 默认勾选保存
         apiKey: values.saveApiKey && values.apiKey?.trim() ? values.apiKey.trim() : undefined, // 根据选项保存 API Key
         updatedAt: now,
@@ -81,6 +82,7 @@ export function BalancePage() {
         extractorScript: values.extractorScript,
         intervalSec: values.intervalSec ?? 0,
         timeoutMs: values.timeoutMs,
+        alertThreshold: values.alertThreshold,
         saveApiKey: values.saveApiKey ?? true, // 默认勾选保存
         apiKey: values.saveApiKey && values.apiKey?.trim() ? values.apiKey.trim() : undefined, // 根据选项保存 API Key
         createdAt: now,
@@ -9,6 +9,7 @@ export interface BalanceConfig {
   timeoutMs?: number; // 请求超时（毫秒）
   saveApiKey?: boolean; // 是否保存 API Key 到文件（新增）
   apiKey?: string; // API Key（可选，持久化时保存，新增）
+  alertThreshold?: number; // 低余额告警阈值（剩余额度低于该值时告警）
   updatedAt: number;
   createdAt: number;
 }
@@ -46,6 +47,7 @@ export interface BalanceFormValues {
   extractorScript: string;
   intervalSec?: number;
   timeoutMs?: number;
+  alertThreshold?: number; // 低余额告警阈值，留空表示不告警
   apiKey?: string; // 用于 Authorization header
   saveApiKey?: boolean; // 是否保存 API Key 到文件（新增）
 }
@@ -0,0 +1,39 @@
+// Dashboard 工具健康巡检 Hook
+
+import { useCallback, useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { getHealthReport, runHealthCheck } from '@/lib/tauri-commands';
+import type { HealthReport } from '@/types/health';
+
+export function useHealthReport() {
+  const [report, setReport] = useState<HealthReport | null>(null);
+  const [checking, setChecking] = useState(false);
+
+  const refresh = useCallback(async () => {
+    try {
+      setChecking(true);
+      setReport(await runHealthCheck());
+    } catch (error) {
+      console.error('执行健康巡检失败:', error);
+    } finally {
+      setChecking(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    getHealthReport()
+      .then(setReport)
+      .catch((error) => console.error('获取健康报告失败:', error));
+
+    // 后台巡检完成后推送最新报告
+    const unlisten = listen<HealthReport>('health-report', (event) => {
+      setReport(event.payload);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return { report, checking, refresh };
+}
@@ -8,6 +8,7 @@ import { UpdateCheckBanner } from './components/UpdateCheckBanner';
 import { ProviderTabs } from './components/ProviderTabs';
 import { useDashboard } from './hooks/useDashboard';
 import { useDashboardProviders } from './hooks/useDashboardProviders';
+import { useHealthReport } from './hooks/useHealthReport';
 import { getToolDisplayName } from '@/utils/constants';
 import { useToast } from '@/hooks/use-toast';
 import { useAppContext } from '@/hooks/useAppContext';
@@ -19,6 +20,13 @@ import {
   setSelectedProviderId as saveSelectedProviderId,
 } from '@/lib/tauri-commands';
 import type { UserQuotaResult, UsageStatsResult } from '@/lib/tauri-commands/types';
+import type { HealthLevel } from '@/types/health';
+
+const HEALTH_LEVEL_STYLES: Record<HealthLevel, { label: string; color: string }> = {
+  green: { label: '健康', color: 'text-green-500' },
+  yellow: { label: '需关注', color: 'text-amber-500' },
+  red: { label: '异常', color: 'text-red-500' },
+};
 
 export function DashboardPage() {
   const { toast } = useToast();
@@ -55,6 +63,10 @@ export function DashboardPage() {
     toolInstances,
   } = useDashboardProviders();
 
+  // 工具健康巡检报告
+  const { report: healthReport, refresh: refreshHealthReport } = useHealthReport();
+  const unhealthyItems = healthReport?.items.filter((item) => item.level !== 'green') ?? [];
+
   // 选中的供应商 ID（持久化到 dashboard.json）
   const [selectedProviderId, setSelectedProviderId] = useState<string | null>(null);
   const [providerIdLoaded, setProviderIdLoaded] = useState(false);
@@ -131,6 +143,7 @@ export function DashboardPage() {
     try {
       const newTools = await refreshAllToolVersions();
       updateTools(newTools);
+      refreshHealthReport();
       toast({
         title: '刷新完成',
         description: '工具版本号已更新',
@@ -329,11 +342,24 @@ export function DashboardPage() {
             <Card>
               <CardHeader className="flex flex-row items-center justify-between space-y-0 pb-2">
                 <CardTitle className="text-sm font-medium">系统状态</CardTitle>
-                <CheckCircle2 className="h-4 w-4 text-green-500" />
+                <CheckCircle2
+                  className={`h-4 w-4 ${HEALTH_LEVEL_STYLES[healthReport?.overall ?? 'green'].color}`}
+                />
               </CardHeader>
               <CardContent>
-                <div className="text-2xl font-bold">运行中</div>
-                <p className="text-xs text-muted-foreground">所有服务正常运行</p>
+                <div className="text-2xl font-bold">
+                  {healthReport ? HEALTH_LEVEL_STYLES[healthReport.overall].label : '-'}
+                </div>
+                <p
+                  className="text-xs text-muted-foreground truncate"
+                  title={unhealthyItems.map((item) => item.message).join('\n')}
+                >
+                  {!healthReport
+                    ? '正在巡检...'
+                    : unhealthyItems.length === 0
+                      ? '所有服务正常运行'
+                      : `${unhealthyItems.length} 项需要关注：${unhealthyItems[0].message}`}
+                </p>
               </CardContent>
             </Card>
           </div>
@@ -30,6 +30,7 @@ import {
   Clock,
 } from 'lucide-react';
 import type { Provider } from '@/lib/tauri-commands';
+import type { CheckinConfig } from '@/types/provider';
 import { performCheckin, getCheckinStatus } from '@/services/checkin';
 import { useToast } from '@/hooks/use-toast';
 
@@ -41,9 +42,17 @@ interface CheckinDialogProps {
 }
 
 /** 格式化时间范围描述 */
-function formatRangeDesc(start: number, end: number): string {
-  if (start >= end) return '全天随机时间自动签到';
-  return `每天 ${start.toString().padStart(2, '0')}:00 ~ ${end.toString().padStart(2, '0')}:00 随机时间自动签到`;
+function formatRangeDesc(start: number, end: number, perDay = 1): string {
+  const times = perDay > 1 ? ` ${perDay} 次` : '';
+  if (start >= end) return `全天随机时间自动签到${times}`;
+  return `每天 ${start.toString().padStart(2, '0')}:00 ~ ${end.toString().padStart(2, '0')}:00 随机时间自动签到${times}`;
+}
+
+/** 当天已签到次数（上次签到不在今天时归零） */
+function todayCheckinCount(config?: CheckinConfig): number {
+  if (!config?.last_checkin_at) return 0;
+  const last = new Date(config.last_checkin_at * 1000);
+  return last.toDateString() === new Date().toDateString() ? config.today_checkin_count || 0 : 0;
 }
 
 export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: CheckinDialogProps) {
@@ -60,6 +69,9 @@ export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: Checki
   const [checkinHourEnd, setCheckinHourEnd] = useState(
     provider.checkin_config?.checkin_hour_end ?? 0,
   );
+  const [checkinsPerDay, setCheckinsPerDay] = useState(
+    provider.checkin_config?.checkins_per_day ?? 1,
+  );
 
   // provider 变化时重置状态
   useEffect(() => {
@@ -67,6 +79,7 @@ export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: Checki
     setAutoCheckinEnabled(provider.checkin_config?.enabled || false);
     setCheckinHourStart(provider.checkin_config?.checkin_hour_start ?? 0);
     setCheckinHourEnd(provider.checkin_config?.checkin_hour_end ?? 0);
+    setCheckinsPerDay(provider.checkin_config?.checkins_per_day ?? 1);
   }, [provider]);
 
   const loadCheckinStatus = useCallback(async () => {
@@ -114,6 +127,7 @@ export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: Checki
             enabled: autoCheckinEnabled,
             endpoint: '/api/user/checkin',
             last_checkin_at: Math.floor(Date.now() / 1000),
+            today_checkin_count: todayCheckinCount(provider.checkin_config) + 1,
             last_checkin_status: 'success' as const,
             last_checkin_message: result.message,
             total_checkins: (provider.checkin_config?.total_checkins || 0) + 1,
@@ -152,13 +166,16 @@ export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: Checki
         endpoint: '/api/user/checkin',
         checkin_hour_start: checkinHourStart,
         checkin_hour_end: checkinHourEnd,
+        checkins_per_day: checkinsPerDay,
       },
     };
     onUpdate(updatedProvider);
 
     toast({
       title: enabled ? '已启用自动签到' : '已关闭自动签到',
-      description: enabled ? formatRangeDesc(checkinHourStart, checkinHourEnd) : '不再自动签到',
+      description: enabled
+        ? formatRangeDesc(checkinHourStart, checkinHourEnd, checkinsPerDay)
+        : '不再自动签到',
     });
   };
 
@@ -181,13 +198,42 @@ export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: Checki
           checkin_hour_end: newEnd,
           // 时间范围变了，清除已生成的计划让调度器重新生成
           next_checkin_at: undefined,
+          pending_checkin_times: [],
         },
       };
       onUpdate(updatedProvider);
 
       toast({
         title: '签到时间已更新',
-        description: formatRangeDesc(newStart, newEnd),
+        description: formatRangeDesc(newStart, newEnd, checkinsPerDay),
+      });
+    }
+  };
+
+  const handleCheckinsPerDayChange = (value: string) => {
+    const perDay = parseInt(value);
+    setCheckinsPerDay(perDay);
+
+    if (autoCheckinEnabled) {
+      const updatedProvider = {
+        ...provider,
+        checkin_config: {
+          ...provider.checkin_config,
+          enabled: autoCheckinEnabled,
+          endpoint: '/api/user/checkin',
+          checkin_hour_start: checkinHourStart,
+          checkin_hour_end: checkinHourEnd,
+          checkins_per_day: perDay,
+          // 次数变了，清除已生成的计划让调度器重新生成
+          next_checkin_at: undefined,
+          pending_checkin_times: [],
+        },
+      };
+      onUpdate(updatedProvider);
+
+      toast({
+        title: '签到次数已更新',
+        description: formatRangeDesc(checkinHourStart, checkinHourEnd, perDay),
       });
     }
   };
@@ -278,6 +324,25 @@ export function CheckinDialog({ open, onOpenChange, provider, onUpdate }: Checki
                     </SelectContent>
                   </Select>
                 </div>
+                <div className="flex items-center gap-2">
+                  <Label className="text-xs text-muted-foreground">每天签到次数:</Label>
+                  <Select
+                    value={checkinsPerDay.toString()}
+                    onValueChange={handleCheckinsPerDayChange}
+                    disabled={controlsDisabled}
+                  >
+                    <SelectTrigger className="h-8 w-24">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {[1, 2, 3, 4].map((n) => (
+                        <SelectItem key={n} value={n.toString()}>
+                          {n} 次
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                </div>
               </div>
             )}
           </div>
@@ -0,0 +1,73 @@
+/**
+ * 结束原因分布组件
+ * 展示请求结束原因占比，帮助发现频繁的 max_tokens 截断
+ */
+import React from 'react';
+import type { StopReasonStat } from '@/types/analytics';
+
+/**
+ * 结束原因显示名称
+ */
+const STOP_REASON_LABELS: Record<string, string> = {
+  end_turn: '正常结束',
+  max_tokens: '达到 max_tokens 截断',
+  tool_use: '工具调用',
+  stop_sequence: '命中停止序列',
+  pause_turn: '暂停',
+  refusal: '拒绝回答',
+  unknown: '未记录',
+};
+
+/**
+ * 截断告警阈值（百分比）
+ */
+const MAX_TOKENS_WARN_PERCENTAGE = 10;
+
+export interface StopReasonDistributionProps {
+  /** 结束原因分布数据 */
+  stats: StopReasonStat[];
+}
+
+export const StopReasonDistribution: React.FC<StopReasonDistributionProps> = ({ stats }) => {
+  if (stats.length === 0) {
+    return null;
+  }
+
+  const maxTokens = stats.find((s) => s.stop_reason === 'max_tokens');
+  const truncationWarning = maxTokens && maxTokens.percentage >= MAX_TOKENS_WARN_PERCENTAGE;
+
+  return (
+    <div className="rounded-lg border bg-white p-6 shadow-sm dark:bg-gray-800 dark:border-gray-700">
+      <div className="mb-4 flex items-center justify-between">
+        <h3 className="text-sm font-medium text-gray-600 dark:text-gray-400">结束原因分布</h3>
+        {truncationWarning && (
+          <span className="text-xs text-amber-600 dark:text-amber-400">
+            {maxTokens.percentage.toFixed(1)}% 的请求因 max_tokens 被截断
+          </span>
+        )}
+      </div>
+      <div className="space-y-3">
+        {stats.map((stat) => (
+          <div key={stat.stop_reason}>
+            <div className="mb-1 flex justify-between text-sm">
+              <span className="text-gray-700 dark:text-gray-300">
+                {STOP_REASON_LABELS[stat.stop_reason] ?? stat.stop_reason}
+              </span>
+              <span className="text-gray-500 dark:text-gray-400">
+                {stat.request_count.toLocaleString('zh-CN')}（{stat.percentage.toFixed(1)}%）
+              </span>
+            </div>
+            <div className="h-2 rounded-full bg-gray-100 dark:bg-gray-700">
+              <div
+                className={`h-2 rounded-full ${
+                  stat.stop_reason === 'max_tokens' ? 'bg-amber-500' : 'bg-blue-500'
+                }`}
+                style={{ width: `${stat.percentage}%` }}
+              />
+            </div>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+};
@@ -17,14 +17,25 @@ import { useToast } from '@/hooks/use-toast';
 import { RealtimeStats } from '../TransparentProxyPage/components/RealtimeStats';
 import { LogsTable } from '../TransparentProxyPage/components/LogsTable';
 import { getTokenStatsSummary, getTokenStatsConfig } from '@/lib/tauri-commands';
-import { queryTokenTrends, queryCostSummary } from '@/lib/tauri-commands/analytics';
+import {
+  queryTokenTrends,
+  queryCostSummary,
+  queryStopReasonDistribution,
+} from '@/lib/tauri-commands/analytics';
 import { Dashboard } from './components/Dashboard';
+import { StopReasonDistribution } from './components/StopReasonDistribution';
 import { TrendsChart } from './components/TrendsChart';
 import { CustomTimeRangeDialog } from '@/components/dialogs/CustomTimeRangeDialog';
 import { useTimeRangeControl } from '@/hooks/useTimeRangeControl';
 import { GRANULARITY_LABELS } from '@/utils/time-range';
 import type { DatabaseSummary, TokenStatsConfig, ToolType } from '@/types/token-stats';
-import type { TrendDataPoint, CostSummary, TimeRange, TimeGranularity } from '@/types/analytics';
+import type {
+  TrendDataPoint,
+  CostSummary,
+  StopReasonStat,
+  TimeRange,
+  TimeGranularity,
+} from '@/types/analytics';
 
 interface TokenStatisticsPageProps {
   /** 会话ID（从导航传入，用于筛选日志） */
@@ -91,6 +102,7 @@ export default function TokenStatisticsPage({
   const [trendsData, setTrendsData] = useState<TrendDataPoint[]>([]);
   const [responseTimeTrends, setResponseTimeTrends] = useState<TrendDataPoint[]>([]); // 填充后的响应时间趋势数据
   const [costSummary, setCostSummary] = useState<CostSummary | null>(null);
+  const [stopReasons, setStopReasons] = useState<StopReasonStat[]>([]);
   const [analyticsLoading, setAnalyticsLoading] = useState(false);
 
   // 加载数据库摘要和配置
@@ -116,7 +128,7 @@ export default function TokenStatisticsPage({
     const loadAnalyticsData = async () => {
       setAnalyticsLoading(true);
       try {
-        const [trends, summary] = await Promise.all([
+        const [trends, summary, stopReasonStats] = await Promise.all([
           queryTokenTrends({
             start_time: timeControl.startTimeMs,
             end_time: timeControl.endTimeMs,
@@ -124,6 +136,11 @@ export default function TokenStatisticsPage({
             granularity: timeControl.granularity,
           }),
           queryCostSummary(timeControl.startTimeMs, timeControl.endTimeMs, toolType),
+          queryStopReasonDistribution({
+            start_time: timeControl.startTimeMs,
+            end_time: timeControl.endTimeMs,
+            tool_type: toolType,
+          }),
         ]);
 
         // 原始数据用于成本和 Token 趋势图
@@ -134,6 +151,7 @@ export default function TokenStatisticsPage({
         setResponseTimeTrends(filledTrends);
 
         setCostSummary(summary);
+        setStopReasons(stopReasonStats);
       } catch (error) {
         console.error('Failed to load analytics data:', error);
         toast({
@@ -281,6 +299,9 @@ export default function TokenStatisticsPage({
         {/* 仪表盘 - 关键指标 */}
         {costSummary && <Dashboard summary={costSummary} loading={analyticsLoading} />}
 
+        {/* 结束原因分布 */}
+        {!analyticsLoading && <StopReasonDistribution stats={stopReasons} />}
+
         {/* 趋势图表 */}
         {trendsData.length > 0 && (
           <>
@@ -84,6 +84,9 @@ export function ProxySettingsDialog({
     config?.session_endpoint_config_enabled ?? false,
   );
   const [autoStart, setAutoStart] = useState(config?.auto_start ?? false);
+  const [acceptInvalidCerts, setAcceptInvalidCerts] = useState(
+    config?.danger_accept_invalid_certs ?? false,
+  );
 
   // AMP Access Token 状态（仅 amp-code）
   const [ampAccessToken, setAmpAccessToken] = useState(config?.real_api_key ?? '');
@@ -101,6 +104,7 @@ export function ProxySettingsDialog({
       setAllowPublic(config.allow_public);
       setSessionEndpointEnabled(config.session_endpoint_config_enabled ?? false);
       setAutoStart(config.auto_start ?? false);
+      setAcceptInvalidCerts(config.danger_accept_invalid_certs ?? false);
       // AMP Access Token
       setAmpAccessToken(config.real_api_key ?? '');
       setAmpUserInfo(null);
@@ -202,6 +206,7 @@ export function ProxySettingsDialog({
         allow_public: allowPublic,
         session_endpoint_config_enabled: sessionEndpointEnabled,
         auto_start: autoStart,
+        danger_accept_invalid_certs: acceptInvalidCerts,
       };
       // AMP Access Token 需要一起保存（空值也需要保存以清除配置）
       if (toolId === 'amp-code') {
@@ -426,6 +431,21 @@ export function ProxySettingsDialog({
                 </div>
                 <Switch checked={autoStart} onCheckedChange={setAutoStart} disabled={isRunning} />
               </div>
+
+              {/* 跳过上游 TLS 证书验证 */}
+              <div className="flex items-center justify-between">
+                <div className="space-y-0.5">
+                  <Label>跳过上游证书验证</Label>
+                  <p className="text-xs text-destructive">
+                    仅用于自签名证书的私有上游，启用后存在中间人攻击风险
+                  </p>
+                </div>
+                <Switch
+                  checked={acceptInvalidCerts}
+                  onCheckedChange={setAcceptInvalidCerts}
+                  disabled={isRunning}
+                />
+              </div>
             </>
           )}
         </div>
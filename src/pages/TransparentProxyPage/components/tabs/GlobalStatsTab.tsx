@@ -119,6 +119,7 @@ const GRANULARITY_LABELS: Record<TimeGranularity, string> = {
   hour: '小时',
   twelve_hours: '12小时',
   day: '天',
+  week: '周',
 };
 
 /**
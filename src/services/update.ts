@@ -18,6 +18,8 @@ export interface UpdateUrls {
 
   // 通用版本
   universal?: string; // 跨平台通用版本
+
+  sha256?: Record<string, string>; // 安装包 SHA256（安装包 URL -> 校验和）
 }
 
 export interface UpdateInfo {
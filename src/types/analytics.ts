@@ -23,7 +23,8 @@ export type TimeGranularity =
   | 'thirty_minutes'
   | 'hour'
   | 'twelve_hours'
-  | 'day';
+  | 'day'
+  | 'week'; // 以 UTC 周一 00:00 为起点
 
 /**
  * 趋势查询参数
@@ -45,6 +46,11 @@ export interface TrendQuery {
   granularity: TimeGranularity;
 }
 
+/**
+ * 统计过滤条件（复用趋势查询参数，非趋势类统计无需时间粒度）
+ */
+export type StatsFilter = Omit<TrendQuery, 'granularity'>;
+
 /**
  * 趋势数据点
  */
@@ -75,6 +81,12 @@ export interface TrendDataPoint {
   error_count: number;
   /** 平均响应时间（毫秒） */
   avg_response_time: number | null;
+  /** 平均首字节延迟（毫秒，未记录 TTFB 的请求不参与平均） */
+  avg_ttfb?: number | null;
+  /** 请求体总字节数 */
+  request_bytes?: number;
+  /** 响应体总字节数 */
+  response_bytes?: number;
 }
 
 /**
@@ -127,3 +139,236 @@ export interface CostSummary {
     cost: number;
   }>;
 }
+
+/**
+ * 成本汇总分组方式（与后端 CostGroupBy 对应）
+ */
+export type CostGroupBy = 'model' | 'config' | 'session' | 'tool' | 'request_kind' | 'category';
+
+/**
+ * 分组成本汇总查询参数
+ */
+export interface CostSummaryQuery {
+  /** 开始时间戳（毫秒） */
+  start_time?: number;
+  /** 结束时间戳（毫秒） */
+  end_time?: number;
+  /** 工具类型过滤（可选） */
+  tool_type?: string;
+  /** 会话 ID 过滤（可选） */
+  session_id?: string;
+  /** 分组方式 */
+  group_by: CostGroupBy;
+}
+
+/**
+ * 单个分组的成本汇总
+ */
+export interface GroupedCostSummary {
+  /** 分组值（模型/配置/会话 ID/工具类型） */
+  group_name: string;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 请求总数 */
+  request_count: number;
+  /** 输入 Token 总数 */
+  input_tokens: number;
+  /** 输出 Token 总数 */
+  output_tokens: number;
+  /** 平均响应时间（毫秒） */
+  avg_response_time: number | null;
+  /** 平均首字节延迟（毫秒） */
+  avg_ttfb?: number | null;
+}
+
+/**
+ * 结束原因分布数据
+ */
+export interface StopReasonStat {
+  /** 结束原因（end_turn / max_tokens / tool_use / stop_sequence / unknown 等） */
+  stop_reason: string;
+  /** 请求数 */
+  request_count: number;
+  /** 占比（0-100） */
+  percentage: number;
+}
+
+/**
+ * 成功率趋势数据点
+ */
+export interface SuccessRatePoint {
+  /** 时间戳（毫秒） */
+  timestamp: number;
+  /** 请求总数 */
+  request_count: number;
+  /** 成功请求数 */
+  success_count: number;
+  /** 失败请求数 */
+  failed_count: number;
+  /** 成功率（0-100，无请求时为 null） */
+  success_rate: number | null;
+}
+
+/**
+ * 缓存收益（Prompt Caching ROI）
+ */
+export interface CacheRoi {
+  /** 缓存写入 Token 总数 */
+  cache_creation_tokens: number;
+  /** 缓存读取 Token 总数 */
+  cache_read_tokens: number;
+  /** 缓存写入总成本（USD） */
+  cache_write_cost: number;
+  /** 缓存读取实际成本（USD） */
+  cache_read_cost: number;
+  /** 缓存读取节省的成本（USD） */
+  cache_read_savings: number;
+  /** 净收益（USD，负数表示亏损） */
+  net_savings: number;
+  /** 收益率（0-100+，无写入成本时为 null） */
+  roi_percentage: number | null;
+}
+
+/**
+ * 单个上游的统计数据
+ */
+export interface UpstreamStat {
+  /** 上游标识（base_url 的 host[:port]，未记录时为 unknown） */
+  upstream: string;
+  /** 请求总数 */
+  request_count: number;
+  /** 成功请求数 */
+  success_count: number;
+  /** 失败请求数 */
+  failed_count: number;
+  /** 成功率（0-100） */
+  success_rate: number;
+  /** 输入 Token 总数 */
+  input_tokens: number;
+  /** 输出 Token 总数 */
+  output_tokens: number;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 平均响应时间（毫秒） */
+  avg_response_time: number | null;
+  /** 平均首字节延迟（毫秒） */
+  avg_ttfb?: number | null;
+}
+
+/**
+ * 单个客户端 IP 的用量（共享代理场景）
+ */
+export interface IpUsage {
+  /** 客户端 IP（未记录时为 unknown） */
+  client_ip: string;
+  /** 请求总数 */
+  request_count: number;
+  /** 成功请求数 */
+  success_count: number;
+  /** 输入 Token 总数 */
+  input_tokens: number;
+  /** 输出 Token 总数 */
+  output_tokens: number;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 最近一次请求时间（毫秒时间戳） */
+  last_request_at: number;
+}
+
+/**
+ * 单个模型的缓存节省
+ */
+export interface ModelSavings {
+  model: string;
+  /** 命中缓存读取的请求数 */
+  cached_request_count: number;
+  /** 缓存读取 Token 总数 */
+  cache_read_tokens: number;
+  /** 节省金额（USD） */
+  savings: number;
+}
+
+/**
+ * Prompt Caching 累计节省汇总
+ */
+export interface SavingsSummary {
+  /** 请求总数 */
+  request_count: number;
+  /** 命中缓存读取的请求数 */
+  cached_request_count: number;
+  /** 缓存读取 Token 总数 */
+  cache_read_tokens: number;
+  /** 实际花费（USD） */
+  actual_cost: number;
+  /** 若无缓存本应花费（USD） */
+  no_cache_cost: number;
+  /** 累计节省金额（USD） */
+  total_savings: number;
+  /** 节省比例（0-100，无花费时为 null） */
+  savings_percentage: number | null;
+  /** 按模型的节省（按节省金额降序） */
+  by_model: ModelSavings[];
+}
+
+/**
+ * 周报中单日的成本
+ */
+export interface WeeklyDailyCost {
+  /** 日期（YYYY-MM-DD，本地时区） */
+  date: string;
+  /** 当日起点（毫秒时间戳） */
+  start_time: number;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 请求数 */
+  request_count: number;
+}
+
+/**
+ * 周报中的模型成本
+ */
+export interface WeeklyModelCost {
+  model: string;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 请求数 */
+  request_count: number;
+}
+
+/**
+ * Token 统计周报（本地时区周一至周日）
+ */
+export interface WeeklyReport {
+  /** 本周起点（周一 00:00，毫秒时间戳） */
+  week_start: number;
+  /** 本周终点（下周一 00:00，毫秒时间戳，不含） */
+  week_end: number;
+  /** 周一至周日每日成本 */
+  daily_costs: WeeklyDailyCost[];
+  /** 成本最高的模型（按成本降序，最多 5 个） */
+  top_models: WeeklyModelCost[];
+  /** 本周总成本（USD） */
+  total_cost: number;
+  /** 本周总请求数 */
+  total_requests: number;
+  /** 上周总成本（USD） */
+  previous_total_cost: number;
+  /** 上周总请求数 */
+  previous_total_requests: number;
+  /** 成本环比变化百分比（上周无花费时为 null） */
+  cost_change_percent: number | null;
+  /** 请求数环比变化百分比（上周无请求时为 null） */
+  request_change_percent: number | null;
+}
+
+/**
+ * 自定义统计查询结果
+ */
+export interface StatsQueryResult {
+  /** 列名 */
+  columns: string[];
+  /** 行数据（与列名一一对应） */
+  rows: unknown[][];
+  /** 是否因超过行数上限被截断 */
+  truncated: boolean;
+}
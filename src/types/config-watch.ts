@@ -61,6 +61,18 @@ export interface ExternalConfigChange {
   is_sensitive: boolean;
 }
 
+/**
+ * 原生配置漂移事件（config-drift-detected）
+ */
+export interface ConfigDriftEvent {
+  /** 工具 ID */
+  tool_id: string;
+  /** 发生变更的配置文件路径 */
+  path: string;
+  /** 检测到变更的时间（ISO 8601） */
+  changed_at: string;
+}
+
 /**
  * 配置变更记录
  */
@@ -0,0 +1,41 @@
+/**
+ * 工具健康巡检类型定义
+ */
+
+/**
+ * 健康等级（绿/黄/红）
+ */
+export type HealthLevel = 'green' | 'yellow' | 'red';
+
+/**
+ * 单项巡检结果
+ */
+export interface HealthCheckItem {
+  /** 检查类别 */
+  category: 'tool' | 'proxy' | 'config';
+  /** 工具 ID */
+  tool_id: string;
+  level: HealthLevel;
+  message: string;
+}
+
+/**
+ * 健康报告
+ */
+export interface HealthReport {
+  /** 巡检时间（ISO 8601） */
+  checked_at: string;
+  /** 总体等级（取最差项） */
+  overall: HealthLevel;
+  items: HealthCheckItem[];
+}
+
+/**
+ * 健康巡检配置
+ */
+export interface HealthCheckConfig {
+  /** 是否启用后台巡检 */
+  enabled: boolean;
+  /** 巡检间隔（秒） */
+  interval_secs: number;
+}
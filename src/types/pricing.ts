@@ -26,6 +26,26 @@ export interface ModelPrice {
   currency: string;
   /** 模型别名列表（支持多种 ID 格式） */
   aliases: string[];
+  /** 阶梯定价（按本次请求提示 Token 总数分档，为空时使用单一价格） */
+  tiers?: PriceTier[];
+}
+
+/**
+ * 阶梯价格档位（提示 Token 总数达到 min_input_tokens 时生效）
+ */
+export interface PriceTier {
+  /** 档位起始阈值（提示 Token 总数：未缓存输入 + 缓存读取 + 缓存创建） */
+  min_input_tokens: number;
+  /** 输入价格（USD/百万 Token） */
+  input_price_per_1m: number;
+  /** 输出价格（USD/百万 Token） */
+  output_price_per_1m: number;
+  /** 缓存写入价格 - 5分钟TTL（未设置时沿用基础价格） */
+  cache_write_price_per_1m?: number;
+  /** 缓存写入价格 - 1小时TTL（未设置时沿用基础价格） */
+  cache_write_1h_price_per_1m?: number;
+  /** 缓存读取价格（未设置时沿用基础价格） */
+  cache_read_price_per_1m?: number;
 }
 
 /**
@@ -73,6 +93,64 @@ export interface PricingTemplate {
   is_default_preset: boolean;
 }
 
+/**
+ * 未知模型兜底价格配置
+ *
+ * 精确模型名与别名都匹配不到时，按 provider 默认档位估算成本
+ */
+export interface FallbackPricesConfig {
+  /** 是否启用兜底估算 */
+  enabled: boolean;
+  /** provider（claude / openai / gemini）-> 默认档位价格 */
+  prices: Record<string, ModelPrice>;
+}
+
+/**
+ * 远程价格同步状态
+ */
+export interface PricingSyncStatus {
+  /** 上次成功同步时间（Unix 时间戳，毫秒） */
+  last_success_at: number | null;
+  /** 最近一次同步失败的错误信息（成功后清空） */
+  last_error: string | null;
+  /** 连续失败次数 */
+  consecutive_failures: number;
+  /** 距上次成功同步的时长（毫秒，从未成功时为 null） */
+  elapsed_since_success_ms: number | null;
+}
+
+/**
+ * 模型价格变更记录（远程同步更新价格时生成）
+ */
+export interface PriceChange {
+  /** 模型 ID */
+  model: string;
+  /** 所属模板 ID */
+  template_id: string;
+  /** 变更前价格（新增模型时为 null） */
+  old_price: ModelPrice | null;
+  /** 变更后价格 */
+  new_price: ModelPrice;
+  /** 变更时间（Unix 时间戳，毫秒） */
+  changed_at: number;
+}
+
+/**
+ * 成本分解结果
+ */
+export interface CostBreakdown {
+  input_price: number;
+  output_price: number;
+  cache_write_price: number;
+  cache_read_price: number;
+  reasoning_price: number;
+  total_cost: number;
+  /** 使用的价格模板 ID */
+  template_id: string;
+  /** 是否为兜底估算（前端应显示「估算」提示） */
+  is_estimated: boolean;
+}
+
 // ==================== 工具 ID 类型 ====================
 
 /**
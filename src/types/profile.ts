@@ -23,14 +23,29 @@ export interface CodexProfilePayload {
   pricing_template_id?: string; // 🆕 Phase 6: 价格模板 ID
 }
 
+/**
+ * Gemini CLI 认证模式
+ */
+export type GeminiAuthMode = 'api-key' | 'vertex' | 'oauth';
+
+/**
+ * Gemini CLI 认证配置
+ */
+export interface GeminiAuth {
+  auth_mode: GeminiAuthMode;
+  project?: string; // Vertex 模式必填：GCP 项目 ID
+  location?: string; // Vertex 模式必填：区域（如 us-central1）
+}
+
 /**
  * Gemini Profile Payload（前端构建 Profile 时使用）
  */
 export interface GeminiProfilePayload {
-  api_key: string;
-  base_url: string;
+  api_key: string; // vertex / oauth 模式可为空
+  base_url: string; // vertex / oauth 模式可为空
   model?: string; // 可选,不填则不修改原生配置
   pricing_template_id?: string; // 🆕 Phase 6: 价格模板 ID
+  auth?: GeminiAuth; // 认证模式（不传则保持不变，新建时默认 api-key）
 }
 
 /**
@@ -62,6 +77,7 @@ export interface ProfileData {
   raw_env?: string;
   // 🆕 Phase 6: 价格模板 ID
   pricing_template_id?: string;
+  auth?: GeminiAuth; // Gemini 认证配置（缺省为 api-key）
 }
 
 /**
@@ -85,18 +101,20 @@ export type ProfileSource =
 export interface ProfileDescriptor {
   tool_id: string;
   name: string;
-  api_key_preview: string; // 脱敏显示（如 "sk-ant-***xxx"）
+  api_key_preview: string; // 脱敏显示（如 "sk-a...xyz9 (共 51 位)"）
   base_url: string;
   source: ProfileSource; // Profile 来源信息
   created_at: string; // ISO 8601 时间字符串
   updated_at: string; // ISO 8601 时间字符串
   is_active: boolean;
   switched_at?: string; // 激活时间（ISO 8601 时间字符串）
+  last_used_at?: string; // 最后被激活的时间（ISO 8601 时间字符串，从未使用则缺省）
   // Codex 特定字段（注意：后端是 wire_api,前端展示用 provider 兼容）
   wire_api?: string;
   provider?: string; // 向后兼容
   // Gemini 特定字段
   model?: string;
+  auth_mode?: GeminiAuthMode;
   // 🆕 Phase 6: 价格模板 ID
   pricing_template_id?: string;
 }
@@ -111,6 +129,39 @@ export type ProfileToolId = 'claude-code' | 'codex' | 'gemini-cli';
  */
 export type ToolId = ProfileToolId | 'amp-code';
 
+/**
+ * Profile 切换结果
+ */
+export interface ProfileSwitch {
+  tool_id: ToolId;
+  /** 切换前激活的 Profile（首次激活时为 null） */
+  previous: string | null;
+  /** 切换后激活的 Profile */
+  current: string;
+}
+
+/**
+ * 多工具配置不一致项（该工具指向的供应商与多数工具不同）
+ */
+export interface Inconsistency {
+  tool_id: ProfileToolId;
+  profile_name: string;
+  base_url: string;
+  provider_domain: string; // 该工具指向的供应商域名
+  expected_domain: string; // 多数工具指向的供应商域名
+}
+
+/**
+ * 配置变更审计记录
+ */
+export interface AuditEntry {
+  timestamp: string;
+  /** 操作名称，如 configure_api / switch_profile / delete_profile */
+  action: string;
+  tool_id: string;
+  profile: string | null;
+}
+
 /**
  * Profile 引用（指向某工具的某个 profile）
  */
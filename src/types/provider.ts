@@ -42,8 +42,14 @@ export interface CheckinConfig {
   checkin_hour_start?: number;
   /** 签到时间范围 - 结束小时（0-23，默认 0）；start==end 或 start>end 时为全天 */
   checkin_hour_end?: number;
+  /** 每天签到次数（默认 1） */
+  checkins_per_day?: number;
+  /** 当天已签到次数（仅在 last_checkin_at 为当天时有效） */
+  today_checkin_count?: number;
   /** 下次计划签到时间（Unix timestamp），由后端调度器生成 */
   next_checkin_at?: number;
+  /** 当天剩余的计划签到时间点（Unix timestamp，升序） */
+  pending_checkin_times?: number[];
   /** 最后签到时间（Unix timestamp） */
   last_checkin_at?: number;
   /** 最后签到状态 */
@@ -78,6 +84,20 @@ export interface CheckinResponse {
   };
 }
 
+/**
+ * 签到流水记录
+ */
+export interface CheckinRecord {
+  /** 签到时间（Unix 时间戳，秒） */
+  timestamp: number;
+  provider_id: string;
+  provider_name: string;
+  success: boolean;
+  /** 奖励额度 */
+  quota_awarded?: number;
+  message?: string;
+}
+
 /**
  * API 地址信息
  */
@@ -100,6 +120,8 @@ export interface ProviderStore {
   active_provider_id?: string;
   /** 最后更新时间（Unix timestamp） */
   updated_at: number;
+  /** 自动签到全局总开关（关闭时忽略各供应商的签到开关） */
+  checkin_enabled?: boolean;
 }
 
 /**
@@ -21,10 +21,24 @@ export interface TokenLog {
   cache_creation_tokens: number;
   cache_creation_1h_tokens?: number;
   cache_read_tokens: number;
-  request_status: 'success' | 'failed'; // 请求状态
+  request_status: 'success' | 'failed' | 'partial'; // 请求状态（partial：流式响应空闲超时提前结束）
   response_type: 'sse' | 'json' | 'unknown'; // 响应类型
-  error_type?: 'parse_error' | 'request_interrupted' | 'upstream_error'; // 错误类型
+  error_type?:
+    | 'parse_error'
+    | 'request_interrupted'
+    | 'upstream_error'
+    | 'stream_idle_timeout'; // 错误类型
   error_detail?: string; // 错误详情
+  stop_reason?: string; // 结束原因（end_turn / max_tokens / tool_use / stop_sequence）
+  request_bytes?: number; // 请求体字节数
+  response_bytes?: number; // 响应体字节数
+  image_count?: number; // 请求中的图片数量
+  image_bytes?: number; // 请求中的图片字节数（解码后）
+  upstream?: string; // 实际使用的上游（host[:port]）
+  downgraded_from?: string; // 上游过载降级重试前的原始模型
+  category?: 'coding' | 'chat' | 'embedding' | 'image' | 'other'; // 请求分类（按 endpoint 与模型自动判断）
+  ttfb_ms?: number; // 首字节延迟（毫秒，从发起上游请求到收到响应头）
+  is_estimated?: boolean; // 成本是否为兜底估算（模型未在价格模板中定义）
   // 成本相关字段（Phase 6）
   total_cost: number; // 总成本
   input_price?: number; // 输入价格
@@ -42,6 +56,7 @@ export interface SessionStats {
   total_cache_creation: number;
   total_cache_read: number;
   request_count: number;
+  tokens_per_second?: number | null; // 流式响应生成速率（输出 Token / 响应时长）
 }
 
 /**
@@ -55,6 +70,53 @@ export interface TokenStatsQuery {
   end_time?: number; // Unix 时间戳（毫秒）
   page: number;
   page_size: number;
+  errors_only?: boolean; // 仅返回失败请求（含 parse_error）
+}
+
+/**
+ * 用量异常告警（`usage-anomaly` 事件载荷）
+ */
+export interface UsageAnomaly {
+  scope: 'hour' | 'day';
+  window_start: number; // Unix 时间戳（毫秒）
+  tokens: number;
+  baseline: number;
+  ratio: number;
+}
+
+/**
+ * 月度成本目标进度
+ */
+export interface BudgetProgress {
+  period_start: number; // Unix 时间戳（毫秒）
+  period_end: number; // Unix 时间戳（毫秒，不含）
+  spent: number; // 本期已花费（USD）
+  target: number | null; // 月度目标（USD）
+  remaining: number | null; // 剩余额度（已超出时为负数）
+  percent: number | null; // 已花费占目标的百分比
+  projected_spend: number; // 按当前速率推算的本期总花费
+  exceeded: boolean;
+  projected_overrun_at: number | null; // 预计超出目标的时间（毫秒）
+}
+
+/**
+ * Token 日志导出格式
+ */
+export type ExportFormat = 'csv' | 'jsonl';
+
+/**
+ * Token 日志导入格式（json 同时支持数组与 JSON Lines）
+ */
+export type ImportFormat = 'csv' | 'json';
+
+/**
+ * Token 日志导入结果
+ */
+export interface ImportSummary {
+  total: number; // 解析出的记录总数（含无效记录）
+  imported: number; // 成功导入条数
+  duplicates: number; // 因重复跳过的条数
+  invalid: number; // 无法解析而跳过的条数
 }
 
 /**
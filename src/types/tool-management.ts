@@ -66,6 +66,8 @@ export interface ToolInstance {
   ssh_config?: SSHConfig;
   /** 是否为内置实例 */
   is_builtin: boolean;
+  /** 自定义环境变量（执行 --version / 更新命令时注入） */
+  custom_env?: Record<string, string>;
   /** 创建时间（Unix timestamp） */
   created_at: number;
   /** 更新时间（Unix timestamp） */
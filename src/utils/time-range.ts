@@ -25,6 +25,7 @@ export const GRANULARITY_MS: Record<TimeGranularity, number> = {
   hour: TIME_CONSTANTS.HOUR_1,
   twelve_hours: TIME_CONSTANTS.HOUR_12,
   day: TIME_CONSTANTS.DAY_1,
+  week: TIME_CONSTANTS.DAY_7,
 };
 
 // 粒度显示标签
@@ -34,6 +35,7 @@ export const GRANULARITY_LABELS: Record<TimeGranularity, string> = {
   hour: '1小时',
   twelve_hours: '12小时',
   day: '1天',
+  week: '1周',
 };
 
 // 时间范围显示标签
@@ -59,7 +61,7 @@ export const PRESET_ALLOWED_GRANULARITIES: Record<
   twelve_hours: ['fifteen_minutes', 'thirty_minutes', 'hour', 'twelve_hours'],
   day: ['thirty_minutes', 'hour', 'twelve_hours', 'day'],
   week: ['day'],
-  month: ['day'],
+  month: ['day', 'week'],
 };
 
 /**